@@ -9,11 +9,7 @@ use crate::{
 };
 use amethyst_error::Error;
 
-use amethyst_core::{
-    ecs::prelude::{Component, DispatcherBuilder},
-    math::RealField,
-    SystemBundle,
-};
+use amethyst_core::{ecs::prelude::Component, math::RealField, BundleBuilder, SystemBundle};
 
 /// Bundle for vertex skinning
 ///
@@ -39,7 +35,7 @@ impl<'a, N: Default> VertexSkinningBundle<'a, N> {
 }
 
 impl<'a, 'b, 'c, N: RealField> SystemBundle<'a, 'b> for VertexSkinningBundle<'c, N> {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             VertexSkinningSystem::<N>::new(),
             "vertex_skinning_system",
@@ -89,7 +85,7 @@ impl<'a, 'b, 'c, T> SystemBundle<'a, 'b> for SamplingBundle<'c, T>
 where
     T: AnimationSampling + Component,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(SamplerProcessor::<T::Primitive>::new(), "", &[]);
         builder.add(SamplerInterpolationSystem::<T>::new(), self.name, self.dep);
         Ok(())
@@ -144,7 +140,7 @@ where
     I: PartialEq + Eq + Hash + Copy + Send + Sync + 'static,
     T: AnimationSampling + Component + Clone,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(AnimationProcessor::<T>::new(), "", &[]);
         builder.add(
             AnimationControlSystem::<I, T>::new(),
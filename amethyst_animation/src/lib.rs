@@ -52,6 +52,7 @@ pub use minterpolate::{InterpolationFunction, InterpolationPrimitive};
 pub use self::{
     bundle::{AnimationBundle, SamplingBundle, VertexSkinningBundle},
     material::{MaterialChannel, MaterialPrimitive},
+    morph::MorphWeightsChannel,
     prefab::{AnimatablePrefab, AnimationHierarchyPrefab, AnimationPrefab, AnimationSetPrefab},
     resources::{
         Animation, AnimationCommand, AnimationControl, AnimationControlSet, AnimationHierarchy,
@@ -69,6 +70,7 @@ pub use self::{
 
 mod bundle;
 mod material;
+mod morph;
 mod prefab;
 mod resources;
 mod skinning;
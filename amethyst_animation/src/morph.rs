@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use amethyst_renderer::MorphWeights;
+
+use crate::{
+    resources::{AnimationSampling, ApplyData, BlendMethod},
+    util::SamplerPrimitive,
+};
+
+/// Channels that can be animated on `MorphWeights`
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub enum MorphWeightsChannel {
+    /// The weight of the morph target with the given index
+    Weight(usize),
+}
+
+impl<'a> ApplyData<'a> for MorphWeights {
+    type ApplyData = ();
+}
+
+impl AnimationSampling for MorphWeights {
+    type Primitive = SamplerPrimitive<f32>;
+    type Channel = MorphWeightsChannel;
+
+    fn apply_sample(&mut self, channel: &Self::Channel, data: &SamplerPrimitive<f32>, _: &()) {
+        match (*channel, *data) {
+            (MorphWeightsChannel::Weight(index), SamplerPrimitive::Scalar(weight)) => {
+                if self.weights.len() <= index {
+                    self.weights.resize(index + 1, 0.0);
+                }
+                self.weights[index] = weight;
+            }
+            _ => panic!("Attempt to apply invalid sample to MorphWeights"),
+        }
+    }
+
+    fn current_sample(&self, channel: &Self::Channel, _: &()) -> SamplerPrimitive<f32> {
+        match *channel {
+            MorphWeightsChannel::Weight(index) => {
+                SamplerPrimitive::Scalar(self.weights.get(index).cloned().unwrap_or(0.0))
+            }
+        }
+    }
+
+    fn default_primitive(_: &Self::Channel) -> Self::Primitive {
+        SamplerPrimitive::Scalar(0.0)
+    }
+
+    fn blend_method(&self, _: &Self::Channel) -> Option<BlendMethod> {
+        Some(BlendMethod::Linear)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_weights() {
+        let mut weights = MorphWeights::default();
+        weights.apply_sample(
+            &MorphWeightsChannel::Weight(2),
+            &SamplerPrimitive::Scalar(0.5),
+            &(),
+        );
+        assert_eq!(weights.weights, vec![0.0, 0.0, 0.5]);
+        weights.apply_sample(
+            &MorphWeightsChannel::Weight(0),
+            &SamplerPrimitive::Scalar(1.0),
+            &(),
+        );
+        assert_eq!(weights.weights, vec![1.0, 0.0, 0.5]);
+        match weights.current_sample(&MorphWeightsChannel::Weight(4), &()) {
+            SamplerPrimitive::Scalar(weight) => assert_eq!(weight, 0.0),
+            _ => panic!("Expected a scalar sample"),
+        }
+    }
+}
@@ -8,6 +8,26 @@ use thread_profiler::profile_scope;
 
 use crate::{Handle, Reload, SingleFile, Source};
 
+/// Separates the path of a container asset from the address of a single asset inside of it,
+/// e.g. `"scene.glb#mesh/Turret"`.
+pub const SUB_ASSET_SEPARATOR: char = '#';
+
+/// Splits an asset name into the path of the file and the address of the sub asset, if any.
+///
+/// Formats supporting sub assets use this to find out which part of the file to load.
+///
+/// ```
+/// # use amethyst_assets::split_sub_asset;
+/// assert_eq!(("scene.glb", Some("mesh/Turret")), split_sub_asset("scene.glb#mesh/Turret"));
+/// assert_eq!(("scene.glb", None), split_sub_asset("scene.glb"));
+/// ```
+pub fn split_sub_asset(name: &str) -> (&str, Option<&str>) {
+    match name.find(SUB_ASSET_SEPARATOR) {
+        Some(index) => (&name[..index], Some(&name[index + 1..])),
+        None => (name, None),
+    }
+}
+
 /// One of the three core traits of this crate.
 ///
 /// You want to implement this for every type of asset like
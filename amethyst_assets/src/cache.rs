@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, hash::Hash};
+use std::{borrow::Borrow, collections::VecDeque, hash::Hash};
 
 use derivative::Derivative;
 use fnv::FnvHashMap;
@@ -6,7 +6,8 @@ use fnv::FnvHashMap;
 use crate::{Handle, WeakHandle};
 
 /// A simple cache for asset handles of type `A`.
-/// This stores `WeakHandle`, so it doesn't keep the assets alive.
+/// This stores `WeakHandle`, so it doesn't keep the assets alive. An entry can only be retrieved
+/// while a `Handle` to its asset is alive somewhere else.
 #[derive(Derivative)]
 #[derivative(Default(bound = ""))]
 pub struct Cache<A> {
@@ -46,3 +47,161 @@ where
         self.map.clear();
     }
 }
+
+/// A cache for asset handles of type `A` which keeps the most recently used assets alive.
+///
+/// Up to `capacity` of the most recently inserted or retrieved assets are held by a strong
+/// `Handle`. All other entries are only stored as `WeakHandle`s, so they can only be retrieved
+/// while a `Handle` to their asset is alive somewhere else, and the asset gets unloaded as soon
+/// as nothing uses it.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct LruCache<A> {
+    capacity: usize,
+    map: FnvHashMap<String, WeakHandle<A>>,
+    recent: VecDeque<(String, Handle<A>)>,
+}
+
+impl<A> LruCache<A> {
+    /// Creates a new `LruCache` keeping at most `capacity` assets alive.
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            capacity,
+            map: Default::default(),
+            recent: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the maximum number of assets this cache keeps alive.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the maximum number of assets this cache keeps alive, releasing the least recently
+    /// used ones if necessary.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.trim();
+    }
+
+    /// Inserts an asset with a given `key`, marks it as most recently used and returns the old
+    /// value (if any).
+    pub fn insert<K: Into<String>>(&mut self, key: K, asset: &Handle<A>) -> Option<WeakHandle<A>> {
+        let key = key.into();
+        let old = self.map.insert(key.clone(), asset.downgrade());
+        self.touch(key, asset.clone());
+        old
+    }
+
+    /// Retrieves an asset handle using a given `key` and marks it as most recently used.
+    ///
+    /// Returns `None` if there is no such entry or all `Handle`s to the asset have been dropped.
+    pub fn get<K>(&mut self, key: &K) -> Option<Handle<A>>
+    where
+        K: ?Sized + Hash + Eq + ToOwned<Owned = String>,
+        String: Borrow<K>,
+    {
+        let handle = self.map.get(key).and_then(WeakHandle::upgrade)?;
+        self.touch(key.to_owned(), handle.clone());
+        Some(handle)
+    }
+
+    /// Retrieves an asset handle using a given `key` without changing the usage order.
+    pub fn peek<K>(&self, key: &K) -> Option<Handle<A>>
+    where
+        K: ?Sized + Hash + Eq,
+        String: Borrow<K>,
+    {
+        self.map.get(key).and_then(WeakHandle::upgrade)
+    }
+
+    /// Returns the number of entries, including the ones only held weakly.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Deletes all cached handles which are invalid.
+    pub fn clear_dead(&mut self) {
+        self.map.retain(|_, h| !h.is_dead());
+    }
+
+    /// Clears all values, allowing the assets to be unloaded.
+    pub fn clear_all(&mut self) {
+        self.map.clear();
+        self.recent.clear();
+    }
+
+    fn touch(&mut self, key: String, handle: Handle<A>) {
+        if let Some(i) = self.recent.iter().position(|(k, _)| *k == key) {
+            self.recent.remove(i);
+        }
+        self.recent.push_back((key, handle));
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.recent.len() > self.capacity {
+            self.recent.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Asset, AssetStorage, Handle};
+
+    use super::LruCache;
+
+    struct Dummy;
+
+    impl Asset for Dummy {
+        const NAME: &'static str = "Dummy";
+        type Data = ();
+        type HandleStorage = amethyst_core::ecs::prelude::VecStorage<Handle<Self>>;
+    }
+
+    #[test]
+    fn lru_cache_releases_least_recently_used_handles() {
+        let storage = AssetStorage::<Dummy>::new();
+        let mut cache = LruCache::new(2);
+
+        let weak_a = {
+            let a = storage.allocate();
+            cache.insert("a", &a);
+            a.downgrade()
+        };
+        let weak_b = {
+            let b = storage.allocate();
+            cache.insert("b", &b);
+            b.downgrade()
+        };
+
+        // Using "a" makes "b" the least recently used entry.
+        assert!(cache.get("a").is_some());
+        let c = storage.allocate();
+        cache.insert("c", &c);
+
+        assert!(!weak_a.is_dead());
+        assert!(weak_b.is_dead());
+        assert!(cache.get("b").is_none());
+        assert_eq!(Some(c.id()), cache.peek("c").map(|h| h.id()));
+    }
+
+    #[test]
+    fn lru_cache_keeps_externally_held_handles_retrievable() {
+        let storage = AssetStorage::<Dummy>::new();
+        let mut cache = LruCache::new(0);
+
+        let a = storage.allocate();
+        cache.insert("a", &a);
+
+        assert_eq!(Some(a.id()), cache.get("a").map(|h| h.id()));
+        drop(a);
+        assert!(cache.get("a").is_none());
+    }
+}
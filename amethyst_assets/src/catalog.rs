@@ -0,0 +1,175 @@
+//! Asset metadata and the `AssetCatalog` resource for querying it.
+
+use std::{borrow::Borrow, hash::Hash};
+
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+
+use amethyst_error::Error;
+
+use crate::Loader;
+
+/// Metadata a `Source` can provide about an asset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetMetadata {
+    /// Size of the asset in bytes.
+    pub size: Option<u64>,
+    /// Hash of the asset contents.
+    pub hash: Option<u64>,
+    /// User defined tags, e.g. the name of the level the asset is used in.
+    pub tags: Vec<String>,
+}
+
+impl AssetMetadata {
+    /// Returns `true` if the asset has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// An asset registered in the `AssetCatalog`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CatalogEntry {
+    /// Name of the asset, as passed to `Loader::load_from`.
+    pub name: String,
+    /// Identifier of the source the asset lives in, `""` for the default source.
+    pub source: String,
+    /// Metadata provided by the source.
+    pub metadata: AssetMetadata,
+}
+
+/// A resource holding the metadata of known assets, which can be queried by tag.
+///
+/// ### Example
+///
+/// ```rust,ignore
+/// let mut catalog = AssetCatalog::new();
+/// catalog.register(&loader, "mesh/cube.obj", "")?;
+/// for entry in catalog.find("level1") {
+///     // start loading `entry.name`
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct AssetCatalog {
+    entries: FnvHashMap<String, CatalogEntry>,
+}
+
+impl AssetCatalog {
+    /// Creates an empty catalog.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Queries the metadata of the asset `name` in the source with id `source` and stores it
+    /// in the catalog, replacing any previous entry with the same name.
+    pub fn register<N, S>(&mut self, loader: &Loader, name: N, source: S) -> Result<(), Error>
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        let name = name.into();
+        let source = source.into();
+        let metadata = loader.metadata(&name, &source)?;
+        self.insert(name, source, metadata);
+        Ok(())
+    }
+
+    /// Stores the given metadata in the catalog and returns the previous entry (if any).
+    pub fn insert<N, S>(
+        &mut self,
+        name: N,
+        source: S,
+        metadata: AssetMetadata,
+    ) -> Option<CatalogEntry>
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        let name = name.into();
+        let entry = CatalogEntry {
+            name: name.clone(),
+            source: source.into(),
+            metadata,
+        };
+        self.entries.insert(name, entry)
+    }
+
+    /// Removes an asset from the catalog.
+    pub fn remove<K>(&mut self, name: &K) -> Option<CatalogEntry>
+    where
+        K: ?Sized + Hash + Eq,
+        String: Borrow<K>,
+    {
+        self.entries.remove(name)
+    }
+
+    /// Retrieves the entry of an asset.
+    pub fn get<K>(&self, name: &K) -> Option<&CatalogEntry>
+    where
+        K: ?Sized + Hash + Eq,
+        String: Borrow<K>,
+    {
+        self.entries.get(name)
+    }
+
+    /// Iterates over all assets with the given tag.
+    pub fn find<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a CatalogEntry> + 'a {
+        self.entries
+            .values()
+            .filter(move |entry| entry.metadata.has_tag(tag))
+    }
+
+    /// Iterates over all assets in the catalog.
+    pub fn iter(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.values()
+    }
+
+    /// Returns the combined size in bytes of all assets with the given tag.
+    ///
+    /// Assets whose size is unknown are not counted.
+    pub fn size_of_tag(&self, tag: &str) -> u64 {
+        self.find(tag).filter_map(|entry| entry.metadata.size).sum()
+    }
+
+    /// Returns the number of assets in the catalog.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the catalog is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AssetCatalog, AssetMetadata};
+
+    fn metadata(size: u64, tags: &[&str]) -> AssetMetadata {
+        AssetMetadata {
+            size: Some(size),
+            hash: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn find_returns_only_assets_with_tag() {
+        let mut catalog = AssetCatalog::new();
+        catalog.insert("a.png", "", metadata(10, &["level1", "ui"]));
+        catalog.insert("b.png", "", metadata(20, &["level2"]));
+        catalog.insert("c.obj", "", metadata(30, &["level1"]));
+
+        let mut names = catalog
+            .find("level1")
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(vec!["a.png", "c.obj"], names);
+        assert_eq!(40, catalog.size_of_tag("level1"));
+        assert_eq!(0, catalog.find("level3").count());
+    }
+}
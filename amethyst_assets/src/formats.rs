@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{Asset, SimpleFormat};
 use amethyst_error::{format_err, Error, ResultExt};
@@ -28,6 +28,58 @@ where
     }
 }
 
+/// Format for loading from binary files encoded with `bincode`.
+///
+/// Parsing is considerably faster than for `RonFormat`, which makes this format a good fit for
+/// large prefabs. It can be used anywhere `RonFormat` is accepted, and `BinFormat::convert_ron`
+/// can be used to produce the binary files from existing Ron sources.
+///
+/// Note that `bincode` is not self-describing, so data types relying on `#[serde(untagged)]`,
+/// `#[serde(flatten)]` or `#[serde(skip_serializing_if = "..")]` can't be stored in this format.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct BinFormat;
+
+impl BinFormat {
+    /// Serializes `data` into the representation expected by `BinFormat`.
+    pub fn serialize<T>(data: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize,
+    {
+        bincode::serialize(data).with_context(|_| format_err!("Failed serializing Bin file"))
+    }
+
+    /// Converts the contents of a Ron file into the representation expected by `BinFormat`.
+    ///
+    /// `T` is the type the Ron file deserializes to, e.g. `Prefab<MyPrefabData>`.
+    pub fn convert_ron<T>(ron_bytes: &[u8]) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        use ron::de::Deserializer;
+        let mut d = Deserializer::from_bytes(ron_bytes)
+            .with_context(|_| format_err!("Failed deserializing Ron file"))?;
+        let val =
+            T::deserialize(&mut d).with_context(|_| format_err!("Failed parsing Ron file"))?;
+        d.end()
+            .with_context(|_| format_err!("Failed parsing Ron file"))?;
+
+        Self::serialize(&val)
+    }
+}
+
+impl<T> SimpleFormat<T> for BinFormat
+where
+    T: Asset,
+    T::Data: for<'a> Deserialize<'a> + Send + Sync + 'static,
+{
+    const NAME: &'static str = "Bin";
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<T::Data, Error> {
+        bincode::deserialize(&bytes).with_context(|_| format_err!("Failed deserializing Bin file"))
+    }
+}
+
 /// Format for loading from Json files.
 #[cfg(feature = "json")]
 #[derive(Default, Clone, Debug)]
@@ -53,3 +105,37 @@ where
         Ok(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::BinFormat;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Data {
+        name: String,
+        values: Vec<u32>,
+        parent: Option<usize>,
+    }
+
+    #[test]
+    fn convert_ron_round_trips_through_bincode() {
+        let ron = b"(name: \"root\", values: [1, 2, 3], parent: Some(0))";
+        let bytes = BinFormat::convert_ron::<Data>(ron).expect("Failed converting Ron file");
+        let data: Data = bincode::deserialize(&bytes).expect("Failed deserializing Bin file");
+        assert_eq!(
+            Data {
+                name: "root".into(),
+                values: vec![1, 2, 3],
+                parent: Some(0),
+            },
+            data
+        );
+    }
+
+    #[test]
+    fn convert_ron_fails_on_invalid_input() {
+        assert!(BinFormat::convert_ron::<Data>(b"(name: 5)").is_err());
+    }
+}
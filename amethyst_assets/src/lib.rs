@@ -12,26 +12,34 @@
 #[cfg(feature = "json")]
 pub use crate::formats::JsonFormat;
 pub use crate::{
-    asset::{Asset, Format, FormatValue, SimpleFormat},
-    cache::Cache,
-    formats::RonFormat,
+    asset::{split_sub_asset, Asset, Format, FormatValue, SimpleFormat, SUB_ASSET_SEPARATOR},
+    cache::{Cache, LruCache},
+    catalog::{AssetCatalog, AssetMetadata, CatalogEntry},
+    formats::{BinFormat, RonFormat},
     helper::AssetLoaderSystemData,
     loader::Loader,
     prefab::{AssetPrefab, Prefab, PrefabData, PrefabLoader, PrefabLoaderSystem},
-    progress::{Completion, Progress, ProgressCounter, Tracker},
+    preload::{PreloadBundle, PreloadEntry, PreloadManifest, PreloadSystem, Preloaded},
+    progress::{
+        AssetFailure, AssetProgress, AssetStatus, Completion, Progress, ProgressCounter, Tracker,
+    },
     reload::{HotReloadBundle, HotReloadStrategy, HotReloadSystem, Reload, SingleFile},
     source::{Directory, Source},
+    stats::{AssetSize, AssetStats, StorageStats},
     storage::{AssetStorage, Handle, ProcessingState, Processor, WeakHandle},
 };
 
 mod asset;
 mod cache;
+mod catalog;
 mod error;
 mod formats;
 mod helper;
 mod loader;
 mod prefab;
+mod preload;
 mod progress;
 mod reload;
 mod source;
+mod stats;
 mod storage;
@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, hash::Hash, path::PathBuf, sync::Arc};
+use std::{borrow::Borrow, hash::Hash, path::PathBuf, sync::Arc, time::Instant};
 
 use fnv::FnvHashMap;
 use log::debug;
@@ -11,7 +11,7 @@ use thread_profiler::profile_scope;
 use crate::{
     error::Error,
     storage::{AssetStorage, Handle, Processed},
-    Asset, Directory, Format, FormatValue, Progress, Source,
+    Asset, AssetMetadata, Directory, Format, FormatValue, Progress, Source,
 };
 
 /// The asset loader, holding the sources and a reference to the `ThreadPool`.
@@ -64,6 +64,23 @@ impl Loader {
         self.add_source(String::new(), source);
     }
 
+    /// Returns the thread pool assets are read and decoded on.
+    ///
+    /// Processor systems also spawn their hot reloads on this pool.
+    pub fn pool(&self) -> &Arc<ThreadPool> {
+        &self.pool
+    }
+
+    /// Sets the thread pool assets are read and decoded on.
+    ///
+    /// By default this is the pool the `Loader` was created with, which inside an `Application`
+    /// is the pool the systems are dispatched on. A dedicated pool keeps expensive imports (e.g.
+    /// decompressing textures or reading glTF files) from stalling the simulation. Only turning
+    /// the decoded data into assets (e.g. the GPU upload) happens in the processor systems.
+    pub fn set_pool(&mut self, pool: Arc<ThreadPool>) {
+        self.pool = pool;
+    }
+
     /// If set to `true`, this `Loader` will ask formats to
     /// generate "reload instructions" which *allow* reloading.
     /// Calling `set_hot_reload(true)` does not actually enable
@@ -153,17 +170,19 @@ impl Loader {
         );
 
         progress.add_assets(1);
-        let tracker = progress.create_tracker();
+        let mut tracker = progress.create_tracker();
 
         let source = self.source(source);
         let handle_clone = handle.clone();
         let processed = storage.processed.clone();
 
         let hot_reload = self.hot_reload;
+        let requested = Instant::now();
 
         let cl = move || {
             #[cfg(feature = "profiler")]
             profile_scope!("load_asset_from_worker");
+            tracker.started(A::NAME, &name, source.size(&name));
             let data = format
                 .import(name.clone(), source, options, hot_reload)
                 .with_context(|_| Error::Format(F::NAME));
@@ -174,6 +193,7 @@ impl Loader {
                 handle,
                 name,
                 tracker,
+                requested,
             });
         };
         self.pool.spawn(cl);
@@ -192,8 +212,11 @@ impl Loader {
         A: Asset,
         P: Progress,
     {
+        use crate::progress::Tracker;
+
         progress.add_assets(1);
-        let tracker = progress.create_tracker();
+        let mut tracker = progress.create_tracker();
+        tracker.started(A::NAME, "<Data>", None);
         let tracker = Box::new(tracker);
         let handle = storage.allocate();
         storage.processed.push(Processed::NewAsset {
@@ -201,12 +224,24 @@ impl Loader {
             handle: handle.clone(),
             name: "<Data>".into(),
             tracker,
+            requested: Instant::now(),
         });
 
         handle
     }
 
-    fn source(&self, source: &str) -> Arc<dyn Source> {
+    /// Asks the source with id `source` for the metadata of the asset `name`.
+    ///
+    /// Use `""` for the default source.
+    pub fn metadata(
+        &self,
+        name: &str,
+        source: &str,
+    ) -> Result<AssetMetadata, amethyst_error::Error> {
+        self.source(source).metadata(name)
+    }
+
+    pub(crate) fn source(&self, source: &str) -> Arc<dyn Source> {
         self.sources
             .get(source)
             .expect("No such source. Maybe you forgot to add it with `Loader::add_source`?")
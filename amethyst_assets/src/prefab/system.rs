@@ -7,11 +7,11 @@ use amethyst_core::{
         storage::ComponentEvent, BitSet, Entities, Entity, Join, Read, ReadExpect, ReadStorage,
         ReaderId, Resources, System, Write, WriteStorage,
     },
-    ArcThreadPool, Parent, Time,
+    Parent, Time,
 };
 use amethyst_error::{format_err, Error, ResultExt};
 
-use crate::{AssetStorage, Completion, Handle, HotReloadStrategy, ProcessingState};
+use crate::{AssetStorage, Completion, Handle, HotReloadStrategy, Loader, ProcessingState};
 
 use super::{Prefab, PrefabData, PrefabTag};
 
@@ -51,7 +51,7 @@ where
         Write<'a, AssetStorage<Prefab<T>>>,
         ReadStorage<'a, Handle<Prefab<T>>>,
         Read<'a, Time>,
-        ReadExpect<'a, ArcThreadPool>,
+        ReadExpect<'a, Loader>,
         Option<Read<'a, HotReloadStrategy>>,
         WriteStorage<'a, Parent>,
         WriteStorage<'a, PrefabTag<T>>,
@@ -64,7 +64,7 @@ where
             mut prefab_storage,
             prefab_handles,
             time,
-            pool,
+            loader,
             strategy,
             mut parents,
             mut tags,
@@ -92,7 +92,7 @@ where
                 }
             },
             time.frame_number(),
-            &**pool,
+            &**loader.pool(),
             strategy,
         );
         prefab_handles
@@ -0,0 +1,276 @@
+//! Preloading of all assets listed in a manifest file.
+
+use std::any::Any;
+
+use fnv::FnvHashMap;
+use log::{debug, error};
+use ron::value::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{Read, Resources, System},
+    BundleBuilder, SystemBundle,
+};
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Asset, AssetStorage, Completion, Format, Handle, Loader, ProgressCounter};
+
+/// A single asset listed in a `PreloadManifest`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PreloadEntry {
+    /// Name of the asset, e.g. `"texture/logo.png"`.
+    pub path: String,
+    /// Key the format was registered with using `PreloadBundle::with_format`.
+    pub format: String,
+    /// Format options. If omitted, the options are deserialized from `()`.
+    ///
+    /// Structs have to be written as maps here, e.g. `Some({"mip_levels": 1})`.
+    #[serde(default)]
+    pub options: Option<Value>,
+    /// Identifier of the source to load from, the default source if omitted.
+    #[serde(default)]
+    pub source: String,
+}
+
+/// List of assets to preload, usually deserialized from a Ron file.
+///
+/// ### Example
+///
+/// ```ron
+/// [
+///     (path: "texture/logo.png", format: "png", options: Some({"mip_levels": 1})),
+///     (path: "prefab/level1.ron", format: "level"),
+/// ]
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PreloadManifest {
+    /// Assets to load.
+    pub entries: Vec<PreloadEntry>,
+}
+
+/// Resource holding the handles of all preloaded assets, keeping them alive, and the
+/// `ProgressCounter` tracking them.
+#[derive(Default)]
+pub struct Preloaded {
+    progress: ProgressCounter,
+    handles: FnvHashMap<String, Box<dyn Any + Send + Sync>>,
+}
+
+impl Preloaded {
+    /// Returns the `ProgressCounter` tracking all preloaded assets.
+    pub fn progress(&self) -> &ProgressCounter {
+        &self.progress
+    }
+
+    /// Returns the handle of the preloaded asset with the given path.
+    ///
+    /// Returns `None` if the asset is not in the manifest or has another type than `A`.
+    pub fn get<A: Asset>(&self, path: &str) -> Option<Handle<A>> {
+        self.handles
+            .get(path)
+            .and_then(|h| h.downcast_ref::<Handle<A>>())
+            .cloned()
+    }
+
+    /// Drops all handles, allowing the assets to be unloaded.
+    pub fn clear(&mut self) {
+        self.handles.clear();
+    }
+}
+
+type LoadFn = Box<
+    dyn Fn(
+            &mut Resources,
+            &PreloadEntry,
+            &mut ProgressCounter,
+        ) -> Result<Box<dyn Any + Send + Sync>, Error>
+        + Send
+        + Sync,
+>;
+
+/// Bundle which loads all assets listed in a `PreloadManifest` when the dispatcher is set up.
+///
+/// Every format used in the manifest has to be registered with `with_format`. The handles and
+/// the shared `ProgressCounter` are available from the `Preloaded` resource.
+///
+/// ### Example
+///
+/// ```rust,ignore
+/// let bundle = PreloadBundle::new("manifest/level1.ron")
+///     .with_format::<Texture, _>("png", PngFormat)
+///     .with_format::<Prefab<MyPrefabData>, _>("level", RonFormat);
+/// ```
+pub struct PreloadBundle {
+    manifest: ManifestSource,
+    formats: FnvHashMap<String, LoadFn>,
+}
+
+enum ManifestSource {
+    File(String),
+    Manifest(PreloadManifest),
+}
+
+impl PreloadBundle {
+    /// Creates a bundle loading the manifest with the given name from the default source.
+    pub fn new<N: Into<String>>(manifest: N) -> Self {
+        PreloadBundle {
+            manifest: ManifestSource::File(manifest.into()),
+            formats: Default::default(),
+        }
+    }
+
+    /// Creates a bundle for an already constructed manifest.
+    pub fn from_manifest(manifest: PreloadManifest) -> Self {
+        PreloadBundle {
+            manifest: ManifestSource::Manifest(manifest),
+            formats: Default::default(),
+        }
+    }
+
+    /// Registers a format under the given key, which manifest entries refer to.
+    pub fn with_format<A, F>(mut self, key: &str, format: F) -> Self
+    where
+        A: Asset,
+        F: Format<A> + Clone + Sync,
+        F::Options: DeserializeOwned,
+    {
+        let load: LoadFn = Box::new(
+            move |res: &mut Resources, entry: &PreloadEntry, progress: &mut ProgressCounter| {
+                let options = entry
+                    .options
+                    .clone()
+                    .unwrap_or(Value::Unit)
+                    .into_rust::<F::Options>()
+                    .with_context(|_| format_err!("Invalid options for asset {:?}", entry.path))?;
+                if !res.has_value::<AssetStorage<A>>() {
+                    res.insert(AssetStorage::<A>::new());
+                }
+                let handle = res.fetch::<Loader>().load_from(
+                    entry.path.as_str(),
+                    format.clone(),
+                    options,
+                    entry.source.as_str(),
+                    progress,
+                    &res.fetch::<AssetStorage<A>>(),
+                );
+                Ok(Box::new(handle) as Box<dyn Any + Send + Sync>)
+            },
+        );
+        self.formats.insert(key.to_owned(), load);
+        self
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for PreloadBundle {
+    fn build(self, dispatcher: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        dispatcher.add(PreloadSystem::new(self), "preload", &[]);
+        Ok(())
+    }
+}
+
+/// System which starts loading a `PreloadManifest` during setup and reports once it finished.
+pub struct PreloadSystem {
+    bundle: Option<PreloadBundle>,
+    reported: bool,
+}
+
+impl PreloadSystem {
+    /// Creates a new `PreloadSystem` from the given bundle.
+    pub fn new(bundle: PreloadBundle) -> Self {
+        PreloadSystem {
+            bundle: Some(bundle),
+            reported: false,
+        }
+    }
+
+    fn preload(bundle: PreloadBundle, res: &mut Resources) -> Result<Preloaded, Error> {
+        let manifest = match bundle.manifest {
+            ManifestSource::Manifest(manifest) => manifest,
+            ManifestSource::File(name) => {
+                let bytes = res
+                    .fetch::<Loader>()
+                    .source("")
+                    .load(&name)
+                    .with_context(|_| format_err!("Failed loading manifest {:?}", name))?;
+                ron::de::from_bytes(&bytes)
+                    .with_context(|_| format_err!("Failed parsing manifest {:?}", name))?
+            }
+        };
+
+        let mut preloaded = Preloaded::default();
+        for entry in &manifest.entries {
+            let load = bundle.formats.get(&entry.format).ok_or_else(|| {
+                format_err!(
+                    "No format registered for key {:?}, used by {:?}",
+                    entry.format,
+                    entry.path
+                )
+            })?;
+            let handle = load(res, entry, &mut preloaded.progress)?;
+            preloaded.handles.insert(entry.path.clone(), handle);
+        }
+        debug!("Preloading {} assets", preloaded.handles.len());
+
+        Ok(preloaded)
+    }
+}
+
+impl<'a> System<'a> for PreloadSystem {
+    type SystemData = Read<'a, Preloaded>;
+
+    fn run(&mut self, preloaded: Self::SystemData) {
+        if self.reported {
+            return;
+        }
+        match preloaded.progress().complete() {
+            Completion::Loading => {}
+            Completion::Complete => {
+                debug!("All preloaded assets finished loading");
+                self.reported = true;
+            }
+            Completion::Failed => {
+                error!(
+                    "{} preloaded assets failed to load",
+                    preloaded.progress().num_failed()
+                );
+                self.reported = true;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst_core::ecs::prelude::SystemData;
+        Self::SystemData::setup(res);
+
+        if let Some(bundle) = self.bundle.take() {
+            match Self::preload(bundle, res) {
+                Ok(preloaded) => res.insert(preloaded),
+                Err(e) => error!("Failed preloading assets: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PreloadManifest;
+
+    #[test]
+    fn manifest_parses_from_ron_list() {
+        let manifest: PreloadManifest = ron::de::from_str(
+            r#"[
+                (path: "texture/logo.png", format: "png", options: Some({"mip_levels": 1})),
+                (path: "prefab/level1.ron", format: "level", source: "packed"),
+            ]"#,
+        )
+        .expect("Failed parsing manifest");
+
+        assert_eq!(2, manifest.entries.len());
+        assert_eq!("png", manifest.entries[0].format);
+        assert!(manifest.entries[0].options.is_some());
+        assert_eq!("", manifest.entries[0].source);
+        assert!(manifest.entries[1].options.is_none());
+        assert_eq!("packed", manifest.entries[1].source);
+    }
+}
@@ -1,12 +1,19 @@
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use amethyst_error::Error;
 use log::error;
 use parking_lot::Mutex;
 
+/// Maximum number of failures kept around by `ProgressCounter::recent_failures`.
+const MAX_RECENT_FAILURES: usize = 16;
+
 /// Completion status, returned by `ProgressCounter::complete`.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Completion {
@@ -18,6 +25,45 @@ pub enum Completion {
     Loading,
 }
 
+/// Loading status of a single asset tracked by a `ProgressCounter`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AssetStatus {
+    /// The asset is still being loaded
+    Loading,
+    /// The asset has been loaded successfully
+    Loaded,
+    /// The asset could not be loaded
+    Failed,
+    /// The asset could not be loaded and was replaced by the placeholder of its storage
+    Placeholder,
+}
+
+/// Progress information about a single asset tracked by a `ProgressCounter`.
+#[derive(Clone, Debug)]
+pub struct AssetProgress {
+    /// Name of the asset type, `None` until the loader has started on the asset.
+    pub asset_type_name: Option<&'static str>,
+    /// Name of the asset, `None` until the loader has started on the asset.
+    pub asset_name: Option<String>,
+    /// Weight of the asset, usually its size in bytes. `None` if the source could not tell.
+    pub weight: Option<u64>,
+    /// Current loading status.
+    pub status: AssetStatus,
+}
+
+/// Description of a failed asset load, see `ProgressCounter::recent_failures`.
+#[derive(Clone, Debug)]
+pub struct AssetFailure {
+    /// Id of the handle the asset was loaded into.
+    pub handle_id: u32,
+    /// Name of the asset type.
+    pub asset_type_name: &'static str,
+    /// Name of the asset.
+    pub asset_name: String,
+    /// Rendered error message.
+    pub message: String,
+}
+
 /// The `Progress` trait, allowing to track which assets are
 /// imported already.
 pub trait Progress {
@@ -43,12 +89,19 @@ impl Progress for () {
 
 /// A progress tracker which is passed to the `Loader`
 /// in order to check how many assets are loaded.
+///
+/// Besides counting assets, the counter keeps track of the status and weight of every single
+/// asset, which allows computing the fraction of work that is done as well as an estimate of the
+/// remaining loading time.
 #[derive(Default)]
 pub struct ProgressCounter {
     errors: Arc<Mutex<Vec<AssetErrorMeta>>>,
     num_assets: usize,
     num_failed: Arc<AtomicUsize>,
     num_loading: Arc<AtomicUsize>,
+    assets: Arc<Mutex<Vec<AssetProgress>>>,
+    recent_failures: Arc<Mutex<VecDeque<AssetFailure>>>,
+    started: Option<Instant>,
 }
 
 impl ProgressCounter {
@@ -63,6 +116,20 @@ impl ProgressCounter {
         lock.drain(..).collect()
     }
 
+    /// Returns the last few failures, oldest first.
+    ///
+    /// Unlike `errors`, this does not remove anything, so it can be polled every frame, e.g. to
+    /// display the failures on a loading screen.
+    pub fn recent_failures(&self) -> Vec<AssetFailure> {
+        self.recent_failures.lock().iter().cloned().collect()
+    }
+
+    /// Returns the progress information of every tracked asset, in the order the assets were
+    /// added.
+    pub fn assets(&self) -> Vec<AssetProgress> {
+        self.assets.lock().clone()
+    }
+
     /// Returns the number of assets this struct is tracking.
     pub fn num_assets(&self) -> usize {
         self.num_assets
@@ -78,11 +145,85 @@ impl ProgressCounter {
         self.num_loading.load(Ordering::Relaxed)
     }
 
+    /// Returns the number of assets that failed to load and were replaced by a placeholder.
+    ///
+    /// These are counted as finished by `num_finished`.
+    pub fn num_placeholders(&self) -> usize {
+        self.assets
+            .lock()
+            .iter()
+            .filter(|a| a.status == AssetStatus::Placeholder)
+            .count()
+    }
+
     /// Returns the number of assets that have successfully loaded.
     pub fn num_finished(&self) -> usize {
         self.num_assets - self.num_loading() - self.num_failed()
     }
 
+    /// Returns the total weight and the weight of all assets that are no longer loading.
+    ///
+    /// Assets with an unknown weight count as the average of the known weights, or `1` if no
+    /// weight is known at all.
+    pub fn weights(&self) -> (u64, u64) {
+        let assets = self.assets.lock();
+        let (known_sum, known_count) = assets
+            .iter()
+            .filter_map(|a| a.weight)
+            .fold((0, 0), |(sum, count), w| (sum + w, count + 1));
+        let fallback = if known_count == 0 {
+            1
+        } else {
+            (known_sum / known_count).max(1)
+        };
+
+        assets.iter().fold((0, 0), |(total, done), a| {
+            let weight = a.weight.unwrap_or(fallback);
+            match a.status {
+                AssetStatus::Loading => (total + weight, done),
+                _ => (total + weight, done + weight),
+            }
+        })
+    }
+
+    /// Returns the fraction of the tracked weight that is no longer loading, from `0.0` to `1.0`.
+    ///
+    /// Failed assets count as done. Returns `1.0` if nothing is tracked.
+    pub fn fraction_complete(&self) -> f32 {
+        match self.weights() {
+            (0, _) => 1.0,
+            (total, done) => done as f32 / total as f32,
+        }
+    }
+
+    /// Returns the percentage of the tracked weight that is no longer loading.
+    pub fn percent_complete(&self) -> f32 {
+        self.fraction_complete() * 100.0
+    }
+
+    /// Returns the time that has passed since the first asset was added.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.started.map(|s| s.elapsed())
+    }
+
+    /// Estimates the remaining loading time from the throughput so far.
+    ///
+    /// Returns `None` until some weight has finished loading.
+    pub fn eta(&self) -> Option<Duration> {
+        let elapsed = self.elapsed()?;
+        match self.weights() {
+            (_, 0) => None,
+            (total, done) => {
+                let secs = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+                let remaining = secs * (total - done) as f64 / done as f64;
+                Some(Duration::new(
+                    remaining.trunc() as u64,
+                    (remaining.fract() * 1e9) as u32,
+                ))
+            }
+        }
+    }
+
     /// Returns `Completion::Complete` if all tracked assets are finished.
     pub fn complete(&self) -> Completion {
         match (
@@ -106,6 +247,9 @@ impl<'a> Progress for &'a mut ProgressCounter {
 
     fn add_assets(&mut self, num: usize) {
         self.num_assets += num;
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
     }
 
     fn create_tracker(self) -> Self::Tracker {
@@ -114,10 +258,25 @@ impl<'a> Progress for &'a mut ProgressCounter {
         let num_loading = self.num_loading.clone();
         num_loading.fetch_add(1, Ordering::Relaxed);
 
+        let assets = self.assets.clone();
+        let index = {
+            let mut lock = assets.lock();
+            lock.push(AssetProgress {
+                asset_type_name: None,
+                asset_name: None,
+                weight: None,
+                status: AssetStatus::Loading,
+            });
+            lock.len() - 1
+        };
+
         ProgressCounterTracker {
             errors,
             num_failed,
             num_loading,
+            assets,
+            index,
+            recent_failures: self.recent_failures.clone(),
         }
     }
 }
@@ -128,10 +287,49 @@ pub struct ProgressCounterTracker {
     errors: Arc<Mutex<Vec<AssetErrorMeta>>>,
     num_failed: Arc<AtomicUsize>,
     num_loading: Arc<AtomicUsize>,
+    assets: Arc<Mutex<Vec<AssetProgress>>>,
+    index: usize,
+    recent_failures: Arc<Mutex<VecDeque<AssetFailure>>>,
+}
+
+impl ProgressCounterTracker {
+    fn set_status(&self, status: AssetStatus) {
+        if let Some(asset) = self.assets.lock().get_mut(self.index) {
+            asset.status = status;
+        }
+    }
+
+    fn push_failure(
+        &self,
+        handle_id: u32,
+        asset_type_name: &'static str,
+        asset_name: String,
+        error: &Error,
+    ) {
+        let mut recent = self.recent_failures.lock();
+        if recent.len() == MAX_RECENT_FAILURES {
+            recent.pop_front();
+        }
+        recent.push_back(AssetFailure {
+            handle_id,
+            asset_type_name,
+            asset_name,
+            message: error.to_string(),
+        });
+    }
 }
 
 impl Tracker for ProgressCounterTracker {
+    fn started(&mut self, asset_type_name: &'static str, asset_name: &str, weight: Option<u64>) {
+        if let Some(asset) = self.assets.lock().get_mut(self.index) {
+            asset.asset_type_name = Some(asset_type_name);
+            asset.asset_name = Some(asset_name.to_owned());
+            asset.weight = weight;
+        }
+    }
+
     fn success(self: Box<Self>) {
+        self.set_status(AssetStatus::Loaded);
         self.num_loading.fetch_sub(1, Ordering::Relaxed);
     }
 
@@ -143,6 +341,8 @@ impl Tracker for ProgressCounterTracker {
         error: Error,
     ) {
         show_error(handle_id, asset_type_name, &asset_name, &error);
+        self.set_status(AssetStatus::Failed);
+        self.push_failure(handle_id, asset_type_name, asset_name.clone(), &error);
         self.errors.lock().push(AssetErrorMeta {
             error,
             handle_id,
@@ -155,6 +355,18 @@ impl Tracker for ProgressCounterTracker {
         // the assets that are still loading.
         self.num_loading.fetch_sub(1, Ordering::Relaxed);
     }
+
+    fn recovered(
+        self: Box<Self>,
+        handle_id: u32,
+        asset_type_name: &'static str,
+        asset_name: String,
+        error: Error,
+    ) {
+        self.set_status(AssetStatus::Placeholder);
+        self.push_failure(handle_id, asset_type_name, asset_name, &error);
+        self.num_loading.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 #[derive(Debug)]
@@ -169,6 +381,11 @@ pub struct AssetErrorMeta {
 /// back to `Progress`.
 pub trait Tracker: Send + 'static {
     // TODO: maybe add handles as parameters?
+    /// Called by the loader right before the asset is imported.
+    ///
+    /// `weight` is the size of the asset in bytes, if the source was able to tell.
+    fn started(&mut self, _asset_type_name: &'static str, _asset_name: &str, _weight: Option<u64>) {
+    }
     /// Called if the asset could be imported.
     fn success(self: Box<Self>);
     /// Called if the asset couldn't be imported to an error.
@@ -179,6 +396,19 @@ pub trait Tracker: Send + 'static {
         asset_name: String,
         error: Error,
     );
+    /// Called if the asset couldn't be imported, but a placeholder was used in its place.
+    ///
+    /// The default implementation logs the error and treats the asset as a success.
+    fn recovered(
+        self: Box<Self>,
+        handle_id: u32,
+        asset_type_name: &'static str,
+        asset_name: String,
+        error: Error,
+    ) {
+        show_error(handle_id, asset_type_name, &asset_name, &error);
+        self.success();
+    }
 }
 
 impl Tracker for () {
@@ -211,7 +441,7 @@ fn show_error(handle_id: u32, asset_type_name: &'static str, asset_name: &String
 mod tests {
     use amethyst_error::Error;
 
-    use super::{Completion, Progress, ProgressCounter, Tracker};
+    use super::{AssetStatus, Completion, Progress, ProgressCounter, Tracker};
 
     #[test]
     fn progress_counter_complete_returns_correct_completion_status_when_loading_or_complete() {
@@ -289,4 +519,78 @@ mod tests {
         tracker_2.success();
         assert_eq!(2, progress.num_finished());
     }
+
+    #[test]
+    fn progress_counter_reports_weighted_fraction_and_statuses() {
+        let mut progress_counter = ProgressCounter::new();
+        let mut progress = &mut progress_counter;
+        progress.add_assets(3);
+        let mut tracker_0 = Box::new(progress.create_tracker());
+        let mut tracker_1 = Box::new(progress.create_tracker());
+        let mut tracker_2 = Box::new(progress.create_tracker());
+        tracker_0.started("AssetType", "a.asset", Some(100));
+        tracker_1.started("AssetType", "b.asset", Some(300));
+        tracker_2.started("AssetType", "c.asset", None);
+
+        // Unknown weights count as the average of the known ones.
+        assert_eq!((600, 0), progress.weights());
+        assert!(progress.eta().is_none());
+
+        tracker_1.success();
+        assert_eq!((600, 300), progress.weights());
+        assert_eq!(50.0, progress.percent_complete());
+        assert!(progress.eta().is_some());
+
+        tracker_0.fail(
+            1,
+            "AssetType",
+            String::from("a.asset"),
+            Error::from_string("broken"),
+        );
+        assert_eq!((600, 400), progress.weights());
+
+        let statuses = progress
+            .assets()
+            .into_iter()
+            .map(|a| a.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                AssetStatus::Failed,
+                AssetStatus::Loaded,
+                AssetStatus::Loading
+            ],
+            statuses
+        );
+
+        let failures = progress.recent_failures();
+        assert_eq!(1, failures.len());
+        assert_eq!("a.asset", failures[0].asset_name);
+        assert_eq!("broken", failures[0].message);
+        // `recent_failures` does not drain anything.
+        assert_eq!(1, progress.recent_failures().len());
+    }
+
+    #[test]
+    fn progress_counter_counts_placeholders_as_finished() {
+        let mut progress_counter = ProgressCounter::new();
+        let mut progress = &mut progress_counter;
+        progress.add_assets(2);
+        let tracker_0 = Box::new(progress.create_tracker());
+        let tracker_1 = Box::new(progress.create_tracker());
+
+        tracker_0.recovered(
+            1,
+            "AssetType",
+            String::from("missing.asset"),
+            Error::from_string("not found"),
+        );
+        tracker_1.success();
+
+        assert_eq!(Completion::Complete, progress.complete());
+        assert_eq!(2, progress.num_finished());
+        assert_eq!(1, progress.num_placeholders());
+        assert_eq!(1, progress.recent_failures().len());
+        assert!(progress.errors().is_empty());
+    }
 }
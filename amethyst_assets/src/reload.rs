@@ -3,8 +3,8 @@
 use std::{sync::Arc, time::Instant};
 
 use amethyst_core::{
-    ecs::prelude::{DispatcherBuilder, Read, Resources, System, Write},
-    SystemBundle, Time,
+    ecs::prelude::{Read, Resources, System, Write},
+    BundleBuilder, SystemBundle, Time,
 };
 use amethyst_error::Error;
 
@@ -28,7 +28,7 @@ impl HotReloadBundle {
 }
 
 impl<'a, 'b> SystemBundle<'a, 'b> for HotReloadBundle {
-    fn build(self, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, dispatcher: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         dispatcher.add(HotReloadSystem::new(self.strategy), "hot_reload", &[]);
         Ok(())
     }
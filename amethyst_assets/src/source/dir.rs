@@ -1,15 +1,20 @@
 use std::{
+    collections::HashMap,
     fs::File,
+    hash::Hasher,
     path::{Path, PathBuf},
-    time::UNIX_EPOCH,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+use fnv::FnvHasher;
+
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
 use amethyst_error::{format_err, Error, ResultExt};
 
-use crate::{error, source::Source};
+use crate::{error, source::Source, AssetMetadata};
 
 /// Directory source.
 ///
@@ -17,9 +22,15 @@ use crate::{error, source::Source};
 /// inside the `Loader`, which is automatically used when you call
 /// `load`. In case you want another, second, directory for assets,
 /// you can instantiate one yourself, too. Please use `Loader::load_from` then.
+///
+/// Tags for an asset can be provided in a Ron file next to it, with `.meta` appended to the
+/// file name, e.g. `mesh/cube.obj.meta` containing `(tags: ["level1"])`.
 #[derive(Debug)]
 pub struct Directory {
     loc: PathBuf,
+    /// The hash of each file whose metadata was read, with its modification time and length
+    /// when it was hashed.
+    hashes: Mutex<HashMap<PathBuf, (SystemTime, u64, u64)>>,
 }
 
 impl Directory {
@@ -28,7 +39,10 @@ impl Directory {
     where
         P: Into<PathBuf>,
     {
-        Directory { loc: loc.into() }
+        Directory {
+            loc: loc.into(),
+            hashes: Mutex::new(HashMap::new()),
+        }
     }
 
     fn path(&self, s_path: &str) -> PathBuf {
@@ -58,6 +72,64 @@ impl Source for Directory {
             .map(|d| d.as_secs())
     }
 
+    fn size(&self, path: &str) -> Option<u64> {
+        std::fs::metadata(self.path(path)).ok().map(|m| m.len())
+    }
+
+    fn local_path(&self, path: &str) -> Option<PathBuf> {
+        Some(self.path(path))
+    }
+
+    fn metadata(&self, path: &str) -> Result<AssetMetadata, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("dir_asset_metadata");
+
+        let file_path = self.path(path);
+        let file = std::fs::metadata(&file_path)
+            .with_context(|_| format_err!("Failed to fetch metadata for {:?}", file_path))
+            .with_context(|_| error::Error::Source)?;
+        let size = file.len();
+        let modified = file.modified().ok();
+
+        // The file is only hashed again if its modification time or length changed.
+        let mut hashes = self.hashes.lock().unwrap_or_else(|err| err.into_inner());
+        let cached = match (modified, hashes.get(&file_path)) {
+            (Some(modified), Some(&(hashed, hashed_size, hash)))
+                if hashed == modified && hashed_size == size =>
+            {
+                Some(hash)
+            }
+            _ => None,
+        };
+        let hash = match cached {
+            Some(hash) => hash,
+            None => {
+                let bytes = self.load(path)?;
+                let mut hasher = FnvHasher::default();
+                hasher.write(&bytes);
+                let hash = hasher.finish();
+                if let Some(modified) = modified {
+                    hashes.insert(file_path, (modified, bytes.len() as u64, hash));
+                }
+                hash
+            }
+        };
+        drop(hashes);
+
+        let meta_path = format!("{}.meta", path);
+        let mut metadata = if self.path(&meta_path).is_file() {
+            let meta = self.load(&meta_path)?;
+            ron::de::from_bytes::<AssetMetadata>(&meta)
+                .with_context(|_| format_err!("Failed parsing metadata file {:?}", meta_path))?
+        } else {
+            AssetMetadata::default()
+        };
+        metadata.size = Some(size);
+        metadata.hash = Some(hash);
+
+        Ok(metadata)
+    }
+
     fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
         #[cfg(feature = "profiler")]
         profile_scope!("dir_load_asset");
@@ -98,6 +170,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn reads_asset_metadata_with_tags() {
+        let test_assets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/assets");
+        let directory = Directory::new(test_assets_dir);
+
+        let metadata = directory
+            .metadata("subdir/asset")
+            .expect("Failed to read metadata of tests/assets/subdir/asset");
+        assert_eq!(Some(4), metadata.size);
+        assert!(metadata.hash.is_some());
+        assert!(metadata.has_tag("test"));
+    }
+
+    #[test]
+    fn hashes_files_again_when_they_change() {
+        use std::{fs, thread, time::Duration};
+
+        let dir = std::env::temp_dir().join("amethyst_assets_dir_metadata");
+        fs::create_dir_all(&dir).expect("Failed to create the test directory");
+        fs::write(dir.join("asset"), "data").expect("Failed to write the test asset");
+        let directory = Directory::new(&dir);
+
+        let first = directory
+            .metadata("asset")
+            .expect("Failed to read metadata");
+        let again = directory
+            .metadata("asset")
+            .expect("Failed to read metadata");
+        assert_eq!(first.hash, again.hash);
+
+        // Some file systems only store the modification time in seconds.
+        thread::sleep(Duration::from_millis(1100));
+        fs::write(dir.join("asset"), "other data").expect("Failed to write the test asset");
+        let changed = directory
+            .metadata("asset")
+            .expect("Failed to read metadata");
+        assert_eq!(Some(10), changed.size);
+        assert_ne!(first.hash, changed.hash);
+    }
+
     #[cfg(windows)]
     #[test]
     fn tolerates_backslashed_location_with_forward_slashed_asset_paths() {
@@ -1,5 +1,9 @@
+use std::path::PathBuf;
+
 use amethyst_error::Error;
 
+use crate::AssetMetadata;
+
 pub use self::dir::Directory;
 
 #[cfg(feature = "profiler")]
@@ -20,6 +24,32 @@ pub trait Source: Send + Sync + 'static {
     /// The id should always use `/` as separator in paths.
     fn load(&self, path: &str) -> Result<Vec<u8>, Error>;
 
+    /// Returns the size in bytes of the asset at the given path.
+    ///
+    /// This is only used for progress reporting and to decide if a file is worth memory mapping,
+    /// so the default implementation returns `None`.
+    fn size(&self, _path: &str) -> Option<u64> {
+        None
+    }
+
+    /// Returns the path of the asset on the local file system, if this source reads from it.
+    ///
+    /// This allows formats to memory map large files instead of loading them, so the default
+    /// implementation returns `None`.
+    fn local_path(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Returns the metadata of the asset at the given path.
+    ///
+    /// The default implementation only provides the size, as returned by `size`.
+    fn metadata(&self, path: &str) -> Result<AssetMetadata, Error> {
+        Ok(AssetMetadata {
+            size: self.size(path),
+            ..Default::default()
+        })
+    }
+
     /// Returns both the result of `load` and `modified` as a tuple.
     /// There's a default implementation which just calls both methods,
     /// but you may be able to provide a more optimized version yourself.
@@ -0,0 +1,266 @@
+//! Memory and load time statistics of the asset storages.
+
+use std::{
+    ops::Add,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use fnv::FnvHashMap;
+
+use crate::Asset;
+
+/// Estimated memory used by one or more assets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AssetSize {
+    /// Bytes of main memory.
+    pub cpu_bytes: u64,
+    /// Bytes of GPU memory, e.g. vertex buffers and textures.
+    pub gpu_bytes: u64,
+}
+
+impl AssetSize {
+    /// Creates a size estimate for an asset living in main memory.
+    pub fn cpu(bytes: u64) -> Self {
+        AssetSize {
+            cpu_bytes: bytes,
+            gpu_bytes: 0,
+        }
+    }
+
+    /// Creates a size estimate for an asset which has been uploaded to the GPU.
+    pub fn gpu(bytes: u64) -> Self {
+        AssetSize {
+            cpu_bytes: 0,
+            gpu_bytes: bytes,
+        }
+    }
+
+    /// The sum of CPU and GPU bytes.
+    pub fn total(&self) -> u64 {
+        self.cpu_bytes + self.gpu_bytes
+    }
+}
+
+impl Add for AssetSize {
+    type Output = AssetSize;
+
+    fn add(self, other: AssetSize) -> AssetSize {
+        AssetSize {
+            cpu_bytes: self.cpu_bytes + other.cpu_bytes,
+            gpu_bytes: self.gpu_bytes + other.gpu_bytes,
+        }
+    }
+}
+
+/// Statistics of a single `AssetStorage`, returned by `AssetStorage::stats`.
+#[derive(Clone, Debug, Default)]
+pub struct StorageStats {
+    /// Number of assets currently in the storage.
+    pub count: usize,
+    /// Estimated memory of all assets in the storage.
+    ///
+    /// This is zero unless a size estimator has been set with `AssetStorage::set_size_estimator`.
+    pub size: AssetSize,
+    /// Number of assets that have been loaded successfully.
+    pub loaded: u64,
+    /// Number of assets that failed to load, including the ones replaced by a placeholder.
+    pub failed: u64,
+    /// Sum of the load latencies, measured from the `Loader` call until the asset got inserted
+    /// into the storage.
+    pub total_load_time: Duration,
+    /// The highest load latency.
+    pub max_load_time: Duration,
+    /// The load latency of the most recently loaded asset.
+    pub last_load_time: Option<Duration>,
+}
+
+impl StorageStats {
+    /// The mean load latency, `None` if no asset has been loaded yet.
+    pub fn mean_load_time(&self) -> Option<Duration> {
+        if self.loaded == 0 {
+            None
+        } else {
+            // `Duration` can only be divided by `u32`
+            let nanos = self.total_load_time.as_secs() as u128 * 1_000_000_000
+                + u128::from(self.total_load_time.subsec_nanos());
+            let mean = nanos / u128::from(self.loaded);
+            Some(Duration::from_nanos(mean as u64))
+        }
+    }
+
+    pub(crate) fn record_load(&mut self, latency: Duration) {
+        self.loaded += 1;
+        self.total_load_time += latency;
+        self.max_load_time = self.max_load_time.max(latency);
+        self.last_load_time = Some(latency);
+    }
+}
+
+/// A resource collecting the `StorageStats` of every asset storage, keyed by `Asset::NAME`.
+///
+/// The stats are only collected if this resource has been added to the world. The `Processor`
+/// and the render system refresh the stats of their storages once per `period`, as estimating
+/// the memory goes over all assets of a storage. The stats are behind a lock, so the systems
+/// only need shared access to the resource and keep running in parallel.
+///
+/// It's meant to be shown by debug overlays, e.g. to find out which storage holds the most
+/// memory.
+#[derive(Debug)]
+pub struct AssetStats {
+    period: Duration,
+    storages: Mutex<FnvHashMap<&'static str, (Duration, StorageStats)>>,
+}
+
+impl Default for AssetStats {
+    fn default() -> Self {
+        AssetStats {
+            period: Duration::from_secs(1),
+            storages: Default::default(),
+        }
+    }
+}
+
+impl AssetStats {
+    /// Creates an empty `AssetStats` resource, refreshed every second.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets how often the stats of a storage are refreshed, in real time.
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Replaces the stats of the storage with the given asset name.
+    pub fn update(&self, asset_type_name: &'static str, stats: StorageStats) {
+        self.lock()
+            .insert(asset_type_name, (Duration::from_secs(0), stats));
+    }
+
+    /// Replaces the stats of the storage with the ones returned by `stats`, unless they were
+    /// refreshed less than a period before `now`.
+    pub fn refresh<F>(&self, asset_type_name: &'static str, now: Duration, stats: F)
+    where
+        F: FnOnce() -> StorageStats,
+    {
+        let due = match self.lock().get(asset_type_name) {
+            Some(&(updated, _)) => now >= updated + self.period,
+            None => true,
+        };
+        if due {
+            let stats = stats();
+            self.lock().insert(asset_type_name, (now, stats));
+        }
+    }
+
+    /// Returns the stats of the storage with the given asset name.
+    pub fn get(&self, asset_type_name: &str) -> Option<StorageStats> {
+        self.lock()
+            .get(asset_type_name)
+            .map(|(_, stats)| stats.clone())
+    }
+
+    /// Returns the stats of the storage for assets of type `A`.
+    pub fn get_asset<A: Asset>(&self) -> Option<StorageStats> {
+        self.get(A::NAME)
+    }
+
+    /// Returns the asset names and stats of all storages.
+    pub fn all(&self) -> Vec<(&'static str, StorageStats)> {
+        self.lock()
+            .iter()
+            .map(|(name, (_, stats))| (*name, stats.clone()))
+            .collect()
+    }
+
+    /// Returns all storages ordered by their estimated memory, largest first.
+    pub fn by_size(&self) -> Vec<(&'static str, StorageStats)> {
+        let mut storages = self.all();
+        storages.sort_by(|a, b| b.1.size.total().cmp(&a.1.size.total()));
+        storages
+    }
+
+    /// The estimated memory of all storages.
+    pub fn total_size(&self) -> AssetSize {
+        self.lock()
+            .values()
+            .fold(AssetSize::default(), |acc, (_, stats)| acc + stats.size)
+    }
+
+    /// The number of assets in all storages.
+    pub fn total_count(&self) -> usize {
+        self.lock().values().map(|(_, stats)| stats.count).sum()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FnvHashMap<&'static str, (Duration, StorageStats)>> {
+        self.storages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_load_time() {
+        let mut stats = StorageStats::default();
+        assert_eq!(None, stats.mean_load_time());
+
+        stats.record_load(Duration::from_millis(10));
+        stats.record_load(Duration::from_millis(30));
+        assert_eq!(Some(Duration::from_millis(20)), stats.mean_load_time());
+        assert_eq!(Duration::from_millis(30), stats.max_load_time);
+        assert_eq!(Some(Duration::from_millis(30)), stats.last_load_time);
+    }
+
+    #[test]
+    fn totals() {
+        let stats = AssetStats::new();
+        stats.update(
+            "Mesh",
+            StorageStats {
+                count: 2,
+                size: AssetSize::gpu(100),
+                ..Default::default()
+            },
+        );
+        stats.update(
+            "Audio",
+            StorageStats {
+                count: 3,
+                size: AssetSize::cpu(500),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(5, stats.total_count());
+        assert_eq!(
+            AssetSize {
+                cpu_bytes: 500,
+                gpu_bytes: 100
+            },
+            stats.total_size()
+        );
+        assert_eq!("Audio", stats.by_size()[0].0);
+    }
+
+    #[test]
+    fn refreshed_once_per_period() {
+        let stats = AssetStats::new().with_period(Duration::from_secs(1));
+        let count = |count| {
+            move || StorageStats {
+                count,
+                ..Default::default()
+            }
+        };
+        stats.refresh("Mesh", Duration::from_millis(100), count(1));
+        stats.refresh("Mesh", Duration::from_millis(600), count(2));
+        assert_eq!(1, stats.get("Mesh").unwrap().count);
+        stats.refresh("Mesh", Duration::from_millis(1100), count(3));
+        assert_eq!(3, stats.get("Mesh").unwrap().count);
+    }
+}
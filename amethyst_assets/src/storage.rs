@@ -4,6 +4,7 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc, Mutex, Weak,
     },
+    time::Instant,
 };
 
 use crossbeam::queue::MsQueue;
@@ -26,6 +27,8 @@ use crate::{
     error,
     progress::Tracker,
     reload::{HotReloadStrategy, Reload},
+    stats::{AssetSize, AssetStats, StorageStats},
+    Loader,
 };
 
 /// An `Allocator`, holding a counter for producing unique IDs.
@@ -52,6 +55,9 @@ pub struct AssetStorage<A: Asset> {
     reloads: Vec<(WeakHandle<A>, Box<dyn Reload<A>>)>,
     unused_handles: MsQueue<Handle<A>>,
     requeue: Mutex<Vec<Processed<A>>>,
+    placeholder: Option<Box<dyn Fn() -> A::Data + Send + Sync>>,
+    size_estimator: Option<Box<dyn Fn(&A) -> AssetSize + Send + Sync>>,
+    stats: StorageStats,
 }
 
 /// Returned by processor systems, describes the loading state of the asset.
@@ -112,6 +118,54 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Sets the placeholder which is used in place of assets that fail to load.
+    ///
+    /// The placeholder data is processed like regular asset data and stored under the handle of
+    /// the failed asset. The failure is still logged and reported to the `Tracker` as recovered,
+    /// so `ProgressCounter`s don't fail (and prefabs using the asset keep loading).
+    pub fn set_placeholder<F>(&mut self, placeholder: F)
+    where
+        F: Fn() -> A::Data + Send + Sync + 'static,
+    {
+        self.placeholder = Some(Box::new(placeholder));
+    }
+
+    /// Removes the placeholder, failing assets are no longer substituted.
+    pub fn clear_placeholder(&mut self) {
+        self.placeholder = None;
+    }
+
+    /// Returns `true` if a placeholder has been set.
+    pub fn has_placeholder(&self) -> bool {
+        self.placeholder.is_some()
+    }
+
+    /// Sets the function used to estimate the memory of the stored assets for `stats`.
+    pub fn set_size_estimator<F>(&mut self, estimator: F)
+    where
+        F: Fn(&A) -> AssetSize + Send + Sync + 'static,
+    {
+        self.size_estimator = Some(Box::new(estimator));
+    }
+
+    /// Returns the number of assets, their estimated memory and the load latencies of this
+    /// storage.
+    ///
+    /// The memory is only estimated if a size estimator has been set, which requires going over
+    /// all assets in the storage.
+    pub fn stats(&self) -> StorageStats {
+        let mut stats = self.stats.clone();
+        stats.count = self.handles.len();
+        if let Some(ref estimator) = self.size_estimator {
+            stats.size = self
+                .handles
+                .iter()
+                .filter_map(|handle| self.get(handle))
+                .fold(AssetSize::default(), |acc, asset| acc + estimator(asset));
+        }
+        stats
+    }
+
     /// Get an asset from a given asset handle.
     pub fn get(&self, handle: &Handle<A>) -> Option<&A> {
         if self.bitset.contains(handle.id()) {
@@ -121,6 +175,13 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Get an asset from a given weak asset handle.
+    ///
+    /// Returns `None` if the asset has already been unloaded.
+    pub fn get_weak(&self, handle: &WeakHandle<A>) -> Option<&A> {
+        handle.upgrade().and_then(|handle| self.get(&handle))
+    }
+
     /// Get an asset mutably from a given asset handle.
     pub fn get_mut(&mut self, handle: &Handle<A>) -> Option<&mut A> {
         if self.bitset.contains(handle.id()) {
@@ -166,6 +227,8 @@ impl<A: Asset> AssetStorage<A> {
                 let bitset = &mut self.bitset;
                 let handles = &mut self.handles;
                 let reloads = &mut self.reloads;
+                let placeholder = &self.placeholder;
+                let stats = &mut self.stats;
 
                 let f = &mut f;
                 let (reload_obj, handle) = match processed {
@@ -174,6 +237,7 @@ impl<A: Asset> AssetStorage<A> {
                         handle,
                         name,
                         tracker,
+                        requested,
                     } => {
                         let (asset, reload_obj) = match data
                             .map(|FormatValue { data, reload }| (data, reload))
@@ -204,6 +268,7 @@ impl<A: Asset> AssetStorage<A> {
                                 } else {
                                     tracker.success();
                                 }
+                                stats.record_load(requested.elapsed());
 
                                 (x, r)
                             }
@@ -219,20 +284,43 @@ impl<A: Asset> AssetStorage<A> {
                                     handle,
                                     name,
                                     tracker,
+                                    requested,
                                 });
                                 continue;
                             }
                             Err(e) => {
-                                error!(
-                                    "{:?}: Asset {:?} (handle id: {:?}) could not be loaded: {}",
-                                    A::NAME,
-                                    name,
-                                    handle,
-                                    e,
-                                );
-                                tracker.fail(handle.id(), A::NAME, name, e);
-
-                                continue;
+                                let substitute = placeholder.as_ref().and_then(|p| match f(p()) {
+                                    Ok(ProcessingState::Loaded(x)) => Some(x),
+                                    _ => None,
+                                });
+                                stats.failed += 1;
+                                match substitute {
+                                    Some(x) => {
+                                        error!(
+                                            "{:?}: Asset {:?} (handle id: {:?}) could not be loaded, \
+                                             using placeholder instead: {}",
+                                            A::NAME,
+                                            name,
+                                            handle,
+                                            e,
+                                        );
+                                        tracker.recovered(handle.id(), A::NAME, name, e);
+
+                                        (x, None)
+                                    }
+                                    None => {
+                                        error!(
+                                            "{:?}: Asset {:?} (handle id: {:?}) could not be loaded: {}",
+                                            A::NAME,
+                                            name,
+                                            handle,
+                                            e,
+                                        );
+                                        tracker.fail(handle.id(), A::NAME, name, e);
+
+                                        continue;
+                                    }
+                                }
                             }
                         };
 
@@ -351,15 +439,28 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Loads all assets again which have a source to reload them from, regardless of whether
+    /// their source changed.
+    ///
+    /// Used when the loaded assets became invalid, e.g. GPU resources after the graphics device
+    /// was lost. Assets loaded from data instead of a source are not reloaded. The new assets
+    /// replace the old ones in a later call of `process`.
+    pub fn reload_all(&mut self, pool: &ThreadPool) {
+        self.reload(pool, true);
+    }
+
     fn hot_reload(&mut self, pool: &ThreadPool) {
+        self.reload(pool, false);
+    }
+
+    fn reload(&mut self, pool: &ThreadPool, all: bool) {
         self.reloads.retain(|&(ref handle, _)| !handle.is_dead());
-        while let Some(p) = self
+        let (reloads, kept): (Vec<_>, Vec<_>) = self
             .reloads
-            .iter()
-            .position(|&(_, ref rel)| rel.needs_reload())
-        {
-            let (handle, rel): (WeakHandle<_>, Box<dyn Reload<_>>) = self.reloads.swap_remove(p);
-
+            .drain(..)
+            .partition(|&(_, ref rel)| all || rel.needs_reload());
+        self.reloads = kept;
+        for (handle, rel) in reloads {
             let name = rel.name();
             let format = rel.format();
             let handle = handle.upgrade();
@@ -402,6 +503,9 @@ impl<A: Asset> Default for AssetStorage<A> {
             reloads: Default::default(),
             unused_handles: MsQueue::new(),
             requeue: Mutex::new(Vec::default()),
+            placeholder: None,
+            size_estimator: None,
+            stats: Default::default(),
         }
     }
 }
@@ -440,20 +544,25 @@ where
 {
     type SystemData = (
         Write<'a, AssetStorage<A>>,
-        ReadExpect<'a, Arc<ThreadPool>>,
+        ReadExpect<'a, Loader>,
         Read<'a, Time>,
         Option<Read<'a, HotReloadStrategy>>,
+        Option<Read<'a, AssetStats>>,
     );
 
-    fn run(&mut self, (mut storage, pool, time, strategy): Self::SystemData) {
+    fn run(&mut self, (mut storage, loader, time, strategy, stats): Self::SystemData) {
         use std::ops::Deref;
 
         storage.process(
             Into::into,
             time.frame_number(),
-            &**pool,
+            &**loader.pool(),
             strategy.as_ref().map(Deref::deref),
         );
+
+        if let Some(stats) = stats {
+            stats.refresh(A::NAME, time.absolute_real_time(), || storage.stats());
+        }
     }
 }
 
@@ -509,6 +618,7 @@ pub(crate) enum Processed<A: Asset> {
         handle: Handle<A>,
         name: String,
         tracker: Box<dyn Tracker>,
+        requested: Instant,
     },
     HotReload {
         data: Result<FormatValue<A>, Error>,
@@ -520,10 +630,15 @@ pub(crate) enum Processed<A: Asset> {
 
 /// A weak handle, which is useful if you don't directly need the asset
 /// like in caches. This way, the asset can still get dropped (if you want that).
+///
+/// A weak handle can only be upgraded to a `Handle` while another `Handle` to the asset is alive.
+/// Upgrading fails as soon as the last `Handle` is dropped, even though the asset storage only
+/// unloads the asset during its next `process` call.
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""))]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
 pub struct WeakHandle<A> {
     id: Weak<u32>,
+    #[derivative(Debug = "ignore")]
     marker: PhantomData<A>,
 }
 
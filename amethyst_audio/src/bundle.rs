@@ -2,7 +2,9 @@
 
 use amethyst_assets::Processor;
 use amethyst_core::{
-    alga::general::SubsetOf, bundle::SystemBundle, ecs::prelude::DispatcherBuilder, math::RealField,
+    alga::general::SubsetOf,
+    bundle::{BundleBuilder, SystemBundle},
+    math::RealField,
 };
 use amethyst_error::Error;
 use std::marker::PhantomData;
@@ -23,7 +25,7 @@ impl<'a, 'b, N: RealField> SystemBundle<'a, 'b> for AudioBundle<N>
 where
     N: RealField + SubsetOf<f32>,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(AudioSystem::<N>::new(self.0), "audio_system", &[]);
         builder.add(Processor::<Source>::new(), "source_processor", &[]);
         Ok(())
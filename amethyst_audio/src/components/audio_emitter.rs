@@ -8,7 +8,7 @@ use smallvec::SmallVec;
 
 use amethyst_core::ecs::{prelude::Component, storage::BTreeStorage};
 
-use crate::{source::Source, DecoderError};
+use crate::{mixer::AudioBus, source::Source, DecoderError};
 
 /// An audio source, add this component to anything that emits sound.
 #[derive(Default)]
@@ -16,6 +16,7 @@ pub struct AudioEmitter {
     pub(crate) sinks: SmallVec<[(SpatialSink, Arc<AtomicBool>); 4]>,
     pub(crate) sound_queue: SmallVec<[Decoder<Cursor<Source>>; 4]>,
     pub(crate) picker: Option<Box<dyn FnMut(&mut AudioEmitter) -> bool + Send + Sync>>,
+    pub(crate) bus: AudioBus,
 }
 
 impl AudioEmitter {
@@ -33,6 +34,17 @@ impl AudioEmitter {
         Ok(())
     }
 
+    /// Returns the `AudioBus` the sounds of this emitter are mixed into, `AudioBus::Sfx` by
+    /// default.
+    pub fn bus(&self) -> AudioBus {
+        self.bus
+    }
+
+    /// Sets the `AudioBus` the sounds of this emitter are mixed into.
+    pub fn set_bus(&mut self, bus: AudioBus) {
+        self.bus = bus;
+    }
+
     /// An emitter's picker will be called by the AudioSystem whenever the emitter runs out of
     /// sounds to play.
     ///
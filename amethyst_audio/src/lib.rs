@@ -5,6 +5,7 @@ pub use self::{
     bundle::AudioBundle,
     components::*,
     formats::{AudioFormat, FlacFormat, Mp3Format, OggFormat, WavFormat},
+    mixer::{AudioBus, AudioMixer, BusSettings},
     sink::AudioSink,
     source::{Source, SourceHandle},
     systems::*,
@@ -21,6 +22,7 @@ mod bundle;
 mod components;
 mod end_signal;
 mod formats;
+mod mixer;
 mod sink;
 mod source;
 mod systems;
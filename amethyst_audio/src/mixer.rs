@@ -0,0 +1,181 @@
+//! Volume groups for the sounds of a game.
+
+use rodio::{Sample, Source as RSource};
+use serde::{Deserialize, Serialize};
+
+/// A category of sounds whose volume and effects are controlled together by the `AudioMixer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AudioBus {
+    /// Applies to all other buses.
+    Master,
+    /// Background music, played by the `AudioSink`.
+    Music,
+    /// Sound effects, played by `AudioEmitter`s and ui sounds.
+    Sfx,
+    /// Dialogue.
+    Voice,
+}
+
+impl AudioBus {
+    /// All buses, in the order of their index.
+    pub const ALL: [AudioBus; 4] = [
+        AudioBus::Master,
+        AudioBus::Music,
+        AudioBus::Sfx,
+        AudioBus::Voice,
+    ];
+
+    fn index(self) -> usize {
+        match self {
+            AudioBus::Master => 0,
+            AudioBus::Music => 1,
+            AudioBus::Sfx => 2,
+            AudioBus::Voice => 3,
+        }
+    }
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        AudioBus::Sfx
+    }
+}
+
+/// The settings of a single `AudioBus`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BusSettings {
+    /// The volume of the bus, 1.0 is unchanged and 0.0 is silent.
+    pub volume: f32,
+    /// Silences the bus without changing its volume.
+    pub muted: bool,
+    /// Cutoff frequency of a low-pass filter in Hz, e.g. for muffling sounds while underwater.
+    pub low_pass: Option<u32>,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        BusSettings {
+            volume: 1.0,
+            muted: false,
+            low_pass: None,
+        }
+    }
+}
+
+/// Resource controlling the volume and effects of the sounds by their `AudioBus`, e.g. from a
+/// settings menu.
+///
+/// Sounds are only mixed while the resource exists: the `AudioSystem` mixes the sounds of
+/// `AudioEmitter`s, the `DjSystem` the music of the `AudioSink` and the `UiSoundSystem` the ui
+/// sounds. Sounds played with `Output::play_on_bus` are mixed as well.
+///
+/// The volume of a bus is multiplied with the volume of the `Master` bus and the volume of each
+/// sound. Volume changes apply to playing sounds, while effects only apply to sounds started after
+/// the change.
+///
+/// It can be saved and loaded with `amethyst_config::Config`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioMixer {
+    buses: [BusSettings; 4],
+}
+
+impl AudioMixer {
+    /// Creates a mixer with all buses at full volume and without effects.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the settings of the bus.
+    pub fn bus(&self, bus: AudioBus) -> &BusSettings {
+        &self.buses[bus.index()]
+    }
+
+    /// Returns the settings of the bus for modification.
+    pub fn bus_mut(&mut self, bus: AudioBus) -> &mut BusSettings {
+        &mut self.buses[bus.index()]
+    }
+
+    /// Returns the volume of the bus.
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        self.bus(bus).volume
+    }
+
+    /// Sets the volume of the bus, 1.0 is unchanged and 0.0 is silent.
+    pub fn set_volume(&mut self, bus: AudioBus, volume: f32) {
+        self.bus_mut(bus).volume = volume.max(0.0);
+    }
+
+    /// Returns `true` if the bus is muted.
+    pub fn is_muted(&self, bus: AudioBus) -> bool {
+        self.bus(bus).muted
+    }
+
+    /// Mutes or unmutes the bus.
+    pub fn set_muted(&mut self, bus: AudioBus, muted: bool) {
+        self.bus_mut(bus).muted = muted;
+    }
+
+    /// Sets the cutoff frequency of the low-pass filter of the bus in Hz, `None` removes it.
+    pub fn set_low_pass(&mut self, bus: AudioBus, cutoff: Option<u32>) {
+        self.bus_mut(bus).low_pass = cutoff;
+    }
+
+    /// Returns the volume sounds on the bus are played at, including the `Master` bus and muting.
+    pub fn gain(&self, bus: AudioBus) -> f32 {
+        let gain = |settings: &BusSettings| {
+            if settings.muted {
+                0.0
+            } else {
+                settings.volume
+            }
+        };
+        let master = gain(self.bus(AudioBus::Master));
+        match bus {
+            AudioBus::Master => master,
+            bus => master * gain(self.bus(bus)),
+        }
+    }
+
+    /// Applies the effects of the bus and the `Master` bus to the sound.
+    pub fn apply_effects<S>(&self, bus: AudioBus, source: S) -> Box<dyn RSource<Item = f32> + Send>
+    where
+        S: RSource + Send + 'static,
+        S::Item: Sample,
+    {
+        let mut source: Box<dyn RSource<Item = f32> + Send> =
+            Box::new(source.convert_samples::<f32>());
+        let mut buses = vec![AudioBus::Master];
+        if bus != AudioBus::Master {
+            buses.push(bus);
+        }
+        for bus in buses {
+            if let Some(cutoff) = self.bus(bus).low_pass {
+                source = Box::new(source.low_pass(cutoff));
+            }
+        }
+        source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_includes_master_and_mute() {
+        let mut mixer = AudioMixer::new();
+        mixer.set_volume(AudioBus::Master, 0.5);
+        mixer.set_volume(AudioBus::Music, 0.5);
+        assert!((mixer.gain(AudioBus::Music) - 0.25).abs() < 1e-6);
+        assert!((mixer.gain(AudioBus::Sfx) - 0.5).abs() < 1e-6);
+
+        mixer.set_muted(AudioBus::Sfx, true);
+        assert!(mixer.gain(AudioBus::Sfx).abs() < 1e-6);
+        assert!((mixer.volume(AudioBus::Sfx) - 1.0).abs() < 1e-6);
+
+        mixer.set_muted(AudioBus::Master, true);
+        assert!(mixer.gain(AudioBus::Voice).abs() < 1e-6);
+    }
+}
@@ -12,7 +12,12 @@ use rodio::{default_output_device, output_devices, Decoder, Device, Sink, Source
 
 use amethyst_core::shred::Resources;
 
-use crate::{sink::AudioSink, source::Source, DecoderError};
+use crate::{
+    mixer::{AudioBus, AudioMixer},
+    sink::AudioSink,
+    source::Source,
+    DecoderError,
+};
 
 /// A speaker(s) through which audio can be played.
 ///
@@ -84,6 +89,38 @@ impl Output {
         sink.detach();
         Ok(())
     }
+
+    /// Play a sound once on a bus of the mixer. A volume of 1.0 is unchanged, while 0.0 is silent.
+    ///
+    /// The volume is multiplied with the gain of the bus and the effects of the bus are applied.
+    ///
+    /// This will return an Error if the loaded audio file in source could not be decoded.
+    pub fn try_play_on_bus(
+        &self,
+        source: &Source,
+        volume: f32,
+        bus: AudioBus,
+        mixer: &AudioMixer,
+    ) -> Result<(), DecoderError> {
+        let decoder = Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?;
+        let sink = Sink::new(&self.device);
+        sink.append(
+            mixer
+                .apply_effects(bus, decoder)
+                .amplify(volume * mixer.gain(bus)),
+        );
+        sink.detach();
+        Ok(())
+    }
+
+    /// Play a sound once on a bus of the mixer. A volume of 1.0 is unchanged, while 0.0 is silent.
+    ///
+    /// This may silently fail, in order to get error information use `try_play_on_bus`.
+    pub fn play_on_bus(&self, source: &Source, volume: f32, bus: AudioBus, mixer: &AudioMixer) {
+        if let Err(err) = self.try_play_on_bus(source, volume, bus, mixer) {
+            error!("An error occurred while trying to play a sound: {:?}", err);
+        }
+    }
 }
 
 impl Debug for Output {
@@ -2,11 +2,21 @@ use std::io::Cursor;
 
 use rodio::{Decoder, Sink};
 
-use crate::{output::Output, source::Source, DecoderError};
+use crate::{
+    mixer::{AudioBus, AudioMixer},
+    output::Output,
+    source::Source,
+    DecoderError,
+};
 
 /// This structure provides a way to programmatically pick and play music.
+///
+/// The sink plays on the `AudioBus::Music` bus of the `AudioMixer`, its volume is multiplied with
+/// the gain of the bus.
 pub struct AudioSink {
     sink: Sink,
+    volume: f32,
+    gain: f32,
 }
 
 impl AudioSink {
@@ -14,6 +24,8 @@ impl AudioSink {
     pub fn new(output: &Output) -> AudioSink {
         AudioSink {
             sink: Sink::new(&output.device),
+            volume: 1.0,
+            gain: 1.0,
         }
     }
 
@@ -24,6 +36,14 @@ impl AudioSink {
         Ok(())
     }
 
+    /// Adds a source to the sink's queue, with the effects of the `AudioBus::Music` bus applied.
+    pub fn append_mixed(&self, source: &Source, mixer: &AudioMixer) -> Result<(), DecoderError> {
+        let decoder = Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?;
+        self.sink
+            .append(mixer.apply_effects(AudioBus::Music, decoder));
+        Ok(())
+    }
+
     /// Returns true if the sink has no more music to play.
     pub fn empty(&self) -> bool {
         self.sink.empty()
@@ -31,12 +51,19 @@ impl AudioSink {
 
     /// Retrieves the volume of the sink, between 0.0 and 1.0;
     pub fn volume(&self) -> f32 {
-        self.sink.volume()
+        self.volume
     }
 
     /// Sets the volume of the sink.
     pub fn set_volume(&mut self, volume: f32) {
-        self.sink.set_volume(volume);
+        self.volume = volume;
+        self.sink.set_volume(volume * self.gain);
+    }
+
+    /// Sets the gain of the mixer bus the sink plays on.
+    pub(crate) fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+        self.sink.set_volume(self.volume * gain);
     }
 
     /// Resumes playback of a paused sink. Has no effect if this sink was never paused.
@@ -25,11 +25,14 @@ use amethyst_core::{
 use crate::{
     components::{AudioEmitter, AudioListener},
     end_signal::EndSignalSource,
+    mixer::AudioMixer,
     output::Output,
 };
 
 /// Syncs 3D transform data with the audio engine to provide 3D audio.
 /// The generic N type should be the same as the one in `Transform<N>`.
+///
+/// If an `AudioMixer` resource exists, the sounds of emitters are mixed into their `AudioBus`.
 #[derive(Default)]
 pub struct AudioSystem<N>(Output, PhantomData<N>);
 
@@ -56,11 +59,12 @@ where
         ReadStorage<'a, Transform<N>>,
         ReadStorage<'a, AudioListener<N>>,
         WriteStorage<'a, AudioEmitter>,
+        Option<Read<'a, AudioMixer>>,
     );
 
     fn run(
         &mut self,
-        (output, select_listener, entities, transform, listener, mut audio_emitter): Self::SystemData,
+        (output, select_listener, entities, transform, listener, mut audio_emitter, mixer): Self::SystemData,
     ) {
         #[cfg(feature = "profiler")]
         profile_scope!("audio_system");
@@ -97,12 +101,16 @@ where
                         let z = transform.global_matrix()[(2, 3)];
                         [convert(x), convert(y), convert(z)]
                     };
+                    let gain = mixer
+                        .as_ref()
+                        .map_or(1.0, |mixer| mixer.gain(audio_emitter.bus));
                     // Remove all sinks whose sounds have ended.
                     audio_emitter.sinks.retain(|s| !s.1.load(Ordering::Relaxed));
                     for &mut (ref mut sink, _) in &mut audio_emitter.sinks {
                         sink.set_emitter_position(emitter_position);
                         sink.set_left_ear_position(left_ear_position);
                         sink.set_right_ear_position(right_ear_position);
+                        sink.set_volume(gain);
                     }
                     if audio_emitter.sinks.is_empty() {
                         if let Some(mut picker) = replace(&mut audio_emitter.picker, None) {
@@ -119,11 +127,19 @@ where
                                 left_ear_position,
                                 right_ear_position,
                             );
+                            sink.set_volume(gain);
                             let atomic_bool = Arc::new(AtomicBool::new(false));
                             let clone = atomic_bool.clone();
-                            sink.append(EndSignalSource::new(source, move || {
+                            let end_signal = move || {
                                 clone.store(true, Ordering::Relaxed);
-                            }));
+                            };
+                            match mixer {
+                                Some(ref mixer) => sink.append(EndSignalSource::new(
+                                    mixer.apply_effects(audio_emitter.bus, source),
+                                    end_signal,
+                                )),
+                                None => sink.append(EndSignalSource::new(source, end_signal)),
+                            }
                             audio_emitter.sinks.push((sink, atomic_bool));
                         }
                     }
@@ -7,18 +7,21 @@ use amethyst_assets::AssetStorage;
 use amethyst_core::{
     ecs::{
         common::Errors,
-        prelude::{Read, System, WriteExpect},
+        prelude::{Read, System, Write, WriteExpect},
     },
     shred::{Resource, Resources},
 };
 
 use crate::{
+    mixer::{AudioBus, AudioMixer},
     output::init_output,
     sink::AudioSink,
     source::{Source, SourceHandle},
 };
 
 /// Calls a closure if the `AudioSink` is empty.
+///
+/// If an `AudioMixer` resource exists, the music is mixed into the `AudioBus::Music` bus.
 pub struct DjSystem<F, R> {
     f: F,
     marker: PhantomData<R>,
@@ -45,17 +48,24 @@ where
     type SystemData = (
         Read<'a, AssetStorage<Source>>,
         Read<'a, Errors>,
-        Option<Read<'a, AudioSink>>,
+        Option<Write<'a, AudioSink>>,
         WriteExpect<'a, R>,
+        Option<Read<'a, AudioMixer>>,
     );
 
-    fn run(&mut self, (storage, errors, sink, mut res): Self::SystemData) {
+    fn run(&mut self, (storage, errors, sink, mut res, mixer): Self::SystemData) {
         #[cfg(feature = "profiler")]
         profile_scope!("dj_system");
-        if let Some(ref sink) = sink {
+        if let Some(mut sink) = sink {
+            if let Some(ref mixer) = mixer {
+                sink.set_gain(mixer.gain(AudioBus::Music));
+            }
             if sink.empty() {
                 if let Some(source) = (&mut self.f)(&mut res).and_then(|h| storage.get(&h)) {
-                    errors.execute(|| sink.append(source));
+                    match mixer {
+                        Some(ref mixer) => errors.execute(|| sink.append_mixed(source, mixer)),
+                        None => errors.execute(|| sink.append(source)),
+                    }
                 }
             }
         }
@@ -0,0 +1,443 @@
+//! Configurations combined from several sources.
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt, fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, ConfigError};
+
+/// A configuration combined from layers, where each layer overrides the previous ones:
+///
+/// 1. The `Default` of the configuration structure.
+/// 2. A RON file, see `with_file`.
+/// 3. Environment variables, see `with_env`.
+/// 4. Command line arguments, see `with_args`.
+///
+/// Structures are merged field by field, so a file only needs to contain the fields it changes.
+/// Overrides from the environment and the command line are written in RON syntax, which means
+/// strings have to be quoted.
+///
+/// ```rust,ignore
+/// let display = LayeredConfig::<DisplayConfig>::new()
+///     .with_file("resources/display_config.ron")
+///     .with_env("GAME_DISPLAY")
+///     .with_args(std::env::args().skip(1))
+///     .load()?;
+/// ```
+///
+/// The `ConfigReloadSystem` of `amethyst_utils` reloads a configuration when its file changes.
+pub struct LayeredConfig<T> {
+    file: Option<PathBuf>,
+    env: Option<String>,
+    args: Vec<(String, String)>,
+    modified: Option<SystemTime>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> LayeredConfig<T>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Default,
+{
+    /// Creates a configuration only consisting of the defaults.
+    pub fn new() -> Self {
+        LayeredConfig {
+            file: None,
+            env: None,
+            args: Vec::new(),
+            modified: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the RON file at `path`, if it exists.
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Reads the environment variables starting with `prefix` and an underscore.
+    ///
+    /// The rest of the name is the lowercase field, where nested fields are separated by two
+    /// underscores: `GAME_DISPLAY__FULLSCREEN=true` sets `display.fullscreen` with the prefix
+    /// `GAME`.
+    pub fn with_env<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.env = Some(prefix.into());
+        self
+    }
+
+    /// Reads the command line arguments of the form `--field=value`, where nested fields are
+    /// separated by dots, for example `--display.fullscreen=true`.
+    ///
+    /// Other arguments are ignored, so all arguments of the process can be passed.
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        for arg in args {
+            let arg = match arg.into().into_string() {
+                Ok(arg) => arg,
+                Err(_) => continue,
+            };
+            if arg.starts_with("--") {
+                let arg = &arg[2..];
+                if let Some(index) = arg.find('=') {
+                    let (key, value) = arg.split_at(index);
+                    self.args.push((key.to_string(), value[1..].to_string()));
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns the file of the configuration.
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_ref().map(PathBuf::as_path)
+    }
+
+    /// Loads the configuration from all its layers.
+    ///
+    /// A missing file is skipped, while invalid files and overrides are errors.
+    pub fn load(&mut self) -> Result<T, ConfigError> {
+        let mut merged = RonStruct::parse(&ron::ser::to_string(&T::default())?)
+            .ok_or_else(|| ConfigError::Override("the defaults are not a structure".to_string()))?;
+
+        if let Some(ref path) = self.file {
+            self.modified = modified(path);
+            if path.exists() {
+                if path.extension().and_then(std::ffi::OsStr::to_str) != Some("ron") {
+                    return Err(ConfigError::Extension(path.to_path_buf()));
+                }
+                let content = fs::read_to_string(path)?;
+                let file = RonStruct::parse(&content).ok_or_else(|| {
+                    ConfigError::Override(format!("{} is not a structure", path.display()))
+                })?;
+                merged.merge(file);
+            }
+        }
+
+        if let Some(ref prefix) = self.env {
+            let prefix = format!("{}_", prefix);
+            let mut vars = env::vars()
+                .filter(|(key, _)| key.starts_with(&prefix))
+                .collect::<Vec<_>>();
+            vars.sort();
+            for (key, value) in vars {
+                let path = key[prefix.len()..].to_lowercase();
+                merged.set(&path.split("__").collect::<Vec<_>>(), &value)?;
+            }
+        }
+
+        for (key, value) in &self.args {
+            merged.set(&key.split('.').collect::<Vec<_>>(), value)?;
+        }
+
+        T::load_bytes(merged.to_string().as_bytes())
+    }
+
+    /// Loads the configuration again if its file was modified since the last `load`.
+    pub fn reload_if_changed(&mut self) -> Option<Result<T, ConfigError>> {
+        let path = self.file.as_ref()?;
+        if modified(path) == self.modified {
+            return None;
+        }
+        Some(self.load())
+    }
+}
+
+impl<T> Default for LayeredConfig<T>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Default,
+{
+    fn default() -> Self {
+        LayeredConfig::new()
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The fields of a RON structure, with their values kept as text.
+#[derive(Debug, PartialEq)]
+struct RonStruct {
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+impl RonStruct {
+    /// Splits a structure like `Name(a: 1, b: (c: 2))` into its fields, or returns `None` if the
+    /// text is another value.
+    fn parse(text: &str) -> Option<Self> {
+        let text = strip_comments(text);
+        let text = text.trim();
+        let open = text.find('(')?;
+        let name = text[..open].trim();
+        if !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return None;
+        }
+        let inner = &text[open + 1..];
+        let pieces = split_top_level(inner)?;
+        let mut fields = Vec::new();
+        for piece in pieces {
+            let piece = piece.trim();
+            if piece.is_empty() {
+                continue;
+            }
+            let colon = piece.find(':')?;
+            let key = piece[..colon].trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return None;
+            }
+            fields.push((key.to_string(), piece[colon + 1..].trim().to_string()));
+        }
+        Some(RonStruct {
+            name: name.to_string(),
+            fields,
+        })
+    }
+
+    /// Overrides the fields with the fields of `other`, merging nested structures.
+    ///
+    /// An unnamed structure takes the name of `other`, but if both are named differently, like
+    /// two variants of an enum, `other` replaces the structure.
+    fn merge(&mut self, other: RonStruct) {
+        if self.name.is_empty() {
+            self.name = other.name;
+        } else if !other.name.is_empty() && other.name != self.name {
+            *self = other;
+            return;
+        }
+        for (key, value) in other.fields {
+            match self.fields.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, existing)) => {
+                    match (RonStruct::parse(existing), RonStruct::parse(&value)) {
+                        (Some(mut base), Some(over)) => {
+                            base.merge(over);
+                            *existing = base.to_string();
+                        }
+                        _ => *existing = value,
+                    }
+                }
+                None => self.fields.push((key, value)),
+            }
+        }
+    }
+
+    /// Sets the field at `path` to `value`.
+    fn set(&mut self, path: &[&str], value: &str) -> Result<(), ConfigError> {
+        let key = match path.first() {
+            Some(key) if !key.is_empty() => *key,
+            _ => return Err(ConfigError::Override(format!("empty field for {}", value))),
+        };
+        let index = match self.fields.iter().position(|(k, _)| k == key) {
+            Some(index) => index,
+            None => {
+                self.fields.push((key.to_string(), "()".to_string()));
+                self.fields.len() - 1
+            }
+        };
+        if path.len() == 1 {
+            self.fields[index].1 = value.trim().to_string();
+            return Ok(());
+        }
+        let mut nested = RonStruct::parse(&self.fields[index].1)
+            .ok_or_else(|| ConfigError::Override(format!("{} is not a structure", key)))?;
+        nested.set(&path[1..], value)?;
+        self.fields[index].1 = nested.to_string();
+        Ok(())
+    }
+}
+
+impl fmt::Display for RonStruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = self
+            .fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>();
+        write!(f, "{}({})", self.name, fields.join(", "))
+    }
+}
+
+/// Splits the text up to the parenthesis closing an already opened one at its top level commas.
+///
+/// Returns `None` if the text after the closing parenthesis isn't empty or if it is missing.
+fn split_top_level(text: &str) -> Option<Vec<&str>> {
+    let mut pieces = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' | '\'' => skip_quoted(&mut chars, c),
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                pieces.push(&text[start..i]);
+                return if text[i + 1..].trim().is_empty() {
+                    Some(pieces)
+                } else {
+                    None
+                };
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                pieces.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn skip_quoted(chars: &mut std::str::CharIndices<'_>, quote: char) {
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == quote => return,
+            _ => {}
+        }
+    }
+}
+
+/// Removes `//` and `/* */` comments outside of strings.
+fn strip_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                result.push(c);
+                while let Some(q) = chars.next() {
+                    result.push(q);
+                    if q == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            result.push(escaped);
+                        }
+                    } else if q == c {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while let Some(&n) = chars.peek() {
+                    if n == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                while let Some(n) = chars.next() {
+                    if last == '*' && n == '/' {
+                        break;
+                    }
+                    last = n;
+                }
+                result.push(' ');
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_derive::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Window {
+        title: String,
+        size: (u32, u32),
+        fullscreen: bool,
+    }
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        window: Window,
+        volume: f32,
+    }
+
+    #[test]
+    fn merges_nested_structures() {
+        let mut base = RonStruct::parse("(window: (title: \"a\", size: (1, 2)), volume: 1.0)")
+            .expect("Failed to parse base");
+        let over = RonStruct::parse(
+            "Settings(\n  // muted\n  window: (title: \"b, (c)\"),\n  volume: 0.0,\n)",
+        )
+        .expect("Failed to parse override");
+        base.merge(over);
+        assert_eq!(
+            "Settings(window: (title: \"b, (c)\", size: (1, 2)), volume: 0.0)",
+            base.to_string()
+        );
+    }
+
+    #[test]
+    fn replaces_other_variants() {
+        let mut base = RonStruct::parse("(mode: Windowed(width: 1, height: 2), vsync: true)")
+            .expect("Failed to parse base");
+        let over =
+            RonStruct::parse("(mode: Fullscreen(monitor: 1))").expect("Failed to parse override");
+        base.merge(over);
+        assert_eq!(
+            "(mode: Fullscreen(monitor: 1), vsync: true)",
+            base.to_string()
+        );
+
+        let over =
+            RonStruct::parse("(mode: Fullscreen(monitor: 2))").expect("Failed to parse override");
+        base.merge(over);
+        assert_eq!(
+            "(mode: Fullscreen(monitor: 2), vsync: true)",
+            base.to_string()
+        );
+    }
+
+    #[test]
+    fn args_override_defaults() {
+        let settings = LayeredConfig::<Settings>::new()
+            .with_args(vec![
+                "game",
+                "--window.fullscreen=true",
+                "--window.title=\"Game\"",
+                "--volume=0.5",
+            ])
+            .load()
+            .expect("Failed to load config");
+        assert_eq!(
+            Settings {
+                window: Window {
+                    title: "Game".to_string(),
+                    size: (0, 0),
+                    fullscreen: true,
+                },
+                volume: 0.5,
+            },
+            settings
+        );
+    }
+
+    #[test]
+    fn override_of_value_is_error() {
+        let result = LayeredConfig::<Settings>::new()
+            .with_args(vec!["--volume.left=1.0"])
+            .load();
+        assert!(result.is_err());
+    }
+}
@@ -13,6 +13,10 @@ use log::error;
 use ron::{self, de::Error as DeError, ser::Error as SerError};
 use serde::{Deserialize, Serialize};
 
+pub use crate::layered::LayeredConfig;
+
+mod layered;
+
 /// Error related to anything that manages/creates configurations as well as
 /// "workspace"-related things.
 #[derive(Debug)]
@@ -25,6 +29,8 @@ pub enum ConfigError {
     Serializer(SerError),
     /// Related to the path of the file.
     Extension(PathBuf),
+    /// A layer of a `LayeredConfig` can't be merged into the configuration.
+    Override(String),
 }
 
 impl fmt::Display for ConfigError {
@@ -46,6 +52,7 @@ impl fmt::Display for ConfigError {
                     found,
                 )
             }
+            ConfigError::Override(ref msg) => write!(f, "Invalid config override: {}", msg),
         }
     }
 }
@@ -75,6 +82,7 @@ impl Error for ConfigError {
             ConfigError::Parser(_) => "Project parser error",
             ConfigError::Serializer(_) => "Project serializer error",
             ConfigError::Extension(_) => "Invalid extension or directory for a file",
+            ConfigError::Override(_) => "Invalid config override",
         }
     }
 
@@ -1,8 +1,7 @@
 use std::{hash::Hash, marker::PhantomData};
 
 use amethyst_core::{
-    bundle::SystemBundle,
-    ecs::prelude::DispatcherBuilder,
+    bundle::{BundleBuilder, SystemBundle},
     math::{one, RealField},
 };
 use amethyst_error::Error;
@@ -80,7 +79,7 @@ where
     B: Send + Sync + Hash + Eq + Clone + 'static,
     N: RealField,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             FlyMovementSystem::<A, B, N>::new(
                 self.speed,
@@ -143,7 +142,7 @@ where
     B: Send + Sync + Hash + Eq + Clone + 'static,
     N: RealField,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             ArcBallRotationSystem::<N>::default(),
             "arc_ball_rotation",
@@ -14,7 +14,7 @@ use amethyst_core::{
     transform::Transform,
 };
 use amethyst_input::{get_input_axis_simple, InputHandler};
-use amethyst_renderer::WindowMessages;
+use amethyst_renderer::{CursorMode, CursorState};
 
 /// The system that manages the fly movement.
 ///
@@ -241,6 +241,9 @@ impl<'a> System<'a> for MouseFocusUpdateSystem {
 
 /// System which hides the cursor when the window is focused.
 /// Requires the usage MouseFocusUpdateSystem at the same time.
+///
+/// The cursor is captured through the `CursorState`, which the `CursorSystem` of the
+/// `RenderBundle` applies to the window.
 pub struct CursorHideSystem {
     is_hidden: bool,
 }
@@ -254,37 +257,32 @@ impl CursorHideSystem {
 
 impl<'a> System<'a> for CursorHideSystem {
     type SystemData = (
-        Write<'a, WindowMessages>,
+        Write<'a, CursorState>,
         Read<'a, HideCursor>,
         Read<'a, WindowFocus>,
     );
 
-    fn run(&mut self, (mut msg, hide, focus): Self::SystemData) {
-        use amethyst_renderer::mouse::*;
+    fn run(&mut self, (mut cursor, hide, focus): Self::SystemData) {
         if focus.is_focused {
             if !self.is_hidden && hide.hide {
-                grab_cursor(&mut msg);
-                hide_cursor(&mut msg);
+                cursor.mode = CursorMode::Captured;
                 self.is_hidden = true;
             } else if self.is_hidden && !hide.hide {
-                release_cursor(&mut msg);
+                cursor.mode = CursorMode::Normal;
                 self.is_hidden = false;
             }
         } else if self.is_hidden {
-            release_cursor(&mut msg);
+            cursor.mode = CursorMode::Normal;
             self.is_hidden = false;
         }
     }
 
     fn setup(&mut self, res: &mut Resources) {
         use amethyst_core::ecs::prelude::SystemData;
-        use amethyst_renderer::mouse::*;
 
         Self::SystemData::setup(res);
 
-        let mut msg = res.fetch_mut::<WindowMessages>();
-        grab_cursor(&mut msg);
-        hide_cursor(&mut msg);
+        res.fetch_mut::<CursorState>().mode = CursorMode::Captured;
         self.is_hidden = true;
     }
 }
@@ -1,10 +1,108 @@
 //! Provides a trait for adding bundles of systems to a dispatcher.
 
-use crate::ecs::prelude::DispatcherBuilder;
+use crate::{
+    ecs::prelude::{DispatcherBuilder, System},
+    system_ext::SystemExt,
+};
 use amethyst_error::Error;
 
 /// A bundle of ECS components, resources and systems.
 pub trait SystemBundle<'a, 'b> {
     /// Build and add ECS resources, register components, add systems etc to the Application.
-    fn build(self, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error>;
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error>;
+}
+
+/// A bundle adding its systems directly to a `DispatcherBuilder`, the signature `SystemBundle`
+/// had before the `BundleBuilder`.
+///
+/// Every `DispatcherBundle` is a `SystemBundle`, so bundles written against the old signature
+/// keep working by implementing this trait instead. Their systems are not profiled.
+///
+/// ```rust,ignore
+/// impl<'a, 'b> DispatcherBundle<'a, 'b> for PongSystemsBundle {
+///     fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+///         builder.add(PaddleSystem, "paddle_system", &["input_system"]);
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait DispatcherBundle<'a, 'b> {
+    /// Build and add ECS resources, register components, add systems etc to the Application.
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error>;
+}
+
+impl<'a, 'b, B> SystemBundle<'a, 'b> for B
+where
+    B: DispatcherBundle<'a, 'b>,
+{
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        DispatcherBundle::build(self, builder.dispatcher)
+    }
+}
+
+/// Adds the systems of a `SystemBundle` to a `DispatcherBuilder`.
+///
+/// With profiling enabled, e.g. by `GameDataBuilder::with_profiling`, every system is wrapped
+/// with `SystemExt::profiled`, so the systems of bundles show up in the `SystemProfile` under
+/// their names like the systems added directly.
+///
+/// Bundles can also be built into a dispatcher of their own:
+///
+/// ```rust,ignore
+/// let mut dispatcher_builder = DispatcherBuilder::new();
+/// PongSystemsBundle::default().build(&mut BundleBuilder::new(&mut dispatcher_builder))?;
+/// ```
+pub struct BundleBuilder<'r, 'a, 'b> {
+    dispatcher: &'r mut DispatcherBuilder<'a, 'b>,
+    profiling: bool,
+}
+
+impl<'r, 'a, 'b> BundleBuilder<'r, 'a, 'b> {
+    /// Creates a builder adding the systems to the dispatcher as they are.
+    pub fn new(dispatcher: &'r mut DispatcherBuilder<'a, 'b>) -> Self {
+        BundleBuilder {
+            dispatcher,
+            profiling: false,
+        }
+    }
+
+    /// Creates a builder recording the run times of the systems in the `SystemProfile`.
+    pub fn profiled(dispatcher: &'r mut DispatcherBuilder<'a, 'b>) -> Self {
+        BundleBuilder {
+            dispatcher,
+            profiling: true,
+        }
+    }
+
+    /// Adds a system, see `DispatcherBuilder::add`.
+    pub fn add<S>(&mut self, system: S, name: &str, dependencies: &[&str])
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        if self.profiling {
+            self.dispatcher
+                .add(system.profiled(name), name, dependencies);
+        } else {
+            self.dispatcher.add(system, name, dependencies);
+        }
+    }
+
+    /// Adds a thread-local system, see `DispatcherBuilder::add_thread_local`.
+    ///
+    /// The name is only used for profiling, as thread-local systems can't be dependencies.
+    pub fn add_thread_local<S>(&mut self, system: S, name: &str)
+    where
+        for<'c> S: System<'c> + 'b,
+    {
+        if self.profiling {
+            self.dispatcher.add_thread_local(system.profiled(name));
+        } else {
+            self.dispatcher.add_thread_local(system);
+        }
+    }
+
+    /// Inserts a barrier, see `DispatcherBuilder::add_barrier`.
+    pub fn add_barrier(&mut self) {
+        self.dispatcher.add_barrier();
+    }
 }
@@ -0,0 +1,110 @@
+//! Deferred creation, modification and deletion of entities from any system.
+
+use shred_derive::SystemData;
+
+use crate::ecs::{world::LazyBuilder, Component, Entities, Entity, LazyUpdate, Read, World};
+
+/// `SystemData` for queueing changes to entities, which are applied at the next sync point.
+///
+/// Queued commands only need shared access to resources, so systems using them can run in
+/// parallel with any other system. They're applied in the order they were queued when
+/// `World::maintain` is called, which the `Application` does after each frame.
+///
+/// Spawned entities are created right away, so their `Entity` can already be used, e.g. as the
+/// `Parent` of other spawned entities. Their components are only added at the sync point.
+///
+/// ```
+/// # use amethyst_core::{ecs::prelude::*, Commands, WithNamed};
+/// struct EmitterSystem;
+///
+/// impl<'a> System<'a> for EmitterSystem {
+///     type SystemData = Commands<'a>;
+///
+///     fn run(&mut self, commands: Self::SystemData) {
+///         commands.spawn().named("Particle").build();
+///     }
+/// }
+/// ```
+#[derive(SystemData)]
+pub struct Commands<'a> {
+    entities: Entities<'a>,
+    lazy: Read<'a, LazyUpdate>,
+}
+
+impl<'a> Commands<'a> {
+    /// Creates a new entity and returns a builder queueing its components.
+    pub fn spawn(&self) -> LazyBuilder<'_> {
+        self.lazy.create_entity(&self.entities)
+    }
+
+    /// Queues inserting a component, replacing the previous one of the entity.
+    pub fn insert<C>(&self, entity: Entity, component: C)
+    where
+        C: Component + Send + Sync,
+    {
+        self.lazy.insert(entity, component);
+    }
+
+    /// Queues removing a component from the entity.
+    pub fn remove<C>(&self, entity: Entity)
+    where
+        C: Component + Send + Sync,
+    {
+        self.lazy.remove::<C>(entity);
+    }
+
+    /// Queues deleting the entity, after the commands queued before.
+    pub fn delete(&self, entity: Entity) {
+        self.lazy.exec_mut(move |world| {
+            // The entity may have been deleted already, which is fine.
+            let _ = world.delete_entity(entity);
+        });
+    }
+
+    /// Queues an arbitrary change to the world.
+    pub fn exec<F>(&self, f: F)
+    where
+        F: FnOnce(&mut World) + Send + Sync + 'static,
+    {
+        self.lazy.exec_mut(f);
+    }
+
+    /// Returns `true` if the entity is alive.
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ecs::prelude::{Builder, World},
+        named::{Named, WithNamed},
+    };
+
+    use super::Commands;
+
+    #[test]
+    fn commands_are_applied_on_maintain() {
+        let mut world = World::new();
+        world.register::<Named>();
+        let existing = world.create_entity().named("Existing").build();
+
+        let spawned = {
+            let commands = world.system_data::<Commands<'_>>();
+            let spawned = commands.spawn().named("Spawned").build();
+            commands.remove::<Named>(existing);
+            commands.insert(existing, Named::new("Renamed"));
+            commands.delete(spawned);
+            spawned
+        };
+        assert!(world.read_storage::<Named>().get(spawned).is_none());
+
+        world.maintain();
+        assert!(!world.is_alive(spawned));
+        assert_eq!(
+            "Renamed",
+            world.read_storage::<Named>().get(existing).unwrap().name
+        );
+    }
+}
@@ -1,4 +1,9 @@
-use crate::ecs::{Resources, SystemData, World};
+use std::marker::PhantomData;
+
+use shred::ResourceId;
+use shrev::{EventChannel, EventIterator, ReaderId};
+
+use crate::ecs::{Read, Resources, SystemData, World, WriteExpect};
 
 /// Read events generically
 pub trait EventReader<'a> {
@@ -21,16 +26,112 @@ pub trait EventReader<'a> {
     }
 }
 
+/// Resource holding the `ReaderId` used by `ReadEvents<T, S>`.
+pub struct ReadEventsReader<T: 'static, S: 'static> {
+    reader: ReaderId<T>,
+    marker: PhantomData<fn() -> S>,
+}
+
+/// `SystemData` for reading the events of an `EventChannel<T>`, which registers its reader in
+/// `setup` and keeps it between runs, so systems don't have to store a `ReaderId`.
+///
+/// The reader is stored in a resource for every marker type `S`, which should be the system
+/// reading the events. Only one instance of a system can use it, as they share the reader.
+///
+/// ```rust
+/// # use amethyst_core::{ecs::System, shrev::EventChannel, ReadEvents};
+/// struct PrintSystem;
+///
+/// impl<'a> System<'a> for PrintSystem {
+///     type SystemData = ReadEvents<'a, String, Self>;
+///
+///     fn run(&mut self, mut events: Self::SystemData) {
+///         for event in events.read() {
+///             println!("{}", event);
+///         }
+///     }
+/// }
+/// ```
+pub struct ReadEvents<'a, T: 'static, S: 'static> {
+    channel: Read<'a, EventChannel<T>>,
+    reader: WriteExpect<'a, ReadEventsReader<T, S>>,
+}
+
+impl<'a, T, S> ReadEvents<'a, T, S>
+where
+    T: Send + Sync + 'static,
+{
+    /// Returns the events written since the last read.
+    pub fn read(&mut self) -> EventIterator<'_, T> {
+        self.channel.read(&mut self.reader.reader)
+    }
+}
+
+impl<'a, T, S> SystemData<'a> for ReadEvents<'a, T, S>
+where
+    T: Send + Sync + 'static,
+    S: 'static,
+{
+    fn setup(res: &mut Resources) {
+        <Read<'a, EventChannel<T>> as SystemData<'a>>::setup(res);
+        if !res.has_value::<ReadEventsReader<T, S>>() {
+            let reader = res.fetch_mut::<EventChannel<T>>().register_reader();
+            res.insert(ReadEventsReader::<T, S> {
+                reader,
+                marker: PhantomData,
+            });
+        }
+    }
+
+    fn fetch(res: &'a Resources) -> Self {
+        ReadEvents {
+            channel: SystemData::fetch(res),
+            reader: SystemData::fetch(res),
+        }
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        vec![ResourceId::new::<EventChannel<T>>()]
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        vec![ResourceId::new::<ReadEventsReader<T, S>>()]
+    }
+}
+
+/// Handles the events of a system derived with `#[derive(EventSystem)]`, one at a time.
+pub trait EventHandler<'a> {
+    /// The type of the handled events.
+    type Event: Send + Sync + 'static;
+    /// Additional `SystemData` needed to handle the events.
+    type SystemData: SystemData<'a>;
+
+    /// Handle a single event.
+    fn handle(&mut self, event: &Self::Event, data: &mut Self::SystemData);
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::ecs::Read;
-    use shrev::{EventChannel, ReaderId};
-
     use super::*;
 
     #[derive(Clone)]
     pub struct TestEvent;
 
+    struct CountSystem;
+
+    #[test]
+    fn read_events_registers_reader() {
+        let mut world = World::new();
+        <ReadEvents<'_, TestEvent, CountSystem> as SystemData<'_>>::setup(&mut world.res);
+        world
+            .write_resource::<EventChannel<TestEvent>>()
+            .iter_write(vec![TestEvent, TestEvent]);
+
+        let mut events = world.system_data::<ReadEvents<'_, TestEvent, CountSystem>>();
+        assert_eq!(2, events.read().count());
+        assert_eq!(0, events.read().count());
+    }
+
     pub struct TestEventReader {
         reader: ReaderId<TestEvent>,
     }
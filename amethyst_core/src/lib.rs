@@ -28,24 +28,33 @@ extern crate thread_profiler;
 use std::sync::Arc;
 
 pub use crate::{
-    bundle::SystemBundle,
-    event::EventReader,
-    system_ext::{Pausable, SystemExt},
+    bundle::{BundleBuilder, DispatcherBundle, SystemBundle},
+    commands::Commands,
+    event::{EventHandler, EventReader, ReadEvents, ReadEventsReader},
+    profile::{SystemProfile, SystemTiming},
+    system_ext::{Pausable, Profiled, SystemExt},
     timing::*,
     transform::*,
 };
 
 pub use self::{
     axis::{Axis2, Axis3},
-    named::{Named, WithNamed},
+    named::{NameIndex, NameIndexSystem, Named, WithNamed},
+    spatial::{
+        Aabb, BoundingSphere, BoundsSystem, Frustum, Ray, SpatialGrid, SpatialGridSystem,
+        WorldBounds,
+    },
 };
 
 pub mod bundle;
 pub mod frame_limiter;
+pub mod profile;
+pub mod spatial;
 pub mod timing;
 pub mod transform;
 
 mod axis;
+mod commands;
 mod event;
 mod named;
 mod system_ext;
@@ -1,6 +1,15 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+};
 
-use crate::ecs::{world::LazyBuilder, Component, DenseVecStorage, EntityBuilder, WriteStorage};
+use hibitset::BitSet;
+
+use crate::ecs::{
+    world::{Index, LazyBuilder},
+    Component, ComponentEvent, DenseVecStorage, Entities, Entity, EntityBuilder, FlaggedStorage,
+    Join, ReadStorage, ReaderId, Resources, System, Write, WriteStorage,
+};
 use serde::{Deserialize, Serialize};
 
 /// A component that gives a name to an [`Entity`].
@@ -113,7 +122,7 @@ impl Named {
 }
 
 impl Component for Named {
-    type Storage = DenseVecStorage<Self>;
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
 }
 
 /// An easy way to name an `Entity` and give it a `Named` `Component`.
@@ -150,3 +159,200 @@ impl<'a> WithNamed for LazyBuilder<'a> {
         self
     }
 }
+
+/// Resource for looking up entities by their `Named` component, kept up to date by the
+/// `NameIndexSystem`.
+///
+/// Names don't have to be unique, e.g. glTF scenes often contain several nodes with the same
+/// name. All entities with a name are kept, in the order they were named.
+#[derive(Debug, Default)]
+pub struct NameIndex {
+    by_name: HashMap<Cow<'static, str>, Vec<Entity>>,
+    sorted: BTreeSet<Cow<'static, str>>,
+    names: HashMap<Index, (Entity, Cow<'static, str>)>,
+}
+
+impl NameIndex {
+    /// Returns the entity with the given name, or the first one named so if it isn't unique.
+    pub fn find(&self, name: &str) -> Option<Entity> {
+        self.by_name
+            .get(name)
+            .and_then(|entities| entities.first().cloned())
+    }
+
+    /// Returns all entities with the given name.
+    pub fn find_exact(&self, name: &str) -> &[Entity] {
+        self.by_name
+            .get(name)
+            .map(|entities| entities.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns all entities whose name starts with `prefix`, sorted by name.
+    pub fn find_all<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = Entity> + 'a {
+        self.sorted
+            .range::<str, _>(prefix..)
+            .take_while(move |name| name.starts_with(prefix))
+            .flat_map(move |name| self.find_exact(name).iter().cloned())
+    }
+
+    /// Returns `true` if exactly one entity has the given name.
+    pub fn is_unique(&self, name: &str) -> bool {
+        self.find_exact(name).len() == 1
+    }
+
+    /// Returns the name of the entity.
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.names
+            .get(&entity.id())
+            .filter(|(named, _)| *named == entity)
+            .map(|(_, name)| name.as_ref())
+    }
+
+    /// Returns the number of named entities.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no entities are named.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    fn insert(&mut self, entity: Entity, name: Cow<'static, str>) {
+        if self.names.get(&entity.id()) == Some(&(entity, name.clone())) {
+            return;
+        }
+        self.remove(entity.id());
+        self.by_name
+            .entry(name.clone())
+            .or_insert_with(Vec::new)
+            .push(entity);
+        self.sorted.insert(name.clone());
+        self.names.insert(entity.id(), (entity, name));
+    }
+
+    fn remove(&mut self, id: Index) {
+        if let Some((entity, name)) = self.names.remove(&id) {
+            let empty = match self.by_name.get_mut(&name) {
+                Some(entities) => {
+                    entities.retain(|e| *e != entity);
+                    entities.is_empty()
+                }
+                None => false,
+            };
+            if empty {
+                self.by_name.remove(&name);
+                self.sorted.remove(&name);
+            }
+        }
+    }
+}
+
+/// System keeping the `NameIndex` in sync with the `Named` components.
+///
+/// Changes are picked up from the flagged `Named` storage, so names changed during a frame can be
+/// found after the next run of this system.
+#[derive(Default)]
+pub struct NameIndexSystem {
+    reader: Option<ReaderId<ComponentEvent>>,
+    changed: BitSet,
+    removed: BitSet,
+}
+
+impl NameIndexSystem {
+    /// Creates a new `NameIndexSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for NameIndexSystem {
+    type SystemData = (Entities<'a>, ReadStorage<'a, Named>, Write<'a, NameIndex>);
+
+    fn run(&mut self, (entities, names, mut index): Self::SystemData) {
+        self.changed.clear();
+        self.removed.clear();
+        for event in names.channel().read(
+            self.reader
+                .as_mut()
+                .expect("`NameIndexSystem::setup` was not called"),
+        ) {
+            match event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.removed.remove(*id);
+                    self.changed.add(*id);
+                }
+                ComponentEvent::Removed(id) => {
+                    self.changed.remove(*id);
+                    self.removed.add(*id);
+                }
+            }
+        }
+
+        // Deleting an entity removes its components, so the removal events cover deletions too.
+        for id in &self.removed {
+            index.remove(id);
+        }
+        for (entity, named, _) in (&entities, &names, &self.changed).join() {
+            index.insert(entity, named.name.clone());
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use crate::ecs::prelude::SystemData;
+        Self::SystemData::setup(res);
+        let mut names = WriteStorage::<Named>::fetch(res);
+        self.reader = Some(names.register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::prelude::{Builder, RunNow, World};
+
+    use super::*;
+
+    #[test]
+    fn index_follows_names() {
+        let mut world = World::new();
+        let mut system = NameIndexSystem::new();
+        System::setup(&mut system, &mut world.res);
+
+        let wheel_left = world.create_entity().named("wheel_left").build();
+        let wheel_right = world.create_entity().named("wheel_right").build();
+        let body = world.create_entity().named("body").build();
+        let other_body = world.create_entity().named("body").build();
+        system.run_now(&world.res);
+
+        {
+            let index = world.read_resource::<NameIndex>();
+            assert_eq!(Some(wheel_left), index.find("wheel_left"));
+            assert_eq!(Some(body), index.find("body"));
+            assert!(!index.is_unique("body"));
+            assert_eq!(
+                vec![wheel_left, wheel_right],
+                index.find_all("wheel").collect::<Vec<_>>()
+            );
+            assert_eq!(None, index.find("wheel"));
+        }
+
+        world
+            .write_storage::<Named>()
+            .insert(wheel_right, Named::new("spare_wheel"))
+            .unwrap();
+        world.delete_entity(body).unwrap();
+        world.maintain();
+        system.run_now(&world.res);
+
+        let index = world.read_resource::<NameIndex>();
+        assert_eq!(
+            vec![wheel_left],
+            index.find_all("wheel").collect::<Vec<_>>()
+        );
+        assert_eq!(Some(wheel_right), index.find("spare_wheel"));
+        assert_eq!(Some(other_body), index.find("body"));
+        assert!(index.is_unique("body"));
+        assert_eq!(3, index.len());
+    }
+}
@@ -0,0 +1,139 @@
+//! Timing of systems, to find out which systems take up the frame time.
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use crate::timing::duration_to_secs;
+
+/// Weight of the last frame in the moving average of a `SystemTiming`.
+const AVERAGE_WEIGHT: f32 = 0.05;
+
+/// The measured run times of a single system.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SystemTiming {
+    /// Time taken by the last run, in seconds.
+    pub last: f32,
+    /// Moving average of the run time, in seconds.
+    pub average: f32,
+    /// The longest run time measured, in seconds.
+    pub max: f32,
+    /// Number of measured runs.
+    pub runs: u64,
+}
+
+impl SystemTiming {
+    fn record(&mut self, secs: f32) {
+        self.average = if self.runs == 0 {
+            secs
+        } else {
+            self.average + (secs - self.average) * AVERAGE_WEIGHT
+        };
+        self.last = secs;
+        self.max = self.max.max(secs);
+        self.runs += 1;
+    }
+}
+
+/// Resource collecting the run times of profiled systems.
+///
+/// Systems are profiled by wrapping them with `SystemExt::profiled`, which
+/// `GameDataBuilder::with_profiling` does for all systems added with `with` and `with_fixed`. The
+/// time of the whole dispatch is recorded by `GameData` when this resource exists.
+///
+/// Profiled systems running in parallel record their timings through a shared reference, so
+/// this resource doesn't prevent them from running in parallel.
+#[derive(Debug, Default)]
+pub struct SystemProfile {
+    systems: Mutex<HashMap<String, SystemTiming>>,
+    dispatch: SystemTiming,
+}
+
+impl SystemProfile {
+    /// Records a run of the system.
+    pub fn record(&self, name: &str, duration: Duration) {
+        let secs = duration_to_secs(duration);
+        let mut systems = self
+            .systems
+            .lock()
+            .expect("System profile lock was poisoned");
+        match systems.get_mut(name) {
+            Some(timing) => timing.record(secs),
+            None => {
+                let mut timing = SystemTiming::default();
+                timing.record(secs);
+                systems.insert(name.to_string(), timing);
+            }
+        }
+    }
+
+    /// Records the time taken by the whole dispatch of a frame.
+    pub fn record_dispatch(&mut self, duration: Duration) {
+        self.dispatch.record(duration_to_secs(duration));
+    }
+
+    /// Returns the timing of the whole dispatch.
+    pub fn dispatch(&self) -> SystemTiming {
+        self.dispatch
+    }
+
+    /// Returns the timing of the system.
+    pub fn get(&self, name: &str) -> Option<SystemTiming> {
+        self.systems
+            .lock()
+            .expect("System profile lock was poisoned")
+            .get(name)
+            .cloned()
+    }
+
+    /// Returns the `count` systems with the highest average run time, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<(String, SystemTiming)> {
+        let mut timings = self
+            .systems
+            .lock()
+            .expect("System profile lock was poisoned")
+            .iter()
+            .map(|(name, timing)| (name.clone(), *timing))
+            .collect::<Vec<_>>();
+        timings.sort_by(|a, b| {
+            b.1.average
+                .partial_cmp(&a.1.average)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        timings.truncate(count);
+        timings
+    }
+
+    /// Discards all measurements.
+    pub fn reset(&mut self) {
+        self.systems
+            .lock()
+            .expect("System profile lock was poisoned")
+            .clear();
+        self.dispatch = SystemTiming::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SystemProfile;
+
+    #[test]
+    fn slowest_systems() {
+        let profile = SystemProfile::default();
+        profile.record("input", Duration::from_millis(1));
+        profile.record("physics", Duration::from_millis(8));
+        profile.record("physics", Duration::from_millis(4));
+        profile.record("render", Duration::from_millis(5));
+
+        let slowest = profile.slowest(2);
+        assert_eq!(
+            vec!["physics", "render"],
+            slowest.iter().map(|s| s.0.as_str()).collect::<Vec<_>>()
+        );
+        let physics = profile.get("physics").unwrap();
+        assert_eq!(2, physics.runs);
+        assert!((physics.max - 0.008).abs() < 1e-6);
+        assert!((physics.last - 0.004).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,830 @@
+//! Spatial index for finding entities by their location, and the bounding volumes, rays and
+//! frustums to query it with, which also serve for custom culling or vision tests.
+
+use fnv::FnvHashMap;
+use hibitset::BitSet;
+use log::error;
+
+use crate::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, ReadStorage, Resources, System, Write,
+        WriteStorage,
+    },
+    math::{Matrix4, Vector3, Vector4, U1, U3},
+    transform::Transform,
+};
+
+/// Items spanning more cells than this are stored separately and tested by every query.
+const MAX_CELLS_PER_ITEM: i64 = 64;
+/// Cell coordinates are clamped to this magnitude, so that they fit an `i32`.
+const MAX_CELL: f32 = 1.0e9;
+
+/// A bounding sphere, in the local space of the entity's `Transform`.
+///
+/// Entities with this component and a `Transform<f32>` get their `WorldBounds` computed by the
+/// `BoundsSystem`, and are indexed by the `SpatialGridSystem`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoundingSphere {
+    /// The center of the sphere.
+    pub center: Vector3<f32>,
+    /// The radius of the sphere.
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Creates a bounding sphere centered on the origin of the entity.
+    pub fn new(radius: f32) -> Self {
+        BoundingSphere {
+            center: Vector3::zeros(),
+            radius,
+        }
+    }
+
+    /// Creates a bounding sphere with the given center.
+    pub fn with_center(center: Vector3<f32>, radius: f32) -> Self {
+        BoundingSphere { center, radius }
+    }
+
+    /// Transforms the sphere using a global matrix, returning the center and radius in world
+    /// space.
+    ///
+    /// The radius is scaled by the largest scale factor of the matrix.
+    pub fn transformed(&self, global_matrix: &Matrix4<f32>) -> (Vector3<f32>, f32) {
+        let center = global_matrix * Vector4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let scale = (0..3)
+            .map(|i| global_matrix.fixed_slice::<U3, U1>(0, i).norm())
+            .fold(0.0, f32::max);
+        (center.xyz(), self.radius * scale)
+    }
+}
+
+impl Default for BoundingSphere {
+    fn default() -> Self {
+        BoundingSphere::new(1.0)
+    }
+}
+
+impl Component for BoundingSphere {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// An axis aligned bounding box, in the local space of the entity's `Transform`.
+///
+/// Entities with this component or a `BoundingSphere` and a `Transform<f32>` get their
+/// `WorldBounds` computed by the `BoundsSystem`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    /// The minimum corner of the box.
+    pub min: Vector3<f32>,
+    /// The maximum corner of the box.
+    pub max: Vector3<f32>,
+}
+
+impl Aabb {
+    /// Creates a box from its corners.
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Self {
+        Aabb { min, max }
+    }
+
+    /// Creates the smallest box containing all points, `None` if there are no points.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Vector3<f32>>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut aabb = Aabb::new(first, first);
+        for point in points {
+            aabb.extend(&point);
+        }
+        Some(aabb)
+    }
+
+    /// Extends the box to contain the point.
+    pub fn extend(&mut self, point: &Vector3<f32>) {
+        self.min = self.min.inf(point);
+        self.max = self.max.sup(point);
+    }
+
+    /// Returns the center of the box.
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns half of the size of the box along each axis.
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Returns `true` if the point is inside of the box or on its surface.
+    pub fn contains_point(&self, point: &Vector3<f32>) -> bool {
+        (0..3).all(|axis| self.min[axis] <= point[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Returns `true` if the boxes overlap or touch.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// Returns the sphere enclosing the box.
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::with_center(self.center(), self.half_extents().norm())
+    }
+
+    /// Transforms the box using a global matrix, returning the world space box enclosing it.
+    pub fn transformed(&self, global_matrix: &Matrix4<f32>) -> Aabb {
+        let center = self.center();
+        let center = global_matrix * Vector4::new(center.x, center.y, center.z, 1.0);
+        let half_extents = global_matrix.fixed_slice::<U3, U3>(0, 0).abs() * self.half_extents();
+        Aabb::new(center.xyz() - half_extents, center.xyz() + half_extents)
+    }
+}
+
+impl Component for Aabb {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The bounds of an entity in world space, computed by the `BoundsSystem` from its `Aabb` or
+/// `BoundingSphere` and its global matrix.
+///
+/// This is what culling, picking and the `SpatialGrid` use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldBounds {
+    /// The box enclosing the entity.
+    pub aabb: Aabb,
+    /// The center of the sphere enclosing the entity.
+    pub center: Vector3<f32>,
+    /// The radius of the sphere enclosing the entity.
+    pub radius: f32,
+}
+
+impl Component for WorldBounds {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Updates the `WorldBounds` of all entities with an `Aabb` or a `BoundingSphere`.
+///
+/// Has to run after the global matrices are final, the `TransformBundle` adds it as
+/// "bounds_system".
+#[derive(Debug, Default)]
+pub struct BoundsSystem;
+
+impl<'a> System<'a> for BoundsSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Transform<f32>>,
+        ReadStorage<'a, Aabb>,
+        ReadStorage<'a, BoundingSphere>,
+        WriteStorage<'a, WorldBounds>,
+    );
+
+    fn run(&mut self, (entities, transforms, aabbs, spheres, mut bounds): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("bounds_system");
+
+        for (entity, transform, aabb, sphere) in
+            (&*entities, &transforms, aabbs.maybe(), spheres.maybe()).join()
+        {
+            let global_matrix = transform.global_matrix();
+            let sphere = sphere.cloned().or_else(|| aabb.map(Aabb::bounding_sphere));
+            let (center, radius) = match sphere {
+                Some(sphere) => sphere.transformed(global_matrix),
+                None => {
+                    bounds.remove(entity);
+                    continue;
+                }
+            };
+            let aabb = match aabb {
+                Some(aabb) => aabb.transformed(global_matrix),
+                None => Aabb::new(
+                    center - Vector3::repeat(radius),
+                    center + Vector3::repeat(radius),
+                ),
+            };
+            let world = WorldBounds {
+                aabb,
+                center,
+                radius,
+            };
+            if let Err(err) = bounds.insert(entity, world) {
+                error!("Failed to insert world bounds: {:?}", err);
+            }
+        }
+    }
+}
+
+/// A ray used for spatial queries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ray {
+    /// The starting point of the ray.
+    pub origin: Vector3<f32>,
+    /// The normalized direction of the ray.
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    /// Creates a new ray, normalizing the direction.
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Self {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// Returns the point at the given distance along the ray.
+    pub fn at(&self, distance: f32) -> Vector3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// Returns the distance along the ray at which it enters the sphere, or `0.0` if the origin
+    /// lies inside of it.
+    pub fn intersect_sphere(&self, center: &Vector3<f32>, radius: f32) -> Option<f32> {
+        let m = self.origin - center;
+        let b = m.dot(&self.direction);
+        let c = m.dot(&m) - radius * radius;
+        if c > 0.0 && b > 0.0 {
+            return None;
+        }
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        Some((-b - discriminant.sqrt()).max(0.0))
+    }
+
+    /// Returns the distance along the ray at which it enters the box, or `0.0` if the origin lies
+    /// inside of it.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = std::f32::INFINITY;
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+            if direction == 0.0 {
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let a = (aabb.min[axis] - origin) / direction;
+            let b = (aabb.max[axis] - origin) / direction;
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
+
+/// A view frustum made of six planes pointing inwards.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frustum {
+    /// The planes as `(normal.x, normal.y, normal.z, distance)`, with normalized normals, in the
+    /// order left, right, bottom, top, near and far.
+    pub planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum from a view projection matrix, i.e. `projection * view`.
+    ///
+    /// Assumes the OpenGL clip space, with depth ranging from -1 to 1.
+    pub fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+        let row = |i: usize| matrix.row(i).transpose();
+        let normalize = |plane: Vector4<f32>| plane / plane.xyz().norm();
+        Frustum {
+            planes: [
+                normalize(row(3) + row(0)),
+                normalize(row(3) - row(0)),
+                normalize(row(3) + row(1)),
+                normalize(row(3) - row(1)),
+                normalize(row(3) + row(2)),
+                normalize(row(3) - row(2)),
+            ],
+        }
+    }
+
+    /// Extracts the frustum of a camera from its projection matrix and the global matrix of
+    /// its `Transform`.
+    pub fn from_camera(projection: &Matrix4<f32>, global_matrix: &Matrix4<f32>) -> Self {
+        let view = global_matrix
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        Frustum::from_matrix(&(projection * view))
+    }
+
+    /// Returns the signed distance of the point to each plane, positive on the inner side.
+    pub fn distances(&self, point: &Vector3<f32>) -> [f32; 6] {
+        let mut distances = [0.0; 6];
+        for (distance, plane) in distances.iter_mut().zip(self.planes.iter()) {
+            *distance = plane.xyz().dot(point) + plane.w;
+        }
+        distances
+    }
+
+    /// Returns `true` if the point is inside of the frustum.
+    pub fn contains_point(&self, point: &Vector3<f32>) -> bool {
+        self.distances(point)
+            .iter()
+            .all(|&distance| distance >= 0.0)
+    }
+
+    /// Returns `true` if the sphere is at least partially inside of the frustum.
+    pub fn contains_sphere(&self, center: &Vector3<f32>, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(center) + plane.w >= -radius)
+    }
+
+    /// Returns `true` if the box is at least partially inside of the frustum.
+    ///
+    /// Like the sphere test, large boxes near the corners of the frustum may pass even though
+    /// they are outside of it.
+    pub fn contains_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // The corner furthest along the normal of the plane.
+            let corner = Vector3::new(
+                if plane.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            plane.xyz().dot(&corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// A uniform grid over the bounding spheres of entities, rebuilt every frame by the
+/// `SpatialGridSystem`.
+///
+/// The cell size should be in the order of the size of typical entities; entities much larger
+/// than a cell are tested by every query.
+#[derive(Debug)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    items: Vec<(Entity, Vector3<f32>, f32)>,
+    cells: FnvHashMap<[i32; 3], Vec<usize>>,
+    large: Vec<usize>,
+    bounds: Option<([i32; 3], [i32; 3])>,
+}
+
+impl SpatialGrid {
+    /// Creates an empty grid with the given cell size.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "Cell size of a `SpatialGrid` must be positive"
+        );
+        SpatialGrid {
+            cell_size,
+            items: Vec::new(),
+            cells: FnvHashMap::default(),
+            large: Vec::new(),
+            bounds: None,
+        }
+    }
+
+    /// The size of the grid's cells.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// The number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if no entities are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Removes all entities from the grid.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.cells.clear();
+        self.large.clear();
+        self.bounds = None;
+    }
+
+    /// Adds an entity with a bounding sphere in world space.
+    pub fn insert(&mut self, entity: Entity, center: Vector3<f32>, radius: f32) {
+        let index = self.items.len();
+        self.items.push((entity, center, radius));
+
+        let min = self.cell(&center.add_scalar(-radius));
+        let max = self.cell(&center.add_scalar(radius));
+        let count = (0..3)
+            .map(|i| i64::from(max[i]) - i64::from(min[i]) + 1)
+            .fold(1, i64::saturating_mul);
+        if !radius.is_finite() || count > MAX_CELLS_PER_ITEM {
+            self.large.push(index);
+            return;
+        }
+
+        for x in min[0]..=max[0] {
+            for y in min[1]..=max[1] {
+                for z in min[2]..=max[2] {
+                    self.cells.entry([x, y, z]).or_default().push(index);
+                }
+            }
+        }
+        self.bounds = Some(match self.bounds {
+            Some((lower, upper)) => (
+                [
+                    lower[0].min(min[0]),
+                    lower[1].min(min[1]),
+                    lower[2].min(min[2]),
+                ],
+                [
+                    upper[0].max(max[0]),
+                    upper[1].max(max[1]),
+                    upper[2].max(max[2]),
+                ],
+            ),
+            None => (min, max),
+        });
+    }
+
+    /// Returns all entities whose bounding sphere intersects the given sphere.
+    pub fn query_sphere(&self, center: &Vector3<f32>, radius: f32) -> Vec<Entity> {
+        let mut seen = BitSet::new();
+        let mut result = Vec::new();
+        let mut test = |index: usize| {
+            let (entity, ref item_center, item_radius) = self.items[index];
+            if !seen.add(index as u32) && (item_center - center).norm() <= item_radius + radius {
+                result.push(entity);
+            }
+        };
+
+        self.large.iter().for_each(|i| test(*i));
+        let (lower, upper) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return result,
+        };
+        // Only the cells with items are visited, however large the sphere is.
+        let mut min = self.cell(&center.add_scalar(-radius));
+        let mut max = self.cell(&center.add_scalar(radius));
+        for i in 0..3 {
+            min[i] = min[i].max(lower[i]);
+            max[i] = max[i].min(upper[i]);
+        }
+        for x in min[0]..=max[0] {
+            for y in min[1]..=max[1] {
+                for z in min[2]..=max[2] {
+                    if let Some(items) = self.cells.get(&[x, y, z]) {
+                        items.iter().for_each(|i| test(*i));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns all entities hit by the ray within `max_distance`, with the distance at which the
+    /// ray enters their bounding sphere, closest first.
+    ///
+    /// The direction of the ray is normalized, a ray without a finite direction or origin hits
+    /// nothing.
+    pub fn query_ray(&self, ray: &Ray, max_distance: f32) -> Vec<(Entity, f32)> {
+        let length = ray.direction.norm();
+        if !length.is_finite() || length == 0.0 || !ray.origin.iter().all(|v| v.is_finite()) {
+            return Vec::new();
+        }
+        let ray = &Ray {
+            origin: ray.origin,
+            direction: ray.direction / length,
+        };
+
+        let mut seen = BitSet::new();
+        let mut result = Vec::new();
+        let mut test = |index: usize| {
+            let (entity, ref center, radius) = self.items[index];
+            if !seen.add(index as u32) {
+                if let Some(distance) = ray.intersect_sphere(center, radius) {
+                    if distance <= max_distance {
+                        result.push((entity, distance));
+                    }
+                }
+            }
+        };
+
+        self.large.iter().for_each(|i| test(*i));
+        if let Some((lower, upper)) = self.bounds {
+            // Walk the cells along the ray (Amanatides & Woo)
+            let mut cell = self.cell(&ray.origin);
+            let mut step = [0; 3];
+            let mut t_max = [std::f32::INFINITY; 3];
+            let mut t_delta = [std::f32::INFINITY; 3];
+            for axis in 0..3 {
+                let direction = ray.direction[axis];
+                if direction > 0.0 {
+                    step[axis] = 1;
+                    let boundary = (cell[axis] + 1) as f32 * self.cell_size;
+                    t_max[axis] = (boundary - ray.origin[axis]) / direction;
+                    t_delta[axis] = self.cell_size / direction;
+                } else if direction < 0.0 {
+                    step[axis] = -1;
+                    let boundary = cell[axis] as f32 * self.cell_size;
+                    t_max[axis] = (boundary - ray.origin[axis]) / direction;
+                    t_delta[axis] = -self.cell_size / direction;
+                }
+            }
+
+            // Every step moves one axis towards the far side of the bounds, which caps the walk
+            // even without a `max_distance`.
+            let mut steps = (0..3)
+                .map(|axis| {
+                    let cell = i64::from(cell[axis]);
+                    let far = (cell - i64::from(lower[axis])).max(i64::from(upper[axis]) - cell);
+                    far + 1
+                })
+                .sum::<i64>();
+            let mut distance = 0.0;
+            while distance <= max_distance && steps > 0 {
+                steps -= 1;
+                // we left the occupied cells and can't come back
+                if (0..3).any(|axis| {
+                    (cell[axis] < lower[axis] && step[axis] <= 0)
+                        || (cell[axis] > upper[axis] && step[axis] >= 0)
+                }) {
+                    break;
+                }
+                if let Some(items) = self.cells.get(&cell) {
+                    items.iter().for_each(|i| test(*i));
+                }
+
+                let axis = if t_max[0] < t_max[1] {
+                    if t_max[0] < t_max[2] {
+                        0
+                    } else {
+                        2
+                    }
+                } else if t_max[1] < t_max[2] {
+                    1
+                } else {
+                    2
+                };
+                distance = t_max[axis];
+                cell[axis] += step[axis];
+                t_max[axis] += t_delta[axis];
+            }
+        }
+
+        result.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        result
+    }
+
+    /// Returns all entities whose bounding sphere is at least partially inside of the frustum.
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<Entity> {
+        let mut seen = BitSet::new();
+        let mut result = Vec::new();
+        let mut test = |index: usize| {
+            let (entity, ref center, radius) = self.items[index];
+            if !seen.add(index as u32) && frustum.contains_sphere(center, radius) {
+                result.push(entity);
+            }
+        };
+
+        self.large.iter().for_each(|i| test(*i));
+        let half_size = self.cell_size / 2.0;
+        let cell_radius = half_size * 3.0f32.sqrt();
+        for (cell, items) in &self.cells {
+            let center = Vector3::new(
+                cell[0] as f32 * self.cell_size + half_size,
+                cell[1] as f32 * self.cell_size + half_size,
+                cell[2] as f32 * self.cell_size + half_size,
+            );
+            if frustum.contains_sphere(&center, cell_radius) {
+                items.iter().for_each(|i| test(*i));
+            }
+        }
+        result
+    }
+
+    fn cell(&self, position: &Vector3<f32>) -> [i32; 3] {
+        // `max` and `min` also turn NaN into a bound, so the casts are always in range.
+        let cell = |value: f32| {
+            (value / self.cell_size)
+                .floor()
+                .max(-MAX_CELL)
+                .min(MAX_CELL) as i32
+        };
+        [cell(position.x), cell(position.y), cell(position.z)]
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(10.0)
+    }
+}
+
+/// Rebuilds the `SpatialGrid` resource from the `WorldBounds` of all entities.
+///
+/// Should run after the `BoundsSystem`, e.g. with a dependency on "bounds_system".
+#[derive(Debug)]
+pub struct SpatialGridSystem {
+    cell_size: f32,
+}
+
+impl SpatialGridSystem {
+    /// Creates a new system, using a grid with the given cell size.
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGridSystem { cell_size }
+    }
+}
+
+impl Default for SpatialGridSystem {
+    fn default() -> Self {
+        SpatialGridSystem::new(SpatialGrid::default().cell_size())
+    }
+}
+
+impl<'a> System<'a> for SpatialGridSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, WorldBounds>,
+        Write<'a, SpatialGrid>,
+    );
+
+    fn run(&mut self, (entities, bounds, mut grid): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("spatial_grid_system");
+
+        grid.clear();
+        for (entity, bounds) in (&*entities, &bounds).join() {
+            grid.insert(entity, bounds.center, bounds.radius);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use crate::ecs::prelude::SystemData;
+        Self::SystemData::setup(res);
+        res.insert(SpatialGrid::new(self.cell_size));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::prelude::{Builder, RunNow, World};
+
+    use super::*;
+
+    fn grid() -> (SpatialGrid, Vec<Entity>) {
+        let mut world = World::new();
+        let entities = (0..3)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(entities[0], Vector3::new(0.5, 0.5, 0.5), 0.5);
+        grid.insert(entities[1], Vector3::new(5.5, 0.5, 0.5), 0.5);
+        grid.insert(entities[2], Vector3::new(0.0, 0.0, 0.0), 100.0);
+        (grid, entities)
+    }
+
+    #[test]
+    fn sphere_query() {
+        let (grid, entities) = grid();
+        let mut found = grid.query_sphere(&Vector3::new(5.0, 0.5, 0.5), 0.2);
+        found.sort();
+        assert_eq!(vec![entities[1], entities[2]], found);
+    }
+
+    #[test]
+    fn huge_spheres() {
+        let (mut grid, entities) = grid();
+        grid.insert(entities[0], Vector3::zeros(), std::f32::INFINITY);
+        grid.insert(entities[1], Vector3::zeros(), std::f32::NAN);
+        grid.insert(entities[2], Vector3::zeros(), 1.0e30);
+        assert_eq!(6, grid.len());
+
+        let mut found = grid.query_sphere(&Vector3::zeros(), 1.0e30);
+        found.sort();
+        found.dedup();
+        assert_eq!(entities, found);
+    }
+
+    #[test]
+    fn ray_query() {
+        let (grid, entities) = grid();
+        let ray = Ray::new(Vector3::new(-200.0, 0.5, 0.5), Vector3::new(1.0, 0.0, 0.0));
+        let hits = grid.query_ray(&ray, 1000.0);
+        let hit_entities = hits.iter().map(|h| h.0).collect::<Vec<_>>();
+        assert_eq!(vec![entities[2], entities[0], entities[1]], hit_entities);
+        assert!((hits[1].1 - 200.0).abs() < 1e-3);
+        assert!(grid.query_ray(&ray, 150.0).len() == 1);
+    }
+
+    #[test]
+    fn ray_query_without_direction() {
+        let (grid, _) = grid();
+        let origin = Vector3::new(0.5, 0.5, 0.5);
+        let zero = Ray {
+            origin,
+            direction: Vector3::zeros(),
+        };
+        assert!(grid.query_ray(&zero, std::f32::INFINITY).is_empty());
+        let nan = Ray {
+            origin,
+            direction: Vector3::new(std::f32::NAN, 0.0, 0.0),
+        };
+        assert!(grid.query_ray(&nan, std::f32::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn ray_query_normalizes_and_ends_without_max_distance() {
+        let (grid, entities) = grid();
+        let ray = Ray {
+            origin: Vector3::new(-200.0, 0.5, 0.5),
+            direction: Vector3::new(10.0, 0.0, 0.0),
+        };
+        let hits = grid.query_ray(&ray, std::f32::INFINITY);
+        assert_eq!(3, hits.len());
+        assert_eq!(entities[0], hits[1].0);
+        assert!((hits[1].1 - 200.0).abs() < 1e-3);
+
+        let away = Ray::new(Vector3::new(1000.0, 0.5, 0.5), Vector3::new(1.0, 1.0, 0.0));
+        assert!(grid.query_ray(&away, std::f32::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn ray_aabb_intersection() {
+        let aabb = Aabb::new(Vector3::new(1.0, -1.0, -1.0), Vector3::new(3.0, 1.0, 1.0));
+        let ray = Ray::new(Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(ray.intersect_aabb(&aabb), Some(2.0));
+        let inside = Ray::new(Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(inside.intersect_aabb(&aabb), Some(0.0));
+        let away = Ray::new(Vector3::new(-1.0, 0.0, 0.0), Vector3::new(-1.0, 0.0, 0.0));
+        assert_eq!(away.intersect_aabb(&aabb), None);
+        let above = Ray::new(Vector3::new(-1.0, 2.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(above.intersect_aabb(&aabb), None);
+    }
+
+    #[test]
+    fn frustum_tests() {
+        // An orthographic box from -1 to 1 on every axis.
+        let frustum = Frustum::from_matrix(&Matrix4::identity());
+        assert!(frustum.contains_point(&Vector3::new(0.5, -0.5, 0.0)));
+        assert!(!frustum.contains_point(&Vector3::new(1.5, 0.0, 0.0)));
+        assert!((frustum.distances(&Vector3::zeros())[0] - 1.0).abs() < 1e-5);
+        assert!(frustum.contains_aabb(&Aabb::new(
+            Vector3::new(0.5, 0.5, 0.5),
+            Vector3::new(2.0, 2.0, 2.0),
+        )));
+        assert!(!frustum.contains_aabb(&Aabb::new(
+            Vector3::new(1.5, -0.5, -0.5),
+            Vector3::new(2.0, 0.5, 0.5),
+        )));
+
+        let mut camera = Transform::<f32>::default();
+        camera.set_translation_xyz(10.0, 0.0, 0.0);
+        let frustum = Frustum::from_camera(&Matrix4::identity(), &camera.matrix());
+        assert!(frustum.contains_point(&Vector3::new(10.5, 0.0, 0.0)));
+        assert!(!frustum.contains_point(&Vector3::zeros()));
+    }
+
+    #[test]
+    fn world_bounds() {
+        let mut world = World::new();
+        let mut system = BoundsSystem;
+        System::setup(&mut system, &mut world.res);
+
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(10.0, 0.0, 0.0);
+        transform.set_rotation_euler(0.0, 0.0, std::f32::consts::FRAC_PI_2);
+        transform.global_matrix = transform.matrix();
+        let entity = world
+            .create_entity()
+            .with(transform)
+            .with(Aabb::new(
+                Vector3::new(0.0, -1.0, -1.0),
+                Vector3::new(2.0, 1.0, 1.0),
+            ))
+            .build();
+        system.run_now(&world.res);
+
+        let bounds = world
+            .read_storage::<WorldBounds>()
+            .get(entity)
+            .unwrap()
+            .clone();
+        assert!((bounds.aabb.min - Vector3::new(9.0, 0.0, -1.0)).norm() < 1e-5);
+        assert!((bounds.aabb.max - Vector3::new(11.0, 2.0, 1.0)).norm() < 1e-5);
+        assert!((bounds.center - Vector3::new(10.0, 1.0, 0.0)).norm() < 1e-5);
+        assert!((bounds.radius - 3.0f32.sqrt()).abs() < 1e-5);
+    }
+}
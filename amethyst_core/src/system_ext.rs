@@ -3,7 +3,12 @@
 //! This modules contains an extension trait for the System trait which adds useful transformation
 //! functions.
 
-use crate::ecs::prelude::{Read, System};
+use std::time::Instant;
+
+use crate::{
+    ecs::prelude::{Read, Resources, System},
+    profile::SystemProfile,
+};
 use shred::{RunningTime, SystemData};
 
 /// Extension functionality associated systems.
@@ -75,6 +80,12 @@ pub trait SystemExt {
     where
         Self: Sized,
         V: Send + Sync + Default + PartialEq;
+
+    /// Measures the run time of the system, recording it under `name` in the `SystemProfile`
+    /// resource.
+    fn profiled(self, name: &str) -> Profiled<Self>
+    where
+        Self: Sized;
 }
 
 impl<'s, S> SystemExt for S
@@ -91,6 +102,16 @@ where
             value,
         }
     }
+
+    fn profiled(self, name: &str) -> Profiled<Self>
+    where
+        Self: Sized,
+    {
+        Profiled {
+            system: self,
+            name: name.to_string(),
+        }
+    }
 }
 
 /// A system that is enabled when `V` has a specific value.
@@ -123,3 +144,36 @@ where
         self.system.running_time()
     }
 }
+
+/// A system whose run time is recorded in the `SystemProfile`.
+///
+/// This is created using the [`SystemExt::profiled`] method.
+///
+/// [`SystemExt::profiled`]: trait.SystemExt.html#tymethod.profiled
+pub struct Profiled<S> {
+    system: S,
+    name: String,
+}
+
+impl<'s, S> System<'s> for Profiled<S>
+where
+    S::SystemData: SystemData<'s>,
+    S: System<'s>,
+{
+    type SystemData = (Read<'s, SystemProfile>, S::SystemData);
+
+    fn run(&mut self, (profile, data): Self::SystemData) {
+        let start = Instant::now();
+        self.system.run(data);
+        profile.record(&self.name, start.elapsed());
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        <Read<'s, SystemProfile> as SystemData<'s>>::setup(res);
+        self.system.setup(res);
+    }
+}
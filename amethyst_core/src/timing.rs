@@ -2,6 +2,20 @@
 
 use std::time::{Duration, Instant};
 
+/// Time channels which can be scaled independently of each other.
+///
+/// The `Gameplay` channel is the main game time returned by `Time::delta_seconds`. Pausing stops
+/// the `Gameplay` and `Audio` channels, while `Ui` time keeps running so menus stay responsive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimeChannel {
+    /// Game time, scaled by `Time::time_scale`.
+    Gameplay,
+    /// Time for animating the user interface.
+    Ui,
+    /// Time for playing sounds and music.
+    Audio,
+}
+
 /// Frame timing values.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Time {
@@ -19,6 +33,8 @@ pub struct Time {
     fixed_time: Duration,
     /// The total number of frames that have been played in this session.
     frame_number: u64,
+    /// The total number of fixed updates that have been performed in this session.
+    fixed_frame_number: u64,
     ///Time elapsed since game start, ignoring the speed multipler.
     absolute_real_time: Duration,
     ///Time elapsed since game start, taking the speed multiplier into account.
@@ -27,8 +43,20 @@ pub struct Time {
     time_scale: f32,
     /// Fixed timestep accumulator.
     fixed_time_accumulator: f32,
+    /// The maximum number of fixed updates per frame.
+    max_fixed_steps: Option<u32>,
+    /// The number of fixed updates performed in the current frame.
+    fixed_steps: u32,
     /// Fixed update interpolation alpha
     interpolation_alpha: f32,
+    /// Whether the game time is paused.
+    paused: bool,
+    /// Number of frames to run while paused.
+    pending_steps: u32,
+    /// Time multipliers of the `Ui` and `Audio` channels.
+    channel_scales: [f32; 2],
+    /// Time elapsed since the last frame in seconds in the `Ui` and `Audio` channels.
+    channel_deltas: [f32; 2],
 }
 
 impl Time {
@@ -92,24 +120,49 @@ impl Time {
         self.time_scale
     }
 
+    /// Gets the total number of fixed updates that have been performed in this session.
+    pub fn fixed_frame_number(&self) -> u64 {
+        self.fixed_frame_number
+    }
+
+    /// Gets the maximum number of fixed updates per frame, `None` if unlimited.
+    pub fn max_fixed_steps(&self) -> Option<u32> {
+        self.max_fixed_steps
+    }
+
     /// Gets the current interpolation alpha factor.
     pub fn interpolation_alpha(&self) -> f32 {
         self.interpolation_alpha
     }
 
+    /// Returns `true` if the game time is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Gets the time difference between frames in seconds in the given channel.
+    pub fn channel_delta_seconds(&self, channel: TimeChannel) -> f32 {
+        match channel_index(channel) {
+            Some(index) => self.channel_deltas[index],
+            None => self.delta_seconds,
+        }
+    }
+
+    /// Gets the time speed multiplier of the channel.
+    pub fn channel_scale(&self, channel: TimeChannel) -> f32 {
+        match channel_index(channel) {
+            Some(index) => self.channel_scales[index],
+            None => self.time_scale,
+        }
+    }
+
     /// Gets the total number of frames that have been played in this session.
     /// Sets both `delta_seconds` and `delta_time` based on the seconds given.
     ///
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn set_delta_seconds(&mut self, secs: f32) {
-        self.delta_seconds = secs * self.time_scale;
-        self.delta_time = secs_to_duration(secs * self.time_scale);
-        self.delta_real_seconds = secs;
-        self.delta_real_time = secs_to_duration(secs);
-
-        self.absolute_time += self.delta_time;
-        self.absolute_real_time += self.delta_real_time;
+        self.advance(secs, secs_to_duration(secs));
     }
 
     /// Sets both `delta_time` and `delta_seconds` based on the duration given.
@@ -117,10 +170,30 @@ impl Time {
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn set_delta_time(&mut self, time: Duration) {
-        self.delta_seconds = duration_to_secs(time) * self.time_scale;
-        self.delta_time = secs_to_duration(duration_to_secs(time) * self.time_scale);
-        self.delta_real_seconds = duration_to_secs(time);
-        self.delta_real_time = time;
+        self.advance(duration_to_secs(time), time);
+    }
+
+    fn advance(&mut self, real_secs: f32, real_time: Duration) {
+        let stepping = self.paused && self.pending_steps > 0;
+        if stepping {
+            self.pending_steps -= 1;
+        }
+        // A stepped frame advances by exactly one fixed update.
+        let game_secs = match (self.paused, stepping) {
+            (false, _) => real_secs,
+            (true, true) => self.fixed_seconds,
+            (true, false) => 0.0,
+        };
+
+        self.delta_seconds = game_secs * self.time_scale;
+        self.delta_time = secs_to_duration(game_secs * self.time_scale);
+        self.delta_real_seconds = real_secs;
+        self.delta_real_time = real_time;
+        // UI time keeps running while paused, audio time stops with the game.
+        self.channel_deltas = [
+            real_secs * self.channel_scales[0],
+            game_secs * self.channel_scales[1],
+        ];
 
         self.absolute_time += self.delta_time;
         self.absolute_real_time += self.delta_real_time;
@@ -158,12 +231,60 @@ impl Time {
         self.time_scale = multiplier;
     }
 
+    /// Sets the time multiplier of the channel. For the `Gameplay` channel this is the same as
+    /// `set_time_scale`.
+    ///
+    /// ## Panics
+    /// This will panic if multiplier is NaN, Infinity, or less than 0.
+    pub fn set_channel_scale(&mut self, channel: TimeChannel, multiplier: f32) {
+        use std::f32::INFINITY;
+        assert!(multiplier >= 0.0);
+        assert!(multiplier != INFINITY);
+        match channel_index(channel) {
+            Some(index) => self.channel_scales[index] = multiplier,
+            None => self.time_scale = multiplier,
+        }
+    }
+
+    /// Pauses the game time, starting with the next frame.
+    ///
+    /// While paused, `delta_seconds` is zero and no fixed updates are performed.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes the game time, starting with the next frame. Discards pending frame steps.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending_steps = 0;
+    }
+
+    /// Runs a single frame while paused, e.g. for debugging.
+    ///
+    /// The stepped frame advances the game time by exactly one fixed time step, independent of
+    /// the real frame time. Does nothing if the game isn't paused.
+    pub fn step_frame(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Limits the number of fixed updates per frame.
+    ///
+    /// If a frame takes so long that more fixed updates would be needed to catch up, the
+    /// remaining time is dropped and the simulation runs slower than real time, instead of
+    /// spending ever more time on catching up.
+    pub fn set_max_fixed_steps(&mut self, max_fixed_steps: Option<u32>) {
+        self.max_fixed_steps = max_fixed_steps;
+    }
+
     /// Restarts the internal fixed update accumulator to the desired fixed update delta time.
     ///
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn start_fixed_update(&mut self) {
         self.fixed_time_accumulator += self.delta_seconds;
+        self.fixed_steps = 0;
     }
 
     /// Checks to see if we should perform another fixed update iteration, and if so, returns true
@@ -172,9 +293,20 @@ impl Time {
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn step_fixed_update(&mut self) -> bool {
+        if self
+            .max_fixed_steps
+            .map(|max| self.fixed_steps >= max)
+            .unwrap_or(false)
+        {
+            // Drop the time we can't catch up on, keeping the progress into the next step.
+            self.fixed_time_accumulator %= self.fixed_seconds;
+            return false;
+        }
         match self.fixed_time_accumulator >= self.fixed_seconds {
             true => {
                 self.fixed_time_accumulator -= self.fixed_seconds;
+                self.fixed_frame_number += 1;
+                self.fixed_steps += 1;
                 true
             }
             false => false,
@@ -200,11 +332,18 @@ impl Default for Time {
             fixed_seconds: duration_to_secs(Duration::new(0, 16_666_666)),
             fixed_time: Duration::new(0, 16_666_666),
             fixed_time_accumulator: 0.0,
+            max_fixed_steps: None,
+            fixed_steps: 0,
             frame_number: 0,
+            fixed_frame_number: 0,
             interpolation_alpha: 0.0,
             absolute_real_time: Duration::default(),
             absolute_time: Duration::default(),
             time_scale: 1.0,
+            paused: false,
+            pending_steps: 0,
+            channel_scales: [1.0; 2],
+            channel_deltas: [0.0; 2],
         }
     }
 }
@@ -426,6 +565,73 @@ mod tests {
         }
         assert_eq!(fixed_count, 2);
     }
+
+    // Test that a long frame only runs up to the maximum number of fixed updates
+    #[test]
+    fn fixed_update_max_steps() {
+        use super::Time;
+
+        let mut time = Time::default();
+        time.set_fixed_seconds(0.1);
+        time.set_max_fixed_steps(Some(3));
+
+        time.set_delta_seconds(1.05);
+        time.start_fixed_update();
+        let mut fixed_count = 0;
+        while time.step_fixed_update() {
+            fixed_count += 1;
+        }
+        time.finish_fixed_update();
+        assert_eq!(fixed_count, 3);
+
+        // the dropped time is not caught up on
+        time.set_delta_seconds(0.0);
+        time.start_fixed_update();
+        assert!(!time.step_fixed_update());
+        assert!(time.interpolation_alpha() < 1.0);
+    }
+
+    // Test that pausing stops game time but not UI time, and that steps run one fixed update
+    #[test]
+    fn pause_and_step() {
+        use super::{Time, TimeChannel};
+
+        let mut time = Time::default();
+        time.set_fixed_seconds(0.1);
+        time.set_channel_scale(TimeChannel::Ui, 2.0);
+        time.pause();
+
+        time.set_delta_seconds(0.5);
+        assert_eq!(time.delta_seconds(), 0.0);
+        assert_eq!(time.channel_delta_seconds(TimeChannel::Audio), 0.0);
+        assert_eq!(time.channel_delta_seconds(TimeChannel::Ui), 1.0);
+        assert_eq!(time.delta_real_seconds(), 0.5);
+
+        time.step_frame();
+        time.set_delta_seconds(0.5);
+        time.start_fixed_update();
+        let mut fixed_count = 0;
+        while time.step_fixed_update() {
+            fixed_count += 1;
+        }
+        assert_eq!(fixed_count, 1);
+
+        time.set_delta_seconds(0.5);
+        assert_eq!(time.delta_seconds(), 0.0);
+
+        time.resume();
+        time.set_delta_seconds(0.5);
+        assert_eq!(time.channel_delta_seconds(TimeChannel::Gameplay), 0.5);
+    }
+}
+
+/// Index of the channel in `Time::channel_scales`, `None` for the `Gameplay` channel.
+fn channel_index(channel: TimeChannel) -> Option<usize> {
+    match channel {
+        TimeChannel::Gameplay => None,
+        TimeChannel::Ui => Some(0),
+        TimeChannel::Audio => Some(1),
+    }
 }
 
 /// Converts a Duration to the time in seconds.
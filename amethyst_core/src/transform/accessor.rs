@@ -0,0 +1,238 @@
+//! Helpers for querying and modifying entity hierarchies.
+
+use std::collections::VecDeque;
+
+use shred_derive::SystemData;
+
+use amethyst_error::{format_err, Error};
+
+use crate::{
+    ecs::prelude::{Entities, Entity, ReadExpect, ReadStorage, WriteStorage},
+    math::{Matrix3, Matrix4, RealField, Rotation3, Translation3, UnitQuaternion, Vector3, U3},
+    named::Named,
+    transform::{Parent, ParentHierarchy, Transform},
+};
+
+/// `SystemData` for working with entity hierarchies, e.g. spawned glTF scenes.
+///
+/// All queries are answered from the `Parent` and `Transform` components directly, so they are
+/// correct even before the `TransformSystem` and `HierarchySystem` ran for the current frame.
+/// Only the list of children is taken from the `ParentHierarchy`, which is updated by the
+/// `HierarchySystem`.
+#[derive(SystemData)]
+pub struct HierarchyAccessor<'a, N: RealField> {
+    entities: Entities<'a>,
+    hierarchy: ReadExpect<'a, ParentHierarchy>,
+    parents: WriteStorage<'a, Parent>,
+    transforms: WriteStorage<'a, Transform<N>>,
+    names: ReadStorage<'a, Named>,
+}
+
+impl<'a, N: RealField> HierarchyAccessor<'a, N> {
+    /// Returns the parent of the entity.
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.parents.get(entity).map(|parent| parent.entity)
+    }
+
+    /// Computes the global transformation matrix of the entity from its local `Transform` and
+    /// the ones of its ancestors.
+    ///
+    /// Entities without a `Transform` count as identity. Returns `None` if the entity is dead.
+    pub fn global_transform(&self, entity: Entity) -> Option<Matrix4<N>> {
+        if !self.entities.is_alive(entity) {
+            return None;
+        }
+
+        let mut matrix = Matrix4::identity();
+        let mut current = Some(entity);
+        while let Some(e) = current {
+            if let Some(transform) = self.transforms.get(e) {
+                matrix = transform.matrix() * matrix;
+            }
+            current = self.parent(e);
+        }
+        Some(matrix)
+    }
+
+    /// Sets the parent of the entity, changing its local `Transform` so it keeps its position,
+    /// rotation and scale in the world. Pass `None` to turn it into a root entity.
+    ///
+    /// Shear can't be represented by a `Transform`, so if the entity ends up non-uniformly scaled
+    /// by a rotated ancestor, the result is only an approximation.
+    ///
+    /// ## Errors
+    ///
+    /// Fails if one of the entities is dead, if the new parent is the entity itself or one of
+    /// its descendants, or if the global matrix of the new parent can't be inverted.
+    pub fn set_parent_keep_world(
+        &mut self,
+        entity: Entity,
+        new_parent: Option<Entity>,
+    ) -> Result<(), Error> {
+        let world = self
+            .global_transform(entity)
+            .ok_or_else(|| format_err!("Entity {:?} is not alive", entity))?;
+
+        let local = match new_parent {
+            Some(parent) => {
+                let mut ancestor = Some(parent);
+                while let Some(a) = ancestor {
+                    if a == entity {
+                        return Err(format_err!(
+                            "Entity {:?} can't be the parent of its ancestor {:?}",
+                            parent,
+                            entity
+                        ));
+                    }
+                    ancestor = self.parent(a);
+                }
+                let parent_world = self
+                    .global_transform(parent)
+                    .ok_or_else(|| format_err!("Parent {:?} is not alive", parent))?;
+                let inverse = parent_world.try_inverse().ok_or_else(|| {
+                    format_err!("Global transform of parent {:?} is not invertible", parent)
+                })?;
+                inverse * world
+            }
+            None => world,
+        };
+
+        let mut transform = decompose(&local);
+        if let Some(old) = self.transforms.get(entity) {
+            transform.global_matrix = old.global_matrix;
+        }
+        self.transforms.insert(entity, transform)?;
+        match new_parent {
+            Some(parent) => {
+                self.parents.insert(entity, Parent::new(parent))?;
+            }
+            None => {
+                self.parents.remove(entity);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the direct children of the entity.
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.hierarchy.children(entity)
+    }
+
+    /// Returns all descendants of the entity, parents before their children.
+    pub fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        let mut descendants = Vec::new();
+        let mut queue = self
+            .children(entity)
+            .iter()
+            .cloned()
+            .collect::<VecDeque<_>>();
+        while let Some(e) = queue.pop_front() {
+            descendants.push(e);
+            queue.extend(self.children(e).iter().cloned());
+        }
+        descendants
+    }
+
+    /// Finds a descendant of the entity by its `Named` component.
+    ///
+    /// The search is breadth first, so if several descendants have the name, the one closest to
+    /// the entity is returned.
+    pub fn find_child_by_name(&self, entity: Entity, name: &str) -> Option<Entity> {
+        self.descendants(entity).into_iter().find(|e| {
+            self.names
+                .get(*e)
+                .map(|named| named.name == name)
+                .unwrap_or(false)
+        })
+    }
+}
+
+/// Decomposes an affine matrix into translation, rotation and scale.
+pub(crate) fn decompose<N: RealField>(matrix: &Matrix4<N>) -> Transform<N> {
+    let translation = Translation3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+    let linear = matrix.fixed_slice::<U3, U3>(0, 0).into_owned();
+    let mut scale = Vector3::new(
+        linear.column(0).norm(),
+        linear.column(1).norm(),
+        linear.column(2).norm(),
+    );
+    // A mirrored matrix has a negative determinant, which a rotation can't represent.
+    if linear.determinant() < N::zero() {
+        scale.x = -scale.x;
+    }
+
+    let rotation = if scale.iter().all(|s| *s != N::zero()) {
+        let matrix = Matrix3::from_columns(&[
+            linear.column(0) / scale.x,
+            linear.column(1) / scale.y,
+            linear.column(2) / scale.z,
+        ]);
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(matrix))
+    } else {
+        UnitQuaternion::identity()
+    };
+
+    Transform::new(translation, rotation, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use shred::RunNow;
+    use specs_hierarchy::HierarchySystem;
+
+    use crate::{
+        ecs::prelude::{Builder, World},
+        math::{Vector3, Vector4},
+        named::Named,
+        transform::{Parent, Transform},
+    };
+
+    use super::HierarchyAccessor;
+
+    #[test]
+    fn reparent_keeps_world_position() {
+        let mut world = World::new();
+        let mut hs = HierarchySystem::<Parent>::new();
+        hs.setup(&mut world.res);
+        world.register::<Transform<f32>>();
+        world.register::<Named>();
+
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(1.0, 0.0, 0.0);
+        let root = world.create_entity().with(transform).build();
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(0.0, 2.0, 0.0);
+        transform.set_scale(Vector3::new(2.0, 2.0, 2.0));
+        let child = world
+            .create_entity()
+            .with(transform)
+            .with(Parent::new(root))
+            .with(Named::new("Turret"))
+            .build();
+        let other = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        hs.run_now(&world.res);
+
+        {
+            let accessor = world.system_data::<HierarchyAccessor<'_, f32>>();
+            assert_eq!(vec![child], accessor.descendants(root));
+            assert_eq!(Some(child), accessor.find_child_by_name(root, "Turret"));
+            assert_eq!(None, accessor.find_child_by_name(root, "Barrel"));
+            let position =
+                accessor.global_transform(child).unwrap() * Vector4::new(0.0, 0.0, 0.0, 1.0);
+            assert!((position - Vector4::new(1.0, 2.0, 0.0, 1.0)).norm() < 1e-5);
+        }
+
+        {
+            let mut accessor = world.system_data::<HierarchyAccessor<'_, f32>>();
+            accessor.set_parent_keep_world(child, Some(other)).unwrap();
+            assert!(accessor.set_parent_keep_world(other, Some(child)).is_err());
+            let position =
+                accessor.global_transform(child).unwrap() * Vector4::new(0.0, 0.0, 0.0, 1.0);
+            assert!((position - Vector4::new(1.0, 2.0, 0.0, 1.0)).norm() < 1e-5);
+            assert_eq!(Some(other), accessor.parent(child));
+        }
+    }
+}
@@ -5,12 +5,24 @@ use std::marker::PhantomData;
 use amethyst_error::Error;
 use specs_hierarchy::HierarchySystem;
 
-use crate::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder, math::RealField, transform::*};
+use crate::{
+    bundle::{BundleBuilder, SystemBundle},
+    math::RealField,
+    spatial::BoundsSystem,
+    transform::*,
+};
 
 /// Transform bundle
 ///
-/// Will register transform components, and the `TransformSystem`.
-/// `TransformSystem` will be registered with name "transform_system".
+/// Will register transform components, the `TransformSystem`, the
+/// `TransformInterpolationSystem` and the `TransformConstraintSystem`.
+/// `TransformSystem` will be registered with name "transform_system",
+/// `TransformInterpolationSystem` with name "transform_interpolation_system",
+/// `TransformConstraintSystem` with name "transform_constraint_system".
+/// The `BoundsSystem` updating the `WorldBounds` of entities is registered as "bounds_system".
+///
+/// Add a `PreviousTransform` to entities moved in `State::fixed_update` to interpolate them
+/// between fixed updates.
 ///
 /// ## Errors
 ///
@@ -43,7 +55,7 @@ impl<'a, N> TransformBundle<'a, N> {
 }
 
 impl<'a, 'b, 'c, N: RealField> SystemBundle<'a, 'b> for TransformBundle<'c, N> {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             HierarchySystem::<Parent>::new(),
             "parent_hierarchy_system",
@@ -54,6 +66,21 @@ impl<'a, 'b, 'c, N: RealField> SystemBundle<'a, 'b> for TransformBundle<'c, N> {
             "transform_system",
             &["parent_hierarchy_system"],
         );
+        builder.add(
+            TransformInterpolationSystem::<N>::new(),
+            "transform_interpolation_system",
+            &["transform_system"],
+        );
+        builder.add(
+            TransformConstraintSystem::<N>::new(),
+            "transform_constraint_system",
+            &["transform_interpolation_system"],
+        );
+        builder.add(
+            BoundsSystem,
+            "bounds_system",
+            &["transform_constraint_system"],
+        );
         Ok(())
     }
 }
@@ -2,8 +2,10 @@
 
 pub use self::{
     parent::{HierarchyEvent, Parent, ParentHierarchy},
+    previous::PreviousTransform,
     transform::Transform,
 };
 
 mod parent;
+mod previous;
 mod transform;
@@ -0,0 +1,135 @@
+//! Component for interpolating transforms between fixed updates.
+
+use crate::{
+    ecs::prelude::{Component, DenseVecStorage},
+    math::{self as na, RealField, Translation3},
+    transform::Transform,
+};
+
+/// Enables transform interpolation for entities which are moved in `State::fixed_update`.
+///
+/// The `TransformInterpolationSystem` stores the local `Transform` after each fixed update in
+/// this component, and renders the entity blended between the last two of them by the
+/// interpolation alpha of the frame. This removes the stutter of objects simulated at a lower
+/// rate than the frame rate, at the cost of displaying them one fixed update late.
+///
+/// Children of interpolated entities follow their interpolated parent, they only need this
+/// component if they're moved in fixed updates themselves. Entities moved every frame shouldn't
+/// have it.
+#[derive(Clone, Debug)]
+pub struct PreviousTransform<N: RealField> {
+    snapshot: Option<Snapshot<N>>,
+}
+
+#[derive(Clone, Debug)]
+struct Snapshot<N: RealField> {
+    previous: Transform<N>,
+    current: Transform<N>,
+    /// Number of fixed updates between `previous` and `current`.
+    steps: u64,
+    fixed_frame_number: u64,
+}
+
+impl<N: RealField> PreviousTransform<N> {
+    /// Creates the component, the first snapshot is taken by the next run of the
+    /// `TransformInterpolationSystem`.
+    pub fn new() -> Self {
+        PreviousTransform { snapshot: None }
+    }
+
+    /// Returns the local transform as it was before the last fixed update.
+    pub fn previous(&self) -> Option<&Transform<N>> {
+        self.snapshot.as_ref().map(|s| &s.previous)
+    }
+
+    /// Discards the previous transform, so the entity is not interpolated until the next fixed
+    /// update. Use this after teleporting an entity.
+    pub fn reset(&mut self) {
+        self.snapshot = None;
+    }
+
+    /// Records the local transform of the current frame.
+    pub(crate) fn update(&mut self, local: &Transform<N>, fixed_frame_number: u64) {
+        match self.snapshot {
+            Some(ref mut snapshot) => {
+                if snapshot.fixed_frame_number != fixed_frame_number {
+                    snapshot.steps = fixed_frame_number.saturating_sub(snapshot.fixed_frame_number);
+                    snapshot.previous = snapshot.current.clone();
+                    snapshot.fixed_frame_number = fixed_frame_number;
+                }
+                snapshot.current = local.clone();
+            }
+            None => {
+                self.snapshot = Some(Snapshot {
+                    previous: local.clone(),
+                    current: local.clone(),
+                    steps: 1,
+                    fixed_frame_number,
+                });
+            }
+        }
+    }
+
+    /// Blends the previous and the current transform using the interpolation `alpha` of the
+    /// frame.
+    ///
+    /// If several fixed updates happened in the frame the snapshot was taken, `previous` is older
+    /// than one step, so `alpha` is scaled to the number of steps.
+    pub(crate) fn blend(&self, alpha: f32) -> Option<Transform<N>> {
+        self.snapshot.as_ref().map(|s| {
+            let steps = s.steps.max(1) as f64;
+            let t = ((steps - 1.0 + f64::from(alpha)) / steps).min(1.0).max(0.0);
+            let t: N = na::convert(t);
+
+            let translation = s.previous.translation().lerp(s.current.translation(), t);
+            let rotation = s.previous.rotation().nlerp(s.current.rotation(), t);
+            let scale = s.previous.scale().lerp(s.current.scale(), t);
+            Transform::new(Translation3::from(translation), rotation, scale)
+        })
+    }
+}
+
+impl<N: RealField> Default for PreviousTransform<N> {
+    fn default() -> Self {
+        PreviousTransform::new()
+    }
+}
+
+impl<N: RealField> Component for PreviousTransform<N> {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Transform<f32> {
+        let mut transform = Transform::default();
+        transform.set_translation_x(x);
+        transform
+    }
+
+    #[test]
+    fn blends_last_fixed_update() {
+        let mut previous = PreviousTransform::new();
+        previous.update(&at(0.0), 0);
+        previous.update(&at(1.0), 1);
+        let blended = previous.blend(0.5).unwrap();
+        assert!((blended.translation().x - 0.5).abs() < 1e-6);
+
+        // frames without a fixed update keep the snapshot
+        previous.update(&at(1.0), 1);
+        let blended = previous.blend(0.25).unwrap();
+        assert!((blended.translation().x - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scales_alpha_to_steps() {
+        let mut previous = PreviousTransform::new();
+        previous.update(&at(0.0), 0);
+        // two fixed updates of one unit each
+        previous.update(&at(2.0), 2);
+        let blended = previous.blend(0.5).unwrap();
+        assert!((blended.translation().x - 1.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,330 @@
+//! Constraints for assembling rigs from existing entities, e.g. the nodes of a glTF scene.
+//!
+//! Constraints are evaluated by the `TransformConstraintSystem` after the global matrices were
+//! computed and interpolated, and override the global matrix of the constrained entity. The
+//! local `Transform` is left untouched, so removing a constraint restores the original
+//! placement. Children of constrained entities follow them.
+
+use std::marker::PhantomData;
+
+use hibitset::BitSet;
+
+use crate::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, ReadExpect, ReadStorage, System,
+        WriteStorage,
+    },
+    math::{Matrix4, RealField, Vector3},
+    transform::{accessor::decompose, Parent, ParentHierarchy, Transform},
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+/// Places the entity at the world position of the target, plus the offset.
+#[derive(Clone, Debug)]
+pub struct CopyPosition<N: RealField> {
+    /// The entity to copy the position from.
+    pub target: Entity,
+    /// Offset from the target in world space.
+    pub offset: Vector3<N>,
+}
+
+impl<N: RealField> CopyPosition<N> {
+    /// Copies the position of the target without offset.
+    pub fn new(target: Entity) -> Self {
+        CopyPosition {
+            target,
+            offset: Vector3::zeros(),
+        }
+    }
+}
+
+impl<N: RealField> Component for CopyPosition<N> {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Gives the entity the world rotation of the target.
+#[derive(Clone, Debug, new)]
+pub struct CopyRotation {
+    /// The entity to copy the rotation from.
+    pub target: Entity,
+}
+
+impl Component for CopyRotation {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Gives the entity the world scale of the target.
+#[derive(Clone, Debug, new)]
+pub struct CopyScale {
+    /// The entity to copy the scale from.
+    pub target: Entity,
+}
+
+impl Component for CopyScale {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Limits the rotation of the entity relative to its parent.
+///
+/// The limits are euler angles in radians, in the order used by `Transform::euler_angles`.
+/// Applied before the copy constraints.
+#[derive(Clone, Debug, new)]
+pub struct LimitRotation<N: RealField> {
+    /// Lower limits of the euler angles.
+    pub min: Vector3<N>,
+    /// Upper limits of the euler angles.
+    pub max: Vector3<N>,
+}
+
+impl<N: RealField> Component for LimitRotation<N> {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Keeps the world position of the entity within a distance range of the target, e.g. for a
+/// camera boom. Applied after the copy constraints.
+#[derive(Clone, Debug, new)]
+pub struct LimitDistance<N: RealField> {
+    /// The entity to keep the distance to.
+    pub target: Entity,
+    /// Minimum distance to the target.
+    pub min: N,
+    /// Maximum distance to the target.
+    pub max: N,
+}
+
+impl<N: RealField> Component for LimitDistance<N> {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Evaluates the transform constraints and updates the global matrices of the constrained
+/// entities and their descendants.
+///
+/// Constraints read the global matrix of their target as computed in the current frame. Entities
+/// are evaluated parents first, so a target constrained itself is only up to date if it comes
+/// before the constrained entity in the hierarchy.
+pub struct TransformConstraintSystem<N> {
+    constrained: BitSet,
+    stack: Vec<Entity>,
+    _phantom: PhantomData<N>,
+}
+
+impl<N> TransformConstraintSystem<N> {
+    /// Creates a new transform constraint system.
+    pub fn new() -> Self {
+        TransformConstraintSystem {
+            constrained: BitSet::default(),
+            stack: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, N: RealField> System<'a> for TransformConstraintSystem<N> {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, ParentHierarchy>,
+        ReadStorage<'a, Parent>,
+        WriteStorage<'a, Transform<N>>,
+        ReadStorage<'a, CopyPosition<N>>,
+        ReadStorage<'a, CopyRotation>,
+        ReadStorage<'a, CopyScale>,
+        ReadStorage<'a, LimitRotation<N>>,
+        ReadStorage<'a, LimitDistance<N>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            hierarchy,
+            parents,
+            mut locals,
+            copy_positions,
+            copy_rotations,
+            copy_scales,
+            limit_rotations,
+            limit_distances,
+        ): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("transform_constraint_system");
+
+        self.constrained.clear();
+        for (entity, _) in (&*entities, &copy_positions).join() {
+            self.constrained.add(entity.id());
+        }
+        for (entity, _) in (&*entities, &copy_rotations).join() {
+            self.constrained.add(entity.id());
+        }
+        for (entity, _) in (&*entities, &copy_scales).join() {
+            self.constrained.add(entity.id());
+        }
+        for (entity, _) in (&*entities, &limit_rotations).join() {
+            self.constrained.add(entity.id());
+        }
+        for (entity, _) in (&*entities, &limit_distances).join() {
+            self.constrained.add(entity.id());
+        }
+
+        let mut ordered = (&*entities, &self.constrained, !&parents)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        ordered.extend(
+            hierarchy
+                .all()
+                .iter()
+                .filter(|entity| self.constrained.contains(entity.id())),
+        );
+
+        let global = |locals: &WriteStorage<'_, Transform<N>>, entity: Entity| {
+            locals
+                .get(entity)
+                .map(|local| decompose(local.global_matrix()))
+        };
+
+        for entity in ordered {
+            let local = match locals.get(entity) {
+                Some(local) => local.clone(),
+                None => continue,
+            };
+            let parent_matrix = parents
+                .get(entity)
+                .and_then(|parent| locals.get(parent.entity))
+                .map(|parent| parent.global_matrix)
+                .unwrap_or_else(Matrix4::identity);
+
+            let mut world = match limit_rotations.get(entity) {
+                Some(limit) => {
+                    let mut local = local;
+                    let (x, y, z) = local.euler_angles();
+                    local.set_rotation_euler(
+                        clamp(x, limit.min.x, limit.max.x),
+                        clamp(y, limit.min.y, limit.max.y),
+                        clamp(z, limit.min.z, limit.max.z),
+                    );
+                    decompose(&(parent_matrix * local.matrix()))
+                }
+                None => decompose(local.global_matrix()),
+            };
+
+            if let Some(copy) = copy_positions.get(entity) {
+                if let Some(target) = global(&locals, copy.target) {
+                    world.set_translation(target.translation() + copy.offset);
+                }
+            }
+            if let Some(copy) = copy_rotations.get(entity) {
+                if let Some(target) = global(&locals, copy.target) {
+                    world.set_rotation(*target.rotation());
+                }
+            }
+            if let Some(copy) = copy_scales.get(entity) {
+                if let Some(target) = global(&locals, copy.target) {
+                    world.set_scale(*target.scale());
+                }
+            }
+            if let Some(limit) = limit_distances.get(entity) {
+                if let Some(target) = global(&locals, limit.target) {
+                    let offset = world.translation() - target.translation();
+                    let distance = offset.norm();
+                    if distance > N::default_epsilon() {
+                        let clamped = clamp(distance, limit.min, limit.max);
+                        world.set_translation(target.translation() + offset * (clamped / distance));
+                    }
+                }
+            }
+
+            locals
+                .get_mut(entity)
+                .expect("unreachable: the entity has a transform")
+                .global_matrix = world.matrix();
+
+            // Move the descendants along.
+            self.stack
+                .extend(hierarchy.children(entity).iter().cloned());
+            while let Some(child) = self.stack.pop() {
+                let parent_matrix = parents
+                    .get(child)
+                    .and_then(|parent| locals.get(parent.entity))
+                    .map(|parent| parent.global_matrix)
+                    .unwrap_or_else(Matrix4::identity);
+                if let Some(local) = locals.get_mut(child) {
+                    local.global_matrix = parent_matrix * local.matrix();
+                }
+                self.stack.extend(hierarchy.children(child).iter().cloned());
+            }
+        }
+    }
+}
+
+fn clamp<N: RealField>(value: N, min: N, max: N) -> N {
+    value.max(min).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use shred::RunNow;
+    use specs_hierarchy::HierarchySystem;
+
+    use crate::{
+        ecs::prelude::{Builder, World},
+        transform::TransformSystem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn camera_boom() {
+        let mut world = World::new();
+        let mut hs = HierarchySystem::<Parent>::new();
+        let mut ts = TransformSystem::<f32>::new();
+        let mut cs = TransformConstraintSystem::<f32>::new();
+        hs.setup(&mut world.res);
+        ts.setup(&mut world.res);
+        cs.setup(&mut world.res);
+
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(10.0, 0.0, 0.0);
+        let player = world.create_entity().with(transform).build();
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(0.0, 0.0, 20.0);
+        let camera = world
+            .create_entity()
+            .with(transform)
+            .with(LimitDistance::new(player, 1.0, 5.0))
+            .build();
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(0.0, 1.0, 0.0);
+        let lens = world
+            .create_entity()
+            .with(transform)
+            .with(Parent::new(camera))
+            .build();
+        let follower = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(CopyPosition {
+                target: player,
+                offset: Vector3::new(0.0, 2.0, 0.0),
+            })
+            .build();
+
+        hs.run_now(&world.res);
+        ts.run_now(&world.res);
+        cs.run_now(&world.res);
+
+        let locals = world.read_storage::<Transform<f32>>();
+        let position = |entity| {
+            let matrix = locals.get(entity).unwrap().global_matrix();
+            Vector3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)])
+        };
+        let boom = position(camera) - position(player);
+        assert!((boom.norm() - 5.0).abs() < 1e-4);
+        assert!((position(lens) - position(camera) - Vector3::new(0.0, 1.0, 0.0)).norm() < 1e-4);
+        assert!((position(follower) - Vector3::new(10.0, 2.0, 0.0)).norm() < 1e-4);
+        // the local transform is untouched
+        assert_eq!(20.0, locals.get(camera).unwrap().translation().z);
+    }
+}
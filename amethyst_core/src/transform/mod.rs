@@ -1,7 +1,18 @@
 //! `amethyst` transform ecs module
 
-pub use self::{bundle::TransformBundle, components::*, systems::*};
+pub use self::{
+    accessor::HierarchyAccessor,
+    bundle::TransformBundle,
+    components::*,
+    constraints::{
+        CopyPosition, CopyRotation, CopyScale, LimitDistance, LimitRotation,
+        TransformConstraintSystem,
+    },
+    systems::*,
+};
 
+pub mod accessor;
 pub mod bundle;
 pub mod components;
+pub mod constraints;
 pub mod systems;
@@ -3,22 +3,28 @@
 use std::marker::PhantomData;
 
 use crate::ecs::prelude::{
-    ComponentEvent, Entities, Join, ReadExpect, ReadStorage, ReaderId, Resources, System,
-    WriteStorage,
+    ComponentEvent, Entities, Entity, Join, Read, ReadExpect, ReadStorage, ReaderId, Resources,
+    System, WriteStorage,
 };
-use hibitset::BitSet;
+use hibitset::{BitSet, BitSetLike};
+use log::error;
 
 use crate::{
-    math::RealField,
-    transform::{HierarchyEvent, Parent, ParentHierarchy, Transform},
+    math::{Matrix4, RealField},
+    transform::{HierarchyEvent, Parent, ParentHierarchy, PreviousTransform, Transform},
+    Time,
 };
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
 /// Handles updating `global_matrix` field from `Transform` components.
+///
+/// Only entities whose `Transform` or `Parent` changed since the last run, and their
+/// descendants, are updated. Static parts of the scene cost nothing.
 pub struct TransformSystem<N> {
     local_modified: BitSet,
+    roots: Vec<Entity>,
     locals_events_id: Option<ReaderId<ComponentEvent>>,
     parent_events_id: Option<ReaderId<HierarchyEvent>>,
     _phantom: PhantomData<N>,
@@ -31,6 +37,7 @@ impl<N> TransformSystem<N> {
             locals_events_id: None,
             parent_events_id: None,
             local_modified: BitSet::default(),
+            roots: Vec::new(),
             _phantom: PhantomData,
         }
     }
@@ -81,46 +88,65 @@ impl<'a, N: RealField> System<'a> for TransformSystem<N> {
             }
         }
 
-        let mut modified = vec![];
-        // Compute transforms without parents.
-        for (entity, _, local, _) in
-            (&*entities, &self.local_modified, &mut locals, !&parents).join()
-        {
-            modified.push(entity.id());
-            local.global_matrix = local.matrix();
-            debug_assert!(
-                local.is_finite(),
-                format!(
-                    "Entity {:?} had a non-finite `Transform` {:?}",
-                    entity, local
-                )
-            );
+        if self.local_modified.is_empty() {
+            return;
         }
-        modified.into_iter().for_each(|id| {
-            self.local_modified.add(id);
-        });
 
-        // Compute transforms with parents.
-        for entity in hierarchy.all() {
-            let self_dirty = self.local_modified.contains(entity.id());
-            if let Some(parent) = parents.get(*entity) {
-                let parent_dirty = self.local_modified.contains(parent.entity.id());
-                if parent_dirty || self_dirty {
-                    let combined_transform = {
-                        let local = locals.get(*entity);
-                        if local.is_none() {
-                            continue;
-                        }
-                        let local = local.unwrap();
-                        if let Some(parent_global) = locals.get(parent.entity) {
-                            (parent_global.global_matrix * local.matrix())
-                        } else {
-                            local.matrix()
-                        }
+        // Only the subtrees below modified transforms are recomputed. Their roots are the
+        // modified entities without a modified ancestor, the parent of such a root already has an
+        // up to date global matrix.
+        // A longer chain of parents than entities in the hierarchy can only be a cycle, whose
+        // entities are skipped.
+        let max_depth = hierarchy.all().len() + 1;
+        self.roots.clear();
+        for (entity, _) in (&*entities, &self.local_modified).join() {
+            let mut ancestor = parents.get(entity).map(|parent| parent.entity);
+            let mut ancestor_modified = false;
+            let mut depth = 0;
+            while let Some(a) = ancestor {
+                if self.local_modified.contains(a.id()) {
+                    ancestor_modified = true;
+                    break;
+                }
+                depth += 1;
+                if depth > max_depth {
+                    error!(
+                        "The parents of {:?} form a cycle, its transform is not updated",
+                        entity
+                    );
+                    ancestor_modified = true;
+                    break;
+                }
+                ancestor = parents.get(a).map(|parent| parent.entity);
+            }
+            if !ancestor_modified {
+                self.roots.push(entity);
+            }
+        }
+
+        // Parents are always popped before their children are pushed.
+        let mut stack = Vec::new();
+        for root in self.roots.drain(..) {
+            stack.push(root);
+            while let Some(entity) = stack.pop() {
+                let parent_global = parents
+                    .get(entity)
+                    .and_then(|parent| locals.get(parent.entity))
+                    .map(|parent| parent.global_matrix);
+                if let Some(local) = locals.get_mut(entity) {
+                    local.global_matrix = match parent_global {
+                        Some(parent_global) => parent_global * local.matrix(),
+                        None => local.matrix(),
                     };
-                    self.local_modified.add(entity.id());
-                    locals.get_mut(*entity).expect("unreachable: We know this entity has a local because is was just modified.").global_matrix = combined_transform;
+                    debug_assert!(
+                        local.is_finite(),
+                        format!(
+                            "Entity {:?} had a non-finite `Transform` {:?}",
+                            entity, local
+                        )
+                    );
                 }
+                stack.extend(hierarchy.children(entity).iter().cloned());
             }
         }
 
@@ -140,10 +166,94 @@ impl<'a, N: RealField> System<'a> for TransformSystem<N> {
     }
 }
 
+/// Blends the global matrix of entities with a `PreviousTransform` between the last two fixed
+/// updates, using the interpolation alpha of `Time`.
+///
+/// Has to run after the `TransformSystem`. The interpolated matrix is only used for rendering,
+/// the `TransformSystem` recomputes the actual global matrix in the next frame.
+pub struct TransformInterpolationSystem<N> {
+    interpolated: BitSet,
+    _phantom: PhantomData<N>,
+}
+
+impl<N> TransformInterpolationSystem<N> {
+    /// Creates a new transform interpolation system.
+    pub fn new() -> Self {
+        TransformInterpolationSystem {
+            interpolated: BitSet::default(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, N: RealField> System<'a> for TransformInterpolationSystem<N> {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, ParentHierarchy>,
+        WriteStorage<'a, Transform<N>>,
+        WriteStorage<'a, PreviousTransform<N>>,
+        ReadStorage<'a, Parent>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, hierarchy, mut locals, mut previous, parents): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("transform_interpolation_system");
+
+        self.interpolated.clear();
+        let fixed_frame_number = time.fixed_frame_number();
+        let alpha = time.interpolation_alpha();
+
+        for (entity, local, previous) in (&*entities, &locals, &mut previous).join() {
+            previous.update(local, fixed_frame_number);
+            self.interpolated.add(entity.id());
+        }
+
+        // Mutably accessing the transforms flags them as modified, so the `TransformSystem`
+        // restores their actual global matrix in the next frame.
+        for (local, previous, _) in (&mut locals, &previous, !&parents).join() {
+            if let Some(blended) = previous.blend(alpha) {
+                local.global_matrix = blended.matrix();
+            }
+        }
+
+        // The hierarchy is sorted, so parents are always interpolated before their children.
+        for entity in hierarchy.all() {
+            let parent = match parents.get(*entity) {
+                Some(parent) => parent.entity,
+                None => continue,
+            };
+            let own = self.interpolated.contains(entity.id());
+            if !own && !self.interpolated.contains(parent.id()) {
+                continue;
+            }
+
+            let local_matrix = match previous.get(*entity).and_then(|p| p.blend(alpha)) {
+                Some(blended) => blended.matrix(),
+                None => match locals.get(*entity) {
+                    Some(local) => local.matrix(),
+                    None => continue,
+                },
+            };
+            let parent_matrix = locals
+                .get(parent)
+                .map(|parent| parent.global_matrix)
+                .unwrap_or_else(Matrix4::identity);
+            if let Some(local) = locals.get_mut(*entity) {
+                local.global_matrix = parent_matrix * local_matrix;
+                self.interpolated.add(entity.id());
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        ecs::prelude::{Builder, World},
+        ecs::prelude::{Builder, ComponentEvent, World},
         math::{Matrix4, Quaternion, RealField, Unit, Vector3},
     };
     use shred::RunNow;
@@ -180,6 +290,49 @@ mod tests {
         global_matrix * local_matrix
     }
 
+    // A cycle of parents is skipped instead of hanging the walk of the ancestors.
+    #[test]
+    fn parent_cycle() {
+        let (mut world, mut hs, mut system) = transform_world();
+
+        let e1 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        let e2 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        let e3 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Parent { entity: e1 })
+            .build();
+
+        hs.run_now(&mut world.res);
+        system.run_now(&mut world.res);
+
+        // The `HierarchySystem` doesn't run, so only the ancestor walk sees the cycle.
+        {
+            let mut parents = world.write_storage::<Parent>();
+            parents.insert(e1, Parent { entity: e2 }).unwrap();
+            parents.insert(e2, Parent { entity: e1 }).unwrap();
+        }
+        world
+            .write_storage::<Transform<f32>>()
+            .get_mut(e3)
+            .unwrap()
+            .set_translation_x(1.0);
+        system.run_now(&mut world.res);
+
+        let transform = world
+            .read_storage::<Transform<f32>>()
+            .get(e3)
+            .unwrap()
+            .clone();
+        assert_eq!(transform.global_matrix(), &Matrix4::identity());
+    }
+
     // Basic default Transform's local matrix -> global matrix  (Should just be identity)
     #[test]
     fn zeroed() {
@@ -524,4 +677,57 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn only_modified_subtrees() {
+        let (mut world, mut hs, mut system) = transform_world();
+
+        let e1 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Parent { entity: e1 })
+            .build();
+        let e3 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        let e4 = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Parent { entity: e3 })
+            .build();
+
+        hs.run_now(&mut world.res);
+        system.run_now(&mut world.res);
+        world.maintain();
+
+        let mut transform_reader = {
+            let mut transforms = world.write_storage::<Transform<f32>>();
+            transforms.register_reader()
+        };
+        world
+            .write_storage::<Transform<f32>>()
+            .get_mut(e3)
+            .unwrap()
+            .set_translation_x(1.0);
+
+        hs.run_now(&mut world.res);
+        system.run_now(&mut world.res);
+        world.maintain();
+
+        let transforms = world.read_storage::<Transform<f32>>();
+        for event in transforms.channel().read(&mut transform_reader) {
+            match event {
+                ComponentEvent::Modified(id) => {
+                    assert!(*id == e3.id() || *id == e4.id());
+                }
+                _ => panic!("Unexpected transform event"),
+            }
+        }
+        assert_eq!(transforms.get(e4).unwrap().global_matrix()[(0, 3)], 1.0);
+    }
 }
@@ -0,0 +1,38 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, GenericParam, Lifetime, LifetimeDef};
+
+pub fn impl_event_system(ast: &DeriveInput) -> TokenStream {
+    let system_name = &ast.ident;
+    let (_, type_generics, where_clause) = ast.generics.split_for_impl();
+    let mut generics = ast.generics.clone();
+
+    let system_lifetime = Lifetime::new("'s", Span::call_site());
+    generics
+        .params
+        .push(GenericParam::Lifetime(LifetimeDef::new(
+            system_lifetime.clone(),
+        )));
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics System<#system_lifetime> for #system_name #type_generics
+        #where_clause
+        {
+            type SystemData = (
+                ReadEvents<
+                    #system_lifetime,
+                    <Self as EventHandler<#system_lifetime>>::Event,
+                    Self,
+                >,
+                <Self as EventHandler<#system_lifetime>>::SystemData,
+            );
+
+            fn run(&mut self, (mut events, mut data): Self::SystemData) {
+                for event in events.read() {
+                    EventHandler::handle(self, event, &mut data);
+                }
+            }
+        }
+    }
+}
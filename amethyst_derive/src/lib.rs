@@ -7,6 +7,7 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
 mod event_reader;
+mod event_system;
 mod prefab_data;
 mod widget_id;
 
@@ -17,6 +18,19 @@ pub fn event_reader_derive(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// Implements `System` for a system handling events one at a time with its `EventHandler`
+/// implementation. The events are read with `ReadEvents`, so the reader is registered when the
+/// system is set up.
+///
+/// Deriving an `EventSystem` requires that `amethyst::ecs::System` and
+/// `amethyst::core::{EventHandler, ReadEvents}` are imported and visible in the current scope.
+#[proc_macro_derive(EventSystem)]
+pub fn event_system_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let gen = event_system::impl_event_system(&ast);
+    gen.into()
+}
+
 /// Deriving a `Prefab` requires that `amethyst::ecs::Entity` and
 /// `amethyst:assets::{PrefabData, PrefabError, ProgressCounter}` are imported
 /// and visible in the current scope. This is due to how Rust macros work.
@@ -1,10 +1,13 @@
-use amethyst_derive::{EventReader, PrefabData};
+use amethyst_derive::{EventReader, EventSystem, PrefabData};
 
 use amethyst_assets::{PrefabData, ProgressCounter};
 use amethyst_core::{
-    ecs::{Component, DenseVecStorage, Entity, Read, Resources, SystemData, WriteStorage},
+    ecs::{
+        Component, DenseVecStorage, Entity, Read, Resources, RunNow, System, SystemData, World,
+        Write, WriteStorage,
+    },
     shrev::{EventChannel, ReaderId},
-    EventReader,
+    EventHandler, EventReader, ReadEvents,
 };
 use amethyst_error::Error;
 
@@ -75,3 +78,34 @@ pub struct Outer {
     #[prefab(Component)]
     external: External,
 }
+
+#[derive(Default)]
+pub struct EventCount(usize);
+
+#[derive(EventSystem)]
+pub struct CountingSystem;
+
+impl<'a> EventHandler<'a> for CountingSystem {
+    type Event = TestEvent1;
+    type SystemData = Write<'a, EventCount>;
+
+    fn handle(&mut self, _event: &TestEvent1, count: &mut Self::SystemData) {
+        count.0 += 1;
+    }
+}
+
+#[test]
+fn event_system_handles_events() {
+    let mut world = World::new();
+    let mut system = CountingSystem;
+    System::setup(&mut system, &mut world.res);
+
+    world
+        .write_resource::<EventChannel<TestEvent1>>()
+        .iter_write(vec![TestEvent1, TestEvent1, TestEvent1]);
+    system.run_now(&world.res);
+    assert_eq!(3, world.read_resource::<EventCount>().0);
+
+    system.run_now(&world.res);
+    assert_eq!(3, world.read_resource::<EventCount>().0);
+}
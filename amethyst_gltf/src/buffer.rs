@@ -0,0 +1,57 @@
+//! Buffers of glTF files loaded as assets of their own, see `GltfSceneOptions::stream_buffers`.
+
+use std::{fmt, sync::Arc};
+
+use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use amethyst_core::ecs::prelude::VecStorage;
+use amethyst_error::Error;
+
+/// The contents of a buffer file of a glTF scene, like the `.bin` file next to a `.gltf` file.
+///
+/// Scenes loaded with `GltfSceneOptions::stream_buffers` load their buffers as `GltfBuffer`s
+/// with the `GltfBufferFormat`, so add a `Processor<GltfBuffer>` to the dispatcher.
+#[derive(Clone)]
+pub struct GltfBuffer(Arc<Vec<u8>>);
+
+impl GltfBuffer {
+    /// The bytes of the buffer.
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub(crate) fn shared(&self) -> Arc<Vec<u8>> {
+        self.0.clone()
+    }
+}
+
+impl fmt::Debug for GltfBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GltfBuffer({} bytes)", self.0.len())
+    }
+}
+
+impl Asset for GltfBuffer {
+    const NAME: &'static str = "gltf::GltfBuffer";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl Into<Result<ProcessingState<GltfBuffer>, Error>> for GltfBuffer {
+    fn into(self) -> Result<ProcessingState<GltfBuffer>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+/// Loads a `GltfBuffer` from a file as it is.
+#[derive(Clone, Debug, Default)]
+pub struct GltfBufferFormat;
+
+impl SimpleFormat<GltfBuffer> for GltfBufferFormat {
+    const NAME: &'static str = "GLTFBuffer";
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<GltfBuffer, Error> {
+        Ok(GltfBuffer(Arc::new(bytes)))
+    }
+}
@@ -0,0 +1,69 @@
+//! Visualization of the extents of glTF nodes.
+
+use std::marker::PhantomData;
+
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{Join, ReadStorage, System, Write},
+    math::{convert, Matrix4, RealField},
+    transform::Transform,
+};
+use amethyst_renderer::{DebugLines, Rgba};
+
+use crate::GltfNodeExtent;
+
+/// Draws the `GltfNodeExtent` of every entity as a box of `DebugLines`, for a `DrawDebugLines`
+/// pass to render.
+///
+/// The extents are in the space of their entity, so the boxes follow its global `Transform`.
+/// Entities without a `Transform` are drawn in world space.
+///
+/// # Type Parameters
+///
+/// * `N`: `RealBound` (f32, f64)
+pub struct GltfExtentLinesSystem<N> {
+    color: Rgba,
+    _marker: PhantomData<N>,
+}
+
+impl<N> Default for GltfExtentLinesSystem<N> {
+    fn default() -> Self {
+        GltfExtentLinesSystem {
+            color: Rgba::GREEN,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<N> GltfExtentLinesSystem<N> {
+    /// Creates a system drawing green boxes.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the color of the boxes.
+    pub fn with_color(mut self, color: Rgba) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl<'a, N: RealField + SubsetOf<f32>> System<'a> for GltfExtentLinesSystem<N> {
+    type SystemData = (
+        ReadStorage<'a, GltfNodeExtent>,
+        ReadStorage<'a, Transform<N>>,
+        Write<'a, DebugLines>,
+    );
+
+    fn run(&mut self, (extents, transforms, mut lines): Self::SystemData) {
+        for (extent, transform) in (&extents, transforms.maybe()).join() {
+            if !extent.valid() {
+                continue;
+            }
+            let matrix = transform
+                .map(|transform| convert::<Matrix4<N>, Matrix4<f32>>(*transform.global_matrix()))
+                .unwrap_or_else(Matrix4::identity);
+            lines.draw_transformed_box(extent.start, extent.end, &matrix, self.color);
+        }
+    }
+}
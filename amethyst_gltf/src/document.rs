@@ -0,0 +1,274 @@
+//! The contents of a glTF file as plain data, for tools which inspect files without spawning
+//! them.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use amethyst_core::ecs::prelude::VecStorage;
+use amethyst_error::{format_err, Error, ResultExt};
+use gltf::{animation::Property, mesh::Semantic, Gltf};
+
+/// The structure of a glTF file: its scenes, nodes, meshes, materials and animations, without
+/// any vertex or image data.
+///
+/// Load it with the `GltfDocumentFormat`, and add a `Processor<GltfDocumentAsset>` to the
+/// dispatcher. No entities are created.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GltfDocumentAsset {
+    /// The scenes of the file.
+    pub scenes: Vec<GltfSceneInfo>,
+    /// The default scene, if the file has one.
+    pub default_scene: Option<usize>,
+    /// All nodes of the file, children refer to them by index.
+    pub nodes: Vec<GltfNodeInfo>,
+    /// All meshes of the file.
+    pub meshes: Vec<GltfMeshInfo>,
+    /// All materials of the file.
+    pub materials: Vec<GltfMaterialInfo>,
+    /// All animations of the file.
+    pub animations: Vec<GltfAnimationInfo>,
+}
+
+/// A scene of a `GltfDocumentAsset`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GltfSceneInfo {
+    /// The name of the scene.
+    pub name: Option<String>,
+    /// The root nodes of the scene.
+    pub nodes: Vec<usize>,
+}
+
+/// A node of a `GltfDocumentAsset`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GltfNodeInfo {
+    /// The name of the node.
+    pub name: Option<String>,
+    /// The indices of the child nodes.
+    pub children: Vec<usize>,
+    /// The index of the mesh of the node.
+    pub mesh: Option<usize>,
+    /// The index of the skin of the node.
+    pub skin: Option<usize>,
+    /// The local translation.
+    pub translation: [f32; 3],
+    /// The local rotation, as a quaternion in `[x, y, z, w]` order.
+    pub rotation: [f32; 4],
+    /// The local scale.
+    pub scale: [f32; 3],
+}
+
+/// A mesh of a `GltfDocumentAsset`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GltfMeshInfo {
+    /// The name of the mesh.
+    pub name: Option<String>,
+    /// The primitives of the mesh.
+    pub primitives: Vec<GltfPrimitiveInfo>,
+}
+
+/// A primitive of a `GltfMeshInfo`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GltfPrimitiveInfo {
+    /// The index of the material.
+    pub material: Option<usize>,
+    /// The number of vertices.
+    pub vertex_count: usize,
+    /// The number of indices, if the primitive is indexed.
+    pub index_count: Option<usize>,
+    /// The bounding box of the positions.
+    pub bounds: Range<[f32; 3]>,
+    /// Whether the vertices have joints and weights for skinning.
+    pub skinned: bool,
+}
+
+/// A material of a `GltfDocumentAsset`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GltfMaterialInfo {
+    /// The name of the material.
+    pub name: Option<String>,
+    /// The base color factor.
+    pub base_color_factor: [f32; 4],
+    /// The metallic factor.
+    pub metallic_factor: f32,
+    /// The roughness factor.
+    pub roughness_factor: f32,
+    /// The emissive factor.
+    pub emissive_factor: [f32; 3],
+    /// Whether back faces are rendered.
+    pub double_sided: bool,
+    /// Whether the material has a base color texture.
+    pub textured: bool,
+}
+
+/// An animation of a `GltfDocumentAsset`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GltfAnimationInfo {
+    /// The name of the animation.
+    pub name: Option<String>,
+    /// The animated node and property of each channel.
+    pub channels: Vec<(usize, GltfAnimationProperty)>,
+}
+
+/// The property animated by a channel of a `GltfAnimationInfo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GltfAnimationProperty {
+    /// The translation of the node.
+    Translation,
+    /// The rotation of the node.
+    Rotation,
+    /// The scale of the node.
+    Scale,
+    /// The morph target weights of the mesh of the node.
+    MorphTargetWeights,
+}
+
+impl GltfDocumentAsset {
+    /// Collects the structure of an already parsed file.
+    pub fn from_gltf(gltf: &Gltf) -> Self {
+        GltfDocumentAsset {
+            scenes: gltf
+                .scenes()
+                .map(|scene| GltfSceneInfo {
+                    name: scene.name().map(String::from),
+                    nodes: scene.nodes().map(|node| node.index()).collect(),
+                })
+                .collect(),
+            default_scene: gltf.default_scene().map(|scene| scene.index()),
+            nodes: gltf.nodes().map(|node| node_info(&node)).collect(),
+            meshes: gltf
+                .meshes()
+                .map(|mesh| GltfMeshInfo {
+                    name: mesh.name().map(String::from),
+                    primitives: mesh.primitives().map(|p| primitive_info(&p)).collect(),
+                })
+                .collect(),
+            materials: gltf.materials().map(|m| material_info(&m)).collect(),
+            animations: gltf
+                .animations()
+                .map(|animation| GltfAnimationInfo {
+                    name: animation.name().map(String::from),
+                    channels: animation
+                        .channels()
+                        .map(|channel| {
+                            let target = channel.target();
+                            let property = match target.property() {
+                                Property::Translation => GltfAnimationProperty::Translation,
+                                Property::Rotation => GltfAnimationProperty::Rotation,
+                                Property::Scale => GltfAnimationProperty::Scale,
+                                Property::MorphTargetWeights => {
+                                    GltfAnimationProperty::MorphTargetWeights
+                                }
+                            };
+                            (target.node().index(), property)
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the index of the first node with the given name.
+    pub fn find_node(&self, name: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.name.as_ref().map(String::as_str) == Some(name))
+    }
+}
+
+fn node_info(node: &gltf::Node<'_>) -> GltfNodeInfo {
+    let (translation, rotation, scale) = node.transform().decomposed();
+    GltfNodeInfo {
+        name: node.name().map(String::from),
+        children: node.children().map(|child| child.index()).collect(),
+        mesh: node.mesh().map(|mesh| mesh.index()),
+        skin: node.skin().map(|skin| skin.index()),
+        translation,
+        rotation,
+        scale,
+    }
+}
+
+fn primitive_info(primitive: &gltf::Primitive<'_>) -> GltfPrimitiveInfo {
+    let bounds = primitive.bounding_box();
+    GltfPrimitiveInfo {
+        material: primitive.material().index(),
+        vertex_count: primitive
+            .get(&Semantic::Positions)
+            .map_or(0, |accessor| accessor.count()),
+        index_count: primitive.indices().map(|accessor| accessor.count()),
+        bounds: bounds.min..bounds.max,
+        skinned: primitive.get(&Semantic::Joints(0)).is_some(),
+    }
+}
+
+fn material_info(material: &gltf::Material<'_>) -> GltfMaterialInfo {
+    let pbr = material.pbr_metallic_roughness();
+    GltfMaterialInfo {
+        name: material.name().map(String::from),
+        base_color_factor: pbr.base_color_factor(),
+        metallic_factor: pbr.metallic_factor(),
+        roughness_factor: pbr.roughness_factor(),
+        emissive_factor: material.emissive_factor(),
+        double_sided: material.double_sided(),
+        textured: pbr.base_color_texture().is_some(),
+    }
+}
+
+impl Asset for GltfDocumentAsset {
+    const NAME: &'static str = "gltf::GltfDocumentAsset";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl Into<Result<ProcessingState<GltfDocumentAsset>, Error>> for GltfDocumentAsset {
+    fn into(self) -> Result<ProcessingState<GltfDocumentAsset>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+/// Loads a `GltfDocumentAsset` from a `.gltf` or `.glb` file. Only the JSON part is read, so
+/// external buffers and images aren't loaded.
+#[derive(Clone, Debug, Default)]
+pub struct GltfDocumentFormat;
+
+impl SimpleFormat<GltfDocumentAsset> for GltfDocumentFormat {
+    const NAME: &'static str = "GLTFDocument";
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<GltfDocumentAsset, Error> {
+        let gltf = Gltf::from_slice(&bytes)
+            .with_context(|_| format_err!("Failed to parse the glTF document"))?;
+        Ok(GltfDocumentAsset::from_gltf(&gltf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "name": "level", "nodes": [0] }],
+        "nodes": [
+            { "name": "root", "children": [1] },
+            { "name": "lamp", "translation": [1.0, 2.0, 3.0] }
+        ]
+    }"#;
+
+    #[test]
+    fn reads_hierarchy() {
+        let document = GltfDocumentFormat
+            .import(DOCUMENT.as_bytes().to_vec(), ())
+            .unwrap();
+        assert_eq!(document.default_scene, Some(0));
+        assert_eq!(document.scenes[0].nodes, vec![0]);
+        assert_eq!(document.nodes[0].children, vec![1]);
+        assert_eq!(document.find_node("lamp"), Some(1));
+        assert_eq!(document.nodes[1].translation, [1.0, 2.0, 3.0]);
+        assert_eq!(document.nodes[1].scale, [1.0, 1.0, 1.0]);
+    }
+}
@@ -26,10 +26,58 @@ pub enum Error {
     #[error(display = "Channel missing outputs")]
     MissingOutputs,
 
+    /// A node given as a target in `GltfSceneOptions::animation_targets` does not exist.
+    #[error(display = "Animation target node {:?} does not exist", _0)]
+    MissingAnimationTarget(String),
+
     /// Not implemented yet
     #[error(display = "Not implemented")]
     NotImplemented,
 
+    /// A sub asset addressed in the load path does not exist in the file.
+    #[error(display = "Gltf file does not contain the sub asset {:?}", _0)]
+    MissingSubAsset(String),
+
+    /// An accessor of a vertex attribute has the wrong type or exceeds its buffer view.
+    #[error(display = "Invalid accessor {}", _0)]
+    InvalidAccessor(usize),
+
+    /// An image is stored in a format without a decoder, like KTX2, or the format can't be
+    /// detected.
+    #[error(display = "Unsupported image format {:?}", _0)]
+    UnsupportedImageFormat(String),
+
+    /// The file requires an extension the loader doesn't support.
+    #[error(display = "Gltf file requires the unsupported extension {:?}", _0)]
+    UnsupportedExtension(String),
+
+    /// The file requires `KHR_draco_mesh_compression`, which is only decoded with the `draco`
+    /// feature.
+    #[cfg(not(feature = "draco"))]
+    #[error(
+        display = "Gltf file requires \"KHR_draco_mesh_compression\", enable the `draco` feature of amethyst_gltf to load it"
+    )]
+    DracoDisabled,
+
+    /// The data of a primitive compressed with `KHR_draco_mesh_compression` is invalid.
+    #[cfg(feature = "draco")]
+    #[error(display = "Invalid Draco compressed primitive")]
+    InvalidDracoData,
+
+    /// A primitive is compressed with a Draco encoding the decoder doesn't support.
+    #[cfg(feature = "draco")]
+    #[error(display = "Unsupported Draco encoding: {}", _0)]
+    UnsupportedDracoEncoding(&'static str),
+
+    /// The scene exceeds one of the `GltfSceneLimits`.
+    #[error(
+        display = "Gltf scene has {} {}, more than the limit of {}",
+        _1,
+        _0,
+        _2
+    )]
+    LimitExceeded(&'static str, u64, u64),
+
     /// A loaded glTF buffer is not of the required length.
     #[error(display = "Loaded buffer does not match required length")]
     BufferLength(gltf::json::Path),
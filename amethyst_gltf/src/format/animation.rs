@@ -5,33 +5,91 @@ use std::{collections::HashMap, fmt::Debug};
 use amethyst_error::Error;
 
 use amethyst_animation::{
-    AnimationPrefab, AnimationSetPrefab, InterpolationFunction, InterpolationPrimitive, Sampler,
-    SamplerPrimitive, TransformChannel,
+    AnimationPrefab, AnimationSetPrefab, InterpolationFunction, InterpolationPrimitive,
+    MorphWeightsChannel, Sampler, SamplerPrimitive, TransformChannel,
 };
 use amethyst_core::{math::RealField, Transform};
+use amethyst_renderer::MorphWeights;
 
-use super::Buffers;
+use super::{
+    attribute::{read_accessor_scalar, read_accessor_vec3, read_accessor_vec4},
+    Buffers,
+};
 use crate::error;
 
+/// Loads the animations of the transforms and of the morph target weights, which share the
+/// animation indices.
 pub fn load_animations<
     N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
 >(
     gltf: &gltf::Gltf,
     buffers: &Buffers,
     node_map: &HashMap<usize, usize>,
-) -> Result<AnimationSetPrefab<usize, Transform<N>>, Error> {
-    let mut prefab = AnimationSetPrefab::default();
+    targets: &HashMap<String, String>,
+) -> Result<
+    (
+        AnimationSetPrefab<usize, Transform<N>>,
+        AnimationSetPrefab<usize, MorphWeights>,
+    ),
+    Error,
+> {
+    let remap = target_indices(gltf, targets)?;
+    let mut transforms = AnimationSetPrefab::default();
+    let mut weights = AnimationSetPrefab::default();
     for animation in gltf.animations() {
-        let anim = load_animation(&animation, buffers)?;
-        if anim
+        let (mut transform_anim, mut weight_anim) = load_animation(&animation, buffers)?;
+        for sampler in &mut transform_anim.samplers {
+            if let Some(&target) = remap.get(&sampler.0) {
+                sampler.0 = target;
+            }
+        }
+        for sampler in &mut weight_anim.samplers {
+            if let Some(&target) = remap.get(&sampler.0) {
+                sampler.0 = target;
+            }
+        }
+        if transform_anim
+            .samplers
+            .iter()
+            .any(|sampler| node_map.contains_key(&sampler.0))
+        {
+            transforms
+                .animations
+                .push((animation.index(), transform_anim));
+        }
+        if weight_anim
             .samplers
             .iter()
             .any(|sampler| node_map.contains_key(&sampler.0))
         {
-            prefab.animations.push((animation.index(), anim));
+            weights.animations.push((animation.index(), weight_anim));
         }
     }
-    Ok(prefab)
+    Ok((transforms, weights))
+}
+
+/// Resolves the node names of `GltfSceneOptions::animation_targets` to node indices.
+fn target_indices(
+    gltf: &gltf::Gltf,
+    targets: &HashMap<String, String>,
+) -> Result<HashMap<usize, usize>, Error> {
+    if targets.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let nodes = gltf
+        .nodes()
+        .filter_map(|node| node.name().map(|name| (name, node.index())))
+        .collect::<HashMap<_, _>>();
+    let mut remap = HashMap::new();
+    for (source, target) in targets {
+        if let Some(&source) = nodes.get(&source[..]) {
+            let target = *nodes
+                .get(&target[..])
+                .ok_or_else(|| error::Error::MissingAnimationTarget(target.clone()))?;
+            remap.insert(source, target);
+        }
+    }
+    Ok(remap)
 }
 
 fn load_animation<
@@ -39,13 +97,22 @@ fn load_animation<
 >(
     animation: &gltf::Animation<'_>,
     buffers: &Buffers,
-) -> Result<AnimationPrefab<Transform<N>>, Error> {
-    let mut a = AnimationPrefab::default();
-    a.samplers = animation
-        .channels()
-        .map(|ref channel| load_channel(channel, buffers))
-        .collect::<Result<Vec<_>, Error>>()?;
-    Ok(a)
+) -> Result<(AnimationPrefab<Transform<N>>, AnimationPrefab<MorphWeights>), Error> {
+    let mut transforms = AnimationPrefab::default();
+    let mut weights = AnimationPrefab::default();
+    for channel in animation.channels() {
+        match load_channel(&channel, buffers)? {
+            LoadedChannel::Transform(sampler) => transforms.samplers.push(sampler),
+            LoadedChannel::Weights(samplers) => weights.samplers.extend(samplers),
+        }
+    }
+    Ok((transforms, weights))
+}
+
+enum LoadedChannel<N: RealField> {
+    Transform((usize, TransformChannel, Sampler<SamplerPrimitive<N>>)),
+    /// A sampler per morph target, glTF animates all weights of a node in one channel.
+    Weights(Vec<(usize, MorphWeightsChannel, Sampler<SamplerPrimitive<f32>>)>),
 }
 
 fn load_channel<
@@ -53,31 +120,30 @@ fn load_channel<
 >(
     channel: &gltf::animation::Channel<'_>,
     buffers: &Buffers,
-) -> Result<(usize, TransformChannel, Sampler<SamplerPrimitive<N>>), Error> {
-    use gltf::animation::util::ReadOutputs::*;
+) -> Result<LoadedChannel<N>, Error> {
+    use gltf::animation::Property;
     let sampler = channel.sampler();
     let target = channel.target();
 
-    let reader = channel.reader(|buffer| buffers.buffer(&buffer));
-    let input = reader
-        .read_inputs()
-        .ok_or(error::Error::MissingInputs)?
-        .collect();
+    // Read with the attribute readers, which resolve sparse accessors.
+    let input = read_accessor_scalar(&sampler.input(), buffers)?;
+    let output = sampler.output();
     let node_index = target.node().index();
 
-    match reader.read_outputs().ok_or(error::Error::MissingOutputs)? {
-        Translations(translations) => Ok((
+    match target.path() {
+        Property::Translation => Ok(LoadedChannel::Transform((
             node_index,
             TransformChannel::Translation,
             Sampler {
                 input,
                 function: map_interpolation_type(&sampler.interpolation()),
-                output: translations
+                output: read_accessor_vec3(&output, buffers)?
+                    .into_iter()
                     .map(|t| [t[0].into(), t[1].into(), t[2].into()].into())
                     .collect(),
             },
-        )),
-        Rotations(rotations) => {
+        ))),
+        Property::Rotation => {
             let ty = map_interpolation_type(&sampler.interpolation());
             let ty = if ty == InterpolationFunction::Linear {
                 InterpolationFunction::SphericalLinear
@@ -85,32 +151,78 @@ fn load_channel<
                 ty
             };
             // gltf quat format: [x, y, z, w], our quat format: [w, x, y, z]
-            Ok((
+            Ok(LoadedChannel::Transform((
                 node_index,
                 TransformChannel::Rotation,
                 Sampler {
                     input,
                     function: ty,
-                    output: rotations
-                        .into_f32()
+                    output: read_accessor_vec4(&output, buffers)?
+                        .into_iter()
                         .map(|q| [q[3].into(), q[0].into(), q[1].into(), q[2].into()].into())
                         .collect(),
                 },
-            ))
+            )))
         }
-        Scales(scales) => Ok((
+        Property::Scale => Ok(LoadedChannel::Transform((
             node_index,
             TransformChannel::Scale,
             Sampler {
                 input,
                 function: map_interpolation_type(&sampler.interpolation()),
-                output: scales
+                output: read_accessor_vec3(&output, buffers)?
+                    .into_iter()
                     .map(|s| [s[0].into(), s[1].into(), s[2].into()].into())
                     .collect(),
             },
-        )),
-        MorphTargetWeights(_) => Err(error::Error::NotImplemented.into()),
+        ))),
+        Property::MorphTargetWeights => {
+            let function = map_interpolation_type(&sampler.interpolation());
+            let weights = read_accessor_scalar(&output, buffers)?;
+            Ok(LoadedChannel::Weights(
+                split_weights(
+                    &input,
+                    &weights,
+                    function == InterpolationFunction::CubicSpline,
+                )
+                .into_iter()
+                .enumerate()
+                .map(|(target, output)| {
+                    (
+                        node_index,
+                        MorphWeightsChannel::Weight(target),
+                        Sampler {
+                            input: input.clone(),
+                            function: function.clone(),
+                            output,
+                        },
+                    )
+                })
+                .collect(),
+            ))
+        }
+    }
+}
+
+/// Splits the weights of all morph targets into the output of each target.
+///
+/// The weights of all targets are stored together for each key, and cubic splines have the
+/// in-tangents, values and out-tangents of all targets after each other.
+fn split_weights(input: &[f32], weights: &[f32], cubic: bool) -> Vec<Vec<SamplerPrimitive<f32>>> {
+    let groups = input.len() * if cubic { 3 } else { 1 };
+    if groups == 0 {
+        return Vec::new();
     }
+    let targets = weights.len() / groups;
+    (0..targets)
+        .map(|target| {
+            weights
+                .chunks(targets)
+                .take(groups)
+                .map(|group| SamplerPrimitive::Scalar(group[target]))
+                .collect()
+        })
+        .collect()
 }
 
 fn map_interpolation_type<T>(ty: &gltf::animation::Interpolation) -> InterpolationFunction<T>
@@ -126,3 +238,32 @@ where
         CatmullRomSpline => InterpolationFunction::CatmullRomSpline,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalars(output: &[SamplerPrimitive<f32>]) -> Vec<f32> {
+        output
+            .iter()
+            .map(|value| match *value {
+                SamplerPrimitive::Scalar(value) => value,
+                _ => panic!("Expected a scalar"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_weights_by_target() {
+        let outputs = split_weights(&[0.0, 1.0], &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6], false);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(scalars(&outputs[0]), vec![0.1, 0.4]);
+        assert_eq!(scalars(&outputs[2]), vec![0.3, 0.6]);
+
+        let weights = (0..12).map(|i| i as f32).collect::<Vec<_>>();
+        let outputs = split_weights(&[0.0, 1.0], &weights, true);
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(scalars(&outputs[1]), vec![1.0, 3.0, 5.0, 7.0, 9.0, 11.0]);
+        assert!(split_weights(&[], &weights, false).is_empty());
+    }
+}
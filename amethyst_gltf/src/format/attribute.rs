@@ -0,0 +1,441 @@
+//! Reading of vertex attributes, including the integer types allowed by `KHR_mesh_quantization`.
+//!
+//! Quantized positions are usually stored in a small integer range, with a node transform scaling
+//! them back to their original size. That transform ends up in the `Transform` of the node, so
+//! the positions are only converted to floats here.
+//!
+//! Sparse accessors are resolved by replacing the elements they list in the data of the buffer
+//! view, which the `gltf` readers don't do.
+
+use amethyst_error::Error;
+use gltf::{
+    accessor::{sparse::IndexType, DataType, Dimensions},
+    mesh::Semantic,
+};
+
+#[cfg(feature = "draco")]
+use super::draco::DracoPrimitive;
+use super::Buffers;
+use crate::error;
+
+/// Reads the attributes of a primitive, from its accessors, or from its compressed data if it
+/// has the `KHR_draco_mesh_compression` extension and the `draco` feature is enabled.
+pub struct PrimitiveReader<'a> {
+    primitive: &'a gltf::Primitive<'a>,
+    buffers: &'a Buffers,
+    #[cfg(feature = "draco")]
+    draco: Option<DracoPrimitive>,
+}
+
+impl<'a> PrimitiveReader<'a> {
+    /// Creates a reader of the primitive with the given index of the mesh, decoding it if it is
+    /// compressed.
+    #[cfg_attr(not(feature = "draco"), allow(unused_variables))]
+    pub fn new(
+        mesh: &gltf::Mesh<'_>,
+        index: usize,
+        primitive: &'a gltf::Primitive<'a>,
+        buffers: &'a Buffers,
+    ) -> Result<Self, Error> {
+        Ok(PrimitiveReader {
+            primitive,
+            buffers,
+            #[cfg(feature = "draco")]
+            draco: buffers.draco_primitive(mesh.index(), index)?,
+        })
+    }
+
+    /// Reads the vertex indices, `None` if the primitive isn't indexed.
+    pub fn indices(&self) -> Option<Vec<u32>> {
+        #[cfg(feature = "draco")]
+        {
+            if let Some(ref draco) = self.draco {
+                return Some(draco.indices().to_vec());
+            }
+        }
+        self.primitive
+            .reader(|buffer| self.buffers.buffer(&buffer))
+            .read_indices()
+            .map(|indices| indices.into_u32().collect())
+    }
+
+    /// Reads a two component attribute.
+    pub fn vec2(&self, semantic: &Semantic) -> Result<Option<Vec<[f32; 2]>>, Error> {
+        Ok(self
+            .attribute(semantic, 2)?
+            .map(|values| values.chunks(2).map(|v| [v[0], v[1]]).collect()))
+    }
+
+    /// Reads a three component attribute.
+    pub fn vec3(&self, semantic: &Semantic) -> Result<Option<Vec<[f32; 3]>>, Error> {
+        Ok(self
+            .attribute(semantic, 3)?
+            .map(|values| values.chunks(3).map(|v| [v[0], v[1], v[2]]).collect()))
+    }
+
+    /// Reads a four component attribute.
+    pub fn vec4(&self, semantic: &Semantic) -> Result<Option<Vec<[f32; 4]>>, Error> {
+        Ok(self
+            .attribute(semantic, 4)?
+            .map(|values| values.chunks(4).map(|v| [v[0], v[1], v[2], v[3]]).collect()))
+    }
+
+    /// Reads a color attribute, such as `COLOR_0`. Colors may have three or four components,
+    /// RGB colors get an alpha of 1.
+    pub fn color(&self, semantic: &Semantic) -> Result<Option<Vec<[f32; 4]>>, Error> {
+        let components = match self.components(semantic) {
+            Some(components) => components,
+            None => return Ok(None),
+        };
+        let values = match self.attribute(semantic, components)? {
+            Some(values) => values,
+            None => return Ok(None),
+        };
+        let colors = if components == 3 {
+            values.chunks(3).map(|v| [v[0], v[1], v[2], 1.0]).collect()
+        } else {
+            values.chunks(4).map(|v| [v[0], v[1], v[2], v[3]]).collect()
+        };
+        Ok(Some(colors))
+    }
+
+    /// Reads the joint ids of the skin.
+    pub fn joints(&self) -> Result<Option<Vec<[u16; 4]>>, Error> {
+        #[cfg(feature = "draco")]
+        {
+            if self.draco.is_some() {
+                return Ok(self.vec4(&Semantic::Joints(0))?.map(|joints| {
+                    joints
+                        .into_iter()
+                        .map(|j| [j[0] as u16, j[1] as u16, j[2] as u16, j[3] as u16])
+                        .collect()
+                }));
+            }
+        }
+        Ok(self
+            .primitive
+            .reader(|buffer| self.buffers.buffer(&buffer))
+            .read_joints(0)
+            .map(|joints| joints.into_u16().collect()))
+    }
+
+    /// Reads the joint weights of the skin.
+    pub fn weights(&self) -> Result<Option<Vec<[f32; 4]>>, Error> {
+        #[cfg(feature = "draco")]
+        {
+            if self.draco.is_some() {
+                return self.vec4(&Semantic::Weights(0));
+            }
+        }
+        Ok(self
+            .primitive
+            .reader(|buffer| self.buffers.buffer(&buffer))
+            .read_weights(0)
+            .map(|weights| weights.into_f32().collect()))
+    }
+
+    /// The number of components of the attribute, `None` if the primitive doesn't have it.
+    fn components(&self, semantic: &Semantic) -> Option<usize> {
+        #[cfg(feature = "draco")]
+        {
+            if let Some((components, _)) = self
+                .draco
+                .as_ref()
+                .and_then(|draco| draco.attribute(semantic))
+            {
+                return Some(components);
+            }
+        }
+        self.primitive
+            .get(semantic)
+            .map(|accessor| multiplicity(accessor.dimensions()))
+    }
+
+    fn attribute(&self, semantic: &Semantic, components: usize) -> Result<Option<Vec<f32>>, Error> {
+        #[cfg(feature = "draco")]
+        {
+            if let Some((decoded, values)) = self
+                .draco
+                .as_ref()
+                .and_then(|draco| draco.attribute(semantic))
+            {
+                if decoded != components {
+                    return Err(error::Error::InvalidDracoData.into());
+                }
+                return Ok(Some(values.to_vec()));
+            }
+        }
+        match self.primitive.get(semantic) {
+            Some(accessor) => read_accessor(&accessor, self.buffers, components).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Checks if the attribute is stored as integers.
+pub fn is_quantized(primitive: &gltf::Primitive<'_>, semantic: &Semantic) -> bool {
+    primitive
+        .get(semantic)
+        .map_or(false, |accessor| accessor.data_type() != DataType::F32)
+}
+
+/// Reads a one component accessor, such as the key times or weights of an animation sampler.
+pub fn read_accessor_scalar(
+    accessor: &gltf::Accessor<'_>,
+    buffers: &Buffers,
+) -> Result<Vec<f32>, Error> {
+    read_accessor(accessor, buffers, 1)
+}
+
+/// Reads a three component accessor, such as the offsets of a morph target.
+pub fn read_accessor_vec3(
+    accessor: &gltf::Accessor<'_>,
+    buffers: &Buffers,
+) -> Result<Vec<[f32; 3]>, Error> {
+    Ok(read_accessor(accessor, buffers, 3)?
+        .chunks(3)
+        .map(|v| [v[0], v[1], v[2]])
+        .collect())
+}
+
+/// Reads a four component accessor, such as the rotations of an animation sampler.
+pub fn read_accessor_vec4(
+    accessor: &gltf::Accessor<'_>,
+    buffers: &Buffers,
+) -> Result<Vec<[f32; 4]>, Error> {
+    Ok(read_accessor(accessor, buffers, 4)?
+        .chunks(4)
+        .map(|v| [v[0], v[1], v[2], v[3]])
+        .collect())
+}
+
+fn read_accessor(
+    accessor: &gltf::Accessor<'_>,
+    buffers: &Buffers,
+    components: usize,
+) -> Result<Vec<f32>, Error> {
+    let invalid = || error::Error::InvalidAccessor(accessor.index());
+    if multiplicity(accessor.dimensions()) != components {
+        return Err(invalid().into());
+    }
+
+    let data_type = accessor.data_type();
+    let size = component_size(data_type);
+    let view = accessor.view();
+    let data = buffers.view(&view).ok_or_else(invalid)?;
+    let stride = view.stride().unwrap_or(components * size);
+    let normalized = accessor.normalized();
+
+    let mut values = Vec::with_capacity(accessor.count() * components);
+    for i in 0..accessor.count() {
+        let start = accessor.offset() + i * stride;
+        let element = data
+            .get(start..start + components * size)
+            .ok_or_else(invalid)?;
+        values.extend(
+            element
+                .chunks(size)
+                .map(|bytes| decode(bytes, data_type, normalized)),
+        );
+    }
+
+    if let Some(sparse) = accessor.sparse() {
+        let indices = sparse.indices();
+        let index_size = match indices.index_type() {
+            IndexType::U8 => 1,
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        };
+        let index_data = buffers.view(&indices.view()).ok_or_else(invalid)?;
+        let sparse_values = sparse.values();
+        let value_data = buffers.view(&sparse_values.view()).ok_or_else(invalid)?;
+        let element_size = components * size;
+        for i in 0..sparse.count() as usize {
+            let start = indices.offset() as usize + i * index_size;
+            let index = index_data
+                .get(start..start + index_size)
+                .ok_or_else(invalid)?
+                .iter()
+                .rev()
+                .fold(0, |index, &byte| index << 8 | usize::from(byte));
+            let start = sparse_values.offset() as usize + i * element_size;
+            let element = value_data
+                .get(start..start + element_size)
+                .ok_or_else(invalid)?;
+            let target = values
+                .get_mut(index * components..(index + 1) * components)
+                .ok_or_else(invalid)?;
+            for (value, bytes) in target.iter_mut().zip(element.chunks(size)) {
+                *value = decode(bytes, data_type, normalized);
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn multiplicity(dimensions: Dimensions) -> usize {
+    match dimensions {
+        Dimensions::Scalar => 1,
+        Dimensions::Vec2 => 2,
+        Dimensions::Vec3 => 3,
+        Dimensions::Vec4 | Dimensions::Mat2 => 4,
+        Dimensions::Mat3 => 9,
+        Dimensions::Mat4 => 16,
+    }
+}
+
+fn component_size(data_type: DataType) -> usize {
+    match data_type {
+        DataType::I8 | DataType::U8 => 1,
+        DataType::I16 | DataType::U16 => 2,
+        DataType::U32 | DataType::F32 => 4,
+    }
+}
+
+/// Decodes a little endian component, normalized integers are mapped to `[0, 1]` or `[-1, 1]`.
+fn decode(bytes: &[u8], data_type: DataType, normalized: bool) -> f32 {
+    let u16_value = || u16::from(bytes[0]) | u16::from(bytes[1]) << 8;
+    let u32_value = || {
+        u32::from(bytes[0])
+            | u32::from(bytes[1]) << 8
+            | u32::from(bytes[2]) << 16
+            | u32::from(bytes[3]) << 24
+    };
+    match data_type {
+        DataType::I8 => {
+            let value = f32::from(bytes[0] as i8);
+            if normalized {
+                (value / 127.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        DataType::U8 => {
+            let value = f32::from(bytes[0]);
+            if normalized {
+                value / 255.0
+            } else {
+                value
+            }
+        }
+        DataType::I16 => {
+            let value = f32::from(u16_value() as i16);
+            if normalized {
+                (value / 32767.0).max(-1.0)
+            } else {
+                value
+            }
+        }
+        DataType::U16 => {
+            let value = f32::from(u16_value());
+            if normalized {
+                value / 65535.0
+            } else {
+                value
+            }
+        }
+        DataType::U32 => u32_value() as f32,
+        DataType::F32 => f32::from_bits(u32_value()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, read_accessor_scalar, read_accessor_vec3};
+    use crate::format::Buffers;
+    use gltf::{accessor::DataType, Gltf};
+
+    // Modelled on the SimpleSparseAccessor sample: a base buffer view with sparse substitutes.
+    const SPARSE_DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": 88 }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 48 },
+            { "buffer": 0, "byteOffset": 48, "byteLength": 4 },
+            { "buffer": 0, "byteOffset": 52, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 76, "byteLength": 12 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 4,
+                "type": "VEC3",
+                "sparse": {
+                    "count": 2,
+                    "indices": { "bufferView": 1, "componentType": 5123 },
+                    "values": { "bufferView": 2 }
+                }
+            },
+            {
+                "bufferView": 3,
+                "componentType": 5126,
+                "count": 3,
+                "type": "SCALAR",
+                "sparse": {
+                    "count": 1,
+                    "indices": { "bufferView": 1, "byteOffset": 1, "componentType": 5121 },
+                    "values": { "bufferView": 2, "byteOffset": 8 }
+                }
+            }
+        ]
+    }"#;
+
+    fn sparse_buffers() -> Buffers {
+        let mut data = Vec::new();
+        let mut push_f32 = |value: f32| {
+            let bits = value.to_bits();
+            data.extend((0..4).map(|i| (bits >> (i * 8)) as u8));
+        };
+        for i in 0..12 {
+            push_f32(i as f32);
+        }
+        for value in &[10.0, 11.0, 12.0, 13.0, 14.0, 15.0] {
+            push_f32(*value);
+        }
+        for value in &[0.0, 0.5, 1.0] {
+            push_f32(*value);
+        }
+        // The u16 indices 1 and 3, whose second byte is read as the u8 index 0 by the scalar
+        // accessor.
+        data.splice(48..48, vec![1, 0, 3, 0]);
+        Buffers::from_data(vec![data])
+    }
+
+    #[test]
+    fn resolves_sparse_accessors() {
+        let gltf = Gltf::from_slice(SPARSE_DOCUMENT.as_bytes()).unwrap();
+        let buffers = sparse_buffers();
+        let accessors = gltf.accessors().collect::<Vec<_>>();
+        assert_eq!(
+            read_accessor_vec3(&accessors[0], &buffers).unwrap(),
+            vec![
+                [0.0, 1.0, 2.0],
+                [10.0, 11.0, 12.0],
+                [6.0, 7.0, 8.0],
+                [13.0, 14.0, 15.0],
+            ]
+        );
+        assert_eq!(
+            read_accessor_scalar(&accessors[1], &buffers).unwrap(),
+            vec![12.0, 0.5, 1.0]
+        );
+    }
+
+    #[test]
+    fn decodes_normalized_integers() {
+        assert_eq!(decode(&[127], DataType::I8, true), 1.0);
+        assert_eq!(decode(&[0x80], DataType::I8, true), -1.0);
+        assert_eq!(decode(&[255], DataType::U8, true), 1.0);
+        assert_eq!(decode(&[0xff, 0x7f], DataType::I16, true), 1.0);
+        assert_eq!(decode(&[0x01, 0x80], DataType::I16, true), -1.0);
+        assert_eq!(decode(&[0xff, 0xff], DataType::U16, true), 1.0);
+    }
+
+    #[test]
+    fn decodes_plain_values() {
+        assert_eq!(decode(&[0xfe, 0xff], DataType::I16, false), -2.0);
+        assert_eq!(decode(&[0x00, 0x01], DataType::U16, false), 256.0);
+        assert_eq!(decode(&[0, 0, 0x80, 0x3f], DataType::F32, false), 1.0);
+    }
+}
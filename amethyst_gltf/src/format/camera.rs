@@ -0,0 +1,72 @@
+use amethyst_core::math::{Orthographic3, Perspective3};
+use amethyst_renderer::CameraPrefab;
+use gltf::camera::Projection;
+
+/// The far plane of perspective cameras with an infinite projection, which the `CameraPrefab`
+/// can't represent.
+const DEFAULT_ZFAR: f32 = 2000.0;
+
+/// Converts the projection of a glTF camera.
+///
+/// Perspective cameras without an aspect ratio get a square one, which should be replaced with
+/// the aspect ratio of the window.
+pub fn load_camera(camera: &gltf::Camera<'_>) -> CameraPrefab {
+    match camera.projection() {
+        Projection::Orthographic(ortho) => CameraPrefab::Orthographic(Orthographic3::new(
+            -ortho.xmag(),
+            ortho.xmag(),
+            -ortho.ymag(),
+            ortho.ymag(),
+            ortho.znear(),
+            ortho.zfar(),
+        )),
+        Projection::Perspective(persp) => CameraPrefab::Perspective(Perspective3::new(
+            persp.aspect_ratio().unwrap_or(1.0),
+            persp.yfov(),
+            persp.znear(),
+            persp.zfar().unwrap_or(DEFAULT_ZFAR),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gltf::Gltf;
+
+    const DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "cameras": [
+            {
+                "type": "perspective",
+                "perspective": { "aspectRatio": 1.5, "yfov": 0.8, "znear": 0.1 }
+            },
+            {
+                "type": "orthographic",
+                "orthographic": { "xmag": 2.0, "ymag": 1.0, "znear": 0.5, "zfar": 10.0 }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn converts_projections() {
+        let gltf = Gltf::from_slice(DOCUMENT.as_bytes()).unwrap();
+        let cameras = gltf.cameras().map(|c| load_camera(&c)).collect::<Vec<_>>();
+        match cameras[0] {
+            CameraPrefab::Perspective(persp) => {
+                assert_eq!(persp.aspect(), 1.5);
+                assert_eq!(persp.fovy(), 0.8);
+                assert_eq!(persp.zfar(), DEFAULT_ZFAR);
+            }
+            _ => panic!("Expected a perspective camera"),
+        }
+        match cameras[1] {
+            CameraPrefab::Orthographic(ortho) => {
+                assert_eq!(ortho.left(), -2.0);
+                assert_eq!(ortho.top(), 1.0);
+                assert_eq!(ortho.zfar(), 10.0);
+            }
+            _ => panic!("Expected an orthographic camera"),
+        }
+    }
+}
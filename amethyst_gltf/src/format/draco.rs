@@ -0,0 +1,1044 @@
+//! Primitives compressed with the `KHR_draco_mesh_compression` extension, behind the `draco`
+//! feature.
+//!
+//! The decoder reads the sequential encoding of Draco 2.x, which the Draco encoder writes at its
+//! highest speed setting, e.g. `draco_encoder -cl 0` or `gltf-pipeline -d --draco.compressionLevel
+//! 0`. Connectivity compressed with Edgebreaker and the mesh prediction schemes, like the
+//! parallelogram prediction, fail with `Error::UnsupportedDracoEncoding`.
+
+use std::{collections::HashMap, ops::Range};
+
+use amethyst_error::Error;
+use gltf::mesh::Semantic;
+use serde_json::Value;
+
+use crate::error;
+
+/// The name of the extension.
+pub const EXTENSION: &str = "KHR_draco_mesh_compression";
+
+const METADATA_FLAG: u16 = 0x8000;
+
+/// The counts of faces, points and values are limited, so invalid data can't request huge
+/// allocations.
+const MAX_VALUES: u64 = 1 << 28;
+
+// Attribute decoders
+const DECODER_GENERIC: u8 = 0;
+const DECODER_INTEGER: u8 = 1;
+const DECODER_QUANTIZATION: u8 = 2;
+const DECODER_NORMALS: u8 = 3;
+
+// Prediction schemes and transforms
+const PREDICTION_NONE: i8 = -2;
+const PREDICTION_DIFFERENCE: i8 = 0;
+const TRANSFORM_DELTA: i8 = 0;
+const TRANSFORM_WRAP: i8 = 1;
+const TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED: i8 = 3;
+
+// Data types
+const DT_INT8: u8 = 1;
+const DT_UINT8: u8 = 2;
+const DT_INT16: u8 = 3;
+const DT_UINT16: u8 = 4;
+const DT_INT32: u8 = 5;
+const DT_UINT32: u8 = 6;
+const DT_INT64: u8 = 7;
+const DT_UINT64: u8 = 8;
+const DT_FLOAT32: u8 = 9;
+const DT_FLOAT64: u8 = 10;
+const DT_BOOL: u8 = 11;
+
+/// The compressed data of a primitive, from its `KHR_draco_mesh_compression` extension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DracoExtension {
+    /// The index of the buffer with the compressed data.
+    pub buffer: usize,
+    /// The range of the compressed data in the buffer.
+    pub range: Range<usize>,
+    /// The Draco ids of the compressed attributes, by their glTF name like `POSITION`.
+    attributes: HashMap<String, u32>,
+}
+
+impl DracoExtension {
+    fn read(primitive: &Value, views: &[Value]) -> Option<Self> {
+        let extension = primitive.get("extensions")?.get(EXTENSION)?;
+        let view = views.get(extension.get("bufferView")?.as_u64()? as usize)?;
+        let offset = view.get("byteOffset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let length = view.get("byteLength")?.as_u64()? as usize;
+        let attributes = extension
+            .get("attributes")?
+            .as_object()?
+            .iter()
+            .filter_map(|(name, id)| Some((name.clone(), id.as_u64()? as u32)))
+            .collect();
+        Some(DracoExtension {
+            buffer: view.get("buffer")?.as_u64()? as usize,
+            range: offset..offset + length,
+            attributes,
+        })
+    }
+}
+
+/// Finds the compressed primitives, by the index of their mesh and their index in the mesh.
+pub fn draco_primitives(root: &Value) -> HashMap<(usize, usize), DracoExtension> {
+    let views = root
+        .get("bufferViews")
+        .and_then(Value::as_array)
+        .map_or(&[][..], |views| &views[..]);
+    let meshes = root
+        .get("meshes")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten();
+    let mut primitives = HashMap::new();
+    for (mesh, info) in meshes.enumerate() {
+        let mesh_primitives = info
+            .get("primitives")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten();
+        for (index, primitive) in mesh_primitives.enumerate() {
+            if let Some(extension) = DracoExtension::read(primitive, views) {
+                primitives.insert((mesh, index), extension);
+            }
+        }
+    }
+    primitives
+}
+
+/// A decoded primitive, with the values of its attributes converted to floats.
+#[derive(Debug, PartialEq)]
+pub struct DracoPrimitive {
+    indices: Vec<u32>,
+    /// The number of components and the values of the attributes, by their glTF name.
+    attributes: HashMap<String, (usize, Vec<f32>)>,
+}
+
+impl DracoPrimitive {
+    /// The vertex indices of the triangles.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// The number of components and the values of an attribute, if it is compressed.
+    pub fn attribute(&self, semantic: &Semantic) -> Option<(usize, &[f32])> {
+        let name = semantic_name(semantic)?;
+        self.attributes
+            .get(&name)
+            .map(|(components, values)| (*components, &values[..]))
+    }
+}
+
+// `Semantic::Extras` only exists with the `extras` feature of `gltf`.
+#[allow(unreachable_patterns)]
+fn semantic_name(semantic: &Semantic) -> Option<String> {
+    Some(match *semantic {
+        Semantic::Positions => "POSITION".to_string(),
+        Semantic::Normals => "NORMAL".to_string(),
+        Semantic::Tangents => "TANGENT".to_string(),
+        Semantic::Colors(set) => format!("COLOR_{}", set),
+        Semantic::TexCoords(set) => format!("TEXCOORD_{}", set),
+        Semantic::Joints(set) => format!("JOINTS_{}", set),
+        Semantic::Weights(set) => format!("WEIGHTS_{}", set),
+        _ => return None,
+    })
+}
+
+/// Decodes the compressed data of a primitive.
+pub fn decode(data: &[u8], extension: &DracoExtension) -> Result<DracoPrimitive, Error> {
+    let (indices, mut decoded) = decode_mesh(data)?;
+    let attributes = extension
+        .attributes
+        .iter()
+        .map(|(name, id)| {
+            decoded
+                .remove(id)
+                .map(|attribute| (name.clone(), attribute))
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(DracoPrimitive {
+        indices,
+        attributes,
+    })
+}
+
+fn invalid() -> Error {
+    error::Error::InvalidDracoData.into()
+}
+
+fn unsupported(encoding: &'static str) -> Error {
+    error::Error::UnsupportedDracoEncoding(encoding).into()
+}
+
+/// Reads the little endian values of a Draco bitstream.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    version: (u8, u8),
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(invalid)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i8(&mut self) -> Result<i8, Error> {
+        Ok(self.u8()? as i8)
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from(bytes[0]) | u16::from(bytes[1]) << 8)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(le_u32(self.bytes(4)?))
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    /// A LEB128 encoded integer.
+    fn varint(&mut self) -> Result<u64, Error> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid())
+    }
+
+    /// A count of at most `MAX_VALUES`.
+    fn count(&mut self) -> Result<usize, Error> {
+        let count = if self.version >= (2, 2) {
+            self.varint()?
+        } else {
+            u64::from(self.u32()?)
+        };
+        checked_count(count)
+    }
+}
+
+fn checked_count(count: u64) -> Result<usize, Error> {
+    if count <= MAX_VALUES {
+        Ok(count as usize)
+    } else {
+        Err(invalid())
+    }
+}
+
+fn le_u32(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |value, &byte| value << 8 | u32::from(byte))
+}
+
+/// Decodes the indices and the attributes by their Draco id.
+fn decode_mesh(data: &[u8]) -> Result<(Vec<u32>, HashMap<u32, (usize, Vec<f32>)>), Error> {
+    let mut reader = Reader {
+        data,
+        pos: 0,
+        version: (0, 0),
+    };
+    if reader.bytes(5)? != b"DRACO" {
+        return Err(invalid());
+    }
+    reader.version = (reader.u8()?, reader.u8()?);
+    if reader.version.0 != 2 {
+        return Err(unsupported("bitstream versions other than 2.x"));
+    }
+    if reader.u8()? != 1 {
+        return Err(unsupported("point clouds"));
+    }
+    match reader.u8()? {
+        0 => {}
+        1 => return Err(unsupported("Edgebreaker connectivity")),
+        _ => return Err(invalid()),
+    }
+    if reader.u16()? & METADATA_FLAG != 0 {
+        skip_geometry_metadata(&mut reader)?;
+    }
+
+    let (indices, num_points) = decode_connectivity(&mut reader)?;
+    let attributes = decode_attributes(&mut reader, num_points)?;
+    Ok((indices, attributes))
+}
+
+fn skip_geometry_metadata(reader: &mut Reader<'_>) -> Result<(), Error> {
+    for _ in 0..reader.varint()? {
+        reader.varint()?;
+        skip_metadata(reader, 0)?;
+    }
+    skip_metadata(reader, 0)
+}
+
+fn skip_metadata(reader: &mut Reader<'_>, depth: usize) -> Result<(), Error> {
+    if depth > 32 {
+        return Err(invalid());
+    }
+    for _ in 0..reader.varint()? {
+        let name = reader.u8()?;
+        reader.bytes(usize::from(name))?;
+        let size = checked_count(reader.varint()?)?;
+        reader.bytes(size)?;
+    }
+    for _ in 0..reader.varint()? {
+        let name = reader.u8()?;
+        reader.bytes(usize::from(name))?;
+        skip_metadata(reader, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Decodes the faces of the sequential encoding, returning the indices and number of points.
+fn decode_connectivity(reader: &mut Reader<'_>) -> Result<(Vec<u32>, usize), Error> {
+    let num_faces = reader.count()?;
+    let num_points = reader.count()?;
+    let num_indices = checked_count(num_faces as u64 * 3)?;
+    let mut indices = Vec::with_capacity(num_indices);
+    if num_faces > 0 && num_points > 0 {
+        match reader.u8()? {
+            // Delta coded indices
+            0 => {
+                let mut last = 0i64;
+                for symbol in decode_symbols(reader, num_indices, 1)? {
+                    let diff = i64::from(symbol >> 1);
+                    last += if symbol & 1 == 1 { -diff } else { diff };
+                    if last < 0 {
+                        return Err(invalid());
+                    }
+                    indices.push(last as u32);
+                }
+            }
+            1 => {
+                for _ in 0..num_indices {
+                    let index = if num_points < 1 << 8 {
+                        u32::from(reader.u8()?)
+                    } else if num_points < 1 << 16 {
+                        u32::from(reader.u16()?)
+                    } else if num_points < 1 << 21 && reader.version >= (2, 2) {
+                        reader.varint()? as u32
+                    } else {
+                        reader.u32()?
+                    };
+                    indices.push(index);
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    if indices.iter().any(|index| *index as usize >= num_points) {
+        return Err(invalid());
+    }
+    Ok((indices, num_points))
+}
+
+struct AttributeInfo {
+    id: u32,
+    data_type: u8,
+    components: usize,
+    normalized: bool,
+    decoder: u8,
+}
+
+/// The values of an attribute before the transform of its decoder.
+enum Portable {
+    Floats(Vec<f32>),
+    Integers(Vec<i32>),
+}
+
+fn decode_attributes(
+    reader: &mut Reader<'_>,
+    num_points: usize,
+) -> Result<HashMap<u32, (usize, Vec<f32>)>, Error> {
+    let num_decoders = reader.u8()?;
+    let mut decoders = Vec::with_capacity(usize::from(num_decoders));
+    for _ in 0..num_decoders {
+        let mut attributes = Vec::new();
+        for _ in 0..reader.varint()? {
+            let _kind = reader.u8()?;
+            let data_type = reader.u8()?;
+            let components = usize::from(reader.u8()?);
+            let normalized = reader.u8()? != 0;
+            let id = reader.varint()? as u32;
+            if components == 0 {
+                return Err(invalid());
+            }
+            attributes.push(AttributeInfo {
+                id,
+                data_type,
+                components,
+                normalized,
+                decoder: 0,
+            });
+        }
+        for attribute in &mut attributes {
+            attribute.decoder = reader.u8()?;
+        }
+        decoders.push(attributes);
+    }
+
+    let mut decoded = HashMap::new();
+    for attributes in decoders {
+        let mut portable = Vec::with_capacity(attributes.len());
+        for attribute in &attributes {
+            portable.push(decode_portable(reader, attribute, num_points)?);
+        }
+        for (attribute, values) in attributes.iter().zip(portable) {
+            let values = transform_portable(reader, attribute, values)?;
+            decoded.insert(attribute.id, (output_components(attribute), values));
+        }
+    }
+    Ok(decoded)
+}
+
+fn output_components(attribute: &AttributeInfo) -> usize {
+    match attribute.decoder {
+        DECODER_NORMALS => 3,
+        _ => attribute.components,
+    }
+}
+
+fn decode_portable(
+    reader: &mut Reader<'_>,
+    attribute: &AttributeInfo,
+    num_points: usize,
+) -> Result<Portable, Error> {
+    match attribute.decoder {
+        DECODER_GENERIC => {
+            let size = data_type_size(attribute.data_type)?;
+            let num_values = checked_count(num_points as u64 * attribute.components as u64)?;
+            let bytes = reader.bytes(num_values * size)?;
+            Ok(Portable::Floats(
+                bytes
+                    .chunks(size)
+                    .map(|bytes| raw_value(bytes, attribute.data_type, attribute.normalized))
+                    .collect(),
+            ))
+        }
+        DECODER_INTEGER | DECODER_QUANTIZATION => {
+            decode_integers(reader, num_points, attribute.components, false).map(Portable::Integers)
+        }
+        DECODER_NORMALS => decode_integers(reader, num_points, 2, true).map(Portable::Integers),
+        _ => Err(invalid()),
+    }
+}
+
+fn transform_portable(
+    reader: &mut Reader<'_>,
+    attribute: &AttributeInfo,
+    values: Portable,
+) -> Result<Vec<f32>, Error> {
+    let values = match values {
+        Portable::Floats(values) => return Ok(values),
+        Portable::Integers(values) => values,
+    };
+    match attribute.decoder {
+        DECODER_QUANTIZATION => {
+            let mut min = Vec::with_capacity(attribute.components);
+            for _ in 0..attribute.components {
+                min.push(reader.f32()?);
+            }
+            let range = reader.f32()?;
+            let bits = reader.u8()?;
+            if bits < 1 || bits > 30 {
+                return Err(invalid());
+            }
+            let delta = range / ((1u32 << bits) - 1) as f32;
+            Ok(values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| *value as f32 * delta + min[i % attribute.components])
+                .collect())
+        }
+        DECODER_NORMALS => {
+            let bits = reader.u8()?;
+            if bits < 2 || bits > 30 {
+                return Err(invalid());
+            }
+            let scale = 2.0 / ((1u32 << bits) - 2) as f32;
+            let mut normals = Vec::with_capacity(values.len() / 2 * 3);
+            for st in values.chunks(2) {
+                normals.extend(&octahedral_to_unit_vector(
+                    st[0] as f32 * scale - 1.0,
+                    st[1] as f32 * scale - 1.0,
+                ));
+            }
+            Ok(normals)
+        }
+        _ => Ok(values
+            .into_iter()
+            .map(|value| integer_value(value, attribute.data_type, attribute.normalized))
+            .collect()),
+    }
+}
+
+fn octahedral_to_unit_vector(s: f32, t: f32) -> [f32; 3] {
+    let x = 1.0 - s.abs() - t.abs();
+    let offset = (-x).max(0.0);
+    let y = if s < 0.0 { s + offset } else { s - offset };
+    let z = if t < 0.0 { t + offset } else { t - offset };
+    let norm_squared = x * x + y * y + z * z;
+    if norm_squared < 1e-6 {
+        [0.0, 0.0, 0.0]
+    } else {
+        let d = 1.0 / norm_squared.sqrt();
+        [x * d, y * d, z * d]
+    }
+}
+
+fn data_type_size(data_type: u8) -> Result<usize, Error> {
+    match data_type {
+        DT_INT8 | DT_UINT8 | DT_BOOL => Ok(1),
+        DT_INT16 | DT_UINT16 => Ok(2),
+        DT_INT32 | DT_UINT32 | DT_FLOAT32 => Ok(4),
+        DT_INT64 | DT_UINT64 | DT_FLOAT64 => Ok(8),
+        _ => Err(invalid()),
+    }
+}
+
+/// Converts a little endian value of the data type to a float, normalized integers are mapped to
+/// `[0, 1]` or `[-1, 1]`.
+fn raw_value(bytes: &[u8], data_type: u8, normalized: bool) -> f32 {
+    let unsigned = bytes
+        .iter()
+        .rev()
+        .fold(0u64, |value, &byte| value << 8 | u64::from(byte));
+    match data_type {
+        DT_FLOAT32 => f32::from_bits(unsigned as u32),
+        DT_FLOAT64 => f64::from_bits(unsigned) as f32,
+        DT_INT64 => unsigned as i64 as f32,
+        DT_UINT64 => unsigned as f32,
+        DT_INT8 => integer_value(i32::from(bytes[0] as i8), data_type, normalized),
+        DT_INT16 => integer_value(i32::from(unsigned as u16 as i16), data_type, normalized),
+        DT_UINT32 => {
+            if normalized {
+                unsigned as f32 / u32::max_value() as f32
+            } else {
+                unsigned as f32
+            }
+        }
+        _ => integer_value(unsigned as i32, data_type, normalized),
+    }
+}
+
+fn integer_value(value: i32, data_type: u8, normalized: bool) -> f32 {
+    let value = value as f32;
+    if !normalized {
+        return value;
+    }
+    match data_type {
+        DT_INT8 => (value / 127.0).max(-1.0),
+        DT_UINT8 => value / 255.0,
+        DT_INT16 => (value / 32767.0).max(-1.0),
+        DT_UINT16 => value / 65535.0,
+        DT_INT32 => (value / i32::max_value() as f32).max(-1.0),
+        DT_UINT32 => value / u32::max_value() as f32,
+        _ => value,
+    }
+}
+
+/// Decodes the integers of the integer, quantization and normal decoders, and reverts their
+/// prediction.
+fn decode_integers(
+    reader: &mut Reader<'_>,
+    num_points: usize,
+    components: usize,
+    normals: bool,
+) -> Result<Vec<i32>, Error> {
+    let transform = match reader.i8()? {
+        PREDICTION_NONE => None,
+        PREDICTION_DIFFERENCE => {
+            let transform = reader.i8()?;
+            match transform {
+                TRANSFORM_DELTA | TRANSFORM_WRAP if !normals => {}
+                TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED if normals && components == 2 => {}
+                _ => {
+                    return Err(unsupported(
+                        "prediction transforms other than wrap and delta",
+                    ))
+                }
+            }
+            Some(transform)
+        }
+        _ => return Err(unsupported("mesh prediction schemes")),
+    };
+
+    let num_values = checked_count(num_points as u64 * components as u64)?;
+    let symbols = if reader.u8()? > 0 {
+        decode_symbols(reader, num_values, components)?
+    } else {
+        let size = usize::from(reader.u8()?);
+        if size == 0 || size > 4 {
+            return Err(invalid());
+        }
+        reader
+            .bytes(num_values * size)?
+            .chunks(size)
+            .map(le_u32)
+            .collect()
+    };
+    let positive = transform == Some(TRANSFORM_NORMAL_OCTAHEDRON_CANONICALIZED);
+    let mut values = symbols
+        .into_iter()
+        .map(|symbol| {
+            if positive {
+                symbol as i32
+            } else {
+                to_signed(symbol)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match transform {
+        None => {}
+        Some(TRANSFORM_DELTA) => {
+            for i in components..values.len() {
+                values[i] = values[i].wrapping_add(values[i - components]);
+            }
+        }
+        Some(TRANSFORM_WRAP) => {
+            let min = reader.i32()?;
+            let max = reader.i32()?;
+            if min > max {
+                return Err(invalid());
+            }
+            let (min, max) = (i64::from(min), i64::from(max));
+            let max_dif = 1 + max - min;
+            for i in 0..values.len() {
+                let predicted = if i < components {
+                    0
+                } else {
+                    i64::from(values[i - components])
+                };
+                let mut value = predicted.max(min).min(max) + i64::from(values[i]);
+                if value > max {
+                    value -= max_dif;
+                } else if value < min {
+                    value += max_dif;
+                }
+                values[i] = value as i32;
+            }
+        }
+        Some(_) => {
+            let octahedron = Octahedron::new(reader.i32()?)?;
+            // The center value is derived from the maximum.
+            reader.i32()?;
+            for i in (0..values.len()).step_by(2) {
+                let predicted = if i < 2 {
+                    (0, 0)
+                } else {
+                    (values[i - 2], values[i - 1])
+                };
+                let (s, t) = octahedron.original_value(predicted, (values[i], values[i + 1]));
+                values[i] = s;
+                values[i + 1] = t;
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Folds the symbols of signed values back, even symbols are positive.
+fn to_signed(symbol: u32) -> i32 {
+    let value = (symbol >> 1) as i32;
+    if symbol & 1 == 0 {
+        value
+    } else {
+        -value - 1
+    }
+}
+
+/// Reverts the canonicalized octahedron transform of normal predictions.
+struct Octahedron {
+    max_quantized: i32,
+    center: i32,
+}
+
+impl Octahedron {
+    fn new(max_quantized: i32) -> Result<Self, Error> {
+        if max_quantized <= 0 || max_quantized % 2 == 0 {
+            return Err(invalid());
+        }
+        let bits = 32 - (max_quantized as u32).leading_zeros();
+        if bits < 2 || bits > 30 {
+            return Err(invalid());
+        }
+        let max_quantized = (1 << bits) - 1;
+        Ok(Octahedron {
+            max_quantized,
+            center: (max_quantized - 1) / 2,
+        })
+    }
+
+    fn original_value(&self, predicted: (i32, i32), correction: (i32, i32)) -> (i32, i32) {
+        let mut pred = (predicted.0 - self.center, predicted.1 - self.center);
+        let in_diamond = pred.0.abs() + pred.1.abs() <= self.center;
+        if !in_diamond {
+            pred = self.invert_diamond(pred);
+        }
+        let in_bottom_left = (pred.0 == 0 && pred.1 == 0) || (pred.0 < 0 && pred.1 <= 0);
+        let rotations = rotation_count(pred);
+        if !in_bottom_left {
+            pred = rotate(pred, rotations);
+        }
+        let mut orig = (
+            self.mod_max(pred.0 + correction.0),
+            self.mod_max(pred.1 + correction.1),
+        );
+        if !in_bottom_left {
+            orig = rotate(orig, (4 - rotations) % 4);
+        }
+        if !in_diamond {
+            orig = self.invert_diamond(orig);
+        }
+        (orig.0 + self.center, orig.1 + self.center)
+    }
+
+    fn invert_diamond(&self, (s, t): (i32, i32)) -> (i32, i32) {
+        let (sign_s, sign_t) = if s >= 0 && t >= 0 {
+            (1, 1)
+        } else if s <= 0 && t <= 0 {
+            (-1, -1)
+        } else {
+            (if s > 0 { 1 } else { -1 }, if t > 0 { 1 } else { -1 })
+        };
+        let corner_s = sign_s * self.center;
+        let corner_t = sign_t * self.center;
+        let (s, t) = (2 * s - corner_s, 2 * t - corner_t);
+        let (s, t) = if sign_s * sign_t >= 0 {
+            (-t, -s)
+        } else {
+            (t, s)
+        };
+        ((s + corner_s) / 2, (t + corner_t) / 2)
+    }
+
+    fn mod_max(&self, value: i32) -> i32 {
+        if value > self.center {
+            value - self.max_quantized
+        } else if value < -self.center {
+            value + self.max_quantized
+        } else {
+            value
+        }
+    }
+}
+
+fn rotation_count((x, y): (i32, i32)) -> i32 {
+    if x == 0 {
+        if y == 0 {
+            0
+        } else if y > 0 {
+            3
+        } else {
+            1
+        }
+    } else if x > 0 {
+        if y >= 0 {
+            2
+        } else {
+            1
+        }
+    } else if y <= 0 {
+        0
+    } else {
+        3
+    }
+}
+
+fn rotate((x, y): (i32, i32), rotations: i32) -> (i32, i32) {
+    match rotations {
+        1 => (y, -x),
+        2 => (-x, -y),
+        3 => (-y, x),
+        _ => (x, y),
+    }
+}
+
+/// Decodes entropy coded symbols, either tagged with their bit length or coded directly.
+fn decode_symbols(
+    reader: &mut Reader<'_>,
+    num_values: usize,
+    components: usize,
+) -> Result<Vec<u32>, Error> {
+    if num_values == 0 {
+        return Ok(Vec::new());
+    }
+    match reader.u8()? {
+        0 => decode_tagged_symbols(reader, num_values, components),
+        1 => {
+            let max_bit_length = u32::from(reader.u8()?);
+            if max_bit_length < 1 || max_bit_length > 18 {
+                return Err(invalid());
+            }
+            let precision = (3 * max_bit_length / 2).max(12).min(20);
+            let mut decoder = RAnsDecoder::new(reader, precision)?;
+            Ok((0..num_values).map(|_| decoder.read()).collect())
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Decodes the bit lengths of the values with rANS, and the values as raw bits following it.
+fn decode_tagged_symbols(
+    reader: &mut Reader<'_>,
+    num_values: usize,
+    components: usize,
+) -> Result<Vec<u32>, Error> {
+    let mut tags = RAnsDecoder::new(reader, 12)?;
+    let mut bits = BitReader {
+        data: &reader.data[reader.pos..],
+        offset: 0,
+    };
+    let mut values = Vec::with_capacity(num_values);
+    while values.len() < num_values {
+        let bit_length = tags.read();
+        if bit_length > 32 {
+            return Err(invalid());
+        }
+        for _ in 0..components {
+            values.push(bits.read(bit_length));
+        }
+    }
+    values.truncate(num_values);
+    let bytes = (bits.offset + 7) / 8;
+    reader.bytes(bytes)?;
+    Ok(values)
+}
+
+/// Reads bits starting with the least significant bit of each byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn read(&mut self, count: u32) -> u32 {
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self.data.get(self.offset / 8).cloned().unwrap_or(0);
+            value |= u32::from((byte >> (self.offset % 8)) & 1) << bit;
+            self.offset += 1;
+        }
+        value
+    }
+}
+
+/// A rANS decoder of symbols with a probability table.
+struct RAnsDecoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+    state: u32,
+    precision: u32,
+    base: u32,
+    lookup: Vec<u32>,
+    /// The probability and cumulative probability of the symbols.
+    symbols: Vec<(u32, u32)>,
+}
+
+impl<'a> RAnsDecoder<'a> {
+    /// Reads the probability table and the coded data.
+    fn new(reader: &mut Reader<'a>, precision_bits: u32) -> Result<Self, Error> {
+        let num_symbols = checked_count(reader.varint()?)?;
+        if num_symbols == 0 {
+            return Err(invalid());
+        }
+        let mut probabilities = vec![0; num_symbols];
+        let mut i = 0;
+        while i < num_symbols {
+            let data = reader.u8()?;
+            let token = data & 3;
+            if token == 3 {
+                // A run of symbols with zero probability.
+                let offset = usize::from(data >> 2);
+                if i + offset >= num_symbols {
+                    return Err(invalid());
+                }
+                i += offset + 1;
+            } else {
+                let mut probability = u32::from(data >> 2);
+                for byte in 0..u32::from(token) {
+                    probability |= u32::from(reader.u8()?) << (8 * (byte + 1) - 2);
+                }
+                probabilities[i] = probability;
+                i += 1;
+            }
+        }
+
+        let precision = 1 << precision_bits;
+        let mut lookup = vec![0; precision as usize];
+        let mut symbols = Vec::with_capacity(num_symbols);
+        let mut cumulative = 0u32;
+        for (symbol, probability) in probabilities.into_iter().enumerate() {
+            let next = cumulative
+                .checked_add(probability)
+                .filter(|next| *next <= precision)
+                .ok_or_else(invalid)?;
+            for slot in &mut lookup[cumulative as usize..next as usize] {
+                *slot = symbol as u32;
+            }
+            symbols.push((probability, cumulative));
+            cumulative = next;
+        }
+        if cumulative != precision {
+            return Err(invalid());
+        }
+
+        let size = checked_count(reader.varint()?)?;
+        let data = reader.bytes(size)?;
+        let base = precision * 4;
+        let last = *data.last().ok_or_else(invalid)?;
+        let (length, mask) = match last >> 6 {
+            0 => (1, 0x3f),
+            1 => (2, 0x3fff),
+            2 => (3, 0x003f_ffff),
+            _ => (4, 0x3fff_ffff),
+        };
+        if data.len() < length {
+            return Err(invalid());
+        }
+        let offset = data.len() - length;
+        let state = (le_u32(&data[offset..]) & mask) + base;
+        if state >= base * 256 {
+            return Err(invalid());
+        }
+        Ok(RAnsDecoder {
+            data,
+            offset,
+            state,
+            precision,
+            base,
+            lookup,
+            symbols,
+        })
+    }
+
+    fn read(&mut self) -> u32 {
+        while self.state < self.base && self.offset > 0 {
+            self.offset -= 1;
+            self.state = self.state * 256 + u32::from(self.data[self.offset]);
+        }
+        let quotient = self.state / self.precision;
+        let remainder = self.state % self.precision;
+        let symbol = self.lookup[remainder as usize];
+        let (probability, cumulative) = self.symbols[symbol as usize];
+        self.state = quotient * probability + remainder - cumulative;
+        symbol
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use gltf::mesh::Semantic;
+
+    use super::{decode, to_signed, DracoExtension};
+
+    fn extension(attributes: &[(&str, u32)]) -> DracoExtension {
+        DracoExtension {
+            buffer: 0,
+            range: 0..0,
+            attributes: attributes
+                .iter()
+                .map(|(name, id)| (name.to_string(), *id))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn push_f32(data: &mut Vec<u8>, value: f32) {
+        let bits = value.to_bits();
+        data.extend((0..4).map(|i| (bits >> (i * 8)) as u8));
+    }
+
+    /// The header of a sequential mesh with one triangle, its indices and one attribute.
+    fn triangle(attribute: &[u8]) -> Vec<u8> {
+        let mut data = b"DRACO".to_vec();
+        // Version 2.2, triangular mesh, sequential encoding, no flags
+        data.extend(&[2, 2, 1, 0, 0, 0]);
+        // One face of three points, with uncompressed indices
+        data.extend(&[1, 3, 1, 0, 1, 2]);
+        // One attribute decoder with one attribute
+        data.extend(&[1, 1]);
+        data.extend(attribute);
+        data
+    }
+
+    #[test]
+    fn decodes_raw_attributes() {
+        // Position, float, three components, not normalized, id 0, generic decoder
+        let mut data = triangle(&[0, 9, 3, 0, 0, 0]);
+        for i in 0..9 {
+            push_f32(&mut data, i as f32);
+        }
+        let primitive = decode(&data, &extension(&[("POSITION", 0)])).unwrap();
+        assert_eq!(primitive.indices(), &[0, 1, 2]);
+        let (components, values) = primitive.attribute(&Semantic::Positions).unwrap();
+        assert_eq!(components, 3);
+        assert_eq!(values, &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0][..]);
+    }
+
+    #[test]
+    fn decodes_quantized_attributes() {
+        // Texture coordinate, float, one component, not normalized, id 4, quantization decoder
+        let mut data = triangle(&[3, 9, 1, 0, 4, 2]);
+        // Difference prediction with the wrap transform, uncompressed one byte corrections of
+        // the quantized values 0, 2 and 3
+        data.extend(&[0, 1, 0, 1, 0, 3, 2]);
+        // Wrap bounds 0 and 3
+        data.extend(&[0, 0, 0, 0, 3, 0, 0, 0]);
+        // Minimum 1, range 3, 2 bits
+        push_f32(&mut data, 1.0);
+        push_f32(&mut data, 3.0);
+        data.push(2);
+        let primitive = decode(&data, &extension(&[("TEXCOORD_0", 4)])).unwrap();
+        let (_, values) = primitive.attribute(&Semantic::TexCoords(0)).unwrap();
+        assert_eq!(values, &[1.0, 3.0, 4.0][..]);
+        assert_eq!(primitive.attribute(&Semantic::Positions), None);
+    }
+
+    #[test]
+    fn rejects_edgebreaker_and_invalid_data() {
+        let mut data = b"DRACO".to_vec();
+        data.extend(&[2, 2, 1, 1, 0, 0]);
+        assert!(decode(&data, &extension(&[])).is_err());
+        assert!(decode(b"DRACO", &extension(&[])).is_err());
+        // An attribute the extension lists but the data doesn't contain
+        let mut data = triangle(&[0, 9, 1, 0, 0, 0]);
+        for i in 0..3 {
+            push_f32(&mut data, i as f32);
+        }
+        assert!(decode(&data, &extension(&[("POSITION", 1)])).is_err());
+    }
+
+    #[test]
+    fn folds_signed_symbols() {
+        assert_eq!(to_signed(0), 0);
+        assert_eq!(to_signed(1), -1);
+        assert_eq!(to_signed(2), 1);
+        assert_eq!(to_signed(3), -2);
+    }
+}
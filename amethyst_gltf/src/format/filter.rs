@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use crate::GltfNodeFilter;
+
+/// Finds the nodes below `roots` that are skipped by the `filter`, including roots.
+///
+/// Only the topmost skipped node of a branch is listed, since its children are never visited.
+pub fn skipped_nodes<'a, I>(roots: I, filter: &GltfNodeFilter) -> HashSet<usize>
+where
+    I: IntoIterator<Item = gltf::Node<'a>>,
+{
+    let mut skipped = HashSet::new();
+    if filter.include.is_empty() && filter.exclude.is_empty() && filter.callback.is_none() {
+        return skipped;
+    }
+    for root in roots {
+        visit(&root, filter, false, &mut skipped);
+    }
+    skipped
+}
+
+/// Returns whether the node is kept.
+fn visit(
+    node: &gltf::Node<'_>,
+    filter: &GltfNodeFilter,
+    parent_included: bool,
+    skipped: &mut HashSet<usize>,
+) -> bool {
+    let matches = |patterns: &[String]| {
+        node.name()
+            .map_or(false, |name| patterns.iter().any(|p| glob_match(p, name)))
+    };
+    let rejected = matches(&filter.exclude)
+        || filter
+            .callback
+            .as_ref()
+            .map_or(false, |callback| !callback(node));
+    if rejected {
+        skipped.insert(node.index());
+        return false;
+    }
+
+    let included = parent_included || filter.include.is_empty() || matches(&filter.include);
+    let mut child_kept = false;
+    for child in node.children() {
+        child_kept |= visit(&child, filter, included, skipped);
+    }
+    let kept = included || child_kept;
+    if !kept {
+        skipped.insert(node.index());
+    }
+    kept
+}
+
+/// Matches a whole name against a pattern, `*` matching any number of characters and `?` one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // The position of the last `*` and of the name when it was reached, to backtrack to.
+    let mut star = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("helper_*", "helper_box"));
+        assert!(glob_match("*_proxy", "tree_proxy"));
+        assert!(glob_match("LOD?_*", "LOD1_house"));
+        assert!(glob_match("*a*b*", "xxaybz"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("LOD?_*", "LOD10_house"));
+        assert!(!glob_match("helper_*", "the_helper_box"));
+        assert!(!glob_match("a*b", "ab_"));
+    }
+}
@@ -1,36 +1,189 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
 
 use amethyst_assets::Source as AssetSource;
 use amethyst_error::Error;
+use amethyst_renderer::Light;
 use gltf::{self, json, Gltf};
+use log::debug;
+use memmap::Mmap;
 
 use crate::error;
 
-#[derive(Debug)]
+#[cfg(feature = "draco")]
+use super::draco::{self, draco_primitives, DracoExtension, DracoPrimitive};
+use super::{
+    light::node_lights,
+    texture_transform::{material_texture_transforms, TextureTransform},
+};
+
+/// The extensions a file may require, other extensions are only used if they are known.
+/// `KHR_draco_mesh_compression` is supported with the `draco` feature.
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "KHR_lights_punctual",
+    "KHR_materials_unlit",
+    "KHR_mesh_quantization",
+    "KHR_texture_transform",
+];
+
+#[derive(Debug, PartialEq)]
 pub enum ImageFormat {
     Png,
     Jpeg,
+    Bmp,
+    Tga,
 }
 
 impl ImageFormat {
-    fn from_mime_type(mime: &str) -> Self {
-        match mime {
-            "image/jpeg" => ImageFormat::Jpeg,
-            "image/png" => ImageFormat::Png,
-            _ => unreachable!(),
+    fn from_mime_type(mime: &str) -> Option<Self> {
+        match &mime.to_ascii_lowercase()[..] {
+            "image/jpeg" | "image/jpg" => Some(ImageFormat::Jpeg),
+            "image/png" => Some(ImageFormat::Png),
+            "image/bmp" | "image/x-bmp" | "image/x-ms-bmp" => Some(ImageFormat::Bmp),
+            "image/tga" | "image/x-tga" | "image/x-targa" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    fn from_extension(extension: &str) -> Option<Self> {
+        match &extension.to_ascii_lowercase()[..] {
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "png" => Some(ImageFormat::Png),
+            "bmp" => Some(ImageFormat::Bmp),
+            "tga" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    /// Detects the format from the first bytes, for images with a generic MIME type like
+    /// `application/octet-stream`. TGA files have no signature.
+    fn from_magic(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(ImageFormat::Png)
+        } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+            Some(ImageFormat::Jpeg)
+        } else if data.starts_with(b"BM") {
+            Some(ImageFormat::Bmp)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the format by the MIME type, then the file extension, then the contents.
+    ///
+    /// KTX2 images, stored with `KHR_texture_basisu`, have no decoder and are reported by name.
+    fn detect(
+        data: &[u8],
+        mime_type: Option<&str>,
+        extension: Option<&str>,
+    ) -> Result<Self, Error> {
+        mime_type
+            .and_then(ImageFormat::from_mime_type)
+            .or_else(|| extension.and_then(ImageFormat::from_extension))
+            .or_else(|| ImageFormat::from_magic(data))
+            .ok_or_else(|| {
+                let name = if data.starts_with(KTX2_IDENTIFIER) {
+                    "KTX2"
+                } else {
+                    mime_type.or(extension).unwrap_or("unknown")
+                };
+                error::Error::UnsupportedImageFormat(name.to_string()).into()
+            })
+    }
+}
+
+/// The first bytes of a KTX2 file.
+const KTX2_IDENTIFIER: &[u8] = b"\xabKTX 20\xbb\r\n\x1a\n";
+
+/// Files at least this large are memory mapped instead of read, if they are on the file system.
+const MAP_THRESHOLD: u64 = 1 << 20;
+
+/// The contents of a file, either read, memory mapped, or loaded by the `Loader` as a
+/// `GltfBuffer`.
+enum Bytes {
+    Read(Vec<u8>),
+    Mapped(Mmap),
+    Loaded(Arc<Vec<u8>>),
+}
+
+impl Bytes {
+    fn as_slice(&self) -> &[u8] {
+        match *self {
+            Bytes::Read(ref data) => data,
+            Bytes::Mapped(ref map) => map,
+            Bytes::Loaded(ref data) => data,
+        }
+    }
+}
+
+/// The data of a buffer, which is a part of a file for the `BIN` section of binary glTF files.
+#[derive(Clone)]
+struct BufferData {
+    bytes: Arc<Bytes>,
+    range: Range<usize>,
+}
+
+impl BufferData {
+    fn whole(bytes: Bytes) -> Self {
+        let range = 0..bytes.as_slice().len();
+        BufferData {
+            bytes: Arc::new(bytes),
+            range,
         }
     }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.bytes.as_slice()[self.range.clone()]
+    }
+}
+
+impl fmt::Debug for BufferData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BufferData({} bytes)", self.range.len())
+    }
 }
 
-/// Buffer data returned from `import`.
+/// Buffer data returned from `import`, along with the extensions the `gltf` crate doesn't read.
 #[derive(Clone, Debug)]
-pub struct Buffers(Vec<Vec<u8>>);
+pub struct Buffers {
+    data: Vec<BufferData>,
+    extensions: Extensions,
+}
+
+/// The extensions the `gltf` crate doesn't read.
+#[derive(Clone, Debug, Default)]
+struct Extensions {
+    unlit_materials: HashSet<usize>,
+    lights: HashMap<usize, Light>,
+    texture_transforms: HashMap<usize, TextureTransform>,
+    /// The compressed primitives, by the index of their mesh and their index in the mesh.
+    #[cfg(feature = "draco")]
+    draco: HashMap<(usize, usize), DracoExtension>,
+}
 
 #[allow(unused)]
 impl Buffers {
     /// Obtain the contents of a loaded buffer.
     pub fn buffer(&self, buffer: &gltf::Buffer<'_>) -> Option<&[u8]> {
-        self.0.get(buffer.index()).map(Vec::as_slice)
+        self.data.get(buffer.index()).map(BufferData::as_slice)
+    }
+
+    /// Wraps buffer data held in memory, in the order of the buffers of the file.
+    #[cfg(test)]
+    pub(crate) fn from_data(data: Vec<Vec<u8>>) -> Self {
+        Buffers {
+            data: data
+                .into_iter()
+                .map(|data| BufferData::whole(Bytes::Read(data)))
+                .collect(),
+            extensions: Extensions::default(),
+        }
     }
 
     /// Obtain the contents of a loaded buffer view.
@@ -44,22 +197,106 @@ impl Buffers {
 
     /// Take the loaded buffer data.
     pub fn take(self) -> Vec<Vec<u8>> {
-        self.0
+        self.data
+            .iter()
+            .map(|data| data.as_slice().to_vec())
+            .collect()
+    }
+
+    /// Checks if the material has the `KHR_materials_unlit` extension.
+    pub fn is_unlit(&self, material: &gltf::Material<'_>) -> bool {
+        material.index().map_or(false, |index| {
+            self.extensions.unlit_materials.contains(&index)
+        })
+    }
+
+    /// The light attached to the node with the `KHR_lights_punctual` extension.
+    pub fn light(&self, node: &gltf::Node<'_>) -> Option<&Light> {
+        self.extensions.lights.get(&node.index())
+    }
+
+    /// The transform of the texture coordinates of the material from the `KHR_texture_transform`
+    /// extension.
+    pub fn texture_transform(&self, material: &gltf::Material<'_>) -> Option<&TextureTransform> {
+        material
+            .index()
+            .and_then(|index| self.extensions.texture_transforms.get(&index))
+    }
+
+    /// Decodes the primitive of the mesh if it is compressed with `KHR_draco_mesh_compression`.
+    #[cfg(feature = "draco")]
+    pub fn draco_primitive(
+        &self,
+        mesh: usize,
+        primitive: usize,
+    ) -> Result<Option<DracoPrimitive>, Error> {
+        let extension = match self.extensions.draco.get(&(mesh, primitive)) {
+            Some(extension) => extension,
+            None => return Ok(None),
+        };
+        let data = self
+            .data
+            .get(extension.buffer)
+            .and_then(|data| data.as_slice().get(extension.range.clone()))
+            .ok_or(error::Error::InvalidDracoData)?;
+        draco::decode(data, extension).map(Some)
     }
 }
 
 /// Imports glTF 2.0
 pub fn import<P>(source: Arc<dyn AssetSource>, path: P) -> Result<(Gltf, Buffers), Error>
+where
+    P: AsRef<Path>,
+{
+    import_with_buffers(source, path, &HashMap::new())
+}
+
+/// Imports glTF 2.0, using the given contents for the buffers with these indices instead of
+/// reading their files.
+pub fn import_with_buffers<P>(
+    source: Arc<dyn AssetSource>,
+    path: P,
+    loaded: &HashMap<usize, Arc<Vec<u8>>>,
+) -> Result<(Gltf, Buffers), Error>
 where
     P: AsRef<Path>,
 {
     let path = path.as_ref();
-    let data = read_to_end(source.clone(), path)?;
-    if data.starts_with(b"glTF") {
-        import_binary(&data, source, path)
+    let data = read_bytes(source.clone(), path)?;
+    if data.as_slice().starts_with(b"glTF") {
+        import_binary(data, source, path, loaded)
     } else {
-        import_standard(&data, source, path)
+        import_standard(data.as_slice(), source, path, loaded)
+    }
+}
+
+/// Reads the JSON of a `.gltf` or `.glb` file, without its buffers.
+pub fn import_document<P>(source: Arc<dyn AssetSource>, path: P) -> Result<Gltf, Error>
+where
+    P: AsRef<Path>,
+{
+    let data = read_bytes(source, path.as_ref())?;
+    Ok(Gltf::from_slice(data.as_slice())?)
+}
+
+/// Reads a file, or memory maps it if it is large and the source is on the file system, so the
+/// buffers sliced from it don't have to be copied into memory.
+fn read_bytes(source: Arc<dyn AssetSource>, path: &Path) -> Result<Bytes, Error> {
+    let name = path
+        .to_str()
+        .expect("Path contains invalid UTF-8 charcters");
+    let large = source
+        .size(name)
+        .map_or(false, |size| size >= MAP_THRESHOLD);
+    if let Some(local) = source.local_path(name).filter(|_| large) {
+        let file = File::open(&local)?;
+        // The map stays valid as long as the file isn't truncated while the scene is imported.
+        match unsafe { Mmap::map(&file) } {
+            Ok(map) => return Ok(Bytes::Mapped(map)),
+            Err(e) => debug!("Failed to map {:?}, reading it instead: {}", local, e),
+        }
     }
+    read_to_end(source, path).map(Bytes::Read)
 }
 
 fn read_to_end<P: AsRef<Path>>(source: Arc<dyn AssetSource>, path: P) -> Result<Vec<u8>, Error> {
@@ -80,18 +317,21 @@ fn load_external_buffers(
     source: Arc<dyn AssetSource>,
     base_path: &Path,
     gltf: &Gltf,
-    mut bin: Option<Vec<u8>>,
-) -> Result<Vec<Vec<u8>>, Error> {
+    mut bin: Option<BufferData>,
+    loaded: &HashMap<usize, Arc<Vec<u8>>>,
+) -> Result<Vec<BufferData>, Error> {
     use gltf::buffer::Source;
     let mut buffers = vec![];
     for (index, buffer) in gltf.buffers().enumerate() {
         let data = match buffer.source() {
             Source::Uri(uri) => {
                 if uri.starts_with("data:") {
-                    parse_data_uri(uri)?
+                    BufferData::whole(Bytes::Read(parse_data_uri(uri)?))
+                } else if let Some(data) = loaded.get(&index) {
+                    BufferData::whole(Bytes::Loaded(data.clone()))
                 } else {
                     let path = base_path.parent().unwrap_or(Path::new("./")).join(uri);
-                    read_to_end(source.clone(), &path)?
+                    BufferData::whole(read_bytes(source.clone(), &path)?)
                 }
             }
             Source::Bin => bin
@@ -99,7 +339,7 @@ fn load_external_buffers(
                 .expect("`BIN` section of binary glTF file is empty or used by another buffer"),
         };
 
-        if data.len() < buffer.length() {
+        if data.range.len() < buffer.length() {
             let path = json::Path::new().field("buffers").index(index);
             return Err(error::Error::BufferLength(path).into());
         }
@@ -112,28 +352,118 @@ fn import_standard(
     data: &[u8],
     source: Arc<dyn AssetSource>,
     base_path: &Path,
+    loaded: &HashMap<usize, Arc<Vec<u8>>>,
 ) -> Result<(Gltf, Buffers), Error> {
+    check_required_extensions(data)?;
     let gltf = Gltf::from_slice(data)?;
-    let buffers = Buffers(load_external_buffers(source, base_path, &gltf, None)?);
+    let buffers = Buffers {
+        data: load_external_buffers(source, base_path, &gltf, None, loaded)?,
+        extensions: read_extensions(data),
+    };
     Ok((gltf, buffers))
 }
 
 fn import_binary(
-    data: &[u8],
+    data: Bytes,
     source: Arc<dyn AssetSource>,
     base_path: &Path,
+    loaded: &HashMap<usize, Arc<Vec<u8>>>,
 ) -> Result<(Gltf, Buffers), Error> {
-    let gltf::binary::Glb {
-        header: _,
-        json,
-        bin,
-    } = gltf::binary::Glb::from_slice(data)?;
-    let gltf = Gltf::from_slice(&json)?;
-    let bin = bin.map(|x| x.to_vec());
-    let buffers = Buffers(load_external_buffers(source, base_path, &gltf, bin)?);
+    let data = Arc::new(data);
+    let (gltf, bin, extensions) = {
+        let slice = data.as_slice();
+        let gltf::binary::Glb {
+            header: _,
+            json,
+            bin,
+        } = gltf::binary::Glb::from_slice(slice)?;
+        // The `BIN` chunk borrows from the file, so it is kept as a range instead of copied.
+        let bin = bin.map(|bin| {
+            let start = bin.as_ptr() as usize - slice.as_ptr() as usize;
+            start..start + bin.len()
+        });
+        check_required_extensions(&json)?;
+        (Gltf::from_slice(&json)?, bin, read_extensions(&json))
+    };
+    let bin = bin.map(|range| BufferData { bytes: data, range });
+    let buffers = Buffers {
+        data: load_external_buffers(source, base_path, &gltf, bin, loaded)?,
+        extensions,
+    };
     Ok((gltf, buffers))
 }
 
+/// Fails for files which can't be loaded without an unsupported extension, before the `gltf`
+/// crate rejects them with a less helpful error. Primitives compressed by
+/// `KHR_draco_mesh_compression` have no accessor data for example, so they are rejected without
+/// the `draco` feature.
+fn check_required_extensions(json: &[u8]) -> Result<(), Error> {
+    let root = match serde_json::from_slice::<serde_json::Value>(json) {
+        Ok(root) => root,
+        // The `gltf` crate reports the syntax error.
+        Err(_) => return Ok(()),
+    };
+    let required = root
+        .get("extensionsRequired")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(serde_json::Value::as_str);
+    for extension in required {
+        #[cfg(feature = "draco")]
+        {
+            if extension == draco::EXTENSION {
+                continue;
+            }
+        }
+        #[cfg(not(feature = "draco"))]
+        {
+            if extension == "KHR_draco_mesh_compression" {
+                return Err(error::Error::DracoDisabled.into());
+            }
+        }
+        if !SUPPORTED_EXTENSIONS.contains(&extension) {
+            return Err(error::Error::UnsupportedExtension(extension.to_string()).into());
+        }
+    }
+    Ok(())
+}
+
+/// Reads the extensions from the JSON of the file, since the `gltf` crate drops unknown
+/// extensions.
+fn read_extensions(json: &[u8]) -> Extensions {
+    match serde_json::from_slice::<serde_json::Value>(json) {
+        Ok(root) => Extensions {
+            unlit_materials: unlit_materials(&root),
+            lights: node_lights(&root),
+            texture_transforms: material_texture_transforms(&root),
+            #[cfg(feature = "draco")]
+            draco: draco_primitives(&root),
+        },
+        Err(_) => Extensions::default(),
+    }
+}
+
+/// Finds the materials with the `KHR_materials_unlit` extension.
+fn unlit_materials(root: &serde_json::Value) -> HashSet<usize> {
+    root.get("materials")
+        .and_then(serde_json::Value::as_array)
+        .map(|materials| {
+            materials
+                .iter()
+                .enumerate()
+                .filter(|(_, material)| {
+                    material
+                        .get("extensions")
+                        .and_then(|extensions| extensions.get("KHR_materials_unlit"))
+                        .is_some()
+                })
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn get_image_data(
     image: &gltf::Image<'_>,
     buffers: &Buffers,
@@ -146,48 +476,185 @@ pub fn get_image_data(
             let data = buffers
                 .view(&view)
                 .expect("`view` of image data points to a buffer which does not exist");
-            Ok((data.to_vec(), ImageFormat::from_mime_type(mime_type)))
+            let format = ImageFormat::detect(data, Some(mime_type), None)?;
+            Ok((data.to_vec(), format))
         }
 
         Source::Uri { uri, mime_type } => {
             if uri.starts_with("data:") {
                 let data = parse_data_uri(uri)?;
-                if let Some(ty) = mime_type {
-                    Ok((data, ImageFormat::from_mime_type(ty)))
-                } else {
-                    let mimetype = uri
-                        .split(',')
-                        .nth(0)
-                        .expect("Unreachable: `split` will always return at least one element")
-                        .split(':')
-                        .nth(1)
-                        .expect("URI does not contain ':'")
-                        .split(';')
-                        .nth(0)
-                        .expect("Unreachable: `split` will always return at least one element");
-                    Ok((data, ImageFormat::from_mime_type(mimetype)))
-                }
+                let mime_type = mime_type.or_else(|| data_uri_mime_type(uri));
+                let format = ImageFormat::detect(&data, mime_type, None)?;
+                Ok((data, format))
             } else {
                 let path = base_path.parent().unwrap_or(Path::new("./")).join(uri);
                 let data = source.load(
                     path.to_str()
                         .expect("Path contains invalid UTF-8 characters"),
                 )?;
-                if let Some(ty) = mime_type {
-                    Ok((data, ImageFormat::from_mime_type(ty)))
-                } else {
-                    let ext = path
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .map_or("".to_string(), |s| s.to_ascii_lowercase());
-                    let format = match &ext[..] {
-                        "jpg" | "jpeg" => ImageFormat::Jpeg,
-                        "png" => ImageFormat::Png,
-                        _ => unreachable!(),
-                    };
-                    Ok((data, format))
-                }
+                let extension = path.extension().and_then(|s| s.to_str());
+                let format = ImageFormat::detect(&data, mime_type, extension)?;
+                Ok((data, format))
             }
         }
     }
 }
+
+/// The path and format of an image stored in its own file, if its MIME type or file extension is
+/// known, so it can be loaded through the `Loader` instead of during the import.
+///
+/// The path is relative to the root of the source the glTF file is loaded from.
+pub fn external_image(image: &gltf::Image<'_>, base_path: &Path) -> Option<(String, ImageFormat)> {
+    use gltf::image::Source;
+    match image.source() {
+        Source::Uri { uri, mime_type } if !uri.starts_with("data:") => {
+            let path = base_path.parent().unwrap_or(Path::new("./")).join(uri);
+            let extension = path.extension().and_then(|s| s.to_str());
+            let format = mime_type
+                .and_then(ImageFormat::from_mime_type)
+                .or_else(|| extension.and_then(ImageFormat::from_extension))?;
+            Some((path.to_str()?.to_string(), format))
+        }
+        _ => None,
+    }
+}
+
+/// The index and path of the buffers stored in their own files, which
+/// `GltfSceneOptions::stream_buffers` loads through the `Loader`.
+///
+/// The paths are relative to the root of the source the glTF file is loaded from.
+pub fn external_buffers(gltf: &Gltf, base_path: &Path) -> Vec<(usize, String)> {
+    use gltf::buffer::Source;
+    gltf.buffers()
+        .filter_map(|buffer| match buffer.source() {
+            Source::Uri(uri) if !uri.starts_with("data:") => {
+                let path = base_path.parent().unwrap_or(Path::new("./")).join(uri);
+                Some((buffer.index(), path.to_str()?.to_string()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The MIME type of a data URI like `data:image/png;base64,...`, if it has one.
+fn data_uri_mime_type(uri: &str) -> Option<&str> {
+    let header = uri.split(',').nth(0)?;
+    let mime_type = header.split(':').nth(1)?.split(';').nth(0)?;
+    if mime_type.is_empty() {
+        None
+    } else {
+        Some(mime_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use gltf::Gltf;
+
+    use super::{
+        check_required_extensions, data_uri_mime_type, external_buffers, external_image,
+        read_extensions, ImageFormat,
+    };
+
+    #[test]
+    fn data_uri_mime_types() {
+        assert_eq!(
+            data_uri_mime_type("data:image/x-tga;base64,AAAA"),
+            Some("image/x-tga")
+        );
+        assert_eq!(data_uri_mime_type("data:;base64,AAAA"), None);
+    }
+
+    #[test]
+    fn rejects_unsupported_required_extensions() {
+        let draco = br#"{
+            "extensionsUsed": ["KHR_draco_mesh_compression", "KHR_materials_unlit"],
+            "extensionsRequired": ["KHR_draco_mesh_compression"]
+        }"#;
+        assert_eq!(
+            check_required_extensions(draco).is_ok(),
+            cfg!(feature = "draco")
+        );
+        let unlit = br#"{ "extensionsRequired": ["KHR_materials_unlit"] }"#;
+        assert!(check_required_extensions(unlit).is_ok());
+        assert!(check_required_extensions(b"{}").is_ok());
+    }
+
+    #[test]
+    fn finds_unlit_materials() {
+        let json = br#"{
+            "materials": [
+                { "name": "lit" },
+                { "name": "unlit", "extensions": { "KHR_materials_unlit": {} } }
+            ]
+        }"#;
+        let unlit = read_extensions(json).unlit_materials;
+        assert!(!unlit.contains(&0));
+        assert!(unlit.contains(&1));
+        assert!(read_extensions(b"{}").unlit_materials.is_empty());
+    }
+
+    #[test]
+    fn finds_external_images() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "images": [
+                    { "uri": "textures/wood.png" },
+                    { "uri": "data:image/png;base64,AAAA" },
+                    { "uri": "textures/wood.ktx2" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let images = gltf.images().collect::<Vec<_>>();
+        let base = Path::new("models/scene.gltf");
+        assert_eq!(
+            external_image(&images[0], base),
+            Some(("models/textures/wood.png".to_string(), ImageFormat::Png))
+        );
+        assert_eq!(external_image(&images[1], base), None);
+        assert_eq!(external_image(&images[2], base), None);
+    }
+
+    #[test]
+    fn finds_external_buffers() {
+        let gltf = Gltf::from_slice(
+            br#"{
+                "asset": { "version": "2.0" },
+                "buffers": [
+                    { "byteLength": 4, "uri": "data:application/octet-stream;base64,AAAAAA==" },
+                    { "byteLength": 1024, "uri": "scene.bin" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            external_buffers(&gltf, Path::new("models/scene.gltf")),
+            vec![(1, "models/scene.bin".to_string())]
+        );
+    }
+
+    #[test]
+    fn detects_image_formats() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0";
+        assert_eq!(
+            ImageFormat::detect(&[], Some("image/x-targa"), None).unwrap(),
+            ImageFormat::Tga
+        );
+        assert_eq!(
+            ImageFormat::detect(png, Some("application/octet-stream"), None).unwrap(),
+            ImageFormat::Png
+        );
+        assert_eq!(
+            ImageFormat::detect(b"BM\0\0", None, Some("BMP")).unwrap(),
+            ImageFormat::Bmp
+        );
+        assert!(ImageFormat::detect(b"\xabKTX 20\xbb", Some("image/ktx2"), None).is_err());
+        let ktx2 = b"\xabKTX 20\xbb\r\n\x1a\n\0\0";
+        let error = ImageFormat::detect(ktx2, Some("application/octet-stream"), None).unwrap_err();
+        assert!(error.to_string().contains("KTX2"));
+    }
+}
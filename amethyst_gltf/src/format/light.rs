@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use log::warn;
+use serde_json::Value;
+
+use amethyst_core::math::{UnitQuaternion, Vector3};
+use amethyst_renderer::{DirectionalLight, Light, LightPrefab, PointLight, Rgba, SpotLight};
+
+const EXTENSION: &str = "KHR_lights_punctual";
+
+/// glTF lights point along the negative Z axis of their node.
+const FORWARD: [f32; 3] = [0.0, 0.0, -1.0];
+
+/// Converts the lights of the `KHR_lights_punctual` extension, by the index of the node they are
+/// attached to.
+///
+/// The intensity is used as is, and lights without a range get the default radius of the
+/// renderer. Directional lights have no intensity in the renderer.
+pub fn node_lights(root: &Value) -> HashMap<usize, Light> {
+    let lights = root
+        .get("extensions")
+        .and_then(|extensions| extensions.get(EXTENSION))
+        .and_then(|extension| extension.get("lights"))
+        .and_then(Value::as_array)
+        .map(|lights| lights.iter().map(convert_light).collect::<Vec<_>>())
+        .unwrap_or_default();
+    root.get("nodes")
+        .and_then(Value::as_array)
+        .map(|nodes| {
+            nodes
+                .iter()
+                .enumerate()
+                .filter_map(|(index, node)| {
+                    let light = node
+                        .get("extensions")?
+                        .get(EXTENSION)?
+                        .get("light")?
+                        .as_u64()?;
+                    let light = lights.get(light as usize)?.clone()?;
+                    Some((index, light))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Creates the prefab of a light, pointing it along the negative Z axis of the global rotation
+/// of its node, since the renderer doesn't use the rotation of lights.
+pub fn load_light(light: &Light, rotation: &UnitQuaternion<f32>) -> LightPrefab {
+    let rotate = |direction: &mut [f32; 3]| {
+        *direction = (rotation * Vector3::from(*direction)).into();
+    };
+    let mut light = light.clone();
+    match light {
+        Light::Directional(ref mut light) => rotate(&mut light.direction),
+        Light::Spot(ref mut light) => rotate(&mut light.direction),
+        _ => {}
+    }
+    LightPrefab {
+        light: Some(light),
+        ..LightPrefab::default()
+    }
+}
+
+fn convert_light(light: &Value) -> Option<Light> {
+    let number = |value: Option<&Value>, default: f32| {
+        value
+            .and_then(Value::as_f64)
+            .map_or(default, |value| value as f32)
+    };
+    let color = light
+        .get("color")
+        .and_then(Value::as_array)
+        .map_or(Rgba::WHITE, |color| {
+            Rgba(
+                number(color.get(0), 1.0),
+                number(color.get(1), 1.0),
+                number(color.get(2), 1.0),
+                1.0,
+            )
+        });
+    let intensity = number(light.get("intensity"), 1.0);
+    let range = light.get("range").and_then(Value::as_f64);
+    match light.get("type").and_then(Value::as_str) {
+        Some("directional") => Some(
+            DirectionalLight {
+                color,
+                direction: FORWARD,
+            }
+            .into(),
+        ),
+        Some("point") => {
+            let default = PointLight::default();
+            Some(
+                PointLight {
+                    color,
+                    intensity,
+                    radius: range.map_or(default.radius, |range| range as f32),
+                    ..default
+                }
+                .into(),
+            )
+        }
+        Some("spot") => {
+            let default = SpotLight::default();
+            let spot = light.get("spot");
+            Some(
+                SpotLight {
+                    angle: number(
+                        spot.and_then(|spot| spot.get("outerConeAngle")),
+                        std::f32::consts::FRAC_PI_4,
+                    ),
+                    color,
+                    direction: FORWARD,
+                    intensity,
+                    range: range.map_or(default.range, |range| range as f32),
+                    ..default
+                }
+                .into(),
+            )
+        }
+        other => {
+            warn!("Ignoring glTF light of unknown type {:?}", other);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &str = r#"{
+        "extensions": {
+            "KHR_lights_punctual": {
+                "lights": [
+                    { "type": "point", "color": [1.0, 0.5, 0.0], "intensity": 20.0 },
+                    { "type": "spot", "range": 4.0, "spot": { "outerConeAngle": 0.5 } },
+                    { "type": "directional" }
+                ]
+            }
+        },
+        "nodes": [
+            { "extensions": { "KHR_lights_punctual": { "light": 1 } } },
+            { "name": "empty" },
+            { "extensions": { "KHR_lights_punctual": { "light": 0 } } },
+            { "extensions": { "KHR_lights_punctual": { "light": 2 } } }
+        ]
+    }"#;
+
+    #[test]
+    fn finds_node_lights() {
+        let lights = node_lights(&serde_json::from_str(DOCUMENT).unwrap());
+        assert_eq!(lights.len(), 3);
+        assert!(!lights.contains_key(&1));
+        match lights[&0] {
+            Light::Spot(ref spot) => {
+                assert_eq!(spot.angle, 0.5);
+                assert_eq!(spot.range, 4.0);
+                assert_eq!(spot.direction, FORWARD);
+            }
+            ref light => panic!("Expected a spot light, got {:?}", light),
+        }
+        match lights[&2] {
+            Light::Point(ref point) => {
+                assert_eq!(point.color, Rgba(1.0, 0.5, 0.0, 1.0));
+                assert_eq!(point.intensity, 20.0);
+            }
+            ref light => panic!("Expected a point light, got {:?}", light),
+        }
+        assert!(node_lights(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn rotates_directions() {
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::PI);
+        let prefab = load_light(&Light::from(DirectionalLight::default()), &rotation);
+        match prefab.light {
+            Some(Light::Directional(ref light)) => {
+                assert!((light.direction[0] - 1.0).abs() < 1e-5);
+                assert!((light.direction[2] - 1.0).abs() < 1e-5);
+            }
+            ref light => panic!("Expected a directional light, got {:?}", light),
+        }
+    }
+}
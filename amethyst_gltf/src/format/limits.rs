@@ -0,0 +1,265 @@
+use std::{collections::HashSet, path::Path, sync::Arc};
+
+use amethyst_assets::Source;
+use amethyst_error::Error;
+use gltf::{mesh::Semantic, Gltf};
+
+use crate::{error, GltfSceneLimits, GltfSceneOptions};
+
+use super::{find_node, find_scene, get_scene_index};
+
+/// Checks the document against the limits before its buffers are loaded, using only the declared
+/// buffer lengths, accessor counts and image sizes.
+///
+/// Only the nodes loaded for the sub asset `address` are counted. A missing sub asset is not an
+/// error here, it is reported when the scene is loaded.
+pub fn check_document(
+    gltf: &Gltf,
+    address: Option<&str>,
+    options: &GltfSceneOptions,
+    source: Arc<dyn Source>,
+    base_path: &str,
+) -> Result<(), Error> {
+    let limits = &options.limits;
+    if let Some(limit) = limits.max_buffer_bytes {
+        let bytes = gltf
+            .buffers()
+            .map(|buffer| buffer.length() as u64)
+            .sum::<u64>();
+        if bytes > limit {
+            return Err(error::Error::LimitExceeded("buffer bytes", bytes, limit).into());
+        }
+    }
+
+    match address {
+        Some(address) if address.starts_with("node/") => {
+            if let Some(node) = find_node(gltf, &address["node/".len()..]) {
+                check_limits(gltf, Some(node), limits, source, base_path)?;
+            }
+        }
+        Some(address) if address.starts_with("scene/") => {
+            if let Some(index) = find_scene(gltf, &address["scene/".len()..]) {
+                let scene = gltf.scenes().nth(index).expect("Scene index was checked");
+                check_limits(gltf, scene.nodes(), limits, source, base_path)?;
+            }
+        }
+        Some(_) => {}
+        None if options.load_all_scenes => {
+            for scene in gltf.scenes() {
+                check_limits(gltf, scene.nodes(), limits, source.clone(), base_path)?;
+            }
+        }
+        None => {
+            let index = get_scene_index(gltf, options)?;
+            if let Some(scene) = gltf.scenes().nth(index) {
+                check_limits(gltf, scene.nodes(), limits, source, base_path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks the nodes below `roots` against the limits, using only the accessor counts and image
+/// sizes from the document.
+fn check_limits<'a, I>(
+    gltf: &'a Gltf,
+    roots: I,
+    limits: &GltfSceneLimits,
+    source: Arc<dyn Source>,
+    base_path: &str,
+) -> Result<(), Error>
+where
+    I: IntoIterator<Item = gltf::Node<'a>>,
+{
+    if limits.max_nodes.is_none()
+        && limits.max_vertices.is_none()
+        && limits.max_texture_bytes.is_none()
+    {
+        return Ok(());
+    }
+
+    let mut nodes = 0;
+    let mut vertices = 0;
+    let mut images = HashSet::new();
+    let mut stack = roots.into_iter().collect::<Vec<_>>();
+    while let Some(node) = stack.pop() {
+        nodes += 1;
+        check("nodes", nodes, limits.max_nodes)?;
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                vertices += primitive
+                    .get(&Semantic::Positions)
+                    .map_or(0, |accessor| accessor.count());
+                let material = primitive.material();
+                let pbr = material.pbr_metallic_roughness();
+                let textures = vec![
+                    pbr.base_color_texture().map(|info| info.texture()),
+                    pbr.metallic_roughness_texture().map(|info| info.texture()),
+                    material.emissive_texture().map(|info| info.texture()),
+                    material.normal_texture().map(|info| info.texture()),
+                    material.occlusion_texture().map(|info| info.texture()),
+                ];
+                images.extend(textures.into_iter().flatten().map(|t| t.source().index()));
+            }
+            check("vertices", vertices, limits.max_vertices)?;
+        }
+        stack.extend(node.children());
+    }
+
+    if let Some(limit) = limits.max_texture_bytes {
+        let bytes = gltf
+            .images()
+            .filter(|image| images.contains(&image.index()))
+            .filter_map(|image| image_size(&image, &*source, base_path))
+            .sum::<u64>();
+        if bytes > limit {
+            return Err(error::Error::LimitExceeded("texture bytes", bytes, limit).into());
+        }
+    }
+    Ok(())
+}
+
+fn check(what: &'static str, count: usize, limit: Option<usize>) -> Result<(), Error> {
+    match limit {
+        Some(limit) if count > limit => {
+            Err(error::Error::LimitExceeded(what, count as u64, limit as u64).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The encoded size of an image, or `None` if the source can't tell the size of its file.
+fn image_size(image: &gltf::Image<'_>, source: &dyn Source, base_path: &str) -> Option<u64> {
+    use gltf::image::Source as ImageSource;
+    match image.source() {
+        ImageSource::View { view, .. } => Some(view.length() as u64),
+        ImageSource::Uri { uri, .. } if uri.starts_with("data:") => {
+            // Base64 stores 3 bytes in 4 characters.
+            let encoded = uri.len() - uri.find(',').map_or(0, |comma| comma + 1);
+            Some(encoded as u64 * 3 / 4)
+        }
+        ImageSource::Uri { uri, .. } => {
+            let path = Path::new(base_path)
+                .parent()
+                .unwrap_or(Path::new("./"))
+                .join(uri);
+            path.to_str().and_then(|path| source.size(path))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use amethyst_assets::Source;
+    use amethyst_error::{format_err, Error};
+    use gltf::Gltf;
+
+    use super::check_document;
+    use crate::{format::load_gltf, GltfSceneLimits, GltfSceneOptions};
+
+    const DOCUMENT: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }, { "nodes": [1] }],
+        "nodes": [
+            { "name": "Tank", "mesh": 0, "children": [1] },
+            { "name": "Turret", "mesh": 0 }
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+        "accessors": [{
+            "bufferView": 0,
+            "componentType": 5126,
+            "count": 100,
+            "type": "VEC3",
+            "min": [0.0, 0.0, 0.0],
+            "max": [1.0, 1.0, 1.0]
+        }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 1200 }],
+        "buffers": [{ "byteLength": 1200, "uri": "tank.bin" }]
+    }"#;
+
+    /// Serves the document and records the files loaded from it.
+    #[derive(Default)]
+    struct DocumentSource {
+        loaded: Mutex<Vec<String>>,
+    }
+
+    impl Source for DocumentSource {
+        fn modified(&self, _path: &str) -> Result<u64, Error> {
+            Ok(0)
+        }
+
+        fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+            self.loaded.lock().unwrap().push(path.to_string());
+            match path {
+                "tank.gltf" => Ok(DOCUMENT.as_bytes().to_vec()),
+                _ => Err(format_err!("No file {}", path)),
+            }
+        }
+    }
+
+    fn options(limits: GltfSceneLimits) -> GltfSceneOptions {
+        GltfSceneOptions {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    fn check(address: Option<&str>, options: &GltfSceneOptions) -> bool {
+        let gltf = Gltf::from_slice(DOCUMENT.as_bytes()).unwrap();
+        let source = Arc::new(DocumentSource::default());
+        check_document(&gltf, address, options, source, "tank.gltf").is_ok()
+    }
+
+    #[test]
+    fn rejects_documents_over_the_limits() {
+        let mut vertices = options(GltfSceneLimits {
+            max_vertices: Some(150),
+            ..Default::default()
+        });
+        assert!(!check(None, &vertices));
+        assert!(check(Some("node/Turret"), &vertices));
+        assert!(check(Some("scene/1"), &vertices));
+        vertices.load_all_scenes = true;
+        assert!(!check(None, &vertices));
+
+        let nodes = options(GltfSceneLimits {
+            max_nodes: Some(1),
+            ..Default::default()
+        });
+        assert!(!check(None, &nodes));
+        assert!(check(Some("node/Turret"), &nodes));
+
+        let buffers = options(GltfSceneLimits {
+            max_buffer_bytes: Some(1000),
+            ..Default::default()
+        });
+        assert!(!check(Some("node/Turret"), &buffers));
+        assert!(check(None, &options(GltfSceneLimits::default())));
+    }
+
+    #[test]
+    fn rejects_documents_before_loading_buffers() {
+        let source = Arc::new(DocumentSource::default());
+        let limits = GltfSceneLimits {
+            max_vertices: Some(150),
+            ..Default::default()
+        };
+        let loaded = load_gltf::<f32>(
+            source.clone(),
+            "tank.gltf",
+            options(limits),
+            &HashMap::new(),
+        );
+        assert!(loaded.is_err());
+        assert_eq!(
+            *source.loaded.lock().unwrap(),
+            vec!["tank.gltf".to_string()]
+        );
+    }
+}
@@ -7,31 +7,41 @@ use itertools::Itertools;
 use amethyst_assets::Source;
 use amethyst_error::Error;
 use amethyst_renderer::{
-    JpgFormat, MaterialPrefab, PngFormat, TextureData, TextureFormat, TextureMetadata,
-    TexturePrefab,
+    BmpFormat, JpgFormat, MaterialPrefab, PngFormat, TextureData, TextureFormat, TextureMetadata,
+    TexturePrefab, TgaFormat,
 };
 
-use super::{get_image_data, Buffers, ImageFormat};
+use super::{external_image, get_image_data, Buffers, ImageFormat};
 
 // Load a single material, and transform into a format usable by the engine
+//
+// A baked vertex color is multiplied into the base color factor, and materials with the
+// `KHR_materials_unlit` extension are flagged as unlit. With `stream`, textures in their own
+// files are loaded by the `Loader` later, see `GltfSceneOptions::stream_textures`.
 pub fn load_material(
     material: &gltf::Material<'_>,
+    vertex_color: Option<[f32; 4]>,
     buffers: &Buffers,
     source: Arc<dyn Source>,
     name: &str,
+    stream: bool,
 ) -> Result<MaterialPrefab<TextureFormat>, Error> {
     let mut prefab = MaterialPrefab::default();
-    prefab.albedo = Some(
-        load_texture_with_factor(
-            material.pbr_metallic_roughness().base_color_texture(),
-            material.pbr_metallic_roughness().base_color_factor(),
-            buffers,
-            source.clone(),
-            name,
-            true,
-        )
-        .map(|(texture, _)| TexturePrefab::Data(texture))?,
-    );
+    let mut base_color = material.pbr_metallic_roughness().base_color_factor();
+    if let Some(color) = vertex_color {
+        for (b, c) in base_color.iter_mut().zip(color.iter()) {
+            *b *= c;
+        }
+    }
+    prefab.albedo = Some(load_texture_or_color(
+        material.pbr_metallic_roughness().base_color_texture(),
+        base_color,
+        buffers,
+        source.clone(),
+        name,
+        true,
+        stream,
+    )?);
 
     let (metallic, roughness) = load_texture_with_factor(
         material
@@ -59,49 +69,44 @@ pub fn load_material(
     prefab.roughness = Some(roughness);
 
     let em_factor = material.emissive_factor();
-    prefab.emission = Some(
-        load_texture_with_factor(
-            material.emissive_texture(),
-            [em_factor[0], em_factor[1], em_factor[2], 1.0],
+    prefab.emission = Some(load_texture_or_color(
+        material.emissive_texture(),
+        [em_factor[0], em_factor[1], em_factor[2], 1.0],
+        buffers,
+        source.clone(),
+        name,
+        false,
+        stream,
+    )?);
+
+    // Can't use map/and_then because of Result returning from the load_texture function
+    prefab.normal = match material.normal_texture() {
+        Some(normal_texture) => Some(load_texture_prefab(
+            &normal_texture.texture(),
             buffers,
             source.clone(),
             name,
             false,
-        )
-        .map(|(texture, _)| TexturePrefab::Data(texture))?,
-    );
-
-    // Can't use map/and_then because of Result returning from the load_texture function
-    prefab.normal = match material.normal_texture() {
-        Some(normal_texture) => Some(
-            load_texture(
-                &normal_texture.texture(),
-                buffers,
-                source.clone(),
-                name,
-                false,
-            )
-            .map(|data| TexturePrefab::Data(data))?,
-        ),
+            stream,
+        )?),
 
         None => None,
     };
 
     // Can't use map/and_then because of Result returning from the load_texture function
     prefab.ambient_occlusion = match material.occlusion_texture() {
-        Some(occlusion_texture) => Some(
-            load_texture(
-                &occlusion_texture.texture(),
-                buffers,
-                source.clone(),
-                name,
-                false,
-            )
-            .map(|data| TexturePrefab::Data(data))?,
-        ),
+        Some(occlusion_texture) => Some(load_texture_prefab(
+            &occlusion_texture.texture(),
+            buffers,
+            source.clone(),
+            name,
+            false,
+            stream,
+        )?),
 
         None => None,
     };
+    prefab.unlit = buffers.is_unlit(material);
     prefab.transparent = if let AlphaMode::Blend = material.alpha_mode() {
         true
     } else {
@@ -174,23 +179,72 @@ fn load_texture_with_factor(
     }
 }
 
-fn load_texture(
+fn load_texture_or_color(
+    texture: Option<gltf::texture::Info<'_>>,
+    color: [f32; 4],
+    buffers: &Buffers,
+    source: Arc<dyn Source>,
+    name: &str,
+    srgb: bool,
+    stream: bool,
+) -> Result<TexturePrefab<TextureFormat>, Error> {
+    match texture {
+        Some(info) => load_texture_prefab(&info.texture(), buffers, source, name, srgb, stream),
+        None => Ok(TexturePrefab::Data(TextureData::color(color))),
+    }
+}
+
+/// Loads a texture now, or refers to its file if it has one and `stream` is set, so the `Loader`
+/// reads it on its own thread with the progress of the prefab.
+fn load_texture_prefab(
     texture: &gltf::Texture<'_>,
     buffers: &Buffers,
     source: Arc<dyn Source>,
     name: &str,
     srgb: bool,
-) -> Result<TextureData, Error> {
-    let (data, format) = get_image_data(&texture.source(), buffers, source, name.as_ref())?;
+    stream: bool,
+) -> Result<TexturePrefab<TextureFormat>, Error> {
+    if stream {
+        if let Some((path, format)) = external_image(&texture.source(), name.as_ref()) {
+            let format = match format {
+                ImageFormat::Png => TextureFormat::Png,
+                ImageFormat::Jpeg => TextureFormat::Jpg,
+                ImageFormat::Bmp => TextureFormat::Bmp,
+                ImageFormat::Tga => TextureFormat::Tga,
+            };
+            return Ok(TexturePrefab::File(
+                path,
+                format,
+                texture_metadata(texture, srgb),
+            ));
+        }
+    }
+    load_texture(texture, buffers, source, name, srgb).map(TexturePrefab::Data)
+}
 
+fn texture_metadata(texture: &gltf::Texture<'_>, srgb: bool) -> TextureMetadata {
     let metadata = match srgb {
         true => TextureMetadata::srgb(),
         false => TextureMetadata::unorm(),
     };
-    let metadata = metadata.with_sampler(load_sampler_info(&texture.sampler()));
+    metadata.with_sampler(load_sampler_info(&texture.sampler()))
+}
+
+fn load_texture(
+    texture: &gltf::Texture<'_>,
+    buffers: &Buffers,
+    source: Arc<dyn Source>,
+    name: &str,
+    srgb: bool,
+) -> Result<TextureData, Error> {
+    let (data, format) = get_image_data(&texture.source(), buffers, source, name.as_ref())?;
+
+    let metadata = texture_metadata(texture, srgb);
     Ok(match format {
         ImageFormat::Png => PngFormat::from_data(&data, metadata),
         ImageFormat::Jpeg => JpgFormat::from_data(&data, metadata),
+        ImageFormat::Bmp => BmpFormat::from_data(&data, metadata),
+        ImageFormat::Tga => TgaFormat::from_data(&data, metadata),
     }?)
 }
 
@@ -1,28 +1,55 @@
-use std::ops::Range;
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 
 use amethyst_error::Error;
-use amethyst_renderer::{AnimatedComboMeshCreator, Attribute, MeshData, Separate};
+use amethyst_renderer::{
+    AnimatedComboMeshCreator, Attribute, MeshData, MorphTarget, MorphTargets, Separate,
+};
 use log::trace;
 
-use super::Buffers;
-use crate::{error, GltfSceneOptions};
+use gltf::mesh::Semantic;
+
+use super::{
+    attribute::{is_quantized, read_accessor_vec3, PrimitiveReader},
+    optimize::optimize,
+    simplify::simplify,
+    Buffers,
+};
+use crate::{error, GltfCollisionGeometry, GltfSceneOptions, NormalGeneration};
+
+/// A primitive loaded by `load_mesh`, or a cell of it if `GltfSceneOptions::split_cell_size` is
+/// set.
+pub struct Primitive {
+    /// The index of the primitive in the glTF mesh.
+    pub index: usize,
+    pub mesh: MeshData,
+    /// The simplified meshes with the fraction of the triangles they keep, if enabled.
+    pub lods: Vec<(f32, MeshData)>,
+    pub material: Option<usize>,
+    pub bounds: Range<[f32; 3]>,
+    pub baked_color: Option<[f32; 4]>,
+    /// The morph targets, matching the vertices of `mesh` but not of the `lods`.
+    pub morph_targets: Option<MorphTargets>,
+}
 
 pub fn load_mesh(
     mesh: &gltf::Mesh<'_>,
     buffers: &Buffers,
     options: &GltfSceneOptions,
-) -> Result<Vec<(MeshData, Option<usize>, Range<[f32; 3]>)>, Error> {
+) -> Result<Vec<Primitive>, Error> {
     trace!("Loading mesh");
     let mut primitives = vec![];
 
-    for primitive in mesh.primitives() {
+    for (index, primitive) in mesh.primitives().enumerate() {
         trace!("Loading mesh primitive");
-        let reader = primitive.reader(|buffer| buffers.buffer(&buffer));
+        let reader = PrimitiveReader::new(mesh, index, &primitive, buffers)?;
 
         trace!("Loading faces");
         let faces = reader
-            .read_indices()
-            .map(|indices| indices.into_u32())
+            .indices()
+            .map(|indices| indices.into_iter())
             .map(|mut indices| {
                 let mut faces = vec![];
                 while let (Some(a), Some(b), Some(c)) =
@@ -37,142 +64,444 @@ pub fn load_mesh(
 
         trace!("Loading positions");
         let positions = reader
-            .read_positions()
+            .vec3(&Semantic::Positions)?
             .map(|positions| match faces {
-                Some(ref faces) => {
-                    let vertices = positions.collect::<Vec<_>>();
-                    faces.iter().map(|i| vertices[*i]).collect::<Vec<_>>()
-                }
-                None => positions.collect(),
+                Some(ref faces) => faces.iter().map(|i| positions[*i]).collect::<Vec<_>>(),
+                None => positions,
             })
             .ok_or(error::Error::MissingPositions)?;
 
         trace!("Loading normals");
+        let quantized_normals = is_quantized(&primitive, &Semantic::Normals);
         let normals = reader
-            .read_normals()
+            .vec3(&Semantic::Normals)?
             .map(|normals| match faces {
-                Some(ref faces) => {
-                    let normals = normals.collect::<Vec<_>>();
-                    faces.iter().map(|i| normals[*i]).collect()
+                Some(ref faces) => faces.iter().map(|i| normals[*i]).collect(),
+                None => normals,
+            })
+            .map(|normals: Vec<[f32; 3]>| {
+                if quantized_normals {
+                    normals.into_iter().map(normalize).collect()
+                } else {
+                    normals
                 }
-                None => normals.collect(),
             })
-            .unwrap_or_else(|| {
-                use amethyst_core::math::Point3;
-                use std::iter::once;
-                let f = faces
-                    .as_ref()
-                    .map(|f| f.clone())
-                    .unwrap_or_else(|| (0..positions.len()).collect::<Vec<_>>());
-                f.chunks(3)
-                    .flat_map(|chunk| {
-                        let a = Point3::from(positions[chunk[0]]);
-                        let ab = Point3::from(positions[chunk[1]]) - a;
-                        let ac = Point3::from(positions[chunk[2]]) - a;
-                        let normal: [f32; 3] = ab.cross(&ac).into();
-                        once(normal.clone())
-                            .chain(once(normal.clone()))
-                            .chain(once(normal))
-                    })
-                    .collect::<Vec<_>>()
+            .unwrap_or_else(|| match options.generate_normals {
+                NormalGeneration::Flat => flat_normals(&positions),
+                NormalGeneration::Smooth => smooth_normals(&positions),
             });
 
         trace!("Loading texture coordinates");
-        let tex_coord = reader
-            .read_tex_coords(0)
-            .map(|tex_coords| tex_coords.into_f32().collect::<Vec<[f32; 2]>>())
-            .unwrap_or_else(|| {
-                vec![
-                    [options.generate_tex_coords.0, options.generate_tex_coords.1];
-                    positions.len()
-                ]
-            });
+        let tex_coord = reader.vec2(&Semantic::TexCoords(0))?.unwrap_or_else(|| {
+            vec![[options.generate_tex_coords.0, options.generate_tex_coords.1]; positions.len()]
+        });
+        // The renderer has no texture transforms, so the one of `KHR_texture_transform` is baked
+        // into the coordinates, before they are flipped since it is in glTF texture space.
+        let texture_transform = buffers.texture_transform(&primitive.material());
+        let convert = |uv: [f32; 2]| {
+            let uv = texture_transform.map_or(uv, |transform| transform.apply(uv));
+            flip_check(uv, options.flip_v_coord)
+        };
         let tex_coord: Vec<[f32; 2]> = match faces {
-            Some(ref faces) => faces
-                .iter()
-                .map(|i| flip_check(tex_coord[*i], options.flip_v_coord))
-                .collect(),
-            None => tex_coord
-                .into_iter()
-                .map(|t| flip_check(t, options.flip_v_coord))
-                .collect(),
+            Some(ref faces) => faces.iter().map(|i| convert(tex_coord[*i])).collect(),
+            None => tex_coord.into_iter().map(convert).collect(),
         };
 
         trace!("Loading tangents");
         let tangents = reader
-            .read_tangents()
+            .vec4(&Semantic::Tangents)?
             .map(|tangents| match faces {
-                Some(ref faces) => {
-                    let tangents = tangents.collect::<Vec<_>>();
-                    faces
-                        .iter()
-                        .map(|i| [tangents[*i][0], tangents[*i][1], tangents[*i][2]])
-                        .collect()
-                }
-                None => tangents.map(|t| [t[0], t[1], t[2]]).collect(),
+                Some(ref faces) => faces
+                    .iter()
+                    .map(|i| [tangents[*i][0], tangents[*i][1], tangents[*i][2]])
+                    .collect(),
+                None => tangents.iter().map(|t| [t[0], t[1], t[2]]).collect(),
             })
-            .unwrap_or_else(|| calculate_tangents(&positions, &normals, &tex_coord));
+            .unwrap_or_else(|| {
+                if options.generate_tangents {
+                    calculate_tangents(&positions, &normals, &tex_coord)
+                } else {
+                    normals.iter().cloned().map(perpendicular).collect()
+                }
+            });
 
         trace!("Loading bounding box");
-        let bounds = primitive.bounding_box();
-        let bounds = bounds.min..bounds.max;
+        let bounds = if is_quantized(&primitive, &Semantic::Positions) {
+            // The bounds of normalized accessors are stored as integers.
+            position_bounds(&positions)
+        } else {
+            let bounds = primitive.bounding_box();
+            bounds.min..bounds.max
+        };
 
         trace!("Loading colors");
         let colors = reader
-            .read_colors(0)
-            .map(|colors| colors.into_rgba_f32())
+            .color(&Semantic::Colors(0))?
             .map(|colors| match faces {
-                Some(ref faces) => {
-                    let colors = colors.collect::<Vec<_>>();
-                    faces.iter().map(|i| colors[*i]).collect()
-                }
-                None => colors.collect(),
+                Some(ref faces) => pick(&colors, faces),
+                None => colors,
             });
+        let bake = options.bake_vertex_colors
+            && primitive
+                .material()
+                .pbr_metallic_roughness()
+                .base_color_texture()
+                .is_none();
+        let (colors, baked_color) = match colors {
+            Some(ref colors) if bake && !colors.is_empty() => {
+                trace!("Baking colors");
+                (None, Some(average_color(colors)))
+            }
+            colors => (colors, None),
+        };
 
         trace!("Loading joint ids");
-        let joint_ids = reader
-            .read_joints(0)
-            .map(|joints| joints.into_u16())
-            .map(|joints| match faces {
-                Some(ref faces) => {
-                    let joints = joints.collect::<Vec<_>>();
-                    faces.iter().map(|i| joints[*i]).collect()
-                }
-                None => joints.collect(),
-            });
+        let joint_ids = reader.joints()?.map(|joints| match faces {
+            Some(ref faces) => pick(&joints, faces),
+            None => joints,
+        });
         trace!("Joint ids: {:?}", joint_ids);
 
         trace!("Loading joint weights");
-        let joint_weights = reader
-            .read_weights(0)
-            .map(|weights| weights.into_f32())
-            .map(|weights| match faces {
-                Some(ref faces) => {
-                    let weights = weights.collect::<Vec<_>>();
-                    faces.iter().map(|i| weights[*i]).collect()
-                }
-                None => weights.collect(),
-            });
+        let joint_weights = reader.weights()?.map(|weights| match faces {
+            Some(ref faces) => pick(&weights, faces),
+            None => weights,
+        });
         trace!("Joint weights: {:?}", joint_weights);
 
+        trace!("Loading morph targets");
+        let unindex = |offsets: Vec<[f32; 3]>| match faces {
+            Some(ref faces) => pick(&offsets, faces),
+            None => offsets,
+        };
+        let read_offsets = |accessor: Option<gltf::Accessor<'_>>| -> Result<_, Error> {
+            Ok(match accessor {
+                Some(ref accessor) => unindex(read_accessor_vec3(accessor, buffers)?),
+                None => Vec::new(),
+            })
+        };
+        let morph_targets = primitive
+            .morph_targets()
+            .map(|target| {
+                Ok(MorphTarget {
+                    positions: read_offsets(target.positions())?,
+                    normals: read_offsets(target.normals())?,
+                    tangents: read_offsets(target.tangents())?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
         let material = primitive.material().index();
 
-        let creator = AnimatedComboMeshCreator::new((
-            cast_attribute(positions),
-            colors.map(cast_attribute),
-            Some(cast_attribute(tex_coord)),
-            Some(cast_attribute(normals)),
-            Some(cast_attribute(tangents)),
-            joint_ids.map(cast_attribute),
-            joint_weights.map(cast_attribute),
-        ));
+        let vertices = Vertices {
+            positions,
+            colors,
+            tex_coord,
+            normals,
+            tangents,
+            joint_ids,
+            joint_weights,
+            morph_targets,
+        };
+        let cells = match options.split_cell_size {
+            Some(size) if (0..3).any(|axis| bounds.end[axis] - bounds.start[axis] > size) => {
+                trace!("Splitting mesh");
+                vertices
+                    .split(size)
+                    .into_iter()
+                    .map(|cell| {
+                        let bounds = position_bounds(&cell.positions);
+                        (cell, bounds)
+                    })
+                    .collect()
+            }
+            _ => vec![(vertices, bounds)],
+        };
 
-        primitives.push((creator.into(), material, bounds));
+        for (vertices, bounds) in cells {
+            let (mesh, lods) = match options.simplify {
+                Some(ref simplification) => {
+                    trace!("Simplifying mesh");
+                    let max_error = simplification.max_error;
+                    let lods = simplification
+                        .lods
+                        .iter()
+                        .map(|&ratio| {
+                            let lod = vertices.simplify(ratio, max_error);
+                            (ratio, lod.into_mesh(options.optimize_meshes).0)
+                        })
+                        .collect();
+                    let mesh = vertices.simplify(simplification.ratio, max_error);
+                    (mesh.into_mesh(options.optimize_meshes), lods)
+                }
+                None => (vertices.into_mesh(options.optimize_meshes), Vec::new()),
+            };
+            let (mesh, morph_targets) = mesh;
+
+            primitives.push(Primitive {
+                index,
+                mesh,
+                lods,
+                material,
+                bounds,
+                baked_color,
+                morph_targets,
+            });
+        }
     }
     trace!("Loaded mesh");
     Ok(primitives)
 }
 
+/// Reads the positions and triangles of all primitives of the mesh, without the other attributes.
+pub fn load_collision_geometry(
+    mesh: &gltf::Mesh<'_>,
+    buffers: &Buffers,
+) -> Result<GltfCollisionGeometry, Error> {
+    trace!("Loading collision geometry");
+    let mut geometry = GltfCollisionGeometry::default();
+    for (index, primitive) in mesh.primitives().enumerate() {
+        let reader = PrimitiveReader::new(mesh, index, &primitive, buffers)?;
+        let positions = reader
+            .vec3(&Semantic::Positions)?
+            .ok_or(error::Error::MissingPositions)?;
+        let base = geometry.positions.len() as u32;
+        let mut indices = reader
+            .indices()
+            .unwrap_or_else(|| (0..positions.len() as u32).collect());
+        indices.truncate(indices.len() - indices.len() % 3);
+        geometry
+            .indices
+            .extend(indices.into_iter().map(|index| base + index));
+        geometry.positions.extend(positions);
+    }
+    Ok(geometry)
+}
+
+/// The attributes of a primitive, one per triangle corner.
+struct Vertices {
+    positions: Vec<[f32; 3]>,
+    colors: Option<Vec<[f32; 4]>>,
+    tex_coord: Vec<[f32; 2]>,
+    normals: Vec<[f32; 3]>,
+    tangents: Vec<[f32; 3]>,
+    joint_ids: Option<Vec<[u16; 4]>>,
+    joint_weights: Option<Vec<[f32; 4]>>,
+    morph_targets: Vec<MorphTarget>,
+}
+
+impl Vertices {
+    /// Returns the vertices at `order`.
+    fn select(&self, order: &[usize]) -> Vertices {
+        Vertices {
+            positions: pick(&self.positions, order),
+            colors: self.colors.as_ref().map(|colors| pick(colors, order)),
+            tex_coord: pick(&self.tex_coord, order),
+            normals: pick(&self.normals, order),
+            tangents: pick(&self.tangents, order),
+            joint_ids: self.joint_ids.as_ref().map(|ids| pick(ids, order)),
+            joint_weights: self
+                .joint_weights
+                .as_ref()
+                .map(|weights| pick(weights, order)),
+            morph_targets: self
+                .morph_targets
+                .iter()
+                .map(|target| MorphTarget {
+                    positions: pick_offsets(&target.positions, order),
+                    normals: pick_offsets(&target.normals, order),
+                    tangents: pick_offsets(&target.tangents, order),
+                })
+                .collect(),
+        }
+    }
+
+    fn simplify(&self, ratio: f32, max_error: Option<f32>) -> Vertices {
+        let corners = simplify(&self.positions, ratio, max_error);
+        let order = corners.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        let mut vertices = self.select(&order);
+        vertices.positions = corners.into_iter().map(|(_, position)| position).collect();
+        vertices
+    }
+
+    /// Splits the triangles into a grid of cubes with the given size, by the cell containing the
+    /// center of each triangle. Empty cells are skipped.
+    fn split(&self, cell_size: f32) -> Vec<Vertices> {
+        let mut cells = BTreeMap::new();
+        for (t, triangle) in self.positions.chunks(3).enumerate() {
+            let mut cell = [0; 3];
+            for axis in 0..3 {
+                let center = triangle.iter().map(|p| p[axis]).sum::<f32>() / triangle.len() as f32;
+                cell[axis] = (center / cell_size).floor() as i32;
+            }
+            cells
+                .entry(cell)
+                .or_insert_with(Vec::new)
+                .extend(t * 3..t * 3 + triangle.len());
+        }
+        cells.values().map(|order| self.select(order)).collect()
+    }
+
+    /// Merges the corners with equal attributes and returns the remaining vertices, with the
+    /// indices of the triangles optimized for the GPU.
+    fn index(&self) -> (Vertices, Vec<u32>) {
+        let mut unique = HashMap::new();
+        let mut order = Vec::new();
+        let mut indices = Vec::with_capacity(self.positions.len());
+        for corner in 0..self.positions.len() {
+            let index = *unique.entry(self.corner_key(corner)).or_insert_with(|| {
+                order.push(corner);
+                order.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+        let vertices = self.select(&order);
+        let order = optimize(&mut indices, &vertices.positions);
+        let order = order.into_iter().map(|i| i as usize).collect::<Vec<_>>();
+        (vertices.select(&order), indices)
+    }
+
+    /// The bits of all attributes of a corner.
+    fn corner_key(&self, corner: usize) -> Vec<u32> {
+        let mut key = Vec::with_capacity(24);
+        key.extend(self.positions[corner].iter().map(|v| v.to_bits()));
+        key.extend(self.tex_coord[corner].iter().map(|v| v.to_bits()));
+        key.extend(self.normals[corner].iter().map(|v| v.to_bits()));
+        key.extend(self.tangents[corner].iter().map(|v| v.to_bits()));
+        if let Some(ref colors) = self.colors {
+            key.extend(colors[corner].iter().map(|v| v.to_bits()));
+        }
+        if let Some(ref ids) = self.joint_ids {
+            key.extend(ids[corner].iter().map(|&v| u32::from(v)));
+        }
+        if let Some(ref weights) = self.joint_weights {
+            key.extend(weights[corner].iter().map(|v| v.to_bits()));
+        }
+        for target in &self.morph_targets {
+            for offsets in &[&target.positions, &target.normals, &target.tangents] {
+                if let Some(offset) = offsets.get(corner) {
+                    key.extend(offset.iter().map(|v| v.to_bits()));
+                }
+            }
+        }
+        key
+    }
+
+    /// Builds the mesh, and the morph targets if the primitive has any.
+    fn into_mesh(self, optimize: bool) -> (MeshData, Option<MorphTargets>) {
+        let (vertices, indices) = if optimize {
+            let (vertices, indices) = self.index();
+            (vertices, Some(indices))
+        } else {
+            (self, None)
+        };
+        let mut creator = AnimatedComboMeshCreator::new((
+            cast_attribute(vertices.positions),
+            vertices.colors.map(cast_attribute),
+            Some(cast_attribute(vertices.tex_coord)),
+            Some(cast_attribute(vertices.normals)),
+            Some(cast_attribute(vertices.tangents)),
+            vertices.joint_ids.map(cast_attribute),
+            vertices.joint_weights.map(cast_attribute),
+        ));
+        if let Some(indices) = indices {
+            creator = creator.with_indices(indices);
+        }
+        let morph_targets = if vertices.morph_targets.is_empty() {
+            None
+        } else {
+            Some(MorphTargets {
+                targets: vertices.morph_targets,
+            })
+        };
+        (creator.into(), morph_targets)
+    }
+}
+
+fn pick<T: Copy>(values: &[T], order: &[usize]) -> Vec<T> {
+    order.iter().map(|&i| values[i]).collect()
+}
+
+/// Picks the offsets of a morph target, which are empty if it doesn't move the attribute.
+fn pick_offsets(offsets: &[[f32; 3]], order: &[usize]) -> Vec<[f32; 3]> {
+    if offsets.is_empty() {
+        Vec::new()
+    } else {
+        pick(offsets, order)
+    }
+}
+
+fn average_color(colors: &[[f32; 4]]) -> [f32; 4] {
+    let mut sum = [0.0; 4];
+    for color in colors {
+        for (s, c) in sum.iter_mut().zip(color.iter()) {
+            *s += c;
+        }
+    }
+    let count = colors.len() as f32;
+    [
+        sum[0] / count,
+        sum[1] / count,
+        sum[2] / count,
+        sum[3] / count,
+    ]
+}
+
+/// The normal of the triangle of each corner, scaled by twice the area of the triangle.
+fn face_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    use amethyst_core::math::Point3;
+    let mut normals = Vec::with_capacity(positions.len());
+    for triangle in positions.chunks(3) {
+        let normal = if triangle.len() == 3 {
+            let a = Point3::from(triangle[0]);
+            let ab = Point3::from(triangle[1]) - a;
+            let ac = Point3::from(triangle[2]) - a;
+            ab.cross(&ac).into()
+        } else {
+            [0.0; 3]
+        };
+        normals.extend(triangle.iter().map(|_| normal));
+    }
+    normals
+}
+
+fn flat_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    face_normals(positions).into_iter().map(normalize).collect()
+}
+
+/// Averages the normals of the triangles around each position, weighted by their area.
+fn smooth_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let key = |position: &[f32; 3]| {
+        [
+            position[0].to_bits(),
+            position[1].to_bits(),
+            position[2].to_bits(),
+        ]
+    };
+    let mut sums = HashMap::new();
+    for (position, normal) in positions.iter().zip(face_normals(positions)) {
+        let sum = sums.entry(key(position)).or_insert([0.0; 3]);
+        for axis in 0..3 {
+            sum[axis] += normal[axis];
+        }
+    }
+    positions
+        .iter()
+        .map(|position| normalize(sums[&key(position)]))
+        .collect()
+}
+
+/// An arbitrary unit vector perpendicular to the normal.
+fn perpendicular(normal: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = normal;
+    // Cross with the axis the normal is least aligned with.
+    if x.abs() < 0.9 {
+        normalize([0.0, z, -y])
+    } else {
+        normalize([-z, 0.0, x])
+    }
+}
+
 fn calculate_tangents(
     positions: &[[f32; 3]],
     normals: &[[f32; 3]],
@@ -231,6 +560,27 @@ where
     new
 }
 
+fn position_bounds(positions: &[[f32; 3]]) -> Range<[f32; 3]> {
+    let mut min = [std::f32::MAX; 3];
+    let mut max = [std::f32::MIN; 3];
+    for position in positions {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    min..max
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length > 0.0 {
+        [v[0] / length, v[1] / length, v[2] / length]
+    } else {
+        v
+    }
+}
+
 fn flip_check(uv: [f32; 2], flip_v: bool) -> [f32; 2] {
     if flip_v {
         [uv[0], 1. - uv[1]]
@@ -241,7 +591,56 @@ fn flip_check(uv: [f32; 2], flip_v: bool) -> [f32; 2] {
 
 #[cfg(test)]
 mod tests {
-    use crate::format::mesh::calculate_tangents;
+    use crate::format::mesh::{
+        average_color, calculate_tangents, flat_normals, perpendicular, smooth_normals,
+    };
+
+    #[test]
+    fn test_average_color() {
+        let colors = &[
+            [1.0, 0.0, 0.5, 1.0],
+            [0.0, 0.0, 0.5, 1.0],
+            [0.5, 0.0, 0.5, 0.25],
+        ];
+        assert_eq!(average_color(colors), [0.5, 0.0, 0.5, 0.75]);
+    }
+
+    #[test]
+    fn test_generated_normals() {
+        // Two triangles folded along the X axis, facing up and towards positive Z.
+        let positions = &[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, -2.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        let flat = flat_normals(positions);
+        assert_eq!(flat[0], [0.0, 1.0, 0.0]);
+        assert_eq!(flat[3], [0.0, 0.0, 1.0]);
+
+        let smooth = smooth_normals(positions);
+        let shared = smooth[0];
+        assert_eq!(smooth[1], shared);
+        assert_eq!(smooth[3], shared);
+        assert_eq!(smooth[4], shared);
+        // The first triangle is twice as large, so it pulls the shared normal up.
+        assert!(shared[1] > shared[2] && shared[2] > 0.0);
+        assert_eq!(smooth[2], [0.0, 1.0, 0.0]);
+        assert_eq!(smooth[5], [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_perpendicular() {
+        for normal in &[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.6, 0.0, 0.8]] {
+            let tangent = perpendicular(*normal);
+            let dot = normal[0] * tangent[0] + normal[1] * tangent[1] + normal[2] * tangent[2];
+            let length = tangent.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!(dot.abs() < 1e-6);
+            assert!((length - 1.0).abs() < 1e-6);
+        }
+    }
 
     #[test]
     fn test_tangent_calc() {
@@ -1,35 +1,61 @@
 //! GLTF format
 
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Debug,
+    path::Path,
+    sync::Arc,
+};
 
 use gltf::{self, Gltf};
-use log::debug;
+use log::{debug, warn};
 use num_traits::NumCast;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use amethyst_animation::AnimationHierarchyPrefab;
-use amethyst_assets::{Format, FormatValue, Prefab, Source};
+use amethyst_animation::{AnimatablePrefab, AnimationHierarchyPrefab};
+use amethyst_assets::{split_sub_asset, Format, FormatValue, Prefab, Source};
 use amethyst_core::{
-    math::{Quaternion, RealField, Unit, Vector3},
+    math::{Quaternion, RealField, Unit, UnitQuaternion, Vector3},
     transform::Transform,
 };
 use amethyst_error::{format_err, Error, ResultExt};
+use amethyst_renderer::{Mesh, MorphWeights};
 
-use crate::{error, GltfMaterialSet, GltfNodeExtent, GltfPrefab, GltfSceneOptions, Named};
+use crate::{
+    error, GltfExtras, GltfMaterialSet, GltfNodeExtent, GltfPrefab, GltfPrefabScene,
+    GltfSceneOptions, GltfStreamedScene, Named,
+};
 
 use self::{
     animation::load_animations,
-    importer::{get_image_data, import, Buffers, ImageFormat},
+    camera::load_camera,
+    filter::skipped_nodes,
+    importer::{
+        external_buffers, external_image, get_image_data, import, import_document,
+        import_with_buffers, Buffers, ImageFormat,
+    },
+    light::load_light,
+    limits::check_document,
     material::load_material,
-    mesh::load_mesh,
+    mesh::{load_collision_geometry, load_mesh},
     skin::load_skin,
 };
 
 mod animation;
+mod attribute;
+mod camera;
+#[cfg(feature = "draco")]
+mod draco;
+mod filter;
 mod importer;
+mod light;
+mod limits;
 mod material;
 mod mesh;
+mod optimize;
+mod simplify;
 mod skin;
+mod texture_transform;
 
 /// Gltf scene format, will load a single scene from a Gltf file.
 ///
@@ -38,6 +64,14 @@ mod skin;
 /// as the root node of the scene hierarchy.
 ///
 /// See `GltfSceneOptions` for more information about the load options.
+///
+/// A part of the file can be loaded by appending a sub asset address to the name:
+///
+/// * `"scene.glb#node/Turret"` loads the node named `Turret` and its children, with the node as
+///   the root of the prefab.
+/// * `"scene.glb#scene/Level1"` loads the scene named `Level1`, `"scene.glb#scene/2"` the scene
+///   with index 2, overriding `GltfSceneOptions::scene_index` and
+///   `GltfSceneOptions::load_all_scenes`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GltfSceneFormat;
 
@@ -57,27 +91,197 @@ impl<
         _create_reload: bool,
     ) -> Result<FormatValue<Prefab<GltfPrefab<N>>>, Error> {
         Ok(FormatValue::data(
-            load_gltf(source, &name, options)
+            load_gltf(source, &name, options, &HashMap::new())
+                .with_context(|_| format_err!("Failed to import gltf scene"))?,
+        ))
+    }
+}
+
+/// Builds a scene loaded with `GltfSceneOptions::stream_buffers` from its loaded buffers, by
+/// buffer index.
+#[derive(Clone)]
+pub(crate) struct GltfStreamedSceneFormat(pub(crate) HashMap<usize, Arc<Vec<u8>>>);
+
+impl<
+        N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+    > Format<Prefab<GltfPrefab<N>>> for GltfStreamedSceneFormat
+{
+    const NAME: &'static str = "GLTFStreamedScene";
+
+    type Options = GltfSceneOptions;
+
+    fn import(
+        &self,
+        name: String,
+        source: Arc<dyn Source>,
+        mut options: GltfSceneOptions,
+        _create_reload: bool,
+    ) -> Result<FormatValue<Prefab<GltfPrefab<N>>>, Error> {
+        options.stream_buffers = false;
+        Ok(FormatValue::data(
+            load_gltf(source, &name, options, &self.0)
                 .with_context(|_| format_err!("Failed to import gltf scene"))?,
         ))
     }
 }
 
+/// Gltf mesh format, loads a single mesh from a Gltf file.
+///
+/// The mesh is addressed by appending its name to the file name, e.g. `"scene.glb#mesh/Turret"`.
+/// If the mesh has more than one primitive, the primitive index can be appended as well, e.g.
+/// `"scene.glb#mesh/Turret/1"`; the first primitive is loaded otherwise.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GltfMeshFormat;
+
+impl Format<Mesh> for GltfMeshFormat {
+    const NAME: &'static str = "GLTFMesh";
+
+    type Options = GltfSceneOptions;
+
+    fn import(
+        &self,
+        name: String,
+        source: Arc<dyn Source>,
+        mut options: GltfSceneOptions,
+        _create_reload: bool,
+    ) -> Result<FormatValue<Mesh>, Error> {
+        // There is no material to bake the vertex colors into, and the primitives are addressed
+        // by their index in the file.
+        options.bake_vertex_colors = false;
+        options.split_cell_size = None;
+        let (path, address) = split_sub_asset(&name);
+        let address = address.unwrap_or("");
+        debug!("Loading GLTF mesh {:?} from {}", address, path);
+        let missing = || error::Error::MissingSubAsset(address.to_string());
+
+        let mesh_name = if address.starts_with("mesh/") {
+            &address["mesh/".len()..]
+        } else {
+            return Err(missing().into());
+        };
+        let (gltf, buffers) =
+            import(source, path).with_context(|_| error::Error::GltfImporterError)?;
+
+        let (mesh, primitive) = match gltf.meshes().find(|m| m.name() == Some(mesh_name)) {
+            Some(mesh) => (mesh, 0),
+            None => {
+                // The last segment may be the index of the primitive.
+                let split = mesh_name.rfind('/').ok_or_else(missing)?;
+                let primitive = mesh_name[split + 1..]
+                    .parse::<usize>()
+                    .map_err(|_| missing())?;
+                let mesh = gltf
+                    .meshes()
+                    .find(|m| m.name() == Some(&mesh_name[..split]))
+                    .ok_or_else(missing)?;
+                (mesh, primitive)
+            }
+        };
+
+        let mut primitives = load_mesh(&mesh, &buffers, &options)
+            .with_context(|_| format_err!("Failed to import gltf mesh"))?;
+        if primitive >= primitives.len() {
+            return Err(missing().into());
+        }
+        Ok(FormatValue::data(primitives.swap_remove(primitive).mesh))
+    }
+}
+
 fn load_gltf<
     N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
 >(
     source: Arc<dyn Source>,
     name: &str,
     options: GltfSceneOptions,
+    loaded: &HashMap<usize, Arc<Vec<u8>>>,
 ) -> Result<Prefab<GltfPrefab<N>>, Error> {
+    let (path, address) = split_sub_asset(name);
+    if options.stream_buffers || options.limits.limited() {
+        let gltf = import_document(source.clone(), path)
+            .with_context(|_| error::Error::GltfImporterError)?;
+        // The limits are checked against the document, before any buffers are loaded.
+        check_document(&gltf, address, &options, source.clone(), path)?;
+        let buffers = external_buffers(&gltf, Path::new(path));
+        if options.stream_buffers && !buffers.is_empty() {
+            // The scene is built by the `GltfStreamedSceneFormat` once the buffers are loaded.
+            debug!(
+                "Loading the {} buffers of GLTF scene {}",
+                buffers.len(),
+                name
+            );
+            return Ok(Prefab::new_main(GltfPrefab {
+                streamed: Some(GltfStreamedScene::new(name.to_string(), options, buffers)),
+                ..Default::default()
+            }));
+        }
+    }
     debug!("Loading GLTF scene {}", name);
-    import(source.clone(), name)
+    import_with_buffers(source.clone(), path, loaded)
         .with_context(|_| error::Error::GltfImporterError)
-        .and_then(|(gltf, buffers)| {
-            load_data(&gltf, &buffers, &options, source, name).map_err(Into::into)
+        .and_then(|(gltf, buffers)| match address {
+            None => load_data(&gltf, &buffers, &options, source, path).map_err(Into::into),
+            Some(address) => load_sub_asset(&gltf, &buffers, &options, source, path, address),
         })
 }
 
+fn load_sub_asset<
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+>(
+    gltf: &Gltf,
+    buffers: &Buffers,
+    options: &GltfSceneOptions,
+    source: Arc<dyn Source>,
+    name: &str,
+    address: &str,
+) -> Result<Prefab<GltfPrefab<N>>, Error> {
+    let missing = || Error::from(error::Error::MissingSubAsset(address.to_string()));
+    let mut prefab = Prefab::<GltfPrefab<N>>::new();
+
+    if address.starts_with("node/") {
+        let node = find_node(gltf, &address["node/".len()..]).ok_or_else(missing)?;
+        // The requested node is loaded even if it is filtered out, only its children are skipped.
+        let mut skipped = skipped_nodes(Some(node.clone()), &options.node_filter);
+        skipped.remove(&node.index());
+        let mut state = SceneState {
+            skipped,
+            ..Default::default()
+        };
+        load_node(
+            gltf,
+            &node,
+            0,
+            buffers,
+            options,
+            source,
+            name,
+            &mut prefab,
+            &mut state.node_map,
+            &mut state.skin_map,
+            &mut state.bounding_box,
+            &mut state.material_set,
+            &state.skipped,
+            &UnitQuaternion::identity(),
+        )?;
+        finish_prefab(gltf, buffers, options, state, 0, &mut prefab)?;
+    } else if address.starts_with("scene/") {
+        let scene_index = find_scene(gltf, &address["scene/".len()..]).ok_or_else(missing)?;
+        load_scene(
+            gltf,
+            scene_index,
+            0,
+            buffers,
+            options,
+            source,
+            name,
+            &mut prefab,
+        )?;
+    } else {
+        return Err(missing());
+    }
+
+    Ok(prefab)
+}
+
 fn load_data<
     N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
 >(
@@ -87,20 +291,61 @@ fn load_data<
     source: Arc<dyn Source>,
     name: &str,
 ) -> Result<Prefab<GltfPrefab<N>>, Error> {
-    let scene_index = get_scene_index(gltf, options)?;
     let mut prefab = Prefab::<GltfPrefab<N>>::new();
-    load_scene(
-        gltf,
-        scene_index,
-        buffers,
-        options,
-        source,
-        name,
-        &mut prefab,
-    )?;
+    if options.load_all_scenes {
+        let mut bounding_box = GltfNodeExtent::default();
+        for scene in gltf.scenes() {
+            let root = prefab.add(Some(0), None);
+            if let Some(scene_name) = scene.name() {
+                prefab.data_or_default(root).name = Some(Named::new(scene_name.to_string()));
+            }
+            load_scene(
+                gltf,
+                scene.index(),
+                root,
+                buffers,
+                options,
+                source.clone(),
+                name,
+                &mut prefab,
+            )?;
+            if let Some(ref extent) = prefab.data_or_default(root).extent {
+                bounding_box.extend(extent);
+            }
+        }
+        if bounding_box.valid() {
+            prefab.data_or_default(0).extent = Some(bounding_box);
+        }
+    } else {
+        let scene_index = get_scene_index(gltf, options)?;
+        load_scene(
+            gltf,
+            scene_index,
+            0,
+            buffers,
+            options,
+            source,
+            name,
+            &mut prefab,
+        )?;
+    }
     Ok(prefab)
 }
 
+/// The node of a `node/` sub asset address.
+fn find_node<'a>(gltf: &'a Gltf, name: &str) -> Option<gltf::Node<'a>> {
+    gltf.nodes().find(|n| n.name() == Some(name))
+}
+
+/// The index of the scene of a `scene/` sub asset address, by name or by index.
+fn find_scene(gltf: &Gltf, scene: &str) -> Option<usize> {
+    gltf.scenes()
+        .find(|s| s.name() == Some(scene))
+        .map(|s| s.index())
+        .or_else(|| scene.parse::<usize>().ok())
+        .filter(|index| gltf.scenes().nth(*index).is_some())
+}
+
 fn get_scene_index(gltf: &Gltf, options: &GltfSceneOptions) -> Result<usize, Error> {
     let num_scenes = gltf.scenes().len();
     match (options.scene_index, gltf.default_scene()) {
@@ -114,11 +359,13 @@ fn get_scene_index(gltf: &Gltf, options: &GltfSceneOptions) -> Result<usize, Err
     }
 }
 
+/// Loads a scene with `root` as its main entity, and adds it to the scenes of the prefab.
 fn load_scene<
     N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
 >(
     gltf: &Gltf,
     scene_index: usize,
+    root: usize,
     buffers: &Buffers,
     options: &GltfSceneOptions,
     source: Arc<dyn Source>,
@@ -129,64 +376,138 @@ fn load_scene<
         .scenes()
         .nth(scene_index)
         .expect("Tried to load a scene which does not exist");
-    let mut node_map = HashMap::new();
-    let mut skin_map = HashMap::new();
-    let mut bounding_box = GltfNodeExtent::default();
-    let mut material_set = GltfMaterialSet::default();
-    if scene.nodes().len() == 1 {
+    prefab.data_or_default(0).scenes.push(GltfPrefabScene {
+        index: scene_index,
+        name: scene.name().map(ToString::to_string),
+        entity: root,
+    });
+    let mut state = SceneState {
+        skipped: skipped_nodes(scene.nodes(), &options.node_filter),
+        ..Default::default()
+    };
+    let nodes = scene
+        .nodes()
+        .filter(|node| !state.skipped.contains(&node.index()))
+        .collect::<Vec<_>>();
+    if nodes.len() == 1 {
         load_node(
             gltf,
-            &scene
-                .nodes()
-                .next()
-                .expect("Unreachable: Length of nodes in scene is checked to be equal to one"),
-            0,
+            &nodes[0],
+            root,
             buffers,
             options,
             source,
             name,
             prefab,
-            &mut node_map,
-            &mut skin_map,
-            &mut bounding_box,
-            &mut material_set,
+            &mut state.node_map,
+            &mut state.skin_map,
+            &mut state.bounding_box,
+            &mut state.material_set,
+            &state.skipped,
+            &UnitQuaternion::identity(),
         )?;
     } else {
-        for node in scene.nodes() {
-            let index = prefab.add(Some(0), None);
+        for node in &nodes {
+            let index = prefab.add(Some(root), None);
             load_node(
                 gltf,
-                &node,
+                node,
                 index,
                 buffers,
                 options,
                 source.clone(),
                 name,
                 prefab,
-                &mut node_map,
-                &mut skin_map,
-                &mut bounding_box,
-                &mut material_set,
+                &mut state.node_map,
+                &mut state.skin_map,
+                &mut state.bounding_box,
+                &mut state.material_set,
+                &state.skipped,
+                &UnitQuaternion::identity(),
             )?;
         }
-        if bounding_box.valid() {
-            prefab.data_or_default(0).extent = Some(bounding_box.clone());
+        if state.bounding_box.valid() {
+            prefab.data_or_default(root).extent = Some(state.bounding_box.clone());
         }
     }
-    prefab.data_or_default(0).materials = Some(material_set);
 
-    // load skins
-    for (node_index, skin_info) in skin_map {
+    finish_prefab(gltf, buffers, options, state, root, prefab)
+}
+
+/// Data collected while loading the nodes of a prefab.
+#[derive(Default)]
+struct SceneState {
+    node_map: HashMap<usize, usize>,
+    skin_map: HashMap<usize, SkinInfo>,
+    bounding_box: GltfNodeExtent,
+    material_set: GltfMaterialSet,
+    /// The nodes skipped by `GltfSceneOptions::node_filter`.
+    skipped: HashSet<usize>,
+}
+
+/// Adds materials, skins and animations to the `root` entity of a prefab after all nodes have
+/// been loaded.
+fn finish_prefab<
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+>(
+    gltf: &Gltf,
+    buffers: &Buffers,
+    options: &GltfSceneOptions,
+    state: SceneState,
+    root: usize,
+    prefab: &mut Prefab<GltfPrefab<N>>,
+) -> Result<(), Error> {
+    let SceneState {
+        node_map,
+        skin_map,
+        material_set,
+        ..
+    } = state;
+    prefab.data_or_default(root).materials = Some(material_set);
+    let nodes = gltf.nodes().collect::<Vec<_>>();
+    prefab.data_or_default(root).node_map = Some(
+        node_map
+            .iter()
+            .map(|(&node, &entity)| {
+                let name = nodes[node].name().map(ToString::to_string);
+                (node, name, entity)
+            })
+            .collect(),
+    );
+
+    // load skins, nodes sharing a skin get a single `SkinPrefab` on the first of them, so the
+    // joints are only updated once for all the meshes
+    let mut skins = BTreeMap::new();
+    let mut skin_nodes = skin_map.into_iter().collect::<Vec<_>>();
+    skin_nodes.sort_by_key(|&(node_index, _)| node_index);
+    for (node_index, skin_info) in skin_nodes {
+        skins
+            .entry(skin_info.skin_index)
+            .or_insert_with(|| (node_index, Vec::new()))
+            .1
+            .extend(skin_info.mesh_indices);
+    }
+    for (skin_index, (node_index, mesh_indices)) in skins {
+        let skin = gltf
+            .skins()
+            .nth(skin_index)
+            .expect("Unreachable: `skin_map` is initialized with indexes from the `Gltf` object");
+        // Only happens when loading a part of the file, using a sub asset address.
+        if skin.joints().any(|j| !node_map.contains_key(&j.index())) {
+            warn!(
+                "Skipping skin {} as some of its joints are not part of the loaded nodes",
+                skin_index
+            );
+            continue;
+        }
         load_skin(
-            &gltf.skins().nth(skin_info.skin_index).expect(
-                "Unreachable: `skin_map` is initialized with indexes from the `Gltf` object",
-            ),
+            &skin,
             buffers,
             *node_map
                 .get(&node_index)
                 .expect("Unreachable: `node_map` should contain all nodes present in `skin_map`"),
             &node_map,
-            skin_info.mesh_indices,
+            mesh_indices,
             prefab,
         )?;
     }
@@ -199,16 +520,32 @@ fn load_scene<
             .map(|(node, entity)| (*node, *entity))
             .collect();
         prefab
-            .data_or_default(0)
+            .data_or_default(root)
             .animatable
             .get_or_insert_with(Default::default)
             .hierarchy = Some(hierarchy_prefab);
 
+        let (transforms, weights) =
+            load_animations(gltf, buffers, &node_map, &options.animation_targets)?;
         prefab
-            .data_or_default(0)
+            .data_or_default(root)
             .animatable
             .get_or_insert_with(Default::default)
-            .animation_set = Some(load_animations(gltf, buffers, &node_map)?);
+            .animation_set = Some(transforms);
+
+        if !weights.animations.is_empty() {
+            let mut hierarchy_prefab = AnimationHierarchyPrefab::default();
+            hierarchy_prefab.nodes = node_map
+                .iter()
+                .filter(|(_, entity)| prefab.data_or_default(**entity).morph_weights.is_some())
+                .map(|(node, entity)| (*node, *entity))
+                .collect();
+            prefab.data_or_default(root).morph_animatable = Some(AnimatablePrefab {
+                animation_set: Some(weights),
+                hierarchy: Some(hierarchy_prefab),
+                rest_state: None,
+            });
+        }
     }
 
     Ok(())
@@ -235,6 +572,8 @@ fn load_node<
     skin_map: &mut HashMap<usize, SkinInfo>,
     parent_bounding_box: &mut GltfNodeExtent,
     material_set: &mut GltfMaterialSet,
+    skipped: &HashSet<usize>,
+    parent_rotation: &UnitQuaternion<f32>,
 ) -> Result<(), Error> {
     node_map.insert(node.index(), entity_index);
 
@@ -242,6 +581,24 @@ fn load_node<
     if let Some(name) = node.name() {
         prefab.data_or_default(entity_index).name = Some(Named::new(name.to_string()));
     }
+    let extras = node
+        .extras()
+        .as_ref()
+        .and_then(|extras| serde_json::from_str::<serde_json::Value>(extras.get()).ok());
+    if !is_visible(node, extras.as_ref(), options) {
+        prefab.data_or_default(entity_index).hidden = true;
+    }
+    if let Some(value) = extras {
+        prefab.data_or_default(entity_index).extras = Some(GltfExtras {
+            name: node.name().map(ToString::to_string),
+            value,
+        });
+    }
+    if options.load_cameras {
+        if let Some(camera) = node.camera() {
+            prefab.data_or_default(entity_index).camera = Some(load_camera(&camera));
+        }
+    }
 
     // Load transformation data, default will be identity
     let (translation, rotation, scale) = node.transform().decomposed();
@@ -261,6 +618,20 @@ fn load_node<
     *local_transform.scale_mut() = Vector3::new(scale[0].into(), scale[1].into(), scale[2].into());
     prefab.data_or_default(entity_index).transform = Some(local_transform);
 
+    // Lights are oriented by the rotation of the whole hierarchy.
+    let rotation = parent_rotation
+        * UnitQuaternion::new_normalize(Quaternion::new(
+            rotation[3],
+            rotation[0],
+            rotation[1],
+            rotation[2],
+        ));
+    if options.load_lights {
+        if let Some(light) = buffers.light(node) {
+            prefab.data_or_default(entity_index).light = Some(load_light(light, &rotation));
+        }
+    }
+
     // check for skinning
     let mut skin = node.skin().map(|skin| SkinInfo {
         skin_index: skin.index(),
@@ -269,26 +640,63 @@ fn load_node<
 
     let mut bounding_box = GltfNodeExtent::default();
 
-    // load graphics
+    // Collider nodes only get the collision geometry.
+    let collider = match (node.name(), &options.collider_prefix) {
+        (Some(name), Some(prefix)) => name.starts_with(prefix.as_str()),
+        _ => false,
+    };
     if let Some(mesh) = node.mesh() {
+        if collider || options.load_collision_shapes {
+            let geometry = load_collision_geometry(&mesh, buffers)?;
+            if collider {
+                // Gives collider nodes bounds, so they are found by spatial queries.
+                for position in &geometry.positions {
+                    bounding_box.extend_range(&(*position..*position));
+                }
+            }
+            prefab.data_or_default(entity_index).collision = Some(geometry);
+        }
+    }
+
+    // load graphics
+    if let Some(mesh) = node.mesh().filter(|_| !collider) {
         let mut graphics = load_mesh(&mesh, buffers, options)?;
+        let materials = mesh.primitives().map(|p| p.material()).collect::<Vec<_>>();
+        let target_count = graphics
+            .iter()
+            .filter_map(|primitive| primitive.morph_targets.as_ref())
+            .map(|morph_targets| morph_targets.targets.len())
+            .max();
+        if let Some(target_count) = target_count {
+            // The weights animated by the node are shared by all of its primitives.
+            let weights = node
+                .weights()
+                .or_else(|| mesh.weights())
+                .map(|weights| weights.to_vec())
+                .unwrap_or_else(|| vec![0.0; target_count]);
+            prefab.data_or_default(entity_index).morph_weights = Some(MorphWeights { weights });
+        }
         if graphics.len() == 1 {
             // single primitive can be loaded directly onto the node
-            let (mesh, material_index, bounds) = graphics.remove(0);
-            bounding_box.extend_range(&bounds);
+            let primitive = graphics.remove(0);
+            bounding_box.extend_range(&primitive.bounds);
+            let material_id = load_primitive_material(
+                gltf,
+                &materials[primitive.index],
+                primitive.baked_color,
+                buffers,
+                source.clone(),
+                name,
+                options,
+                material_set,
+            )?;
             let prefab_data = prefab.data_or_default(entity_index);
-            prefab_data.mesh = Some(mesh);
-            if let Some((material_id, material)) =
-                material_index.and_then(|index| gltf.materials().nth(index).map(|m| (index, m)))
-            {
-                if !material_set.materials.contains_key(&material_id) {
-                    material_set.materials.insert(
-                        material_id,
-                        load_material(&material, buffers, source.clone(), name)?,
-                    );
-                }
-                prefab_data.material_id = Some(material_id);
-            }
+            prefab_data.mesh = Some(primitive.mesh);
+            prefab_data.lods = primitive.lods;
+            prefab_data.morph_targets = primitive.morph_targets;
+            prefab_data.skip_mesh_data =
+                !options.keep_mesh_data && prefab_data.morph_targets.is_none();
+            prefab_data.material_id = material_id;
             // if we have a skin we need to track the mesh entities
             if let Some(ref mut skin) = skin {
                 skin.mesh_indices.push(entity_index);
@@ -296,22 +704,26 @@ fn load_node<
         } else if graphics.len() > 1 {
             // if we have multiple primitives,
             // we need to add each primitive as a child entity to the node
-            for (mesh, material_index, bounds) in graphics {
+            for primitive in graphics {
+                let material_id = load_primitive_material(
+                    gltf,
+                    &materials[primitive.index],
+                    primitive.baked_color,
+                    buffers,
+                    source.clone(),
+                    name,
+                    options,
+                    material_set,
+                )?;
                 let mesh_entity = prefab.add(Some(entity_index), None);
                 let prefab_data = prefab.data_or_default(mesh_entity);
                 prefab_data.transform = Some(Transform::default());
-                prefab_data.mesh = Some(mesh);
-                if let Some((material_id, material)) =
-                    material_index.and_then(|index| gltf.materials().nth(index).map(|m| (index, m)))
-                {
-                    if !material_set.materials.contains_key(&material_id) {
-                        material_set.materials.insert(
-                            material_id,
-                            load_material(&material, buffers, source.clone(), name)?,
-                        );
-                    }
-                    prefab_data.material_id = Some(material_id);
-                }
+                prefab_data.mesh = Some(primitive.mesh);
+                prefab_data.lods = primitive.lods;
+                prefab_data.morph_targets = primitive.morph_targets;
+                prefab_data.skip_mesh_data =
+                    !options.keep_mesh_data && prefab_data.morph_targets.is_none();
+                prefab_data.material_id = material_id;
 
                 // if we have a skin we need to track the mesh entities
                 if let Some(ref mut skin) = skin {
@@ -319,14 +731,17 @@ fn load_node<
                 }
 
                 // extent
-                bounding_box.extend_range(&bounds);
-                prefab_data.extent = Some(bounds.into());
+                bounding_box.extend_range(&primitive.bounds);
+                prefab_data.extent = Some(primitive.bounds.into());
             }
         }
     }
 
     // load children
     for child in node.children() {
+        if skipped.contains(&child.index()) {
+            continue;
+        }
         let index = prefab.add(Some(entity_index), None);
         load_node(
             gltf,
@@ -341,6 +756,8 @@ fn load_node<
             skin_map,
             &mut bounding_box,
             material_set,
+            skipped,
+            &rotation,
         )?;
     }
     if bounding_box.valid() {
@@ -355,3 +772,79 @@ fn load_node<
 
     Ok(())
 }
+
+/// Checks the `visible` field of the extras of a node, and `GltfSceneOptions::hidden_nodes`.
+fn is_visible(
+    node: &gltf::Node<'_>,
+    extras: Option<&serde_json::Value>,
+    options: &GltfSceneOptions,
+) -> bool {
+    if let Some(name) = node.name() {
+        if options.hidden_nodes.iter().any(|hidden| hidden == name) {
+            return false;
+        }
+    }
+    extras
+        .and_then(|extras| extras.get("visible").and_then(serde_json::Value::as_bool))
+        .unwrap_or(true)
+}
+
+/// Adds the material of a primitive to the material set if it is missing, and returns its id.
+///
+/// Materials with a baked vertex color are added once per primitive, after the materials of the
+/// glTF file.
+fn load_primitive_material(
+    gltf: &Gltf,
+    material: &gltf::Material<'_>,
+    baked_color: Option<[f32; 4]>,
+    buffers: &Buffers,
+    source: Arc<dyn Source>,
+    name: &str,
+    options: &GltfSceneOptions,
+    material_set: &mut GltfMaterialSet,
+) -> Result<Option<usize>, Error> {
+    let load = |vertex_color| {
+        let overridden = material
+            .name()
+            .and_then(|name| options.material_overrides.get(name));
+        match overridden {
+            Some(prefab) => Ok(prefab.clone()),
+            None => load_material(
+                material,
+                vertex_color,
+                buffers,
+                source,
+                name,
+                options.stream_textures,
+            ),
+        }
+        .map(|mut prefab| {
+            prefab.unlit |= options.force_unlit;
+            match options.material_processor {
+                Some(ref processor) => processor(material, prefab),
+                None => prefab,
+            }
+        })
+    };
+    let material_count = gltf.materials().count();
+    if baked_color.is_some() {
+        let baked_count = material_set
+            .materials
+            .keys()
+            .filter(|id| **id >= material_count)
+            .count();
+        let material_id = material_count + baked_count;
+        material_set
+            .materials
+            .insert(material_id, load(baked_color)?);
+        return Ok(Some(material_id));
+    }
+    let material_id = match material.index() {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    if !material_set.materials.contains_key(&material_id) {
+        material_set.materials.insert(material_id, load(None)?);
+    }
+    Ok(Some(material_id))
+}
@@ -0,0 +1,317 @@
+//! Reordering of indexed triangle lists for the GPU: triangles are sorted for the post-transform
+//! vertex cache, following Tom Forsyth's "Linear-Speed Vertex Cache Optimisation", then runs of
+//! triangles are sorted to reduce overdraw, and the vertices are sorted in the order they are
+//! fetched.
+
+/// The size of the simulated cache.
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+/// The cache size used for splitting the triangles into clusters for the overdraw sorting.
+const CLUSTER_CACHE_SIZE: usize = 16;
+
+/// Optimizes the triangle list `indices` into `positions`, and returns the order of the
+/// vertices, where each entry is the old index of the vertex. The indices are rewritten to refer
+/// to the new order.
+pub fn optimize(indices: &mut Vec<u32>, positions: &[[f32; 3]]) -> Vec<u32> {
+    optimize_vertex_cache(indices, positions.len());
+    optimize_overdraw(indices, positions);
+    optimize_vertex_fetch(indices, positions.len())
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+        None => 0.0,
+    };
+    cache_score + VALENCE_BOOST_SCALE * (remaining as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+/// Reorders the triangles so vertices are used again while they are still in the cache.
+fn optimize_vertex_cache(indices: &mut Vec<u32>, vertex_count: usize) {
+    let triangle_count = indices.len() / 3;
+    let mut vertex_triangles = vec![Vec::new(); vertex_count];
+    for (t, triangle) in indices.chunks(3).take(triangle_count).enumerate() {
+        for &v in triangle {
+            vertex_triangles[v as usize].push(t);
+        }
+    }
+
+    let mut vertex_scores = vertex_triangles
+        .iter()
+        .map(|triangles| vertex_score(None, triangles.len()))
+        .collect::<Vec<_>>();
+    let triangle_score = |scores: &[f32], t: usize| -> f32 {
+        (0..3).map(|k| scores[indices[t * 3 + k] as usize]).sum()
+    };
+    let mut emitted = vec![false; triangle_count];
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut output = Vec::with_capacity(indices.len());
+    let mut best = None;
+    let mut cursor = 0;
+
+    for _ in 0..triangle_count {
+        let t = match best {
+            Some(t) => t,
+            None => {
+                // Nothing in the cache has triangles left, continue with an unused triangle.
+                while emitted[cursor] {
+                    cursor += 1;
+                }
+                cursor
+            }
+        };
+        emitted[t] = true;
+        let triangle = [indices[t * 3], indices[t * 3 + 1], indices[t * 3 + 2]];
+        output.extend_from_slice(&triangle);
+
+        for &v in triangle.iter().rev() {
+            let triangles = &mut vertex_triangles[v as usize];
+            if let Some(position) = triangles.iter().position(|&other| other == t) {
+                triangles.swap_remove(position);
+            }
+            if let Some(position) = cache.iter().position(|&c| c == v) {
+                cache.remove(position);
+            }
+            cache.insert(0, v);
+        }
+        for v in cache.drain(CACHE_SIZE.min(cache.len())..) {
+            vertex_scores[v as usize] = vertex_score(None, vertex_triangles[v as usize].len());
+        }
+        for (position, &v) in cache.iter().enumerate() {
+            vertex_scores[v as usize] =
+                vertex_score(Some(position), vertex_triangles[v as usize].len());
+        }
+
+        best = None;
+        let mut best_score = -1.0;
+        for &v in &cache {
+            for &other in &vertex_triangles[v as usize] {
+                let score = triangle_score(&vertex_scores, other);
+                if score > best_score {
+                    best_score = score;
+                    best = Some(other);
+                }
+            }
+        }
+    }
+
+    output.extend_from_slice(&indices[triangle_count * 3..]);
+    *indices = output;
+}
+
+/// Splits the triangles into runs starting at triangles without cached vertices, and draws the
+/// runs facing away from the center of the mesh first, as they are likely to hide the others.
+fn optimize_overdraw(indices: &mut Vec<u32>, positions: &[[f32; 3]]) {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return;
+    }
+
+    let mut clusters = vec![0];
+    let mut cache: Vec<u32> = Vec::with_capacity(CLUSTER_CACHE_SIZE);
+    for (t, triangle) in indices.chunks(3).take(triangle_count).enumerate() {
+        let misses = triangle.iter().filter(|v| !cache.contains(v)).count();
+        if misses == 3 && t > 0 {
+            clusters.push(t);
+        }
+        for &v in triangle {
+            if !cache.contains(&v) {
+                if cache.len() == CLUSTER_CACHE_SIZE {
+                    cache.remove(0);
+                }
+                cache.push(v);
+            }
+        }
+    }
+    clusters.push(triangle_count);
+
+    let corner = |t: usize, k: usize| positions[indices[t * 3 + k] as usize];
+    let mut center = [0.0; 3];
+    for t in 0..triangle_count {
+        for k in 0..3 {
+            let p = corner(t, k);
+            for axis in 0..3 {
+                center[axis] += p[axis] / (triangle_count * 3) as f32;
+            }
+        }
+    }
+
+    let mut keys = clusters
+        .windows(2)
+        .map(|range| {
+            let mut centroid = [0.0; 3];
+            let mut normal = [0.0; 3];
+            let mut area_sum = 0.0;
+            for t in range[0]..range[1] {
+                let (a, b, c) = (corner(t, 0), corner(t, 1), corner(t, 2));
+                let n = cross(sub(b, a), sub(c, a));
+                let area = dot(n, n).sqrt();
+                for axis in 0..3 {
+                    centroid[axis] += (a[axis] + b[axis] + c[axis]) / 3.0 * area;
+                    normal[axis] += n[axis];
+                }
+                area_sum += area;
+            }
+            let length = dot(normal, normal).sqrt();
+            let key = if area_sum > 0.0 && length > 0.0 {
+                let centroid = [
+                    centroid[0] / area_sum,
+                    centroid[1] / area_sum,
+                    centroid[2] / area_sum,
+                ];
+                dot(sub(centroid, center), normal) / length
+            } else {
+                0.0
+            };
+            (key, range[0], range[1])
+        })
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut output = Vec::with_capacity(indices.len());
+    for (_, start, end) in keys {
+        output.extend_from_slice(&indices[start * 3..end * 3]);
+    }
+    output.extend_from_slice(&indices[triangle_count * 3..]);
+    *indices = output;
+}
+
+/// Numbers the vertices in the order they are first used, and returns the old index of each
+/// vertex in the new order. Unused vertices are dropped.
+fn optimize_vertex_fetch(indices: &mut [u32], vertex_count: usize) -> Vec<u32> {
+    let mut remap = vec![None; vertex_count];
+    let mut order = Vec::with_capacity(vertex_count);
+    for index in indices.iter_mut() {
+        let new = *remap[*index as usize].get_or_insert_with(|| {
+            order.push(*index);
+            order.len() as u32 - 1
+        });
+        *index = new;
+    }
+    order
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An indexed grid of `n` by `n` quads, with the triangles in a random order.
+    fn grid(n: u32) -> (Vec<u32>, Vec<[f32; 3]>) {
+        let mut positions = Vec::new();
+        for x in 0..=n {
+            for z in 0..=n {
+                positions.push([x as f32, 0.0, z as f32]);
+            }
+        }
+        let vertex = |x: u32, z: u32| x * (n + 1) + z;
+        let mut triangles = Vec::new();
+        for x in 0..n {
+            for z in 0..n {
+                triangles.push([vertex(x, z), vertex(x, z + 1), vertex(x + 1, z + 1)]);
+                triangles.push([vertex(x, z), vertex(x + 1, z + 1), vertex(x + 1, z)]);
+            }
+        }
+        // A fixed shuffle, which doesn't need a random number generator.
+        let len = triangles.len();
+        for i in 0..len {
+            triangles.swap(i, (i * 7919 + 13) % len);
+        }
+        (
+            triangles.iter().flat_map(|t| t.iter().cloned()).collect(),
+            positions,
+        )
+    }
+
+    /// The average number of cache misses per triangle with a FIFO cache of 16 vertices.
+    fn acmr(indices: &[u32]) -> f32 {
+        let mut cache = Vec::new();
+        let mut misses = 0;
+        for &v in indices {
+            if !cache.contains(&v) {
+                misses += 1;
+                cache.push(v);
+                if cache.len() > 16 {
+                    cache.remove(0);
+                }
+            }
+        }
+        misses as f32 / (indices.len() / 3) as f32
+    }
+
+    fn sorted_triangles(indices: &[u32], order: &[u32]) -> Vec<[u32; 3]> {
+        let mut triangles = indices
+            .chunks(3)
+            .map(|t| {
+                let mut t = [
+                    order[t[0] as usize],
+                    order[t[1] as usize],
+                    order[t[2] as usize],
+                ];
+                // Rotate the smallest index to the front, keeping the winding.
+                while t[0] > t[1] || t[0] > t[2] {
+                    t = [t[1], t[2], t[0]];
+                }
+                t
+            })
+            .collect::<Vec<_>>();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn keeps_triangles() {
+        let (original, positions) = grid(8);
+        let mut indices = original.clone();
+        let order = optimize(&mut indices, &positions);
+        assert_eq!(order.len(), positions.len());
+        let identity = (0..positions.len() as u32).collect::<Vec<_>>();
+        assert_eq!(
+            sorted_triangles(&original, &identity),
+            sorted_triangles(&indices, &order)
+        );
+    }
+
+    #[test]
+    fn improves_cache_hits() {
+        let (original, positions) = grid(16);
+        let mut indices = original.clone();
+        optimize_vertex_cache(&mut indices, positions.len());
+        assert!(acmr(&indices) < acmr(&original));
+        assert!(acmr(&indices) < 1.0);
+    }
+
+    #[test]
+    fn fetch_order_follows_indices() {
+        let mut indices = vec![5, 3, 0, 3, 5, 4];
+        let order = optimize_vertex_fetch(&mut indices, 6);
+        assert_eq!(order, vec![5, 3, 0, 4]);
+        assert_eq!(indices, vec![0, 1, 2, 1, 0, 3]);
+    }
+}
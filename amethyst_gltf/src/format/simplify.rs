@@ -0,0 +1,424 @@
+//! Mesh simplification by quadric error edge collapses, as described by Garland and Heckbert in
+//! "Surface Simplification Using Quadric Error Metrics".
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// The weight of the planes keeping the boundary edges of open meshes in place.
+const BOUNDARY_WEIGHT: f64 = 10.0;
+
+/// Simplifies the triangle list given by `positions`, with one position per triangle corner,
+/// until at most `ratio` of the triangles remain or the next collapse would exceed `max_error`.
+///
+/// Returns the corners of the remaining triangles, each as the index of the corner in `positions`
+/// which its other attributes are taken from, and its new position.
+pub fn simplify(
+    positions: &[[f32; 3]],
+    ratio: f32,
+    max_error: Option<f32>,
+) -> Vec<(usize, [f32; 3])> {
+    let triangle_count = positions.len() / 3;
+    let target = (triangle_count as f32 * ratio.max(0.0)).ceil() as usize;
+    if target >= triangle_count {
+        return positions
+            .iter()
+            .take(triangle_count * 3)
+            .cloned()
+            .enumerate()
+            .collect();
+    }
+
+    let mut mesh = Simplifier::new(positions);
+    mesh.collapse_until(target, max_error.map(f64::from));
+    mesh.corners()
+}
+
+/// A symmetric 4x4 matrix measuring the squared distance to a set of planes.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane(normal: [f64; 3], d: f64, weight: f64) -> Self {
+        let [a, b, c] = normal;
+        let mut q = [
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ];
+        for value in q.iter_mut() {
+            *value *= weight;
+        }
+        Quadric(q)
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+    }
+
+    fn error(&self, [x, y, z]: [f64; 3]) -> f64 {
+        let q = &self.0;
+        q[0] * x * x
+            + 2.0 * q[1] * x * y
+            + 2.0 * q[2] * x * z
+            + 2.0 * q[3] * x
+            + q[4] * y * y
+            + 2.0 * q[5] * y * z
+            + 2.0 * q[6] * y
+            + q[7] * z * z
+            + 2.0 * q[8] * z
+            + q[9]
+    }
+}
+
+/// A possible collapse of the edge between `a` and `b` into `target`.
+struct Collapse {
+    cost: f64,
+    a: usize,
+    b: usize,
+    target: [f64; 3],
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    // Reversed, so the `BinaryHeap` pops the cheapest collapse first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+struct Simplifier {
+    vertices: Vec<[f64; 3]>,
+    quadrics: Vec<Quadric>,
+    versions: Vec<u32>,
+    removed: Vec<bool>,
+    vertex_triangles: Vec<Vec<usize>>,
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    alive_count: usize,
+    heap: BinaryHeap<Collapse>,
+}
+
+impl Simplifier {
+    fn new(positions: &[[f32; 3]]) -> Self {
+        // Weld the corners sharing a position, the other attributes stay with the corners.
+        let mut welded = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::with_capacity(positions.len() / 3);
+        for corners in positions.chunks(3).filter(|c| c.len() == 3) {
+            let mut triangle = [0; 3];
+            for (index, position) in triangle.iter_mut().zip(corners) {
+                let key = [
+                    position[0].to_bits(),
+                    position[1].to_bits(),
+                    position[2].to_bits(),
+                ];
+                *index = *welded.entry(key).or_insert_with(|| {
+                    vertices.push([
+                        f64::from(position[0]),
+                        f64::from(position[1]),
+                        f64::from(position[2]),
+                    ]);
+                    vertices.len() - 1
+                });
+            }
+            triangles.push(triangle);
+        }
+
+        let mut quadrics = vec![Quadric::default(); vertices.len()];
+        let mut vertex_triangles = vec![Vec::new(); vertices.len()];
+        let mut edges = HashMap::new();
+        for (t, triangle) in triangles.iter().enumerate() {
+            let normal = face_normal(&vertices, triangle);
+            let area = length(normal);
+            if area > 0.0 {
+                let normal = scale(normal, 1.0 / area);
+                let d = -dot(normal, vertices[triangle[0]]);
+                let quadric = Quadric::plane(normal, d, area);
+                for &v in triangle {
+                    quadrics[v].add(&quadric);
+                }
+            }
+            for (k, &a) in triangle.iter().enumerate() {
+                let b = triangle[(k + 1) % 3];
+                vertex_triangles[a].push(t);
+                let edge = (a.min(b), a.max(b));
+                edges.entry(edge).or_insert((t, 0)).1 += 1;
+            }
+        }
+
+        // Sorted, so the result doesn't depend on the order of the hash map.
+        let mut edges = edges.into_iter().collect::<Vec<_>>();
+        edges.sort_by_key(|&(edge, _)| edge);
+
+        // Edges with a single triangle are on the boundary, keep them from moving sideways.
+        for &((a, b), (t, count)) in &edges {
+            if count != 1 {
+                continue;
+            }
+            let edge = sub(vertices[b], vertices[a]);
+            let normal = cross(edge, face_normal(&vertices, &triangles[t]));
+            let len = length(normal);
+            if len > 0.0 {
+                let normal = scale(normal, 1.0 / len);
+                let d = -dot(normal, vertices[a]);
+                let quadric = Quadric::plane(normal, d, BOUNDARY_WEIGHT * dot(edge, edge));
+                quadrics[a].add(&quadric);
+                quadrics[b].add(&quadric);
+            }
+        }
+
+        let vertex_count = vertices.len();
+        let alive_count = triangles.len();
+        let mut simplifier = Simplifier {
+            vertices,
+            quadrics,
+            versions: vec![0; vertex_count],
+            removed: vec![false; vertex_count],
+            vertex_triangles,
+            alive: vec![true; triangles.len()],
+            triangles,
+            alive_count,
+            heap: BinaryHeap::new(),
+        };
+        for ((a, b), _) in edges {
+            simplifier.push_collapse(a, b);
+        }
+        simplifier
+    }
+
+    fn push_collapse(&mut self, a: usize, b: usize) {
+        let mut quadric = self.quadrics[a];
+        quadric.add(&self.quadrics[b]);
+        let (pa, pb) = (self.vertices[a], self.vertices[b]);
+        let middle = scale(add(pa, pb), 0.5);
+        let (cost, target) = [pa, pb, middle]
+            .iter()
+            .map(|&p| (quadric.error(p), p))
+            .fold((std::f64::INFINITY, middle), |best, candidate| {
+                if candidate.0 < best.0 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        self.heap.push(Collapse {
+            cost: cost.max(0.0),
+            a,
+            b,
+            target,
+            versions: (self.versions[a], self.versions[b]),
+        });
+    }
+
+    fn collapse_until(&mut self, target: usize, max_error: Option<f64>) {
+        while self.alive_count > target {
+            let collapse = match self.heap.pop() {
+                Some(collapse) => collapse,
+                None => break,
+            };
+            let (a, b) = (collapse.a, collapse.b);
+            if self.removed[a]
+                || self.removed[b]
+                || (self.versions[a], self.versions[b]) != collapse.versions
+            {
+                continue;
+            }
+            if max_error.map_or(false, |max| collapse.cost > max) {
+                break;
+            }
+            if self.flips(a, b, collapse.target) || self.flips(b, a, collapse.target) {
+                continue;
+            }
+            self.collapse(a, b, collapse.target);
+        }
+    }
+
+    /// Checks if moving `v` to `target` turns over a triangle which doesn't contain `other`.
+    fn flips(&self, v: usize, other: usize, target: [f64; 3]) -> bool {
+        self.vertex_triangles[v]
+            .iter()
+            .filter(|&&t| self.alive[t] && !self.triangles[t].contains(&other))
+            .any(|&t| {
+                let triangle = self.triangles[t];
+                let before = face_normal(&self.vertices, &triangle);
+                let mut moved = [
+                    self.vertices[triangle[0]],
+                    self.vertices[triangle[1]],
+                    self.vertices[triangle[2]],
+                ];
+                for (k, &corner) in triangle.iter().enumerate() {
+                    if corner == v {
+                        moved[k] = target;
+                    }
+                }
+                let after = cross(sub(moved[1], moved[0]), sub(moved[2], moved[0]));
+                dot(before, after) <= 0.0
+            })
+    }
+
+    /// Merges `b` into `a`, which is moved to `target`.
+    fn collapse(&mut self, a: usize, b: usize, target: [f64; 3]) {
+        self.vertices[a] = target;
+        let quadric = self.quadrics[b];
+        self.quadrics[a].add(&quadric);
+        self.removed[b] = true;
+        self.versions[a] += 1;
+
+        for t in std::mem::replace(&mut self.vertex_triangles[b], Vec::new()) {
+            if !self.alive[t] {
+                continue;
+            }
+            if self.triangles[t].contains(&a) {
+                self.alive[t] = false;
+                self.alive_count -= 1;
+            } else {
+                for corner in self.triangles[t].iter_mut() {
+                    if *corner == b {
+                        *corner = a;
+                    }
+                }
+                self.vertex_triangles[a].push(t);
+            }
+        }
+        let alive = &self.alive;
+        self.vertex_triangles[a].retain(|&t| alive[t]);
+
+        let mut neighbours = self.vertex_triangles[a]
+            .iter()
+            .flat_map(|&t| self.triangles[t].iter().cloned())
+            .filter(|&v| v != a)
+            .collect::<Vec<_>>();
+        neighbours.sort();
+        neighbours.dedup();
+        for n in neighbours {
+            self.push_collapse(a, n);
+        }
+    }
+
+    fn corners(&self) -> Vec<(usize, [f32; 3])> {
+        let mut corners = Vec::with_capacity(self.alive_count * 3);
+        for (t, triangle) in self.triangles.iter().enumerate() {
+            if !self.alive[t] {
+                continue;
+            }
+            for (k, &v) in triangle.iter().enumerate() {
+                let [x, y, z] = self.vertices[v];
+                corners.push((t * 3 + k, [x as f32, y as f32, z as f32]));
+            }
+        }
+        corners
+    }
+}
+
+fn face_normal(vertices: &[[f64; 3]], triangle: &[usize; 3]) -> [f64; 3] {
+    let a = vertices[triangle[0]];
+    cross(sub(vertices[triangle[1]], a), sub(vertices[triangle[2]], a))
+}
+
+fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f64; 3], s: f64) -> [f64; 3] {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn length(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simplify;
+
+    /// A flat grid of `n` by `n` quads.
+    fn grid(n: usize) -> Vec<[f32; 3]> {
+        let mut positions = Vec::new();
+        for x in 0..n {
+            for z in 0..n {
+                let (x0, z0, x1, z1) = (x as f32, z as f32, x as f32 + 1.0, z as f32 + 1.0);
+                positions.extend_from_slice(&[[x0, 0.0, z0], [x0, 0.0, z1], [x1, 0.0, z1]]);
+                positions.extend_from_slice(&[[x0, 0.0, z0], [x1, 0.0, z1], [x1, 0.0, z0]]);
+            }
+        }
+        positions
+    }
+
+    #[test]
+    fn flat_grid_is_reduced() {
+        let positions = grid(8);
+        let corners = simplify(&positions, 0.25, None);
+        assert!(!corners.is_empty());
+        assert!(corners.len() <= positions.len() / 4);
+        for (corner, position) in corners {
+            assert!(corner < positions.len());
+            assert_eq!(position[1], 0.0);
+            assert!(position[0] >= 0.0 && position[0] <= 8.0);
+            assert!(position[2] >= 0.0 && position[2] <= 8.0);
+        }
+    }
+
+    #[test]
+    fn error_budget_stops_collapses() {
+        let mut positions = grid(4);
+        // A spike in the middle of the grid, which can't be removed without error.
+        for position in positions.iter_mut() {
+            if position[0] == 2.0 && position[2] == 2.0 {
+                position[1] = 5.0;
+            }
+        }
+        let corners = simplify(&positions, 0.0, Some(1e-6));
+        assert!(corners.iter().any(|(_, position)| position[1] == 5.0));
+    }
+
+    #[test]
+    fn full_ratio_keeps_mesh() {
+        let positions = grid(2);
+        let corners = simplify(&positions, 1.0, None);
+        assert_eq!(corners.len(), positions.len());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use log::warn;
+use serde_json::Value;
+
+const EXTENSION: &str = "KHR_texture_transform";
+
+/// The paths of the textures of a material, in the order their transform is picked.
+const TEXTURES: &[&[&str]] = &[
+    &["pbrMetallicRoughness", "baseColorTexture"],
+    &["pbrMetallicRoughness", "metallicRoughnessTexture"],
+    &["normalTexture"],
+    &["occlusionTexture"],
+    &["emissiveTexture"],
+];
+
+/// The offset, rotation and scale of texture coordinates from the `KHR_texture_transform`
+/// extension.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextureTransform {
+    offset: [f32; 2],
+    rotation: f32,
+    scale: [f32; 2],
+}
+
+impl TextureTransform {
+    /// Transforms texture coordinates, scaling them first, then rotating and offsetting them.
+    pub fn apply(&self, uv: [f32; 2]) -> [f32; 2] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = uv[0] * self.scale[0];
+        let y = uv[1] * self.scale[1];
+        [
+            cos * x + sin * y + self.offset[0],
+            -sin * x + cos * y + self.offset[1],
+        ]
+    }
+
+    fn read(info: &Value) -> Option<Self> {
+        let extension = info.get("extensions")?.get(EXTENSION)?;
+        let pair = |name, default: [f32; 2]| {
+            extension
+                .get(name)
+                .and_then(Value::as_array)
+                .and_then(|values| {
+                    Some([
+                        values.get(0)?.as_f64()? as f32,
+                        values.get(1)?.as_f64()? as f32,
+                    ])
+                })
+                .unwrap_or(default)
+        };
+        if extension.get("texCoord").is_some() {
+            warn!("`texCoord` of `{}` is not supported", EXTENSION);
+        }
+        Some(TextureTransform {
+            offset: pair("offset", [0.0, 0.0]),
+            rotation: extension
+                .get("rotation")
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0) as f32,
+            scale: pair("scale", [1.0, 1.0]),
+        })
+    }
+}
+
+/// Reads the texture transforms of the `KHR_texture_transform` extension, by the index of their
+/// material.
+///
+/// The renderer uses the same texture coordinates for all textures of a material, so the
+/// transform of the base color texture is used, or else the first transformed texture's.
+pub fn material_texture_transforms(root: &Value) -> HashMap<usize, TextureTransform> {
+    root.get("materials")
+        .and_then(Value::as_array)
+        .map(|materials| {
+            materials
+                .iter()
+                .enumerate()
+                .filter_map(|(index, material)| {
+                    let mut transforms = TEXTURES.iter().filter_map(|path| {
+                        path.iter()
+                            .try_fold(material, |value, name| value.get(name))
+                            .and_then(TextureTransform::read)
+                    });
+                    let transform = transforms.next()?;
+                    if transforms.any(|other| other != transform) {
+                        warn!(
+                            "Material {} has textures with different transforms, only one is used",
+                            index
+                        );
+                    }
+                    Some((index, transform))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_applies_transforms() {
+        let root = serde_json::json!({
+            "materials": [
+                {},
+                {
+                    "pbrMetallicRoughness": {
+                        "baseColorTexture": {
+                            "index": 0,
+                            "extensions": {
+                                "KHR_texture_transform": {
+                                    "offset": [0.5, 0.0],
+                                    "rotation": std::f64::consts::FRAC_PI_2,
+                                    "scale": [2.0, 4.0]
+                                }
+                            }
+                        }
+                    }
+                },
+                {
+                    "normalTexture": {
+                        "index": 0,
+                        "extensions": { "KHR_texture_transform": { "scale": [3.0, 3.0] } }
+                    }
+                }
+            ]
+        });
+        let transforms = material_texture_transforms(&root);
+
+        assert_eq!(transforms.len(), 2);
+        let uv = transforms[&1].apply([1.0, 1.0]);
+        assert!((uv[0] - 4.5).abs() < 1e-5);
+        assert!((uv[1] + 2.0).abs() < 1e-5);
+        assert_eq!(transforms[&2].apply([0.5, 1.0]), [1.5, 3.0]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Building `GltfPrefab` hierarchies from other scene formats.
+
+use std::{fmt::Debug, ops::Range};
+
+use num_traits::NumCast;
+use serde::{de::DeserializeOwned, Serialize};
+
+use amethyst_assets::Prefab;
+use amethyst_core::{math::RealField, transform::Transform, Named};
+use amethyst_renderer::{MaterialPrefab, MeshData, TextureFormat};
+
+use crate::{GltfMaterialSet, GltfNodeExtent, GltfPrefab};
+
+/// Builds the same prefab structure the `GltfSceneFormat` produces, for importers of other scene
+/// formats (e.g. OBJ or FBX).
+///
+/// An importer implements `Format<Prefab<GltfPrefab<N>>>` and uses this builder to generate its
+/// data, so the scene can be loaded with the `GltfSceneLoaderSystem` and used like any glTF
+/// scene:
+///
+/// * the root entity (index `0`) holds the materials of the scene and its extent,
+/// * every node is an entity with an optional name and a `Transform`,
+/// * meshes are placed on their nodes and reference their material by id.
+pub struct GltfPrefabBuilder<
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField,
+> {
+    prefab: Prefab<GltfPrefab<N>>,
+    material_set: GltfMaterialSet,
+    parents: Vec<Option<usize>>,
+    extents: Vec<(usize, GltfNodeExtent)>,
+}
+
+impl<N> GltfPrefabBuilder<N>
+where
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+{
+    /// Creates a builder holding only the root entity.
+    pub fn new() -> Self {
+        GltfPrefabBuilder {
+            prefab: Prefab::new(),
+            material_set: GltfMaterialSet::default(),
+            parents: vec![None],
+            extents: Vec::new(),
+        }
+    }
+
+    /// The index of the root entity.
+    pub fn root(&self) -> usize {
+        0
+    }
+
+    /// Adds a material which can be used by any mesh in the scene and returns its id.
+    pub fn add_material(&mut self, material: MaterialPrefab<TextureFormat>) -> usize {
+        let id = self.material_set.materials.len();
+        self.material_set.materials.insert(id, material);
+        id
+    }
+
+    /// Adds a node to the scene and returns its index.
+    ///
+    /// The transform is relative to the `parent` node; nodes without a transform use the
+    /// identity.
+    pub fn add_node(
+        &mut self,
+        parent: usize,
+        name: Option<String>,
+        transform: Option<Transform<N>>,
+    ) -> usize {
+        let index = self.prefab.add(Some(parent), None);
+        self.parents.push(Some(parent));
+        let data = self.prefab.data_or_default(index);
+        data.name = name.map(Named::new);
+        data.transform = Some(transform.unwrap_or_default());
+        index
+    }
+
+    /// Places a mesh with the given bounds on the node, using the material with id `material`.
+    ///
+    /// Each node can only hold a single mesh, add child nodes for meshes made of several parts.
+    pub fn set_mesh(
+        &mut self,
+        node: usize,
+        mesh: MeshData,
+        material: Option<usize>,
+        bounds: Range<[f32; 3]>,
+    ) {
+        let data = self.prefab.data_or_default(node);
+        data.mesh = Some(mesh);
+        data.material_id = material;
+        self.extents.push((node, bounds.into()));
+    }
+
+    /// Returns the data of the node, e.g. to add animations or skins.
+    pub fn data_mut(&mut self, node: usize) -> &mut GltfPrefab<N> {
+        self.prefab.data_or_default(node)
+    }
+
+    /// Finishes the scene, computing the extents of all nodes.
+    ///
+    /// The extent of a node is in its own space, the extents of its children are moved into it
+    /// with their transforms.
+    pub fn build(mut self) -> Prefab<GltfPrefab<N>> {
+        for (node, mut extent) in self.extents.drain(..) {
+            // extend the node and all of its ancestors
+            let mut index = node;
+            loop {
+                let data = self.prefab.data_or_default(index);
+                data.extent
+                    .get_or_insert_with(GltfNodeExtent::default)
+                    .extend(&extent);
+                let parent = match self.parents[index] {
+                    Some(parent) => parent,
+                    None => break,
+                };
+                if let Some(ref transform) = data.transform {
+                    let matrix = transform
+                        .matrix()
+                        .map(|v| NumCast::from(v).unwrap_or(0.0f32));
+                    extent = extent.transformed(&matrix);
+                }
+                index = parent;
+            }
+        }
+
+        self.prefab.data_or_default(0).materials = Some(self.material_set);
+        self.prefab
+    }
+}
+
+impl<N> Default for GltfPrefabBuilder<N>
+where
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+{
+    fn default() -> Self {
+        GltfPrefabBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::math::Vector3;
+    use amethyst_renderer::PosTex;
+
+    use super::*;
+
+    #[test]
+    fn extents_propagate_to_ancestors() {
+        let mut builder = GltfPrefabBuilder::<f32>::new();
+        let root = builder.root();
+        let body = builder.add_node(root, Some("Body".to_string()), None);
+        let turret = builder.add_node(body, Some("Turret".to_string()), None);
+        builder.set_mesh(
+            turret,
+            MeshData::from(Vec::<PosTex>::new()),
+            None,
+            [0.0, 0.0, 0.0]..[1.0, 2.0, 3.0],
+        );
+        builder.set_mesh(
+            body,
+            MeshData::from(Vec::<PosTex>::new()),
+            None,
+            [-1.0, -1.0, -1.0]..[0.0, 0.0, 0.0],
+        );
+        let mut moved = Transform::default();
+        moved.set_translation_xyz(10.0, 0.0, 0.0);
+        moved.set_scale(Vector3::new(2.0, 2.0, 2.0));
+        let wheel = builder.add_node(body, Some("Wheel".to_string()), Some(moved));
+        builder.set_mesh(
+            wheel,
+            MeshData::from(Vec::<PosTex>::new()),
+            None,
+            [0.0, 0.0, 0.0]..[1.0, 1.0, 1.0],
+        );
+        let mut prefab = builder.build();
+
+        assert_eq!(4, prefab.len());
+        let extent = prefab.data_or_default(root).extent.clone().unwrap();
+        assert_eq!(
+            [-1.0, -1.0, -1.0],
+            [extent.start.x, extent.start.y, extent.start.z]
+        );
+        assert_eq!([12.0, 2.0, 3.0], [extent.end.x, extent.end.y, extent.end.z]);
+        let extent = prefab.data_or_default(wheel).extent.clone().unwrap();
+        assert_eq!([1.0, 1.0, 1.0], [extent.end.x, extent.end.y, extent.end.z]);
+        let extent = prefab.data_or_default(turret).extent.clone().unwrap();
+        assert_eq!(
+            [0.0, 0.0, 0.0],
+            [extent.start.x, extent.start.y, extent.start.z]
+        );
+        assert!(prefab.data_or_default(root).materials.is_some());
+    }
+}
@@ -2,26 +2,52 @@
 
 #![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
 
+use log::error;
 use num_traits::cast::NumCast;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-pub use crate::format::GltfSceneFormat;
+pub use crate::{
+    buffer::{GltfBuffer, GltfBufferFormat},
+    debug::GltfExtentLinesSystem,
+    document::{
+        GltfAnimationInfo, GltfAnimationProperty, GltfDocumentAsset, GltfDocumentFormat,
+        GltfMaterialInfo, GltfMeshInfo, GltfNodeInfo, GltfPrimitiveInfo, GltfSceneInfo,
+    },
+    format::{GltfMeshFormat, GltfSceneFormat},
+    importer::GltfPrefabBuilder,
+};
 
-use std::{collections::HashMap, fmt::Debug, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Debug},
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
 use amethyst_animation::{AnimatablePrefab, SkinnablePrefab};
-use amethyst_assets::{Handle, Prefab, PrefabData, PrefabLoaderSystem, ProgressCounter};
+use amethyst_assets::{
+    AssetStorage, Handle, Loader, Prefab, PrefabData, PrefabLoaderSystem, ProgressCounter,
+};
 use amethyst_core::{
-    ecs::prelude::{Component, DenseVecStorage, Entity, Write, WriteStorage},
-    math::{Point3, RealField, Vector3},
+    ecs::prelude::{Component, DenseVecStorage, Entity, LazyUpdate, Read, Write, WriteStorage},
+    math::{Matrix4, Point3, RealField, Vector3},
     transform::Transform,
-    Named,
+    Aabb, Named,
+};
+use amethyst_error::{format_err, Error};
+use amethyst_renderer::{
+    CameraPrefab, HiddenPropagate, LightPrefab, MaterialPrefab, Mesh, MeshData, MorphTargets,
+    MorphWeights, TextureFormat,
 };
-use amethyst_error::Error;
-use amethyst_renderer::{MaterialPrefab, Mesh, MeshData, TextureFormat};
 
+use crate::format::GltfStreamedSceneFormat;
+
+mod buffer;
+mod debug;
+mod document;
 mod error;
 mod format;
+mod importer;
 
 /// Load `GltfSceneAsset`s
 pub type GltfSceneLoaderSystem<N> = PrefabLoaderSystem<GltfPrefab<N>>;
@@ -41,6 +67,10 @@ pub struct GltfPrefab<
     pub mesh: Option<MeshData>,
     /// Mesh handle after sub asset loading is done
     pub mesh_handle: Option<Handle<Mesh>>,
+    /// Simplified versions of `mesh`, see `GltfSceneOptions::simplify`
+    pub lods: Vec<(f32, MeshData)>,
+    /// `MeshLods` after sub asset loading is done
+    pub lod_handles: Option<MeshLods>,
     /// `Material` is placed on all `Entity`s with graphics primitives with material
     pub material: Option<MaterialPrefab<TextureFormat>>,
     /// Loaded animations, if applicable, will always only be placed on the main `Entity`
@@ -48,12 +78,145 @@ pub struct GltfPrefab<
     /// Skin data is placed on `Entity`s involved in the skin, skeleton or graphical primitives
     /// using the skin
     pub skinnable: Option<SkinnablePrefab<N>>,
+    /// The morph targets of the mesh, placed on `Entity`s with graphics primitives
+    pub morph_targets: Option<MorphTargets>,
+    /// The morph target weights, placed on the `Entity` of the node with the mesh, so all its
+    /// primitives share them
+    pub morph_weights: Option<MorphWeights>,
+    /// Loaded animations of the morph target weights, if applicable, will always only be placed
+    /// on the main `Entity`
+    pub morph_animatable: Option<AnimatablePrefab<usize, MorphWeights>>,
+    /// The camera of the node, see `GltfSceneOptions::load_cameras`
+    pub camera: Option<CameraPrefab>,
+    /// The light of the node, see `GltfSceneOptions::load_lights`
+    pub light: Option<LightPrefab>,
     /// Node extent
     pub extent: Option<GltfNodeExtent>,
     /// Node name
     pub name: Option<Named>,
+    /// The custom properties of the node
+    pub extras: Option<GltfExtras>,
+    /// The triangles of the mesh of the node, see `GltfSceneOptions::load_collision_shapes`
+    pub collision: Option<GltfCollisionGeometry>,
+    /// The scenes loaded into the prefab, only placed on the main `Entity`
+    pub scenes: Vec<GltfPrefabScene>,
+    /// Hidden nodes get a `HiddenPropagate` component, see `GltfSceneOptions::hidden_nodes`
+    pub hidden: bool,
+    /// Don't add the `MeshData` as a component, see `GltfSceneOptions::keep_mesh_data`
+    pub skip_mesh_data: bool,
     pub(crate) materials: Option<GltfMaterialSet>,
     pub(crate) material_id: Option<usize>,
+    /// The glTF index, name and prefab entity index of the nodes, only on the main `Entity` of
+    /// a scene
+    pub(crate) node_map: Option<Vec<(usize, Option<String>, usize)>>,
+    /// The scene waiting for its buffers, only on the main `Entity`, see
+    /// `GltfSceneOptions::stream_buffers`
+    pub(crate) streamed: Option<GltfStreamedScene<N>>,
+}
+
+/// A scene loaded with `GltfSceneOptions::stream_buffers`, which is built once the buffers
+/// stored in their own files are loaded.
+#[derive(Clone, Debug)]
+pub(crate) struct GltfStreamedScene<
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField,
+> {
+    name: String,
+    options: GltfSceneOptions,
+    /// The index and path of the buffers
+    buffers: Vec<(usize, String)>,
+    handles: Vec<Handle<GltfBuffer>>,
+    /// The built scene, shared by all instances of the prefab
+    scene: Arc<Mutex<Option<Handle<GltfSceneAsset<N>>>>>,
+}
+
+impl<N> GltfStreamedScene<N>
+where
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField + From<f32>,
+{
+    pub(crate) fn new(
+        name: String,
+        options: GltfSceneOptions,
+        buffers: Vec<(usize, String)>,
+    ) -> Self {
+        GltfStreamedScene {
+            name,
+            options,
+            buffers,
+            handles: Vec::new(),
+            scene: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn load_buffers(
+        &mut self,
+        loader: &Loader,
+        progress: &mut ProgressCounter,
+        storage: &AssetStorage<GltfBuffer>,
+    ) {
+        self.handles = self
+            .buffers
+            .iter()
+            .map(|(_, path)| {
+                loader.load(path.as_str(), GltfBufferFormat, (), &mut *progress, storage)
+            })
+            .collect();
+    }
+
+    /// Replaces the `Handle` of the scene on the entity with one to the scene built from the
+    /// loaded buffers, which the `GltfSceneLoaderSystem` then spawns like any other scene.
+    ///
+    /// The scene is built for the first entity only, later instances share its handle.
+    fn build(
+        &self,
+        entity: Entity,
+        storage: &AssetStorage<GltfBuffer>,
+        lazy: &LazyUpdate,
+    ) -> Result<(), Error> {
+        let built = self
+            .scene
+            .lock()
+            .map_err(|_| format_err!("The GLTF scene {} was poisoned", self.name))?
+            .clone();
+        let mut loaded = HashMap::new();
+        if built.is_none() {
+            for ((index, path), handle) in self.buffers.iter().zip(&self.handles) {
+                let buffer = storage
+                    .get(handle)
+                    .ok_or_else(|| format_err!("GLTF buffer {} is not loaded", path))?;
+                loaded.insert(*index, buffer.shared());
+            }
+        }
+        let name = self.name.clone();
+        let options = self.options.clone();
+        let scene = self.scene.clone();
+        lazy.exec_mut(move |world| {
+            // Entities instantiated in the same frame all queue a build before the first runs.
+            let handle = match built {
+                Some(handle) => handle,
+                None => {
+                    let mut scene = scene.lock().expect("The GLTF scene was poisoned");
+                    scene
+                        .get_or_insert_with(|| {
+                            world.read_resource::<Loader>().load(
+                                name.as_str(),
+                                GltfStreamedSceneFormat(loaded),
+                                options,
+                                (),
+                                &world.read_resource::<AssetStorage<GltfSceneAsset<N>>>(),
+                            )
+                        })
+                        .clone()
+                }
+            };
+            // Removed first, so the loader system sees the new handle as inserted.
+            let mut scenes = world.write_storage::<Handle<GltfSceneAsset<N>>>();
+            scenes.remove(entity);
+            if let Err(err) = scenes.insert(entity, handle) {
+                error!("Failed to insert the GLTF scene {}: {}", name, err);
+            }
+        });
+        Ok(())
+    }
 }
 
 impl<
@@ -85,6 +248,156 @@ impl<
     }
 }
 
+/// The `extras` of a glTF node, where exporters like Blender store the custom properties of
+/// objects, for spawn points or collider tags for example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfExtras {
+    /// The name of the node.
+    pub name: Option<String>,
+    /// The parsed JSON of the extras.
+    pub value: serde_json::Value,
+}
+
+impl GltfExtras {
+    /// Returns the property with the given name, if the extras are an object containing it.
+    pub fn get(&self, property: &str) -> Option<&serde_json::Value> {
+        self.value.get(property)
+    }
+}
+
+impl Component for GltfExtras {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The triangles of the mesh of a node, kept on the CPU for building physics colliders, see
+/// `GltfSceneOptions::load_collision_shapes` and `GltfSceneOptions::collider_prefix`.
+///
+/// The positions are in the space of the node, and all primitives of the mesh are merged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfCollisionGeometry {
+    /// The positions of the vertices.
+    pub positions: Vec<[f32; 3]>,
+    /// The indices of the vertices of each triangle, three per triangle.
+    pub indices: Vec<u32>,
+}
+
+impl GltfCollisionGeometry {
+    /// Returns the corners of each triangle.
+    pub fn triangles<'a>(&'a self) -> impl Iterator<Item = [[f32; 3]; 3]> + 'a {
+        self.indices.chunks(3).map(move |triangle| {
+            [
+                self.positions[triangle[0] as usize],
+                self.positions[triangle[1] as usize],
+                self.positions[triangle[2] as usize],
+            ]
+        })
+    }
+}
+
+impl Component for GltfCollisionGeometry {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The entities instantiated for the nodes of a glTF scene, placed on its main `Entity`, so
+/// gameplay code can find the node of a weapon attachment or a muzzle to attach children to or
+/// read the transform of.
+///
+/// ```rust,ignore
+/// let muzzle = node_maps
+///     .get(scene_entity)
+///     .and_then(|nodes| nodes.get("Muzzle"));
+/// ```
+///
+/// When several nodes have the same name, the one with the lowest glTF index is returned.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GltfNodeMap {
+    by_index: BTreeMap<usize, Entity>,
+    by_name: HashMap<String, Entity>,
+}
+
+impl GltfNodeMap {
+    fn new(nodes: &[(usize, Option<String>, usize)], entities: &[Entity]) -> Self {
+        let mut map = GltfNodeMap::default();
+        let mut nodes = nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|&&(node, _, _)| node);
+        for &(node, ref name, index) in nodes {
+            let entity = match entities.get(index) {
+                Some(entity) => *entity,
+                None => continue,
+            };
+            map.by_index.insert(node, entity);
+            if let Some(name) = name {
+                map.by_name.entry(name.clone()).or_insert(entity);
+            }
+        }
+        map
+    }
+
+    /// Returns the entity of the node with the given name.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.by_name.get(name).cloned()
+    }
+
+    /// Returns the entity of the node with the given index in the glTF file.
+    pub fn get_by_index(&self, index: usize) -> Option<Entity> {
+        self.by_index.get(&index).cloned()
+    }
+
+    /// Iterates over the names of the nodes and their entities, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = (&str, Entity)> + '_ {
+        self.by_name
+            .iter()
+            .map(|(name, entity)| (name.as_str(), *entity))
+    }
+
+    /// Iterates over the glTF indices of the nodes and their entities, by index.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Entity)> + '_ {
+        self.by_index
+            .iter()
+            .map(|(index, entity)| (*index, *entity))
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.by_index.len()
+    }
+
+    /// Returns true if the scene has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.by_index.is_empty()
+    }
+}
+
+impl Component for GltfNodeMap {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A scene loaded into a `GltfSceneAsset`, see `GltfSceneOptions::load_all_scenes`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GltfPrefabScene {
+    /// The index of the scene in the Gltf file.
+    pub index: usize,
+    /// The name of the scene.
+    pub name: Option<String>,
+    /// The index of the root `Entity` of the scene in the prefab.
+    pub entity: usize,
+}
+
+/// Returns the scenes loaded into a prefab, so they can be looked up before it's instantiated.
+///
+/// The list is empty until the asset is loaded, and for prefabs of a single node.
+pub fn gltf_scenes<N>(asset: &GltfSceneAsset<N>) -> &[GltfPrefabScene]
+where
+    N: Clone + Debug + Default + DeserializeOwned + Serialize + NumCast + RealField,
+{
+    asset
+        .entities()
+        .next()
+        .and_then(|entity| entity.data())
+        .map(|data| &data.scenes[..])
+        .unwrap_or(&[])
+}
+
 /// A GLTF node extent
 #[derive(Clone, Debug)]
 pub struct GltfNodeExtent {
@@ -128,6 +441,40 @@ impl GltfNodeExtent {
         }
     }
 
+    /// Returns the extent containing the corners of this one transformed by `matrix`, e.g. to move
+    /// the extent of a node into the space of its parent.
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> GltfNodeExtent {
+        if !self.valid() {
+            return self.clone();
+        }
+        let mut extent = GltfNodeExtent::default();
+        for corner in 0..8 {
+            let point = Point3::new(
+                if corner & 1 == 0 {
+                    self.start.x
+                } else {
+                    self.end.x
+                },
+                if corner & 2 == 0 {
+                    self.start.y
+                } else {
+                    self.end.y
+                },
+                if corner & 4 == 0 {
+                    self.start.z
+                } else {
+                    self.end.z
+                },
+            );
+            let point = matrix.transform_point(&point);
+            extent.extend(&GltfNodeExtent {
+                start: point,
+                end: point,
+            });
+        }
+        extent
+    }
+
     /// Returns the centroid of this extent
     pub fn centroid(&self) -> Point3<f32> {
         (self.start + self.end.coords) / 2.
@@ -168,24 +515,550 @@ pub struct GltfMaterialSet {
     pub(crate) materials: HashMap<usize, MaterialPrefab<TextureFormat>>,
 }
 
+impl GltfMaterialSet {
+    /// Returns the material with the given id.
+    pub fn get(&self, material_id: usize) -> Option<&MaterialPrefab<TextureFormat>> {
+        self.materials.get(&material_id)
+    }
+
+    /// Iterates over the ids and materials of the set.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &MaterialPrefab<TextureFormat>)> {
+        self.materials.iter().map(|(id, material)| (*id, material))
+    }
+}
+
 /// Options used when loading a GLTF file
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(default)]
+///
+/// In RON files, the options can start from a `GltfScenePreset`, which the other fields override:
+///
+/// ```ron
+/// gltf: File("mesh/hero.gltf", GltfSceneFormat, (
+///     preset: Character,
+///     flip_v_coord: true,
+/// )),
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "GltfSceneOptionsData")]
 pub struct GltfSceneOptions {
     /// Generate texture coordinates if none exist in the Gltf file
     pub generate_tex_coords: (f32, f32),
-    /// Load animation data from the Gltf file
+    /// How to generate the normals of primitives without them, flat by default.
+    pub generate_normals: NormalGeneration,
+    /// Generate MikkTSpace tangents for primitives without them, true by default. Otherwise
+    /// they get an arbitrary tangent perpendicular to the normal, which is faster for scenes
+    /// without normal maps.
+    pub generate_tangents: bool,
+    /// Load animation data from the Gltf file. Animations of morph target weights are loaded into
+    /// `GltfPrefab::morph_animatable`, and are played by an `AnimationBundle<usize, MorphWeights>`
+    /// with the same animation ids as the transforms.
     pub load_animations: bool,
+    /// Add a `Camera` to the nodes with a camera. glTF cameras look along the negative Z axis of
+    /// their node, like the `Camera`.
+    pub load_cameras: bool,
+    /// Add a `Light` to the nodes with a light of the `KHR_lights_punctual` extension. Enabled by
+    /// default, disable it to use the lights of the game instead.
+    pub load_lights: bool,
     /// Flip the v coordinate for all texture coordinates
     pub flip_v_coord: bool,
+    /// Moves the animation channels of the node named by each key to the node named by its value,
+    /// for pipelines which animate proxy nodes instead of the rendered ones.
+    pub animation_targets: HashMap<String, String>,
+    /// Names of the nodes to hide when spawning the scene.
+    ///
+    /// Nodes with `"visible": false` in their extras are hidden as well. Hidden nodes keep their
+    /// meshes and hierarchy, but get a `HiddenPropagate` component, which the `HideHierarchySystem`
+    /// propagates to their children. Removing the component shows them again.
+    pub hidden_nodes: Vec<String>,
+    /// Skip nodes by name, like helper geometry or proxy meshes, so they get no entities and
+    /// their meshes are never loaded.
+    pub node_filter: GltfNodeFilter,
+    /// Bake the vertex colors of primitives without a base color texture into the base color
+    /// factor of their material, instead of loading them as a vertex attribute.
+    ///
+    /// The colors of a primitive are averaged, so this suits assets with a single color per
+    /// primitive. Each baked primitive gets a material of its own in the `GltfMaterialSet`.
+    pub bake_vertex_colors: bool,
+    /// Simplify the meshes of the primitives, and generate more detail levels for a `MeshLods`
+    /// component.
+    pub simplify: Option<MeshSimplification>,
+    /// Split primitives larger than this size into cells of this size, each loaded as a child
+    /// entity with its own `GltfNodeExtent`, so frustum culling can skip the parts of a big merged
+    /// level mesh which are out of view.
+    ///
+    /// The size is measured in the space of the mesh, before the transforms of the nodes, and
+    /// each triangle goes to the cell containing its center.
+    pub split_cell_size: Option<f32>,
+    /// Index the vertices of the meshes, and reorder them and their triangles for the vertex cache
+    /// and fetching of the GPU and for less overdraw. This makes loading slower.
+    pub optimize_meshes: bool,
+    /// Add the `MeshData` of the primitives as a component, for example to build colliders or a
+    /// `NavMesh` from them. Enabled by default. Primitives with morph targets always keep it, for
+    /// the `MorphSystem` of the renderer.
+    pub keep_mesh_data: bool,
+    /// Add a `GltfCollisionGeometry` with the triangles of the mesh to every node with a mesh,
+    /// next to its render mesh.
+    pub load_collision_shapes: bool,
+    /// Nodes whose name starts with this prefix, like `"_collider"`, only get a
+    /// `GltfCollisionGeometry`, and their mesh is neither rendered nor loaded on the GPU.
+    pub collider_prefix: Option<String>,
+    /// Materials replacing the ones of the file with the same glTF material name, for example to
+    /// spawn a model with the textures of another team. Their textures are loaded with the other
+    /// sub assets of the scene, and the `material_processor` still applies to them.
+    pub material_overrides: HashMap<String, MaterialPrefab<TextureFormat>>,
+    /// Called with every imported material and the glTF material it was loaded from, to adjust
+    /// it before it is added to the `GltfMaterialSet`. Can't be set from files, see
+    /// `with_material_processor`.
+    #[serde(skip)]
+    pub material_processor: Option<Arc<MaterialProcessor>>,
+    /// Flag all materials as unlit, as if they had the `KHR_materials_unlit` extension.
+    pub force_unlit: bool,
+    /// Load the textures stored in their own files through the `Loader`, which reads them on its
+    /// own threads and counts them in the `ProgressCounter` of the scene, instead of reading them
+    /// while importing the scene. False by default.
+    ///
+    /// The `Loader` reads them from its default source, so only enable it for files loaded from
+    /// the default source. Metallic roughness textures are still read during the import, since
+    /// they are split into two textures. See `stream_buffers` for the buffers.
+    pub stream_textures: bool,
+    /// Load the buffers stored in their own files, like the `.bin` file next to a `.gltf` file,
+    /// through the `Loader` as `GltfBuffer`s, instead of reading them while importing the scene.
+    /// Each buffer is counted in the `ProgressCounter` of the scene. False by default.
+    ///
+    /// The scene is built from the buffers once they are loaded, so the `ProgressCounter`
+    /// completes before the scene has been spawned. It requires a `Processor<GltfBuffer>`, and
+    /// the `Loader` reads the buffers from its default source, so only enable it for files
+    /// loaded from the default source.
+    pub stream_buffers: bool,
+    /// Fail before loading scenes which are too large, for example files exported by accident
+    /// with far more nodes than intended.
+    pub limits: GltfSceneLimits,
     /// Load the given scene index, if not supplied will either load the default scene (if set),
     /// or the first scene (only if there is only one scene, otherwise an `Error` will be returned).
     pub scene_index: Option<usize>,
+    /// Load every scene of the file as a child of the main `Entity`, ignoring `scene_index`.
+    /// The scenes are listed in `GltfPrefab::scenes`, and each has its own materials, skins and
+    /// animations on its root `Entity`.
+    pub load_all_scenes: bool,
+}
+
+impl Default for GltfSceneOptions {
+    fn default() -> Self {
+        GltfSceneOptions {
+            generate_tex_coords: (0.0, 0.0),
+            generate_normals: NormalGeneration::Flat,
+            generate_tangents: true,
+            load_animations: false,
+            load_cameras: false,
+            load_lights: true,
+            flip_v_coord: false,
+            animation_targets: HashMap::new(),
+            hidden_nodes: Vec::new(),
+            node_filter: GltfNodeFilter::default(),
+            bake_vertex_colors: false,
+            simplify: None,
+            split_cell_size: None,
+            optimize_meshes: false,
+            keep_mesh_data: true,
+            load_collision_shapes: false,
+            collider_prefix: None,
+            material_overrides: HashMap::new(),
+            material_processor: None,
+            force_unlit: false,
+            stream_textures: false,
+            stream_buffers: false,
+            limits: GltfSceneLimits::default(),
+            scene_index: None,
+            load_all_scenes: false,
+        }
+    }
+}
+
+impl Debug for GltfSceneOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GltfSceneOptions")
+            .field("generate_tex_coords", &self.generate_tex_coords)
+            .field("generate_normals", &self.generate_normals)
+            .field("generate_tangents", &self.generate_tangents)
+            .field("load_animations", &self.load_animations)
+            .field("load_cameras", &self.load_cameras)
+            .field("load_lights", &self.load_lights)
+            .field("flip_v_coord", &self.flip_v_coord)
+            .field("animation_targets", &self.animation_targets)
+            .field("hidden_nodes", &self.hidden_nodes)
+            .field("node_filter", &self.node_filter)
+            .field("bake_vertex_colors", &self.bake_vertex_colors)
+            .field("simplify", &self.simplify)
+            .field("split_cell_size", &self.split_cell_size)
+            .field("optimize_meshes", &self.optimize_meshes)
+            .field("keep_mesh_data", &self.keep_mesh_data)
+            .field("load_collision_shapes", &self.load_collision_shapes)
+            .field("collider_prefix", &self.collider_prefix)
+            .field("material_overrides", &self.material_overrides)
+            .field(
+                "material_processor",
+                &self.material_processor.as_ref().map(|_| ".."),
+            )
+            .field("force_unlit", &self.force_unlit)
+            .field("stream_textures", &self.stream_textures)
+            .field("stream_buffers", &self.stream_buffers)
+            .field("limits", &self.limits)
+            .field("scene_index", &self.scene_index)
+            .field("load_all_scenes", &self.load_all_scenes)
+            .finish()
+    }
+}
+
+/// A callback adjusting imported materials, see `GltfSceneOptions::material_processor`.
+pub type MaterialProcessor = dyn Fn(&gltf::Material<'_>, MaterialPrefab<TextureFormat>) -> MaterialPrefab<TextureFormat>
+    + Send
+    + Sync;
+
+impl GltfSceneOptions {
+    /// Options for static level geometry: animations are skipped, and the `MeshData` is kept for
+    /// building colliders or a `NavMesh`.
+    pub fn for_static_level() -> Self {
+        GltfSceneOptions {
+            load_animations: false,
+            optimize_meshes: true,
+            keep_mesh_data: true,
+            ..Default::default()
+        }
+    }
+
+    /// Options for animated characters: animations and skins are loaded, and the `MeshData` is
+    /// dropped after the meshes are created.
+    pub fn for_character() -> Self {
+        GltfSceneOptions {
+            load_animations: true,
+            optimize_meshes: true,
+            keep_mesh_data: false,
+            ..Default::default()
+        }
+    }
+
+    /// Options for small props: animations and the `MeshData` are skipped, and the vertex colors
+    /// of untextured primitives are baked into their materials.
+    pub fn for_prop() -> Self {
+        GltfSceneOptions {
+            load_animations: false,
+            keep_mesh_data: false,
+            bake_vertex_colors: true,
+            optimize_meshes: true,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the `material_processor`, for example to clamp the roughness of all materials.
+    pub fn with_material_processor<F>(mut self, processor: F) -> Self
+    where
+        F: Fn(&gltf::Material<'_>, MaterialPrefab<TextureFormat>) -> MaterialPrefab<TextureFormat>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.material_processor = Some(Arc::new(processor));
+        self
+    }
+
+    /// Sets the `callback` of the `node_filter`, for example to skip the nodes of LOD variants
+    /// by their extras.
+    pub fn with_node_filter<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&gltf::Node<'_>) -> bool + Send + Sync + 'static,
+    {
+        self.node_filter.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Returns the options of the preset.
+    pub fn from_preset(preset: GltfScenePreset) -> Self {
+        match preset {
+            GltfScenePreset::StaticLevel => GltfSceneOptions::for_static_level(),
+            GltfScenePreset::Character => GltfSceneOptions::for_character(),
+            GltfScenePreset::Prop => GltfSceneOptions::for_prop(),
+        }
+    }
+}
+
+/// Skips the nodes of a glTF scene while loading it, see `GltfSceneOptions::node_filter`.
+///
+/// Patterns match the whole node name, `*` standing for any number of characters and `?` for one.
+/// A skipped node is skipped with all of its children.
+///
+/// ```rust,ignore
+/// node_filter: (
+///     exclude: ["*_proxy", "LOD?_*", "helper_*"],
+/// ),
+/// ```
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GltfNodeFilter {
+    /// If not empty, only the nodes whose name matches one of these patterns are kept, along
+    /// with their parents and children.
+    pub include: Vec<String>,
+    /// Nodes whose name matches one of these patterns are skipped, even if they are included.
+    pub exclude: Vec<String>,
+    /// Called with every node that is not excluded, skipping it if it returns false. Can't be
+    /// set from files, see `GltfSceneOptions::with_node_filter`.
+    #[serde(skip)]
+    pub callback: Option<Arc<NodeFilterCallback>>,
+}
+
+impl Debug for GltfNodeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GltfNodeFilter")
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .field("callback", &self.callback.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// A callback deciding which nodes are loaded, see `GltfNodeFilter::callback`.
+pub type NodeFilterCallback = dyn Fn(&gltf::Node<'_>) -> bool + Send + Sync;
+
+/// Mesh simplification at import time, which collapses the edges of the mesh with the smallest
+/// quadric error until enough triangles are removed.
+///
+/// Meant for distant scenery: the positions are moved, while the normals and texture coordinates
+/// are kept from the original vertices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshSimplification {
+    /// The fraction of the triangles to keep, between 0 and 1.
+    pub ratio: f32,
+    /// Stop early once collapsing an edge would exceed this error, the squared distance to the
+    /// original surface weighted by the area of the triangles.
+    pub max_error: Option<f32>,
+    /// The fractions of the original triangles to keep in each level of a `MeshLods` component,
+    /// usually decreasing. No component is added if this is empty.
+    pub lods: Vec<f32>,
+}
+
+impl Default for MeshSimplification {
+    fn default() -> Self {
+        MeshSimplification {
+            ratio: 0.5,
+            max_error: None,
+            lods: Vec::new(),
+        }
+    }
+}
+
+/// How normals are generated for primitives without them, see
+/// `GltfSceneOptions::generate_normals`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NormalGeneration {
+    /// Each triangle gets its own normal, for faceted surfaces.
+    Flat,
+    /// Corners at the same position share the average normal of their triangles, weighted by
+    /// their area, for curved surfaces.
+    Smooth,
+}
+
+impl Default for NormalGeneration {
+    fn default() -> Self {
+        NormalGeneration::Flat
+    }
+}
+
+/// Limits on the size of a loaded glTF scene, see `GltfSceneOptions::limits`. They are checked
+/// against the document before any buffers, meshes or images are loaded, and nothing is limited
+/// by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GltfSceneLimits {
+    /// The maximum number of nodes in the scene.
+    pub max_nodes: Option<usize>,
+    /// The maximum number of vertices of all meshes in the scene, counting meshes once per node
+    /// using them.
+    pub max_vertices: Option<usize>,
+    /// The maximum size of the images used by the materials in the scene, as stored in the file.
+    /// External images are only counted if the `Source` knows their size.
+    pub max_texture_bytes: Option<u64>,
+    /// The maximum length of all buffers of the file, as declared in the document. All buffers
+    /// are counted, as all of them are loaded.
+    pub max_buffer_bytes: Option<u64>,
+}
+
+impl GltfSceneLimits {
+    /// Whether any of the limits is set.
+    pub(crate) fn limited(&self) -> bool {
+        self.max_nodes.is_some()
+            || self.max_vertices.is_some()
+            || self.max_texture_bytes.is_some()
+            || self.max_buffer_bytes.is_some()
+    }
+}
+
+/// Simplified meshes of an entity generated by `GltfSceneOptions::simplify`, to be swapped in by
+/// the game when the entity is far from the camera.
+#[derive(Debug, Clone)]
+pub struct MeshLods {
+    /// The fraction of the triangles kept and the mesh of each level.
+    pub levels: Vec<(f32, Handle<Mesh>)>,
+}
+
+impl Component for MeshLods {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Named combinations of `GltfSceneOptions`, see `GltfSceneOptions::from_preset`. All presets
+/// enable `optimize_meshes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GltfScenePreset {
+    /// `GltfSceneOptions::for_static_level`
+    StaticLevel,
+    /// `GltfSceneOptions::for_character`
+    Character,
+    /// `GltfSceneOptions::for_prop`
+    Prop,
+}
+
+/// `GltfSceneOptions` as written in files, where every field overrides the preset.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct GltfSceneOptionsData {
+    preset: Option<GltfScenePreset>,
+    #[serde(deserialize_with = "deserialize_some")]
+    generate_tex_coords: Option<(f32, f32)>,
+    #[serde(deserialize_with = "deserialize_some")]
+    generate_normals: Option<NormalGeneration>,
+    #[serde(deserialize_with = "deserialize_some")]
+    generate_tangents: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    load_animations: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    load_cameras: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    load_lights: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    flip_v_coord: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    animation_targets: Option<HashMap<String, String>>,
+    #[serde(deserialize_with = "deserialize_some")]
+    hidden_nodes: Option<Vec<String>>,
+    #[serde(deserialize_with = "deserialize_some")]
+    node_filter: Option<GltfNodeFilter>,
+    #[serde(deserialize_with = "deserialize_some")]
+    bake_vertex_colors: Option<bool>,
+    simplify: Option<MeshSimplification>,
+    split_cell_size: Option<f32>,
+    #[serde(deserialize_with = "deserialize_some")]
+    optimize_meshes: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    keep_mesh_data: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    load_collision_shapes: Option<bool>,
+    collider_prefix: Option<String>,
+    #[serde(deserialize_with = "deserialize_some")]
+    material_overrides: Option<HashMap<String, MaterialPrefab<TextureFormat>>>,
+    #[serde(deserialize_with = "deserialize_some")]
+    force_unlit: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    stream_textures: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    stream_buffers: Option<bool>,
+    #[serde(deserialize_with = "deserialize_some")]
+    limits: Option<GltfSceneLimits>,
+    scene_index: Option<usize>,
+    #[serde(deserialize_with = "deserialize_some")]
+    load_all_scenes: Option<bool>,
+}
+
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+impl From<GltfSceneOptionsData> for GltfSceneOptions {
+    fn from(data: GltfSceneOptionsData) -> Self {
+        let mut options = data
+            .preset
+            .map(GltfSceneOptions::from_preset)
+            .unwrap_or_default();
+        if let Some(generate_tex_coords) = data.generate_tex_coords {
+            options.generate_tex_coords = generate_tex_coords;
+        }
+        if let Some(generate_normals) = data.generate_normals {
+            options.generate_normals = generate_normals;
+        }
+        if let Some(generate_tangents) = data.generate_tangents {
+            options.generate_tangents = generate_tangents;
+        }
+        if let Some(load_animations) = data.load_animations {
+            options.load_animations = load_animations;
+        }
+        if let Some(load_cameras) = data.load_cameras {
+            options.load_cameras = load_cameras;
+        }
+        if let Some(load_lights) = data.load_lights {
+            options.load_lights = load_lights;
+        }
+        if let Some(flip_v_coord) = data.flip_v_coord {
+            options.flip_v_coord = flip_v_coord;
+        }
+        if let Some(animation_targets) = data.animation_targets {
+            options.animation_targets = animation_targets;
+        }
+        if let Some(hidden_nodes) = data.hidden_nodes {
+            options.hidden_nodes = hidden_nodes;
+        }
+        if let Some(node_filter) = data.node_filter {
+            options.node_filter = node_filter;
+        }
+        if let Some(bake_vertex_colors) = data.bake_vertex_colors {
+            options.bake_vertex_colors = bake_vertex_colors;
+        }
+        if data.simplify.is_some() {
+            options.simplify = data.simplify;
+        }
+        if data.split_cell_size.is_some() {
+            options.split_cell_size = data.split_cell_size;
+        }
+        if let Some(optimize_meshes) = data.optimize_meshes {
+            options.optimize_meshes = optimize_meshes;
+        }
+        if let Some(keep_mesh_data) = data.keep_mesh_data {
+            options.keep_mesh_data = keep_mesh_data;
+        }
+        if let Some(load_collision_shapes) = data.load_collision_shapes {
+            options.load_collision_shapes = load_collision_shapes;
+        }
+        if data.collider_prefix.is_some() {
+            options.collider_prefix = data.collider_prefix;
+        }
+        if let Some(material_overrides) = data.material_overrides {
+            options.material_overrides = material_overrides;
+        }
+        if let Some(force_unlit) = data.force_unlit {
+            options.force_unlit = force_unlit;
+        }
+        if let Some(stream_textures) = data.stream_textures {
+            options.stream_textures = stream_textures;
+        }
+        if let Some(stream_buffers) = data.stream_buffers {
+            options.stream_buffers = stream_buffers;
+        }
+        if let Some(limits) = data.limits {
+            options.limits = limits;
+        }
+        if data.scene_index.is_some() {
+            options.scene_index = data.scene_index;
+        }
+        if let Some(load_all_scenes) = data.load_all_scenes {
+            options.load_all_scenes = load_all_scenes;
+        }
+        options
+    }
 }
 
 impl<'a, N> PrefabData<'a> for GltfPrefab<N>
 where
-    N: RealField + Serialize + DeserializeOwned + NumCast + Clone + Debug + Default,
+    N: RealField + Serialize + DeserializeOwned + NumCast + Clone + Debug + Default + From<f32>,
 {
     type SystemData = (
         <Transform<N> as PrefabData<'a>>::SystemData,
@@ -198,6 +1071,18 @@ where
         // TODO make optional after prefab refactor. We need a way to pass options to decide to enable this or not, but without touching the prefab.
         WriteStorage<'a, MeshData>,
         Write<'a, GltfMaterialSet>,
+        WriteStorage<'a, HiddenPropagate>,
+        WriteStorage<'a, MeshLods>,
+        <CameraPrefab as PrefabData<'a>>::SystemData,
+        <LightPrefab as PrefabData<'a>>::SystemData,
+        WriteStorage<'a, MorphTargets>,
+        WriteStorage<'a, MorphWeights>,
+        <AnimatablePrefab<usize, MorphWeights> as PrefabData<'a>>::SystemData,
+        WriteStorage<'a, GltfExtras>,
+        WriteStorage<'a, GltfCollisionGeometry>,
+        WriteStorage<'a, GltfNodeMap>,
+        Read<'a, AssetStorage<GltfBuffer>>,
+        Read<'a, LazyUpdate>,
     );
     type Result = ();
 
@@ -218,16 +1103,33 @@ where
             ref mut extents,
             ref mut mesh_data,
             _,
+            ref mut hidden,
+            ref mut lods,
+            ref mut cameras,
+            ref mut lights,
+            ref mut morph_targets,
+            ref mut morph_weights,
+            ref mut morph_animatables,
+            ref mut extras,
+            ref mut collisions,
+            ref mut node_maps,
+            ref buffers,
+            ref lazy,
         ) = system_data;
         if let Some(ref transform) = self.transform {
             transform.add_to_entity(entity, transforms, entities, children)?;
         }
         if let Some(ref mesh) = self.mesh {
-            mesh_data.insert(entity, mesh.clone())?;
+            if !self.skip_mesh_data {
+                mesh_data.insert(entity, mesh.clone())?;
+            }
         }
         if let Some(ref mesh) = self.mesh_handle {
             meshes.1.insert(entity, mesh.clone())?;
         }
+        if let Some(ref mesh_lods) = self.lod_handles {
+            lods.insert(entity, mesh_lods.clone())?;
+        }
         if let Some(ref name) = self.name {
             name.add_to_entity(entity, names, entities, children)?;
         }
@@ -240,8 +1142,45 @@ where
         if let Some(ref skinnable) = self.skinnable {
             skinnable.add_to_entity(entity, skinnables, entities, children)?;
         }
+        if self.hidden {
+            hidden.insert(entity, HiddenPropagate)?;
+        }
+        if let Some(ref camera) = self.camera {
+            camera.add_to_entity(entity, cameras, entities, children)?;
+        }
+        if let Some(ref light) = self.light {
+            light.add_to_entity(entity, lights, entities, children)?;
+        }
+        if let Some(ref targets) = self.morph_targets {
+            morph_targets.insert(entity, targets.clone())?;
+        }
+        if let Some(ref weights) = self.morph_weights {
+            morph_weights.insert(entity, weights.clone())?;
+        }
+        if let Some(ref animatable) = self.morph_animatable {
+            animatable.add_to_entity(entity, morph_animatables, entities, children)?;
+        }
+        if let Some(ref node_extras) = self.extras {
+            extras.insert(entity, node_extras.clone())?;
+        }
+        if let Some(ref collision) = self.collision {
+            collisions.insert(entity, collision.clone())?;
+        }
+        if let Some(ref nodes) = self.node_map {
+            node_maps.insert(entity, GltfNodeMap::new(nodes, entities))?;
+        }
+        if let Some(ref streamed) = self.streamed {
+            streamed.build(entity, buffers, lazy)?;
+        }
         if let Some(ref extent) = self.extent {
             extents.insert(entity, extent.clone())?;
+            // Nodes without a mesh only hold the merged extents of their children.
+            let has_mesh = self.mesh.is_some() || self.mesh_handle.is_some();
+            if has_mesh && extent.valid() && !meshes.3.contains(entity) {
+                meshes
+                    .3
+                    .insert(entity, Aabb::new(extent.start.coords, extent.end.coords))?;
+            }
         }
         Ok(())
     }
@@ -261,8 +1200,24 @@ where
             _,
             _,
             ref mut mat_set,
+            _,
+            _,
+            _,
+            _,
+            _,
+            _,
+            ref mut morph_animatables,
+            _,
+            _,
+            _,
+            ref buffers,
+            _,
         ) = system_data;
         let mut ret = false;
+        if let Some(ref mut streamed) = self.streamed {
+            streamed.load_buffers(&meshes.0, progress, buffers);
+            ret = true;
+        }
         if let Some(ref mut mats) = self.materials {
             mat_set.materials.clear();
             for (id, material) in mats.materials.iter_mut() {
@@ -280,6 +1235,20 @@ where
             ));
             ret = true;
         }
+        if !self.lods.is_empty() {
+            let levels = self
+                .lods
+                .iter()
+                .map(|(ratio, mesh)| {
+                    let handle = meshes
+                        .0
+                        .load_from_data(mesh.clone(), &mut *progress, &meshes.2);
+                    (*ratio, handle)
+                })
+                .collect();
+            self.lod_handles = Some(MeshLods { levels });
+            ret = true;
+        }
         match self.material_id {
             Some(material_id) => {
                 if let Some(mat) = mat_set.materials.get(&material_id) {
@@ -299,6 +1268,11 @@ where
                 ret = true;
             }
         }
+        if let Some(ref mut animatable) = self.morph_animatable {
+            if animatable.load_sub_assets(progress, morph_animatables)? {
+                ret = true;
+            }
+        }
         Ok(ret)
     }
 }
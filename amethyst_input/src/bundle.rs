@@ -2,13 +2,20 @@
 
 use derivative::Derivative;
 use serde::{de::DeserializeOwned, Serialize};
-use std::{error, fmt, hash::Hash, path::Path};
+use std::{
+    error, fmt,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
 
 use amethyst_config::{Config, ConfigError};
-use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
+use amethyst_core::bundle::{BundleBuilder, SystemBundle};
 use amethyst_error::Error;
 
-use crate::{BindingError, Bindings, InputSystem};
+use crate::{
+    recording::{InputRecording, InputSource},
+    BindingError, Bindings, InputSystem,
+};
 
 #[cfg(feature = "sdl_controller")]
 use crate::sdl_events_system::ControllerMappings;
@@ -37,6 +44,7 @@ where
     AC: Hash + Eq + Clone,
 {
     bindings: Option<Bindings<AX, AC>>,
+    source: InputSource,
     #[cfg(feature = "sdl_controller")]
     controller_mappings: Option<ControllerMappings>,
 }
@@ -71,6 +79,36 @@ where
         Ok(self.with_bindings(bindings))
     }
 
+    /// Record the window input, writing it to `path` when the application exits.
+    ///
+    /// See `InputSystem::with_recording`.
+    pub fn with_recording<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.source = InputSource::Recording {
+            recording: InputRecording::new(),
+            path: Some(path.into()),
+            start: None,
+        };
+        self
+    }
+
+    /// Play back the recording instead of the window input.
+    ///
+    /// See `InputSystem::with_playback`.
+    pub fn with_playback(mut self, recording: InputRecording) -> Self {
+        self.source = InputSource::Playback {
+            recording,
+            next: 0,
+            start: None,
+        };
+        self
+    }
+
+    /// Load a recording from file and play it back instead of the window input.
+    pub fn with_playback_from_file<P: AsRef<Path>>(self, file: P) -> Result<Self, ConfigError> {
+        let recording = InputRecording::load_no_fallback(file)?;
+        Ok(self.with_playback(recording))
+    }
+
     /// Load SDL controller mappings from file
     #[cfg(feature = "sdl_controller")]
     pub fn with_sdl_controller_mappings(mut self, mappings: String) -> Self {
@@ -98,17 +136,18 @@ where
     AX: Hash + Eq + Clone + Send + Sync + 'static,
     AC: Hash + Eq + Clone + Send + Sync + 'static,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         #[cfg(feature = "sdl_controller")]
         {
             use super::SdlEventsSystem;
             builder.add_thread_local(
                 // TODO: improve errors when migrating to failure
                 SdlEventsSystem::<AX, AC>::new(self.controller_mappings).unwrap(),
+                "sdl_events_system",
             );
         }
         builder.add(
-            InputSystem::<AX, AC>::new(self.bindings),
+            InputSystem::<AX, AC>::new(self.bindings).with_source(self.source),
             "input_system",
             &[],
         );
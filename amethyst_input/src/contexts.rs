@@ -0,0 +1,299 @@
+//! Layered bindings for switching between input contexts, e.g. "gameplay" and "menu".
+
+use std::{collections::HashSet, fmt::Display, hash::Hash};
+
+use derivative::Derivative;
+use fnv::FnvHashMap as HashMap;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+
+use crate::{BindingError, Bindings, Button};
+
+/// Named sets of bindings, of which any number can be active as a stack of layers.
+///
+/// Insert this as a resource to let the `InputSystem` replace the bindings of the
+/// `InputHandler` with the bindings of the active contexts whenever they change. A context pushed
+/// later takes precedence: its actions and axes replace the ones of the same name in lower
+/// contexts, and bindings of lower contexts using the same buttons as a higher context are
+/// ignored.
+///
+/// The contexts can be saved and loaded with `amethyst_config::Config`, e.g. to persist
+/// bindings customized by the player with `rebind_action`. The stack of active contexts is not
+/// saved.
+#[derive(Derivative, Serialize, Deserialize, Clone)]
+#[derivative(Default(bound = ""))]
+pub struct InputContexts<AX = String, AC = String>
+where
+    AX: Clone + Hash + Eq,
+    AC: Clone + Hash + Eq,
+{
+    contexts: HashMap<String, Bindings<AX, AC>>,
+    #[serde(skip)]
+    stack: Vec<String>,
+    #[serde(skip)]
+    active: Bindings<AX, AC>,
+    #[serde(skip)]
+    generation: u64,
+}
+
+impl<AX, AC> InputContexts<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    /// Returns the combined bindings of all active contexts.
+    pub fn active_bindings(&self) -> &Bindings<AX, AC> {
+        &self.active
+    }
+
+    /// Counter which is changed every time the active bindings change.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl<AX, AC> InputContexts<AX, AC>
+where
+    AX: Hash + Eq + Clone + Display,
+    AC: Hash + Eq + Clone + Display,
+{
+    /// Creates an empty set of contexts.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a context, returning the bindings it replaced.
+    pub fn insert_context<S: Into<String>>(
+        &mut self,
+        name: S,
+        bindings: Bindings<AX, AC>,
+    ) -> Option<Bindings<AX, AC>> {
+        let previous = self.contexts.insert(name.into(), bindings);
+        self.rebuild();
+        previous
+    }
+
+    /// Removes a context, also removing it from the active contexts.
+    pub fn remove_context(&mut self, name: &str) -> Option<Bindings<AX, AC>> {
+        self.stack.retain(|active| active != name);
+        let removed = self.contexts.remove(name);
+        self.rebuild();
+        removed
+    }
+
+    /// Returns the bindings of a context.
+    pub fn context(&self, name: &str) -> Option<&Bindings<AX, AC>> {
+        self.contexts.get(name)
+    }
+
+    /// Returns the names of all contexts.
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.contexts.keys().map(String::as_str)
+    }
+
+    /// Activates a context on top of the active ones.
+    ///
+    /// Contexts which don't exist yet can be activated, they take effect once inserted.
+    pub fn push<S: Into<String>>(&mut self, name: S) {
+        self.stack.push(name.into());
+        self.rebuild();
+    }
+
+    /// Deactivates the topmost context and returns its name.
+    pub fn pop(&mut self) -> Option<String> {
+        let popped = self.stack.pop();
+        self.rebuild();
+        popped
+    }
+
+    /// Returns the names of the active contexts, topmost first.
+    pub fn active(&self) -> impl Iterator<Item = &str> {
+        self.stack.iter().rev().map(String::as_str)
+    }
+
+    /// Returns `true` if the context is active.
+    pub fn is_active(&self, name: &str) -> bool {
+        self.stack.iter().any(|active| active == name)
+    }
+
+    /// Replaces all bindings of the action in the context with a single binding, creating the
+    /// context if it doesn't exist.
+    ///
+    /// Fails without changing the bindings if the binding conflicts with another binding of the
+    /// context. Use `conflicts` to find conflicts with other contexts.
+    pub fn rebind_action<B>(
+        &mut self,
+        context: &str,
+        action: AC,
+        binding: B,
+    ) -> Result<(), BindingError<AX, AC>>
+    where
+        B: IntoIterator<Item = Button>,
+    {
+        let result = {
+            let bindings = self
+                .contexts
+                .entry(context.to_string())
+                .or_insert_with(Bindings::new);
+            let previous = bindings.actions.remove(&action);
+            let result = bindings.insert_action_binding(action.clone(), binding);
+            if result.is_err() {
+                if let Some(previous) = previous {
+                    bindings.actions.insert(action, previous);
+                }
+            }
+            result
+        };
+        self.rebuild();
+        result
+    }
+
+    /// Returns the contexts and actions which are bound to the same buttons as `binding`, in any
+    /// order.
+    pub fn conflicts(&self, binding: &[Button]) -> Vec<(&str, &AC)> {
+        let mut conflicts = Vec::new();
+        for (name, bindings) in self.contexts.iter() {
+            for (action, combos) in bindings.actions.iter() {
+                if combos.iter().any(|combo| same_buttons(combo, binding)) {
+                    conflicts.push((name.as_str(), action));
+                }
+            }
+        }
+        conflicts
+    }
+
+    fn rebuild(&mut self) {
+        let mut active = Bindings::new();
+        let mut seen_axes = HashSet::new();
+        let mut seen_actions = HashSet::new();
+        for name in self.stack.iter().rev() {
+            let bindings = match self.contexts.get(name) {
+                Some(bindings) => bindings,
+                None => continue,
+            };
+            for (id, axis) in bindings.axes.iter() {
+                // Shadowed by a higher context, or conflicting with its buttons.
+                if !seen_axes.contains(id) {
+                    let _ = active.insert_axis(id.clone(), axis.clone());
+                }
+            }
+            for (id, combos) in bindings.actions.iter() {
+                if !seen_actions.contains(id) {
+                    for combo in combos {
+                        let _ = active.insert_action_binding(id.clone(), combo.iter().cloned());
+                    }
+                }
+            }
+            seen_axes.extend(bindings.axes.keys().cloned());
+            seen_actions.extend(bindings.actions.keys().cloned());
+        }
+        self.active = active;
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+fn same_buttons(combo: &SmallVec<[Button; 2]>, binding: &[Button]) -> bool {
+    combo.len() == binding.len() && combo.iter().all(|button| binding.contains(button))
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_config::Config;
+    use winit::VirtualKeyCode;
+
+    use super::*;
+
+    fn bindings(action: &str, key: VirtualKeyCode) -> Bindings<String, String> {
+        let mut bindings = Bindings::new();
+        bindings
+            .insert_action_binding(action.to_string(), vec![Button::Key(key)])
+            .unwrap();
+        bindings
+    }
+
+    #[test]
+    fn contexts_are_layered() {
+        let mut contexts = InputContexts::<String, String>::new();
+        let mut gameplay = bindings("jump", VirtualKeyCode::Space);
+        gameplay
+            .insert_action_binding(
+                "pause".to_string(),
+                vec![Button::Key(VirtualKeyCode::Escape)],
+            )
+            .unwrap();
+        contexts.insert_context("gameplay", gameplay);
+        contexts.insert_context("menu", bindings("back", VirtualKeyCode::Escape));
+
+        contexts.push("gameplay");
+        assert!(contexts
+            .active_bindings()
+            .action_bindings("pause")
+            .next()
+            .is_some());
+
+        contexts.push("menu");
+        // escape is taken by the menu
+        assert!(contexts
+            .active_bindings()
+            .action_bindings("pause")
+            .next()
+            .is_none());
+        assert!(contexts
+            .active_bindings()
+            .action_bindings("back")
+            .next()
+            .is_some());
+        assert!(contexts
+            .active_bindings()
+            .action_bindings("jump")
+            .next()
+            .is_some());
+
+        assert_eq!(Some("menu".to_string()), contexts.pop());
+        assert!(contexts
+            .active_bindings()
+            .action_bindings("back")
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn rebinding_round_trips() {
+        let mut contexts = InputContexts::<String, String>::new();
+        let mut gameplay = bindings("jump", VirtualKeyCode::Space);
+        gameplay
+            .insert_action_binding("fire".to_string(), vec![Button::Key(VirtualKeyCode::F)])
+            .unwrap();
+        contexts.insert_context("gameplay", gameplay);
+
+        assert!(contexts
+            .rebind_action(
+                "gameplay",
+                "jump".to_string(),
+                vec![Button::Key(VirtualKeyCode::F)]
+            )
+            .is_err());
+        assert_eq!(
+            vec![("gameplay", &"fire".to_string())],
+            contexts.conflicts(&[Button::Key(VirtualKeyCode::F)])
+        );
+        contexts
+            .rebind_action(
+                "gameplay",
+                "jump".to_string(),
+                vec![Button::Key(VirtualKeyCode::J)],
+            )
+            .unwrap();
+
+        let path = std::env::temp_dir().join("amethyst_input_contexts_test.ron");
+        contexts.write(&path).unwrap();
+        let loaded = InputContexts::<String, String>::load_no_fallback(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let jump = loaded
+            .context("gameplay")
+            .unwrap()
+            .action_bindings("jump")
+            .collect::<Vec<_>>();
+        assert_eq!(vec![[Button::Key(VirtualKeyCode::J)]], jump);
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 use winit::{MouseButton, VirtualKeyCode};
 
@@ -86,6 +88,12 @@ pub enum InputEvent<T> {
         /// The id for the controller disconnected.
         which: u32,
     },
+    /// A file is dragged over the window, sent once for each file.
+    FileHovered(PathBuf),
+    /// A file was dropped onto the window, sent once for each file.
+    FileDropped(PathBuf),
+    /// The files dragged over the window left it without being dropped.
+    FileHoverCancelled,
     /// The associated action had any related button or combination pressed.
     ///
     /// If a combination is bound to an action, it will be pressed
@@ -3,6 +3,7 @@
 use std::{borrow::Borrow, hash::Hash};
 
 use derivative::Derivative;
+use fnv::FnvHashMap as HashMap;
 use smallvec::SmallVec;
 use winit::{
     dpi::LogicalPosition, DeviceEvent, ElementState, Event, KeyboardInput, MouseButton,
@@ -42,6 +43,8 @@ where
     /// while second is the ID used by incoming events.
     connected_controllers: SmallVec<[(u32, u32); 8]>,
     mouse_position: Option<(f64, f64)>,
+    /// The device and the bindings of each player.
+    players: HashMap<u32, (PlayerDevice, Bindings<AX, AC>)>,
 }
 
 impl<AX, AC> InputHandler<AX, AC>
@@ -236,6 +239,15 @@ where
                     }
                     self.mouse_position = Some((x * hidpi, y * hidpi));
                 }
+                WindowEvent::HoveredFile(ref path) => {
+                    event_handler.single_write(FileHovered(path.clone()));
+                }
+                WindowEvent::DroppedFile(ref path) => {
+                    event_handler.single_write(FileDropped(path.clone()));
+                }
+                WindowEvent::HoveredFileCancelled => {
+                    event_handler.single_write(FileHoverCancelled);
+                }
                 WindowEvent::Focused(false) => {
                     self.pressed_keys.clear();
                     self.pressed_mouse_buttons.clear();
@@ -488,10 +500,123 @@ where
     where
         AX: Borrow<T>,
     {
-        self.bindings.axes.get(id).map(|a| match *a {
+        self.bindings
+            .axes
+            .get(id)
+            .map(|axis| self.resolve_axis(axis, None))
+    }
+
+    /// Returns true if any of the actions bindings is down.
+    ///
+    /// If a binding represents a combination of buttons, all of them need to be down.
+    pub fn action_is_down<T: Hash + Eq + ?Sized>(&self, action: &T) -> Option<bool>
+    where
+        AC: Borrow<T>,
+    {
+        self.bindings
+            .actions
+            .get(action)
+            .map(|combinations| self.resolve_action(combinations, None))
+    }
+
+    /// Assigns a device and bindings to a player of a local multiplayer game.
+    ///
+    /// The bindings of the player are only evaluated on its device: keyboard and mouse buttons
+    /// for `PlayerDevice::KeyboardMouse`, and the buttons and axes of the controller for
+    /// `PlayerDevice::Controller`. The controller ids in the bindings are replaced by the one of
+    /// the device, so all controller players can use the same bindings.
+    ///
+    /// Player bindings are independent of `bindings`, so they don't generate `InputEvent`s for
+    /// their actions.
+    pub fn set_player(&mut self, player: u32, device: PlayerDevice, bindings: Bindings<AX, AC>) {
+        self.players.insert(player, (device, bindings));
+    }
+
+    /// Removes the device and bindings of the player.
+    pub fn remove_player(&mut self, player: u32) -> Option<(PlayerDevice, Bindings<AX, AC>)> {
+        self.players.remove(&player)
+    }
+
+    /// Returns an iterator over all players with a device.
+    pub fn players(&self) -> impl Iterator<Item = u32> + '_ {
+        self.players.keys().cloned()
+    }
+
+    /// Returns the device of the player.
+    pub fn player_device(&self, player: u32) -> Option<PlayerDevice> {
+        self.players.get(&player).map(|&(device, _)| device)
+    }
+
+    /// Returns the player using the device, e.g. to assign a newly connected controller to the
+    /// next free player.
+    pub fn device_player(&self, device: PlayerDevice) -> Option<u32> {
+        self.players
+            .iter()
+            .find(|&(_, &(d, _))| d == device)
+            .map(|(&player, _)| player)
+    }
+
+    /// Returns the bindings of the player.
+    pub fn player_bindings(&self, player: u32) -> Option<&Bindings<AX, AC>> {
+        self.players.get(&player).map(|(_, bindings)| bindings)
+    }
+
+    /// Returns the value of an axis of the player, if the player or the axis doesn't exist this
+    /// returns None.
+    pub fn player_axis_value<T: Hash + Eq + ?Sized>(&self, player: u32, id: &T) -> Option<f64>
+    where
+        AX: Borrow<T>,
+    {
+        let (device, bindings) = self.players.get(&player)?;
+        bindings
+            .axes
+            .get(id)
+            .map(|axis| self.resolve_axis(axis, Some(*device)))
+    }
+
+    /// Returns true if any of the bindings of the players action is down on its device.
+    pub fn player_action_is_down<T: Hash + Eq + ?Sized>(
+        &self,
+        player: u32,
+        action: &T,
+    ) -> Option<bool>
+    where
+        AC: Borrow<T>,
+    {
+        let (device, bindings) = self.players.get(&player)?;
+        bindings
+            .actions
+            .get(action)
+            .map(|combinations| self.resolve_action(combinations, Some(*device)))
+    }
+
+    /// Checks if the button is down, on the device if there is one.
+    fn resolve_button(&self, button: Button, device: Option<PlayerDevice>) -> bool {
+        match device {
+            Some(device) => device
+                .map_button(button)
+                .map_or(false, |button| self.button_is_down(button)),
+            None => self.button_is_down(button),
+        }
+    }
+
+    fn resolve_action(
+        &self,
+        combinations: &SmallVec<[SmallVec<[Button; 2]>; 4]>,
+        device: Option<PlayerDevice>,
+    ) -> bool {
+        combinations.iter().any(|combination| {
+            combination
+                .iter()
+                .all(|button| self.resolve_button(*button, device))
+        })
+    }
+
+    fn resolve_axis(&self, axis: &Axis, device: Option<PlayerDevice>) -> f64 {
+        match *axis {
             Axis::Emulated { pos, neg, .. } => {
-                let pos = self.button_is_down(pos);
-                let neg = self.button_is_down(neg);
+                let pos = self.resolve_button(pos, device);
+                let neg = self.resolve_button(neg, device);
                 if pos == neg {
                     0.0
                 } else if pos {
@@ -506,38 +631,30 @@ where
                 invert,
                 dead_zone,
                 ..
-            } => self
-                .controller_axes
-                .iter()
-                .find(|&&(id, a, _)| id == controller_id && a == axis)
-                .map(|&(_, _, val)| if invert { -val } else { val })
-                .map(|val| {
-                    if val < -dead_zone {
-                        (val + dead_zone) / (1.0 - dead_zone)
-                    } else if val > dead_zone {
-                        (val - dead_zone) / (1.0 - dead_zone)
-                    } else {
-                        0.0
-                    }
-                })
-                .unwrap_or(0.0),
-        })
-    }
-
-    /// Returns true if any of the actions bindings is down.
-    ///
-    /// If a binding represents a combination of buttons, all of them need to be down.
-    pub fn action_is_down<T: Hash + Eq + ?Sized>(&self, action: &T) -> Option<bool>
-    where
-        AC: Borrow<T>,
-    {
-        self.bindings.actions.get(action).map(|combinations| {
-            combinations.iter().any(|combination| {
-                combination
+            } => {
+                let controller_id = match device {
+                    Some(device) => match device.controller_id() {
+                        Some(id) => id,
+                        None => return 0.0,
+                    },
+                    None => controller_id,
+                };
+                self.controller_axes
                     .iter()
-                    .all(|button| self.button_is_down(*button))
-            })
-        })
+                    .find(|&&(id, a, _)| id == controller_id && a == axis)
+                    .map(|&(_, _, val)| if invert { -val } else { val })
+                    .map(|val| {
+                        if val < -dead_zone {
+                            (val + dead_zone) / (1.0 - dead_zone)
+                        } else if val > dead_zone {
+                            (val - dead_zone) / (1.0 - dead_zone)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .unwrap_or(0.0)
+            }
+        }
     }
 
     /// Retrieve next free controller number to allocate new controller to
@@ -642,6 +759,54 @@ mod tests {
 
     const HIDPI: f64 = 1.0;
 
+    #[test]
+    fn player_bindings_use_their_device() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        let mut keyboard = Bindings::new();
+        keyboard
+            .insert_action_binding(
+                String::from("jump"),
+                [Button::Key(VirtualKeyCode::Space)].iter().cloned(),
+            )
+            .unwrap();
+        let mut controller = Bindings::new();
+        controller
+            .insert_action_binding(
+                String::from("jump"),
+                [Button::Controller(0, ControllerButton::A)].iter().cloned(),
+            )
+            .unwrap();
+        handler.set_player(0, PlayerDevice::KeyboardMouse, keyboard);
+        handler.set_player(1, PlayerDevice::Controller(0), controller.clone());
+        handler.set_player(2, PlayerDevice::Controller(1), controller);
+
+        handler.send_controller_event(
+            &ControllerEvent::ControllerConnected { which: 10 },
+            &mut events,
+        );
+        handler.send_controller_event(
+            &ControllerEvent::ControllerConnected { which: 11 },
+            &mut events,
+        );
+        handler.send_controller_event(
+            &ControllerEvent::ControllerButtonPressed {
+                which: 11,
+                button: ControllerButton::A,
+            },
+            &mut events,
+        );
+        assert_eq!(Some(false), handler.player_action_is_down(0, "jump"));
+        assert_eq!(Some(false), handler.player_action_is_down(1, "jump"));
+        assert_eq!(Some(true), handler.player_action_is_down(2, "jump"));
+        assert_eq!(None, handler.player_action_is_down(3, "jump"));
+
+        handler.send_event(&key_press(57, VirtualKeyCode::Space), &mut events, HIDPI);
+        assert_eq!(Some(true), handler.player_action_is_down(0, "jump"));
+        assert_eq!(Some(false), handler.player_action_is_down(1, "jump"));
+        assert_eq!(Some(2), handler.device_player(PlayerDevice::Controller(1)));
+    }
+
     #[test]
     fn key_action_response() {
         // Register an action triggered by a key
@@ -9,11 +9,15 @@ pub use self::{
     bindings::{BindingError, Bindings},
     bundle::{BindingsFileError, InputBundle},
     button::Button,
+    contexts::InputContexts,
     controller::{ControllerAxis, ControllerButton, ControllerEvent},
     event::InputEvent,
     input_handler::InputHandler,
+    player::PlayerDevice,
+    recording::{InputRecording, RecordedEvent, RecordedFrame},
     scroll_direction::ScrollDirection,
     system::InputSystem,
+    text_input::{TextComposition, TextInputEvent},
     util::{get_input_axis_simple, get_key, is_close_requested, is_key_down},
 };
 
@@ -25,11 +29,15 @@ mod axis;
 mod bindings;
 mod bundle;
 mod button;
+mod contexts;
 mod controller;
 mod event;
 mod input_handler;
+mod player;
+mod recording;
 mod scroll_direction;
 mod system;
+mod text_input;
 mod util;
 
 #[cfg(feature = "sdl_controller")]
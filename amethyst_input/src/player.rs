@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use super::Button;
+
+/// The input device a player of a local multiplayer game uses.
+///
+/// Set with `InputHandler::set_player`, together with the bindings of the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlayerDevice {
+    /// The keyboard and the mouse.
+    KeyboardMouse,
+    /// The controller with the given id, see `InputHandler::connected_controllers`.
+    Controller(u32),
+}
+
+impl PlayerDevice {
+    /// Returns the button as it is pressed on this device, or `None` if the device doesn't have
+    /// it.
+    ///
+    /// The controller ids in the bindings of a player are ignored, so all players using a
+    /// controller can share the same bindings.
+    pub(crate) fn map_button(self, button: Button) -> Option<Button> {
+        match (self, button) {
+            (PlayerDevice::KeyboardMouse, Button::Controller(..)) => None,
+            (PlayerDevice::KeyboardMouse, button) => Some(button),
+            (PlayerDevice::Controller(id), Button::Controller(_, button)) => {
+                Some(Button::Controller(id, button))
+            }
+            (PlayerDevice::Controller(_), _) => None,
+        }
+    }
+
+    /// Returns the id of the controller whose axes are read for this device.
+    pub(crate) fn controller_id(self) -> Option<u32> {
+        match self {
+            PlayerDevice::KeyboardMouse => None,
+            PlayerDevice::Controller(id) => Some(id),
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Recording of raw input for replays.
+
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::LogicalPosition, DeviceEvent, DeviceId, ElementState, Event, KeyboardInput,
+    ModifiersState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent, WindowId,
+};
+
+/// A raw input event which can be stored in an `InputRecording`.
+///
+/// Only the parts of the window events the `InputHandler` uses are kept, so keyboard modifiers
+/// and device ids are not recorded. Controller events of the `sdl_controller` feature are not
+/// recorded either.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    /// A keyboard key was pressed or released.
+    Key {
+        /// The scancode of the key.
+        scancode: u32,
+        /// The `VirtualKeyCode` of the key, if it has one.
+        key_code: Option<VirtualKeyCode>,
+        /// Whether the key was pressed or released.
+        state: ElementState,
+    },
+    /// A unicode character was typed.
+    Character(char),
+    /// A mouse button was pressed or released.
+    MouseButton {
+        /// The button.
+        button: MouseButton,
+        /// Whether the button was pressed or released.
+        state: ElementState,
+    },
+    /// The cursor moved to a position on the window, in logical pixels.
+    CursorMoved {
+        /// The horizontal position.
+        x: f64,
+        /// The vertical position.
+        y: f64,
+    },
+    /// The mouse device moved.
+    MouseMotion {
+        /// The horizontal movement.
+        delta_x: f64,
+        /// The vertical movement.
+        delta_y: f64,
+    },
+    /// The mouse wheel moved by lines.
+    MouseWheelLines {
+        /// The horizontal movement.
+        delta_x: f32,
+        /// The vertical movement.
+        delta_y: f32,
+    },
+    /// The mouse wheel moved by pixels.
+    MouseWheelPixels {
+        /// The horizontal movement.
+        delta_x: f64,
+        /// The vertical movement.
+        delta_y: f64,
+    },
+    /// The window gained or lost focus.
+    Focused(bool),
+}
+
+impl RecordedEvent {
+    /// Returns the recordable part of a window event, or `None` if it is not input.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match *event {
+            Event::WindowEvent { ref event, .. } => match *event {
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            scancode,
+                            virtual_keycode,
+                            state,
+                            ..
+                        },
+                    ..
+                } => Some(RecordedEvent::Key {
+                    scancode,
+                    key_code: virtual_keycode,
+                    state,
+                }),
+                WindowEvent::ReceivedCharacter(c) => Some(RecordedEvent::Character(c)),
+                WindowEvent::MouseInput { button, state, .. } => {
+                    Some(RecordedEvent::MouseButton { button, state })
+                }
+                WindowEvent::CursorMoved {
+                    position: LogicalPosition { x, y },
+                    ..
+                } => Some(RecordedEvent::CursorMoved { x, y }),
+                WindowEvent::Focused(focused) => Some(RecordedEvent::Focused(focused)),
+                _ => None,
+            },
+            Event::DeviceEvent { ref event, .. } => match *event {
+                DeviceEvent::MouseMotion {
+                    delta: (delta_x, delta_y),
+                } => Some(RecordedEvent::MouseMotion { delta_x, delta_y }),
+                DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::LineDelta(delta_x, delta_y),
+                } => Some(RecordedEvent::MouseWheelLines { delta_x, delta_y }),
+                DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::PixelDelta(LogicalPosition { x, y }),
+                } => Some(RecordedEvent::MouseWheelPixels {
+                    delta_x: x,
+                    delta_y: y,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Turns the recorded event back into a window event.
+    pub fn to_event(&self) -> Event {
+        // The ids are only compared by the receivers of the events, they never reach the
+        // platform code.
+        let window_id = unsafe { WindowId::dummy() };
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = ModifiersState::default();
+
+        let window_event = |event| Event::WindowEvent { window_id, event };
+        let device_event = |event| Event::DeviceEvent { device_id, event };
+        match *self {
+            RecordedEvent::Key {
+                scancode,
+                key_code,
+                state,
+            } => window_event(WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode,
+                    state,
+                    virtual_keycode: key_code,
+                    modifiers,
+                },
+            }),
+            RecordedEvent::Character(c) => window_event(WindowEvent::ReceivedCharacter(c)),
+            RecordedEvent::MouseButton { button, state } => window_event(WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            }),
+            RecordedEvent::CursorMoved { x, y } => window_event(WindowEvent::CursorMoved {
+                device_id,
+                position: LogicalPosition { x, y },
+                modifiers,
+            }),
+            RecordedEvent::Focused(focused) => window_event(WindowEvent::Focused(focused)),
+            RecordedEvent::MouseMotion { delta_x, delta_y } => {
+                device_event(DeviceEvent::MouseMotion {
+                    delta: (delta_x, delta_y),
+                })
+            }
+            RecordedEvent::MouseWheelLines { delta_x, delta_y } => {
+                device_event(DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::LineDelta(delta_x, delta_y),
+                })
+            }
+            RecordedEvent::MouseWheelPixels { delta_x, delta_y } => {
+                device_event(DeviceEvent::MouseWheel {
+                    delta: MouseScrollDelta::PixelDelta(LogicalPosition {
+                        x: delta_x,
+                        y: delta_y,
+                    }),
+                })
+            }
+        }
+    }
+}
+
+/// The input events of a single frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// The frame the events were received in, counted from the start of the recording.
+    pub frame: u64,
+    /// The time the frame started, in seconds since the start of the recording.
+    pub time: f64,
+    /// The events of the frame, in the order they were received.
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Raw input recorded frame by frame, e.g. for replay tests or demos.
+///
+/// Recordings are made and played back by the `InputSystem`; use `InputBundle::with_recording`
+/// and `InputBundle::with_playback`. They can be saved and loaded with `amethyst_config::Config`.
+///
+/// Playback is driven by the frame numbers, not the time stamps, so a replay with a fixed frame
+/// rate or fixed updates sees the input in the same frames as the recording.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// The frames which received input, ordered by their frame number.
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Creates an empty recording.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends an event received in `frame`, `time` seconds after the start of the recording.
+    ///
+    /// Events which are not input are ignored.
+    pub fn record(&mut self, frame: u64, time: f64, event: &Event) {
+        if let Some(event) = RecordedEvent::from_event(event) {
+            match self.frames.last_mut() {
+                Some(ref mut last) if last.frame == frame => last.events.push(event),
+                _ => self.frames.push(RecordedFrame {
+                    frame,
+                    time,
+                    events: vec![event],
+                }),
+            }
+        }
+    }
+
+    /// Returns the number of frames until the last recorded input.
+    pub fn len_frames(&self) -> u64 {
+        self.frames.last().map_or(0, |last| last.frame + 1)
+    }
+}
+
+/// Where the `InputSystem` takes its input from.
+#[derive(Debug)]
+pub(crate) enum InputSource {
+    /// The events of the window.
+    Live,
+    /// The events of the window, which are also appended to the recording.
+    Recording {
+        recording: InputRecording,
+        path: Option<std::path::PathBuf>,
+        start: Option<(u64, f64)>,
+    },
+    /// The events of a recording, the window events are ignored.
+    Playback {
+        recording: InputRecording,
+        next: usize,
+        start: Option<u64>,
+    },
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::Live
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_round_trip() {
+        let events = vec![
+            RecordedEvent::Key {
+                scancode: 104,
+                key_code: Some(VirtualKeyCode::Up),
+                state: ElementState::Pressed,
+            },
+            RecordedEvent::Character('w'),
+            RecordedEvent::MouseButton {
+                button: MouseButton::Left,
+                state: ElementState::Released,
+            },
+            RecordedEvent::CursorMoved { x: 1.0, y: 2.0 },
+            RecordedEvent::MouseMotion {
+                delta_x: 3.0,
+                delta_y: 4.0,
+            },
+            RecordedEvent::MouseWheelLines {
+                delta_x: 0.0,
+                delta_y: 1.0,
+            },
+            RecordedEvent::MouseWheelPixels {
+                delta_x: 0.0,
+                delta_y: -5.0,
+            },
+            RecordedEvent::Focused(false),
+        ];
+        for event in events {
+            assert_eq!(
+                Some(event.clone()),
+                RecordedEvent::from_event(&event.to_event())
+            );
+        }
+    }
+
+    #[test]
+    fn groups_events_by_frame() {
+        let mut recording = InputRecording::new();
+        let key = RecordedEvent::Character('a').to_event();
+        recording.record(0, 0.0, &key);
+        recording.record(0, 0.0, &key);
+        recording.record(3, 0.05, &key);
+
+        assert_eq!(2, recording.frames.len());
+        assert_eq!(2, recording.frames[0].events.len());
+        assert_eq!(3, recording.frames[1].frame);
+        assert_eq!(4, recording.len_frames());
+    }
+}
@@ -1,21 +1,35 @@
 //! Input system
 
-use std::hash::Hash;
+use std::{hash::Hash, path::PathBuf};
 
-use winit::Event;
+use log::error;
+use winit::{Event, WindowEvent};
 
+use amethyst_config::Config;
 use amethyst_core::{
     ecs::prelude::{Read, ReadExpect, Resources, System, Write},
     shrev::{EventChannel, ReaderId},
+    timing::Time,
 };
 use amethyst_renderer::ScreenDimensions;
 
-use crate::{Bindings, InputEvent, InputHandler};
+use crate::{
+    recording::{InputRecording, InputSource},
+    text_input::ReceivedText,
+    Bindings, InputContexts, InputEvent, InputHandler, TextInputEvent,
+};
 
 /// Input system
 ///
 /// Will read `winit::Event` from `EventHandler<winit::Event>`, process them with `InputHandler`,
-/// and push the results in `EventHandler<InputEvent>`.
+/// and push the results in `EventHandler<InputEvent>`. Typed text is also pushed in
+/// `EventHandler<TextInputEvent>`, with the text committed by input methods as compositions.
+///
+/// If an `InputContexts` resource exists, the bindings of the `InputHandler` are replaced with
+/// its active bindings whenever they change.
+///
+/// The system can record the raw input it receives into an `InputRecording`, or play one back
+/// instead of the window input, see `with_recording` and `with_playback`.
 pub struct InputSystem<AX, AC>
 where
     AX: Hash + Eq + Clone,
@@ -23,6 +37,9 @@ where
 {
     reader: Option<ReaderId<Event>>,
     bindings: Option<Bindings<AX, AC>>,
+    contexts_generation: Option<u64>,
+    source: InputSource,
+    received: ReceivedText,
 }
 
 impl<AX, AC> InputSystem<AX, AC>
@@ -35,6 +52,59 @@ where
         InputSystem {
             reader: None,
             bindings,
+            contexts_generation: None,
+            source: InputSource::Live,
+            received: ReceivedText::default(),
+        }
+    }
+
+    pub(crate) fn with_source(mut self, source: InputSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Records the window input, writing the recording to `path` when the system is dropped,
+    /// i.e. when the application exits.
+    ///
+    /// Without a path the recording is only available through `recording`.
+    pub fn with_recording(mut self, path: Option<PathBuf>) -> Self {
+        self.source = InputSource::Recording {
+            recording: InputRecording::new(),
+            path,
+            start: None,
+        };
+        self
+    }
+
+    /// Plays back the recording instead of the window input, starting with the first run of the
+    /// system.
+    pub fn with_playback(mut self, recording: InputRecording) -> Self {
+        self.source = InputSource::Playback {
+            recording,
+            next: 0,
+            start: None,
+        };
+        self
+    }
+
+    /// Returns the recording being made or played back.
+    pub fn recording(&self) -> Option<&InputRecording> {
+        match self.source {
+            InputSource::Live => None,
+            InputSource::Recording { ref recording, .. }
+            | InputSource::Playback { ref recording, .. } => Some(recording),
+        }
+    }
+
+    /// Returns `true` once all events of the played back recording were sent.
+    pub fn playback_finished(&self) -> bool {
+        match self.source {
+            InputSource::Playback {
+                ref recording,
+                next,
+                ..
+            } => next >= recording.frames.len(),
+            _ => false,
         }
     }
 
@@ -42,11 +112,30 @@ where
         event: &Event,
         handler: &mut InputHandler<AX, AC>,
         output: &mut EventChannel<InputEvent<AC>>,
+        text: &mut EventChannel<TextInputEvent>,
+        received: &mut ReceivedText,
         hidpi: f64,
     ) where
         AX: Hash + Eq + Clone + Send + Sync + 'static,
         AC: Hash + Eq + Clone + Send + Sync + 'static,
     {
+        match *event {
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } => {
+                if !c.is_control() {
+                    received.push(c);
+                }
+            }
+            // Typed characters follow their key press, the ones committed by an input method
+            // arrive without key presses between them.
+            Event::WindowEvent {
+                event: WindowEvent::KeyboardInput { .. },
+                ..
+            } => received.flush(text),
+            _ => {}
+        }
         handler.send_event(event, output, hidpi);
     }
 }
@@ -60,23 +149,91 @@ where
         Read<'a, EventChannel<Event>>,
         Write<'a, InputHandler<AX, AC>>,
         Write<'a, EventChannel<InputEvent<AC>>>,
+        Write<'a, EventChannel<TextInputEvent>>,
         ReadExpect<'a, ScreenDimensions>,
+        Option<Read<'a, InputContexts<AX, AC>>>,
+        Read<'a, Time>,
     );
 
-    fn run(&mut self, (input, mut handler, mut output, screen_dimensions): Self::SystemData) {
-        for event in input.read(
-            &mut self
-                .reader
+    fn run(
+        &mut self,
+        (input, mut handler, mut output, mut text, screen_dimensions, contexts, time): Self::SystemData,
+    ) {
+        if let Some(contexts) = contexts {
+            if self.contexts_generation != Some(contexts.generation()) {
+                handler.bindings = contexts.active_bindings().clone();
+                self.contexts_generation = Some(contexts.generation());
+            }
+        }
+
+        let hidpi = screen_dimensions.hidpi_factor();
+        let events = input.read(
+            self.reader
                 .as_mut()
                 .expect("`InputSystem::setup` was not called before `InputSystem::run`"),
-        ) {
-            Self::process_event(
-                event,
-                &mut *handler,
-                &mut *output,
-                screen_dimensions.hidpi_factor(),
-            );
+        );
+        let received = &mut self.received;
+        match self.source {
+            InputSource::Live => {
+                for event in events {
+                    Self::process_event(
+                        event,
+                        &mut *handler,
+                        &mut *output,
+                        &mut *text,
+                        received,
+                        hidpi,
+                    );
+                }
+            }
+            InputSource::Recording {
+                ref mut recording,
+                ref mut start,
+                ..
+            } => {
+                let (start_frame, start_time) =
+                    *start.get_or_insert((time.frame_number(), time.absolute_time_seconds()));
+                let frame = time.frame_number() - start_frame;
+                let seconds = time.absolute_time_seconds() - start_time;
+                for event in events {
+                    recording.record(frame, seconds, event);
+                    Self::process_event(
+                        event,
+                        &mut *handler,
+                        &mut *output,
+                        &mut *text,
+                        received,
+                        hidpi,
+                    );
+                }
+            }
+            InputSource::Playback {
+                ref recording,
+                ref mut next,
+                ref mut start,
+            } => {
+                // Drain the window events, so they aren't processed when the playback is over.
+                events.for_each(drop);
+                let frame = time.frame_number() - *start.get_or_insert(time.frame_number());
+                while let Some(recorded) = recording.frames.get(*next) {
+                    if recorded.frame > frame {
+                        break;
+                    }
+                    for event in &recorded.events {
+                        Self::process_event(
+                            &event.to_event(),
+                            &mut *handler,
+                            &mut *output,
+                            &mut *text,
+                            received,
+                            hidpi,
+                        );
+                    }
+                    *next += 1;
+                }
+            }
         }
+        received.flush(&mut *text);
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -88,3 +245,22 @@ where
         }
     }
 }
+
+impl<AX, AC> Drop for InputSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    fn drop(&mut self) {
+        if let InputSource::Recording {
+            ref recording,
+            path: Some(ref path),
+            ..
+        } = self.source
+        {
+            if let Err(err) = recording.write(path) {
+                error!("Failed to write input recording to {:?}: {}", path, err);
+            }
+        }
+    }
+}
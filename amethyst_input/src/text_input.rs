@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::shrev::EventChannel;
+
+/// Text typed by the user, written to `EventChannel<TextInputEvent>` by the `InputSystem`.
+///
+/// Text widgets should read these instead of interpreting key presses, so input methods (IME)
+/// for languages which compose characters from several key presses work.
+///
+/// The window backend only reports the text committed by an input method, as characters without
+/// key presses between them. The `InputSystem` sends such a run of several characters as a
+/// `CompositionStart` followed by a `CompositionEnd` with the text, and other characters as
+/// `Character` events. The backend doesn't report the text while it is composed, so there are no
+/// `CompositionUpdate` events yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextInputEvent {
+    /// A character was typed, or committed by an input method.
+    ///
+    /// Control characters like backspace are not sent, they're handled as key presses.
+    Character(char),
+    /// An input method started composing text.
+    CompositionStart,
+    /// The text being composed changed.
+    CompositionUpdate {
+        /// The text composed so far, which is not part of the edited text yet.
+        text: String,
+        /// The selected range in `text` in bytes, if any.
+        selection: Option<(usize, usize)>,
+    },
+    /// The composition ended, either by committing its text, or by cancelling it when `None`.
+    CompositionEnd(Option<String>),
+}
+
+impl TextInputEvent {
+    /// Returns the text this event adds to the edited text.
+    pub fn committed_text(&self) -> Option<String> {
+        match self {
+            TextInputEvent::Character(c) => Some(c.to_string()),
+            TextInputEvent::CompositionEnd(text) => text.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// The text being composed by an input method, tracked from `TextInputEvent`s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextComposition {
+    composing: bool,
+    text: String,
+    selection: Option<(usize, usize)>,
+}
+
+impl TextComposition {
+    /// Creates the state of a text field without composition.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Updates the composition with the event, returning the text it commits.
+    pub fn apply(&mut self, event: &TextInputEvent) -> Option<String> {
+        match event {
+            TextInputEvent::CompositionStart => {
+                self.composing = true;
+                self.text.clear();
+                self.selection = None;
+            }
+            TextInputEvent::CompositionUpdate { text, selection } => {
+                self.composing = true;
+                self.text.clone_from(text);
+                self.selection = *selection;
+            }
+            TextInputEvent::CompositionEnd(_) => {
+                self.composing = false;
+                self.text.clear();
+                self.selection = None;
+            }
+            TextInputEvent::Character(_) => {}
+        }
+        event.committed_text()
+    }
+
+    /// Returns `true` while an input method composes text.
+    pub fn is_composing(&self) -> bool {
+        self.composing
+    }
+
+    /// Returns the text being composed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the selected range of the composed text in bytes.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.selection
+    }
+}
+
+/// Collects the characters received since the last key press, to tell the text committed by an
+/// input method from typed characters.
+#[derive(Debug, Default)]
+pub(crate) struct ReceivedText {
+    chars: String,
+}
+
+impl ReceivedText {
+    /// Adds a received character.
+    pub(crate) fn push(&mut self, c: char) {
+        self.chars.push(c);
+    }
+
+    /// Sends the collected characters, called on key presses and at the end of a frame.
+    pub(crate) fn flush(&mut self, channel: &mut EventChannel<TextInputEvent>) {
+        let mut chars = self.chars.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => {}
+            (Some(c), None) => channel.single_write(TextInputEvent::Character(c)),
+            (Some(_), Some(_)) => {
+                channel.single_write(TextInputEvent::CompositionStart);
+                channel.single_write(TextInputEvent::CompositionEnd(Some(self.chars.clone())));
+            }
+        }
+        self.chars.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_of_characters_are_compositions() {
+        let mut channel = EventChannel::new();
+        let mut reader = channel.register_reader();
+        let mut received = ReceivedText::default();
+        received.push('a');
+        received.flush(&mut channel);
+        received.push('日');
+        received.push('本');
+        received.flush(&mut channel);
+        received.flush(&mut channel);
+        assert_eq!(
+            vec![
+                TextInputEvent::Character('a'),
+                TextInputEvent::CompositionStart,
+                TextInputEvent::CompositionEnd(Some("日本".to_string())),
+            ],
+            channel.read(&mut reader).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn composition_commits_text() {
+        let mut composition = TextComposition::new();
+        assert_eq!(
+            Some("a".to_string()),
+            composition.apply(&TextInputEvent::Character('a'))
+        );
+        assert_eq!(None, composition.apply(&TextInputEvent::CompositionStart));
+        assert_eq!(
+            None,
+            composition.apply(&TextInputEvent::CompositionUpdate {
+                text: "にほ".to_string(),
+                selection: Some((3, 6)),
+            })
+        );
+        assert!(composition.is_composing());
+        assert_eq!("にほ", composition.text());
+        assert_eq!(
+            Some("日本".to_string()),
+            composition.apply(&TextInputEvent::CompositionEnd(Some("日本".to_string())))
+        );
+        assert!(!composition.is_composing());
+        assert_eq!("", composition.text());
+    }
+}
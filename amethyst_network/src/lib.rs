@@ -1,4 +1,7 @@
 //! Provides a client-server networking architecture to amethyst.
+//!
+//! The `replication` module builds on it to replicate components from a server to its clients, and
+//! the `lockstep` module to run deterministic lockstep simulations.
 
 #![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
 
@@ -22,8 +25,10 @@ use serde::{de::DeserializeOwned, Serialize};
 mod bundle;
 mod connection;
 mod error;
+pub mod lockstep;
 mod net_event;
 mod network_socket;
+pub mod replication;
 mod server;
 mod test;
 
@@ -0,0 +1,81 @@
+//! The bundle of the lockstep systems.
+
+use std::marker::PhantomData;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    ecs::prelude::Component,
+};
+use amethyst_error::Error;
+
+use super::{ChecksumSystem, LockstepConfig, LockstepSystem};
+
+/// Adds the checksum system of a component type to the dispatcher with a name.
+type AddChecksum = fn(&mut BundleBuilder<'_, '_, '_>, &str, &[&str]);
+
+fn add_checksum<T: Component + Serialize>(
+    builder: &mut BundleBuilder<'_, '_, '_>,
+    name: &str,
+    dep: &[&str],
+) {
+    builder.add(ChecksumSystem::<T>::new(), name, dep);
+}
+
+/// Adds the `LockstepSystem` exchanging commands of type `C`, as "lockstep_system", and the
+/// `ChecksumSystem`s before it.
+///
+/// The systems of the simulation depend on the "lockstep_system".
+pub struct LockstepBundle<'a, C> {
+    config: LockstepConfig,
+    checksums: Vec<AddChecksum>,
+    dep: &'a [&'a str],
+    _marker: PhantomData<C>,
+}
+
+impl<'a, C> LockstepBundle<'a, C> {
+    /// Creates a new `LockstepBundle`.
+    pub fn new(config: LockstepConfig) -> Self {
+        LockstepBundle {
+            config,
+            checksums: Vec::new(),
+            dep: &[],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds the components of type `T` to the checksum of the simulation.
+    pub fn with_checksum<T: Component + Serialize>(mut self) -> Self {
+        self.checksums.push(add_checksum::<T>);
+        self
+    }
+
+    /// Sets the dependencies of the lockstep systems.
+    pub fn with_dep(mut self, dep: &'a [&'a str]) -> Self {
+        self.dep = dep;
+        self
+    }
+}
+
+impl<'a, 'b, 'c, C> SystemBundle<'a, 'b> for LockstepBundle<'c, C>
+where
+    C: Clone + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        let mut names = Vec::new();
+        for (i, add) in self.checksums.into_iter().enumerate() {
+            let name = format!("checksum_system_{}", i);
+            add(builder, &name, self.dep);
+            names.push(name);
+        }
+        let mut deps = names.iter().map(String::as_str).collect::<Vec<_>>();
+        deps.extend(self.dep);
+        builder.add(
+            LockstepSystem::<C>::new(self.config),
+            "lockstep_system",
+            &deps,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! The checksum of the simulation, compared between the players to detect desyncs.
+
+use std::marker::PhantomData;
+
+use log::error;
+use serde::Serialize;
+
+use amethyst_core::ecs::prelude::{Component, Entities, Join, Read, ReadStorage, System, Write};
+
+use super::LockstepPhase;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The checksum of the state of the simulation after a tick, added to by the `ChecksumSystem`s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LockstepChecksum {
+    tick: u32,
+    value: u64,
+}
+
+impl LockstepChecksum {
+    /// Returns the tick of the checksum.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Returns the checksum of the state hashed so far.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Adds the hash of some state to the checksum. The order the states are added in does not
+    /// change the checksum, so systems may add to it in any order.
+    pub fn add(&mut self, bytes: &[u8]) {
+        self.value = self.value.wrapping_add(fnv1a(FNV_OFFSET, bytes));
+    }
+
+    pub(crate) fn reset(&mut self, tick: u32) {
+        self.tick = tick;
+        self.value = 0;
+    }
+}
+
+/// Adds the components of type `T` of all entities, serialized, to the `LockstepChecksum`.
+///
+/// Runs at the start of the frame after each tick, before the "lockstep_system" sends the
+/// checksum. Added by the `LockstepBundle` with `with_checksum`.
+#[derive(Debug)]
+pub struct ChecksumSystem<T> {
+    bytes: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> ChecksumSystem<T> {
+    /// Creates a new `ChecksumSystem`.
+    pub fn new() -> Self {
+        ChecksumSystem {
+            bytes: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for ChecksumSystem<T> {
+    fn default() -> Self {
+        ChecksumSystem::new()
+    }
+}
+
+impl<'a, T: Component + Serialize> System<'a> for ChecksumSystem<T> {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, LockstepPhase>,
+        ReadStorage<'a, T>,
+        Write<'a, LockstepChecksum>,
+    );
+
+    fn run(&mut self, (entities, phase, components, mut checksum): Self::SystemData) {
+        if *phase != LockstepPhase::Tick {
+            return;
+        }
+        self.bytes.clear();
+        for (entity, component) in (&*entities, &components).join() {
+            let serialized = bincode::serialize_into(&mut self.bytes, &entity.id())
+                .and_then(|_| bincode::serialize_into(&mut self.bytes, component));
+            if let Err(e) = serialized {
+                error!("Failed to serialize a component for the checksum: {}", e);
+            }
+        }
+        checksum.add(&self.bytes);
+    }
+}
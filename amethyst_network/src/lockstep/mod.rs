@@ -0,0 +1,254 @@
+//! Deterministic lockstep simulation, for games like real-time strategy games where each player
+//! runs the whole simulation and only the commands of the players are exchanged.
+//!
+//! The simulation advances in fixed ticks. The commands a player issues on a tick are sent to
+//! the other players and executed by everyone a few ticks later, the input delay, and a tick
+//! only runs once the commands of all players for it arrived. As long as every player starts
+//! from the same state and the simulation is deterministic, they all stay in sync. Every few
+//! ticks the players exchange a checksum of the simulated components to detect when they do not.
+//!
+//! For the simulation to be deterministic, its systems must:
+//!
+//! * run only on tick frames, made `pausable(LockstepPhase::Tick)` and depending on the
+//!   "lockstep_system";
+//! * change the world only from the `Lockstep::commands`, never from local input;
+//! * use `Lockstep::delta_seconds` instead of `Time`, and the `LockstepRng` instead of other
+//!   random numbers;
+//! * not depend on the iteration order of `HashMap`s or `HashSet`s, or on the order of the
+//!   entities created before the simulation started.
+//!
+//! Floating point math is only deterministic between builds of the game for the same platform.
+//!
+//! ```rust,ignore
+//! let game_data = GameDataBuilder::default()
+//!     .with_bundle(NetworkBundle::<LockstepMessage<Command>>::new(addr))?
+//!     .with_bundle(
+//!         LockstepBundle::<Command>::new(LockstepConfig::new(2, local_player))
+//!             .with_checksum::<Unit>(),
+//!     )?
+//!     .with(
+//!         MoveUnitsSystem.pausable(LockstepPhase::Tick),
+//!         "move_units",
+//!         &["lockstep_system"],
+//!     );
+//! ```
+
+mod bundle;
+mod checksum;
+mod system;
+
+pub use self::{
+    bundle::LockstepBundle,
+    checksum::{ChecksumSystem, LockstepChecksum},
+    system::LockstepSystem,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Identifies a player of a lockstep game, from 0 to the number of players.
+pub type PlayerId = u16;
+
+/// The settings of a lockstep game, the same for all players except for `local_player`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockstepConfig {
+    /// The number of players, each with a `NetConnection` to all the others.
+    pub players: u16,
+    /// The player of this game.
+    pub local_player: PlayerId,
+    /// The number of ticks per second.
+    pub tick_rate: f32,
+    /// How many ticks after being issued the commands are executed. It has to cover the latency
+    /// between the players, or the simulation waits for their commands.
+    pub input_delay: u32,
+    /// How many ticks apart the checksums of the simulation are compared.
+    pub checksum_interval: u32,
+    /// The seed of the `LockstepRng`.
+    pub seed: u64,
+}
+
+impl LockstepConfig {
+    /// Creates the settings of a game of `players` players, with 10 ticks per second, an input
+    /// delay of 2 ticks and a checksum every 10 ticks.
+    pub fn new(players: u16, local_player: PlayerId) -> Self {
+        LockstepConfig {
+            players,
+            local_player,
+            tick_rate: 10.0,
+            input_delay: 2,
+            checksum_interval: 10,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of ticks per second.
+    pub fn with_tick_rate(mut self, tick_rate: f32) -> Self {
+        self.tick_rate = tick_rate;
+        self
+    }
+
+    /// Sets how many ticks after being issued the commands are executed.
+    pub fn with_input_delay(mut self, input_delay: u32) -> Self {
+        self.input_delay = input_delay;
+        self
+    }
+
+    /// Sets how many ticks apart the checksums are compared.
+    pub fn with_checksum_interval(mut self, checksum_interval: u32) -> Self {
+        self.checksum_interval = checksum_interval;
+        self
+    }
+
+    /// Sets the seed of the `LockstepRng`, which all players have to agree on.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Whether the simulation runs a tick on this frame, set by the `LockstepSystem`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockstepPhase {
+    /// The simulation does not advance on this frame.
+    Waiting,
+    /// The simulation runs the tick of `Lockstep::tick` on this frame.
+    Tick,
+}
+
+impl Default for LockstepPhase {
+    fn default() -> Self {
+        LockstepPhase::Waiting
+    }
+}
+
+/// The commands of the players executed on the current tick, and the commands issued by the
+/// local player.
+#[derive(Debug)]
+pub struct Lockstep<C> {
+    pub(crate) tick: u32,
+    pub(crate) delta_seconds: f32,
+    pub(crate) stalled: bool,
+    pub(crate) commands: Vec<(PlayerId, C)>,
+    pub(crate) issued: Vec<C>,
+}
+
+impl<C> Default for Lockstep<C> {
+    fn default() -> Self {
+        Lockstep {
+            tick: 0,
+            delta_seconds: 0.0,
+            stalled: false,
+            commands: Vec::new(),
+            issued: Vec::new(),
+        }
+    }
+}
+
+impl<C> Lockstep<C> {
+    /// Returns the latest tick of the simulation.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Returns the simulated time of a tick in seconds, for the simulation to use instead of
+    /// `Time`.
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    /// Returns whether the simulation is behind, waiting for the commands of other players.
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Returns the commands of all players to execute on the current tick, in the order they
+    /// were issued by each player, and ordered by player.
+    pub fn commands(&self) -> &[(PlayerId, C)] {
+        &self.commands
+    }
+
+    /// Issues a command of the local player, executed by all players after the input delay.
+    pub fn issue(&mut self, command: C) {
+        self.issued.push(command);
+    }
+}
+
+/// A random number generator giving the same numbers to all players, reseeded on each tick.
+///
+/// Only the simulation may use it, on tick frames, so all players draw the same numbers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LockstepRng {
+    state: u64,
+}
+
+impl LockstepRng {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        LockstepRng { state: seed }
+    }
+
+    pub(crate) fn for_tick(seed: u64, tick: u32) -> Self {
+        let mut rng = LockstepRng::new(seed ^ u64::from(tick));
+        LockstepRng::new(rng.next_u64())
+    }
+
+    /// Returns a random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a random number between 0 inclusive and 1 exclusive.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns a random number between `low` inclusive and `high` exclusive.
+    pub fn range(&mut self, low: i32, high: i32) -> i32 {
+        let span = (i64::from(high) - i64::from(low)).max(1) as u64;
+        (i64::from(low) + (self.next_u64() % span) as i64) as i32
+    }
+}
+
+/// The messages exchanged by the players, the event type of the `NetConnection`s used.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LockstepMessage<C> {
+    /// The commands of a player to execute on a tick.
+    Commands {
+        /// The tick to execute the commands on.
+        tick: u32,
+        /// The player issuing the commands.
+        player: PlayerId,
+        /// The commands, possibly none.
+        commands: Vec<C>,
+    },
+    /// The checksum of the simulation of a player after a tick.
+    Checksum {
+        /// The tick of the checksum.
+        tick: u32,
+        /// The player sending the checksum.
+        player: PlayerId,
+        /// The checksum.
+        checksum: u64,
+    },
+}
+
+/// Events of the lockstep simulation, written to an `EventChannel<LockstepEvent>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LockstepEvent {
+    /// The simulation of a player went out of sync with the local one, as of a tick.
+    Desync {
+        /// The tick whose checksums differ.
+        tick: u32,
+        /// The player whose checksum differs.
+        player: PlayerId,
+    },
+}
@@ -0,0 +1,300 @@
+//! The system exchanging the commands and checksums, and advancing the ticks.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    mem,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use shrev::{EventChannel, ReaderId};
+
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity, Join, Read, System, Write, WriteStorage},
+    Time,
+};
+
+use super::{
+    Lockstep, LockstepChecksum, LockstepConfig, LockstepEvent, LockstepMessage, LockstepPhase,
+    LockstepRng, PlayerId,
+};
+use crate::{ConnectionState, NetConnection, NetEvent, NetPacket};
+
+/// How many of the local checksums are kept to compare with the late ones of other players.
+const CHECKSUM_HISTORY: usize = 64;
+
+/// Exchanges the commands and checksums with the other players, and starts a tick once its
+/// interval passed and the commands of all players for it arrived.
+///
+/// Every `NetConnection::<LockstepMessage<C>>` is another player. The commands are sent reliably
+/// and in order, and the desyncs found written as `LockstepEvent`s.
+///
+/// Added by the `LockstepBundle`, as "lockstep_system".
+pub struct LockstepSystem<C> {
+    config: LockstepConfig,
+    accumulator: f32,
+    next_tick: u32,
+    commands: BTreeMap<u32, BTreeMap<PlayerId, Vec<C>>>,
+    checksums: VecDeque<(u32, u64)>,
+    remote_checksums: Vec<(u32, PlayerId, u64)>,
+    readers: HashMap<Entity, ReaderId<NetEvent<LockstepMessage<C>>>>,
+}
+
+impl<C> LockstepSystem<C> {
+    /// Creates a new `LockstepSystem`.
+    pub fn new(config: LockstepConfig) -> Self {
+        LockstepSystem {
+            config,
+            accumulator: 0.0,
+            next_tick: 0,
+            commands: BTreeMap::new(),
+            checksums: VecDeque::new(),
+            remote_checksums: Vec::new(),
+            readers: HashMap::new(),
+        }
+    }
+
+    /// Returns whether the commands of all players for `tick` are there. There are none for the
+    /// ticks before the input delay.
+    fn is_complete(&self, tick: u32) -> bool {
+        tick < self.config.input_delay
+            || self.commands.get(&tick).map_or(false, |commands| {
+                commands.len() >= usize::from(self.config.players)
+            })
+    }
+}
+
+impl<'a, C> System<'a> for LockstepSystem<C>
+where
+    C: Clone + PartialEq + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Write<'a, LockstepChecksum>,
+        WriteStorage<'a, NetConnection<LockstepMessage<C>>>,
+        Write<'a, Lockstep<C>>,
+        Write<'a, LockstepPhase>,
+        Write<'a, LockstepRng>,
+        Write<'a, EventChannel<LockstepEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            mut checksum,
+            mut connections,
+            mut lockstep,
+            mut phase,
+            mut rng,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        let mut outgoing = Vec::new();
+
+        self.readers
+            .retain(|entity, _| connections.get(*entity).is_some());
+        for (entity, connection) in (&*entities, &mut connections).join() {
+            let reader = self
+                .readers
+                .entry(entity)
+                .or_insert_with(|| connection.receive_buffer.register_reader());
+            for event in connection.receive_buffer.read(reader) {
+                let message = match event {
+                    NetEvent::Packet(packet) => packet.content(),
+                    _ => continue,
+                };
+                match message {
+                    LockstepMessage::Commands {
+                        tick,
+                        player,
+                        commands,
+                    } => {
+                        if *tick >= self.next_tick {
+                            self.commands
+                                .entry(*tick)
+                                .or_insert_with(BTreeMap::new)
+                                .insert(*player, commands.clone());
+                        }
+                    }
+                    LockstepMessage::Checksum {
+                        tick,
+                        player,
+                        checksum,
+                    } => self.remote_checksums.push((*tick, *player, *checksum)),
+                }
+            }
+        }
+
+        // The checksum systems hashed the state after the tick of the previous frame.
+        if *phase == LockstepPhase::Tick {
+            self.checksums.push_back((lockstep.tick, checksum.value()));
+            while self.checksums.len() > CHECKSUM_HISTORY {
+                self.checksums.pop_front();
+            }
+            if lockstep.tick % self.config.checksum_interval.max(1) == 0 {
+                outgoing.push(LockstepMessage::Checksum {
+                    tick: lockstep.tick,
+                    player: self.config.local_player,
+                    checksum: checksum.value(),
+                });
+            }
+        }
+        let checksums = &self.checksums;
+        let oldest = checksums.front().map_or(0, |&(tick, _)| tick);
+        let next_tick = self.next_tick;
+        self.remote_checksums.retain(|&(tick, player, remote)| {
+            match checksums.iter().find(|&&(local, _)| local == tick) {
+                Some(&(_, local)) => {
+                    if local != remote {
+                        events.single_write(LockstepEvent::Desync { tick, player });
+                    }
+                    false
+                }
+                // Keep the checksums of ticks not simulated yet.
+                None => tick >= oldest && tick >= next_tick,
+            }
+        });
+
+        let interval = 1.0 / self.config.tick_rate;
+        self.accumulator += time.delta_real_seconds();
+        *phase = LockstepPhase::Waiting;
+        lockstep.stalled = false;
+        if self.accumulator >= interval {
+            let tick = self.next_tick;
+            if self.is_complete(tick) {
+                // Ticks missed by a slow frame are caught up with over the next frames.
+                self.accumulator = (self.accumulator - interval).min(interval * 4.0);
+                self.next_tick += 1;
+
+                lockstep.tick = tick;
+                lockstep.delta_seconds = interval;
+                lockstep.commands = self
+                    .commands
+                    .remove(&tick)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flat_map(|(player, commands)| {
+                        commands.into_iter().map(move |command| (player, command))
+                    })
+                    .collect();
+                *rng = LockstepRng::for_tick(self.config.seed, tick);
+                checksum.reset(tick);
+                *phase = LockstepPhase::Tick;
+
+                let scheduled = tick + self.config.input_delay;
+                let issued = mem::replace(&mut lockstep.issued, Vec::new());
+                self.commands
+                    .entry(scheduled)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(self.config.local_player, issued.clone());
+                outgoing.push(LockstepMessage::Commands {
+                    tick: scheduled,
+                    player: self.config.local_player,
+                    commands: issued,
+                });
+            } else {
+                lockstep.stalled = true;
+                self.accumulator = interval;
+            }
+        }
+
+        for connection in (&mut connections).join() {
+            if connection.state == ConnectionState::Disconnected {
+                continue;
+            }
+            for message in &outgoing {
+                connection
+                    .send_buffer
+                    .single_write(NetEvent::Packet(NetPacket::reliable_ordered(
+                        message.clone(),
+                        None,
+                    )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::ecs::prelude::{Builder, RunNow, World};
+
+    fn frame(world: &mut World, system: &mut LockstepSystem<u32>) -> LockstepPhase {
+        world.write_resource::<Time>().set_delta_seconds(0.1);
+        system.run_now(&world.res);
+        *world.read_resource::<LockstepPhase>()
+    }
+
+    #[test]
+    fn ticks_wait_for_all_players() {
+        let mut world = World::new();
+        let mut system = LockstepSystem::<u32>::new(
+            LockstepConfig::new(2, 0)
+                .with_input_delay(1)
+                .with_checksum_interval(1),
+        );
+        System::setup(&mut system, &mut world.res);
+        let remote = world
+            .create_entity()
+            .with(NetConnection::<LockstepMessage<u32>>::new(
+                "127.0.0.1:21300".parse().unwrap(),
+            ))
+            .build();
+        let mut desyncs = world
+            .write_resource::<EventChannel<LockstepEvent>>()
+            .register_reader();
+
+        // The tick before the input delay has no commands.
+        world.write_resource::<Lockstep<u32>>().issue(7);
+        assert_eq!(frame(&mut world, &mut system), LockstepPhase::Tick);
+        assert_eq!(world.read_resource::<Lockstep<u32>>().tick(), 0);
+
+        // Tick 1 waits for the commands of the other player.
+        assert_eq!(frame(&mut world, &mut system), LockstepPhase::Waiting);
+        assert!(world.read_resource::<Lockstep<u32>>().is_stalled());
+
+        {
+            let mut connections = world.write_storage::<NetConnection<LockstepMessage<u32>>>();
+            let buffer = &mut connections.get_mut(remote).unwrap().receive_buffer;
+            for message in vec![
+                LockstepMessage::Commands {
+                    tick: 1,
+                    player: 1,
+                    commands: vec![3],
+                },
+                // The checksum of the tick 0 differs from the local one.
+                LockstepMessage::Checksum {
+                    tick: 0,
+                    player: 1,
+                    checksum: 1,
+                },
+            ] {
+                buffer.single_write(NetEvent::Packet(NetPacket::reliable_ordered(message, None)));
+            }
+        }
+        assert_eq!(frame(&mut world, &mut system), LockstepPhase::Tick);
+        let lockstep = world.read_resource::<Lockstep<u32>>();
+        assert_eq!(lockstep.tick(), 1);
+        assert_eq!(lockstep.commands(), &[(0, 7), (1, 3)]);
+        assert_eq!(
+            world
+                .read_resource::<EventChannel<LockstepEvent>>()
+                .read(&mut desyncs)
+                .collect::<Vec<_>>(),
+            vec![&LockstepEvent::Desync { tick: 0, player: 1 }]
+        );
+    }
+
+    #[test]
+    fn rng_is_the_same_for_a_tick() {
+        let mut a = LockstepRng::for_tick(5, 10);
+        let mut b = LockstepRng::for_tick(5, 10);
+        let a = (0..8).map(|_| a.range(-3, 3)).collect::<Vec<_>>();
+        assert_eq!(a, (0..8).map(|_| b.range(-3, 3)).collect::<Vec<_>>());
+        assert!(a.iter().all(|n| *n >= -3 && *n < 3));
+        assert_ne!(LockstepRng::for_tick(5, 11), LockstepRng::for_tick(5, 10));
+    }
+}
@@ -0,0 +1,140 @@
+//! The bundle of the replication systems.
+
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    Transform,
+};
+use amethyst_error::Error;
+
+use super::{
+    ApplyComponentSystem, Replicate, ReplicateComponentSystem, ReplicationClientSystem,
+    ReplicationServerSystem, ReplicationTickSystem, TransformInterpolationSystem,
+};
+
+/// Adds the system of a replicated type to the dispatcher, returning its name.
+type AddComponent = fn(&mut BundleBuilder<'_, '_, '_>, bool) -> String;
+
+fn add_component<C: Replicate>(builder: &mut BundleBuilder<'_, '_, '_>, server: bool) -> String {
+    if server {
+        let name = format!("replicate_component_system_{}", C::KIND);
+        builder.add(
+            ReplicateComponentSystem::<C>::new(),
+            &name,
+            &["replication_tick_system"],
+        );
+        name
+    } else {
+        let name = format!("apply_component_system_{}", C::KIND);
+        builder.add(
+            ApplyComponentSystem::<C>::new(),
+            &name,
+            &["replication_client_system"],
+        );
+        name
+    }
+}
+
+/// Adds the systems replicating components, on the server or on a client.
+///
+/// Both sides list the same replicated types. A `NetworkBundle::<ReplicationMessage>` carries
+/// the messages.
+pub struct ReplicationBundle<'a> {
+    server: bool,
+    tick_rate: f32,
+    interpolation_delay: f32,
+    transforms: bool,
+    components: Vec<AddComponent>,
+    dep: &'a [&'a str],
+}
+
+impl<'a> ReplicationBundle<'a> {
+    /// Creates the bundle of a server taking `tick_rate` snapshots per second.
+    pub fn server(tick_rate: f32) -> Self {
+        ReplicationBundle::new(true, tick_rate)
+    }
+
+    /// Creates the bundle of a client, for a server taking `tick_rate` snapshots per second.
+    pub fn client(tick_rate: f32) -> Self {
+        ReplicationBundle::new(false, tick_rate)
+    }
+
+    fn new(server: bool, tick_rate: f32) -> Self {
+        ReplicationBundle {
+            server,
+            tick_rate,
+            interpolation_delay: 2.0,
+            transforms: false,
+            components: Vec::new(),
+            dep: &[],
+        }
+    }
+
+    /// Replicates the components of type `C`.
+    pub fn with_component<C: Replicate>(mut self) -> Self {
+        self.components.push(add_component::<C>);
+        self
+    }
+
+    /// Replicates the `Transform`s, interpolated on the client.
+    pub fn with_transforms(mut self) -> Self {
+        self.transforms = true;
+        self
+    }
+
+    /// Sets how many ticks behind the latest snapshot the client shows the transforms, 2 by
+    /// default. Longer delays hide more lost snapshots, shorter ones show the server sooner.
+    pub fn with_interpolation_delay(mut self, ticks: f32) -> Self {
+        self.interpolation_delay = ticks;
+        self
+    }
+
+    /// Sets the dependencies of the first replication system. On the server, depending on the
+    /// "transform_system" gives the `NetInterest` up to date positions.
+    pub fn with_dep(mut self, dep: &'a [&'a str]) -> Self {
+        self.dep = dep;
+        self
+    }
+}
+
+impl<'a, 'b, 'c> SystemBundle<'a, 'b> for ReplicationBundle<'c> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        if self.server {
+            builder.add(
+                ReplicationTickSystem::new(self.tick_rate),
+                "replication_tick_system",
+                self.dep,
+            );
+            let mut names = Vec::new();
+            if self.transforms {
+                names.push(add_component::<Transform<f32>>(builder, true));
+            }
+            for add in self.components {
+                names.push(add(builder, true));
+            }
+            let mut deps = names.iter().map(String::as_str).collect::<Vec<_>>();
+            deps.push("replication_tick_system");
+            builder.add(
+                ReplicationServerSystem::new(),
+                "replication_server_system",
+                &deps,
+            );
+        } else {
+            builder.add(
+                ReplicationClientSystem::new(),
+                "replication_client_system",
+                self.dep,
+            );
+            if self.transforms {
+                builder.add(
+                    TransformInterpolationSystem::new(self.tick_rate, self.interpolation_delay),
+                    "transform_interpolation_system",
+                    &["replication_client_system"],
+                );
+            }
+            for add in self.components {
+                add(builder, false);
+            }
+        }
+        Ok(())
+    }
+}
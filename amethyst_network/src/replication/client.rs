@@ -0,0 +1,324 @@
+//! The systems receiving snapshots from the server and applying them to the local entities.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+};
+
+use log::error;
+use shrev::ReaderId;
+
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity, Join, Read, System, Write, WriteStorage},
+    Time,
+};
+
+use super::{
+    snapshot::{Snapshot, SnapshotDelta},
+    NetworkId, Replicate, ReplicationMessage, HISTORY,
+};
+use crate::{NetConnection, NetEvent, NetPacket};
+
+/// How often the client asks the server to replicate to it until it receives a snapshot, in
+/// seconds.
+const CONNECT_INTERVAL: f32 = 0.5;
+
+/// The local entities of the client replicating the entities of the server.
+#[derive(Debug, Default)]
+pub struct NetworkEntities {
+    entities: HashMap<NetworkId, Entity>,
+}
+
+impl NetworkEntities {
+    /// Returns the local entity replicating the entity of the server with `id`.
+    pub fn get(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id).cloned()
+    }
+}
+
+/// The changes of the replicated entities on this frame of the client, applied by the
+/// `ApplyComponentSystem`s.
+#[derive(Debug, Default)]
+pub struct ReplicationUpdate {
+    /// The tick of the latest snapshot received, `None` before the first one.
+    pub tick: Option<u32>,
+    pub(crate) delta: Option<SnapshotDelta>,
+}
+
+impl ReplicationUpdate {
+    /// Returns whether a newer snapshot was received on this frame.
+    pub fn is_updated(&self) -> bool {
+        self.delta.is_some()
+    }
+}
+
+/// Receives the snapshots from the server, acknowledges them, and spawns and deletes the local
+/// entities to match the latest one.
+///
+/// The server is the first `NetConnection::<ReplicationMessage>` of the client.
+///
+/// Added by the `ReplicationBundle` on the client, as "replication_client_system".
+#[derive(Default)]
+pub struct ReplicationClientSystem {
+    server: Option<(Entity, ReaderId<NetEvent<ReplicationMessage>>)>,
+    connect_timer: f32,
+    history: VecDeque<(u32, Snapshot)>,
+    applied: Snapshot,
+}
+
+impl ReplicationClientSystem {
+    /// Creates a new `ReplicationClientSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for ReplicationClientSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        WriteStorage<'a, NetConnection<ReplicationMessage>>,
+        WriteStorage<'a, NetworkId>,
+        Write<'a, NetworkEntities>,
+        Write<'a, ReplicationUpdate>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, mut connections, mut ids, mut network_entities, mut update): Self::SystemData,
+    ) {
+        update.delta = None;
+        let (entity, connection) = match (&*entities, &mut connections).join().next() {
+            Some(server) => server,
+            None => return,
+        };
+        if self.server.as_ref().map(|&(server, _)| server) != Some(entity) {
+            self.server = Some((entity, connection.receive_buffer.register_reader()));
+        }
+        let reader = match self.server {
+            Some((_, ref mut reader)) => reader,
+            None => return,
+        };
+
+        let latest = update.tick;
+        for event in connection.receive_buffer.read(reader) {
+            let (tick, baseline, delta) = match event {
+                NetEvent::Packet(packet) => match packet.content() {
+                    ReplicationMessage::Snapshot {
+                        tick,
+                        baseline,
+                        delta,
+                    } => (*tick, *baseline, delta),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if update.tick.map_or(false, |latest| tick <= latest) {
+                continue;
+            }
+            let snapshot = match baseline {
+                Some(baseline) => match self.history.iter().find(|&&(t, _)| t == baseline) {
+                    Some((_, baseline)) => baseline.apply(delta),
+                    // The baseline is too old, the server sends a newer one once acknowledged.
+                    None => continue,
+                },
+                None => Snapshot::default().apply(delta),
+            };
+            self.history.push_back((tick, snapshot));
+            while self.history.len() > HISTORY {
+                self.history.pop_front();
+            }
+            update.tick = Some(tick);
+        }
+
+        if update.tick.is_none() {
+            self.connect_timer -= time.delta_real_seconds();
+            if self.connect_timer <= 0.0 {
+                self.connect_timer = CONNECT_INTERVAL;
+                connection
+                    .send_buffer
+                    .single_write(NetEvent::Packet(NetPacket::unreliable(
+                        ReplicationMessage::Connect,
+                    )));
+            }
+            return;
+        }
+        if update.tick == latest {
+            return;
+        }
+        let tick = update.tick.unwrap();
+        connection
+            .send_buffer
+            .single_write(NetEvent::Packet(NetPacket::unreliable(
+                ReplicationMessage::Ack { tick },
+            )));
+
+        let current = match self.history.back() {
+            Some((_, current)) => current.clone(),
+            None => return,
+        };
+        let delta = current.delta(&self.applied);
+        self.applied = current;
+
+        for id in &delta.removed {
+            if let Some(entity) = network_entities.entities.remove(id) {
+                if let Err(e) = entities.delete(entity) {
+                    error!("Failed to delete a replicated entity: {}", e);
+                }
+            }
+        }
+        for changed in &delta.changed {
+            if network_entities.entities.contains_key(&changed.id) {
+                continue;
+            }
+            let entity = entities.create();
+            if let Err(e) = ids.insert(entity, changed.id) {
+                error!("Failed to insert a network id: {}", e);
+            }
+            network_entities.entities.insert(changed.id, entity);
+        }
+        update.delta = Some(delta);
+    }
+}
+
+/// Inserts, replaces and removes the components of type `C` of the local entities as they change
+/// in the snapshots.
+///
+/// Added by the `ReplicationBundle` on the client for each replicated type.
+#[derive(Debug)]
+pub struct ApplyComponentSystem<C> {
+    _marker: PhantomData<C>,
+}
+
+impl<C> ApplyComponentSystem<C> {
+    /// Creates a new `ApplyComponentSystem`.
+    pub fn new() -> Self {
+        ApplyComponentSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C> Default for ApplyComponentSystem<C> {
+    fn default() -> Self {
+        ApplyComponentSystem::new()
+    }
+}
+
+impl<'a, C: Replicate> System<'a> for ApplyComponentSystem<C> {
+    type SystemData = (
+        Read<'a, ReplicationUpdate>,
+        Read<'a, NetworkEntities>,
+        WriteStorage<'a, C>,
+    );
+
+    fn run(&mut self, (update, network_entities, mut components): Self::SystemData) {
+        let delta = match update.delta {
+            Some(ref delta) => delta,
+            None => return,
+        };
+        for changed in &delta.changed {
+            let entity = match network_entities.get(changed.id) {
+                Some(entity) => entity,
+                None => continue,
+            };
+            for (kind, data) in &changed.changed {
+                if *kind != C::KIND {
+                    continue;
+                }
+                match bincode::deserialize::<C>(data) {
+                    Ok(component) => {
+                        if let Err(e) = components.insert(entity, component) {
+                            error!("Failed to insert a replicated component: {}", e);
+                        }
+                    }
+                    Err(e) => error!("Failed to deserialize a replicated component: {}", e),
+                }
+            }
+            if changed.removed.contains(&C::KIND) {
+                components.remove(entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::ecs::prelude::{Builder, RunNow, World};
+
+    fn receive(
+        world: &mut World,
+        server: Entity,
+        tick: u32,
+        baseline: Option<u32>,
+        delta: SnapshotDelta,
+    ) {
+        world
+            .write_storage::<NetConnection<ReplicationMessage>>()
+            .get_mut(server)
+            .unwrap()
+            .receive_buffer
+            .single_write(NetEvent::Packet(NetPacket::unreliable_sequenced(
+                ReplicationMessage::Snapshot {
+                    tick,
+                    baseline,
+                    delta,
+                },
+                None,
+            )));
+    }
+
+    fn snapshot(ids: &[u32]) -> Snapshot {
+        Snapshot {
+            entities: ids
+                .iter()
+                .map(|&id| (NetworkId(id), Default::default()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn deletes_entities_removed_from_the_snapshot() {
+        let mut world = World::new();
+        let mut system = ReplicationClientSystem::new();
+        System::setup(&mut system, &mut world.res);
+        let server = world
+            .create_entity()
+            .with(NetConnection::<ReplicationMessage>::new(
+                "127.0.0.1:21401".parse().unwrap(),
+            ))
+            .build();
+        system.run_now(&world.res);
+
+        let first = snapshot(&[1, 2]);
+        receive(
+            &mut world,
+            server,
+            1,
+            None,
+            first.delta(&Snapshot::default()),
+        );
+        system.run_now(&world.res);
+        world.maintain();
+        let (one, two) = {
+            let network_entities = world.read_resource::<NetworkEntities>();
+            (
+                network_entities.get(NetworkId(1)).unwrap(),
+                network_entities.get(NetworkId(2)).unwrap(),
+            )
+        };
+
+        // Entity 2 left the interest of the client, the delta is based on the first snapshot.
+        receive(&mut world, server, 2, Some(1), snapshot(&[1]).delta(&first));
+        system.run_now(&world.res);
+        world.maintain();
+        assert_eq!(world.read_resource::<ReplicationUpdate>().tick, Some(2));
+        let network_entities = world.read_resource::<NetworkEntities>();
+        assert_eq!(network_entities.get(NetworkId(1)), Some(one));
+        assert_eq!(network_entities.get(NetworkId(2)), None);
+        assert!(world.is_alive(one));
+        assert!(!world.is_alive(two));
+    }
+}
@@ -0,0 +1,213 @@
+//! Interpolation of the replicated transforms between the snapshots received.
+
+use std::collections::VecDeque;
+
+use log::error;
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, Entities, Join, Read, System, WriteStorage},
+    math::{Translation3, Vector3},
+    Time, Transform,
+};
+
+use super::{NetworkEntities, Replicate, ReplicationUpdate};
+
+/// The transforms received for an entity, by tick, which the `TransformInterpolationSystem`
+/// interpolates between.
+#[derive(Clone, Debug, Default)]
+pub struct InterpolationBuffer {
+    samples: VecDeque<(u32, Transform<f32>)>,
+}
+
+impl InterpolationBuffer {
+    /// Adds the transform of a tick, ignored if it is not newer than the latest.
+    pub fn push(&mut self, tick: u32, transform: Transform<f32>) {
+        if self
+            .samples
+            .back()
+            .map_or(true, |&(latest, _)| tick > latest)
+        {
+            self.samples.push_back((tick, transform));
+        }
+    }
+
+    /// Returns the transform at `tick`, which may fall between two ticks, interpolated between
+    /// the transforms of the ticks around it. Before the first or past the latest transform, that
+    /// transform is returned.
+    pub fn sample(&self, tick: f32) -> Option<Transform<f32>> {
+        let next = match self.samples.iter().position(|&(t, _)| t as f32 > tick) {
+            Some(0) => return self.samples.front().map(|(_, transform)| transform.clone()),
+            Some(next) => next,
+            None => return self.samples.back().map(|(_, transform)| transform.clone()),
+        };
+        let (from_tick, ref from) = self.samples[next - 1];
+        let (to_tick, ref to) = self.samples[next];
+        let s = (tick - from_tick as f32) / (to_tick - from_tick) as f32;
+
+        let lerp = |from: &Vector3<f32>, to: &Vector3<f32>| from + (to - from) * s;
+        let rotation = from
+            .rotation()
+            .try_slerp(to.rotation(), s, 1e-6)
+            .unwrap_or_else(|| *to.rotation());
+        Some(Transform::new(
+            Translation3::from(lerp(from.translation(), to.translation())),
+            rotation,
+            lerp(from.scale(), to.scale()),
+        ))
+    }
+
+    /// Drops the transforms no longer needed to sample at `tick` or later.
+    fn discard_before(&mut self, tick: f32) {
+        while self.samples.len() > 1 && self.samples[1].0 as f32 <= tick {
+            self.samples.pop_front();
+        }
+    }
+}
+
+impl Component for InterpolationBuffer {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Applies the replicated `Transform`s on the client, interpolated between the snapshots and
+/// a delay behind the latest one.
+///
+/// The clock of the interpolation advances at the tick rate of the server and is eased towards
+/// the delay behind the latest tick received, so it follows the server while smoothing out
+/// snapshots arriving early or late. With a delay of a few ticks, the transforms stay smooth
+/// when snapshots are lost.
+///
+/// Added by the `ReplicationBundle` on the client with `with_transforms`, as
+/// "transform_interpolation_system".
+#[derive(Debug)]
+pub struct TransformInterpolationSystem {
+    tick_rate: f32,
+    delay: f32,
+    clock: Option<f32>,
+}
+
+impl TransformInterpolationSystem {
+    /// Creates a new `TransformInterpolationSystem` for a server taking `tick_rate` snapshots per
+    /// second, showing the transforms `delay` ticks behind the latest snapshot.
+    pub fn new(tick_rate: f32, delay: f32) -> Self {
+        TransformInterpolationSystem {
+            tick_rate,
+            delay,
+            clock: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for TransformInterpolationSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, ReplicationUpdate>,
+        Read<'a, NetworkEntities>,
+        WriteStorage<'a, InterpolationBuffer>,
+        WriteStorage<'a, Transform<f32>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, update, network_entities, mut buffers, mut transforms): Self::SystemData,
+    ) {
+        let latest = match update.tick {
+            Some(latest) => latest,
+            None => return,
+        };
+
+        if let Some(ref delta) = update.delta {
+            for changed in &delta.changed {
+                let entity = match network_entities.get(changed.id) {
+                    Some(entity) => entity,
+                    None => continue,
+                };
+                for (kind, data) in &changed.changed {
+                    if *kind != Transform::<f32>::KIND {
+                        continue;
+                    }
+                    match bincode::deserialize::<Transform<f32>>(data) {
+                        Ok(transform) => match buffers.entry(entity) {
+                            Ok(entry) => entry
+                                .or_insert_with(Default::default)
+                                .push(latest, transform),
+                            Err(e) => error!("Failed to buffer a replicated transform: {}", e),
+                        },
+                        Err(e) => error!("Failed to deserialize a replicated transform: {}", e),
+                    }
+                }
+                if changed.removed.contains(&Transform::<f32>::KIND) {
+                    buffers.remove(entity);
+                    transforms.remove(entity);
+                }
+            }
+        }
+
+        let target = latest as f32 - self.delay;
+        let clock = match self.clock {
+            Some(clock) => clock + time.delta_real_seconds() * self.tick_rate,
+            None => target,
+        };
+        // Far behind or ahead, after a stall or a burst of losses, the clock jumps to the target.
+        let clock = if (target - clock).abs() > self.delay.max(1.0) * 2.0 {
+            target
+        } else {
+            clock + (target - clock) * 0.1
+        };
+        self.clock = Some(clock);
+
+        for (entity, buffer) in (&*entities, &mut buffers).join() {
+            let sample = match buffer.sample(clock) {
+                Some(sample) => sample,
+                None => continue,
+            };
+            buffer.discard_before(clock);
+            match transforms.get_mut(entity) {
+                Some(transform) => {
+                    *transform.isometry_mut() = *sample.isometry();
+                    *transform.scale_mut() = *sample.scale();
+                }
+                None => {
+                    if let Err(e) = transforms.insert(entity, sample) {
+                        error!("Failed to insert a replicated transform: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::math::UnitQuaternion;
+
+    fn transform(x: f32, angle: f32) -> Transform<f32> {
+        Transform::new(
+            Translation3::new(x, 0.0, 0.0),
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn samples_between_ticks() {
+        let mut buffer = InterpolationBuffer::default();
+        assert!(buffer.sample(0.0).is_none());
+
+        buffer.push(2, transform(0.0, 0.0));
+        buffer.push(4, transform(4.0, 1.0));
+        buffer.push(3, transform(100.0, 0.0));
+
+        let sample = buffer.sample(3.0).unwrap();
+        assert!((sample.translation().x - 2.0).abs() < 1e-5);
+        assert!((sample.rotation().angle() - 0.5).abs() < 1e-5);
+        assert_eq!(buffer.sample(1.0).unwrap().translation().x, 0.0);
+        assert_eq!(buffer.sample(9.0).unwrap().translation().x, 4.0);
+
+        buffer.discard_before(5.0);
+        assert_eq!(buffer.samples.len(), 1);
+        assert_eq!(buffer.sample(3.0).unwrap().translation().x, 4.0);
+    }
+}
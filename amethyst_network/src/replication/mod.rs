@@ -0,0 +1,146 @@
+//! Replication of components from a server to its clients.
+//!
+//! The server owns the state of the replicated entities. At a fixed tick rate it takes a snapshot
+//! of their replicated components and sends each client the delta from the last snapshot the
+//! client acknowledged, over unreliable sequenced packets, so lost snapshots are never resent but
+//! superseded by the next one. The `NetInterest` of a connection limits the entities it is sent.
+//!
+//! Clients rebuild the snapshots, acknowledge them, and spawn, update and delete local entities
+//! to match. Replicated transforms are interpolated between the snapshots received, a few ticks
+//! behind the server, to hide the tick rate and the jitter of the network.
+//!
+//! Both sides add a `NetworkBundle::<ReplicationMessage>` along with the `ReplicationBundle`,
+//! and the client a `NetConnection::<ReplicationMessage>` to the server:
+//!
+//! ```rust,ignore
+//! // Server
+//! let game_data = GameDataBuilder::default()
+//!     .with_bundle(TransformBundle::new())?
+//!     .with_bundle(NetworkBundle::<ReplicationMessage>::new(server_addr))?
+//!     .with_bundle(ReplicationBundle::server(20.0).with_transforms().with_component::<Health>())?;
+//!
+//! world.create_entity().with(Transform::default()).with(Health(100)).with(Replicated).build();
+//! ```
+
+mod bundle;
+mod client;
+mod interpolation;
+mod server;
+mod snapshot;
+
+pub use self::{
+    bundle::ReplicationBundle,
+    client::{ApplyComponentSystem, NetworkEntities, ReplicationClientSystem, ReplicationUpdate},
+    interpolation::{InterpolationBuffer, TransformInterpolationSystem},
+    server::{
+        ReplicateComponentSystem, ReplicationServerSystem, ReplicationTick, ReplicationTickSystem,
+    },
+    snapshot::SnapshotDelta,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, Entity, NullStorage},
+    Transform,
+};
+
+/// How many snapshots are kept as baselines for deltas, by the server for each client and by the
+/// clients. A client acknowledging nothing for longer is sent full snapshots again.
+pub(crate) const HISTORY: usize = 32;
+
+/// A component type replicated from the server to its clients.
+///
+/// The component is serialized with `bincode` whenever it changes, and inserted on the entities
+/// of the clients or replaced there.
+///
+/// ```rust,ignore
+/// #[derive(Clone, Serialize, Deserialize)]
+/// struct Health(u32);
+///
+/// impl Component for Health {
+///     type Storage = DenseVecStorage<Self>;
+/// }
+///
+/// impl Replicate for Health {
+///     const KIND: u16 = 1;
+/// }
+/// ```
+pub trait Replicate: Component + Clone + Serialize + DeserializeOwned + Send + Sync {
+    /// Identifies the type in snapshots, unique among the replicated types of the game. The
+    /// largest value is used by `Transform`.
+    const KIND: u16;
+}
+
+impl Replicate for Transform<f32> {
+    const KIND: u16 = u16::max_value();
+}
+
+/// The identifier of a replicated entity, the same on the server and the clients.
+///
+/// The server assigns it to entities with `Replicated`, and the clients to the entities they
+/// spawn for them, which `NetworkEntities` maps it to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NetworkId(pub u32);
+
+impl Component for NetworkId {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Marks an entity of the server as replicated to the clients.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Replicated;
+
+impl Component for Replicated {
+    type Storage = NullStorage<Self>;
+}
+
+/// Limits the entities replicated to a client to those around a point of interest, usually the
+/// entity the client controls.
+///
+/// Add it to the `NetConnection` entity of the client on the server. Replicated entities without
+/// a `Transform` are always sent to the client.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetInterest {
+    /// The entity the interest is centered on, which is always replicated. `None` replicates only
+    /// the entities without a `Transform`.
+    pub focus: Option<Entity>,
+    /// The distance from the focus up to which entities are replicated.
+    pub radius: f32,
+}
+
+impl NetInterest {
+    /// Creates a new `NetInterest` around `focus`.
+    pub fn new(focus: Entity, radius: f32) -> Self {
+        NetInterest {
+            focus: Some(focus),
+            radius,
+        }
+    }
+}
+
+impl Component for NetInterest {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The messages of the replication protocol, the event type of the `NetConnection`s used.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationMessage {
+    /// Sent by the clients until they receive a snapshot, to make the server start replicating
+    /// to them.
+    Connect,
+    /// The snapshot of a tick, sent by the server.
+    Snapshot {
+        /// The tick of the snapshot.
+        tick: u32,
+        /// The tick of the snapshot the delta is from, `None` for an empty baseline.
+        baseline: Option<u32>,
+        /// The changes from the baseline.
+        delta: SnapshotDelta,
+    },
+    /// Acknowledges that the client received the snapshot of a tick.
+    Ack {
+        /// The tick acknowledged.
+        tick: u32,
+    },
+}
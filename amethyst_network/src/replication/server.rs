@@ -0,0 +1,405 @@
+//! The systems taking snapshots of the replicated entities and sending them to the clients.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+};
+
+use log::error;
+use shrev::ReaderId;
+
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
+    math::{convert, RealField, Vector3},
+    Time, Transform,
+};
+
+use super::{
+    snapshot::Snapshot, NetInterest, NetworkId, Replicate, Replicated, ReplicationMessage, HISTORY,
+};
+use crate::{ConnectionState, NetConnection, NetEvent, NetPacket};
+
+/// The tick of the server, and the snapshot being taken on it.
+#[derive(Debug, Default)]
+pub struct ReplicationTick {
+    /// The number of the latest tick, 0 before the first one.
+    pub tick: u32,
+    pub(crate) taking: bool,
+    pub(crate) snapshot: Snapshot,
+    pub(crate) positions: HashMap<NetworkId, Vector3<f32>>,
+}
+
+impl ReplicationTick {
+    /// Returns whether a snapshot is taken and sent on this frame.
+    pub fn is_snapshot_frame(&self) -> bool {
+        self.taking
+    }
+}
+
+/// Advances the `ReplicationTick` at the tick rate, and starts the snapshot of each tick.
+///
+/// Assigns a `NetworkId` to the entities with `Replicated` that do not have one yet. Reads the
+/// global matrices of the `Transform`s for the `NetInterest` of the clients, so it should run
+/// after the "transform_system".
+///
+/// Added by the `ReplicationBundle` on the server, as "replication_tick_system".
+#[derive(Debug)]
+pub struct ReplicationTickSystem {
+    tick_rate: f32,
+    accumulator: f32,
+    next_id: u32,
+}
+
+impl ReplicationTickSystem {
+    /// Creates a new `ReplicationTickSystem` taking `tick_rate` snapshots per second.
+    pub fn new(tick_rate: f32) -> Self {
+        ReplicationTickSystem {
+            tick_rate,
+            accumulator: 0.0,
+            next_id: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for ReplicationTickSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadStorage<'a, Replicated>,
+        ReadStorage<'a, Transform<f32>>,
+        WriteStorage<'a, NetworkId>,
+        Write<'a, ReplicationTick>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, replicated, transforms, mut ids, mut tick): Self::SystemData,
+    ) {
+        let interval = 1.0 / self.tick_rate;
+        self.accumulator += time.delta_real_seconds();
+        tick.taking = self.accumulator >= interval;
+        if !tick.taking {
+            return;
+        }
+        // Ticks missed by a slow frame are skipped rather than caught up with.
+        self.accumulator = (self.accumulator - interval).min(interval);
+        tick.tick = tick.tick.wrapping_add(1);
+
+        let unassigned = (&*entities, &replicated, !&ids)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in unassigned {
+            if let Err(e) = ids.insert(entity, NetworkId(self.next_id)) {
+                error!("Failed to assign a network id: {}", e);
+                continue;
+            }
+            self.next_id += 1;
+        }
+
+        let tick = &mut *tick;
+        tick.snapshot.entities.clear();
+        tick.positions.clear();
+        for (id, _, transform) in (&ids, &replicated, transforms.maybe()).join() {
+            tick.snapshot.entities.insert(*id, Default::default());
+            if let Some(transform) = transform {
+                tick.positions.insert(*id, position(transform));
+            }
+        }
+    }
+}
+
+/// Serializes the components of type `C` of the replicated entities into the snapshot of the
+/// tick.
+///
+/// Added by the `ReplicationBundle` on the server for each replicated type.
+#[derive(Debug)]
+pub struct ReplicateComponentSystem<C> {
+    _marker: PhantomData<C>,
+}
+
+impl<C> ReplicateComponentSystem<C> {
+    /// Creates a new `ReplicateComponentSystem`.
+    pub fn new() -> Self {
+        ReplicateComponentSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C> Default for ReplicateComponentSystem<C> {
+    fn default() -> Self {
+        ReplicateComponentSystem::new()
+    }
+}
+
+impl<'a, C: Replicate> System<'a> for ReplicateComponentSystem<C> {
+    type SystemData = (
+        ReadStorage<'a, NetworkId>,
+        ReadStorage<'a, Replicated>,
+        ReadStorage<'a, C>,
+        Write<'a, ReplicationTick>,
+    );
+
+    fn run(&mut self, (ids, replicated, components, mut tick): Self::SystemData) {
+        if !tick.taking {
+            return;
+        }
+        for (id, _, component) in (&ids, &replicated, &components).join() {
+            match bincode::serialize(component) {
+                Ok(data) => {
+                    if let Some(state) = tick.snapshot.entities.get_mut(id) {
+                        state.insert(C::KIND, data);
+                    }
+                }
+                Err(e) => error!("Failed to serialize a replicated component: {}", e),
+            }
+        }
+    }
+}
+
+struct ClientState {
+    reader: ReaderId<NetEvent<ReplicationMessage>>,
+    acked: Option<u32>,
+    /// The snapshots sent to the client which are not older than the last one acknowledged.
+    history: VecDeque<(u32, Snapshot)>,
+}
+
+/// Sends the snapshot of each tick to the clients, as a delta from the last snapshot they
+/// acknowledged, and receives their acknowledgements.
+///
+/// Every `NetConnection::<ReplicationMessage>` of the server is a client. The snapshots are
+/// sent unreliably, so they should stay within the size of a packet.
+///
+/// Added by the `ReplicationBundle` on the server, as "replication_server_system".
+#[derive(Default)]
+pub struct ReplicationServerSystem {
+    clients: HashMap<Entity, ClientState>,
+}
+
+impl ReplicationServerSystem {
+    /// Creates a new `ReplicationServerSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for ReplicationServerSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, ReplicationTick>,
+        ReadStorage<'a, NetInterest>,
+        ReadStorage<'a, Transform<f32>>,
+        WriteStorage<'a, NetConnection<ReplicationMessage>>,
+    );
+
+    fn run(&mut self, (entities, tick, interests, transforms, mut connections): Self::SystemData) {
+        self.clients
+            .retain(|entity, _| connections.get(*entity).is_some());
+
+        for (entity, connection) in (&*entities, &mut connections).join() {
+            let client = self.clients.entry(entity).or_insert_with(|| ClientState {
+                reader: connection.receive_buffer.register_reader(),
+                acked: None,
+                history: VecDeque::new(),
+            });
+
+            for event in connection.receive_buffer.read(&mut client.reader) {
+                let message = match event {
+                    NetEvent::Packet(packet) => packet.content(),
+                    _ => continue,
+                };
+                match *message {
+                    ReplicationMessage::Ack { tick } => {
+                        if client.acked.map_or(true, |acked| tick > acked) {
+                            client.acked = Some(tick);
+                            client.history.retain(|&(sent, _)| sent >= tick);
+                        }
+                    }
+                    // The client started over, so it has none of the baselines.
+                    ReplicationMessage::Connect => {
+                        client.acked = None;
+                        client.history.clear();
+                    }
+                    _ => {}
+                }
+            }
+
+            if !tick.taking || connection.state == ConnectionState::Disconnected {
+                continue;
+            }
+
+            let snapshot = interesting(&tick, interests.get(entity), &transforms);
+            let baseline = client
+                .acked
+                .and_then(|acked| client.history.iter().find(|&&(sent, _)| sent == acked));
+            let (baseline, delta) = match baseline {
+                Some((sent, baseline)) => (Some(*sent), snapshot.delta(baseline)),
+                None => (None, snapshot.delta(&Snapshot::default())),
+            };
+            connection
+                .send_buffer
+                .single_write(NetEvent::Packet(NetPacket::unreliable_sequenced(
+                    ReplicationMessage::Snapshot {
+                        tick: tick.tick,
+                        baseline,
+                        delta,
+                    },
+                    None,
+                )));
+
+            client.history.push_back((tick.tick, snapshot));
+            while client.history.len() > HISTORY {
+                client.history.pop_front();
+            }
+        }
+    }
+}
+
+/// The global position of a transform, as stored in `ReplicationTick::positions`.
+fn position<N: RealField + SubsetOf<f32>>(transform: &Transform<N>) -> Vector3<f32> {
+    convert(transform.global_matrix().column(3).xyz())
+}
+
+/// Returns the part of the snapshot of the tick within the interest of a client.
+fn interesting<N: RealField + SubsetOf<f32>>(
+    tick: &ReplicationTick,
+    interest: Option<&NetInterest>,
+    transforms: &ReadStorage<'_, Transform<N>>,
+) -> Snapshot {
+    let interest = match interest {
+        Some(interest) => interest,
+        None => return tick.snapshot.clone(),
+    };
+    let focus = interest
+        .focus
+        .and_then(|focus| transforms.get(focus))
+        .map(position);
+    let radius = interest.radius * interest.radius;
+
+    Snapshot {
+        entities: tick
+            .snapshot
+            .entities
+            .iter()
+            .filter(|&(id, _)| match (tick.positions.get(id), focus) {
+                (None, _) => true,
+                (Some(position), Some(focus)) => (position - focus).norm_squared() <= radius,
+                (Some(_), None) => false,
+            })
+            .map(|(id, state)| (*id, state.clone()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use amethyst_core::ecs::prelude::{Builder, RunNow, World};
+
+    use crate::replication::SnapshotDelta;
+
+    /// Takes a snapshot of entities with a component of kind 0 holding `data`, at `x` on the x
+    /// axis.
+    fn take(world: &mut World, number: u32, entities: &[(u32, f32, u8)]) {
+        let mut tick = world.write_resource::<ReplicationTick>();
+        tick.tick = number;
+        tick.taking = true;
+        tick.snapshot.entities.clear();
+        tick.positions.clear();
+        for &(id, x, data) in entities {
+            let mut state = BTreeMap::new();
+            state.insert(0, vec![data]);
+            tick.snapshot.entities.insert(NetworkId(id), state);
+            tick.positions
+                .insert(NetworkId(id), Vector3::new(x, 0.0, 0.0));
+        }
+    }
+
+    fn sent(
+        world: &World,
+        client: Entity,
+        reader: &mut ReaderId<NetEvent<ReplicationMessage>>,
+    ) -> Vec<(u32, Option<u32>, SnapshotDelta)> {
+        let connections = world.read_storage::<NetConnection<ReplicationMessage>>();
+        connections
+            .get(client)
+            .unwrap()
+            .send_buffer
+            .read(reader)
+            .filter_map(|event| match event {
+                NetEvent::Packet(packet) => match packet.content() {
+                    ReplicationMessage::Snapshot {
+                        tick,
+                        baseline,
+                        delta,
+                    } => Some((*tick, *baseline, delta.clone())),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deltas_are_based_on_the_acked_snapshot() {
+        let mut world = World::new();
+        let mut system = ReplicationServerSystem::new();
+        System::setup(&mut system, &mut world.res);
+        let focus = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+        let client = world
+            .create_entity()
+            .with(NetConnection::<ReplicationMessage>::new(
+                "127.0.0.1:21400".parse().unwrap(),
+            ))
+            .with(NetInterest::new(focus, 10.0))
+            .build();
+        let mut reader = world
+            .write_storage::<NetConnection<ReplicationMessage>>()
+            .get_mut(client)
+            .unwrap()
+            .send_buffer
+            .register_reader();
+
+        take(&mut world, 1, &[(1, 0.0, 1), (2, 5.0, 1)]);
+        system.run_now(&world.res);
+        let first = sent(&world, client, &mut reader);
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].0, first[0].1), (1, None));
+        assert_eq!(first[0].2.changed.len(), 2);
+
+        // Not acknowledged yet, so the next delta is still from the empty snapshot.
+        take(&mut world, 2, &[(1, 0.0, 1), (2, 5.0, 1)]);
+        system.run_now(&world.res);
+        let second = sent(&world, client, &mut reader);
+        assert_eq!((second[0].0, second[0].1), (2, None));
+        assert_eq!(second[0].2.changed.len(), 2);
+
+        world
+            .write_storage::<NetConnection<ReplicationMessage>>()
+            .get_mut(client)
+            .unwrap()
+            .receive_buffer
+            .single_write(NetEvent::Packet(NetPacket::unreliable(
+                ReplicationMessage::Ack { tick: 1 },
+            )));
+
+        // Entity 2 left the radius of the interest, entity 1 changed.
+        take(&mut world, 3, &[(1, 0.0, 2), (2, 50.0, 1)]);
+        system.run_now(&world.res);
+        let third = sent(&world, client, &mut reader);
+        assert_eq!((third[0].0, third[0].1), (3, Some(1)));
+        let delta = &third[0].2;
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed[0].id, NetworkId(1));
+        assert_eq!(delta.changed[0].changed, vec![(0, vec![2])]);
+        assert_eq!(delta.removed, vec![NetworkId(2)]);
+    }
+}
@@ -0,0 +1,143 @@
+//! The serialized state of the replicated entities, and the deltas between two states.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::NetworkId;
+
+/// The serialized components of an entity, by the `Replicate::KIND` of their type.
+pub(crate) type EntityState = BTreeMap<u16, Vec<u8>>;
+
+/// The serialized replicated components of the entities at one tick.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Snapshot {
+    pub entities: BTreeMap<NetworkId, EntityState>,
+}
+
+impl Snapshot {
+    /// Returns the changes turning `baseline` into this snapshot.
+    pub fn delta(&self, baseline: &Snapshot) -> SnapshotDelta {
+        let mut delta = SnapshotDelta::default();
+        for (&id, state) in &self.entities {
+            let base = baseline.entities.get(&id);
+            let changed = state
+                .iter()
+                .filter(|&(kind, data)| base.and_then(|base| base.get(kind)) != Some(data))
+                .map(|(&kind, data)| (kind, data.clone()))
+                .collect::<Vec<_>>();
+            let removed = base
+                .map(|base| {
+                    base.keys()
+                        .filter(|kind| !state.contains_key(kind))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            // New entities are sent even without components, so they are spawned.
+            if !changed.is_empty() || !removed.is_empty() || base.is_none() {
+                delta.changed.push(EntityDelta {
+                    id,
+                    changed,
+                    removed,
+                });
+            }
+        }
+        delta.removed = baseline
+            .entities
+            .keys()
+            .filter(|id| !self.entities.contains_key(id))
+            .cloned()
+            .collect();
+        delta
+    }
+
+    /// Returns this snapshot with the changes of `delta` applied.
+    pub fn apply(&self, delta: &SnapshotDelta) -> Snapshot {
+        let mut snapshot = self.clone();
+        for id in &delta.removed {
+            snapshot.entities.remove(id);
+        }
+        for entity in &delta.changed {
+            let state = snapshot.entities.entry(entity.id).or_default();
+            for kind in &entity.removed {
+                state.remove(kind);
+            }
+            for (kind, data) in &entity.changed {
+                state.insert(*kind, data.clone());
+            }
+        }
+        snapshot
+    }
+}
+
+/// The changes between two snapshots, sent in a `ReplicationMessage`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub(crate) changed: Vec<EntityDelta>,
+    pub(crate) removed: Vec<NetworkId>,
+}
+
+/// The changes of one entity between two snapshots.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct EntityDelta {
+    pub id: NetworkId,
+    pub changed: Vec<(u16, Vec<u8>)>,
+    pub removed: Vec<u16>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entities: &[(u32, &[(u16, u8)])]) -> Snapshot {
+        Snapshot {
+            entities: entities
+                .iter()
+                .map(|&(id, components)| {
+                    let state = components
+                        .iter()
+                        .map(|&(kind, data)| (kind, vec![data]))
+                        .collect();
+                    (NetworkId(id), state)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn deltas_only_contain_changes() {
+        let baseline = snapshot(&[(1, &[(0, 1), (1, 1)]), (2, &[(0, 2)]), (3, &[])]);
+        let current = snapshot(&[(1, &[(0, 1), (1, 5)]), (2, &[]), (4, &[])]);
+
+        let delta = current.delta(&baseline);
+        assert_eq!(
+            delta,
+            SnapshotDelta {
+                changed: vec![
+                    EntityDelta {
+                        id: NetworkId(1),
+                        changed: vec![(1, vec![5])],
+                        removed: vec![],
+                    },
+                    EntityDelta {
+                        id: NetworkId(2),
+                        changed: vec![],
+                        removed: vec![0],
+                    },
+                    EntityDelta {
+                        id: NetworkId(4),
+                        changed: vec![],
+                        removed: vec![],
+                    },
+                ],
+                removed: vec![NetworkId(3)],
+            }
+        );
+        assert_eq!(baseline.apply(&delta), current);
+        assert_eq!(
+            Snapshot::default().apply(&current.delta(&Snapshot::default())),
+            current
+        );
+    }
+}
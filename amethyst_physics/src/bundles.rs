@@ -0,0 +1,99 @@
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    math::Vector3,
+};
+use amethyst_error::Error;
+
+use crate::{
+    resources::PhysicsSettings,
+    systems::{CharacterControllerSystem, ColliderGenerationSystem, PhysicsSystem, TriggerSystem},
+};
+
+/// The bundle that moves rigid bodies and makes colliders for glTF scenes.
+///
+/// Add `"physics_system"` and `"character_controller_system"` as dependencies of the
+/// `TransformBundle` to see the moved bodies and characters in the same frame.
+///
+/// # Systems
+///
+/// This bundle adds the following systems:
+///
+/// * `ColliderGenerationSystem`, named `"collider_generation_system"`
+/// * `PhysicsSystem`, named `"physics_system"`
+/// * `CharacterControllerSystem`, named `"character_controller_system"`
+/// * `TriggerSystem`, named `"trigger_system"`
+pub struct PhysicsBundle<'a> {
+    settings: PhysicsSettings,
+    gltf_colliders: bool,
+    dep: &'a [&'a str],
+}
+
+impl<'a> Default for PhysicsBundle<'a> {
+    fn default() -> Self {
+        PhysicsBundle::new()
+    }
+}
+
+impl<'a> PhysicsBundle<'a> {
+    /// Creates a bundle with the default `PhysicsSettings`, which makes static mesh colliders
+    /// for all `GltfCollisionGeometry`.
+    pub fn new() -> Self {
+        PhysicsBundle {
+            settings: PhysicsSettings::default(),
+            gltf_colliders: true,
+            dep: &[],
+        }
+    }
+
+    /// Sets the acceleration of dynamic bodies.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.settings.gravity = gravity;
+        self
+    }
+
+    /// Sets the length of one physics step in seconds, and the most steps made in one frame.
+    pub fn with_timestep(mut self, timestep: f32, max_substeps: u32) -> Self {
+        self.settings.timestep = timestep;
+        self.settings.max_substeps = max_substeps;
+        self
+    }
+
+    /// Sets whether colliders are made for all `GltfCollisionGeometry`, not only for the
+    /// entities with an `AutoCollider`.
+    pub fn with_gltf_colliders(mut self, gltf_colliders: bool) -> Self {
+        self.gltf_colliders = gltf_colliders;
+        self
+    }
+
+    /// Sets the systems that run before the physics systems.
+    pub fn with_dep(mut self, dep: &'a [&'a str]) -> Self {
+        self.dep = dep;
+        self
+    }
+}
+
+impl<'a, 'b, 'c> SystemBundle<'a, 'b> for PhysicsBundle<'c> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        builder.add(
+            ColliderGenerationSystem::new(self.gltf_colliders),
+            "collider_generation_system",
+            self.dep,
+        );
+        builder.add(
+            PhysicsSystem::new(self.settings),
+            "physics_system",
+            &["collider_generation_system"],
+        );
+        builder.add(
+            CharacterControllerSystem::default(),
+            "character_controller_system",
+            &["physics_system"],
+        );
+        builder.add(
+            TriggerSystem::default(),
+            "trigger_system",
+            &["character_controller_system"],
+        );
+        Ok(())
+    }
+}
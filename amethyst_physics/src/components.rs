@@ -0,0 +1,424 @@
+use std::sync::Arc;
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, NullStorage},
+    math::Vector3,
+};
+use amethyst_gltf::{GltfCollisionGeometry, GltfExtras, GltfNodeExtent};
+
+use crate::shape::{ColliderShape, TriMesh};
+
+/// How a `RigidBody` is moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyType {
+    /// Moved by its velocity, gravity and collisions.
+    Dynamic,
+    /// Moved by its velocity only, it pushes dynamic bodies but is never pushed back.
+    Kinematic,
+    /// Never moved, the same as a `Collider` without a body.
+    Static,
+}
+
+/// A body moved by the `PhysicsSystem`, which writes its position and rotation to the
+/// `Transform` of its entity.
+///
+/// The `Transform` is read again every frame, so teleporting a body is done by changing its
+/// `Transform`. Bodies are moved in the local space of their `Transform`, so they should be top
+/// level entities, or children of entities that don't move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigidBody {
+    /// How the body is moved.
+    pub body_type: BodyType,
+    /// The velocity in units per second.
+    pub velocity: Vector3<f32>,
+    /// The rotation per second, as an axis scaled by the angle in radians.
+    pub angular_velocity: Vector3<f32>,
+    /// The mass, which only matters for dynamic bodies.
+    pub mass: f32,
+    /// How much of the velocity is lost per second.
+    pub linear_damping: f32,
+    /// How much of the angular velocity is lost per second.
+    pub angular_damping: f32,
+    /// The factor of `PhysicsSettings::gravity` applied to the body.
+    pub gravity_scale: f32,
+}
+
+impl RigidBody {
+    /// Creates a dynamic body with the given mass.
+    pub fn dynamic(mass: f32) -> Self {
+        RigidBody {
+            body_type: BodyType::Dynamic,
+            velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            mass,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            gravity_scale: 1.0,
+        }
+    }
+
+    /// Creates a kinematic body, moved by setting its velocity.
+    pub fn kinematic() -> Self {
+        RigidBody {
+            body_type: BodyType::Kinematic,
+            ..RigidBody::dynamic(0.0)
+        }
+    }
+
+    /// Sets the starting velocity.
+    pub fn with_velocity(mut self, velocity: Vector3<f32>) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Sets the starting angular velocity.
+    pub fn with_angular_velocity(mut self, angular_velocity: Vector3<f32>) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    /// Sets how much of the velocity and angular velocity is lost per second.
+    pub fn with_damping(mut self, linear: f32, angular: f32) -> Self {
+        self.linear_damping = linear;
+        self.angular_damping = angular;
+        self
+    }
+
+    /// Sets the factor of the gravity applied to the body.
+    pub fn with_gravity_scale(mut self, gravity_scale: f32) -> Self {
+        self.gravity_scale = gravity_scale;
+        self
+    }
+
+    /// Returns the inverse of the mass, zero for bodies that collisions can't move.
+    pub fn inverse_mass(&self) -> f32 {
+        match self.body_type {
+            BodyType::Dynamic if self.mass > 0.0 => 1.0 / self.mass,
+            _ => 0.0,
+        }
+    }
+
+    /// Changes the velocity of a dynamic body by an impulse, scaled by its inverse mass.
+    pub fn apply_impulse(&mut self, impulse: Vector3<f32>) {
+        self.velocity += impulse * self.inverse_mass();
+    }
+}
+
+impl Component for RigidBody {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The shape an entity collides with, moved with its `RigidBody` or static without one.
+///
+/// The shape is placed by the global matrix of the entity's `Transform`, so the colliders of
+/// static entities can be children anywhere in a hierarchy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collider {
+    /// The shape.
+    pub shape: ColliderShape,
+    /// The position of the shape in the space of the entity.
+    pub offset: Vector3<f32>,
+    /// Sensors only report `CollisionEvent`s, they don't push anything.
+    pub sensor: bool,
+    /// How much of the speed is kept when bouncing, the highest of two colliders is used.
+    pub restitution: f32,
+    /// How much the shape slows down sliding, the lowest of two colliders is used.
+    pub friction: f32,
+}
+
+impl Collider {
+    /// Creates a collider of the given shape.
+    pub fn new(shape: ColliderShape) -> Self {
+        Collider {
+            shape,
+            offset: Vector3::zeros(),
+            sensor: false,
+            restitution: 0.0,
+            friction: 0.5,
+        }
+    }
+
+    /// Creates a sphere collider.
+    pub fn sphere(radius: f32) -> Self {
+        Collider::new(ColliderShape::Sphere { radius })
+    }
+
+    /// Creates a box collider, extending `half_extents` from the origin on each axis.
+    pub fn cuboid(half_extents: Vector3<f32>) -> Self {
+        Collider::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    /// Creates a capsule collider along the Y axis.
+    pub fn capsule(half_height: f32, radius: f32) -> Self {
+        Collider::new(ColliderShape::Capsule {
+            half_height,
+            radius,
+        })
+    }
+
+    /// Sets the position of the shape in the space of the entity.
+    pub fn with_offset(mut self, offset: Vector3<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Makes the collider a sensor.
+    pub fn with_sensor(mut self, sensor: bool) -> Self {
+        self.sensor = sensor;
+        self
+    }
+
+    /// Sets how much of the speed is kept when bouncing.
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    /// Sets how much the shape slows down sliding.
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The shape of a `Collider` made by the `ColliderGenerationSystem`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoColliderShape {
+    /// A box around the `GltfNodeExtent` of the entity.
+    Cuboid,
+    /// A sphere around the `GltfNodeExtent` of the entity.
+    Sphere,
+    /// The triangles of the `GltfCollisionGeometry` of the entity.
+    TriMesh,
+}
+
+/// Asks the `ColliderGenerationSystem` to make the `Collider` of its entity from the mesh data
+/// of the glTF importer, once the prefab is loaded.
+///
+/// `GltfSceneOptions::load_collision_shapes` has to be enabled for `AutoColliderShape::TriMesh`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AutoCollider {
+    /// The shape to make.
+    pub shape: AutoColliderShape,
+    /// The collider made, the shape of which is replaced.
+    pub template: Collider,
+}
+
+impl AutoCollider {
+    /// Creates an `AutoCollider` of the given shape.
+    pub fn new(shape: AutoColliderShape) -> Self {
+        AutoCollider {
+            shape,
+            template: Collider::sphere(0.0),
+        }
+    }
+
+    /// Sets the collider made, the shape of which is replaced.
+    pub fn with_template(mut self, template: Collider) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Makes the collider, `None` if the data it needs isn't there.
+    pub fn build(
+        &self,
+        extent: Option<&GltfNodeExtent>,
+        geometry: Option<&GltfCollisionGeometry>,
+    ) -> Option<Collider> {
+        let mut collider = self.template.clone();
+        match self.shape {
+            AutoColliderShape::Cuboid | AutoColliderShape::Sphere => {
+                let extent = extent.filter(|extent| extent.valid())?;
+                let half_extents = extent.distance() / 2.0;
+                collider.shape = if self.shape == AutoColliderShape::Cuboid {
+                    ColliderShape::Cuboid { half_extents }
+                } else {
+                    ColliderShape::Sphere {
+                        radius: half_extents.norm(),
+                    }
+                };
+                collider.offset = extent.centroid().coords;
+            }
+            AutoColliderShape::TriMesh => {
+                collider.shape = ColliderShape::TriMesh(Arc::new(TriMesh::from(geometry?)));
+            }
+        }
+        Some(collider)
+    }
+}
+
+impl Component for AutoCollider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Walks an entity around the colliders of other entities, moved by the
+/// `CharacterControllerSystem` instead of the `PhysicsSystem`.
+///
+/// The character is a capsule standing on the origin of the entity. It slides along walls,
+/// climbs steps up to `step_offset` high and slopes up to `max_slope`, and falls with the
+/// gravity of the `PhysicsSettings`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CharacterController {
+    /// The radius of the capsule.
+    pub radius: f32,
+    /// The height of the capsule, from the feet to the top of the head.
+    pub height: f32,
+    /// The highest step the character climbs without jumping.
+    pub step_offset: f32,
+    /// The steepest slope the character can walk up, in radians.
+    pub max_slope: f32,
+    /// The velocity the character walks with, set by the game every frame. The Y axis is
+    /// ignored.
+    pub movement: Vector3<f32>,
+    /// The speed of falling or jumping, positive being up.
+    pub vertical_velocity: f32,
+    grounded: bool,
+    ground_normal: Vector3<f32>,
+}
+
+impl CharacterController {
+    /// Creates a controller for a capsule of the given radius and height.
+    pub fn new(radius: f32, height: f32) -> Self {
+        CharacterController {
+            radius,
+            height,
+            step_offset: 0.3,
+            max_slope: std::f32::consts::FRAC_PI_4,
+            movement: Vector3::zeros(),
+            vertical_velocity: 0.0,
+            grounded: false,
+            ground_normal: Vector3::y(),
+        }
+    }
+
+    /// Sets the highest step the character climbs.
+    pub fn with_step_offset(mut self, step_offset: f32) -> Self {
+        self.step_offset = step_offset;
+        self
+    }
+
+    /// Sets the steepest slope the character can walk up, in radians.
+    pub fn with_max_slope(mut self, max_slope: f32) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    /// Returns whether the character stood on walkable ground after its last move.
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Returns the normal of the ground the character stands on, up while in the air.
+    pub fn ground_normal(&self) -> &Vector3<f32> {
+        &self.ground_normal
+    }
+
+    /// Jumps with the given upwards speed, returning false if the character isn't grounded.
+    pub fn jump(&mut self, speed: f32) -> bool {
+        if !self.grounded {
+            return false;
+        }
+        self.vertical_velocity = speed;
+        self.grounded = false;
+        true
+    }
+
+    pub(crate) fn set_ground(&mut self, ground: Option<Vector3<f32>>) {
+        self.grounded = ground.is_some();
+        self.ground_normal = ground.unwrap_or_else(Vector3::y);
+    }
+}
+
+impl Component for CharacterController {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A volume which sends a `TriggerEvent` when an entity with a `TriggerTracked` enters or leaves
+/// it, for checkpoints, doors or cutscenes. Triggers don't push anything, and don't need a
+/// `Collider`.
+///
+/// The `TriggerSystem` also makes the volumes of glTF nodes with a `"trigger"` property in their
+/// extras, set to `"box"` or `"sphere"` for a volume around the `GltfNodeExtent` of the node, or
+/// to `"mesh"` for the shape of its `GltfCollisionGeometry`. Naming such nodes with the
+/// `GltfSceneOptions::collider_prefix` loads their geometry without rendering it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TriggerVolume {
+    /// The shape. Mesh volumes contain the entities touching their triangles or with their center
+    /// inside, so the mesh should be closed.
+    pub shape: ColliderShape,
+    /// The position of the shape in the space of the entity.
+    pub offset: Vector3<f32>,
+}
+
+impl TriggerVolume {
+    /// Creates a volume of the given shape.
+    pub fn new(shape: ColliderShape) -> Self {
+        TriggerVolume {
+            shape,
+            offset: Vector3::zeros(),
+        }
+    }
+
+    /// Creates a sphere volume.
+    pub fn sphere(radius: f32) -> Self {
+        TriggerVolume::new(ColliderShape::Sphere { radius })
+    }
+
+    /// Creates a box volume, extending `half_extents` from the origin on each axis.
+    pub fn cuboid(half_extents: Vector3<f32>) -> Self {
+        TriggerVolume::new(ColliderShape::Cuboid { half_extents })
+    }
+
+    /// Sets the position of the shape in the space of the entity.
+    pub fn with_offset(mut self, offset: Vector3<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Makes a volume from the mesh data of the glTF importer, like an `AutoCollider`, `None` if
+    /// the data it needs isn't there.
+    pub fn from_gltf(
+        shape: AutoColliderShape,
+        extent: Option<&GltfNodeExtent>,
+        geometry: Option<&GltfCollisionGeometry>,
+    ) -> Option<Self> {
+        let collider = AutoCollider::new(shape).build(extent, geometry)?;
+        Some(TriggerVolume {
+            shape: collider.shape,
+            offset: collider.offset,
+        })
+    }
+
+    pub(crate) fn collider(&self) -> Collider {
+        Collider::new(self.shape.clone()).with_offset(self.offset)
+    }
+}
+
+impl Component for TriggerVolume {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Returns the shape asked for by the `"trigger"` property of the extras of a glTF node.
+pub(crate) fn trigger_tag(extras: &GltfExtras) -> Option<AutoColliderShape> {
+    match extras.get("trigger")?.as_str()? {
+        "box" => Some(AutoColliderShape::Cuboid),
+        "sphere" => Some(AutoColliderShape::Sphere),
+        "mesh" => Some(AutoColliderShape::TriMesh),
+        _ => None,
+    }
+}
+
+/// Marks the entities which enter and leave `TriggerVolume`s.
+///
+/// The overlap is tested with the `Collider` of the entity, or the capsule of its
+/// `CharacterController`, or else the origin of the entity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TriggerTracked;
+
+impl Component for TriggerTracked {
+    type Storage = NullStorage<Self>;
+}
@@ -0,0 +1,32 @@
+//! Amethyst physics crate.
+//!
+//! Moves `RigidBody`s with a fixed timestep and keeps their `Collider`s from overlapping,
+//! writing the results to the `Transform` of their entities. Colliders can be made from the
+//! collision geometry and extents loaded by the glTF importer, see `AutoCollider` and
+//! `PhysicsBundle::with_gltf_colliders`, and characters walk around them with a
+//! `CharacterController`. Entities with a `TriggerTracked` send `TriggerEvent`s when they enter
+//! or leave a `TriggerVolume`, which can also be made from tagged glTF nodes.
+//!
+//! The `SceneQuery` casts rays, spheres and boxes against the scene without the rest of the
+//! physics.
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use self::{
+    bundles::PhysicsBundle,
+    components::{
+        AutoCollider, AutoColliderShape, BodyType, CharacterController, Collider, RigidBody,
+        TriggerTracked, TriggerVolume,
+    },
+    query::{CastShape, QueryHit, SceneQuery},
+    resources::{CollisionEvent, PhysicsSettings, TriggerEvent},
+    shape::{ColliderShape, TriMesh},
+    systems::{CharacterControllerSystem, ColliderGenerationSystem, PhysicsSystem, TriggerSystem},
+};
+
+mod bundles;
+mod components;
+mod query;
+mod resources;
+mod shape;
+mod systems;
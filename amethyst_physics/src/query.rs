@@ -0,0 +1,505 @@
+//! Raycasts and shape casts against the geometry of the scene.
+
+use std::{cmp::Ordering, collections::HashSet};
+
+use amethyst_core::{
+    ecs::prelude::{Entity, Read, ReadStorage},
+    math::{Matrix4, Point3, Vector3},
+    transform::Transform,
+    Aabb, Ray, SpatialGrid, WorldBounds,
+};
+use amethyst_gltf::GltfCollisionGeometry;
+use shred_derive::SystemData;
+
+use crate::shape::{closest_point_on_triangle, cuboid_triangles, ColliderShape, TriMesh};
+
+const EPSILON: f32 = 1e-6;
+/// How close a sphere cast gets to a triangle before it counts as touching.
+const SKIN: f32 = 1e-4;
+/// The most steps a sphere cast makes towards one triangle.
+const MAX_ADVANCE_STEPS: usize = 64;
+/// How many times the distance of a box cast is halved to find where it starts touching.
+const BISECTIONS: usize = 16;
+
+/// The shape moved along the ray by `SceneQuery::cast`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CastShape {
+    /// A point, which makes a plain raycast.
+    Ray,
+    /// A sphere.
+    Sphere {
+        /// The radius.
+        radius: f32,
+    },
+    /// A box aligned with the world axes.
+    Cuboid {
+        /// How far the box extends from its center on each axis.
+        half_extents: Vector3<f32>,
+    },
+}
+
+impl CastShape {
+    /// Returns how far the shape extends from its center on each axis.
+    fn extent(&self) -> Vector3<f32> {
+        match *self {
+            CastShape::Ray => Vector3::zeros(),
+            CastShape::Sphere { radius } => Vector3::from_element(radius),
+            CastShape::Cuboid { half_extents } => half_extents,
+        }
+    }
+}
+
+/// Where a `SceneQuery` hit an entity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryHit {
+    /// The entity hit.
+    pub entity: Entity,
+    /// The distance along the ray at which the shape first touches the entity.
+    pub distance: f32,
+    /// The point touched, on the surface of the entity.
+    pub point: Vector3<f32>,
+    /// The normal of the surface at the point, facing the ray.
+    pub normal: Vector3<f32>,
+}
+
+/// Casts rays and shapes against the entities of the `SpatialGrid`, for shooting, finding the
+/// ground or checking the line of sight, without needing the `PhysicsSystem`.
+///
+/// Entities with a `GltfCollisionGeometry` are hit by its triangles, the others by the box of
+/// their `WorldBounds`. The triangles are placed by the global matrix of each candidate on every
+/// query, so the geometry should be simple, like that of collider nodes.
+///
+/// The grid is only filled by the `SpatialGridSystem`, which has to be added to the dispatcher.
+#[derive(SystemData)]
+pub struct SceneQuery<'a> {
+    grid: Read<'a, SpatialGrid>,
+    bounds: ReadStorage<'a, WorldBounds>,
+    transforms: ReadStorage<'a, Transform<f32>>,
+    geometry: ReadStorage<'a, GltfCollisionGeometry>,
+}
+
+impl<'a> SceneQuery<'a> {
+    /// Returns the closest hit of the ray within `max_distance`.
+    pub fn raycast(&self, ray: &Ray, max_distance: f32) -> Option<QueryHit> {
+        self.cast(&CastShape::Ray, ray, max_distance, |_| true)
+    }
+
+    /// Returns the closest hit on each entity the ray hits within `max_distance`, closest
+    /// first.
+    pub fn raycast_all(&self, ray: &Ray, max_distance: f32) -> Vec<QueryHit> {
+        let mut hits = self
+            .grid
+            .query_ray(ray, max_distance)
+            .into_iter()
+            .filter_map(|(entity, _)| self.cast_entity(entity, &CastShape::Ray, ray, max_distance))
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        hits
+    }
+
+    /// Returns the closest hit of a sphere moved along the ray within `max_distance`.
+    pub fn sphere_cast(&self, ray: &Ray, radius: f32, max_distance: f32) -> Option<QueryHit> {
+        self.cast(&CastShape::Sphere { radius }, ray, max_distance, |_| true)
+    }
+
+    /// Returns the closest hit of a world aligned box moved along the ray within
+    /// `max_distance`.
+    pub fn box_cast(
+        &self,
+        ray: &Ray,
+        half_extents: Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<QueryHit> {
+        self.cast(
+            &CastShape::Cuboid { half_extents },
+            ray,
+            max_distance,
+            |_| true,
+        )
+    }
+
+    /// Returns whether no entity accepted by the `filter` lies between two points, the filter
+    /// usually rejecting the entity looking and the one looked at.
+    pub fn line_of_sight<F>(&self, from: &Vector3<f32>, to: &Vector3<f32>, filter: F) -> bool
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let distance = (to - from).norm();
+        if distance < EPSILON {
+            return true;
+        }
+        let ray = Ray::new(*from, to - from);
+        self.cast(&CastShape::Ray, &ray, distance, filter).is_none()
+    }
+
+    /// Returns the closest hit of the shape moved along the ray within `max_distance`, among
+    /// the entities accepted by the `filter`.
+    ///
+    /// A shape overlapping an entity at the origin of the ray hits it at a distance of zero.
+    /// The `max_distance` of a sphere or box cast has to be finite, nothing is hit otherwise.
+    pub fn cast<F>(
+        &self,
+        shape: &CastShape,
+        ray: &Ray,
+        max_distance: f32,
+        mut filter: F,
+    ) -> Option<QueryHit>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let mut closest: Option<QueryHit> = None;
+        for (entity, entry) in self.candidates(shape, ray, max_distance) {
+            let max_distance = closest.as_ref().map_or(max_distance, |hit| hit.distance);
+            if entry > max_distance {
+                break;
+            }
+            if !filter(entity) {
+                continue;
+            }
+            if let Some(hit) = self.cast_entity(entity, shape, ray, max_distance) {
+                closest = Some(hit);
+            }
+        }
+        closest
+    }
+
+    /// Finds the entities the shape may hit, with the distance at which it reaches their
+    /// bounding sphere, closest first.
+    fn candidates(&self, shape: &CastShape, ray: &Ray, max_distance: f32) -> Vec<(Entity, f32)> {
+        let radius = shape.extent().norm();
+        if radius == 0.0 {
+            return self.grid.query_ray(ray, max_distance);
+        }
+        if !max_distance.is_finite() {
+            return Vec::new();
+        }
+
+        // The grid has no sweep query, so the path is covered by spheres of about a cell each
+        let step = self.grid.cell_size().max(radius);
+        let mut seen = HashSet::new();
+        let mut candidates = Vec::new();
+        let mut start = 0.0;
+        while start < max_distance {
+            let length = step.min(max_distance - start);
+            let center = ray.at(start + length / 2.0);
+            for entity in self.grid.query_sphere(&center, length / 2.0 + radius) {
+                if !seen.insert(entity) {
+                    continue;
+                }
+                let entry = self.bounds.get(entity).and_then(|bounds| {
+                    ray.intersect_sphere(&bounds.center, bounds.radius + radius)
+                });
+                match entry {
+                    Some(entry) if entry <= max_distance => candidates.push((entity, entry)),
+                    _ => {}
+                }
+            }
+            start += step;
+        }
+        candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+        candidates
+    }
+
+    /// Casts the shape against the triangles of one entity.
+    fn cast_entity(
+        &self,
+        entity: Entity,
+        shape: &CastShape,
+        ray: &Ray,
+        max_distance: f32,
+    ) -> Option<QueryHit> {
+        let extent = shape.extent();
+        let mut closest: Option<(f32, Point3<f32>, Vector3<f32>)> = None;
+        for triangle in self.triangles(entity)? {
+            let max_distance = closest.map_or(max_distance, |hit| hit.0);
+            // The part of the ray along which the shape overlaps the box of the triangle
+            let mut bounds = Aabb::new(triangle[0].coords, triangle[0].coords);
+            bounds.extend(&triangle[1].coords);
+            bounds.extend(&triangle[2].coords);
+            bounds.min -= extent;
+            bounds.max += extent;
+            let (near, far) = match ray_box(ray, &bounds) {
+                Some((near, far)) if near <= max_distance => (near, far.min(max_distance)),
+                _ => continue,
+            };
+            let hit = match *shape {
+                CastShape::Ray => ray_triangle(ray, &triangle, max_distance),
+                CastShape::Sphere { radius } => sphere_triangle(ray, radius, &triangle, near, far),
+                CastShape::Cuboid { half_extents } => {
+                    box_triangle(ray, &half_extents, &triangle, near, far)
+                }
+            };
+            if hit.is_some() {
+                closest = hit;
+            }
+        }
+        closest.map(|(distance, point, normal)| QueryHit {
+            entity,
+            distance,
+            point: point.coords,
+            normal,
+        })
+    }
+
+    /// Returns the triangles of an entity in world space.
+    fn triangles(&self, entity: Entity) -> Option<Vec<[Point3<f32>; 3]>> {
+        match (self.geometry.get(entity), self.transforms.get(entity)) {
+            (Some(geometry), Some(transform)) => {
+                let matrix = transform.global_matrix();
+                let triangles = TriMesh::from(geometry)
+                    .triangles()
+                    .map(|triangle| {
+                        [
+                            matrix.transform_point(&triangle[0]),
+                            matrix.transform_point(&triangle[1]),
+                            matrix.transform_point(&triangle[2]),
+                        ]
+                    })
+                    .collect();
+                Some(triangles)
+            }
+            _ => self
+                .bounds
+                .get(entity)
+                .map(|bounds| box_triangles(&bounds.aabb)),
+        }
+    }
+}
+
+/// Splits the faces of a box into triangles.
+fn box_triangles(aabb: &Aabb) -> Vec<[Point3<f32>; 3]> {
+    // The corners of each face in order, the bits of a corner picking the maximum of each axis
+    const FACES: [[usize; 4]; 6] = [
+        [0, 2, 6, 4],
+        [1, 3, 7, 5],
+        [0, 1, 5, 4],
+        [2, 3, 7, 6],
+        [0, 1, 3, 2],
+        [4, 5, 7, 6],
+    ];
+    let corner = |i: usize| {
+        let pick = |axis: usize| {
+            if i & (1 << axis) == 0 {
+                aabb.min[axis]
+            } else {
+                aabb.max[axis]
+            }
+        };
+        Point3::new(pick(0), pick(1), pick(2))
+    };
+    let mut triangles = Vec::with_capacity(12);
+    for face in &FACES {
+        triangles.push([corner(face[0]), corner(face[1]), corner(face[2])]);
+        triangles.push([corner(face[0]), corner(face[2]), corner(face[3])]);
+    }
+    triangles
+}
+
+/// Returns the distances at which the ray enters and leaves the box.
+fn ray_box(ray: &Ray, aabb: &Aabb) -> Option<(f32, f32)> {
+    let mut near = 0.0f32;
+    let mut far = std::f32::INFINITY;
+    for axis in 0..3 {
+        let origin = ray.origin[axis];
+        let direction = ray.direction[axis];
+        if direction == 0.0 {
+            if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let a = (aabb.min[axis] - origin) / direction;
+        let b = (aabb.max[axis] - origin) / direction;
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+        if near > far {
+            return None;
+        }
+    }
+    Some((near, far))
+}
+
+/// Returns the normal of the triangle on the side the ray comes from.
+fn face_normal(triangle: &[Point3<f32>; 3], direction: &Vector3<f32>) -> Vector3<f32> {
+    let normal = (triangle[1] - triangle[0])
+        .cross(&(triangle[2] - triangle[0]))
+        .try_normalize(EPSILON)
+        .unwrap_or_else(|| -direction);
+    if normal.dot(direction) > 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// Intersects a ray with both sides of a triangle (Möller & Trumbore).
+pub(crate) fn ray_triangle(
+    ray: &Ray,
+    triangle: &[Point3<f32>; 3],
+    max_distance: f32,
+) -> Option<(f32, Point3<f32>, Vector3<f32>)> {
+    let ab = triangle[1] - triangle[0];
+    let ac = triangle[2] - triangle[0];
+    let p = ray.direction.cross(&ac);
+    let determinant = ab.dot(&p);
+    if determinant.abs() < EPSILON {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let s = Point3::from(ray.origin) - triangle[0];
+    let u = s.dot(&p) * inverse;
+    if u < 0.0 || u > 1.0 {
+        return None;
+    }
+    let q = s.cross(&ab);
+    let v = ray.direction.dot(&q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = ac.dot(&q) * inverse;
+    if distance < 0.0 || distance > max_distance {
+        return None;
+    }
+    Some((
+        distance,
+        Point3::from(ray.at(distance)),
+        face_normal(triangle, &ray.direction),
+    ))
+}
+
+/// Moves a sphere along the ray until it touches the triangle, by the distance between them
+/// each step, so it never passes through.
+fn sphere_triangle(
+    ray: &Ray,
+    radius: f32,
+    triangle: &[Point3<f32>; 3],
+    near: f32,
+    far: f32,
+) -> Option<(f32, Point3<f32>, Vector3<f32>)> {
+    let mut distance = near;
+    for _ in 0..MAX_ADVANCE_STEPS {
+        let center = Point3::from(ray.at(distance));
+        let point = closest_point_on_triangle(&center, triangle);
+        let offset = center - point;
+        let gap = offset.norm() - radius;
+        if gap < SKIN {
+            let normal = offset
+                .try_normalize(EPSILON)
+                .unwrap_or_else(|| face_normal(triangle, &ray.direction));
+            return Some((distance, point, normal));
+        }
+        distance += gap;
+        if distance > far {
+            return None;
+        }
+    }
+    None
+}
+
+/// Moves a box along the ray in steps of its smallest half extent until it touches the
+/// triangle, then halves the last step to find where it starts touching.
+///
+/// A box only grazing a corner of the triangle between two steps can be missed.
+fn box_triangle(
+    ray: &Ray,
+    half_extents: &Vector3<f32>,
+    triangle: &[Point3<f32>; 3],
+    near: f32,
+    far: f32,
+) -> Option<(f32, Point3<f32>, Vector3<f32>)> {
+    let shape = ColliderShape::Cuboid {
+        half_extents: *half_extents,
+    };
+    let touches = |distance: f32| {
+        let matrix = Matrix4::new_translation(&ray.at(distance));
+        cuboid_triangles(&shape.to_world(&matrix, &Vector3::zeros()), &[*triangle])
+    };
+    let hit = |distance: f32, normal: Vector3<f32>| {
+        let point = closest_point_on_triangle(&Point3::from(ray.at(distance)), triangle);
+        Some((distance, point, -normal))
+    };
+
+    if let Some(contact) = touches(near) {
+        return hit(near, contact.normal);
+    }
+    let step = half_extents[0]
+        .min(half_extents[1])
+        .min(half_extents[2])
+        .max((far - near) / 1024.0)
+        .max(EPSILON);
+    let mut previous = near;
+    loop {
+        let distance = (previous + step).min(far);
+        if let Some(mut contact) = touches(distance) {
+            let (mut outside, mut inside) = (previous, distance);
+            for _ in 0..BISECTIONS {
+                let middle = (outside + inside) / 2.0;
+                match touches(middle) {
+                    Some(touching) => {
+                        inside = middle;
+                        contact = touching;
+                    }
+                    None => outside = middle,
+                }
+            }
+            return hit(inside, contact.normal);
+        }
+        if distance >= far {
+            return None;
+        }
+        previous = distance;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn floor() -> [Point3<f32>; 3] {
+        [
+            Point3::new(-5.0, 0.0, -5.0),
+            Point3::new(5.0, 0.0, -5.0),
+            Point3::new(0.0, 0.0, 5.0),
+        ]
+    }
+
+    #[test]
+    fn casts_against_triangles() {
+        let down = Ray::new(Vector3::new(0.0, 2.0, 0.0), -Vector3::y());
+
+        let (distance, point, normal) = ray_triangle(&down, &floor(), 10.0).unwrap();
+        assert!((distance - 2.0).abs() < 1e-5);
+        assert!(point.coords.norm() < 1e-5);
+        assert_eq!(normal, Vector3::y());
+        assert_eq!(ray_triangle(&down, &floor(), 1.0), None);
+
+        let (distance, _, normal) = sphere_triangle(&down, 0.5, &floor(), 0.0, 10.0).unwrap();
+        assert!((distance - 1.5).abs() < 1e-3);
+        assert!((normal - Vector3::y()).norm() < 1e-5);
+
+        let half_extents = Vector3::new(1.0, 0.25, 1.0);
+        let (distance, _, normal) =
+            box_triangle(&down, &half_extents, &floor(), 0.0, 10.0).unwrap();
+        assert!((distance - 1.75).abs() < 1e-3);
+        assert!((normal - Vector3::y()).norm() < 1e-5);
+
+        let beside = Ray::new(Vector3::new(10.0, 2.0, 0.0), -Vector3::y());
+        assert_eq!(sphere_triangle(&beside, 0.5, &floor(), 0.0, 10.0), None);
+    }
+
+    #[test]
+    fn boxes_are_split_into_triangles() {
+        let aabb = Aabb::new(Vector3::from_element(-1.0), Vector3::from_element(1.0));
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 5.0), -Vector3::z());
+        let hits = box_triangles(&aabb)
+            .iter()
+            .filter_map(|triangle| ray_triangle(&ray, triangle, 10.0))
+            .map(|hit| hit.0)
+            .fold(std::f32::INFINITY, f32::min);
+        assert!((hits - 4.0).abs() < 1e-5);
+    }
+}
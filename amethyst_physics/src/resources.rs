@@ -0,0 +1,85 @@
+use amethyst_core::{ecs::prelude::Entity, math::Vector3};
+
+/// The settings of the `PhysicsSystem`, which can be changed at runtime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhysicsSettings {
+    /// The acceleration of dynamic bodies, scaled by their `gravity_scale`.
+    pub gravity: Vector3<f32>,
+    /// The length of one physics step in seconds.
+    pub timestep: f32,
+    /// The most steps made in one frame, the rest of the time of slow frames is dropped.
+    pub max_substeps: u32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        PhysicsSettings {
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            timestep: 1.0 / 60.0,
+            max_substeps: 4,
+        }
+    }
+}
+
+/// Sent in an `EventChannel` when two colliders start or stop touching.
+///
+/// The pair is ordered, so `a` is always lower than `b`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CollisionEvent {
+    /// The colliders started touching.
+    Started {
+        /// The first entity.
+        a: Entity,
+        /// The second entity.
+        b: Entity,
+        /// Whether either of the colliders is a sensor.
+        sensor: bool,
+    },
+    /// The colliders stopped touching, or one of them was removed.
+    Stopped {
+        /// The first entity.
+        a: Entity,
+        /// The second entity.
+        b: Entity,
+    },
+}
+
+impl CollisionEvent {
+    /// Returns the two entities.
+    pub fn entities(&self) -> (Entity, Entity) {
+        match *self {
+            CollisionEvent::Started { a, b, .. } | CollisionEvent::Stopped { a, b } => (a, b),
+        }
+    }
+}
+
+/// Sent in an `EventChannel` by the `TriggerSystem` when an entity with a `TriggerTracked` enters
+/// or leaves a `TriggerVolume`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TriggerEvent {
+    /// The entity entered the volume.
+    Enter {
+        /// The entity of the `TriggerVolume`.
+        trigger: Entity,
+        /// The entity which entered it.
+        entity: Entity,
+    },
+    /// The entity left the volume, or one of them was removed.
+    Exit {
+        /// The entity of the `TriggerVolume`.
+        trigger: Entity,
+        /// The entity which left it.
+        entity: Entity,
+    },
+}
+
+impl TriggerEvent {
+    /// Returns the entity of the volume and the entity which entered or left it.
+    pub fn entities(&self) -> (Entity, Entity) {
+        match *self {
+            TriggerEvent::Enter { trigger, entity } | TriggerEvent::Exit { trigger, entity } => {
+                (trigger, entity)
+            }
+        }
+    }
+}
@@ -0,0 +1,777 @@
+//! Collision shapes and the contact tests between them.
+
+use std::{collections::HashMap, sync::Arc};
+
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity},
+    math::{Matrix4, Point3, Vector3},
+};
+use amethyst_gltf::GltfCollisionGeometry;
+
+use crate::components::Collider;
+
+const EPSILON: f32 = 1e-6;
+
+/// The triangles of a `ColliderShape::TriMesh`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriMesh {
+    /// The positions of the vertices.
+    pub positions: Vec<Point3<f32>>,
+    /// The indices of the vertices of each triangle.
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl TriMesh {
+    /// Returns the corners of each triangle.
+    pub fn triangles<'a>(&'a self) -> impl Iterator<Item = [Point3<f32>; 3]> + 'a {
+        self.indices.iter().map(move |triangle| {
+            [
+                self.positions[triangle[0] as usize],
+                self.positions[triangle[1] as usize],
+                self.positions[triangle[2] as usize],
+            ]
+        })
+    }
+}
+
+impl<'a> From<&'a GltfCollisionGeometry> for TriMesh {
+    fn from(geometry: &'a GltfCollisionGeometry) -> Self {
+        TriMesh {
+            positions: geometry
+                .positions
+                .iter()
+                .map(|p| Point3::new(p[0], p[1], p[2]))
+                .collect(),
+            indices: geometry
+                .indices
+                .chunks(3)
+                .filter(|triangle| triangle.len() == 3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect(),
+        }
+    }
+}
+
+/// The shape of a `Collider`, in the space of its entity.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColliderShape {
+    /// A sphere around the origin.
+    Sphere {
+        /// The radius of the sphere.
+        radius: f32,
+    },
+    /// A box around the origin.
+    Cuboid {
+        /// Half the size of the box on each axis.
+        half_extents: Vector3<f32>,
+    },
+    /// A capsule around the origin, along the Y axis.
+    Capsule {
+        /// Half the distance between the centers of the two half spheres.
+        half_height: f32,
+        /// The radius of the capsule.
+        radius: f32,
+    },
+    /// A triangle mesh, which collides with every shape but other meshes.
+    ///
+    /// Meshes are meant for static level geometry: they don't have a volume, so contacts with
+    /// them are only found while a shape touches their surface.
+    TriMesh(Arc<TriMesh>),
+}
+
+impl ColliderShape {
+    /// Places the shape in the world, the `offset` being in the space of the entity.
+    pub(crate) fn to_world(&self, matrix: &Matrix4<f32>, offset: &Vector3<f32>) -> WorldShape {
+        let center = matrix.transform_point(&Point3::from(*offset));
+        let scaled = [
+            matrix.transform_vector(&Vector3::x()),
+            matrix.transform_vector(&Vector3::y()),
+            matrix.transform_vector(&Vector3::z()),
+        ];
+        match *self {
+            ColliderShape::Sphere { radius } => WorldShape::Sphere {
+                center,
+                radius: radius * scaled.iter().map(|axis| axis.norm()).fold(0.0, f32::max),
+            },
+            ColliderShape::Cuboid { half_extents } => {
+                let mut axes = [Vector3::x(), Vector3::y(), Vector3::z()];
+                let mut half_extents = half_extents;
+                for i in 0..3 {
+                    let length = scaled[i].norm();
+                    if length > EPSILON {
+                        axes[i] = scaled[i] / length;
+                    }
+                    half_extents[i] *= length;
+                }
+                WorldShape::Cuboid {
+                    center,
+                    axes,
+                    half_extents,
+                }
+            }
+            ColliderShape::Capsule {
+                half_height,
+                radius,
+            } => WorldShape::Capsule {
+                start: matrix.transform_point(&Point3::from(offset - Vector3::y() * half_height)),
+                end: matrix.transform_point(&Point3::from(offset + Vector3::y() * half_height)),
+                radius: radius * scaled.iter().map(|axis| axis.norm()).fold(0.0, f32::max),
+            },
+            ColliderShape::TriMesh(ref mesh) => {
+                let triangles = mesh
+                    .triangles()
+                    .map(|triangle| {
+                        [
+                            matrix.transform_point(&(triangle[0] + offset)),
+                            matrix.transform_point(&(triangle[1] + offset)),
+                            matrix.transform_point(&(triangle[2] + offset)),
+                        ]
+                    })
+                    .collect::<Vec<_>>();
+                let mut min = Point3::from(Vector3::from_element(std::f32::MAX));
+                let mut max = Point3::from(Vector3::from_element(std::f32::MIN));
+                for point in triangles.iter().flat_map(|triangle| triangle.iter()) {
+                    for i in 0..3 {
+                        min[i] = min[i].min(point[i]);
+                        max[i] = max[i].max(point[i]);
+                    }
+                }
+                WorldShape::TriMesh {
+                    triangles: Arc::new(triangles),
+                    min,
+                    max,
+                }
+            }
+        }
+    }
+}
+
+/// Places the colliders of entities in the world, reusing the triangles of meshes that didn't
+/// move.
+#[derive(Default)]
+pub(crate) struct ShapeCache {
+    meshes: HashMap<Entity, CachedMesh>,
+}
+
+struct CachedMesh {
+    mesh: Arc<TriMesh>,
+    matrix: Matrix4<f32>,
+    offset: Vector3<f32>,
+    shape: WorldShape,
+}
+
+impl ShapeCache {
+    /// Places the collider of an entity by the given matrix.
+    pub(crate) fn place(
+        &mut self,
+        entity: Entity,
+        collider: &Collider,
+        matrix: &Matrix4<f32>,
+    ) -> WorldShape {
+        let mesh = match collider.shape {
+            ColliderShape::TriMesh(ref mesh) => mesh,
+            _ => return collider.shape.to_world(matrix, &collider.offset),
+        };
+        if let Some(cached) = self.meshes.get(&entity) {
+            if Arc::ptr_eq(&cached.mesh, mesh)
+                && cached.matrix == *matrix
+                && cached.offset == collider.offset
+            {
+                return cached.shape.clone();
+            }
+        }
+        let shape = collider.shape.to_world(matrix, &collider.offset);
+        self.meshes.insert(
+            entity,
+            CachedMesh {
+                mesh: mesh.clone(),
+                matrix: *matrix,
+                offset: collider.offset,
+                shape: shape.clone(),
+            },
+        );
+        shape
+    }
+
+    /// Drops the meshes of deleted entities.
+    pub(crate) fn maintain(&mut self, entities: &Entities<'_>) {
+        self.meshes.retain(|entity, _| entities.is_alive(*entity));
+    }
+}
+
+/// A collider shape placed in the world.
+#[derive(Clone, Debug)]
+pub(crate) enum WorldShape {
+    Sphere {
+        center: Point3<f32>,
+        radius: f32,
+    },
+    Cuboid {
+        center: Point3<f32>,
+        axes: [Vector3<f32>; 3],
+        half_extents: Vector3<f32>,
+    },
+    Capsule {
+        start: Point3<f32>,
+        end: Point3<f32>,
+        radius: f32,
+    },
+    TriMesh {
+        triangles: Arc<Vec<[Point3<f32>; 3]>>,
+        min: Point3<f32>,
+        max: Point3<f32>,
+    },
+}
+
+impl WorldShape {
+    /// Returns the minimum and maximum corners of the world aligned box around the shape.
+    pub(crate) fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        match *self {
+            WorldShape::Sphere { center, radius } => {
+                let radius = Vector3::from_element(radius);
+                (center - radius, center + radius)
+            }
+            WorldShape::Cuboid {
+                center,
+                ref axes,
+                half_extents,
+            } => {
+                let extent = axes[0].abs() * half_extents[0]
+                    + axes[1].abs() * half_extents[1]
+                    + axes[2].abs() * half_extents[2];
+                (center - extent, center + extent)
+            }
+            WorldShape::Capsule { start, end, radius } => {
+                let mut min = start;
+                let mut max = start;
+                for i in 0..3 {
+                    min[i] = start[i].min(end[i]) - radius;
+                    max[i] = start[i].max(end[i]) + radius;
+                }
+                (min, max)
+            }
+            WorldShape::TriMesh { min, max, .. } => (min, max),
+        }
+    }
+}
+
+/// The deepest point of contact between two shapes.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Contact {
+    /// The direction from the first shape to the second.
+    pub normal: Vector3<f32>,
+    /// How far the shapes overlap along the normal.
+    pub depth: f32,
+}
+
+impl Contact {
+    fn flipped(self) -> Self {
+        Contact {
+            normal: -self.normal,
+            depth: self.depth,
+        }
+    }
+}
+
+/// Finds the contact between two shapes, `None` if they don't touch.
+pub(crate) fn contact(a: &WorldShape, b: &WorldShape) -> Option<Contact> {
+    use self::WorldShape::*;
+
+    match (a, b) {
+        (Capsule { start, end, radius }, _) => capsule_contact(start, end, *radius, b),
+        (_, Capsule { start, end, radius }) => {
+            capsule_contact(start, end, *radius, a).map(Contact::flipped)
+        }
+        (
+            Sphere {
+                center: a,
+                radius: a_radius,
+            },
+            Sphere {
+                center: b,
+                radius: b_radius,
+            },
+        ) => sphere_sphere(a, *a_radius, b, *b_radius),
+        (Sphere { center, radius }, Cuboid { .. }) => sphere_cuboid(center, *radius, b),
+        (Cuboid { .. }, Sphere { center, radius }) => {
+            sphere_cuboid(center, *radius, a).map(Contact::flipped)
+        }
+        (Sphere { center, radius }, TriMesh { triangles, .. }) => {
+            sphere_triangles(center, *radius, triangles)
+        }
+        (TriMesh { triangles, .. }, Sphere { center, radius }) => {
+            sphere_triangles(center, *radius, triangles).map(Contact::flipped)
+        }
+        (Cuboid { axes: a_axes, .. }, Cuboid { axes: b_axes, .. }) => {
+            let mut axes = Vec::with_capacity(15);
+            axes.extend_from_slice(a_axes);
+            axes.extend_from_slice(b_axes);
+            for a_axis in a_axes {
+                for b_axis in b_axes {
+                    axes.push(a_axis.cross(b_axis));
+                }
+            }
+            separating_axes(&corners(a), &corners(b), &axes)
+        }
+        (Cuboid { .. }, TriMesh { triangles, .. }) => cuboid_triangles(a, triangles),
+        (TriMesh { triangles, .. }, Cuboid { .. }) => {
+            cuboid_triangles(b, triangles).map(Contact::flipped)
+        }
+        (TriMesh { .. }, TriMesh { .. }) => None,
+    }
+}
+
+fn sphere_sphere(
+    a: &Point3<f32>,
+    a_radius: f32,
+    b: &Point3<f32>,
+    b_radius: f32,
+) -> Option<Contact> {
+    let distance = b - a;
+    let length = distance.norm();
+    let depth = a_radius + b_radius - length;
+    if depth <= 0.0 {
+        None
+    } else if length > EPSILON {
+        Some(Contact {
+            normal: distance / length,
+            depth,
+        })
+    } else {
+        Some(Contact {
+            normal: Vector3::y(),
+            depth,
+        })
+    }
+}
+
+/// Tests the sphere along a capsule's segment which is closest to the other shape.
+fn capsule_contact(
+    start: &Point3<f32>,
+    end: &Point3<f32>,
+    radius: f32,
+    other: &WorldShape,
+) -> Option<Contact> {
+    match *other {
+        WorldShape::Sphere {
+            ref center,
+            radius: other_radius,
+        } => {
+            let point = closest_point_on_segment(center, start, end);
+            sphere_sphere(&point, radius, center, other_radius)
+        }
+        WorldShape::Capsule {
+            start: ref other_start,
+            end: ref other_end,
+            radius: other_radius,
+        } => {
+            let (point, other_point) =
+                closest_points_on_segments(start, end, other_start, other_end);
+            sphere_sphere(&point, radius, &other_point, other_radius)
+        }
+        WorldShape::Cuboid { .. } => {
+            let point =
+                closest_point_on_segment_to(start, end, |p| closest_point_on_cuboid(p, other).0);
+            sphere_cuboid(&point, radius, other)
+        }
+        WorldShape::TriMesh { ref triangles, .. } => {
+            let (min, max) = WorldShape::Capsule {
+                start: *start,
+                end: *end,
+                radius,
+            }
+            .bounds();
+            let mut deepest: Option<Contact> = None;
+            for triangle in triangles.iter() {
+                let outside = (0..3).any(|i| {
+                    triangle.iter().all(|p| p[i] < min[i]) || triangle.iter().all(|p| p[i] > max[i])
+                });
+                if outside {
+                    continue;
+                }
+                let point = closest_point_on_segment_to(start, end, |p| {
+                    closest_point_on_triangle(p, triangle)
+                });
+                if let Some(contact) = sphere_triangles(&point, radius, &[*triangle]) {
+                    if deepest.as_ref().map_or(true, |c| contact.depth > c.depth) {
+                        deepest = Some(contact);
+                    }
+                }
+            }
+            deepest
+        }
+    }
+}
+
+/// Returns the point of the box closest to `point`, and whether `point` is inside the box.
+fn closest_point_on_cuboid(point: &Point3<f32>, cuboid: &WorldShape) -> (Point3<f32>, bool) {
+    let (center, axes, half_extents) = match *cuboid {
+        WorldShape::Cuboid {
+            center,
+            ref axes,
+            half_extents,
+        } => (center, axes, half_extents),
+        _ => return (*point, false),
+    };
+    let local = point - center;
+    let mut closest = center;
+    let mut inside = true;
+    for i in 0..3 {
+        let distance = local.dot(&axes[i]);
+        let clamped = distance.max(-half_extents[i]).min(half_extents[i]);
+        inside &= distance == clamped;
+        closest += axes[i] * clamped;
+    }
+    (closest, inside)
+}
+
+fn sphere_cuboid(center: &Point3<f32>, radius: f32, cuboid: &WorldShape) -> Option<Contact> {
+    let (box_center, axes, half_extents) = match *cuboid {
+        WorldShape::Cuboid {
+            center,
+            ref axes,
+            half_extents,
+        } => (center, axes, half_extents),
+        _ => return None,
+    };
+    let local = center - box_center;
+    let (closest, inside) = closest_point_on_cuboid(center, cuboid);
+    if !inside {
+        let distance = closest - center;
+        let length = distance.norm();
+        return if length < radius {
+            Some(Contact {
+                normal: distance / length,
+                depth: radius - length,
+            })
+        } else {
+            None
+        };
+    }
+
+    // The center is inside the box, so the sphere is pushed out through the nearest face.
+    let (axis, distance) = (0..3)
+        .map(|i| (i, half_extents[i] - local.dot(&axes[i]).abs()))
+        .fold((0, std::f32::MAX), |best, next| {
+            if next.1 < best.1 {
+                next
+            } else {
+                best
+            }
+        });
+    let sign = if local.dot(&axes[axis]) < 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+    Some(Contact {
+        normal: axes[axis] * sign,
+        depth: radius + distance,
+    })
+}
+
+fn sphere_triangles(
+    center: &Point3<f32>,
+    radius: f32,
+    triangles: &[[Point3<f32>; 3]],
+) -> Option<Contact> {
+    let mut deepest: Option<Contact> = None;
+    for triangle in triangles {
+        let distance = closest_point_on_triangle(center, triangle) - center;
+        let length = distance.norm();
+        if length >= radius
+            || deepest
+                .as_ref()
+                .map_or(false, |c| radius - length <= c.depth)
+        {
+            continue;
+        }
+        let normal = if length > EPSILON {
+            distance / length
+        } else {
+            // The center lies in the triangle, the triangle is taken to be below it.
+            -(triangle[1] - triangle[0])
+                .cross(&(triangle[2] - triangle[0]))
+                .try_normalize(EPSILON)
+                .unwrap_or_else(Vector3::y)
+        };
+        deepest = Some(Contact {
+            normal,
+            depth: radius - length,
+        });
+    }
+    deepest
+}
+
+pub(crate) fn cuboid_triangles(
+    cuboid: &WorldShape,
+    triangles: &[[Point3<f32>; 3]],
+) -> Option<Contact> {
+    let axes_of_box = match *cuboid {
+        WorldShape::Cuboid { ref axes, .. } => axes,
+        _ => return None,
+    };
+    let (min, max) = cuboid.bounds();
+    let points = corners(cuboid);
+    let mut deepest: Option<Contact> = None;
+    for triangle in triangles {
+        let outside = (0..3).any(|i| {
+            triangle.iter().all(|p| p[i] < min[i]) || triangle.iter().all(|p| p[i] > max[i])
+        });
+        if outside {
+            continue;
+        }
+        let edges = [
+            triangle[1] - triangle[0],
+            triangle[2] - triangle[1],
+            triangle[0] - triangle[2],
+        ];
+        let mut axes = Vec::with_capacity(13);
+        axes.extend_from_slice(axes_of_box);
+        axes.push(edges[0].cross(&edges[1]));
+        for axis in axes_of_box {
+            for edge in &edges {
+                axes.push(axis.cross(edge));
+            }
+        }
+        if let Some(contact) = separating_axes(&points, triangle, &axes) {
+            if deepest.as_ref().map_or(true, |c| contact.depth > c.depth) {
+                deepest = Some(contact);
+            }
+        }
+    }
+    deepest
+}
+
+fn corners(cuboid: &WorldShape) -> Vec<Point3<f32>> {
+    match *cuboid {
+        WorldShape::Cuboid {
+            center,
+            ref axes,
+            half_extents,
+        } => (0..8)
+            .map(|i| {
+                let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                center
+                    + axes[0] * (half_extents[0] * sign(1))
+                    + axes[1] * (half_extents[1] * sign(2))
+                    + axes[2] * (half_extents[2] * sign(4))
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Tests two convex point sets for overlap on each axis, returning the axis of least overlap.
+fn separating_axes(a: &[Point3<f32>], b: &[Point3<f32>], axes: &[Vector3<f32>]) -> Option<Contact> {
+    let project = |points: &[Point3<f32>], axis: &Vector3<f32>| {
+        points
+            .iter()
+            .map(|p| p.coords.dot(axis))
+            .fold((std::f32::MAX, std::f32::MIN), |(min, max), d| {
+                (min.min(d), max.max(d))
+            })
+    };
+    let mut best: Option<Contact> = None;
+    for axis in axes {
+        let axis = match axis.try_normalize(EPSILON) {
+            Some(axis) => axis,
+            // Parallel edges don't give an axis.
+            None => continue,
+        };
+        let (a_min, a_max) = project(a, &axis);
+        let (b_min, b_max) = project(b, &axis);
+        // How far `a` has to move back along the axis, or forward, to separate the sets.
+        let (forward, backward) = (a_max - b_min, b_max - a_min);
+        if forward <= 0.0 || backward <= 0.0 {
+            return None;
+        }
+        let (normal, depth) = if forward <= backward {
+            (axis, forward)
+        } else {
+            (-axis, backward)
+        };
+        if best.as_ref().map_or(true, |best| depth < best.depth) {
+            best = Some(Contact { normal, depth });
+        }
+    }
+    best
+}
+
+fn closest_point_on_segment(
+    point: &Point3<f32>,
+    start: &Point3<f32>,
+    end: &Point3<f32>,
+) -> Point3<f32> {
+    let direction = end - start;
+    let length = direction.norm_squared();
+    if length <= EPSILON {
+        return *start;
+    }
+    let t = (point - start).dot(&direction) / length;
+    start + direction * t.max(0.0).min(1.0)
+}
+
+/// Finds the point of a segment closest to a convex shape, by projecting back and forth between
+/// them.
+fn closest_point_on_segment_to<F>(start: &Point3<f32>, end: &Point3<f32>, closest: F) -> Point3<f32>
+where
+    F: Fn(&Point3<f32>) -> Point3<f32>,
+{
+    let mut point = start + (end - start) * 0.5;
+    for _ in 0..8 {
+        let next = closest_point_on_segment(&closest(&point), start, end);
+        let moved = (next - point).norm_squared();
+        point = next;
+        if moved <= EPSILON * EPSILON {
+            break;
+        }
+    }
+    point
+}
+
+/// Finds the closest points of two segments, from "Real-Time Collision Detection".
+fn closest_points_on_segments(
+    p1: &Point3<f32>,
+    q1: &Point3<f32>,
+    p2: &Point3<f32>,
+    q2: &Point3<f32>,
+) -> (Point3<f32>, Point3<f32>) {
+    let clamp = |x: f32| x.max(0.0).min(1.0);
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.norm_squared();
+    let e = d2.norm_squared();
+    let f = d2.dot(&r);
+    if a <= EPSILON && e <= EPSILON {
+        return (*p1, *p2);
+    }
+    let (s, t) = if a <= EPSILON {
+        (0.0, clamp(f / e))
+    } else {
+        let c = d1.dot(&r);
+        if e <= EPSILON {
+            (clamp(-c / a), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denom = a * e - b * b;
+            let s = if denom > EPSILON {
+                clamp((b * f - c * e) / denom)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                (clamp(-c / a), 0.0)
+            } else if t > 1.0 {
+                (clamp((b - c) / a), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+/// Finds the point of a triangle closest to `p`, from "Real-Time Collision Detection".
+pub(crate) fn closest_point_on_triangle(
+    p: &Point3<f32>,
+    triangle: &[Point3<f32>; 3],
+) -> Point3<f32> {
+    let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere(x: f32, radius: f32) -> WorldShape {
+        ColliderShape::Sphere { radius }.to_world(
+            &Matrix4::new_translation(&Vector3::new(x, 0.0, 0.0)),
+            &Vector3::zeros(),
+        )
+    }
+
+    fn cuboid(x: f32, y: f32) -> WorldShape {
+        ColliderShape::Cuboid {
+            half_extents: Vector3::from_element(1.0),
+        }
+        .to_world(
+            &Matrix4::new_translation(&Vector3::new(x, y, 0.0)),
+            &Vector3::zeros(),
+        )
+    }
+
+    #[test]
+    fn spheres_and_cuboids() {
+        let c = contact(&sphere(0.0, 1.0), &sphere(1.5, 1.0)).unwrap();
+        assert_eq!(c.normal, Vector3::x());
+        assert!((c.depth - 0.5).abs() < 1e-5);
+        assert_eq!(contact(&sphere(0.0, 1.0), &sphere(2.5, 1.0)), None);
+
+        let c = contact(&cuboid(0.0, 0.0), &sphere(1.5, 1.0)).unwrap();
+        assert_eq!(c.normal, Vector3::x());
+        assert!((c.depth - 0.5).abs() < 1e-5);
+
+        let c = contact(&cuboid(0.0, 1.5), &cuboid(0.0, 0.0)).unwrap();
+        assert_eq!(c.normal, -Vector3::y());
+        assert!((c.depth - 0.5).abs() < 1e-5);
+        assert_eq!(contact(&cuboid(0.0, 0.0), &cuboid(2.5, 0.0)), None);
+    }
+
+    #[test]
+    fn meshes() {
+        let floor = ColliderShape::TriMesh(Arc::new(TriMesh {
+            positions: vec![
+                Point3::new(-5.0, 0.0, -5.0),
+                Point3::new(5.0, 0.0, -5.0),
+                Point3::new(0.0, 0.0, 5.0),
+            ],
+            indices: vec![[0, 2, 1]],
+        }))
+        .to_world(&Matrix4::identity(), &Vector3::zeros());
+
+        let above = ColliderShape::Sphere { radius: 1.0 }
+            .to_world(&Matrix4::new_translation(&Vector3::y()), &Vector3::zeros());
+        assert_eq!(contact(&floor, &above), None);
+
+        let c = contact(&cuboid(0.0, 0.75), &floor).unwrap();
+        assert_eq!(c.normal, -Vector3::y());
+        assert!((c.depth - 0.25).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,918 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_core::{
+    ecs::prelude::{
+        Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, Write,
+        WriteStorage,
+    },
+    math::{Matrix4, Point3, UnitQuaternion, Vector3},
+    shrev::EventChannel,
+    timing::Time,
+    transform::{Parent, Transform},
+    Ray,
+};
+use amethyst_gltf::{GltfCollisionGeometry, GltfExtras, GltfNodeExtent};
+
+use crate::{
+    components::{
+        trigger_tag, AutoCollider, BodyType, CharacterController, Collider, RigidBody,
+        TriggerTracked, TriggerVolume,
+    },
+    query::ray_triangle,
+    resources::{CollisionEvent, PhysicsSettings, TriggerEvent},
+    shape::{contact, ColliderShape, Contact, ShapeCache, TriMesh, WorldShape},
+};
+
+/// How deep colliders may overlap before they are pushed apart, which keeps resting contacts
+/// from jittering.
+const SLOP: f32 = 0.005;
+/// The part of the overlap removed each step.
+const CORRECTION: f32 = 0.8;
+const EPSILON: f32 = 1e-6;
+/// The longest frame a character is moved by.
+const MAX_CHARACTER_DELTA: f32 = 0.1;
+/// The most steps a character moves by in one slide.
+const MAX_SLIDE_STEPS: f32 = 32.0;
+/// How many times a character is pushed out of obstacles after each step.
+const DEPENETRATION_ITERATIONS: usize = 4;
+
+/// Moves the `RigidBody`s in fixed steps of `PhysicsSettings::timestep`, pushes their
+/// `Collider`s out of each other and writes the results to their `Transform`s.
+///
+/// Contacts only push bodies, they don't make them spin. `CollisionEvent`s are sent when two
+/// colliders start or stop touching, for pairs where one of them has a moving body.
+#[derive(Default)]
+pub struct PhysicsSystem {
+    settings: Option<PhysicsSettings>,
+    accumulator: f32,
+    contacts: HashSet<(Entity, Entity)>,
+    shapes: ShapeCache,
+}
+
+impl PhysicsSystem {
+    /// Creates a `PhysicsSystem`, inserting the `settings` resource on setup.
+    pub fn new(settings: PhysicsSettings) -> Self {
+        PhysicsSystem {
+            settings: Some(settings),
+            ..Default::default()
+        }
+    }
+}
+
+impl<'a> System<'a> for PhysicsSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, PhysicsSettings>,
+        Write<'a, EventChannel<CollisionEvent>>,
+        ReadStorage<'a, Parent>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, RigidBody>,
+        WriteStorage<'a, Transform<f32>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            settings,
+            mut events,
+            parents,
+            colliders,
+            mut rigid_bodies,
+            mut transforms,
+        ): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("physics_system");
+
+        if settings.timestep <= 0.0 {
+            return;
+        }
+        self.accumulator += time.delta_seconds();
+        let mut steps = (self.accumulator / settings.timestep) as u32;
+        self.accumulator -= steps as f32 * settings.timestep;
+        if steps > settings.max_substeps {
+            steps = settings.max_substeps;
+            self.accumulator = 0.0;
+        }
+        if steps == 0 {
+            return;
+        }
+        self.shapes.maintain(&entities);
+
+        let mut bodies = (&entities, &rigid_bodies, &transforms)
+            .join()
+            .filter(|(_, body, _)| body.body_type != BodyType::Static)
+            .map(|(entity, body, transform)| Body {
+                entity,
+                body_type: body.body_type,
+                inverse_mass: body.inverse_mass(),
+                translation: *transform.translation(),
+                rotation: *transform.rotation(),
+                scale: *transform.scale(),
+                parent: parents
+                    .get(entity)
+                    .and_then(|parent| transforms.get(parent.entity))
+                    .map(|parent| *parent.global_matrix())
+                    .unwrap_or_else(Matrix4::identity),
+                velocity: body.velocity,
+                angular_velocity: body.angular_velocity,
+                linear_damping: body.linear_damping,
+                angular_damping: body.angular_damping,
+                gravity_scale: body.gravity_scale,
+            })
+            .collect::<Vec<_>>();
+        let moving = bodies
+            .iter()
+            .enumerate()
+            .filter_map(|(index, body)| Some((index, colliders.get(body.entity)?)))
+            .collect::<Vec<_>>();
+
+        let mut fixed = Vec::new();
+        for (entity, collider) in (&entities, &colliders).join() {
+            if bodies.iter().any(|body| body.entity == entity) {
+                continue;
+            }
+            let matrix = transforms
+                .get(entity)
+                .map(|transform| *transform.global_matrix())
+                .unwrap_or_else(Matrix4::identity);
+            let shape = self.shapes.place(entity, collider, &matrix);
+            fixed.push(Shape::new(entity, None, collider, shape));
+        }
+
+        let mut touching = HashMap::new();
+        for _ in 0..steps {
+            for body in &mut bodies {
+                body.integrate(&settings);
+            }
+            let mut placed = Vec::with_capacity(moving.len());
+            for &(index, collider) in &moving {
+                let entity = bodies[index].entity;
+                let shape = self.shapes.place(entity, collider, &bodies[index].matrix());
+                placed.push(Shape::new(entity, Some(index), collider, shape));
+            }
+
+            // Sweeps the shapes along the x axis, so only shapes overlapping on it are tested.
+            let mut sorted = fixed.iter().chain(placed.iter()).collect::<Vec<_>>();
+            sorted.sort_by(|a, b| a.min.x.partial_cmp(&b.min.x).unwrap_or(Ordering::Equal));
+            for (i, a) in sorted.iter().enumerate() {
+                for b in &sorted[i + 1..] {
+                    if b.min.x > a.max.x {
+                        break;
+                    }
+                    if (a.body.is_none() && b.body.is_none())
+                        || a.entity == b.entity
+                        || (1..3).any(|k| a.min[k] > b.max[k] || b.min[k] > a.max[k])
+                    {
+                        continue;
+                    }
+                    if let Some(contact) = contact(&a.shape, &b.shape) {
+                        let sensor = a.sensor || b.sensor;
+                        let pair = if a.entity < b.entity {
+                            (a.entity, b.entity)
+                        } else {
+                            (b.entity, a.entity)
+                        };
+                        touching.insert(pair, sensor);
+                        if !sensor {
+                            resolve(&mut bodies, a, b, &contact);
+                        }
+                    }
+                }
+            }
+        }
+
+        for body in &bodies {
+            if let Some(transform) = transforms.get_mut(body.entity) {
+                if *transform.translation() != body.translation {
+                    *transform.translation_mut() = body.translation;
+                }
+                if *transform.rotation() != body.rotation {
+                    *transform.rotation_mut() = body.rotation;
+                }
+            }
+            if let Some(rigid_body) = rigid_bodies.get_mut(body.entity) {
+                rigid_body.velocity = body.velocity;
+                rigid_body.angular_velocity = body.angular_velocity;
+            }
+        }
+
+        for (&(a, b), &sensor) in &touching {
+            if !self.contacts.contains(&(a, b)) {
+                events.single_write(CollisionEvent::Started { a, b, sensor });
+            }
+        }
+        for &(a, b) in &self.contacts {
+            if !touching.contains_key(&(a, b)) {
+                events.single_write(CollisionEvent::Stopped { a, b });
+            }
+        }
+        self.contacts = touching.keys().cloned().collect();
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        if let Some(settings) = self.settings.take() {
+            res.insert(settings);
+        }
+    }
+}
+
+/// Moves the entities with a `CharacterController` by their `movement`, sliding along the
+/// `Collider`s of other entities and falling with the gravity of the `PhysicsSettings`.
+///
+/// Characters are moved by the translation of their `Transform`, so they should be top level
+/// entities. Give a character a kinematic `RigidBody` and a capsule `Collider` as well for
+/// dynamic bodies to be pushed by it.
+#[derive(Default)]
+pub struct CharacterControllerSystem {
+    shapes: ShapeCache,
+}
+
+impl<'a> System<'a> for CharacterControllerSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, PhysicsSettings>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, CharacterController>,
+        WriteStorage<'a, Transform<f32>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, time, settings, colliders, mut controllers, mut transforms): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("character_controller_system");
+
+        // Long frames are slowed down, so characters don't fall through floors.
+        let dt = time.delta_seconds().min(MAX_CHARACTER_DELTA);
+        if dt <= 0.0 {
+            return;
+        }
+        self.shapes.maintain(&entities);
+
+        let mut obstacles = Vec::new();
+        for (entity, collider) in (&entities, &colliders).join() {
+            if collider.sensor {
+                continue;
+            }
+            let matrix = transforms
+                .get(entity)
+                .map(|transform| *transform.global_matrix())
+                .unwrap_or_else(Matrix4::identity);
+            let shape = self.shapes.place(entity, collider, &matrix);
+            let (min, max) = shape.bounds();
+            obstacles.push((entity, shape, min, max));
+        }
+
+        for (entity, controller, transform) in (&entities, &mut controllers, &mut transforms).join()
+        {
+            if controller.is_grounded() && controller.vertical_velocity < 0.0 {
+                controller.vertical_velocity = 0.0;
+            }
+            controller.vertical_velocity += settings.gravity.y * dt;
+            let walk = Vector3::new(controller.movement.x, 0.0, controller.movement.z) * dt;
+            let fall = controller.vertical_velocity * dt;
+
+            // Only the obstacles within reach of this move are tested.
+            let mut position = *transform.translation();
+            let reach = walk.norm() + fall.abs() + controller.step_offset;
+            let (min, max) = capsule(controller, &position).bounds();
+            let near = obstacles
+                .iter()
+                .filter(|(other, _, other_min, other_max)| {
+                    *other != entity
+                        && (0..3).all(|i| {
+                            other_min[i] <= max[i] + reach && other_max[i] >= min[i] - reach
+                        })
+                })
+                .map(|(_, shape, _, _)| shape)
+                .collect::<Vec<_>>();
+
+            let was_grounded = controller.is_grounded();
+            let mut climbed = 0.0;
+            if was_grounded && controller.step_offset > 0.0 && walk.norm_squared() > 0.0 {
+                let start = position.y;
+                slide(
+                    &mut position,
+                    Vector3::y() * controller.step_offset,
+                    controller,
+                    &near,
+                    false,
+                );
+                climbed = position.y - start;
+            }
+            slide(&mut position, walk, controller, &near, true);
+            let vertical = slide(
+                &mut position,
+                Vector3::y() * (fall - climbed),
+                controller,
+                &near,
+                false,
+            );
+            let mut ground = vertical.ground;
+            if vertical.ceiling && controller.vertical_velocity > 0.0 {
+                controller.vertical_velocity = 0.0;
+            }
+
+            // Snaps characters walking down slopes and stairs to the ground, unless there is no
+            // ground within a step.
+            if ground.is_none() && was_grounded && fall <= 0.0 && controller.step_offset > 0.0 {
+                let mut snapped = position;
+                let snap = slide(
+                    &mut snapped,
+                    -Vector3::y() * controller.step_offset,
+                    controller,
+                    &near,
+                    false,
+                );
+                if snap.ground.is_some() {
+                    position = snapped;
+                    ground = snap.ground;
+                }
+            }
+
+            controller.set_ground(ground);
+            if *transform.translation() != position {
+                *transform.translation_mut() = position;
+            }
+        }
+    }
+}
+
+/// What a character touched while sliding.
+struct Slide {
+    /// The normal of the walkable ground touched.
+    ground: Option<Vector3<f32>>,
+    /// Whether something above the character was touched.
+    ceiling: bool,
+}
+
+/// Returns the capsule of a character with its feet at `position`.
+fn capsule(controller: &CharacterController, position: &Vector3<f32>) -> WorldShape {
+    let radius = controller.radius;
+    let length = (controller.height - radius * 2.0).max(0.0);
+    WorldShape::Capsule {
+        start: Point3::from(position + Vector3::y() * radius),
+        end: Point3::from(position + Vector3::y() * (radius + length)),
+        radius,
+    }
+}
+
+/// Moves a character by `displacement` in steps of half its radius, pushing it out of the
+/// obstacles after each step and removing the part of the move going into them.
+///
+/// When `walking`, slopes too steep to walk up are treated as vertical walls, so walking into
+/// them doesn't push the character up.
+fn slide(
+    position: &mut Vector3<f32>,
+    displacement: Vector3<f32>,
+    controller: &CharacterController,
+    obstacles: &[&WorldShape],
+    walking: bool,
+) -> Slide {
+    let mut result = Slide {
+        ground: None,
+        ceiling: false,
+    };
+    let length = displacement.norm();
+    if length <= EPSILON {
+        return result;
+    }
+    let min_ground_y = controller.max_slope.cos();
+    let steps = (length / (controller.radius * 0.5).max(EPSILON))
+        .ceil()
+        .max(1.0)
+        .min(MAX_SLIDE_STEPS) as usize;
+    let mut step = displacement / steps as f32;
+    for _ in 0..steps {
+        *position += step;
+        for _ in 0..DEPENETRATION_ITERATIONS {
+            let shape = capsule(controller, position);
+            let deepest = obstacles
+                .iter()
+                .filter_map(|obstacle| contact(&shape, obstacle))
+                .fold(None, |deepest: Option<Contact>, contact| match deepest {
+                    Some(ref deepest) if deepest.depth >= contact.depth => Some(deepest.clone()),
+                    _ => Some(contact),
+                });
+            let contact = match deepest {
+                Some(contact) => contact,
+                None => break,
+            };
+
+            // The contact normal points from the character to the obstacle.
+            let normal = -contact.normal;
+            if normal.y >= min_ground_y {
+                result.ground = Some(normal);
+            } else if normal.y < -0.5 {
+                result.ceiling = true;
+            }
+            let wall = Vector3::new(normal.x, 0.0, normal.z).try_normalize(EPSILON);
+            let (push, normal) = match wall {
+                Some(wall) if walking && normal.y < min_ground_y => {
+                    (wall * (contact.depth / wall.dot(&normal).max(0.1)), wall)
+                }
+                _ => (normal * contact.depth, normal),
+            };
+            *position += push;
+            let into = step.dot(&normal);
+            if into < 0.0 {
+                step -= normal * into;
+            }
+        }
+    }
+    result
+}
+
+/// Makes the `Collider`s of entities with an `AutoCollider` once their glTF data is loaded, and
+/// static mesh colliders for the `GltfCollisionGeometry` of entities without either.
+///
+/// Entities that already have a `Collider` are left alone, and so are glTF nodes tagged as
+/// triggers, which get a `TriggerVolume` instead.
+pub struct ColliderGenerationSystem {
+    gltf_colliders: bool,
+}
+
+impl ColliderGenerationSystem {
+    /// Creates a `ColliderGenerationSystem`, `gltf_colliders` making colliders for all
+    /// `GltfCollisionGeometry`, such as the nodes picked by `GltfSceneOptions::collider_prefix`.
+    pub fn new(gltf_colliders: bool) -> Self {
+        ColliderGenerationSystem { gltf_colliders }
+    }
+}
+
+impl<'a> System<'a> for ColliderGenerationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, GltfNodeExtent>,
+        ReadStorage<'a, GltfCollisionGeometry>,
+        ReadStorage<'a, GltfExtras>,
+        ReadStorage<'a, AutoCollider>,
+        WriteStorage<'a, Collider>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, extents, geometry, extras, auto_colliders, mut colliders): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("collider_generation_system");
+
+        let mut generated = (&entities, &auto_colliders, !&colliders)
+            .join()
+            .filter_map(|(entity, auto_collider, _)| {
+                let collider = auto_collider.build(extents.get(entity), geometry.get(entity))?;
+                Some((entity, collider))
+            })
+            .collect::<Vec<_>>();
+        if self.gltf_colliders {
+            generated.extend(
+                (&entities, &geometry, !&auto_colliders, !&colliders)
+                    .join()
+                    .filter(|(entity, _, _, _)| extras.get(*entity).and_then(trigger_tag).is_none())
+                    .map(|(entity, geometry, _, _)| {
+                        let mesh = TriMesh::from(geometry);
+                        (
+                            entity,
+                            Collider::new(ColliderShape::TriMesh(Arc::new(mesh))),
+                        )
+                    }),
+            );
+        }
+        for (entity, collider) in generated {
+            colliders
+                .insert(entity, collider)
+                .expect("Unreachable: entities are alive");
+        }
+    }
+}
+
+/// Sends a `TriggerEvent` when an entity with a `TriggerTracked` enters or leaves a
+/// `TriggerVolume`, and makes the volumes of glTF nodes tagged as triggers once their data is
+/// loaded.
+///
+/// Volumes are placed by the global matrix of their entity's `Transform`, and can be moved.
+#[derive(Default)]
+pub struct TriggerSystem {
+    inside: HashSet<(Entity, Entity)>,
+    volumes: ShapeCache,
+    tracked: ShapeCache,
+}
+
+impl<'a> System<'a> for TriggerSystem {
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, EventChannel<TriggerEvent>>,
+        ReadStorage<'a, GltfNodeExtent>,
+        ReadStorage<'a, GltfCollisionGeometry>,
+        ReadStorage<'a, GltfExtras>,
+        ReadStorage<'a, TriggerTracked>,
+        ReadStorage<'a, Collider>,
+        ReadStorage<'a, CharacterController>,
+        ReadStorage<'a, Transform<f32>>,
+        WriteStorage<'a, TriggerVolume>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut events,
+            extents,
+            geometry,
+            extras,
+            tracked,
+            colliders,
+            controllers,
+            transforms,
+            mut volumes,
+        ): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("trigger_system");
+
+        let generated = (&entities, &extras, !&volumes)
+            .join()
+            .filter_map(|(entity, extras, _)| {
+                let volume = TriggerVolume::from_gltf(
+                    trigger_tag(extras)?,
+                    extents.get(entity),
+                    geometry.get(entity),
+                )?;
+                Some((entity, volume))
+            })
+            .collect::<Vec<_>>();
+        for (entity, volume) in generated {
+            volumes
+                .insert(entity, volume)
+                .expect("Unreachable: entities are alive");
+        }
+
+        self.volumes.maintain(&entities);
+        self.tracked.maintain(&entities);
+        let matrix = |entity| {
+            transforms
+                .get(entity)
+                .map(|transform| *transform.global_matrix())
+                .unwrap_or_else(Matrix4::identity)
+        };
+        let mut placed = Vec::new();
+        for (entity, volume) in (&entities, &volumes).join() {
+            let shape = self
+                .volumes
+                .place(entity, &volume.collider(), &matrix(entity));
+            let (min, max) = shape.bounds();
+            placed.push((entity, shape, min, max));
+        }
+
+        let mut inside = HashSet::new();
+        for (entity, _) in (&entities, &tracked).join() {
+            let matrix = matrix(entity);
+            let origin = matrix.transform_point(&Point3::origin());
+            let shape = match (colliders.get(entity), controllers.get(entity)) {
+                (Some(collider), _) => self.tracked.place(entity, collider, &matrix),
+                (None, Some(controller)) => capsule(controller, &origin.coords),
+                (None, None) => WorldShape::Sphere {
+                    center: origin,
+                    radius: 0.0,
+                },
+            };
+            let (min, max) = shape.bounds();
+            for (trigger, volume, volume_min, volume_max) in &placed {
+                let apart = (0..3).any(|i| min[i] > volume_max[i] || volume_min[i] > max[i]);
+                if *trigger != entity && !apart && overlaps(volume, &shape) {
+                    inside.insert((*trigger, entity));
+                }
+            }
+        }
+
+        for &(trigger, entity) in &inside {
+            if !self.inside.contains(&(trigger, entity)) {
+                events.single_write(TriggerEvent::Enter { trigger, entity });
+            }
+        }
+        for &(trigger, entity) in &self.inside {
+            if !inside.contains(&(trigger, entity)) {
+                events.single_write(TriggerEvent::Exit { trigger, entity });
+            }
+        }
+        self.inside = inside;
+    }
+}
+
+/// Returns whether a shape is in a trigger volume.
+///
+/// Meshes have no volume for `contact`, so the shapes which don't touch their triangles are in
+/// them if a ray from their center crosses the triangles an odd number of times.
+fn overlaps(volume: &WorldShape, shape: &WorldShape) -> bool {
+    if contact(volume, shape).is_some() {
+        return true;
+    }
+    match *volume {
+        WorldShape::TriMesh { ref triangles, .. } => {
+            let (min, max) = shape.bounds();
+            // Slanted, so the ray doesn't run along the edges of axis aligned meshes.
+            let ray = Ray::new(
+                (min.coords + max.coords) / 2.0,
+                Vector3::new(1.0, 0.013, 0.007),
+            );
+            let crossings = triangles
+                .iter()
+                .filter(|triangle| ray_triangle(&ray, triangle, std::f32::MAX).is_some())
+                .count();
+            crossings % 2 == 1
+        }
+        _ => false,
+    }
+}
+
+/// A body moved during the steps of one frame.
+struct Body {
+    entity: Entity,
+    body_type: BodyType,
+    inverse_mass: f32,
+    translation: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+    parent: Matrix4<f32>,
+    velocity: Vector3<f32>,
+    angular_velocity: Vector3<f32>,
+    linear_damping: f32,
+    angular_damping: f32,
+    gravity_scale: f32,
+}
+
+impl Body {
+    fn integrate(&mut self, settings: &PhysicsSettings) {
+        let dt = settings.timestep;
+        if self.body_type == BodyType::Dynamic {
+            self.velocity += settings.gravity * (self.gravity_scale * dt);
+            self.velocity /= 1.0 + dt * self.linear_damping;
+            self.angular_velocity /= 1.0 + dt * self.angular_damping;
+        }
+        self.translation += self.velocity * dt;
+        self.rotation =
+            UnitQuaternion::from_scaled_axis(self.angular_velocity * dt) * self.rotation;
+    }
+
+    fn matrix(&self) -> Matrix4<f32> {
+        self.parent
+            * Matrix4::new_translation(&self.translation)
+            * self.rotation.to_homogeneous()
+            * Matrix4::new_nonuniform_scaling(&self.scale)
+    }
+}
+
+/// A collider placed in the world for one step.
+struct Shape {
+    entity: Entity,
+    body: Option<usize>,
+    shape: WorldShape,
+    min: Point3<f32>,
+    max: Point3<f32>,
+    sensor: bool,
+    restitution: f32,
+    friction: f32,
+}
+
+impl Shape {
+    fn new(entity: Entity, body: Option<usize>, collider: &Collider, shape: WorldShape) -> Self {
+        let (min, max) = shape.bounds();
+        Shape {
+            entity,
+            body,
+            shape,
+            min,
+            max,
+            sensor: collider.sensor,
+            restitution: collider.restitution,
+            friction: collider.friction,
+        }
+    }
+}
+
+/// Pushes the bodies of two touching shapes apart and removes the velocity moving them into
+/// each other.
+fn resolve(bodies: &mut [Body], a: &Shape, b: &Shape, contact: &Contact) {
+    let state = |body: Option<usize>| {
+        body.map_or((0.0, Vector3::zeros()), |i| {
+            (bodies[i].inverse_mass, bodies[i].velocity)
+        })
+    };
+    let (a_weight, a_velocity) = state(a.body);
+    let (b_weight, b_velocity) = state(b.body);
+    let total = a_weight + b_weight;
+    if total <= 0.0 {
+        return;
+    }
+
+    let normal = contact.normal;
+    let correction = normal * ((contact.depth - SLOP).max(0.0) * CORRECTION / total);
+    let relative = b_velocity - a_velocity;
+    let speed = relative.dot(&normal);
+    let mut impulse = Vector3::zeros();
+    if speed < 0.0 {
+        let restitution = a.restitution.max(b.restitution);
+        let push = -(1.0 + restitution) * speed / total;
+        impulse = normal * push;
+        let sliding = relative - normal * speed;
+        if let Some(tangent) = sliding.try_normalize(EPSILON) {
+            let friction = a.friction.min(b.friction);
+            let slow = (sliding.norm() / total).min(push * friction);
+            impulse -= tangent * slow;
+        }
+    }
+
+    if let Some(i) = a.body {
+        bodies[i].translation -= correction * a_weight;
+        bodies[i].velocity -= impulse * a_weight;
+    }
+    if let Some(i) = b.body {
+        bodies[i].translation += correction * b_weight;
+        bodies[i].velocity += impulse * b_weight;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::ecs::prelude::{Builder, RunNow, World};
+
+    #[test]
+    fn bodies_fall_onto_colliders() {
+        let mut world = World::new();
+        let mut system = PhysicsSystem::new(PhysicsSettings::default());
+        System::setup(&mut system, &mut world.res);
+
+        let ground = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Collider::cuboid(Vector3::new(10.0, 1.0, 10.0)))
+            .build();
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_y(2.0);
+        let ball = world
+            .create_entity()
+            .with(transform)
+            .with(RigidBody::dynamic(1.0))
+            .with(Collider::sphere(0.5))
+            .build();
+        let mut reader = world
+            .write_resource::<EventChannel<CollisionEvent>>()
+            .register_reader();
+
+        for _ in 0..120 {
+            world.write_resource::<Time>().set_delta_seconds(1.0 / 60.0);
+            system.run_now(&world.res);
+        }
+
+        let height = world
+            .read_storage::<Transform<f32>>()
+            .get(ball)
+            .unwrap()
+            .translation()
+            .y;
+        assert!((height - 1.5).abs() < 0.05, "height {}", height);
+        let events = world
+            .read_resource::<EventChannel<CollisionEvent>>()
+            .read(&mut reader)
+            .cloned()
+            .collect::<Vec<_>>();
+        let (a, b) = if ground < ball {
+            (ground, ball)
+        } else {
+            (ball, ground)
+        };
+        assert_eq!(
+            events,
+            vec![CollisionEvent::Started {
+                a,
+                b,
+                sensor: false
+            }]
+        );
+    }
+
+    #[test]
+    fn characters_climb_steps() {
+        let mut world = World::new();
+        let mut system = CharacterControllerSystem::default();
+        System::setup(&mut system, &mut world.res);
+
+        let floor = Collider::cuboid(Vector3::new(10.0, 0.5, 10.0));
+        let step = Collider::cuboid(Vector3::new(5.0, 0.1, 10.0));
+        for collider in vec![
+            floor.with_offset(Vector3::new(0.0, -0.5, 0.0)),
+            step.with_offset(Vector3::new(6.0, 0.1, 0.0)),
+        ] {
+            world
+                .create_entity()
+                .with(Transform::<f32>::default())
+                .with(collider)
+                .build();
+        }
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_y(0.5);
+        let mut controller = CharacterController::new(0.3, 1.8);
+        controller.movement = Vector3::x();
+        let character = world
+            .create_entity()
+            .with(transform)
+            .with(controller)
+            .build();
+
+        for _ in 0..180 {
+            world.write_resource::<Time>().set_delta_seconds(1.0 / 60.0);
+            system.run_now(&world.res);
+        }
+
+        let transforms = world.read_storage::<Transform<f32>>();
+        let position = transforms.get(character).unwrap().translation();
+        assert!(position.x > 2.5, "position {:?}", position);
+        assert!((position.y - 0.2).abs() < 0.02, "position {:?}", position);
+        let controllers = world.read_storage::<CharacterController>();
+        assert!(controllers.get(character).unwrap().is_grounded());
+    }
+
+    #[test]
+    fn triggers_send_enter_and_exit() {
+        let mut world = World::new();
+        let mut system = TriggerSystem::default();
+        System::setup(&mut system, &mut world.res);
+
+        let trigger = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(TriggerVolume::cuboid(Vector3::new(1.0, 1.0, 1.0)))
+            .build();
+        // Global matrices are only set by the `TransformSystem`, so the offset of the collider
+        // moves the entity.
+        let entity = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Collider::sphere(0.1))
+            .with(TriggerTracked)
+            .build();
+        let mut reader = world
+            .write_resource::<EventChannel<TriggerEvent>>()
+            .register_reader();
+
+        let mut events = Vec::new();
+        for &x in &[3.0, 0.5, 0.0, 3.0] {
+            world
+                .write_storage::<Collider>()
+                .get_mut(entity)
+                .unwrap()
+                .offset = Vector3::new(x, 0.0, 0.0);
+            system.run_now(&world.res);
+            events.extend(
+                world
+                    .read_resource::<EventChannel<TriggerEvent>>()
+                    .read(&mut reader)
+                    .cloned(),
+            );
+        }
+        assert_eq!(
+            events,
+            vec![
+                TriggerEvent::Enter { trigger, entity },
+                TriggerEvent::Exit { trigger, entity },
+            ]
+        );
+    }
+
+    #[test]
+    fn mesh_triggers_contain_points() {
+        let mesh = TriMesh {
+            positions: vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(4.0, 0.0, 0.0),
+                Point3::new(0.0, 4.0, 0.0),
+                Point3::new(0.0, 0.0, 4.0),
+            ],
+            indices: vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]],
+        };
+        let volume = ColliderShape::TriMesh(Arc::new(mesh))
+            .to_world(&Matrix4::identity(), &Vector3::zeros());
+        let point = |x| WorldShape::Sphere {
+            center: Point3::new(x, 0.5, 0.5),
+            radius: 0.0,
+        };
+        assert!(overlaps(&volume, &point(0.5)));
+        assert!(!overlaps(&volume, &point(5.0)));
+    }
+}
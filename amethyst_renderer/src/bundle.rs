@@ -1,16 +1,23 @@
 //! ECS rendering bundle
 
 use amethyst_assets::Processor;
-use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder, math::RealField};
+use amethyst_core::{
+    alga::general::SubsetOf,
+    bundle::{BundleBuilder, SystemBundle},
+    math::RealField,
+};
 use amethyst_error::{format_err, Error, ResultExt};
 use std::marker::PhantomData;
 
 use crate::{
     config::DisplayConfig,
+    cursor::CursorSystem,
+    morph::MorphSystem,
     pipe::{PipelineBuild, PolyPipeline},
+    shadow::LightSpaceSystem,
     sprite::SpriteSheet,
     sprite_visibility::SpriteVisibilitySortingSystem,
-    system::RenderSystem,
+    system::{pipeline_recovery, PipelineRecovery, RenderSystem},
     visibility::VisibilitySortingSystem,
     HideHierarchySystem,
 };
@@ -21,9 +28,17 @@ use crate::{
 /// Will also register asset contexts with the asset `Loader`, and add systems for merging
 /// `AssetFuture` into its related component.
 ///
+/// Will register the `CursorSystem`, with name `cursor_system`, applying the `CursorState` to
+/// the window.
+///
 /// Will register `TransparentSortingSystem`, with name `transparent_sorting_system` if sorting is
 /// requested.
 ///
+/// Will register the `LightSpaceSystem`, with name `light_space_system`, if shadows are
+/// requested.
+///
+/// Will register the `MorphSystem`, with name `morph_system`, if morphing is requested.
+///
 /// # Type Parameters:
 ///
 /// * `N`: `RealBound` (f32, f64)
@@ -37,8 +52,12 @@ where
     config: Option<DisplayConfig>,
     visibility_sorting: Option<&'a [&'a str]>,
     sprite_visibility_sorting: Option<&'a [&'a str]>,
+    shadows: Option<&'a [&'a str]>,
     sprite_sheet_processor_enabled: bool,
     hide_hierarchy_system_enabled: bool,
+    morph: Option<&'a [&'a str]>,
+    placeholders_enabled: bool,
+    recovery: Option<PipelineRecovery<P>>,
     _pd: PhantomData<N>,
 }
 
@@ -55,13 +74,18 @@ where
             config,
             visibility_sorting: None,
             sprite_visibility_sorting: None,
+            shadows: None,
             sprite_sheet_processor_enabled: false,
             hide_hierarchy_system_enabled: false,
+            morph: None,
+            placeholders_enabled: false,
+            recovery: None,
             _pd: PhantomData,
         }
     }
 
-    /// Enable transparent mesh sorting, with the given dependencies
+    /// Enable transparent mesh sorting and frustum culling, with the given dependencies, which
+    /// should include the "bounds_system" for the meshes with `WorldBounds` to be culled.
     pub fn with_visibility_sorting(mut self, dep: &'a [&'a str]) -> Self {
         self.visibility_sorting = Some(dep);
         self
@@ -73,6 +97,13 @@ where
         self
     }
 
+    /// Enable the `LightSpaceSystem`, with the given dependencies, for passes drawing shadows
+    /// with `DrawShadowMap`.
+    pub fn with_shadows(mut self, dep: &'a [&'a str]) -> Self {
+        self.shadows = Some(dep);
+        self
+    }
+
     /// Enable the sprite sheet processor
     ///
     /// If you load a `SpriteSheet` in memory as an asset `Format`, this adds the `Processor` that
@@ -88,15 +119,42 @@ where
         self.hide_hierarchy_system_enabled = true;
         self
     }
+
+    /// Enable the `MorphSystem`, with the given dependencies, which should include the systems
+    /// animating the `MorphWeights`.
+    pub fn with_morph_system(mut self, dep: &'a [&'a str]) -> Self {
+        self.morph = Some(dep);
+        self
+    }
+
+    /// Replace meshes and textures that fail to load with placeholders, instead of failing the
+    /// load (and any prefab using them).
+    ///
+    /// See [`RenderSystem::with_placeholders`](struct.RenderSystem.html#method.with_placeholders).
+    pub fn with_placeholders(mut self) -> Self {
+        self.placeholders_enabled = true;
+        self
+    }
+
+    /// Recreate the renderer when the graphics device is lost, instead of panicking.
+    ///
+    /// See [`RenderSystem::with_device_recovery`](struct.RenderSystem.html#method.with_device_recovery).
+    pub fn with_device_recovery(mut self) -> Self
+    where
+        B: Clone + 'static,
+    {
+        self.recovery = Some(pipeline_recovery(self.pipe.clone()));
+        self
+    }
 }
 
 impl<'a, 'b, 'c, B, P, N> SystemBundle<'a, 'b> for RenderBundle<'c, B, P, N>
 where
     B: PipelineBuild<Pipeline = P>,
     P: 'b + PolyPipeline,
-    N: RealField + Default,
+    N: RealField + SubsetOf<f32> + Default,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         if let Some(dep) = self.visibility_sorting {
             builder.add(
                 VisibilitySortingSystem::<N>::new(),
@@ -111,6 +169,9 @@ where
                 dep,
             );
         };
+        if let Some(dep) = self.shadows {
+            builder.add(LightSpaceSystem::<N>::new(), "light_space_system", dep);
+        }
         if self.sprite_sheet_processor_enabled {
             builder.add(
                 Processor::<SpriteSheet>::new(),
@@ -125,9 +186,16 @@ where
                 &["parent_hierarchy_system"],
             );
         }
+        if let Some(dep) = self.morph {
+            builder.add(MorphSystem::default(), "morph_system", dep);
+        }
+        builder.add(CursorSystem::new(), "cursor_system", &[]);
         builder.add_thread_local(
             RenderSystem::build(self.pipe, self.config)
-                .with_context(|_| format_err!("Renderer error!"))?,
+                .with_context(|_| format_err!("Renderer error!"))?
+                .with_placeholders(self.placeholders_enabled)
+                .with_recovery(self.recovery),
+            "render_system",
         );
         Ok(())
     }
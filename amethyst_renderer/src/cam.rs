@@ -2,7 +2,7 @@
 
 use amethyst_assets::PrefabData;
 use amethyst_core::{
-    ecs::prelude::{Component, Entity, HashMapStorage, Write, WriteStorage},
+    ecs::prelude::{Component, DenseVecStorage, Entity, HashMapStorage, Write, WriteStorage},
     math::{Matrix4, Orthographic3, Perspective3, Point2, Point3},
 };
 use amethyst_error::Error;
@@ -113,6 +113,29 @@ pub struct ActiveCamera {
     pub entity: Option<Entity>,
 }
 
+/// Makes a camera draw the stages rendering into the named target instead of the `ActiveCamera`,
+/// for example a top-down view rendered into a texture for a minimap.
+///
+/// Cameras with this component are never used as the fallback when there is no active camera.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TargetCamera {
+    /// The name of the target, as given to `Target::named`.
+    pub target: String,
+}
+
+impl TargetCamera {
+    /// Creates a `TargetCamera` for the target with this name.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        TargetCamera {
+            target: target.into(),
+        }
+    }
+}
+
+impl Component for TargetCamera {
+    type Storage = DenseVecStorage<Self>;
+}
+
 /// Projection prefab
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum CameraPrefab {
@@ -54,7 +54,12 @@ pub struct DisplayConfig {
     /// Enables or disables vertical synchronization.
     pub vsync: bool,
 
-    /// Level of MSAA anti-aliasing.
+    /// Level of MSAA anti-aliasing, the number of samples per pixel of the window: 2, 4, 8 or
+    /// 16. 0 and 1 disable it, and other counts are rounded down to a power of two, see
+    /// `samples`. Only the OpenGL backend supports it.
+    ///
+    /// This only applies to the window. Offscreen targets are multisampled with
+    /// `TargetBuilder::with_samples`.
     pub multisampling: u16,
 
     /// Sets the visibility of the window.
@@ -105,6 +110,21 @@ impl Default for DisplayConfig {
 }
 
 impl DisplayConfig {
+    /// The most samples per pixel requested from the backend.
+    pub const MAX_SAMPLES: u16 = 16;
+
+    /// Returns the number of samples per pixel used for `multisampling`, 0 if it is disabled.
+    pub fn samples(&self) -> u16 {
+        match self.multisampling {
+            0 | 1 => 0,
+            samples => {
+                let samples = samples.min(Self::MAX_SAMPLES);
+                // Rounds down to a power of two.
+                1 << (15 - samples.leading_zeros())
+            }
+        }
+    }
+
     /// Creates a `WindowBuilder` using the values set in the DisplayConfig
     ///
     /// The `MonitorId` is needed to configure a fullscreen window
@@ -161,3 +181,25 @@ impl From<WindowBuilder> for DisplayConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DisplayConfig;
+
+    #[test]
+    fn samples_are_powers_of_two() {
+        let samples = |multisampling| {
+            DisplayConfig {
+                multisampling,
+                ..Default::default()
+            }
+            .samples()
+        };
+        assert_eq!(samples(0), 0);
+        assert_eq!(samples(1), 0);
+        assert_eq!(samples(2), 2);
+        assert_eq!(samples(6), 4);
+        assert_eq!(samples(8), 8);
+        assert_eq!(samples(64), 16);
+    }
+}
@@ -0,0 +1,164 @@
+//! Cubemap resource.
+
+use amethyst_assets::{Asset, Handle, ProcessingState};
+use amethyst_core::ecs::prelude::DenseVecStorage;
+use amethyst_error::{Error, ResultExt};
+
+use crate::{
+    error,
+    types::{Factory, RawShaderResourceView, RawTexture, Sampler},
+    Renderer,
+};
+
+/// A handle to a `Cubemap` asset.
+pub type CubemapHandle = Handle<Cubemap>;
+
+/// Handle to a GPU cube texture, with six square faces sampled by direction.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Cubemap {
+    sampler: Sampler,
+    texture: RawTexture,
+    view: RawShaderResourceView,
+}
+
+impl Cubemap {
+    /// Returns the sampler for the cubemap.
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    /// Returns the cubemap's raw shader resource view.
+    pub fn view(&self) -> &RawShaderResourceView {
+        &self.view
+    }
+
+    /// Returns the width and height of the faces of the largest level.
+    pub fn size(&self) -> usize {
+        let (w, _, _, _) = self.texture.get_info().kind.get_dimensions();
+        w as usize
+    }
+
+    /// Returns the number of mip levels.
+    pub fn levels(&self) -> u8 {
+        self.texture.get_info().levels
+    }
+
+    /// Returns an estimate of the GPU memory used by the cubemap in bytes, including all mip
+    /// levels.
+    pub fn byte_size(&self) -> usize {
+        let info = self.texture.get_info();
+        let bytes_per_texel = info.format.get_total_bits() as usize / 8;
+        (0..info.levels)
+            .map(|level| (self.size() >> level).max(1).pow(2))
+            .sum::<usize>()
+            * 6
+            * bytes_per_texel
+    }
+}
+
+impl Asset for Cubemap {
+    const NAME: &'static str = "renderer::Cubemap";
+    type Data = CubemapData;
+    type HandleStorage = DenseVecStorage<CubemapHandle>;
+}
+
+/// The texels of a cubemap in linear RGBA floats, as loaded by the `HdrFormat`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubemapData {
+    /// The width and height of the faces of the largest level.
+    pub size: u16,
+    /// The mip levels from the largest, each halving the size of the previous one, with the
+    /// faces ordered +X, -X, +Y, -Y, +Z, -Z. The texels of a face are stored row by row, from
+    /// the top row as seen from the center of the cube.
+    pub levels: Vec<[Vec<[f32; 4]>; 6]>,
+}
+
+impl CubemapData {
+    /// A cubemap of one texel per face, all of the same color.
+    pub(crate) fn solid(color: [f32; 4]) -> Self {
+        let face = vec![color];
+        CubemapData {
+            size: 1,
+            levels: vec![[
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face.clone(),
+                face,
+            ]],
+        }
+    }
+
+    /// Builds the cubemap, sampled with trilinear filtering.
+    pub fn build(&self, factory: &mut Factory) -> Result<Cubemap, Error> {
+        use gfx::{
+            format::{ChannelType, SurfaceType, Swizzle},
+            memory::{cast_slice, Bind, Usage},
+            texture::{FilterMethod, Info, Kind, Mipmap, ResourceDesc, SamplerInfo, WrapMode},
+            Factory,
+        };
+
+        for (level, faces) in self.levels.iter().enumerate() {
+            let size = (usize::from(self.size) >> level).max(1);
+            if let Some(face) = faces.iter().find(|face| face.len() != size * size) {
+                let error = format!(
+                    "Cubemap size mismatch: Expected {} texels per face on level {} (actual: {})",
+                    size * size,
+                    level,
+                    face.len()
+                );
+                return Err(error::Error::PixelDataMismatch(error).into());
+            }
+        }
+
+        let info = Info {
+            kind: Kind::Cube(self.size),
+            levels: self.levels.len() as u8,
+            format: SurfaceType::R32_G32_B32_A32,
+            bind: Bind::SHADER_RESOURCE,
+            usage: Usage::Data,
+        };
+        // The data of the levels of each face follow each other.
+        let data = (0..6)
+            .flat_map(|face| {
+                self.levels
+                    .iter()
+                    .map(move |level| cast_slice(&level[face]))
+            })
+            .collect::<Vec<&[u8]>>();
+        let texture = factory.create_texture_raw(
+            info,
+            Some(ChannelType::Float),
+            Some((&data, Mipmap::Provided)),
+        )?;
+
+        let desc = ResourceDesc {
+            channel: ChannelType::Float,
+            layer: None,
+            min: 0,
+            max: info.levels - 1,
+            swizzle: Swizzle::new(),
+        };
+        let view = factory.view_texture_as_shader_resource_raw(&texture, desc)?;
+        let sampler =
+            factory.create_sampler(SamplerInfo::new(FilterMethod::Trilinear, WrapMode::Clamp));
+
+        Ok(Cubemap {
+            sampler,
+            texture,
+            view,
+        })
+    }
+}
+
+/// Create a cubemap asset.
+pub fn create_cubemap_asset(
+    data: CubemapData,
+    renderer: &mut Renderer,
+) -> Result<ProcessingState<Cubemap>, Error> {
+    renderer
+        .create_cubemap(&data)
+        .with_context(|_| error::Error::BuildTextureError)
+        .map(ProcessingState::Loaded)
+}
@@ -0,0 +1,105 @@
+//! Resource and system controlling how the mouse cursor behaves and looks.
+
+use amethyst_core::ecs::prelude::{Read, System, Write};
+use log::error;
+use winit::MouseCursor;
+
+use crate::resources::WindowMessages;
+
+/// How the cursor interacts with the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorMode {
+    /// The cursor is visible and can leave the window.
+    Normal,
+    /// The cursor is invisible while over the window, but can leave it.
+    Hidden,
+    /// The cursor is visible, but confined to the window.
+    Confined,
+    /// The cursor is invisible and confined to the window, e.g. for mouse look.
+    Captured,
+}
+
+impl CursorMode {
+    fn hidden(self) -> bool {
+        match self {
+            CursorMode::Hidden | CursorMode::Captured => true,
+            CursorMode::Normal | CursorMode::Confined => false,
+        }
+    }
+
+    fn grabbed(self) -> bool {
+        match self {
+            CursorMode::Confined | CursorMode::Captured => true,
+            CursorMode::Normal | CursorMode::Hidden => false,
+        }
+    }
+}
+
+impl Default for CursorMode {
+    fn default() -> Self {
+        CursorMode::Normal
+    }
+}
+
+/// The requested state of the cursor, applied to the window by the `CursorSystem`.
+///
+/// Systems which need the cursor in a certain state, e.g. the `CursorHideSystem` of the controls
+/// or the `UiCursorSystem`, write here instead of sending window commands themselves, so they
+/// don't undo each other's changes.
+///
+/// The window backend only supports the system cursor icons, so custom images are not possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CursorState {
+    /// How the cursor interacts with the window.
+    pub mode: CursorMode,
+    /// The icon shown while the cursor is visible.
+    pub icon: MouseCursor,
+}
+
+impl CursorState {
+    /// Creates a visible cursor with the default icon.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// Applies changes of the `CursorState` to the window.
+///
+/// Added by the `RenderBundle`.
+#[derive(Debug, Default)]
+pub struct CursorSystem {
+    applied: Option<CursorState>,
+}
+
+impl CursorSystem {
+    /// Creates the system, the current `CursorState` is applied in its first run.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for CursorSystem {
+    type SystemData = (Read<'a, CursorState>, Write<'a, WindowMessages>);
+
+    fn run(&mut self, (state, mut messages): Self::SystemData) {
+        let state = *state;
+        let applied = self.applied.replace(state);
+        if applied == Some(state) {
+            return;
+        }
+
+        if applied.map(|a| a.mode) != Some(state.mode) {
+            let (hidden, grabbed) = (state.mode.hidden(), state.mode.grabbed());
+            messages.send_command(move |win| {
+                if let Err(err) = win.grab_cursor(grabbed) {
+                    error!("Unable to change the cursor grab. Error: {:?}", err);
+                }
+                win.hide_cursor(hidden);
+            });
+        }
+        if applied.map(|a| a.icon) != Some(state.icon) {
+            let icon = state.icon;
+            messages.send_command(move |win| win.set_cursor(icon));
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use std::f32::consts::PI;
+
 use amethyst_core::{
     ecs::{Component, DenseVecStorage},
-    math::{Point3, Vector3},
+    math::{Matrix4, Point3, Vector3},
 };
 
 use crate::{color::Rgba, vertex::PosColorNorm};
@@ -59,6 +61,20 @@ impl DebugLinesComponent {
         self.lines.push(vertex);
     }
 
+    /// Adds the edges of the axis aligned box between `min` and `max`.
+    pub fn add_box(&mut self, min: Point3<f32>, max: Point3<f32>, color: Rgba) {
+        for (start, end) in box_edges(&min, &max, &Matrix4::identity()).iter() {
+            self.add_line(*start, *end, color);
+        }
+    }
+
+    /// Adds a sphere, drawn as three circles around its axes.
+    pub fn add_sphere(&mut self, center: Point3<f32>, radius: f32, color: Rgba) {
+        for (start, end) in sphere_edges(&center, radius) {
+            self.add_line(start, end, color);
+        }
+    }
+
     /// Clears lines buffer.
     ///
     /// As lines are persistent, it's necessary to use this function for updating or deleting lines.
@@ -110,4 +126,111 @@ impl DebugLines {
 
         self.lines.push(vertex);
     }
+
+    /// Submits the edges of the axis aligned box between `min` and `max`.
+    pub fn draw_box(&mut self, min: Point3<f32>, max: Point3<f32>, color: Rgba) {
+        self.draw_transformed_box(min, max, &Matrix4::identity(), color);
+    }
+
+    /// Submits the edges of the box between `min` and `max`, transformed by the matrix, e.g. the
+    /// global matrix of an entity to draw its local bounds.
+    pub fn draw_transformed_box(
+        &mut self,
+        min: Point3<f32>,
+        max: Point3<f32>,
+        transform: &Matrix4<f32>,
+        color: Rgba,
+    ) {
+        for (start, end) in box_edges(&min, &max, transform).iter() {
+            self.draw_line(*start, *end, color);
+        }
+    }
+
+    /// Submits a sphere, drawn as three circles around its axes.
+    pub fn draw_sphere(&mut self, center: Point3<f32>, radius: f32, color: Rgba) {
+        for (start, end) in sphere_edges(&center, radius) {
+            self.draw_line(start, end, color);
+        }
+    }
+}
+
+/// The number of lines of each circle of a sphere.
+const SPHERE_SEGMENTS: usize = 24;
+
+/// The 12 edges of the box between `min` and `max`, transformed by the matrix.
+fn box_edges(
+    min: &Point3<f32>,
+    max: &Point3<f32>,
+    transform: &Matrix4<f32>,
+) -> [(Point3<f32>, Point3<f32>); 12] {
+    // The bits of the index of a corner pick the `max` coordinate on each axis.
+    let corner = |i: usize| {
+        let point = Point3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        );
+        transform.transform_point(&point)
+    };
+    let mut edges = [(Point3::origin(), Point3::origin()); 12];
+    let mut count = 0;
+    for i in 0..8 {
+        for axis in &[1, 2, 4] {
+            if i & axis == 0 {
+                edges[count] = (corner(i), corner(i | axis));
+                count += 1;
+            }
+        }
+    }
+    edges
+}
+
+/// The lines of the circles of a sphere in the XY, YZ and ZX planes.
+fn sphere_edges(center: &Point3<f32>, radius: f32) -> Vec<(Point3<f32>, Point3<f32>)> {
+    let mut edges = Vec::with_capacity(SPHERE_SEGMENTS * 3);
+    for axis in 0..3 {
+        let point = |segment: usize| {
+            let angle = segment as f32 / SPHERE_SEGMENTS as f32 * 2.0 * PI;
+            let mut offset = Vector3::zeros();
+            offset[axis] = angle.cos() * radius;
+            offset[(axis + 1) % 3] = angle.sin() * radius;
+            center + offset
+        };
+        for segment in 0..SPHERE_SEGMENTS {
+            edges.push((point(segment), point(segment + 1)));
+        }
+    }
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_edges_follow_the_axes() {
+        let edges = box_edges(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(1.0, 2.0, 3.0),
+            &Matrix4::new_translation(&Vector3::new(1.0, 0.0, 0.0)),
+        );
+        let mut lengths = edges
+            .iter()
+            .map(|(start, end)| (end - start).norm())
+            .collect::<Vec<_>>();
+        lengths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(
+            lengths,
+            vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0]
+        );
+        assert!(edges.iter().all(|(start, _)| start.x >= 1.0));
+
+        let mut lines = DebugLines::new();
+        lines.draw_sphere(Point3::origin(), 2.0, Rgba::WHITE);
+        assert_eq!(lines.lines.len(), SPHERE_SEGMENTS * 3);
+        assert!(lines
+            .lines
+            .iter()
+            .all(|line| (line.position.norm() - 2.0).abs() < 1e-5));
+    }
 }
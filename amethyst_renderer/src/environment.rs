@@ -0,0 +1,160 @@
+//! Image based lighting from an environment map.
+
+use std::f32::consts::PI;
+
+use amethyst_assets::{AssetStorage, Loader, ProgressCounter};
+use amethyst_core::math::Vector3;
+
+use crate::{
+    cubemap::{Cubemap, CubemapHandle},
+    formats::{CubemapOptions, HdrFormat},
+};
+
+/// The environment lighting the scene in the PBR passes drawing `with_environment`, as the
+/// ambient light reflected by their surfaces.
+///
+/// Both cubemaps are loaded from the same environment, usually an equirectangular HDR image with
+/// `EnvironmentMap::load`. Until both are loaded the passes only use the `AmbientColor`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvironmentMap {
+    /// The light reflected by surfaces, prefiltered for their roughness along its mip levels, as
+    /// loaded with `CubemapOptions::specular`.
+    pub specular: Option<CubemapHandle>,
+    /// The light diffused by surfaces, as loaded with `CubemapOptions::irradiance`.
+    pub irradiance: Option<CubemapHandle>,
+    /// Scales the light of the environment.
+    pub intensity: f32,
+}
+
+impl Default for EnvironmentMap {
+    fn default() -> Self {
+        EnvironmentMap {
+            specular: None,
+            irradiance: None,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl EnvironmentMap {
+    /// Loads both cubemaps from the equirectangular HDR image at `name`, with specular faces of
+    /// `size` texels.
+    pub fn load<N: Into<String>>(
+        name: N,
+        size: u16,
+        loader: &Loader,
+        storage: &AssetStorage<Cubemap>,
+        progress: &mut ProgressCounter,
+    ) -> Self {
+        let name = name.into();
+        let specular = loader.load(
+            name.as_str(),
+            HdrFormat,
+            CubemapOptions::specular(size),
+            &mut *progress,
+            storage,
+        );
+        let irradiance = loader.load(
+            name,
+            HdrFormat,
+            CubemapOptions::irradiance(),
+            &mut *progress,
+            storage,
+        );
+        EnvironmentMap {
+            specular: Some(specular),
+            irradiance: Some(irradiance),
+            intensity: 1.0,
+        }
+    }
+}
+
+/// The `i`th of `count` points of the Hammersley sequence, evenly spread over the unit square.
+pub(crate) fn hammersley(i: u32, count: u32) -> (f32, f32) {
+    let mut bits = i;
+    bits = (bits << 16) | (bits >> 16);
+    bits = ((bits & 0x5555_5555) << 1) | ((bits & 0xAAAA_AAAA) >> 1);
+    bits = ((bits & 0x3333_3333) << 2) | ((bits & 0xCCCC_CCCC) >> 2);
+    bits = ((bits & 0x0F0F_0F0F) << 4) | ((bits & 0xF0F0_F0F0) >> 4);
+    bits = ((bits & 0x00FF_00FF) << 8) | ((bits & 0xFF00_FF00) >> 8);
+    (i as f32 / count as f32, bits as f32 * 2.328_306_4e-10)
+}
+
+/// Returns a halfway vector around `normal`, distributed like the microfacets of a surface with
+/// the GGX distribution and the squared roughness `a`.
+pub(crate) fn importance_sample_ggx(
+    (u, v): (f32, f32),
+    normal: &Vector3<f32>,
+    a: f32,
+) -> Vector3<f32> {
+    let phi = 2.0 * PI * u;
+    let cos_theta = ((1.0 - v) / (1.0 + (a * a - 1.0) * v)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+    let up = if normal.z.abs() < 0.999 {
+        Vector3::z()
+    } else {
+        Vector3::x()
+    };
+    let tangent = up.cross(normal).normalize();
+    let bitangent = normal.cross(&tangent);
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + normal * cos_theta
+}
+
+/// The scale and bias applied to the fresnel base of the prefiltered specular light, by the
+/// cosine of the view angle on the x axis and the roughness on the y axis, which the PBR passes
+/// sample from a texture built from it.
+///
+/// The rows are stored from the top, so that the roughness rises with the y texture coordinate.
+pub(crate) fn brdf_lut(size: usize) -> Vec<[f32; 2]> {
+    const SAMPLES: u32 = 128;
+
+    let normal = Vector3::z();
+    let mut lut = Vec::with_capacity(size * size);
+    for y in 0..size {
+        let roughness = 1.0 - (y as f32 + 0.5) / size as f32;
+        let a = roughness * roughness;
+        // The geometry term of image based lighting, with k = a / 2.
+        let k = a / 2.0;
+        let geometry = |cos: f32| cos / (cos * (1.0 - k) + k);
+        for x in 0..size {
+            let n_dot_v = (x as f32 + 0.5) / size as f32;
+            let view = Vector3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+            let (mut scale, mut bias) = (0.0, 0.0);
+            for i in 0..SAMPLES {
+                let halfway = importance_sample_ggx(hammersley(i, SAMPLES), &normal, a);
+                let light = halfway * (2.0 * view.dot(&halfway)) - view;
+                let n_dot_l = light.z.max(0.0);
+                let n_dot_h = halfway.z.max(0.0);
+                let v_dot_h = view.dot(&halfway).max(0.0);
+                if n_dot_l > 0.0 && n_dot_h > 0.0 {
+                    let visibility =
+                        geometry(n_dot_v) * geometry(n_dot_l) * v_dot_h / (n_dot_h * n_dot_v);
+                    let fresnel = (1.0 - v_dot_h).powi(5);
+                    scale += (1.0 - fresnel) * visibility;
+                    bias += fresnel * visibility;
+                }
+            }
+            lut.push([scale / SAMPLES as f32, bias / SAMPLES as f32]);
+        }
+    }
+    lut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn brdf_lut_is_bounded() {
+        let lut = brdf_lut(16);
+        assert_eq!(lut.len(), 16 * 16);
+        for &[scale, bias] in &lut {
+            assert!(scale >= 0.0 && bias >= 0.0 && scale + bias <= 1.001);
+        }
+        // Smooth surfaces seen head on reflect all of the fresnel base.
+        let [scale, bias] = lut[15 * 16 + 15];
+        assert!((scale + bias - 1.0).abs() < 0.05);
+        assert!(scale > 0.9);
+    }
+}
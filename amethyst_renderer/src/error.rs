@@ -23,10 +23,25 @@ pub(crate) enum Error {
     DecodeImageError,
     /// Failed to create texture.
     CreateTextureError,
+    /// The images of a sprite atlas don't fit into its maximum size.
+    AtlasTooLarge(u32),
 }
 
 impl error::Error for Error {}
 
+/// The graphics context was lost, e.g. by a driver reset, and the renderer has to be recreated
+/// with `Renderer::recreate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextLost;
+
+impl error::Error for ContextLost {}
+
+impl fmt::Display for ContextLost {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "The graphics context has been lost")
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         use self::Error::*;
@@ -48,6 +63,11 @@ impl fmt::Display for Error {
             ),
             DecodeImageError => write!(fmt, "Image decoding failed"),
             CreateTextureError => write!(fmt, "Failed to create texture from texture data"),
+            AtlasTooLarge(size) => write!(
+                fmt,
+                "The sprite atlas images don't fit into a {0}x{0} texture",
+                size
+            ),
         }
     }
 }
@@ -0,0 +1,365 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::SimpleFormat;
+use amethyst_core::math::Vector3;
+use amethyst_error::{Error, ResultExt};
+
+use crate::{
+    cubemap::{Cubemap, CubemapData},
+    environment::{hammersley, importance_sample_ggx},
+    error,
+};
+
+/// The most mip levels of a specular cubemap, from smooth to fully rough.
+const SPECULAR_LEVELS: u16 = 6;
+/// The samples of the environment averaged for each texel of a specular cubemap.
+const SPECULAR_SAMPLES: u32 = 64;
+
+/// What a cubemap loaded by the `HdrFormat` holds.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CubemapKind {
+    /// The environment itself, with one mip level, for example for a skybox.
+    Radiance,
+    /// The environment blurred for the reflections of rougher surfaces along the mip levels, for
+    /// the `EnvironmentMap`.
+    Specular,
+    /// The light of the whole environment diffused by surfaces facing each direction, for the
+    /// `EnvironmentMap`.
+    Irradiance,
+}
+
+/// Options of the `HdrFormat`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CubemapOptions {
+    /// What the cubemap holds.
+    pub kind: CubemapKind,
+    /// The width and height of the faces of the largest level.
+    pub size: u16,
+}
+
+impl CubemapOptions {
+    /// The environment itself with faces of `size` texels.
+    pub fn radiance(size: u16) -> Self {
+        CubemapOptions {
+            kind: CubemapKind::Radiance,
+            size,
+        }
+    }
+
+    /// The specular reflections of the environment with faces of `size` texels. Sharp
+    /// reflections need a larger size, and take longer to load.
+    pub fn specular(size: u16) -> Self {
+        CubemapOptions {
+            kind: CubemapKind::Specular,
+            size,
+        }
+    }
+
+    /// The diffuse light of the environment, which varies slowly enough for faces of 32 texels.
+    pub fn irradiance() -> Self {
+        CubemapOptions {
+            kind: CubemapKind::Irradiance,
+            size: 32,
+        }
+    }
+}
+
+/// Allows loading of Radiance HDR files (`.hdr`) holding equirectangular panoramas as cubemaps,
+/// which are prefiltered on loading for image based lighting.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HdrFormat;
+
+impl SimpleFormat<Cubemap> for HdrFormat {
+    const NAME: &'static str = "HDR";
+
+    type Options = CubemapOptions;
+
+    fn import(&self, bytes: Vec<u8>, options: CubemapOptions) -> Result<CubemapData, Error> {
+        use image::hdr::HDRDecoder;
+
+        let decoder =
+            HDRDecoder::new(bytes.as_slice()).with_context(|_| error::Error::DecodeImageError)?;
+        let metadata = decoder.metadata();
+        let texels = decoder
+            .read_image_hdr()
+            .with_context(|_| error::Error::DecodeImageError)?;
+        let panorama = Panorama {
+            width: metadata.width as usize,
+            height: metadata.height as usize,
+            texels: texels
+                .into_iter()
+                .map(|rgb| Vector3::new(rgb.data[0], rgb.data[1], rgb.data[2]))
+                .collect(),
+        };
+        if panorama.texels.is_empty() {
+            return Err(error::Error::DecodeImageError.into());
+        }
+        Ok(panorama.cubemap(&options))
+    }
+}
+
+/// An equirectangular panorama, with the up direction on the top row.
+#[derive(Clone, Debug)]
+struct Panorama {
+    width: usize,
+    height: usize,
+    texels: Vec<Vector3<f32>>,
+}
+
+impl Panorama {
+    /// Returns the color seen along `direction`, filtered between the four nearest texels.
+    fn sample(&self, direction: &Vector3<f32>) -> Vector3<f32> {
+        let u = direction.z.atan2(direction.x) / (2.0 * PI) + 0.5;
+        let v = direction.y.max(-1.0).min(1.0).acos() / PI;
+        let x = u * self.width as f32 - 0.5;
+        let y = (v * self.height as f32 - 0.5)
+            .max(0.0)
+            .min((self.height - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let width = self.width as isize;
+        let wrap = |x: isize| (((x % width) + width) % width) as usize;
+        let (x0, x1) = (wrap(x0 as isize), wrap(x0 as isize + 1));
+        let (y0, y1) = (y0 as usize, (y0 as usize + 1).min(self.height - 1));
+        let texel = |x: usize, y: usize| self.texels[y * self.width + x];
+
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x1, y0) * fx;
+        let bottom = texel(x0, y1) * (1.0 - fx) + texel(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+
+    /// Returns the panorama averaged down to at most `width` texels wide, so sampling it does
+    /// not skip over details smaller than a texel of the cubemap.
+    fn downsampled(&self, width: usize) -> Panorama {
+        let mut panorama = self.clone();
+        while panorama.width > width.max(1) && panorama.height > 1 {
+            let (w, h) = (panorama.width / 2, panorama.height / 2);
+            let texel = |x: usize, y: usize| panorama.texels[y * panorama.width + x];
+            let texels = (0..h)
+                .flat_map(|y| (0..w).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    (texel(x * 2, y * 2)
+                        + texel(x * 2 + 1, y * 2)
+                        + texel(x * 2, y * 2 + 1)
+                        + texel(x * 2 + 1, y * 2 + 1))
+                        * 0.25
+                })
+                .collect();
+            panorama = Panorama {
+                width: w,
+                height: h,
+                texels,
+            };
+        }
+        panorama
+    }
+
+    fn cubemap(&self, options: &CubemapOptions) -> CubemapData {
+        let size = options.size.max(1);
+        let levels = match options.kind {
+            CubemapKind::Radiance => {
+                let source = self.downsampled(usize::from(size) * 4);
+                vec![faces(usize::from(size), &|direction| {
+                    source.sample(direction)
+                })]
+            }
+            CubemapKind::Specular => {
+                let levels = (16 - size.leading_zeros() as u16).min(SPECULAR_LEVELS);
+                (0..levels)
+                    .map(|level| {
+                        let size = (usize::from(size) >> level).max(1);
+                        let source = self.downsampled(size * 4);
+                        let roughness = f32::from(level) / f32::from((levels - 1).max(1));
+                        faces(size, &|direction| prefilter(&source, direction, roughness))
+                    })
+                    .collect()
+            }
+            CubemapKind::Irradiance => {
+                let harmonics = Harmonics::project(&self.downsampled(128));
+                vec![faces(usize::from(size), &|direction| {
+                    harmonics.irradiance(direction)
+                })]
+            }
+        };
+        CubemapData { size, levels }
+    }
+}
+
+/// The direction from the center of the cube through the center of a texel of a face.
+fn direction(face: usize, x: usize, y: usize, size: usize) -> Vector3<f32> {
+    let u = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let v = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let direction = match face {
+        0 => Vector3::new(1.0, -v, -u),
+        1 => Vector3::new(-1.0, -v, u),
+        2 => Vector3::new(u, 1.0, v),
+        3 => Vector3::new(u, -1.0, -v),
+        4 => Vector3::new(u, -v, 1.0),
+        _ => Vector3::new(-u, -v, -1.0),
+    };
+    direction.normalize()
+}
+
+fn faces(size: usize, color: &dyn Fn(&Vector3<f32>) -> Vector3<f32>) -> [Vec<[f32; 4]>; 6] {
+    let mut faces: [Vec<[f32; 4]>; 6] = Default::default();
+    for (face, texels) in faces.iter_mut().enumerate() {
+        texels.reserve(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let color = color(&direction(face, x, y, size));
+                texels.push([color.x, color.y, color.z, 1.0]);
+            }
+        }
+    }
+    faces
+}
+
+/// The light reflected along `direction` by a surface facing it with the given roughness.
+fn prefilter(source: &Panorama, direction: &Vector3<f32>, roughness: f32) -> Vector3<f32> {
+    if roughness <= 0.0 {
+        return source.sample(direction);
+    }
+    let a = roughness * roughness;
+    let mut color = Vector3::zeros();
+    let mut weight = 0.0;
+    for i in 0..SPECULAR_SAMPLES {
+        let halfway = importance_sample_ggx(hammersley(i, SPECULAR_SAMPLES), direction, a);
+        let light = halfway * (2.0 * direction.dot(&halfway)) - direction;
+        let n_dot_l = direction.dot(&light);
+        if n_dot_l > 0.0 {
+            color += source.sample(&light) * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    if weight > 0.0 {
+        color / weight
+    } else {
+        source.sample(direction)
+    }
+}
+
+/// The light of a panorama projected on the first nine spherical harmonics, which are enough to
+/// represent the light diffused by surfaces.
+struct Harmonics([Vector3<f32>; 9]);
+
+impl Harmonics {
+    fn basis(d: &Vector3<f32>) -> [f32; 9] {
+        [
+            0.282_095,
+            0.488_603 * d.y,
+            0.488_603 * d.z,
+            0.488_603 * d.x,
+            1.092_548 * d.x * d.y,
+            1.092_548 * d.y * d.z,
+            0.315_392 * (3.0 * d.z * d.z - 1.0),
+            1.092_548 * d.x * d.z,
+            0.546_274 * (d.x * d.x - d.y * d.y),
+        ]
+    }
+
+    fn project(panorama: &Panorama) -> Self {
+        let mut coefficients = [Vector3::zeros(); 9];
+        for y in 0..panorama.height {
+            let theta = (y as f32 + 0.5) / panorama.height as f32 * PI;
+            // The solid angle of the texels of the row.
+            let solid_angle =
+                (2.0 * PI / panorama.width as f32) * (PI / panorama.height as f32) * theta.sin();
+            for x in 0..panorama.width {
+                let phi = ((x as f32 + 0.5) / panorama.width as f32 - 0.5) * 2.0 * PI;
+                let direction = Vector3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                let color = panorama.texels[y * panorama.width + x] * solid_angle;
+                for (coefficient, basis) in coefficients.iter_mut().zip(&Self::basis(&direction)) {
+                    *coefficient += color * *basis;
+                }
+            }
+        }
+        Harmonics(coefficients)
+    }
+
+    /// The light diffused by a white surface facing `normal`, divided by pi so the PBR passes
+    /// only multiply it with the albedo.
+    fn irradiance(&self, normal: &Vector3<f32>) -> Vector3<f32> {
+        // The convolution with the cosine of each band.
+        const BANDS: [f32; 9] = [
+            PI,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            2.0 * PI / 3.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+            PI / 4.0,
+        ];
+        let basis = Self::basis(normal);
+        let irradiance = (0..9).fold(Vector3::zeros(), |sum: Vector3<f32>, i| {
+            sum + self.0[i] * (BANDS[i] * basis[i])
+        });
+        irradiance.map(|c| c.max(0.0)) / PI
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panorama(color: impl Fn(usize, usize) -> f32) -> Panorama {
+        let (width, height) = (64, 32);
+        Panorama {
+            width,
+            height,
+            texels: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(|(x, y)| Vector3::repeat(color(x, y)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn uniform_environments_light_evenly() {
+        let white = panorama(|_, _| 1.0);
+
+        let irradiance = white.cubemap(&CubemapOptions::irradiance());
+        assert_eq!(irradiance.levels.len(), 1);
+        for face in &irradiance.levels[0] {
+            for texel in face {
+                assert!((texel[0] - 1.0).abs() < 0.02, "{:?}", texel);
+            }
+        }
+
+        let specular = white.cubemap(&CubemapOptions::specular(16));
+        assert_eq!(specular.levels.len(), 5);
+        assert_eq!(specular.levels[4][0].len(), 1);
+        for texel in specular
+            .levels
+            .iter()
+            .flat_map(|faces| faces.iter().flatten())
+        {
+            assert!((texel[0] - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn faces_look_along_their_axis() {
+        // Lit from above only.
+        let sky = panorama(|_, y| if y < 16 { 1.0 } else { 0.0 });
+        let radiance = sky.cubemap(&CubemapOptions::radiance(4));
+        let center = |face: usize| radiance.levels[0][face][5][0];
+        assert_eq!(center(2), 1.0);
+        assert_eq!(center(3), 0.0);
+        // The top rows of the side faces look up.
+        assert_eq!(radiance.levels[0][0][0][0], 1.0);
+        assert_eq!(radiance.levels[0][0][15][0], 0.0);
+
+        let irradiance = Harmonics::project(&sky);
+        assert!(irradiance.irradiance(&Vector3::y()).x > 0.9);
+        assert!(irradiance.irradiance(&-Vector3::y()).x < 0.1);
+    }
+}
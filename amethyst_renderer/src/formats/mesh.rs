@@ -4,6 +4,7 @@ use amethyst_assets::{Asset, AssetStorage, Loader, PrefabData, ProcessingState,
 use amethyst_core::{
     ecs::prelude::{Component, Entity, Read, ReadExpect, VecStorage, WriteStorage},
     math::{Vector2, Vector3},
+    Aabb,
 };
 use amethyst_error::{format_err, Error, ResultExt};
 
@@ -42,6 +43,71 @@ pub enum MeshData {
     Creator(Box<dyn MeshCreator>),
 }
 
+impl MeshData {
+    /// Computes the bounding box of the vertex positions.
+    ///
+    /// The bounds of a `MeshData::Creator` only contain the vertices used by its triangles if it
+    /// is indexed. Returns `None` for meshes without vertices.
+    pub fn bounds(&self) -> Option<Aabb> {
+        match *self {
+            MeshData::PosColor(ref vertices) => {
+                Aabb::from_points(vertices.iter().map(|v| v.position))
+            }
+            MeshData::PosColorNorm(ref vertices) => {
+                Aabb::from_points(vertices.iter().map(|v| v.position))
+            }
+            MeshData::PosTex(ref vertices) => {
+                Aabb::from_points(vertices.iter().map(|v| v.position))
+            }
+            MeshData::PosNormTex(ref vertices) => {
+                Aabb::from_points(vertices.iter().map(|v| v.position))
+            }
+            MeshData::PosNormTangTex(ref vertices) => {
+                Aabb::from_points(vertices.iter().map(|v| v.position))
+            }
+            MeshData::Creator(ref creator) => {
+                let vertices = creator.vertices();
+                let position = |v: &Separate<Position>| Vector3::from(v.0);
+                match creator.indices() {
+                    Some(indices) => Aabb::from_points(
+                        indices
+                            .iter()
+                            .filter_map(|&i| vertices.get(i as usize))
+                            .map(position),
+                    ),
+                    None => Aabb::from_points(vertices.iter().map(position)),
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the mesh data with the position of each vertex moved by the offset at
+    /// its index, see `MorphSystem`.
+    ///
+    /// Returns `None` if `MeshData::Creator` doesn't support offsetting its positions.
+    pub fn with_position_offsets(&self, offsets: &[[f32; 3]]) -> Option<MeshData> {
+        macro_rules! offset {
+            ($variant:ident, $vertices:expr) => {{
+                let mut vertices = $vertices.clone();
+                for (vertex, offset) in vertices.iter_mut().zip(offsets) {
+                    vertex.position += Vector3::from(*offset);
+                }
+                Some(MeshData::$variant(vertices))
+            }};
+        }
+        match *self {
+            MeshData::PosColor(ref vertices) => offset!(PosColor, vertices),
+            MeshData::PosColorNorm(ref vertices) => offset!(PosColorNorm, vertices),
+            MeshData::PosTex(ref vertices) => offset!(PosTex, vertices),
+            MeshData::PosNormTex(ref vertices) => offset!(PosNormTex, vertices),
+            MeshData::PosNormTangTex(ref vertices) => offset!(PosNormTangTex, vertices),
+            MeshData::Creator(ref creator) => creator
+                .with_position_offsets(offsets)
+                .map(MeshData::Creator),
+        }
+    }
+}
+
 impl Component for MeshData {
     type Storage = VecStorage<Self>;
 }
@@ -96,9 +162,11 @@ impl<'a> PrefabData<'a> for MeshData {
         ReadExpect<'a, Loader>,
         WriteStorage<'a, MeshHandle>,
         Read<'a, AssetStorage<Mesh>>,
+        WriteStorage<'a, Aabb>,
     );
     type Result = ();
 
+    /// Also adds the bounding box of the mesh, unless the entity already has one.
     fn add_to_entity(
         &self,
         entity: Entity,
@@ -110,6 +178,11 @@ impl<'a> PrefabData<'a> for MeshData {
             .0
             .load_from_data(self.clone(), (), &system_data.2);
         system_data.1.insert(entity, handle).map(|_| ())?;
+        if !system_data.3.contains(entity) {
+            if let Some(bounds) = self.bounds() {
+                system_data.3.insert(entity, bounds)?;
+            }
+        }
         Ok(())
     }
 }
@@ -258,10 +331,30 @@ pub trait MeshCreator: Send + Sync + Debug + 'static {
     /// Returns the vertices contained in the MeshCreator.
     fn vertices(&self) -> &Vec<Separate<Position>>;
 
+    /// Returns the indices into `vertices` of the triangles, if the mesh is indexed.
+    fn indices(&self) -> Option<&[u32]> {
+        None
+    }
+
+    /// Returns a copy of the creator with the position of each vertex moved by the offset at its
+    /// index, or `None` if the creator doesn't support it.
+    fn with_position_offsets(&self, _offsets: &[[f32; 3]]) -> Option<Box<dyn MeshCreator>> {
+        None
+    }
+
     /// Clone a boxed version of this object
     fn box_clone(&self) -> Box<dyn MeshCreator>;
 }
 
+/// Moves each position by the offset at its index.
+pub(crate) fn offset_positions(positions: &mut [Separate<Position>], offsets: &[[f32; 3]]) {
+    for (position, offset) in positions.iter_mut().zip(offsets) {
+        for axis in 0..3 {
+            position.0[axis] += offset[axis];
+        }
+    }
+}
+
 impl Clone for Box<dyn MeshCreator> {
     fn clone(&self) -> Box<dyn MeshCreator> {
         self.box_clone()
@@ -290,6 +383,12 @@ impl MeshCreator for ComboMeshCreator {
         &self.combo.0
     }
 
+    fn with_position_offsets(&self, offsets: &[[f32; 3]]) -> Option<Box<dyn MeshCreator>> {
+        let mut creator = self.clone();
+        offset_positions(&mut creator.combo.0, offsets);
+        Some(Box::new(creator))
+    }
+
     fn box_clone(&self) -> Box<dyn MeshCreator> {
         Box::new((*self).clone())
     }
@@ -300,3 +399,27 @@ impl From<VertexBufferCombination> for ComboMeshCreator {
         Self::new(combo)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::skinning::AnimatedComboMeshCreator;
+
+    #[test]
+    fn bounds_of_mesh_creators() {
+        let vertices = vec![[0.0, 0.0, 0.0], [1.0, 2.0, 3.0], [-5.0, 0.0, 0.0]]
+            .into_iter()
+            .map(Separate::new)
+            .collect::<Vec<_>>();
+        let creator = AnimatedComboMeshCreator::new((vertices, None, None, None, None, None, None));
+
+        let bounds = MeshData::from(creator.clone()).bounds().unwrap();
+        assert_eq!(bounds.min, Vector3::new(-5.0, 0.0, 0.0));
+        assert_eq!(bounds.max, Vector3::new(1.0, 2.0, 3.0));
+
+        // The unused vertex is not part of the bounds.
+        let indexed = MeshData::from(creator.with_indices(vec![0, 1, 1]));
+        assert_eq!(indexed.bounds().unwrap().min, Vector3::new(0.0, 0.0, 0.0));
+    }
+}
@@ -1,7 +1,7 @@
 //! Provides texture formats
 //!
 
-pub use self::{mesh::*, mtl::*, texture::*};
+pub use self::{cubemap::*, mesh::*, mtl::*, texture::*};
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
@@ -11,6 +11,7 @@ use amethyst_error::Error;
 
 use crate::{shape::InternalShape, Mesh, ShapePrefab, Texture};
 
+mod cubemap;
 mod mesh;
 mod mtl;
 mod texture;
@@ -54,6 +54,8 @@ where
     pub transparent: bool,
     /// Alpha cutoff: the value below which we do not draw the pixel
     pub alpha_cutoff: f32,
+    /// Draw without lighting, see `Material::unlit`
+    pub unlit: bool,
 }
 
 impl<F> Default for MaterialPrefab<F>
@@ -78,6 +80,7 @@ where
             caveat_offset: TextureOffset::default(),
             transparent: false,
             alpha_cutoff: 0.01,
+            unlit: false,
         }
     }
 }
@@ -139,6 +142,7 @@ where
             caveat: load_handle(entity, &self.caveat, tp_data, &mat_default.0.caveat),
             caveat_offset: self.caveat_offset.clone(),
             alpha_cutoff: self.alpha_cutoff,
+            unlit: self.unlit,
         };
         material.insert(entity, mtl)?;
         if self.transparent {
@@ -354,6 +354,13 @@ impl SimpleFormat<Texture> for PngFormat {
 #[derive(Clone, Deserialize, Serialize)]
 pub struct BmpFormat;
 
+impl BmpFormat {
+    /// Loads a BMP image from a byte slice.
+    pub fn from_data(data: &[u8], options: TextureMetadata) -> Result<TextureData, Error> {
+        load_into_rgba8_from_memory(data, options, ImageFormat::BMP)
+    }
+}
+
 impl SimpleFormat<Texture> for BmpFormat {
     const NAME: &'static str = "BMP";
 
@@ -362,7 +369,7 @@ impl SimpleFormat<Texture> for BmpFormat {
     fn import(&self, bytes: Vec<u8>, options: TextureMetadata) -> Result<TextureData, Error> {
         // TODO: consider reading directly into GPU-visible memory
         // TODO: as noted by @omni-viral.
-        load_into_rgba8_from_memory(&bytes, options, ImageFormat::BMP)
+        BmpFormat::from_data(&bytes, options)
     }
 }
 
@@ -29,15 +29,19 @@
 pub use crate::{
     blink::{Blink, BlinkSystem},
     bundle::RenderBundle,
-    cam::{ActiveCamera, ActiveCameraPrefab, Camera, CameraPrefab, Projection},
+    cam::{ActiveCamera, ActiveCameraPrefab, Camera, CameraPrefab, Projection, TargetCamera},
     color::Rgba,
     config::DisplayConfig,
+    cubemap::{create_cubemap_asset, Cubemap, CubemapData, CubemapHandle},
+    cursor::{CursorMode, CursorState, CursorSystem},
     debug_drawing::{DebugLines, DebugLinesComponent},
+    environment::EnvironmentMap,
+    error::ContextLost,
     formats::{
         build_mesh_with_combo, create_mesh_asset, create_texture_asset, BmpFormat,
-        ComboMeshCreator, GraphicsPrefab, ImageData, JpgFormat, MaterialPrefab, MeshCreator,
-        MeshData, ObjFormat, PngFormat, TextureData, TextureFormat, TextureMetadata, TexturePrefab,
-        TgaFormat,
+        ComboMeshCreator, CubemapKind, CubemapOptions, GraphicsPrefab, HdrFormat, ImageData,
+        JpgFormat, MaterialPrefab, MeshCreator, MeshData, ObjFormat, PngFormat, TextureData,
+        TextureFormat, TextureMetadata, TexturePrefab, TgaFormat,
     },
     hidden::{Hidden, HiddenPropagate},
     hide_system::HideHierarchySystem,
@@ -46,32 +50,39 @@ pub use crate::{
     },
     light::{DirectionalLight, Light, LightPrefab, PointLight, SpotLight, SunLight},
     mesh::{vertex_data, Mesh, MeshBuilder, MeshHandle, VertexBuffer},
+    morph::{MorphSystem, MorphTarget, MorphTargets, MorphWeights},
     mtl::{Material, MaterialDefaults, TextureOffset},
     pass::{
-        get_camera, set_vertex_args, DebugLinesParams, DrawDebugLines, DrawFlat, DrawFlat2D,
-        DrawFlatSeparate, DrawPbm, DrawPbmSeparate, DrawShaded, DrawShadedSeparate, DrawSkybox,
-        SkyboxColor,
+        get_camera, get_target_camera, set_vertex_args, AlphaMask, DebugLinesParams, DitherPattern,
+        DrawDebugLines, DrawDepth, DrawDither, DrawFlat, DrawFlat2D, DrawFlatSeparate,
+        DrawObjectId, DrawPbm, DrawPbmSeparate, DrawResolve, DrawShaded, DrawShadedSeparate,
+        DrawShadowMap, DrawSkybox, SkyboxColor, SkyboxCubemap, SkyboxPrefab,
     },
+    picking::{ObjectPicking, PickRequest, PickResult},
     pipe::{
         ColorBuffer, Data, DepthBuffer, DepthMode, Effect, EffectBuilder, Init, Meta, NewEffect,
         Pipeline, PipelineBuild, PipelineBuilder, PipelineData, PolyPipeline, PolyStage,
         PolyStages, Stage, StageBuilder, Target, TargetBuilder, Targets,
     },
     renderer::Renderer,
-    resources::{AmbientColor, ScreenDimensions, WindowMessages},
+    resources::{
+        AmbientColor, DrawCalls, RenderStats, ScreenDimensions, WindowControl, WindowMessages,
+    },
     screen_space::{ScreenSpace, ScreenSpaceSettings},
+    shadow::{LightSpace, LightSpaceSystem, ShadowLight},
     shape::{InternalShape, Shape, ShapePrefab, ShapeUpload},
     skinning::{
         AnimatedComboMeshCreator, AnimatedVertexBufferCombination, JointIds, JointTransforms,
         JointTransformsPrefab, JointWeights,
     },
     sprite::{
-        Flipped, Sprite, SpriteGrid, SpriteList, SpritePosition, SpriteRender, SpriteRenderPrefab,
-        SpriteScenePrefab, SpriteSheet, SpriteSheetFormat, SpriteSheetHandle, SpriteSheetPrefab,
-        Sprites, TextureCoordinates,
+        Flipped, Sprite, SpriteAtlas, SpriteAtlasBuilder, SpriteGrid, SpriteList, SpritePosition,
+        SpriteRender, SpriteRenderPrefab, SpriteScenePrefab, SpriteSheet, SpriteSheetFormat,
+        SpriteSheetHandle, SpriteSheetPrefab, Sprites, TextureCoordinates,
     },
     sprite_visibility::{SpriteVisibility, SpriteVisibilitySortingSystem},
-    system::RenderSystem,
+    stencil::{StencilMask, StencilOp, StencilSettings, StencilTest},
+    system::{placeholder_material, placeholder_mesh, placeholder_texture, RenderSystem},
     tex::{
         FilterMethod, SamplerInfo, SurfaceType, Texture, TextureBuilder, TextureHandle, WrapMode,
     },
@@ -84,7 +95,7 @@ pub use crate::{
         PosNormTangTex, PosNormTex, PosTex, Position, Query, Separate, Tangent, TexCoord,
         VertexBufferCombination, VertexFormat, With,
     },
-    visibility::{Visibility, VisibilitySortingSystem},
+    visibility::{RenderOrder, Visibility, VisibilitySortingSystem},
 };
 
 mod error;
@@ -99,22 +110,29 @@ mod bundle;
 mod cam;
 mod color;
 mod config;
+mod cubemap;
+mod cursor;
 mod debug_drawing;
+mod environment;
 mod formats;
 mod hidden;
 mod hide_system;
 mod input;
 mod light;
 mod mesh;
+mod morph;
 mod mtl;
 mod pass;
+mod picking;
 mod renderer;
 mod resources;
 mod screen_space;
+mod shadow;
 mod shape;
 mod skinning;
 mod sprite;
 mod sprite_visibility;
+mod stencil;
 mod system;
 mod tex;
 mod transparent;
@@ -10,7 +10,7 @@ use amethyst_error::Error;
 use gfx_macros::ConstantBuffer;
 use serde::{Deserialize, Serialize};
 
-use crate::{color::Rgba, resources::AmbientColor};
+use crate::{color::Rgba, resources::AmbientColor, shadow::ShadowLight};
 
 /// A light source.
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize, PrefabData)]
@@ -185,9 +185,13 @@ impl Component for Light {
 }
 
 /// Prefab for lighting
-#[derive(Default, Clone, Serialize, Deserialize, PrefabData)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PrefabData)]
 #[serde(default)]
 pub struct LightPrefab {
-    light: Option<Light>,
-    ambient_color: Option<AmbientColor>,
+    /// The light added to the entity.
+    pub light: Option<Light>,
+    /// Enables the shadows of the light.
+    pub shadow: Option<ShadowLight>,
+    /// The ambient color resource of the scene.
+    pub ambient_color: Option<AmbientColor>,
 }
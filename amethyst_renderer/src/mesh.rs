@@ -198,6 +198,22 @@ impl Mesh {
     pub fn transform(&self) -> Matrix4<f32> {
         self.transform
     }
+
+    /// Returns the total size of the mesh's vertex and index buffers in bytes.
+    pub fn byte_size(&self) -> usize {
+        use gfx::IndexBuffer;
+        let indices = match self.slice.buffer {
+            IndexBuffer::Auto => 0,
+            IndexBuffer::Index16(ref buffer) => buffer.get_info().size,
+            IndexBuffer::Index32(ref buffer) => buffer.get_info().size,
+        };
+        indices
+            + self
+                .vbufs
+                .iter()
+                .map(|vbuf| vbuf.raw.get_info().size)
+                .sum::<usize>()
+    }
 }
 
 /// Builds new meshes.
@@ -206,6 +222,8 @@ pub struct MeshBuilder<T> {
     prim: Primitive,
     transform: Matrix4<f32>,
     vertices: T,
+    #[serde(default)]
+    indices: Option<Vec<u32>>,
 }
 
 impl<D, V> MeshBuilder<((D, PhantomData<V>), ())>
@@ -220,6 +238,7 @@ where
             prim: Primitive::TriangleList,
             transform: Matrix4::identity(),
             vertices: (vertex_data(verts), ()),
+            indices: None,
         }
     }
 }
@@ -239,9 +258,16 @@ where
             prim: self.prim,
             transform: self.transform,
             vertices: (vertex_data(verts), self.vertices),
+            indices: self.indices,
         }
     }
 
+    /// Draws the vertices in the order of `indices`, instead of in the order they were given.
+    pub fn with_indices(mut self, indices: Vec<u32>) -> Self {
+        self.indices = Some(indices);
+        self
+    }
+
     /// Sets the primitive type of the mesh.
     ///
     /// By default, meshes are constructed as triangle lists.
@@ -283,15 +309,18 @@ where
 
     /// Builds and returns the new mesh.
     pub fn build(self, fac: &mut Factory) -> Result<Mesh, Error> {
-        use gfx::IndexBuffer;
-        let count = self.vertices.len();
+        use gfx::{traits::FactoryExt, IndexBuffer};
+        let (count, buffer) = match self.indices {
+            Some(ref indices) => (indices.len(), fac.create_index_buffer(&indices[..])),
+            None => (self.vertices.len(), IndexBuffer::Auto),
+        };
 
         let slice = Slice {
             start: 0,
             end: count as u32,
             base_vertex: 0,
             instances: None,
-            buffer: IndexBuffer::Auto,
+            buffer,
         };
 
         Ok(Mesh {
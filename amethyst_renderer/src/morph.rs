@@ -0,0 +1,267 @@
+//! Morph targets, also known as blend shapes.
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    ecs::prelude::{
+        BitSet, Component, ComponentEvent, DenseVecStorage, Entities, FlaggedStorage, Join, Read,
+        ReadExpect, ReadStorage, ReaderId, Resources, System, SystemData, WriteStorage,
+    },
+    transform::Parent,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use crate::{
+    formats::MeshData,
+    mesh::{Mesh, MeshHandle},
+};
+
+/// The vertex offsets of one morph target of a mesh.
+///
+/// Each list has an offset per vertex of the mesh, in the order of its vertex buffers, or is
+/// empty if the target doesn't move that attribute.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MorphTarget {
+    /// The position offsets.
+    pub positions: Vec<[f32; 3]>,
+    /// The normal offsets.
+    pub normals: Vec<[f32; 3]>,
+    /// The tangent offsets.
+    pub tangents: Vec<[f32; 3]>,
+}
+
+/// The morph targets of the mesh of an entity, weighted by `MorphWeights`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MorphTargets {
+    /// The targets, in the order of the weights.
+    pub targets: Vec<MorphTarget>,
+}
+
+impl Component for MorphTargets {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl MorphTargets {
+    /// The offset of each vertex position for the weights, to add to the positions of the mesh.
+    pub fn position_offsets(&self, weights: &MorphWeights) -> Vec<[f32; 3]> {
+        let len = self
+            .targets
+            .iter()
+            .map(|target| target.positions.len())
+            .max()
+            .unwrap_or(0);
+        let mut offsets = vec![[0.0; 3]; len];
+        for (target, &weight) in self.targets.iter().zip(&weights.weights) {
+            if weight == 0.0 {
+                continue;
+            }
+            for (offset, delta) in offsets.iter_mut().zip(&target.positions) {
+                for axis in 0..3 {
+                    offset[axis] += delta[axis] * weight;
+                }
+            }
+        }
+        offsets
+    }
+
+    /// Returns the mesh data with its positions moved by the targets for the weights, or `None`
+    /// if the mesh data can't be offset.
+    pub fn morph(&self, base: &MeshData, weights: &MorphWeights) -> Option<MeshData> {
+        base.with_position_offsets(&self.position_offsets(weights))
+    }
+}
+
+/// The weight of each morph target, animated with `MorphWeightsChannel` of the animation crate.
+///
+/// The weights apply to the `MorphTargets` of the same entity, or of its children if its mesh is
+/// split into several entities. Missing weights count as 0.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MorphWeights {
+    /// The weights, in the order of the targets.
+    pub weights: Vec<f32>,
+}
+
+impl Component for MorphWeights {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Applies the `MorphWeights` to the entities with `MorphTargets`, by loading a new mesh from the
+/// `MeshData` of the entity with its positions moved, whenever the weights change.
+///
+/// The weights are those of the entity, or else of its parent. The blending happens on the CPU
+/// and only moves the positions, so it suits small meshes with few animated targets.
+#[derive(Default)]
+pub struct MorphSystem {
+    dirty: BitSet,
+    weights_events_id: Option<ReaderId<ComponentEvent>>,
+}
+
+impl<'a> System<'a> for MorphSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, MorphTargets>,
+        ReadStorage<'a, MorphWeights>,
+        ReadStorage<'a, MeshData>,
+        ReadStorage<'a, Parent>,
+        WriteStorage<'a, MeshHandle>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Mesh>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, targets, weights, mesh_data, parents, mut meshes, loader, storage): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("morph_system");
+
+        self.dirty.clear();
+        for event in weights.channel().read(
+            self.weights_events_id
+                .as_mut()
+                .expect("`MorphSystem::setup` was not called before `MorphSystem::run`"),
+        ) {
+            match *event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.dirty.add(id);
+                }
+                ComponentEvent::Removed(_) => {}
+            }
+        }
+        for (entity, targets, base, mesh) in (&*entities, &targets, &mesh_data, &mut meshes).join()
+        {
+            let source = if weights.contains(entity) {
+                Some(entity)
+            } else {
+                parents.get(entity).map(|parent| parent.entity)
+            };
+            let source_weights = match source.filter(|source| self.dirty.contains(source.id())) {
+                Some(source) => match weights.get(source) {
+                    Some(source_weights) => source_weights,
+                    None => continue,
+                },
+                None => continue,
+            };
+            match targets.morph(base, source_weights) {
+                Some(data) => *mesh = loader.load_from_data(data, (), &storage),
+                None => warn!("The mesh data of {:?} can't be morphed", entity),
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.weights_events_id = Some(WriteStorage::<MorphWeights>::fetch(res).register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vertex::PosTex;
+    use amethyst_core::{
+        ecs::{Builder, RunNow, World},
+        math::{Vector2, Vector3},
+    };
+    use rayon::ThreadPoolBuilder;
+    use std::sync::Arc;
+
+    #[test]
+    fn weights_the_position_offsets() {
+        let targets = MorphTargets {
+            targets: vec![
+                MorphTarget {
+                    positions: vec![[1.0, 0.0, 0.0], [0.0, 2.0, 0.0]],
+                    ..MorphTarget::default()
+                },
+                MorphTarget {
+                    positions: vec![[0.0, 0.0, 4.0], [0.0, 0.0, 4.0]],
+                    ..MorphTarget::default()
+                },
+            ],
+        };
+        let weights = MorphWeights {
+            weights: vec![0.5, 0.25],
+        };
+        assert_eq!(
+            targets.position_offsets(&weights),
+            vec![[0.5, 0.0, 1.0], [0.0, 1.0, 1.0]]
+        );
+        let weights = MorphWeights { weights: vec![1.0] };
+        assert_eq!(
+            targets.position_offsets(&weights),
+            vec![[1.0, 0.0, 0.0], [0.0, 2.0, 0.0]]
+        );
+    }
+
+    fn positions(data: &MeshData) -> Vec<[f32; 3]> {
+        match *data {
+            MeshData::PosTex(ref vertices) => vertices.iter().map(|v| v.position.into()).collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn changing_a_weight_moves_the_vertices() {
+        let mut world = World::new();
+        world.add_resource(Loader::new(
+            ".",
+            Arc::new(ThreadPoolBuilder::new().build().unwrap()),
+        ));
+        let mut system = MorphSystem::default();
+        RunNow::setup(&mut system, &mut world.res);
+
+        let base = MeshData::PosTex(vec![
+            PosTex {
+                position: Vector3::new(0.0, 0.0, 0.0),
+                tex_coord: Vector2::new(0.0, 0.0),
+            };
+            2
+        ]);
+        let targets = MorphTargets {
+            targets: vec![MorphTarget {
+                positions: vec![[0.0, 2.0, 0.0], [4.0, 0.0, 0.0]],
+                ..MorphTarget::default()
+            }],
+        };
+        let weights = MorphWeights { weights: vec![0.5] };
+        assert_eq!(
+            positions(&targets.morph(&base, &weights).unwrap()),
+            vec![[0.0, 1.0, 0.0], [2.0, 0.0, 0.0]]
+        );
+
+        let handle = world.exec(
+            |(loader, storage): (ReadExpect<'_, Loader>, Read<'_, AssetStorage<Mesh>>)| {
+                loader.load_from_data(base.clone(), (), &storage)
+            },
+        );
+        let node = world.create_entity().with(weights).build();
+        let primitive = world
+            .create_entity()
+            .with(Parent { entity: node })
+            .with(targets)
+            .with(base)
+            .with(handle.clone())
+            .build();
+        let mesh = |world: &World| world.read_storage::<MeshHandle>().get(primitive).cloned();
+
+        system.run_now(&world.res);
+        let morphed = mesh(&world);
+        assert_ne!(morphed, Some(handle));
+
+        system.run_now(&world.res);
+        assert_eq!(mesh(&world), morphed);
+
+        world
+            .write_storage::<MorphWeights>()
+            .get_mut(node)
+            .unwrap()
+            .weights[0] = 1.0;
+        system.run_now(&world.res);
+        assert_ne!(mesh(&world), morphed);
+    }
+}
@@ -57,6 +57,8 @@ pub struct Material {
     pub caveat: TextureHandle,
     /// Caveat texture offset
     pub caveat_offset: TextureOffset,
+    /// Ignore the lights in the shaded and physically based passes, and only draw the albedo.
+    pub unlit: bool,
 }
 
 impl Component for Material {
@@ -64,8 +66,9 @@ impl Component for Material {
 }
 
 /// A resource providing default textures for `Material`.
-/// These will be be used by the renderer in case a texture
-/// handle points to a texture which is not loaded already.
+/// The renderer draws a `Material` with this one instead
+/// while any of its textures is not loaded already, see
+/// `placeholder_material`.
 /// Additionally, you can use it to fill up the fields of
 /// `Material` you don't want to specify.
 #[derive(Clone)]
@@ -15,10 +15,10 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     debug_drawing::{DebugLine, DebugLines, DebugLinesComponent},
     mesh::Mesh,
-    pass::util::{get_camera, set_attribute_buffers, set_vertex_args, setup_vertex_args},
+    pass::util::{get_target_camera, set_attribute_buffers, set_vertex_args, setup_vertex_args},
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
@@ -78,6 +78,7 @@ where
     type Data = (
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         ReadStorage<'a, Transform<N>>,
         WriteStorage<'a, DebugLinesComponent>, // DebugLines components
         Option<Write<'a, DebugLines>>,         // DebugLines resource
@@ -111,7 +112,7 @@ where
         encoder: &mut Encoder,
         effect: &mut Effect,
         mut factory: Factory,
-        (active, camera, transform, lines_components, lines_resource, lines_params): <Self as PassData<'a>>::Data,
+        (active, camera, target_camera, transform, lines_components, lines_resource, lines_params): <Self as PassData<'a>>::Data,
     ) {
         trace!("Drawing debug lines pass");
         let debug_lines = {
@@ -133,7 +134,7 @@ where
             return;
         }
 
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
         effect.update_global(
             "camera_position",
             camera
@@ -0,0 +1,191 @@
+//! Depth pre-pass.
+
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+use gfx::{pso::buffer::ElemStride, traits::Pod};
+use gfx_core::state::ColorMask;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    math::RealField,
+    transform::Transform,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera, TargetCamera},
+    hidden::{Hidden, HiddenPropagate},
+    mesh::{Mesh, MeshHandle},
+    mtl::{Material, MaterialDefaults},
+    pass::util::{draw_mesh, get_target_camera, setup_textures, setup_vertex_args, TextureType},
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    tex::Texture,
+    types::{Encoder, Factory},
+    vertex::{Position, Query, TexCoord},
+    visibility::Visibility,
+    Rgba, Transparent, REPLACE,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/depth.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/depth.glsl");
+
+/// Draw the depth of opaque meshes, without any color.
+///
+/// Add it before a pass with a depth pre-pass enabled, like `DrawPbm::with_depth_prepass`, in the
+/// same stage. The main pass then only shades the closest surface of each pixel, which saves the
+/// work of expensive fragment shaders on occluded pixels.
+///
+/// The pass draws the same entities with a `Material` as the opaque part of the main pass: the
+/// `visible_unordered` entities of the `Visibility`, or all entities without a `Transparent`
+/// component if there is none.
+///
+/// Fragments with an albedo alpha (tinted by the `Rgba` of the entity) below the `alpha_cutoff` of
+/// their material are discarded, so masked materials don't hide what is behind their holes. The
+/// alpha of the vertex colors of the mesh is not taken into account.
+///
+/// # Type Parameters
+///
+/// * `V`: `VertexFormat`
+/// * `N`: `RealBound` (f32, f64)
+#[derive(Derivative, Clone, Debug, PartialEq)]
+#[derivative(Default(bound = "V: Query<(Position, TexCoord)>"))]
+pub struct DrawDepth<V, N> {
+    _marker: PhantomData<(V, N)>,
+}
+
+impl<V, N> DrawDepth<V, N>
+where
+    V: Query<(Position, TexCoord)>,
+{
+    /// Create instance of `DrawDepth` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, V, N> PassData<'a> for DrawDepth<V, N>
+where
+    V: Query<(Position, TexCoord)>,
+    N: RealField,
+{
+    type Data = (
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
+        Read<'a, AssetStorage<Mesh>>,
+        Read<'a, AssetStorage<Texture>>,
+        ReadExpect<'a, MaterialDefaults>,
+        Option<Read<'a, Visibility>>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, Transparent>,
+        ReadStorage<'a, MeshHandle>,
+        ReadStorage<'a, Material>,
+        ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transform<N>>,
+    );
+}
+
+impl<V, N> Pass for DrawDepth<V, N>
+where
+    V: Query<(Position, TexCoord)>,
+    N: RealField + SubsetOf<f32> + Pod,
+{
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
+        setup_vertex_args(&mut builder);
+        setup_textures(&mut builder, &[TextureType::Albedo]);
+        builder.with_raw_global("alpha_cutoff");
+        builder.with_blended_output(
+            "color",
+            ColorMask::empty(),
+            REPLACE,
+            Some(DepthMode::LessEqualWrite),
+        );
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        (
+            active,
+            camera,
+            target_camera,
+            mesh_storage,
+            tex_storage,
+            material_defaults,
+            visibility,
+            hidden,
+            hidden_prop,
+            transparent,
+            mesh,
+            material,
+            rgba,
+            transform,
+        ): <Self as PassData<'a>>::Data,
+    ) {
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
+
+        let mut draw = |mesh: &MeshHandle,
+                        material: &Material,
+                        rgba: Option<&Rgba>,
+                        transform: &Transform<N>| {
+            draw_mesh(
+                encoder,
+                effect,
+                false,
+                mesh_storage.get(mesh),
+                None,
+                &tex_storage,
+                Some(material),
+                &material_defaults,
+                rgba,
+                camera,
+                Some(transform),
+                &[V::QUERIED_ATTRIBUTES],
+                &[TextureType::Albedo],
+            );
+        };
+
+        match visibility {
+            None => {
+                for (mesh, material, rgba, transform, _, _, _) in (
+                    &mesh,
+                    &material,
+                    rgba.maybe(),
+                    &transform,
+                    !&hidden,
+                    !&hidden_prop,
+                    !&transparent,
+                )
+                    .join()
+                {
+                    draw(mesh, material, rgba, transform);
+                }
+            }
+            Some(ref visibility) => {
+                for (mesh, material, rgba, transform, _) in (
+                    &mesh,
+                    &material,
+                    rgba.maybe(),
+                    &transform,
+                    &visibility.visible_unordered,
+                )
+                    .join()
+                {
+                    draw(mesh, material, rgba, transform);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Dithering pass
+
+use gfx::{
+    memory::Typed,
+    pso::buffer::ElemStride,
+    texture::{FilterMethod, SamplerInfo, WrapMode},
+    Factory as GfxFactory,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::math::{Vector2, Vector3};
+use amethyst_error::Error;
+
+use crate::{
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, Target, Targets,
+    },
+    types::{RawShaderResourceView, Sampler},
+    Encoder, Factory, Mesh, PosTex, VertexFormat,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/dither.glsl");
+
+/// The noise added by `DrawDither`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DitherPattern {
+    /// An 8x8 Bayer matrix, which gives a regular cross hatched pattern.
+    Ordered,
+    /// Interleaved gradient noise, which is less visible than the ordered pattern.
+    BlueNoise,
+}
+
+impl Default for DitherPattern {
+    fn default() -> Self {
+        DitherPattern::BlueNoise
+    }
+}
+
+/// Copies the first color buffer of another target to the target of its stage, with a little
+/// noise added to hide the banding of dark gradients in 8 bit output.
+///
+/// Render the scene into a named target, and add the pass in a last stage drawing to the
+/// backbuffer:
+///
+/// ```rust,ignore
+/// let pipe = Pipeline::build()
+///     .with_target(Target::named("scene"))
+///     .with_stage(
+///         Stage::with_target("scene")
+///             .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+///             .with_pass(DrawShaded::<PosNormTex>::new()),
+///     )
+///     .with_stage(Stage::with_backbuffer().with_pass(DrawDither::new("scene")));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DrawDither {
+    source: String,
+    pattern: DitherPattern,
+    strength: f32,
+    mesh: Option<Mesh>,
+    sampler: Option<Sampler>,
+    view: Option<RawShaderResourceView>,
+}
+
+impl DrawDither {
+    /// Create instance of `DrawDither` pass, reading the target with the given name.
+    pub fn new<N: Into<String>>(source: N) -> Self {
+        DrawDither {
+            source: source.into(),
+            pattern: DitherPattern::default(),
+            strength: 1.0,
+            mesh: None,
+            sampler: None,
+            view: None,
+        }
+    }
+
+    /// Sets the noise pattern.
+    pub fn with_pattern(mut self, pattern: DitherPattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Sets the amplitude of the noise, in steps of the 8 bit output. Defaults to 1.
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        self.strength = strength;
+        self
+    }
+}
+
+impl<'a> PassData<'a> for DrawDither {
+    type Data = ();
+}
+
+impl Pass for DrawDither {
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let corner = |x: f32, y: f32| PosTex {
+            position: Vector3::new(x, y, 0.0),
+            tex_coord: Vector2::new((x + 1.0) / 2.0, (y + 1.0) / 2.0),
+        };
+        let verts = vec![
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, 1.0),
+        ];
+        self.mesh = Some(Mesh::build(verts).build(&mut effect.factory)?);
+        self.sampler = Some(
+            effect
+                .factory
+                .create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp)),
+        );
+
+        effect
+            .simple(VERT_SRC, FRAG_SRC)
+            .without_back_face_culling()
+            .with_raw_vertex_buffer(PosTex::ATTRIBUTES, PosTex::size() as ElemStride, 0)
+            .with_texture("source")
+            .with_raw_global("strength")
+            .with_raw_global("pattern")
+            .with_output("color", None)
+            .build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        _: (),
+    ) {
+        let mesh = self
+            .mesh
+            .as_ref()
+            .expect("Pass doesn't seem to be compiled.");
+        let (view, sampler) = match (self.view.as_ref(), self.sampler.as_ref()) {
+            (Some(view), Some(sampler)) => (view, sampler),
+            _ => return,
+        };
+
+        if let Some(vbuf) = mesh.buffer(PosTex::ATTRIBUTES) {
+            effect.data.vertex_bufs.push(vbuf.clone());
+        } else {
+            effect.clear();
+            return;
+        }
+        effect.data.textures.push(view.clone());
+        effect.data.samplers.push(sampler.clone());
+
+        effect.update_global("strength", self.strength);
+        effect.update_global(
+            "pattern",
+            match self.pattern {
+                DitherPattern::Ordered => 0,
+                DitherPattern::BlueNoise => 1,
+            },
+        );
+        effect.draw(mesh.slice(), encoder);
+        effect.clear();
+    }
+
+    fn new_targets(&mut self, _output: &Target, targets: &Targets) {
+        self.view = targets
+            .get(&self.source)
+            .and_then(|target| target.color_buf(0))
+            .and_then(|buffer| buffer.as_input.as_ref())
+            .map(|view| view.raw().clone());
+        if self.view.is_none() {
+            warn!(
+                "Dither source target {:?} doesn't exist or can't be read",
+                self.source
+            );
+        }
+    }
+}
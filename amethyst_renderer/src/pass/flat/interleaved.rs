@@ -18,15 +18,19 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     hidden::{Hidden, HiddenPropagate},
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
-    pass::util::{default_transparency, draw_mesh, get_camera, setup_textures, VertexArgs},
+    pass::util::{
+        default_transparency, draw_mesh, get_target_camera, set_stencil_reference, setup_textures,
+        VertexArgs,
+    },
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
+    stencil::{StencilMask, StencilSettings},
     tex::Texture,
     types::{Encoder, Factory},
     vertex::{Position, Query, TexCoord},
@@ -51,6 +55,7 @@ pub struct DrawFlat<V, N> {
     _marker: PhantomData<(V, N)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    stencil: Option<StencilSettings>,
 }
 
 impl<V, N> DrawFlat<V, N>
@@ -90,6 +95,13 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Sets the stencil state. Each entity is drawn with the reference value of its
+    /// `StencilMask`, or 0 without one.
+    pub fn with_stencil(mut self, stencil: StencilSettings) -> Self {
+        self.stencil = Some(stencil);
+        self
+    }
 }
 
 impl<'a, V, N> PassData<'a> for DrawFlat<V, N>
@@ -100,6 +112,7 @@ where
     type Data = (
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
         ReadExpect<'a, MaterialDefaults>,
@@ -110,6 +123,7 @@ where
         ReadStorage<'a, Material>,
         ReadStorage<'a, Transform<N>>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, StencilMask>,
     );
 }
 
@@ -133,6 +147,9 @@ where
             Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
             None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
         };
+        if let Some(stencil) = self.stencil {
+            builder.with_stencil(stencil);
+        }
         builder.build()
     }
 
@@ -144,6 +161,7 @@ where
         (
             active,
             camera,
+            target_camera,
             mesh_storage,
             tex_storage,
             material_defaults,
@@ -154,22 +172,25 @@ where
             material,
             transform,
             rgba,
+            stencil,
         ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
         match visibility {
             None => {
-                for (mesh, material, transform, rgba, _, _) in (
+                for (mesh, material, transform, rgba, mask, _, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    stencil.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
                     draw_mesh(
                         encoder,
                         effect,
@@ -188,15 +209,17 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, transform, rgba, _) in (
+                for (mesh, material, transform, rgba, mask, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    stencil.maybe(),
                     &visibility.visible_unordered,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
                     draw_mesh(
                         encoder,
                         effect,
@@ -216,6 +239,7 @@ where
 
                 for entity in &visibility.visible_ordered {
                     if let Some(mesh) = mesh.get(*entity) {
+                        set_stencil_reference(effect, stencil.get(*entity));
                         draw_mesh(
                             encoder,
                             effect,
@@ -16,13 +16,18 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     hidden::{Hidden, HiddenPropagate},
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
-        skinning::{create_skinning_effect, setup_skinning_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, VertexArgs},
+        skinning::{
+            create_colored_skinning_effect, create_skinning_effect, setup_skinning_buffers,
+        },
+        util::{
+            default_transparency, draw_mesh, get_target_camera, set_vertex_colors, setup_textures,
+            VertexArgs,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
@@ -30,8 +35,8 @@ use crate::{
     },
     skinning::JointTransforms,
     tex::Texture,
-    types::{Encoder, Factory},
-    vertex::{Attributes, Position, Separate, TexCoord, VertexFormat},
+    types::{Encoder, Factory, RawBuffer},
+    vertex::{Attributes, Color, Position, Separate, TexCoord, VertexFormat},
     visibility::Visibility,
     Rgba,
 };
@@ -48,6 +53,9 @@ static ATTRIBUTES: [Attributes<'static>; 2] = [
 /// See the [crate level documentation](index.html) for information about interleaved and separate
 /// passes.
 ///
+/// The albedo is multiplied with the `Rgba` component of the entity, and with the vertex colors
+/// if they are enabled with `with_vertex_colors`.
+///
 /// # Type Parameters
 ///
 /// * `N`: `RealBound` (f32, f64)
@@ -57,6 +65,9 @@ pub struct DrawFlatSeparate<N> {
     skinning: bool,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    vertex_colors: bool,
+    /// White vertex colors for meshes without colors, and the number of vertices they cover.
+    white: Option<(RawBuffer, usize)>,
     _pd: PhantomData<N>,
 }
 
@@ -76,6 +87,13 @@ where
         self
     }
 
+    /// Multiply the albedo with the `Color` vertex attribute, like the `COLOR_0` attribute of
+    /// glTF primitives. Meshes without vertex colors are drawn as if they were white.
+    pub fn with_vertex_colors(mut self) -> Self {
+        self.vertex_colors = true;
+        self
+    }
+
     /// Transparency is enabled by default.
     /// If you pass false to this function transparency will be disabled.
     ///
@@ -104,10 +122,25 @@ where
     }
 }
 
+impl<N> DrawFlatSeparate<N> {
+    /// Binds the vertex colors of the mesh if they are enabled, or white ones if it has none.
+    fn set_vertex_colors(
+        &mut self,
+        effect: &mut Effect,
+        factory: &mut Factory,
+        mesh: Option<&Mesh>,
+    ) {
+        if self.vertex_colors {
+            set_vertex_colors(effect, factory, mesh, &mut self.white);
+        }
+    }
+}
+
 impl<'a, N: RealField> PassData<'a> for DrawFlatSeparate<N> {
     type Data = (
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
         ReadExpect<'a, MaterialDefaults>,
@@ -125,11 +158,20 @@ impl<'a, N: RealField> PassData<'a> for DrawFlatSeparate<N> {
 impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
         use std::mem;
-        let mut builder = if self.skinning {
-            create_skinning_effect(effect, FRAG_SRC)
-        } else {
-            effect.simple(VERT_SRC, FRAG_SRC)
+        let mut builder = match (self.skinning, self.vertex_colors) {
+            (true, true) => create_colored_skinning_effect(effect, FRAG_SRC),
+            (true, false) => create_skinning_effect(effect, FRAG_SRC),
+            (false, true) => effect.simple(VERT_COLOR_SRC, FRAG_SRC),
+            (false, false) => effect.simple(VERT_SRC, FRAG_SRC),
         };
+        // The colors are bound before the buffers bound by `draw_mesh`.
+        if self.vertex_colors {
+            builder.with_raw_vertex_buffer(
+                Separate::<Color>::ATTRIBUTES,
+                Separate::<Color>::size() as ElemStride,
+                0,
+            );
+        }
         builder
             .with_raw_vertex_buffer(
                 Separate::<Position>::ATTRIBUTES,
@@ -161,10 +203,11 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
         &'a mut self,
         encoder: &mut Encoder,
         effect: &mut Effect,
-        _factory: Factory,
+        mut factory: Factory,
         (
             active,
             camera,
+            target_camera,
             mesh_storage,
             tex_storage,
             material_defaults,
@@ -178,7 +221,7 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
             rgba,
         ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
         match visibility {
             None => {
@@ -193,6 +236,7 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
                 )
                     .join()
                 {
+                    self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
                     draw_mesh(
                         encoder,
                         effect,
@@ -221,6 +265,7 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
                 )
                     .join()
                 {
+                    self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
                     draw_mesh(
                         encoder,
                         effect,
@@ -240,6 +285,7 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawFlatSeparate<N> {
 
                 for entity in &visibility.visible_ordered {
                     if let Some(mesh) = mesh.get(*entity) {
+                        self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
                         draw_mesh(
                             encoder,
                             effect,
@@ -16,11 +16,11 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     hidden::{Hidden, HiddenPropagate},
     mesh::MeshHandle,
     pass::util::{
-        add_texture, default_transparency, get_camera, set_view_args, set_view_args_screen,
+        add_texture, default_transparency, get_target_camera, set_view_args, set_view_args_screen,
         setup_textures, ViewArgs,
     },
     pipe::{
@@ -41,6 +41,10 @@ use super::*;
 
 /// Draws sprites on a 2D quad.
 ///
+/// The sprites are drawn with a draw call for each run of sprites using the same texture. The
+/// unordered sprites are sorted by texture first, and packing small images into a shared texture
+/// with a `SpriteAtlasBuilder` lets their sprites be drawn together.
+///
 /// # Type Parameters:
 ///
 /// * `N`: `RealBound` (f32, f64)
@@ -101,6 +105,7 @@ impl<'a, N: RealField + Default> PassData<'a> for DrawFlat2D<N> {
     type Data = (
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AssetStorage<SpriteSheet>>,
         Read<'a, AssetStorage<Texture>>,
         Option<Read<'a, SpriteVisibility>>,
@@ -147,6 +152,7 @@ impl<N: RealField + Default + SubsetOf<f32>> Pass for DrawFlat2D<N> {
         (
             active,
             camera,
+            target_camera,
             sprite_sheet_storage,
             tex_storage,
             visibility,
@@ -163,7 +169,7 @@ impl<N: RealField + Default + SubsetOf<f32>> Pass for DrawFlat2D<N> {
             screen_space_settings,
         ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
         match visibility {
             None => {
@@ -2,21 +2,31 @@
 //
 pub use self::{
     debug_lines::*,
+    depth::*,
+    dither::*,
     flat::*,
     flat2d::*,
+    object_id::*,
     pbm::*,
+    resolve::*,
     shaded::*,
+    shadow::*,
     skinning::set_skinning_buffers,
     skybox::*,
-    util::{get_camera, set_vertex_args},
+    util::{get_camera, get_target_camera, set_vertex_args},
 };
 
 mod debug_lines;
+mod depth;
+mod dither;
 mod flat;
 mod flat2d;
+mod object_id;
 mod pbm;
+mod resolve;
 mod shaded;
 mod shaded_util;
+mod shadow;
 mod skinning;
 mod skybox;
 mod util;
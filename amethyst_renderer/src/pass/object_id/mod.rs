@@ -0,0 +1,255 @@
+//! Object id pass, for picking.
+
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+use gfx::{
+    format::Formatted, handle::Buffer, memory::Typed, pso::buffer::ElemStride,
+    texture::RawImageInfo, Factory as GfxFactory,
+};
+use log::error;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, Write},
+    math::RealField,
+    transform::Transform,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera, TargetCamera},
+    hidden::{Hidden, HiddenPropagate},
+    mesh::{Mesh, MeshHandle},
+    pass::util::{get_target_camera, set_attribute_buffers, set_vertex_args, setup_vertex_args},
+    picking::{decode_id, encode_id, ObjectPicking, PickRequest, PickResult},
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect, Target, Targets,
+    },
+    types::{ColorFormat, Encoder, Factory, RawTexture, Resources},
+    vertex::{Position, Query},
+    visibility::Visibility,
+    Rgba,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/depth.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/object_id.glsl");
+
+/// Draw the id of each entity into the color buffer, for `ObjectPicking`.
+///
+/// Each pixel holds the id of the closest entity plus one as a little endian number in the
+/// RGBA channels, and 0 where no entity is drawn. Other passes can read the target as a mask,
+/// for selection outlines for example.
+///
+/// Add it in a stage with its own target, which needs a depth buffer:
+///
+/// ```rust,ignore
+/// let pipe = Pipeline::build()
+///     .with_target(Target::named("object_id").with_depth_buf(true))
+///     .with_stage(
+///         Stage::with_target("object_id")
+///             .clear_target([0.0, 0.0, 0.0, 0.0], 1.0)
+///             .with_pass(DrawObjectId::<PosNormTex>::new()),
+///     )
+///     .with_stage(/* The stages drawing to the window */);
+/// ```
+///
+/// # Type Parameters
+///
+/// * `V`: `VertexFormat`
+/// * `N`: `RealBound` (f32, f64)
+#[derive(Derivative, Clone, Debug)]
+#[derivative(Default(bound = "V: Query<(Position,)>"))]
+pub struct DrawObjectId<V, N> {
+    _marker: PhantomData<(V, N)>,
+    texture: Option<RawTexture>,
+    in_flight: Vec<(PickRequest, (u32, u32), Option<Buffer<Resources, [u8; 4]>>)>,
+}
+
+impl<V, N> DrawObjectId<V, N>
+where
+    V: Query<(Position,)>,
+{
+    /// Create instance of `DrawObjectId` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, V, N> PassData<'a> for DrawObjectId<V, N>
+where
+    V: Query<(Position,)>,
+    N: RealField,
+{
+    type Data = (
+        Entities<'a>,
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
+        Read<'a, AssetStorage<Mesh>>,
+        Option<Read<'a, Visibility>>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, MeshHandle>,
+        ReadStorage<'a, Transform<N>>,
+        Write<'a, ObjectPicking>,
+    );
+}
+
+impl<V, N> Pass for DrawObjectId<V, N>
+where
+    V: Query<(Position,)>,
+    N: RealField + SubsetOf<f32>,
+{
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
+        setup_vertex_args(&mut builder);
+        builder
+            .with_raw_global("object_id")
+            .with_output("color", Some(DepthMode::LessEqualWrite));
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        (
+            entities,
+            active,
+            camera,
+            target_camera,
+            mesh_storage,
+            visibility,
+            hidden,
+            hidden_prop,
+            mesh,
+            transform,
+            mut picking,
+        ): <Self as PassData<'a>>::Data,
+    ) {
+        // The copies of the last frame were submitted with it, so they can be read without
+        // waiting.
+        for (request, position, buffer) in self.in_flight.drain(..) {
+            let id = buffer.and_then(|buffer| match factory.read_mapping(&buffer) {
+                Ok(reader) => decode_id(reader[0]),
+                Err(err) => {
+                    error!("Failed to read the object id: {:?}", err);
+                    None
+                }
+            });
+            let entity = id
+                .map(|id| entities.entity(id))
+                .filter(|entity| entities.is_alive(*entity));
+            picking.finish(request, PickResult { position, entity });
+        }
+
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
+
+        {
+            let mut draw = |entity: Entity, mesh: &MeshHandle, transform: &Transform<N>| {
+                let mesh = match mesh_storage.get(mesh) {
+                    Some(mesh) => mesh,
+                    None => return,
+                };
+                if !set_attribute_buffers(effect, mesh, &[V::QUERIED_ATTRIBUTES]) {
+                    effect.clear();
+                    return;
+                }
+                set_vertex_args(
+                    effect,
+                    encoder,
+                    camera,
+                    transform.global_matrix(),
+                    Rgba::WHITE,
+                );
+                effect.update_global("object_id", encode_id(entity.id()));
+                effect.draw(mesh.slice(), encoder);
+                effect.clear();
+            };
+
+            match visibility {
+                None => {
+                    for (entity, mesh, transform, _, _) in
+                        (&entities, &mesh, &transform, !&hidden, !&hidden_prop).join()
+                    {
+                        draw(entity, mesh, transform);
+                    }
+                }
+                Some(ref visibility) => {
+                    for (entity, mesh, transform, _) in
+                        (&entities, &mesh, &transform, &visibility.visible_unordered).join()
+                    {
+                        draw(entity, mesh, transform);
+                    }
+                    for &entity in &visibility.visible_ordered {
+                        if let (Some(mesh), Some(transform)) =
+                            (mesh.get(entity), transform.get(entity))
+                        {
+                            draw(entity, mesh, transform);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (request, position) in picking.drain_pending() {
+            let buffer = self
+                .texture
+                .as_ref()
+                .and_then(|texture| copy_pixel(encoder, &mut factory, texture, position));
+            self.in_flight.push((request, position, buffer));
+        }
+    }
+
+    fn new_targets(&mut self, output: &Target, _targets: &Targets) {
+        self.texture = output.color_texture(0).cloned();
+        if self.texture.is_none() {
+            error!("`DrawObjectId` needs a stage with its own target, it can't read the window");
+        }
+    }
+}
+
+/// Copies a pixel of the texture into a new download buffer, `None` if it's outside of the
+/// texture.
+fn copy_pixel(
+    encoder: &mut Encoder,
+    factory: &mut Factory,
+    texture: &RawTexture,
+    (x, y): (u32, u32),
+) -> Option<Buffer<Resources, [u8; 4]>> {
+    let (width, height, _, _) = texture.get_info().kind.get_dimensions();
+    if x >= u32::from(width) || y >= u32::from(height) {
+        return None;
+    }
+    // OpenGL counts the rows from the bottom.
+    let y = if cfg!(feature = "opengl") {
+        height - 1 - y as u16
+    } else {
+        y as u16
+    };
+
+    let buffer = factory
+        .create_download_buffer::<[u8; 4]>(1)
+        .map_err(|err| error!("Failed to create the object id buffer: {:?}", err))
+        .ok()?;
+    let info = RawImageInfo {
+        xoffset: x as u16,
+        yoffset: y,
+        zoffset: 0,
+        width: 1,
+        height: 1,
+        depth: 1,
+        format: ColorFormat::get_format(),
+        mipmap: 0,
+    };
+    encoder
+        .copy_texture_to_buffer_raw(texture, None, info, buffer.raw(), 0)
+        .map_err(|err| error!("Failed to copy the object id: {:?}", err))
+        .ok()?;
+    Some(buffer)
+}
@@ -1,6 +1,6 @@
 //! Forward physically-based drawing pass.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, mem};
 
 use derivative::Derivative;
 use gfx::{pso::buffer::ElemStride, traits::Pod};
@@ -9,29 +9,36 @@ use gfx_core::state::{Blend, ColorMask};
 use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    ecs::prelude::{Entities, Join, Read, ReadExpect, ReadStorage},
     math::RealField,
     transform::Transform,
 };
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
+    cubemap::Cubemap,
+    environment::EnvironmentMap,
     hidden::{Hidden, HiddenPropagate},
     light::Light,
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
-        shaded_util::{set_light_args, setup_light_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        shaded_util::{set_light_args, setup_light_buffers, EnvironmentLighting, ShadowMap},
+        util::{
+            default_transparency, draw_mesh, get_target_camera, set_stencil_reference,
+            setup_textures, setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
-        DepthMode, Effect, NewEffect,
+        DepthMode, Effect, NewEffect, Target, Targets,
     },
     resources::AmbientColor,
+    shadow::LightSpace,
+    stencil::{StencilMask, StencilSettings},
     tex::Texture,
-    types::{Encoder, Factory},
+    types::{Encoder, Factory, RawBuffer},
     vertex::{Normal, Position, Query, Tangent, TexCoord},
     visibility::Visibility,
     Rgba,
@@ -54,6 +61,14 @@ pub struct DrawPbm<V, N> {
     _marker: PhantomData<(V, N)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    depth_prepass: bool,
+    stencil: Option<StencilSettings>,
+    alpha_mask: AlphaMask,
+    shadow_map: Option<ShadowMap>,
+    environment: Option<EnvironmentLighting>,
+    instancing: bool,
+    /// The per instance buffer of `with_instancing`, and the number of instances it holds.
+    instances: Option<(RawBuffer, usize)>,
 }
 
 impl<V, N> DrawPbm<V, N>
@@ -81,6 +96,15 @@ where
         self
     }
 
+    /// Expect the depth of the opaque meshes to be drawn by a `DrawDepth` pass before this one.
+    ///
+    /// The depth is then only tested, so of the opaque meshes only the closest surface of each
+    /// pixel is shaded. Transparent meshes in front of them are still drawn.
+    pub fn with_depth_prepass(mut self, input: bool) -> Self {
+        self.depth_prepass = input;
+        self
+    }
+
     /// Set transparency settings to custom values.
     pub fn with_transparency_settings(
         mut self,
@@ -91,6 +115,45 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Sets the stencil state. Each entity is drawn with the reference value of its
+    /// `StencilMask`, or 0 without one.
+    pub fn with_stencil(mut self, stencil: StencilSettings) -> Self {
+        self.stencil = Some(stencil);
+        self
+    }
+
+    /// Sets how the alpha of materials with an `alpha_cutoff` is used, see `AlphaMask`.
+    pub fn with_alpha_mask(mut self, mask: AlphaMask) -> Self {
+        self.alpha_mask = mask;
+        self
+    }
+
+    /// Shade the light of the `LightSpace` with the shadow map in the depth buffer of the
+    /// target with the given name, drawn by a `DrawShadowMap` pass in an earlier stage.
+    pub fn with_shadows<T: Into<String>>(mut self, target: T) -> Self {
+        self.shadow_map = Some(ShadowMap::new(target.into()));
+        self
+    }
+
+    /// Add the light of the `EnvironmentMap` resource to the ambient light, as reflected by the
+    /// surfaces of the materials. Without the resource, or until its cubemaps are loaded, only
+    /// the `AmbientColor` is used.
+    pub fn with_environment(mut self) -> Self {
+        self.environment = Some(EnvironmentLighting::new());
+        self
+    }
+
+    /// Draw the entities sharing a mesh, a material and a stencil reference with one draw call,
+    /// putting their model matrices and colors in a per instance buffer, for scenes with many
+    /// copies of the same object.
+    ///
+    /// The ordered entities of the `Visibility`, like the transparent ones, are still drawn one
+    /// by one.
+    pub fn with_instancing(mut self, input: bool) -> Self {
+        self.instancing = input;
+        self
+    }
 }
 
 impl<'a, V, N> PassData<'a> for DrawPbm<V, N>
@@ -99,8 +162,10 @@ where
     N: RealField,
 {
     type Data = (
+        Entities<'a>,
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AmbientColor>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
@@ -113,6 +178,10 @@ where
         ReadStorage<'a, Transform<N>>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, StencilMask>,
+        Read<'a, LightSpace>,
+        Option<Read<'a, EnvironmentMap>>,
+        Read<'a, AssetStorage<Cubemap>>,
     );
 }
 
@@ -121,16 +190,52 @@ where
     V: Query<(Position, Normal, Tangent, TexCoord)>,
     N: RealField + SubsetOf<f32> + Pod,
 {
-    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
-        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
+        if let Some(ref mut shadow_map) = self.shadow_map {
+            shadow_map.create_sampler(effect.factory);
+        }
+        if let Some(ref mut environment) = self.environment {
+            environment.create_textures(effect.factory)?;
+        }
+        let vert_src = if self.instancing {
+            VERT_INSTANCED_SRC
+        } else {
+            VERT_SRC
+        };
+        let mut builder = effect.simple(vert_src, FRAG_SRC);
         builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
+        if self.instancing {
+            builder.with_raw_vertex_buffer(
+                INSTANCE_ATTRIBUTES,
+                mem::size_of::<Instance>() as ElemStride,
+                1,
+            );
+        }
         setup_vertex_args(&mut builder);
         setup_light_buffers(&mut builder);
+        if self.shadow_map.is_some() {
+            ShadowMap::setup(&mut builder);
+        }
+        if self.environment.is_some() {
+            EnvironmentLighting::setup(&mut builder);
+        }
         setup_textures(&mut builder, &TEXTURES);
-        match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
+        setup_alpha_mask(&mut builder);
+        // The depth pre-pass already wrote the depth of the opaque meshes.
+        let depth_prepass = self.depth_prepass;
+        let depth = |depth| match depth_prepass {
+            true => Some(DepthMode::LessEqualTest),
+            false => depth,
+        };
+        match self.alpha_mask.transparency(self.transparency) {
+            Some((mask, blend, mode)) => {
+                builder.with_blended_output("color", mask, blend, depth(mode))
+            }
+            None => builder.with_output("color", depth(Some(DepthMode::LessEqualWrite))),
         };
+        if let Some(stencil) = self.stencil {
+            builder.with_stencil(stencil);
+        }
         builder.build()
     }
 
@@ -138,10 +243,12 @@ where
         &'a mut self,
         encoder: &mut Encoder,
         effect: &mut Effect,
-        _factory: Factory,
+        mut factory: Factory,
         (
+            entities,
             active,
             camera,
+            target_camera,
             ambient,
             mesh_storage,
             tex_storage,
@@ -154,24 +261,124 @@ where
             transform,
             light,
             rgba,
+            stencil,
+            light_space,
+            environment_map,
+            cubemap_storage,
         ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
-        set_light_args(effect, encoder, &light, &transform, &ambient, camera);
+        let shadowed = self
+            .shadow_map
+            .as_ref()
+            .and_then(|shadow_map| shadow_map.shadowed(&light_space));
+        set_light_args(
+            effect, encoder, &entities, &light, &transform, &ambient, camera, shadowed,
+        );
+        if let Some(ref shadow_map) = self.shadow_map {
+            shadow_map.set_args(effect, &light_space);
+        }
+        if let Some(ref mut environment) = self.environment {
+            environment.set_args(
+                effect,
+                environment_map.as_ref().map(|e| &**e),
+                &cubemap_storage,
+            );
+        }
+        effect.update_global("alpha_mask", self.alpha_mask.id());
+
+        if self.instancing {
+            let mut batches = Batches::default();
+            let mut ordered = Batches::default();
+            match visibility {
+                None => {
+                    for (mesh, material, transform, rgba, mask, _, _) in (
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        stencil.maybe(),
+                        !&hidden,
+                        !&hidden_prop,
+                    )
+                        .join()
+                    {
+                        let instance = Instance::new(transform, rgba);
+                        batches.add(&mesh_storage, mesh, material, mask, instance);
+                    }
+                }
+                Some(ref visibility) => {
+                    for (mesh, material, transform, rgba, mask, _) in (
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        stencil.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
+                        let instance = Instance::new(transform, rgba);
+                        batches.add(&mesh_storage, mesh, material, mask, instance);
+                    }
+                    for entity in &visibility.visible_ordered {
+                        if let (Some(mesh), Some(material), Some(transform)) = (
+                            mesh.get(*entity),
+                            material.get(*entity),
+                            transform.get(*entity),
+                        ) {
+                            let instance = Instance::new(transform, rgba.get(*entity));
+                            let mask = stencil.get(*entity);
+                            ordered.push(&mesh_storage, mesh, material, mask, instance);
+                        }
+                    }
+                }
+            }
+            for batch in batches.batches.iter().chain(&ordered.batches) {
+                bind_light_maps(
+                    self.shadow_map.as_ref(),
+                    self.environment.as_ref(),
+                    effect,
+                    &tex_storage,
+                    &material_defaults,
+                );
+                draw_batch(
+                    encoder,
+                    effect,
+                    &mut factory,
+                    &mut self.instances,
+                    batch,
+                    &tex_storage,
+                    &material_defaults,
+                    camera,
+                    V::QUERIED_ATTRIBUTES,
+                );
+            }
+            return;
+        }
 
         match visibility {
             None => {
-                for (mesh, material, transform, rgba, _, _) in (
+                for (mesh, material, transform, rgba, mask, _, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    stencil.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
+                    bind_light_maps(
+                        self.shadow_map.as_ref(),
+                        self.environment.as_ref(),
+                        effect,
+                        &tex_storage,
+                        &material_defaults,
+                    );
                     draw_mesh(
                         encoder,
                         effect,
@@ -190,15 +397,24 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, transform, rgba, _) in (
+                for (mesh, material, transform, rgba, mask, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    stencil.maybe(),
                     &visibility.visible_unordered,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
+                    bind_light_maps(
+                        self.shadow_map.as_ref(),
+                        self.environment.as_ref(),
+                        effect,
+                        &tex_storage,
+                        &material_defaults,
+                    );
                     draw_mesh(
                         encoder,
                         effect,
@@ -218,6 +434,14 @@ where
 
                 for entity in &visibility.visible_ordered {
                     if let Some(mesh) = mesh.get(*entity) {
+                        set_stencil_reference(effect, stencil.get(*entity));
+                        bind_light_maps(
+                            self.shadow_map.as_ref(),
+                            self.environment.as_ref(),
+                            effect,
+                            &tex_storage,
+                            &material_defaults,
+                        );
                         draw_mesh(
                             encoder,
                             effect,
@@ -238,4 +462,10 @@ where
             }
         }
     }
+
+    fn new_targets(&mut self, _output: &Target, targets: &Targets) {
+        if let Some(ref mut shadow_map) = self.shadow_map {
+            shadow_map.new_targets(targets);
+        }
+    }
 }
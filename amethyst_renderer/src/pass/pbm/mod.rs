@@ -3,9 +3,49 @@ pub use self::{interleaved::DrawPbm, separate::DrawPbmSeparate};
 mod interleaved;
 mod separate;
 
-use crate::pass::util::TextureType;
+use fnv::FnvHashMap as HashMap;
+use gfx::{
+    buffer::Role,
+    format::{ChannelType, Format, SurfaceType},
+    handle::Buffer,
+    memory::{Bind, Typed, Usage},
+    pso::buffer::Element,
+    traits::Pod,
+    Factory as GfxFactory,
+};
+use gfx_core::state::{Blend, ColorMask};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    alga::general::SubsetOf,
+    math::{convert, Matrix4, RealField},
+    transform::Transform,
+};
+
+use crate::{
+    cam::Camera,
+    mesh::{Mesh, MeshHandle},
+    mtl::{Material, MaterialDefaults},
+    pass::{
+        shaded_util::{EnvironmentLighting, ShadowMap},
+        util::{
+            add_textures, set_attribute_buffers, set_stencil_reference, set_vertex_args,
+            TextureType,
+        },
+    },
+    pipe::{DepthMode, Effect, EffectBuilder},
+    stencil::StencilMask,
+    tex::Texture,
+    types::{Encoder, Factory, RawBuffer, Resources},
+    vertex::{AttributeFormat, Attributes},
+    Rgba, ALPHA,
+};
 
 static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/basic.glsl");
+static VERT_INSTANCED_SRC: &[u8] = include_bytes!("../shaders/vertex/basic_instanced.glsl");
+static VERT_COLOR_SRC: &[u8] = include_bytes!("../shaders/vertex/basic_color.glsl");
 static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/pbm.glsl");
 
 static TEXTURES: [TextureType; 7] = [
@@ -17,3 +57,263 @@ static TEXTURES: [TextureType; 7] = [
     TextureType::Normal,
     TextureType::Albedo,
 ];
+
+/// How `DrawPbm` and `DrawPbmSeparate` use the alpha of materials with an `alpha_cutoff`, to
+/// draw masked materials like foliage without aliased edges. Materials with an `alpha_cutoff` of
+/// 0 aren't masked.
+///
+/// For the dual pass mode, add the pass twice to the stage, first with `Opaque` and then with
+/// `Fringe`:
+///
+/// ```rust,ignore
+/// Stage::with_backbuffer()
+///     .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+///     .with_pass(DrawPbm::<PosNormTangTex>::new().with_alpha_mask(AlphaMask::Opaque))
+///     .with_pass(DrawPbm::<PosNormTangTex>::new().with_alpha_mask(AlphaMask::Fringe))
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AlphaMask {
+    /// Discard the fragments with an alpha below the cutoff.
+    Cutoff,
+    /// Turn the alpha around the cutoff into the coverage of the samples, which gives smooth
+    /// edges with multisampling enabled. Without multisampling, or on drivers without
+    /// `GL_ARB_sample_shading`, it acts like `Cutoff`.
+    Coverage,
+    /// Draw the part at or above the cutoff as opaque, writing the depth.
+    Opaque,
+    /// Draw the part below the cutoff blended on top of the opaque part, without writing the
+    /// depth. The transparency settings of the pass are ignored. Unmasked materials are skipped,
+    /// the `Opaque` pass draws them whole.
+    Fringe,
+}
+
+impl Default for AlphaMask {
+    fn default() -> Self {
+        AlphaMask::Cutoff
+    }
+}
+
+impl AlphaMask {
+    /// The value of the `alpha_mask` uniform of the shader.
+    fn id(self) -> i32 {
+        match self {
+            AlphaMask::Cutoff => 0,
+            AlphaMask::Coverage => 1,
+            AlphaMask::Opaque => 2,
+            AlphaMask::Fringe => 3,
+        }
+    }
+
+    /// The transparency settings of a pass drawing with this mask.
+    fn transparency(
+        self,
+        transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    ) -> Option<(ColorMask, Blend, Option<DepthMode>)> {
+        match self {
+            AlphaMask::Fringe => Some((ColorMask::all(), ALPHA, Some(DepthMode::LessEqualTest))),
+            _ => transparency,
+        }
+    }
+}
+
+fn setup_alpha_mask(builder: &mut EffectBuilder<'_>) {
+    builder
+        .with_raw_global("alpha_cutoff")
+        .with_raw_global("alpha_mask");
+}
+
+/// Binds the shadow map and the environment of a pass drawing with them, before the textures of
+/// the material.
+fn bind_light_maps(
+    shadow_map: Option<&ShadowMap>,
+    environment: Option<&EnvironmentLighting>,
+    effect: &mut Effect,
+    tex_storage: &AssetStorage<Texture>,
+    material_defaults: &MaterialDefaults,
+) {
+    if let Some(shadow_map) = shadow_map {
+        shadow_map.bind(effect, tex_storage.get(&material_defaults.0.albedo));
+    }
+    if let Some(environment) = environment {
+        environment.bind(effect);
+    }
+}
+
+/// A four component float attribute of the instance buffer, at the given offset.
+const fn vec4(offset: u32) -> AttributeFormat {
+    Element {
+        offset,
+        format: Format(SurfaceType::R32_G32_B32_A32, ChannelType::Float),
+    }
+}
+
+/// The attributes of the per instance buffer of `DrawPbm::with_instancing`.
+static INSTANCE_ATTRIBUTES: Attributes<'static> = &[
+    ("model_0", vec4(0)),
+    ("model_1", vec4(16)),
+    ("model_2", vec4(32)),
+    ("model_3", vec4(48)),
+    ("instance_color", vec4(64)),
+];
+
+/// The columns of the model matrix and the color of one instance.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct Instance {
+    model: [[f32; 4]; 4],
+    color: [f32; 4],
+}
+
+unsafe impl Pod for Instance {}
+
+impl Instance {
+    fn new<N: RealField + SubsetOf<f32>>(transform: &Transform<N>, rgba: Option<&Rgba>) -> Self {
+        let rgba = rgba.cloned().unwrap_or(Rgba::WHITE);
+        Instance {
+            model: convert::<Matrix4<N>, Matrix4<f32>>(*transform.global_matrix()).into(),
+            color: [rgba.0, rgba.1, rgba.2, rgba.3],
+        }
+    }
+}
+
+/// The entities drawn with one instanced draw call, which share a mesh, a material and a
+/// stencil reference.
+struct Batch<'a> {
+    mesh: &'a Mesh,
+    material: &'a Material,
+    stencil: Option<&'a StencilMask>,
+    instances: Vec<Instance>,
+}
+
+/// Groups the entities of a frame into batches, kept in the order of the first entity of each.
+#[derive(Default)]
+struct Batches<'a> {
+    batches: Vec<Batch<'a>>,
+    by_mesh: HashMap<(u32, u8), Vec<usize>>,
+}
+
+impl<'a> Batches<'a> {
+    /// Adds an entity to the batch of its mesh, material and stencil reference.
+    fn add(
+        &mut self,
+        mesh_storage: &'a AssetStorage<Mesh>,
+        mesh: &MeshHandle,
+        material: &'a Material,
+        stencil: Option<&'a StencilMask>,
+        instance: Instance,
+    ) {
+        let key = (mesh.id(), stencil.map_or(0, |mask| mask.0));
+        let batches = &mut self.batches;
+        let indices = self.by_mesh.entry(key).or_insert_with(Vec::new);
+        match indices.iter().find(|&&i| *batches[i].material == *material) {
+            Some(&i) => batches[i].instances.push(instance),
+            None => {
+                if let Some(mesh) = mesh_storage.get(mesh) {
+                    indices.push(batches.len());
+                    batches.push(Batch {
+                        mesh,
+                        material,
+                        stencil,
+                        instances: vec![instance],
+                    });
+                }
+            }
+        }
+    }
+
+    /// Adds an entity as a batch of its own, for entities drawn in order.
+    fn push(
+        &mut self,
+        mesh_storage: &'a AssetStorage<Mesh>,
+        mesh: &MeshHandle,
+        material: &'a Material,
+        stencil: Option<&'a StencilMask>,
+        instance: Instance,
+    ) {
+        if let Some(mesh) = mesh_storage.get(mesh) {
+            self.batches.push(Batch {
+                mesh,
+                material,
+                stencil,
+                instances: vec![instance],
+            });
+        }
+    }
+}
+
+/// Returns the instance buffer of the pass, after replacing it with a larger one if it can't hold
+/// `len` instances.
+fn instance_buffer(
+    factory: &mut Factory,
+    instances: &mut Option<(RawBuffer, usize)>,
+    len: usize,
+) -> Option<Buffer<Resources, Instance>> {
+    if instances
+        .as_ref()
+        .map_or(true, |&(_, capacity)| capacity < len)
+    {
+        let capacity = len.next_power_of_two();
+        *instances = factory
+            .create_buffer::<Instance>(capacity, Role::Vertex, Usage::Dynamic, Bind::empty())
+            .map_err(|err| error!("Failed to create the instance buffer: {:?}", err))
+            .ok()
+            .map(|buffer| (buffer.raw().clone(), capacity));
+    }
+    instances
+        .as_ref()
+        .map(|&(ref buffer, _)| Typed::new(buffer.clone()))
+}
+
+/// Draws a batch with one instanced draw call.
+///
+/// The instances are uploaded into the instance buffer of the pass, which is shared by all
+/// batches and grows to fit the largest one.
+fn draw_batch<N>(
+    encoder: &mut Encoder,
+    effect: &mut Effect,
+    factory: &mut Factory,
+    instances: &mut Option<(RawBuffer, usize)>,
+    batch: &Batch<'_>,
+    tex_storage: &AssetStorage<Texture>,
+    material_defaults: &MaterialDefaults,
+    camera: Option<(&Camera, &Transform<N>)>,
+    attributes: Attributes<'static>,
+) where
+    N: RealField + SubsetOf<f32>,
+{
+    if !set_attribute_buffers(effect, batch.mesh, &[attributes]) {
+        effect.clear();
+        return;
+    }
+    let buffer = match instance_buffer(factory, instances, batch.instances.len()) {
+        Some(buffer) => buffer,
+        None => {
+            effect.clear();
+            return;
+        }
+    };
+    if let Err(err) = encoder.update_buffer(&buffer, &batch.instances, 0) {
+        error!("Failed to update the instance buffer: {:?}", err);
+        effect.clear();
+        return;
+    }
+    effect.data.vertex_bufs.push(buffer.raw().clone());
+
+    set_vertex_args(effect, encoder, camera, &Matrix4::identity(), Rgba::WHITE);
+    set_stencil_reference(effect, batch.stencil);
+    let material = add_textures(
+        effect,
+        encoder,
+        tex_storage,
+        batch.material,
+        &material_defaults.0,
+        &TEXTURES,
+    );
+    effect.update_global("unlit", i32::from(material.unlit));
+    effect.update_global("alpha_cutoff", material.alpha_cutoff);
+
+    let mut slice = batch.mesh.slice().clone();
+    slice.instances = Some((batch.instances.len() as u32, 0));
+    effect.draw(&slice, encoder);
+    effect.clear();
+}
@@ -12,32 +12,40 @@ use thread_profiler::profile_scope;
 use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    ecs::prelude::{Entities, Join, Read, ReadExpect, ReadStorage},
     math::RealField,
     transform::Transform,
 };
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
+    cubemap::Cubemap,
+    environment::EnvironmentMap,
     hidden::{Hidden, HiddenPropagate},
     light::Light,
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
-        shaded_util::{set_light_args, setup_light_buffers},
-        skinning::{create_skinning_effect, setup_skinning_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        shaded_util::{set_light_args, setup_light_buffers, EnvironmentLighting, ShadowMap},
+        skinning::{
+            create_colored_skinning_effect, create_skinning_effect, setup_skinning_buffers,
+        },
+        util::{
+            default_transparency, draw_mesh, get_target_camera, set_vertex_colors, setup_textures,
+            setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
-        DepthMode, Effect, NewEffect,
+        DepthMode, Effect, NewEffect, Target, Targets,
     },
     resources::AmbientColor,
+    shadow::LightSpace,
     skinning::JointTransforms,
     tex::Texture,
-    types::{Encoder, Factory},
-    vertex::{Attributes, Normal, Position, Separate, Tangent, TexCoord, VertexFormat},
+    types::{Encoder, Factory, RawBuffer},
+    vertex::{Attributes, Color, Normal, Position, Separate, Tangent, TexCoord, VertexFormat},
     visibility::Visibility,
     Rgba,
 };
@@ -56,6 +64,9 @@ static ATTRIBUTES: [Attributes<'static>; 4] = [
 /// See the [crate level documentation](index.html) for information about interleaved and separate
 /// passes.
 ///
+/// The albedo is multiplied with the `Rgba` component of the entity, and with the vertex colors
+/// if they are enabled with `with_vertex_colors`.
+///
 /// # Type Parameters:
 ///
 /// * `N`: `RealBound` (f32, f64)
@@ -66,6 +77,12 @@ pub struct DrawPbmSeparate<N> {
     skinning: bool,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    vertex_colors: bool,
+    /// White vertex colors for meshes without colors, and the number of vertices they cover.
+    white: Option<(RawBuffer, usize)>,
+    alpha_mask: AlphaMask,
+    shadow_map: Option<ShadowMap>,
+    environment: Option<EnvironmentLighting>,
 }
 
 impl<N> DrawPbmSeparate<N> {
@@ -80,6 +97,13 @@ impl<N> DrawPbmSeparate<N> {
         self
     }
 
+    /// Multiply the albedo with the `Color` vertex attribute, like the `COLOR_0` attribute of
+    /// glTF primitives. Meshes without vertex colors are drawn as if they were white.
+    pub fn with_vertex_colors(mut self) -> Self {
+        self.vertex_colors = true;
+        self
+    }
+
     /// Transparency is enabled by default.
     /// If you pass false to this function transparency will be disabled.
     ///
@@ -106,12 +130,49 @@ impl<N> DrawPbmSeparate<N> {
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Sets how the alpha of materials with an `alpha_cutoff` is used, see `AlphaMask`.
+    pub fn with_alpha_mask(mut self, mask: AlphaMask) -> Self {
+        self.alpha_mask = mask;
+        self
+    }
+
+    /// Shade the light of the `LightSpace` with the shadow map in the depth buffer of the
+    /// target with the given name, drawn by a `DrawShadowMap` pass in an earlier stage.
+    pub fn with_shadows<T: Into<String>>(mut self, target: T) -> Self {
+        self.shadow_map = Some(ShadowMap::new(target.into()));
+        self
+    }
+
+    /// Add the light of the `EnvironmentMap` resource to the ambient light, as reflected by the
+    /// surfaces of the materials. Without the resource, or until its cubemaps are loaded, only
+    /// the `AmbientColor` is used.
+    pub fn with_environment(mut self) -> Self {
+        self.environment = Some(EnvironmentLighting::new());
+        self
+    }
+}
+
+impl<N> DrawPbmSeparate<N> {
+    /// Binds the vertex colors of the mesh if they are enabled, or white ones if it has none.
+    fn set_vertex_colors(
+        &mut self,
+        effect: &mut Effect,
+        factory: &mut Factory,
+        mesh: Option<&Mesh>,
+    ) {
+        if self.vertex_colors {
+            set_vertex_colors(effect, factory, mesh, &mut self.white);
+        }
+    }
 }
 
 impl<'a, N: RealField> PassData<'a> for DrawPbmSeparate<N> {
     type Data = (
+        Entities<'a>,
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AmbientColor>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
@@ -125,19 +186,37 @@ impl<'a, N: RealField> PassData<'a> for DrawPbmSeparate<N> {
         ReadStorage<'a, Light>,
         ReadStorage<'a, JointTransforms<N>>,
         ReadStorage<'a, Rgba>,
+        Read<'a, LightSpace>,
+        Option<Read<'a, EnvironmentMap>>,
+        Read<'a, AssetStorage<Cubemap>>,
     );
 }
 
 impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
-    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
         #[cfg(feature = "profiler")]
         profile_scope!("render_pass_pbm_compile");
 
-        let mut builder = if self.skinning {
-            create_skinning_effect(effect, FRAG_SRC)
-        } else {
-            effect.simple(VERT_SRC, FRAG_SRC)
+        if let Some(ref mut shadow_map) = self.shadow_map {
+            shadow_map.create_sampler(effect.factory);
+        }
+        if let Some(ref mut environment) = self.environment {
+            environment.create_textures(effect.factory)?;
+        }
+        let mut builder = match (self.skinning, self.vertex_colors) {
+            (true, true) => create_colored_skinning_effect(effect, FRAG_SRC),
+            (true, false) => create_skinning_effect(effect, FRAG_SRC),
+            (false, true) => effect.simple(VERT_COLOR_SRC, FRAG_SRC),
+            (false, false) => effect.simple(VERT_SRC, FRAG_SRC),
         };
+        // The colors are bound before the buffers bound by `draw_mesh`.
+        if self.vertex_colors {
+            builder.with_raw_vertex_buffer(
+                Separate::<Color>::ATTRIBUTES,
+                Separate::<Color>::size() as ElemStride,
+                0,
+            );
+        }
         builder
             .with_raw_vertex_buffer(
                 Separate::<Position>::ATTRIBUTES,
@@ -164,8 +243,15 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
         }
         setup_vertex_args(&mut builder);
         setup_light_buffers(&mut builder);
+        if self.shadow_map.is_some() {
+            ShadowMap::setup(&mut builder);
+        }
+        if self.environment.is_some() {
+            EnvironmentLighting::setup(&mut builder);
+        }
         setup_textures(&mut builder, &TEXTURES);
-        match self.transparency {
+        setup_alpha_mask(&mut builder);
+        match self.alpha_mask.transparency(self.transparency) {
             Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
             None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
         };
@@ -176,10 +262,12 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
         &'a mut self,
         encoder: &mut Encoder,
         effect: &mut Effect,
-        _factory: Factory,
+        mut factory: Factory,
         (
+            entities,
             active,
             camera,
+            target_camera,
             ambient,
             mesh_storage,
             tex_storage,
@@ -193,14 +281,34 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
             light,
             joints,
             rgba,
+            light_space,
+            environment_map,
+            cubemap_storage,
         ): <Self as PassData<'a>>::Data,
     ) {
         #[cfg(feature = "profiler")]
         profile_scope!("render_pass_pbm_apply");
 
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
-        set_light_args(effect, encoder, &light, &transform, &ambient, camera);
+        let shadowed = self
+            .shadow_map
+            .as_ref()
+            .and_then(|shadow_map| shadow_map.shadowed(&light_space));
+        set_light_args(
+            effect, encoder, &entities, &light, &transform, &ambient, camera, shadowed,
+        );
+        if let Some(ref shadow_map) = self.shadow_map {
+            shadow_map.set_args(effect, &light_space);
+        }
+        if let Some(ref mut environment) = self.environment {
+            environment.set_args(
+                effect,
+                environment_map.as_ref().map(|e| &**e),
+                &cubemap_storage,
+            );
+        }
+        effect.update_global("alpha_mask", self.alpha_mask.id());
 
         match visibility {
             None => {
@@ -215,6 +323,14 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
                 )
                     .join()
                 {
+                    self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
+                    bind_light_maps(
+                        self.shadow_map.as_ref(),
+                        self.environment.as_ref(),
+                        effect,
+                        &tex_storage,
+                        &material_defaults,
+                    );
                     draw_mesh(
                         encoder,
                         effect,
@@ -243,6 +359,14 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
                 )
                     .join()
                 {
+                    self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
+                    bind_light_maps(
+                        self.shadow_map.as_ref(),
+                        self.environment.as_ref(),
+                        effect,
+                        &tex_storage,
+                        &material_defaults,
+                    );
                     draw_mesh(
                         encoder,
                         effect,
@@ -262,6 +386,14 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
 
                 for entity in &visibility.visible_ordered {
                     if let Some(mesh) = mesh.get(*entity) {
+                        self.set_vertex_colors(effect, &mut factory, mesh_storage.get(mesh));
+                        bind_light_maps(
+                            self.shadow_map.as_ref(),
+                            self.environment.as_ref(),
+                            effect,
+                            &tex_storage,
+                            &material_defaults,
+                        );
                         draw_mesh(
                             encoder,
                             effect,
@@ -282,4 +414,10 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawPbmSeparate<N> {
             }
         }
     }
+
+    fn new_targets(&mut self, _output: &Target, targets: &Targets) {
+        if let Some(ref mut shadow_map) = self.shadow_map {
+            shadow_map.new_targets(targets);
+        }
+    }
 }
@@ -0,0 +1,136 @@
+//! Multisample resolve pass
+
+use gfx::{
+    memory::Typed,
+    pso::buffer::ElemStride,
+    texture::{FilterMethod, SamplerInfo, WrapMode},
+    Factory as GfxFactory,
+};
+use log::warn;
+
+use amethyst_core::math::{Vector2, Vector3};
+use amethyst_error::Error;
+
+use crate::{
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, Target, Targets,
+    },
+    types::{RawShaderResourceView, Sampler},
+    Encoder, Factory, Mesh, PosTex, VertexFormat,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/resolve.glsl");
+
+/// Resolves the first color buffer of a multisampled target into the target of its stage, by
+/// averaging the samples of each pixel.
+///
+/// The source is built with `TargetBuilder::with_samples`, and has to be as large as the target
+/// of the stage. See `TargetBuilder::with_samples` for a pipeline using it.
+#[derive(Clone, Debug)]
+pub struct DrawResolve {
+    source: String,
+    samples: u8,
+    mesh: Option<Mesh>,
+    sampler: Option<Sampler>,
+    view: Option<RawShaderResourceView>,
+}
+
+impl DrawResolve {
+    /// Create instance of `DrawResolve` pass, reading the target with the given name.
+    pub fn new<N: Into<String>>(source: N) -> Self {
+        DrawResolve {
+            source: source.into(),
+            samples: 0,
+            mesh: None,
+            sampler: None,
+            view: None,
+        }
+    }
+}
+
+impl<'a> PassData<'a> for DrawResolve {
+    type Data = ();
+}
+
+impl Pass for DrawResolve {
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let corner = |x: f32, y: f32| PosTex {
+            position: Vector3::new(x, y, 0.0),
+            tex_coord: Vector2::new((x + 1.0) / 2.0, (y + 1.0) / 2.0),
+        };
+        let verts = vec![
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, 1.0),
+        ];
+        self.mesh = Some(Mesh::build(verts).build(&mut effect.factory)?);
+        // Multisampled textures are fetched per sample, the sampler is only bound to satisfy
+        // the texture slot.
+        self.sampler = Some(
+            effect
+                .factory
+                .create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp)),
+        );
+
+        effect
+            .simple(VERT_SRC, FRAG_SRC)
+            .without_back_face_culling()
+            .with_raw_vertex_buffer(PosTex::ATTRIBUTES, PosTex::size() as ElemStride, 0)
+            .with_texture("source")
+            .with_raw_global("samples")
+            .with_output("color", None)
+            .build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        _: (),
+    ) {
+        let mesh = self
+            .mesh
+            .as_ref()
+            .expect("Pass doesn't seem to be compiled.");
+        let (view, sampler) = match (self.view.as_ref(), self.sampler.as_ref()) {
+            (Some(view), Some(sampler)) => (view, sampler),
+            _ => return,
+        };
+
+        if let Some(vbuf) = mesh.buffer(PosTex::ATTRIBUTES) {
+            effect.data.vertex_bufs.push(vbuf.clone());
+        } else {
+            effect.clear();
+            return;
+        }
+        effect.data.textures.push(view.clone());
+        effect.data.samplers.push(sampler.clone());
+
+        effect.update_global("samples", i32::from(self.samples));
+        effect.draw(mesh.slice(), encoder);
+        effect.clear();
+    }
+
+    fn new_targets(&mut self, _output: &Target, targets: &Targets) {
+        let source = targets
+            .get(&self.source)
+            .filter(|target| target.samples() > 1);
+        self.samples = source.map_or(0, Target::samples);
+        self.view = source
+            .and_then(|target| target.color_buf(0))
+            .and_then(|buffer| buffer.as_input.as_ref())
+            .map(|view| view.raw().clone());
+        if self.view.is_none() {
+            warn!(
+                "Resolve source target {:?} doesn't exist or isn't multisampled",
+                self.source
+            );
+        }
+    }
+}
@@ -10,27 +10,31 @@ use glsl_layout::Pod;
 use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    ecs::prelude::{Entities, Join, Read, ReadExpect, ReadStorage},
     math::RealField,
     transform::Transform,
 };
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     hidden::{Hidden, HiddenPropagate},
     light::Light,
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
         shaded_util::{set_light_args, setup_light_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        util::{
+            default_transparency, draw_mesh, get_target_camera, set_stencil_reference,
+            setup_textures, setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
     resources::AmbientColor,
+    stencil::{StencilMask, StencilSettings},
     tex::Texture,
     types::{Encoder, Factory},
     vertex::{Normal, Position, Query, TexCoord},
@@ -55,6 +59,7 @@ pub struct DrawShaded<V, N> {
     _marker: PhantomData<(V, N)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    stencil: Option<StencilSettings>,
 }
 
 impl<V, N> DrawShaded<V, N>
@@ -92,6 +97,13 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Sets the stencil state. Each entity is drawn with the reference value of its
+    /// `StencilMask`, or 0 without one.
+    pub fn with_stencil(mut self, stencil: StencilSettings) -> Self {
+        self.stencil = Some(stencil);
+        self
+    }
 }
 
 impl<'a, V, N> PassData<'a> for DrawShaded<V, N>
@@ -100,8 +112,10 @@ where
     N: RealField,
 {
     type Data = (
+        Entities<'a>,
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AmbientColor>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
@@ -114,6 +128,7 @@ where
         ReadStorage<'a, Transform<N>>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, StencilMask>,
     );
 }
 
@@ -132,6 +147,9 @@ where
             Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
             None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
         };
+        if let Some(stencil) = self.stencil {
+            builder.with_stencil(stencil);
+        }
         builder.build()
     }
 
@@ -141,8 +159,10 @@ where
         effect: &mut Effect,
         _factory: Factory,
         (
+            entities,
             active,
             camera,
+            target_camera,
             ambient,
             mesh_storage,
             tex_storage,
@@ -155,24 +175,29 @@ where
             global,
             light,
             rgba,
+            stencil,
         ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &global);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &global);
 
-        set_light_args(effect, encoder, &light, &global, &ambient, camera);
+        set_light_args(
+            effect, encoder, &entities, &light, &global, &ambient, camera, None,
+        );
 
         match visibility {
             None => {
-                for (mesh, material, global, rgba, _, _) in (
+                for (mesh, material, global, rgba, mask, _, _) in (
                     &mesh,
                     &material,
                     &global,
                     rgba.maybe(),
+                    stencil.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
                     draw_mesh(
                         encoder,
                         effect,
@@ -191,15 +216,17 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, global, rgba, _) in (
+                for (mesh, material, global, rgba, mask, _) in (
                     &mesh,
                     &material,
                     &global,
                     rgba.maybe(),
+                    stencil.maybe(),
                     &visibility.visible_unordered,
                 )
                     .join()
                 {
+                    set_stencil_reference(effect, mask);
                     draw_mesh(
                         encoder,
                         effect,
@@ -219,6 +246,7 @@ where
 
                 for entity in &visibility.visible_ordered {
                     if let Some(mesh) = mesh.get(*entity) {
+                        set_stencil_reference(effect, stencil.get(*entity));
                         draw_mesh(
                             encoder,
                             effect,
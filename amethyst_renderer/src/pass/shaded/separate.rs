@@ -11,14 +11,14 @@ use log::{debug, trace};
 use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    ecs::prelude::{Entities, Join, Read, ReadExpect, ReadStorage},
     math::RealField,
     transform::Transform,
 };
 use amethyst_error::Error;
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     hidden::{Hidden, HiddenPropagate},
     light::Light,
     mesh::{Mesh, MeshHandle},
@@ -26,7 +26,9 @@ use crate::{
     pass::{
         shaded_util::{set_light_args, setup_light_buffers},
         skinning::{create_skinning_effect, setup_skinning_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        util::{
+            default_transparency, draw_mesh, get_target_camera, setup_textures, setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
@@ -111,8 +113,10 @@ where
     N: RealField,
 {
     type Data = (
+        Entities<'a>,
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         Read<'a, AmbientColor>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
@@ -173,8 +177,10 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawShadedSeparate<N> {
         effect: &mut Effect,
         _factory: Factory,
         (
+            entities,
             active,
             camera,
+            target_camera,
             ambient,
             mesh_storage,
             tex_storage,
@@ -191,9 +197,11 @@ impl<N: RealField + SubsetOf<f32> + Pod> Pass for DrawShadedSeparate<N> {
         ): <Self as PassData<'a>>::Data,
     ) {
         trace!("Drawing shaded pass");
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
-        set_light_args(effect, encoder, &light, &transform, &ambient, camera);
+        set_light_args(
+            effect, encoder, &entities, &light, &transform, &ambient, camera, None,
+        );
 
         match visibility {
             None => {
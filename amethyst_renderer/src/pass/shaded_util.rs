@@ -1,20 +1,33 @@
 use std::mem;
 
+use gfx::{
+    format::{ChannelType, SurfaceType},
+    memory::Typed,
+    texture::{FilterMethod, SamplerInfo, WrapMode},
+    Factory as GfxFactory,
+};
 use glsl_layout::*;
+use log::warn;
 
+use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
-    ecs::prelude::{Join, ReadStorage},
+    ecs::prelude::{Entities, Entity, Join, ReadStorage},
     math::{convert, Matrix4, RealField},
     Transform,
 };
+use amethyst_error::Error;
 
 use crate::{
     cam::Camera,
+    cubemap::{Cubemap, CubemapData, CubemapHandle},
+    environment::{brdf_lut, EnvironmentMap},
     light::Light,
-    pipe::{Effect, EffectBuilder},
+    pipe::{Effect, EffectBuilder, Targets},
     resources::AmbientColor,
-    types::Encoder,
+    shadow::LightSpace,
+    tex::{Texture, TextureBuilder},
+    types::{Encoder, Factory, RawShaderResourceView, Sampler},
 };
 
 #[derive(Clone, Copy, Debug, Uniform)]
@@ -36,6 +49,7 @@ pub(crate) struct PointLightPod {
 pub(crate) struct DirectionalLightPod {
     color: vec3,
     direction: vec3,
+    shadow: int,
 }
 
 #[derive(Clone, Copy, Debug, Uniform)]
@@ -47,16 +61,22 @@ pub(crate) struct SpotLightPod {
     intensity: float,
     range: float,
     smoothness: float,
+    shadow: int,
 }
 
+/// Uploads the lights, marking the `shadowed` light as the one casting the shadow map.
 pub(crate) fn set_light_args<N: RealField + SubsetOf<f32>>(
     effect: &mut Effect,
     encoder: &mut Encoder,
+    entities: &Entities<'_>,
     light: &ReadStorage<'_, Light>,
     transform: &ReadStorage<'_, Transform<N>>,
     ambient: &AmbientColor,
     camera: Option<(&Camera, &Transform<N>)>,
+    shadowed: Option<Entity>,
 ) {
+    let shadow = |entity: Entity| i32::from(shadowed == Some(entity));
+
     let point_lights: Vec<_> = (light, transform)
         .join()
         .filter_map(|(light, transform)| {
@@ -81,14 +101,15 @@ pub(crate) fn set_light_args<N: RealField + SubsetOf<f32>>(
         })
         .collect();
 
-    let directional_lights: Vec<_> = light
+    let directional_lights: Vec<_> = (entities, light)
         .join()
-        .filter_map(|light| {
+        .filter_map(|(entity, light)| {
             if let Light::Directional(ref light) = *light {
                 Some(
                     DirectionalLightPod {
                         color: light.color.into(),
                         direction: light.direction.into(),
+                        shadow: shadow(entity),
                     }
                     .std140(),
                 )
@@ -98,9 +119,9 @@ pub(crate) fn set_light_args<N: RealField + SubsetOf<f32>>(
         })
         .collect();
 
-    let spot_lights: Vec<_> = (light, transform)
+    let spot_lights: Vec<_> = (entities, light, transform)
         .join()
-        .filter_map(|(light, transform)| {
+        .filter_map(|(entity, light, transform)| {
             if let Light::Spot(ref light) = *light {
                 let position: [f32; 3] =
                     convert::<Matrix4<N>, Matrix4<f32>>(*transform.global_matrix())
@@ -116,6 +137,7 @@ pub(crate) fn set_light_args<N: RealField + SubsetOf<f32>>(
                         intensity: light.intensity,
                         range: light.range,
                         smoothness: light.smoothness,
+                        shadow: shadow(entity),
                     }
                     .std140(),
                 )
@@ -175,5 +197,177 @@ pub(crate) fn setup_light_buffers(builder: &mut EffectBuilder<'_>) {
             128,
         )
         .with_raw_global("ambient_color")
-        .with_raw_global("camera_position");
+        .with_raw_global("camera_position")
+        .with_raw_global("unlit");
+}
+
+/// The shadow map read by a lit pass, from the depth buffer of the target written by a
+/// `DrawShadowMap` pass.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ShadowMap {
+    target: String,
+    view: Option<RawShaderResourceView>,
+    sampler: Option<Sampler>,
+}
+
+impl ShadowMap {
+    pub(crate) fn new(target: String) -> Self {
+        ShadowMap {
+            target,
+            view: None,
+            sampler: None,
+        }
+    }
+
+    /// Creates the sampler, before the effect is built.
+    pub(crate) fn create_sampler(&mut self, factory: &mut Factory) {
+        self.sampler =
+            Some(factory.create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp)));
+    }
+
+    /// Adds the shadow map to the effect, before the textures of the materials.
+    pub(crate) fn setup(builder: &mut EffectBuilder<'_>) {
+        builder
+            .with_texture("shadow_map")
+            .with_raw_global("light_space")
+            .with_raw_global("shadow_bias");
+    }
+
+    pub(crate) fn new_targets(&mut self, targets: &Targets) {
+        self.view = targets
+            .get(&self.target)
+            .and_then(|target| target.depth_buf())
+            .and_then(|buffer| buffer.as_input.as_ref())
+            .map(|view| view.raw().clone());
+        if self.view.is_none() {
+            warn!(
+                "Shadow map target {:?} doesn't exist or has no depth buffer",
+                self.target
+            );
+        }
+    }
+
+    /// Returns the light casting shadows, if the shadow map can be read.
+    pub(crate) fn shadowed(&self, light_space: &LightSpace) -> Option<Entity> {
+        self.view.as_ref().and(light_space.light)
+    }
+
+    pub(crate) fn set_args(&self, effect: &mut Effect, light_space: &LightSpace) {
+        let matrix: [[f32; 4]; 4] = light_space.matrix.into();
+        effect.update_global("light_space", matrix);
+        effect.update_global("shadow_bias", light_space.bias);
+    }
+
+    /// Binds the shadow map for the next draw, or the `fallback` texture if it can't be read, in
+    /// which case no light is marked as casting shadows.
+    pub(crate) fn bind(&self, effect: &mut Effect, fallback: Option<&Texture>) {
+        let binding = match (self.view.as_ref(), self.sampler.as_ref()) {
+            (Some(view), Some(sampler)) => Some((view, sampler)),
+            _ => fallback.map(|texture| (texture.view(), texture.sampler())),
+        };
+        if let Some((view, sampler)) = binding {
+            effect.data.textures.push(view.clone());
+            effect.data.samplers.push(sampler.clone());
+        }
+    }
+}
+
+/// The image based lighting of a PBR pass drawing `with_environment`, from the cubemaps of the
+/// `EnvironmentMap`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EnvironmentLighting {
+    brdf_lut: Option<Texture>,
+    /// A black cubemap bound while the `EnvironmentMap` isn't loaded.
+    fallback: Option<Cubemap>,
+    maps: Option<(Cubemap, Cubemap)>,
+}
+
+impl EnvironmentLighting {
+    pub(crate) fn new() -> Self {
+        EnvironmentLighting {
+            brdf_lut: None,
+            fallback: None,
+            maps: None,
+        }
+    }
+
+    /// Creates the textures which don't depend on the environment, before the effect is built.
+    pub(crate) fn create_textures(&mut self, factory: &mut Factory) -> Result<(), Error> {
+        const LUT_SIZE: u16 = 32;
+
+        let lut = brdf_lut(usize::from(LUT_SIZE));
+        self.brdf_lut = Some(
+            TextureBuilder::new(lut)
+                .with_size(LUT_SIZE, LUT_SIZE)
+                .with_format(SurfaceType::R32_G32)
+                .with_channel_type(ChannelType::Float)
+                .with_sampler(SamplerInfo::new(FilterMethod::Bilinear, WrapMode::Clamp))
+                .dynamic(false)
+                .build(factory)?,
+        );
+        self.fallback = Some(CubemapData::solid([0.0; 4]).build(factory)?);
+        Ok(())
+    }
+
+    /// Adds the environment to the effect, after the shadow map and before the textures of the
+    /// materials.
+    pub(crate) fn setup(builder: &mut EffectBuilder<'_>) {
+        builder
+            .with_texture("environment_specular")
+            .with_texture("environment_irradiance")
+            .with_texture("brdf_lut")
+            .with_raw_global("environment_intensity")
+            .with_raw_global("environment_max_lod");
+    }
+
+    /// Picks up the cubemaps of the `EnvironmentMap`, turning the environment off until both
+    /// are loaded.
+    pub(crate) fn set_args(
+        &mut self,
+        effect: &mut Effect,
+        environment: Option<&EnvironmentMap>,
+        cubemap_storage: &AssetStorage<Cubemap>,
+    ) {
+        let cubemap = |handle: &Option<CubemapHandle>| {
+            handle
+                .as_ref()
+                .and_then(|handle| cubemap_storage.get(handle))
+                .cloned()
+        };
+        self.maps = environment.and_then(|environment| {
+            match (
+                cubemap(&environment.specular),
+                cubemap(&environment.irradiance),
+            ) {
+                (Some(specular), Some(irradiance)) => Some((specular, irradiance)),
+                _ => None,
+            }
+        });
+        let (intensity, max_lod) = match (environment, self.maps.as_ref()) {
+            (Some(environment), Some((specular, _))) => (
+                environment.intensity,
+                f32::from(specular.levels().max(1) - 1),
+            ),
+            _ => (0.0, 0.0),
+        };
+        effect.update_global("environment_intensity", intensity);
+        effect.update_global("environment_max_lod", max_lod);
+    }
+
+    /// Binds the environment for the next draw.
+    pub(crate) fn bind(&self, effect: &mut Effect) {
+        let (specular, irradiance) = match (self.maps.as_ref(), self.fallback.as_ref()) {
+            (Some((specular, irradiance)), _) => (specular, irradiance),
+            (None, Some(fallback)) => (fallback, fallback),
+            (None, None) => return,
+        };
+        for cubemap in &[specular, irradiance] {
+            effect.data.textures.push(cubemap.view().clone());
+            effect.data.samplers.push(cubemap.sampler().clone());
+        }
+        if let Some(ref lut) = self.brdf_lut {
+            effect.data.textures.push(lut.view().clone());
+            effect.data.samplers.push(lut.sampler().clone());
+        }
+    }
 }
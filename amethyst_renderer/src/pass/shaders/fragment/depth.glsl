@@ -0,0 +1,33 @@
+// Only the depth is written, the color output is masked. Fragments below the alpha cutoff of
+// the material are discarded, like in `pbm.glsl`.
+
+#version 150 core
+
+uniform float alpha_cutoff;
+uniform sampler2D albedo;
+
+layout (std140) uniform AlbedoOffset {
+    vec2 u_offset;
+    vec2 v_offset;
+} albedo_offset;
+
+in VertexData {
+    vec2 tex_coord;
+    vec4 color;
+} vertex;
+
+out vec4 out_color;
+
+float tex_coord(float coord, vec2 offset) {
+    return offset.x + coord * (offset.y - offset.x);
+}
+
+vec2 tex_coords(vec2 coord, vec2 u, vec2 v) {
+    return vec2(tex_coord(coord.x, u), tex_coord(coord.y, v));
+}
+
+void main() {
+    float alpha = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset)).a * vertex.color.a;
+    if (alpha < alpha_cutoff) discard;
+    out_color = vec4(0.0);
+}
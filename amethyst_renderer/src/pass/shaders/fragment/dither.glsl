@@ -0,0 +1,39 @@
+// Adds a small per pixel offset to the colors of a target, to hide banding when they are
+// quantized to 8 bits per channel.
+
+#version 150 core
+
+uniform sampler2D source;
+uniform float strength;
+uniform int pattern;
+
+in VertexData {
+    vec3 position;
+    vec2 tex_coord;
+} vertex;
+
+out vec4 out_color;
+
+// The threshold of an 8x8 Bayer matrix, built by interleaving the bits of the coordinates.
+float bayer(ivec2 pixel) {
+    int x = pixel.x & 7;
+    int y = pixel.y & 7;
+    int xy = x ^ y;
+    int index = ((xy & 1) << 5) | ((y & 1) << 4)
+        | ((xy & 2) << 2) | ((y & 2) << 1)
+        | ((xy & 4) >> 1) | ((y & 4) >> 2);
+    return (float(index) + 0.5) / 64.0;
+}
+
+// Jorge Jimenez' interleaved gradient noise, which is close to blue noise without a texture.
+float gradient_noise(vec2 pixel) {
+    return fract(52.9829189 * fract(dot(pixel, vec2(0.06711056, 0.00583715))));
+}
+
+void main() {
+    vec4 color = texture(source, vertex.tex_coord);
+    float noise = pattern == 0
+        ? bayer(ivec2(gl_FragCoord.xy))
+        : gradient_noise(floor(gl_FragCoord.xy));
+    out_color = vec4(color.rgb + (noise - 0.5) * strength / 255.0, color.a);
+}
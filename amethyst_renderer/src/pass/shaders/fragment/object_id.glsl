@@ -0,0 +1,11 @@
+// Writes the encoded entity id, see `DrawObjectId`.
+
+#version 150 core
+
+uniform vec4 object_id;
+
+out vec4 out_color;
+
+void main() {
+    out_color = object_id;
+}
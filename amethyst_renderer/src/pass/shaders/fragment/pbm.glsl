@@ -1,6 +1,8 @@
 // TODO: Needs documentation.
 
 #version 150 core
+// For the sample mask of the alpha to coverage mode, which falls back to the cutoff without it.
+#extension GL_ARB_sample_shading : enable
 
 layout (std140) uniform FragmentArgs {
     int point_light_count;
@@ -22,6 +24,7 @@ layout (std140) uniform PointLights {
 struct DirectionalLight {
     vec3 color;
     vec3 direction;
+    int shadow;
 };
 
 layout (std140) uniform DirectionalLights {
@@ -36,6 +39,7 @@ struct SpotLight {
     float intensity;
     float range;
     float smoothness;
+    int shadow;
 };
 
 layout (std140) uniform SpotLights {
@@ -46,6 +50,22 @@ uniform vec3 ambient_color;
 uniform vec3 camera_position;
 
 uniform float alpha_cutoff;
+// 0: cutoff, 1: alpha to coverage, 2: opaque part of the dual pass mode, 3: fringe of the dual
+// pass mode. See `AlphaMask`.
+uniform int alpha_mask;
+uniform int unlit;
+
+// The depth seen from the light marked with `shadow`, see `DrawShadowMap`.
+uniform sampler2D shadow_map;
+uniform mat4 light_space;
+uniform float shadow_bias;
+
+uniform samplerCube environment_specular;
+uniform samplerCube environment_irradiance;
+uniform sampler2D brdf_lut;
+// Zero while the pass draws without an environment.
+uniform float environment_intensity;
+uniform float environment_max_lod;
 
 uniform sampler2D albedo;
 uniform sampler2D emission;
@@ -135,6 +155,32 @@ vec3 fresnel(float HdotV, vec3 fresnel_base) {
     return fresnel_base + (1.0 - fresnel_base) * pow(1.0 - HdotV, 5.0);
 }
 
+// The fresnel of the ambient light, from all directions around the normal at once.
+vec3 fresnel_roughness(float NdotV, vec3 fresnel_base, float roughness) {
+    return fresnel_base + (max(vec3(1.0 - roughness), fresnel_base) - fresnel_base) * pow(1.0 - NdotV, 5.0);
+}
+
+// The share of the light reaching the position, comparing its depth seen from the light with the
+// shadow map at the 3x3 nearest texels, to soften the edges of the shadow.
+float shadow_factor(vec3 position, vec3 normal, vec3 light_direction) {
+    vec4 light_position = light_space * vec4(position, 1.0);
+    vec3 coords = light_position.xyz / light_position.w * 0.5 + 0.5;
+    if (coords.z > 1.0 || any(lessThan(coords.xy, vec2(0.0))) || any(greaterThan(coords.xy, vec2(1.0)))) {
+        return 1.0;
+    }
+    // Surfaces at a grazing angle to the light need more bias.
+    float bias = shadow_bias * (2.0 - max(dot(normal, light_direction), 0.0));
+    vec2 texel = 1.0 / vec2(textureSize(shadow_map, 0));
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            float depth = texture(shadow_map, coords.xy + vec2(x, y) * texel).r;
+            lit += coords.z - bias > depth ? 0.0 : 1.0;
+        }
+    }
+    return lit / 9.0;
+}
+
 vec3 compute_light(vec3 attenuation,
                    vec3 light_color,
                    vec3 view_direction,
@@ -166,10 +212,39 @@ vec3 compute_light(vec3 attenuation,
 }
 
 void main() {
-    vec4 albedo_alpha       = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset)).rgba;
+    // The vertex color holds the `Rgba` tint of the entity, and the vertex colors of the mesh.
+    vec4 albedo_alpha       = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset)).rgba * vertex.color;
 
     float alpha             = albedo_alpha.a;
-    if(alpha < alpha_cutoff) discard;
+    // Materials without a cutoff aren't masked.
+    if (alpha_cutoff > 0.0) {
+        if (alpha_mask == 1) {
+            // Sharpen the alpha to about a pixel around the cutoff, and cover that many samples.
+            float coverage = clamp((alpha - alpha_cutoff) / max(fwidth(alpha), 0.0001) + 0.5, 0.0, 1.0);
+#ifdef GL_ARB_sample_shading
+            int samples = int(round(coverage * gl_NumSamples));
+            if (samples == 0) discard;
+            gl_SampleMask[0] = (1 << samples) - 1;
+#else
+            if (coverage < 0.5) discard;
+#endif
+            alpha = 1.0;
+        } else if (alpha_mask == 3) {
+            if (alpha >= alpha_cutoff) discard;
+        } else {
+            if (alpha < alpha_cutoff) discard;
+            if (alpha_mask == 2) alpha = 1.0;
+        }
+    } else if (alpha_mask == 3) {
+        // Unmasked materials are drawn whole by the opaque pass.
+        discard;
+    }
+    albedo_alpha.a = alpha;
+
+    if (unlit != 0) {
+        out_color = albedo_alpha;
+        return;
+    }
 
     vec3 albedo             = albedo_alpha.rgb;
     vec3 emission           = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset)).rgb;
@@ -214,6 +289,9 @@ void main() {
     for (int i = 0; i < directional_light_count; i++) {
         vec3 light_direction = -normalize(dlight[i].direction);
         float attenuation = 1.0;
+        if (dlight[i].shadow != 0) {
+            attenuation *= shadow_factor(vertex.position, vertex_normal, light_direction);
+        }
 
         vec3 light = compute_light(vec3(attenuation),
                                    dlight[i].color,
@@ -267,6 +345,9 @@ void main() {
 
         // combine the attenuations and intensity
         float attenuation = range_attenuation * ring_attenuation * slight[i].intensity;
+        if (slight[i].shadow != 0) {
+            attenuation *= shadow_factor(vertex.position, vertex_normal, normalized_light_vec);
+        }
 
         vec3 light = compute_light(vec3(attenuation),
                                    slight[i].color,
@@ -281,7 +362,18 @@ void main() {
     }
 
     vec3 ambient = ambient_color * albedo * ambient_occlusion;
+    if (environment_intensity > 0.0) {
+        float NdotV = max(dot(normal, view_direction), 0.0);
+        vec3 fresnel = fresnel_roughness(NdotV, fresnel_base, roughness);
+        vec3 diffuse = (vec3(1.0) - fresnel) * (1.0 - metallic);
+        vec3 irradiance = texture(environment_irradiance, normal).rgb;
+        vec3 reflected = reflect(-view_direction, normal);
+        vec3 prefiltered = textureLod(environment_specular, reflected, roughness * environment_max_lod).rgb;
+        vec2 brdf = texture(brdf_lut, vec2(NdotV, roughness)).rg;
+        vec3 specular = prefiltered * (fresnel * brdf.x + brdf.y);
+        ambient += (diffuse * irradiance * albedo + specular) * ambient_occlusion * environment_intensity;
+    }
     vec3 color = ambient + lighted + emission;
 
-    out_color = vec4(color, alpha) * vertex.color;
+    out_color = vec4(color, alpha);
 }
@@ -0,0 +1,17 @@
+// Averages the samples of each pixel of a multisampled target of the same size.
+
+#version 150 core
+
+uniform sampler2DMS source;
+uniform int samples;
+
+out vec4 out_color;
+
+void main() {
+    ivec2 pixel = ivec2(gl_FragCoord.xy);
+    vec4 color = vec4(0.0);
+    for (int i = 0; i < samples; i++) {
+        color += texelFetch(source, pixel, i);
+    }
+    out_color = color / float(samples);
+}
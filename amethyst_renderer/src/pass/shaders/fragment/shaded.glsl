@@ -29,6 +29,7 @@ layout (std140) uniform DirectionalLights {
 
 uniform vec3 ambient_color;
 uniform vec3 camera_position;
+uniform int unlit;
 
 uniform sampler2D albedo;
 uniform sampler2D emission;
@@ -63,6 +64,10 @@ vec2 tex_coords(vec2 coord, vec2 u, vec2 v) {
 
 void main() {
     vec4 color = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset));
+    if (unlit != 0) {
+        out_color = color * vertex.color;
+        return;
+    }
     vec4 ecolor = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset));
     vec3 lighting = vec3(0.0);
     vec3 normal = normalize(vertex.normal);
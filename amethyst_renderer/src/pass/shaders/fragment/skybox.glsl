@@ -12,8 +12,16 @@ out vec4 out_color;
 uniform vec3 zenith_color;
 uniform vec3 nadir_color;
 
+uniform samplerCube skybox;
+// Zero to draw the gradient instead of the cubemap.
+uniform float skybox_intensity;
+
 void main() {
     vec3 normalized_position = normalize(vertex.position.xyz);
+    if (skybox_intensity > 0.0) {
+        out_color = vec4(texture(skybox, normalized_position).rgb * skybox_intensity, 1.0);
+        return;
+    }
     vec3 horizon_color = mix(nadir_color, zenith_color, smoothstep(-1., 1., normalized_position.y));
     out_color = vec4(horizon_color, 1.0f);
 }
@@ -0,0 +1,41 @@
+// TODO: Needs documentation.
+
+#version 150 core
+
+layout (std140) uniform VertexArgs {
+    uniform mat4 proj;
+    uniform mat4 view;
+    uniform mat4 model;
+    uniform vec4 color;
+};
+
+in vec3 position;
+in vec3 normal;
+in vec3 tangent;
+in vec2 tex_coord;
+
+// The columns of the model matrix and the color of the instance.
+in vec4 model_0;
+in vec4 model_1;
+in vec4 model_2;
+in vec4 model_3;
+in vec4 instance_color;
+
+out VertexData {
+    vec3 position;
+    vec3 normal;
+    vec3 tangent;
+    vec2 tex_coord;
+    vec4 color;
+} vertex;
+
+void main() {
+    mat4 instance_model = mat4(model_0, model_1, model_2, model_3);
+    vec4 vertex_position = instance_model * vec4(position, 1.0);
+    vertex.position = vertex_position.xyz;
+    vertex.normal = mat3(instance_model) * normal;
+    vertex.tangent = mat3(instance_model) * tangent;
+    vertex.tex_coord = tex_coord;
+    vertex.color = instance_color;
+    gl_Position = proj * view * vertex_position;
+}
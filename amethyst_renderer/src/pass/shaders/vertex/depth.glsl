@@ -0,0 +1,25 @@
+// Transforms the positions the same way as `basic.glsl`, so the depth matches exactly.
+
+#version 150 core
+
+layout (std140) uniform VertexArgs {
+    uniform mat4 proj;
+    uniform mat4 view;
+    uniform mat4 model;
+    uniform vec4 color;
+};
+
+in vec3 position;
+in vec2 tex_coord;
+
+out VertexData {
+    vec2 tex_coord;
+    vec4 color;
+} vertex;
+
+void main() {
+    vec4 vertex_position = model * vec4(position, 1.0);
+    vertex.tex_coord = tex_coord;
+    vertex.color = color;
+    gl_Position = proj * view * vertex_position;
+}
@@ -0,0 +1,17 @@
+// Passes the vertices of a quad in normalized device coordinates through unchanged.
+
+#version 150 core
+
+in vec3 position;
+in vec2 tex_coord;
+
+out VertexData {
+    vec3 position;
+    vec2 tex_coord;
+} vertex;
+
+void main() {
+    vertex.position = position;
+    vertex.tex_coord = tex_coord;
+    gl_Position = vec4(position.xy, 0.0, 1.0);
+}
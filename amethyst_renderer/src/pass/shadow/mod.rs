@@ -0,0 +1,150 @@
+//! Shadow map pass.
+
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+use gfx::pso::buffer::ElemStride;
+use gfx_core::state::ColorMask;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{Join, Read, ReadStorage},
+    math::RealField,
+    transform::Transform,
+};
+use amethyst_error::Error;
+
+use crate::{
+    hidden::{Hidden, HiddenPropagate},
+    mesh::{Mesh, MeshHandle},
+    pass::util::{set_attribute_buffers, set_light_vertex_args, setup_vertex_args},
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    shadow::LightSpace,
+    types::{Encoder, Factory},
+    vertex::{Position, Query},
+    Transparent, REPLACE,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/depth.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/depth.glsl");
+
+/// Draw the depth of opaque meshes from the view of the light in the `LightSpace`, for the lit
+/// passes enabled with `with_shadows` to sample.
+///
+/// Add it to a stage of its own drawing into a target with a depth buffer, which the lit passes
+/// then read by its name. The size of the target is the resolution of the shadows. Shadows also
+/// need the `ShadowLight` component on a light, and the `RenderBundle::with_shadows` system.
+///
+/// ```rust,ignore
+/// let pipe = Pipeline::build()
+///     .with_target(
+///         Target::named("shadow_map")
+///             .with_depth_buf(true)
+///             .with_size((2048, 2048)),
+///     )
+///     .with_stage(
+///         Stage::with_target("shadow_map")
+///             .clear_target([1.0; 4], 1.0)
+///             .with_pass(DrawShadowMap::<PosNormTangTex>::new()),
+///     )
+///     .with_stage(
+///         Stage::with_backbuffer()
+///             .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+///             .with_pass(DrawPbm::<PosNormTangTex>::new().with_shadows("shadow_map")),
+///     );
+/// ```
+///
+/// All meshes without a `Transparent` component cast shadows, including those outside the view
+/// of the camera. Skinned meshes are drawn in their bind pose.
+///
+/// # Type Parameters
+///
+/// * `V`: `VertexFormat`
+/// * `N`: `RealBound` (f32, f64)
+#[derive(Derivative, Clone, Debug, PartialEq)]
+#[derivative(Default(bound = "V: Query<(Position,)>"))]
+pub struct DrawShadowMap<V, N> {
+    _marker: PhantomData<(V, N)>,
+}
+
+impl<V, N> DrawShadowMap<V, N>
+where
+    V: Query<(Position,)>,
+{
+    /// Create instance of `DrawShadowMap` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, V, N> PassData<'a> for DrawShadowMap<V, N>
+where
+    V: Query<(Position,)>,
+    N: RealField,
+{
+    type Data = (
+        Read<'a, LightSpace>,
+        Read<'a, AssetStorage<Mesh>>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, Transparent>,
+        ReadStorage<'a, MeshHandle>,
+        ReadStorage<'a, Transform<N>>,
+    );
+}
+
+impl<V, N> Pass for DrawShadowMap<V, N>
+where
+    V: Query<(Position,)>,
+    N: RealField + SubsetOf<f32>,
+{
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
+        setup_vertex_args(&mut builder);
+        builder.with_blended_output(
+            "color",
+            ColorMask::empty(),
+            REPLACE,
+            Some(DepthMode::LessEqualWrite),
+        );
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        (light_space, mesh_storage, hidden, hidden_prop, transparent, mesh, transform): <Self as PassData<'a>>::Data,
+    ) {
+        if light_space.light.is_none() {
+            return;
+        }
+
+        for (mesh, transform, _, _, _) in
+            (&mesh, &transform, !&hidden, !&hidden_prop, !&transparent).join()
+        {
+            let mesh = match mesh_storage.get(mesh) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            if !set_attribute_buffers(effect, mesh, &[V::QUERIED_ATTRIBUTES]) {
+                effect.clear();
+                continue;
+            }
+            set_light_vertex_args(
+                effect,
+                encoder,
+                &light_space.matrix,
+                transform.global_matrix(),
+            );
+            effect.draw(mesh.slice(), encoder);
+            effect.clear();
+        }
+    }
+}
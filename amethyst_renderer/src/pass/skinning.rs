@@ -13,6 +13,7 @@ use crate::{
 };
 
 static VERT_SKIN_SRC: &[u8] = include_bytes!("shaders/vertex/skinned.glsl");
+static VERT_SKIN_COLOR_SRC: &[u8] = include_bytes!("shaders/vertex/skinned_color.glsl");
 static ATTRIBUTES: [Attributes<'static>; 2] = [
     Separate::<JointIds>::ATTRIBUTES,
     Separate::<JointWeights>::ATTRIBUTES,
@@ -25,6 +26,15 @@ pub(crate) fn create_skinning_effect<'a>(
     effect.simple(VERT_SKIN_SRC, frag)
 }
 
+/// Like `create_skinning_effect`, with a `Color` vertex attribute multiplied into the color of
+/// the vertex arguments.
+pub(crate) fn create_colored_skinning_effect<'a>(
+    effect: NewEffect<'a>,
+    frag: &'a [u8],
+) -> EffectBuilder<'a> {
+    effect.simple(VERT_SKIN_COLOR_SRC, frag)
+}
+
 pub(crate) fn setup_skinning_buffers<'a>(builder: &mut EffectBuilder<'a>) {
     builder
         .with_raw_vertex_buffer(
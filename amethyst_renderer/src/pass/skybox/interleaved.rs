@@ -2,6 +2,7 @@
 
 use std::marker::PhantomData;
 
+use amethyst_assets::AssetStorage;
 use amethyst_core::{
     alga::general::SubsetOf,
     ecs::{Read, ReadStorage},
@@ -11,19 +12,20 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    get_camera,
+    cubemap::{Cubemap, CubemapData},
+    get_target_camera,
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
     set_vertex_args, ActiveCamera, Camera, Encoder, Factory, Mesh, PosTex, Rgba, Shape,
-    VertexFormat,
+    TargetCamera, VertexFormat,
 };
 
 use gfx::pso::buffer::ElemStride;
 use glsl_layout::{mat4, vec4, Uniform};
 
-use super::{SkyboxColor, FRAG_SRC, VERT_SRC};
+use super::{SkyboxColor, SkyboxCubemap, FRAG_SRC, VERT_SRC};
 
 #[repr(C, align(16))]
 #[derive(Clone, Copy, Debug, Uniform)]
@@ -34,7 +36,8 @@ pub(crate) struct VertexArgs {
     rgba: vec4,
 }
 
-/// Draw a simple gradient skybox
+/// Draw a skybox behind everything else, the cubemap of the `SkyboxCubemap` once it is loaded or
+/// else the gradient of the `SkyboxColor`. Both can be set with a `SkyboxPrefab`.
 ///
 /// # Type Parameters:
 ///
@@ -42,6 +45,8 @@ pub(crate) struct VertexArgs {
 #[derive(Clone, Debug)]
 pub struct DrawSkybox<N> {
     mesh: Option<Mesh>,
+    /// A black cubemap bound while the `SkyboxCubemap` isn't loaded.
+    fallback: Option<Cubemap>,
     _pd: PhantomData<N>,
 }
 
@@ -50,6 +55,7 @@ impl<N> DrawSkybox<N> {
     pub fn new() -> Self {
         DrawSkybox {
             mesh: None,
+            fallback: None,
             _pd: PhantomData,
         }
     }
@@ -62,8 +68,11 @@ where
     type Data = (
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
         ReadStorage<'a, Transform<N>>,
         Read<'a, SkyboxColor>,
+        Read<'a, SkyboxCubemap>,
+        Read<'a, AssetStorage<Cubemap>>,
     );
 }
 
@@ -74,6 +83,7 @@ where
     fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
         let verts = Shape::Cube.generate_vertices::<Vec<PosTex>>(None);
         self.mesh = Some(Mesh::build(verts).build(&mut effect.factory)?);
+        self.fallback = Some(CubemapData::solid([0.0; 4]).build(&mut effect.factory)?);
 
         effect
             .simple(VERT_SRC, FRAG_SRC)
@@ -87,6 +97,8 @@ where
             .with_raw_global("camera_position")
             .with_raw_global("zenith_color")
             .with_raw_global("nadir_color")
+            .with_texture("skybox")
+            .with_raw_global("skybox_intensity")
             .with_output("color", Some(DepthMode::LessEqualWrite))
             .build()
     }
@@ -96,9 +108,17 @@ where
         encoder: &mut Encoder,
         effect: &mut Effect,
         mut _factory: Factory,
-        (active, camera, transform, skybox_color): <Self as PassData<'a>>::Data,
+        (
+            active,
+            camera,
+            target_camera,
+            transform,
+            skybox_color,
+            skybox_cubemap,
+            cubemap_storage,
+        ): <Self as PassData<'a>>::Data,
     ) {
-        let camera = get_camera(active, &camera, &transform);
+        let camera = get_target_camera(effect, &target_camera, active, &camera, &transform);
 
         let mesh = self
             .mesh
@@ -116,6 +136,17 @@ where
 
         effect.update_global("zenith_color", Into::<[f32; 3]>::into(skybox_color.zenith));
         effect.update_global("nadir_color", Into::<[f32; 3]>::into(skybox_color.nadir));
+
+        let cubemap = skybox_cubemap
+            .cubemap
+            .as_ref()
+            .and_then(|handle| cubemap_storage.get(handle));
+        let intensity = cubemap.map_or(0.0, |_| skybox_cubemap.intensity);
+        if let Some(cubemap) = cubemap.or_else(|| self.fallback.as_ref()) {
+            effect.data.textures.push(cubemap.view().clone());
+            effect.data.samplers.push(cubemap.sampler().clone());
+        }
+        effect.update_global("skybox_intensity", intensity);
         effect.draw(mesh.slice(), encoder);
         effect.clear();
     }
@@ -2,7 +2,15 @@ pub use self::interleaved::DrawSkybox;
 
 use serde::{Deserialize, Serialize};
 
-use crate::color::Rgba;
+use amethyst_assets::{AssetStorage, Loader, PrefabData, ProgressCounter};
+use amethyst_core::ecs::prelude::{Entity, Read, ReadExpect, Write};
+use amethyst_error::Error;
+
+use crate::{
+    color::Rgba,
+    cubemap::{Cubemap, CubemapHandle},
+    formats::{CubemapOptions, HdrFormat},
+};
 
 mod interleaved;
 
@@ -26,3 +34,132 @@ impl Default for SkyboxColor {
         }
     }
 }
+
+/// The cubemap drawn by `DrawSkybox` instead of the gradient of the `SkyboxColor`, once it is
+/// loaded.
+///
+/// The cubemap is usually loaded from the same HDR image as the `EnvironmentMap`, with
+/// `CubemapOptions::radiance`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkyboxCubemap {
+    /// The cubemap, `None` to draw the gradient.
+    pub cubemap: Option<CubemapHandle>,
+    /// Scales the colors of the cubemap.
+    pub intensity: f32,
+}
+
+impl Default for SkyboxCubemap {
+    fn default() -> Self {
+        SkyboxCubemap {
+            cubemap: None,
+            intensity: 1.0,
+        }
+    }
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+/// Sets up the skybox drawn by `DrawSkybox`, replacing the `SkyboxColor` and `SkyboxCubemap`
+/// resources when the prefab is instantiated.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum SkyboxPrefab {
+    /// Draw a gradient between the colors.
+    Gradient(SkyboxColor),
+
+    /// Draw the equirectangular HDR image in `file`, loaded as a cubemap with faces of `size`
+    /// texels.
+    Hdr {
+        /// The path of the image.
+        file: String,
+        /// The width and height of the faces of the cubemap.
+        size: u16,
+        /// Scales the colors of the image.
+        #[serde(default = "default_intensity")]
+        intensity: f32,
+    },
+
+    /// Clone handle only
+    #[serde(skip)]
+    Handle(CubemapHandle, f32),
+}
+
+impl<'a> PrefabData<'a> for SkyboxPrefab {
+    type SystemData = (
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Cubemap>>,
+        Write<'a, SkyboxColor>,
+        Write<'a, SkyboxCubemap>,
+    );
+
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        _: Entity,
+        (loader, storage, color, cubemap): &mut Self::SystemData,
+        _: &[Entity],
+        _: &[Entity],
+    ) -> Result<(), Error> {
+        match *self {
+            SkyboxPrefab::Gradient(ref gradient) => {
+                **color = gradient.clone();
+                **cubemap = SkyboxCubemap::default();
+            }
+
+            // the sub assets were not loaded, so the image is loaded without tracking its progress
+            SkyboxPrefab::Hdr {
+                ref file,
+                size,
+                intensity,
+            } => {
+                **cubemap = SkyboxCubemap {
+                    cubemap: Some(loader.load(
+                        file.as_str(),
+                        HdrFormat,
+                        CubemapOptions::radiance(size),
+                        (),
+                        &**storage,
+                    )),
+                    intensity,
+                };
+            }
+
+            SkyboxPrefab::Handle(ref handle, intensity) => {
+                **cubemap = SkyboxCubemap {
+                    cubemap: Some(handle.clone()),
+                    intensity,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn load_sub_assets(
+        &mut self,
+        progress: &mut ProgressCounter,
+        (loader, storage, _, _): &mut Self::SystemData,
+    ) -> Result<bool, Error> {
+        let (handle, intensity) = match *self {
+            SkyboxPrefab::Hdr {
+                ref file,
+                size,
+                intensity,
+            } => (
+                loader.load(
+                    file.as_str(),
+                    HdrFormat,
+                    CubemapOptions::radiance(size),
+                    progress,
+                    &**storage,
+                ),
+                intensity,
+            ),
+
+            _ => return Ok(false),
+        };
+        *self = SkyboxPrefab::Handle(handle, intensity);
+        Ok(true)
+    }
+}
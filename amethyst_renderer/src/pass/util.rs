@@ -16,7 +16,7 @@ use amethyst_core::{
 };
 
 use crate::{
-    cam::{ActiveCamera, Camera},
+    cam::{ActiveCamera, Camera, TargetCamera},
     mesh::Mesh,
     mtl::{Material, MaterialDefaults, TextureOffset},
     pass::set_skinning_buffers,
@@ -24,9 +24,10 @@ use crate::{
     resources::ScreenDimensions,
     screen_space::ScreenSpaceSettings,
     skinning::JointTransforms,
+    stencil::StencilMask,
     tex::Texture,
-    types::Encoder,
-    vertex::Attributes,
+    types::{Encoder, Factory, RawBuffer},
+    vertex::{Attribute, Attributes, Color, Position, Separate, VertexFormat},
     Rgba,
 };
 
@@ -121,16 +122,45 @@ pub(crate) fn setup_textures(builder: &mut EffectBuilder<'_>, types: &[TextureTy
     setup_texture_offsets(builder, types);
 }
 
-pub(crate) fn add_textures(
+/// Returns `true` if the textures of the material used by a pass are loaded.
+fn textures_loaded(
+    storage: &AssetStorage<Texture>,
+    material: &Material,
+    types: &[TextureType],
+) -> bool {
+    use self::TextureType::*;
+
+    types.iter().all(|ty| {
+        let texture = match *ty {
+            Albedo => &material.albedo,
+            Emission => &material.emission,
+            Normal => &material.normal,
+            Metallic => &material.metallic,
+            Roughness => &material.roughness,
+            AmbientOcclusion => &material.ambient_occlusion,
+            Caveat => &material.caveat,
+        };
+        storage.get(texture).is_some()
+    })
+}
+
+/// Binds the textures of the material, or of the placeholder `default` material while any of
+/// them is still loading, and returns the material which was bound.
+pub(crate) fn add_textures<'a>(
     effect: &mut Effect,
     encoder: &mut Encoder,
     storage: &AssetStorage<Texture>,
-    material: &Material,
-    default: &Material,
+    material: &'a Material,
+    default: &'a Material,
     types: &[TextureType],
-) {
+) -> &'a Material {
     use self::TextureType::*;
 
+    let material = if textures_loaded(storage, material, types) {
+        material
+    } else {
+        default
+    };
     for ty in types {
         let texture = match *ty {
             Albedo => storage
@@ -158,6 +188,7 @@ pub(crate) fn add_textures(
         add_texture(effect, texture.expect("Texture missing in asset storage"));
     }
     set_texture_offsets(effect, encoder, material, types);
+    material
 }
 
 pub(crate) fn setup_texture_offsets(builder: &mut EffectBuilder<'_>, types: &[TextureType]) {
@@ -308,6 +339,26 @@ pub fn set_vertex_args<N: RealField + SubsetOf<f32>>(
     effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
 }
 
+/// Sets the vertex arguments to draw from the view of a light, `view_proj` taking world space
+/// to its clip space.
+pub(crate) fn set_light_vertex_args<N: RealField + SubsetOf<f32>>(
+    effect: &mut Effect,
+    encoder: &mut Encoder,
+    view_proj: &Matrix4<f32>,
+    global_matrix: &Matrix4<N>,
+) {
+    let model: [[f32; 4]; 4] = convert::<Matrix4<N>, Matrix4<f32>>(*global_matrix).into();
+    let proj: [[f32; 4]; 4] = (*view_proj).into();
+    let view: [[f32; 4]; 4] = Matrix4::<f32>::identity().into();
+    let vertex_args = VertexArgs {
+        proj: proj.into(),
+        view: view.into(),
+        model: model.into(),
+        rgba: Rgba::WHITE.into(),
+    };
+    effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
+}
+
 /// Sets the view arguments in the contant buffer.
 pub fn set_view_args<N>(
     effect: &mut Effect,
@@ -396,7 +447,11 @@ pub(crate) fn draw_mesh<N>(
     // Consider changing function signature?
     let (mesh, material, transform) = match (mesh, material, transform) {
         (Some(v1), Some(v2), Some(v3)) => (v1, v2, v3),
-        _ => return,
+        _ => {
+            // Passes may have bound buffers of their own already.
+            effect.clear();
+            return;
+        }
     };
 
     if !set_attribute_buffers(effect, mesh, attributes)
@@ -420,7 +475,7 @@ pub(crate) fn draw_mesh<N>(
         }
     }
 
-    add_textures(
+    let material = add_textures(
         effect,
         encoder,
         &tex_storage,
@@ -428,11 +483,64 @@ pub(crate) fn draw_mesh<N>(
         &material_defaults.0,
         textures,
     );
+    // Only the lit passes have the global.
+    if effect.has_global("unlit") {
+        effect.update_global("unlit", i32::from(material.unlit));
+    }
+    if effect.has_global("alpha_cutoff") {
+        effect.update_global("alpha_cutoff", material.alpha_cutoff);
+    }
 
     effect.draw(mesh.slice(), encoder);
     effect.clear();
 }
 
+/// Sets the stencil reference of the next draw, 0 for entities without a `StencilMask`.
+/// Binds the `Color` vertex buffer of the mesh, or white colors if it has none. The white colors
+/// are kept in `white` with the number of vertices they cover, and grown when a larger mesh is
+/// drawn.
+pub(crate) fn set_vertex_colors(
+    effect: &mut Effect,
+    factory: &mut Factory,
+    mesh: Option<&Mesh>,
+    white: &mut Option<(RawBuffer, usize)>,
+) {
+    let mesh = match mesh {
+        Some(mesh) => mesh,
+        None => return,
+    };
+    if let Some(colors) = mesh.buffer(Separate::<Color>::ATTRIBUTES) {
+        effect.data.vertex_bufs.push(colors.clone());
+        return;
+    }
+
+    let vertices = mesh
+        .buffer(Separate::<Position>::ATTRIBUTES)
+        .map_or(0, |positions| {
+            positions.get_info().size / Position::SIZE as usize
+        });
+    if white.as_ref().map_or(true, |&(_, len)| len < vertices) {
+        let len = vertices.next_power_of_two();
+        let colors = vec![Separate::<Color>::new([1.0; 4]); len];
+        *white = Mesh::build(colors)
+            .build(factory)
+            .map_err(|err| error!("Failed to create white vertex colors: {}", err))
+            .ok()
+            .and_then(|mesh| mesh.buffer(Separate::<Color>::ATTRIBUTES).cloned())
+            .map(|buffer| (buffer, len));
+    }
+    if let Some((ref white, _)) = *white {
+        effect.data.vertex_bufs.push(white.clone());
+    }
+}
+
+pub(crate) fn set_stencil_reference(effect: &mut Effect, mask: Option<&StencilMask>) {
+    let value = mask.map_or(0, |mask| mask.0);
+    if let Some((_, ref mut reference)) = effect.data.out_depth {
+        *reference = (value, value);
+    }
+}
+
 /// Returns the main camera and its `Transform<N>`
 pub fn get_camera<'a, N>(
     active: Read<'a, ActiveCamera>,
@@ -455,6 +563,47 @@ where
         .or_else(|| (camera, transform).join().next())
 }
 
+/// Gets the camera drawing the effect: the camera with a `TargetCamera` naming the target of the
+/// effect if there is one, else the active camera, else the first camera without a
+/// `TargetCamera`.
+pub fn get_target_camera<'a, N>(
+    effect: &Effect,
+    target_camera: &'a ReadStorage<'a, TargetCamera>,
+    active: Read<'a, ActiveCamera>,
+    camera: &'a ReadStorage<'a, Camera>,
+    transform: &'a ReadStorage<'a, Transform<N>>,
+) -> Option<(&'a Camera, &'a Transform<N>)>
+where
+    N: RealField,
+{
+    #[cfg(feature = "profiler")]
+    profile_scope!("render_gettargetcamera");
+
+    if !effect.target().is_empty() {
+        let found = (camera, transform, target_camera)
+            .join()
+            .find(|(_, _, target)| target.target == effect.target())
+            .map(|(camera, transform, _)| (camera, transform));
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    active
+        .entity
+        .and_then(|entity| {
+            let cam = camera.get(entity);
+            let transform = transform.get(entity);
+            cam.into_iter().zip(transform.into_iter()).next()
+        })
+        .or_else(|| {
+            (camera, transform, !target_camera)
+                .join()
+                .next()
+                .map(|(c, t, _)| (c, t))
+        })
+}
+
 pub fn default_transparency() -> Option<(ColorMask, Blend, Option<DepthMode>)> {
     Some((
         ColorMask::all(),
@@ -0,0 +1,122 @@
+//! Pixel exact picking through the object id target drawn by `DrawObjectId`.
+
+use amethyst_core::ecs::prelude::Entity;
+
+/// A picking request, to get its result from `ObjectPicking::take`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PickRequest(u64);
+
+/// The entity drawn at a pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PickResult {
+    /// The requested pixel.
+    pub position: (u32, u32),
+    /// The entity drawn at the pixel, `None` if nothing was drawn there or the entity was
+    /// deleted since.
+    pub entity: Option<Entity>,
+}
+
+/// Reads the entities drawn by a `DrawObjectId` pass back from the GPU.
+///
+/// A request copies one pixel of the object id target after it's drawn, and the result is read
+/// in the next frame so the renderer doesn't wait for the GPU:
+///
+/// ```rust,ignore
+/// // When the mouse is clicked
+/// self.request = Some(picking.request((x, y)));
+///
+/// // In later frames
+/// if let Some(result) = self.request.and_then(|request| picking.take(request)) {
+///     self.selected = result.entity;
+///     self.request = None;
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ObjectPicking {
+    next: u64,
+    pending: Vec<(PickRequest, (u32, u32))>,
+    results: Vec<(PickRequest, PickResult)>,
+}
+
+impl ObjectPicking {
+    /// Requests the entity drawn at a pixel of the object id target, counted from the top left
+    /// corner like `ScreenDimensions`.
+    pub fn request(&mut self, position: (u32, u32)) -> PickRequest {
+        let request = PickRequest(self.next);
+        self.next += 1;
+        self.pending.push((request, position));
+        request
+    }
+
+    /// Returns the result of a request once it's read back, and forgets it.
+    pub fn take(&mut self, request: PickRequest) -> Option<PickResult> {
+        let index = self.results.iter().position(|&(r, _)| r == request)?;
+        Some(self.results.swap_remove(index).1)
+    }
+
+    /// Returns the results which weren't taken yet, and forgets them.
+    pub fn drain_results(&mut self) -> impl Iterator<Item = (PickRequest, PickResult)> + '_ {
+        self.results.drain(..)
+    }
+
+    pub(crate) fn drain_pending(&mut self) -> impl Iterator<Item = (PickRequest, (u32, u32))> + '_ {
+        self.pending.drain(..)
+    }
+
+    pub(crate) fn finish(&mut self, request: PickRequest, result: PickResult) {
+        self.results.push((request, result));
+    }
+}
+
+/// Encodes an entity id as the color written to the object id target, 0 is left for pixels
+/// without an entity.
+pub(crate) fn encode_id(id: u32) -> [f32; 4] {
+    let value = id.wrapping_add(1);
+    let byte = |shift: u32| ((value >> shift) & 0xff) as f32 / 255.0;
+    [byte(0), byte(8), byte(16), byte(24)]
+}
+
+/// Decodes the entity id from the bytes of an object id target pixel.
+pub(crate) fn decode_id(pixel: [u8; 4]) -> Option<u32> {
+    let value = u32::from(pixel[0])
+        | u32::from(pixel[1]) << 8
+        | u32::from(pixel[2]) << 16
+        | u32::from(pixel[3]) << 24;
+    value.checked_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn id_roundtrip() {
+        for &id in &[0, 1, 255, 256, 70_000, 0xfffe_fffe] {
+            let color = encode_id(id);
+            let mut pixel = [0; 4];
+            for (byte, channel) in pixel.iter_mut().zip(color.iter()) {
+                *byte = (channel * 255.0).round() as u8;
+            }
+            assert_eq!(decode_id(pixel), Some(id));
+        }
+        assert_eq!(decode_id([0; 4]), None);
+    }
+
+    #[test]
+    fn results_are_taken_once() {
+        let mut picking = ObjectPicking::default();
+        let first = picking.request((1, 2));
+        let second = picking.request((3, 4));
+        assert_ne!(first, second);
+        assert_eq!(picking.drain_pending().count(), 2);
+
+        let result = PickResult {
+            position: (3, 4),
+            entity: None,
+        };
+        picking.finish(second, result);
+        assert_eq!(picking.take(first), None);
+        assert_eq!(picking.take(second), Some(result));
+        assert_eq!(picking.take(second), None);
+    }
+}
@@ -4,6 +4,8 @@
 
 pub use self::pso::{Data, Init, Meta};
 
+use std::mem;
+
 use amethyst_error::{Error, ResultExt};
 
 use derivative::Derivative;
@@ -26,6 +28,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     error,
     pipe::Target,
+    stencil::StencilSettings,
     types::{Encoder, Factory, PipelineState, Resources, Slice},
     vertex::Attributes,
 };
@@ -75,6 +78,9 @@ pub struct Effect {
     pub data: Data,
     const_bufs: HashMap<String, usize>,
     globals: HashMap<String, usize>,
+    pub(crate) target: String,
+    #[derivative(PartialEq = "ignore")]
+    draw_calls: usize,
 }
 
 impl Effect {
@@ -90,6 +96,16 @@ impl Effect {
         }
     }
 
+    /// Returns the name of the target the effect draws into, which is empty for the window target.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns whether the effect was built with a global of this name.
+    pub(crate) fn has_global<N: AsRef<str>>(&self, name: N) -> bool {
+        self.globals.contains_key(name.as_ref())
+    }
+
     /// FIXME: Update raw buffer without transmute, use `Result` somehow.
     pub fn update_buffer<N, T>(&mut self, name: N, data: &[T], enc: &mut Encoder)
     where
@@ -145,8 +161,14 @@ impl Effect {
     }
 
     pub fn draw(&mut self, slice: &Slice, enc: &mut Encoder) {
+        self.draw_calls += 1;
         enc.draw(&slice, &self.pso, &self.data);
     }
+
+    /// Returns and resets the number of draw calls made with this effect.
+    pub(crate) fn take_draw_calls(&mut self) -> usize {
+        mem::replace(&mut self.draw_calls, 0)
+    }
 }
 
 pub struct NewEffect<'f> {
@@ -188,6 +210,7 @@ pub struct EffectBuilder<'a> {
     prog: ProgramSource<'a>,
     rast: Rasterizer,
     const_bufs: Vec<BufferInfo>,
+    stencil: Option<Stencil>,
 }
 
 impl<'a> EffectBuilder<'a> {
@@ -198,7 +221,7 @@ impl<'a> EffectBuilder<'a> {
         src: ProgramSource<'a>,
     ) -> Self {
         let mut rast = Rasterizer::new_fill().with_cull_back();
-        if multisampling > 0 {
+        if multisampling > 0 || out.samples() > 1 {
             rast.samples = Some(MultiSample);
         }
         EffectBuilder {
@@ -209,6 +232,7 @@ impl<'a> EffectBuilder<'a> {
             rast,
             prog: src,
             const_bufs: Vec::new(),
+            stencil: None,
         }
     }
 
@@ -310,6 +334,14 @@ impl<'a> EffectBuilder<'a> {
         self
     }
 
+    /// Sets the stencil state of the PSO, used with the depth mode of the output.
+    ///
+    /// The reference value is set per draw through `Data::out_depth`, see `StencilMask`.
+    pub fn with_stencil(&mut self, stencil: StencilSettings) -> &mut Self {
+        self.stencil = Some(stencil.to_gfx());
+        self
+    }
+
     /// Adds a texture sampler to this `Effect`.
     pub fn with_texture(&mut self, name: &'a str) -> &mut Self {
         self.init.samplers.push(name);
@@ -337,6 +369,17 @@ impl<'a> EffectBuilder<'a> {
         let fac = &mut self.factory;
         let prog = self.prog.compile(fac)?;
 
+        if let Some(stencil) = self.stencil {
+            let depth = self.init.out_depth.map_or(
+                Depth {
+                    fun: Comparison::Always,
+                    write: false,
+                },
+                |(depth, _)| depth,
+            );
+            self.init.out_depth = Some((depth, stencil));
+        }
+
         debug!("Creating pipeline state");
         let pso = fac.create_pipeline_state(&prog, self.prim, self.rast, self.init.clone())?;
         let mut data = Data::default();
@@ -394,6 +437,8 @@ impl<'a> EffectBuilder<'a> {
             data,
             const_bufs,
             globals,
+            target: self.out.name().to_string(),
+            draw_calls: 0,
         })
     }
 }
@@ -4,7 +4,7 @@ use amethyst_core::ecs::prelude::SystemData;
 use amethyst_error::Error;
 
 use crate::{
-    pipe::{Effect, NewEffect, Target},
+    pipe::{Effect, NewEffect, Target, Targets},
     types::{Encoder, Factory},
 };
 
@@ -29,6 +29,11 @@ pub trait Pass: for<'a> PassData<'a> {
         factory: Factory,
         data: <Self as PassData<'b>>::Data,
     );
+
+    /// Called with the output target of the pass and all targets of the pipeline after the pass
+    /// is compiled, and again whenever they are recreated, for passes which read targets as
+    /// textures.
+    fn new_targets(&mut self, _output: &Target, _targets: &Targets) {}
 }
 
 /// A compiled pass.  These are created and managed by the `Renderer`.  This should not be
@@ -47,9 +52,11 @@ where
         mut pass: P,
         fac: &mut Factory,
         out: &Target,
+        targets: &Targets,
         multisampling: u16,
     ) -> Result<Self, Error> {
         let effect = pass.compile(NewEffect::new(fac, out, multisampling))?;
+        pass.new_targets(out, targets);
         Ok(CompiledPass {
             effect,
             inner: pass,
@@ -70,8 +77,19 @@ impl<P> CompiledPass<P> {
         self.inner.apply(encoder, &mut self.effect, factory, data)
     }
 
+    /// Returns and resets the number of draw calls the pass made.
+    pub(crate) fn take_draw_calls(&mut self) -> usize {
+        self.effect.take_draw_calls()
+    }
+
     /// Distributes new target data to the pass.
-    pub fn new_target(&mut self, target: &Target) {
+    pub fn new_target(&mut self, target: &Target, targets: &Targets)
+    where
+        P: Pass,
+    {
+        self.inner.new_targets(target, targets);
+        self.effect.target = target.name().to_string();
+
         // Distribute new targets that don't blend.
         self.effect.data.out_colors.clear();
         self.effect
@@ -5,7 +5,7 @@ use fnv::FnvHashMap as HashMap;
 use hetseq::*;
 use log::error;
 
-use amethyst_core::ecs::prelude::SystemData;
+use amethyst_core::ecs::prelude::{Read, SystemData};
 use amethyst_error::Error;
 
 use crate::{
@@ -14,6 +14,7 @@ use crate::{
         pass::{CompiledPass, Pass, PassData},
         Target, Targets,
     },
+    resources::DrawCalls,
     types::{Encoder, Factory},
 };
 
@@ -70,7 +71,10 @@ pub trait Passes: for<'a> PassesData<'a> {
     );
 
     /// Distributes new targets
-    fn new_target(&mut self, new_target: &Target);
+    fn new_target(&mut self, new_target: &Target, targets: &Targets);
+
+    /// Returns and resets the number of draw calls made by the passes.
+    fn take_draw_calls(&mut self) -> usize;
 }
 
 impl<'a, HP> PassesData<'a> for List<(CompiledPass<HP>, List<()>)>
@@ -94,9 +98,14 @@ where
         hp.apply(encoder, factory, hd);
     }
 
-    fn new_target(&mut self, new_target: &Target) {
+    fn new_target(&mut self, new_target: &Target, targets: &Targets) {
         let List((ref mut hp, _)) = *self;
-        hp.new_target(new_target);
+        hp.new_target(new_target, targets);
+    }
+
+    fn take_draw_calls(&mut self) -> usize {
+        let List((ref mut hp, _)) = *self;
+        hp.take_draw_calls()
     }
 }
 
@@ -124,10 +133,15 @@ where
         tp.apply(encoder, factory, td);
     }
 
-    fn new_target(&mut self, new_target: &Target) {
+    fn new_target(&mut self, new_target: &Target, targets: &Targets) {
         let List((ref mut hp, ref mut tp)) = *self;
-        hp.new_target(new_target);
-        tp.new_target(new_target);
+        hp.new_target(new_target, targets);
+        tp.new_target(new_target, targets);
+    }
+
+    fn take_draw_calls(&mut self) -> usize {
+        let List((ref mut hp, ref mut tp)) = *self;
+        hp.take_draw_calls() + tp.take_draw_calls()
     }
 }
 
@@ -154,7 +168,7 @@ impl<'a, L> StageData<'a> for Stage<L>
 where
     L: Passes,
 {
-    type Data = <L as PassesData<'a>>::Data;
+    type Data = (Read<'a, DrawCalls>, <L as PassesData<'a>>::Data);
 }
 
 impl<L> PolyStage for Stage<L>
@@ -165,7 +179,7 @@ where
         &'a mut self,
         encoder: &mut Encoder,
         factory: Factory,
-        data: <L as PassesData<'b>>::Data,
+        (draw_calls, data): (Read<'b, DrawCalls>, <L as PassesData<'b>>::Data),
     ) {
         if let Some(color) = self.clear_color {
             self.target.clear_color(encoder, color);
@@ -176,13 +190,14 @@ where
         }
 
         self.passes.apply(encoder, factory, data);
+        draw_calls.add(self.passes.take_draw_calls());
     }
 
     fn new_targets(&mut self, new_targets: &HashMap<String, Target>) {
         match new_targets.get(&self.target_name) {
             Some(target) => {
                 self.target = target.clone();
-                self.passes.new_target(target);
+                self.passes.new_target(target, new_targets);
             }
             None => {
                 error!("Target name {:?} not found!", self.target_name);
@@ -256,7 +271,7 @@ impl<Q> StageBuilder<Q> {
         let passes = self
             .passes
             .into_list()
-            .fmap(CompilePass::new(fac, &out, multisampling))
+            .fmap(CompilePass::new(fac, &out, targets, multisampling))
             .r#try()?;
 
         Ok(Stage {
@@ -286,14 +301,21 @@ impl<Q> StageBuilder<Queue<Q>> {
 pub struct CompilePass<'a> {
     factory: &'a mut Factory,
     target: &'a Target,
+    targets: &'a Targets,
     multisampling: u16,
 }
 
 impl<'a> CompilePass<'a> {
-    fn new(factory: &'a mut Factory, target: &'a Target, multisampling: u16) -> Self {
+    fn new(
+        factory: &'a mut Factory,
+        target: &'a Target,
+        targets: &'a Targets,
+        multisampling: u16,
+    ) -> Self {
         CompilePass {
             factory,
             target,
+            targets,
             multisampling,
         }
     }
@@ -305,7 +327,13 @@ where
 {
     type Output = Result<CompiledPass<P>, Error>;
     fn call_once(self, (pass,): (P,)) -> Result<CompiledPass<P>, Error> {
-        CompiledPass::compile(pass, self.factory, self.target, self.multisampling)
+        CompiledPass::compile(
+            pass,
+            self.factory,
+            self.target,
+            self.targets,
+            self.multisampling,
+        )
     }
 }
 impl<'a, P> HetFnMut<(P,)> for CompilePass<'a>
@@ -313,6 +341,12 @@ where
     P: Pass,
 {
     fn call_mut(&mut self, (pass,): (P,)) -> Result<CompiledPass<P>, Error> {
-        CompiledPass::compile(pass, self.factory, self.target, self.multisampling)
+        CompiledPass::compile(
+            pass,
+            self.factory,
+            self.target,
+            self.targets,
+            self.multisampling,
+        )
     }
 }
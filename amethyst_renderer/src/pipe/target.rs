@@ -8,7 +8,8 @@ use serde::{Deserialize, Serialize};
 use thread_profiler::profile_scope;
 
 use crate::types::{
-    DepthStencilView, Encoder, Factory, RenderTargetView, ShaderResourceView, Window,
+    ColorFormat, DepthFormat, DepthStencilView, Encoder, Factory, RawTexture, RenderTargetView,
+    ShaderResourceView, Window,
 };
 
 /// Target color buffer.
@@ -38,18 +39,24 @@ pub type Targets = HashMap<String, Target>;
 /// optional depth buffer.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Target {
+    name: String,
     color_bufs: Vec<ColorBuffer>,
+    color_textures: Vec<RawTexture>,
     depth_buf: Option<DepthBuffer>,
     size: (u32, u32),
+    samples: u8,
 }
 
 impl Target {
     /// Creates a new `Target` from a single color buffer and depth buffer pair.
     pub(crate) fn new(cb: ColorBuffer, db: DepthBuffer, size: (u32, u32)) -> Self {
         Target {
+            name: String::new(),
             color_bufs: vec![cb],
+            color_textures: Vec::new(),
             depth_buf: Some(db),
             size,
+            samples: 0,
         }
     }
 
@@ -68,25 +75,44 @@ impl Target {
         }
     }
 
-    /// Clears the depth stencil buffer to the given value.
+    /// Clears the depth buffer to the given value, and the stencil buffer to 0.
     pub fn clear_depth_stencil<V: Into<f32>>(&self, enc: &mut Encoder, value: V) {
         if let Some(ref buf) = self.depth_buf {
-            let val = value.into();
-            enc.clear_depth(&buf.as_output, val);
-            enc.clear_stencil(&buf.as_output, val as u8);
+            enc.clear_depth(&buf.as_output, value.into());
+            enc.clear_stencil(&buf.as_output, 0);
         }
     }
 
+    /// Returns the name of the render target, which is empty for the window target.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Returns the width and height of the render target, measured in pixels.
     pub fn size(&self) -> (u32, u32) {
         self.size
     }
 
+    /// Returns the number of samples per pixel of a target built with
+    /// `TargetBuilder::with_samples`, or 0 if it is single sampled.
+    ///
+    /// The window target returns 0, its multisampling is set by `DisplayConfig::multisampling`.
+    pub fn samples(&self) -> u8 {
+        self.samples
+    }
+
     /// Returns the color buffer with index `i`.
     pub fn color_buf(&self, i: usize) -> Option<&ColorBuffer> {
         self.color_bufs.get(i)
     }
 
+    /// Returns the texture of the color buffer with index `i`, to copy its pixels.
+    ///
+    /// The window target and multisampled targets have no textures.
+    pub fn color_texture(&self, i: usize) -> Option<&RawTexture> {
+        self.color_textures.get(i)
+    }
+
     /// Returns an array slice of the render target's color buffers.
     pub fn color_bufs(&self) -> &[ColorBuffer] {
         self.color_bufs.as_ref()
@@ -135,6 +161,8 @@ pub struct TargetBuilder {
     name: String,
     has_depth_buf: bool,
     num_color_bufs: usize,
+    #[serde(default)]
+    samples: u8,
 }
 
 impl TargetBuilder {
@@ -145,6 +173,7 @@ impl TargetBuilder {
             name: name.into(),
             has_depth_buf: false,
             num_color_bufs: 1,
+            samples: 0,
         }
     }
 
@@ -165,6 +194,30 @@ impl TargetBuilder {
         self
     }
 
+    /// Renders into multisampled color and depth-stencil buffers with this many samples per
+    /// pixel, e.g. 4. 0 and 1 disable multisampling, which is the default.
+    ///
+    /// Multisampled color buffers can only be read by a `DrawResolve` pass, which averages the
+    /// samples of each pixel into the target of its stage. The depth-stencil buffer can't be
+    /// read at all.
+    ///
+    /// ```rust,ignore
+    /// let pipe = Pipeline::build()
+    ///     .with_target(Target::named("msaa").with_depth_buf(true).with_samples(4))
+    ///     .with_target(Target::named("scene"))
+    ///     .with_stage(
+    ///         Stage::with_target("msaa")
+    ///             .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+    ///             .with_pass(DrawShaded::<PosNormTex>::new()),
+    ///     )
+    ///     .with_stage(Stage::with_target("scene").with_pass(DrawResolve::new("msaa")))
+    ///     .with_stage(Stage::with_backbuffer().with_pass(DrawDither::new("scene")));
+    /// ```
+    pub fn with_samples(mut self, samples: u8) -> Self {
+        self.samples = samples;
+        self
+    }
+
     /// Specifies a custom target size.
     pub fn with_size(mut self, size: (u32, u32)) -> Self {
         self.custom_size = Some(size);
@@ -177,17 +230,23 @@ impl TargetBuilder {
         fac: &mut Factory,
         size: (u32, u32),
     ) -> Result<(String, Target), Error> {
-        use gfx::Factory;
+        use gfx::{memory::Typed, Factory};
 
         #[cfg(feature = "profiler")]
         profile_scope!("render_target_build");
 
         let size = self.custom_size.unwrap_or(size);
+        let samples = if self.samples > 1 { self.samples } else { 0 };
 
+        let mut color_textures = Vec::with_capacity(self.num_color_bufs);
         let color_bufs = (0..self.num_color_bufs)
             .map(|_| {
                 let (w, h) = (size.0 as u16, size.1 as u16);
-                let (_, res, rt) = fac.create_render_target(w, h)?;
+                if samples > 1 {
+                    return create_multisampled_color(fac, (w, h), samples);
+                }
+                let (tex, res, rt) = fac.create_render_target(w, h)?;
+                color_textures.push(tex.raw().clone());
                 Ok(ColorBuffer {
                     as_input: Some(res),
                     as_output: rt,
@@ -195,7 +254,10 @@ impl TargetBuilder {
             })
             .collect::<Result<_, Error>>()?;
 
-        let depth_buf = if self.has_depth_buf {
+        let depth_buf = if self.has_depth_buf && samples > 1 {
+            let (w, h) = (size.0 as u16, size.1 as u16);
+            Some(create_multisampled_depth(fac, (w, h), samples)?)
+        } else if self.has_depth_buf {
             let (w, h) = (size.0 as u16, size.1 as u16);
             let (_, res, dt) = fac.create_depth_stencil(w, h)?;
             let depth = DepthBuffer {
@@ -208,11 +270,71 @@ impl TargetBuilder {
         };
 
         let target = Target {
+            name: self.name.clone(),
             color_bufs,
+            color_textures,
             depth_buf,
             size,
+            samples,
         };
 
         Ok((self.name, target))
     }
 }
+
+/// Creates a color buffer with `samples` samples per pixel, see `TargetBuilder::with_samples`.
+fn create_multisampled_color(
+    fac: &mut Factory,
+    (w, h): (u16, u16),
+    samples: u8,
+) -> Result<ColorBuffer, Error> {
+    use gfx::{
+        format::{ChannelTyped, Formatted, Swizzle},
+        memory::{Bind, Usage},
+        texture::{AaMode, Kind},
+        Factory,
+    };
+
+    let channel = <<ColorFormat as Formatted>::Channel as ChannelTyped>::get_channel_type();
+    let tex = fac.create_texture::<<ColorFormat as Formatted>::Surface>(
+        Kind::D2(w, h, AaMode::Multi(samples)),
+        1,
+        Bind::SHADER_RESOURCE | Bind::RENDER_TARGET,
+        Usage::Data,
+        Some(channel),
+    )?;
+    let res = fac.view_texture_as_shader_resource::<ColorFormat>(&tex, (0, 0), Swizzle::new())?;
+    let rt = fac.view_texture_as_render_target(&tex, 0, None)?;
+    Ok(ColorBuffer {
+        as_input: Some(res),
+        as_output: rt,
+    })
+}
+
+/// Creates a depth-stencil buffer with `samples` samples per pixel, which can't be read.
+fn create_multisampled_depth(
+    fac: &mut Factory,
+    (w, h): (u16, u16),
+    samples: u8,
+) -> Result<DepthBuffer, Error> {
+    use gfx::{
+        format::{ChannelTyped, Formatted},
+        memory::{Bind, Usage},
+        texture::{AaMode, Kind},
+        Factory,
+    };
+
+    let channel = <<DepthFormat as Formatted>::Channel as ChannelTyped>::get_channel_type();
+    let tex = fac.create_texture::<<DepthFormat as Formatted>::Surface>(
+        Kind::D2(w, h, AaMode::Multi(samples)),
+        1,
+        Bind::DEPTH_STENCIL,
+        Usage::Data,
+        Some(channel),
+    )?;
+    let dt = fac.view_texture_as_depth_stencil_trivial(&tex)?;
+    Ok(DepthBuffer {
+        as_input: None,
+        as_output: dt,
+    })
+}
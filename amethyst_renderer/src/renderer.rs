@@ -1,6 +1,7 @@
 use amethyst_error::{format_err, Error};
 use fnv::FnvHashMap as HashMap;
 use gfx::memory::Pod;
+use log::warn;
 use winit::{dpi::LogicalSize, EventsLoop, Window as WinitWindow, WindowBuilder};
 
 #[cfg(feature = "profiler")]
@@ -8,7 +9,8 @@ use thread_profiler::profile_scope;
 
 use crate::{
     config::DisplayConfig,
-    error,
+    cubemap::{Cubemap, CubemapData},
+    error::{self, ContextLost},
     mesh::{Mesh, MeshBuilder, VertexDataSet},
     pipe::{
         ColorBuffer, DepthBuffer, PipelineBuild, PipelineData, PolyPipeline, Target, TargetBuilder,
@@ -30,6 +32,8 @@ pub struct Renderer {
     multisampling: u16,
     cached_size: LogicalSize,
     cached_hidpi_factor: f64,
+    config: DisplayConfig,
+    window_builder: WindowBuilder,
 }
 
 impl Renderer {
@@ -65,6 +69,11 @@ impl Renderer {
         tb.build(&mut self.factory)
     }
 
+    /// Builds a new cubemap resource.
+    pub fn create_cubemap(&mut self, data: &CubemapData) -> Result<Cubemap, Error> {
+        data.build(&mut self.factory)
+    }
+
     /// Builds a new renderer pipeline.
     pub fn create_pipe<B, P>(&mut self, pb: B) -> Result<P, Error>
     where
@@ -75,8 +84,19 @@ impl Renderer {
     }
 
     /// Draws a scene with the given pipeline.
+    ///
+    /// Fails if the graphics context was lost, after which the renderer has to be recreated with
+    /// `recreate`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the buffers can't be swapped for another reason.
     #[allow(clippy::float_cmp)] // cmp just used to recognize change
-    pub fn draw<'a, P>(&mut self, pipe: &mut P, data: <P as PipelineData<'a>>::Data)
+    pub fn draw<'a, P>(
+        &mut self,
+        pipe: &mut P,
+        data: <P as PipelineData<'a>>::Data,
+    ) -> Result<(), ContextLost>
     where
         P: PolyPipeline,
     {
@@ -117,10 +137,39 @@ impl Renderer {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_draw_swapbuffers");
             #[cfg(feature = "opengl")]
-            self.window
-                .swap_buffers()
-                .expect("OpenGL context has been lost");
+            {
+                match self.window.swap_buffers() {
+                    Ok(()) => {}
+                    Err(glutin::ContextError::ContextLost) => return Err(ContextLost),
+                    Err(err) => panic!("Failed to swap buffers: {}", err),
+                }
+            }
         }
+        Ok(())
+    }
+
+    /// Replaces the window and the graphics device with new ones using the original
+    /// configuration, e.g. after the context was lost.
+    ///
+    /// All meshes, textures and pipelines created before belong to the old device, so they have
+    /// to be created again.
+    pub fn recreate(&mut self) -> Result<(), Error> {
+        use gfx::Device;
+
+        let Backend(device, mut factory, main_target, window) =
+            init_backend(self.window_builder.clone(), &self.events, &self.config)?;
+        self.cached_size = window
+            .get_inner_size()
+            .ok_or_else(|| format_err!("Unable to fetch window size, as the window went away."))?;
+        self.cached_hidpi_factor = window.get_hidpi_factor();
+
+        self.device.cleanup();
+        self.encoder = factory.create_command_buffer().into();
+        self.device = device;
+        self.factory = factory;
+        self.main_target = main_target;
+        self.window = window;
+        Ok(())
     }
 
     /// Retrieve a mutable borrow of the events loop
@@ -205,8 +254,15 @@ impl RendererBuilder {
 
     /// Consumes the builder and creates the new `Renderer`.
     pub fn build(self) -> Result<Renderer, Error> {
+        let samples = self.config.samples();
+        if self.config.multisampling > 1 && samples != self.config.multisampling {
+            warn!(
+                "Multisampling with {} samples is not supported, using {} samples",
+                self.config.multisampling, samples
+            );
+        }
         let Backend(device, mut factory, main_target, window) =
-            init_backend(self.window_builder, &self.events, &self.config)?;
+            init_backend(self.window_builder.clone(), &self.events, &self.config)?;
 
         let cached_size = window
             .get_inner_size()
@@ -222,9 +278,11 @@ impl RendererBuilder {
             main_target,
             window,
             events: self.events,
-            multisampling: self.config.multisampling,
+            multisampling: self.config.samples(),
             cached_size,
             cached_hidpi_factor,
+            config: self.config,
+            window_builder: self.window_builder,
         })
     }
 }
@@ -305,7 +363,7 @@ fn init_backend(
     use glutin::{GlProfile, GlRequest};
 
     let ctx = glutin::ContextBuilder::new()
-        .with_multisampling(config.multisampling)
+        .with_multisampling(config.samples())
         .with_vsync(config.vsync);
     #[cfg(target_os = "macos")]
     let ctx = ctx
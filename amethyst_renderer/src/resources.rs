@@ -1,8 +1,13 @@
 //! `amethyst` rendering ecs resources
 //!
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
-use winit::Window;
+use winit::{dpi::LogicalSize, Icon, Window};
 
 use amethyst_assets::PrefabData;
 use amethyst_core::ecs::{Entity, Write};
@@ -65,6 +70,124 @@ impl WindowMessages {
     }
 }
 
+/// Statistics of the last rendered frame, updated by the `RenderSystem`.
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    /// The number of draw calls made by all passes.
+    pub draw_calls: usize,
+    /// The time the `RenderSystem` spent drawing the frame, including waiting for the GPU.
+    pub render_time: Duration,
+    /// How often the graphics device was lost and the renderer recreated, see
+    /// `RenderSystem::with_device_recovery`.
+    pub device_losses: u32,
+}
+
+/// The number of draw calls made by the passes, added by each stage after applying its passes.
+///
+/// The `RenderSystem` takes them after drawing a frame for the `RenderStats`.
+#[derive(Debug, Default)]
+pub struct DrawCalls(AtomicUsize);
+
+impl DrawCalls {
+    /// Adds draw calls made by passes.
+    pub fn add(&self, count: usize) {
+        self.0.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Returns and resets the number of draw calls.
+    pub fn take(&self) -> usize {
+        self.0.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// A change of the window requested through the `WindowControl`.
+#[derive(Clone, Debug)]
+pub(crate) enum WindowRequest {
+    Fullscreen(bool),
+    Size(f64, f64),
+    Title(String),
+    Icon(Option<Icon>),
+    Maximized(bool),
+}
+
+/// Resource for changing the window at runtime, e.g. to toggle fullscreen from an options menu.
+///
+/// The requests are applied by the `RenderSystem` at the start of the next frame, in the order
+/// they were made. Changes of the window size, including switching to and from fullscreen,
+/// recreate the render targets and update the `ScreenDimensions` the same way a resize by the user
+/// does.
+#[derive(Clone, Debug, Default)]
+pub struct WindowControl {
+    fullscreen: bool,
+    pub(crate) requests: Vec<WindowRequest>,
+}
+
+impl WindowControl {
+    /// Creates the resource for a window which is initially in fullscreen or not.
+    pub fn new(fullscreen: bool) -> Self {
+        WindowControl {
+            fullscreen,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the window is, or was requested to be, in fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Makes the window borderless fullscreen on its current monitor, or turns it back into a
+    /// normal window.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+        self.requests.push(WindowRequest::Fullscreen(fullscreen));
+    }
+
+    /// Switches between fullscreen and a normal window.
+    pub fn toggle_fullscreen(&mut self) {
+        let fullscreen = !self.fullscreen;
+        self.set_fullscreen(fullscreen);
+    }
+
+    /// Resizes the window, in logical pixels.
+    pub fn set_size(&mut self, width: f64, height: f64) {
+        self.requests.push(WindowRequest::Size(width, height));
+    }
+
+    /// Sets the title of the window.
+    pub fn set_title<S: Into<String>>(&mut self, title: S) {
+        self.requests.push(WindowRequest::Title(title.into()));
+    }
+
+    /// Sets the icon of the window, `None` restores the platform default.
+    pub fn set_icon(&mut self, icon: Option<Icon>) {
+        self.requests.push(WindowRequest::Icon(icon));
+    }
+
+    /// Maximizes the window or restores its previous size.
+    pub fn set_maximized(&mut self, maximized: bool) {
+        self.requests.push(WindowRequest::Maximized(maximized));
+    }
+
+    /// Applies and removes all pending requests.
+    pub(crate) fn apply(&mut self, window: &Window) {
+        for request in self.requests.drain(..) {
+            match request {
+                WindowRequest::Fullscreen(true) => {
+                    window.set_fullscreen(Some(window.get_current_monitor()))
+                }
+                WindowRequest::Fullscreen(false) => window.set_fullscreen(None),
+                WindowRequest::Size(width, height) => {
+                    window.set_inner_size(LogicalSize::new(width, height))
+                }
+                WindowRequest::Title(title) => window.set_title(&title),
+                WindowRequest::Icon(icon) => window.set_window_icon(icon),
+                WindowRequest::Maximized(maximized) => window.set_maximized(maximized),
+            }
+        }
+    }
+}
+
 /// World resource that stores screen dimensions.
 #[derive(Debug)]
 pub struct ScreenDimensions {
@@ -133,3 +256,25 @@ impl ScreenDimensions {
         self.hidpi = factor;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_fullscreen() {
+        let mut control = WindowControl::new(true);
+        control.toggle_fullscreen();
+        assert!(!control.is_fullscreen());
+        control.set_title("Options");
+        control.toggle_fullscreen();
+        assert!(control.is_fullscreen());
+        match control.requests.as_slice() {
+            [WindowRequest::Fullscreen(false), WindowRequest::Title(title), WindowRequest::Fullscreen(true)] =>
+            {
+                assert_eq!("Options", title)
+            }
+            requests => panic!("Unexpected requests: {:?}", requests),
+        }
+    }
+}
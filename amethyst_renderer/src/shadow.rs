@@ -0,0 +1,227 @@
+//! Shadow maps of directional and spot lights.
+
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{PrefabData, ProgressCounter};
+use amethyst_core::{
+    alga::general::SubsetOf,
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, Write,
+        WriteStorage,
+    },
+    math::{convert, Matrix4, Orthographic3, Perspective3, Point3, RealField, Vector3},
+    Transform,
+};
+use amethyst_derive::PrefabData;
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    light::Light,
+};
+
+/// Enables the shadows of the directional or spot `Light` of its entity.
+///
+/// There is one shadow map, drawn by `DrawShadowMap`, so only the first light with shadows
+/// enabled casts them.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, PrefabData)]
+#[prefab(Component)]
+#[serde(default)]
+pub struct ShadowLight {
+    /// How much closer to the light surfaces are taken to be when compared with the shadow map,
+    /// which hides the stripes of surfaces shadowing themselves at the cost of shadows starting
+    /// a little away from their casters. It is scaled up on surfaces facing away from the light.
+    pub bias: f32,
+    /// Half the width of the square area around the active camera which a directional light
+    /// shadows.
+    pub extent: f32,
+    /// The distance from a spot light at which its shadows start.
+    pub near: f32,
+}
+
+impl Default for ShadowLight {
+    fn default() -> Self {
+        ShadowLight {
+            bias: 0.005,
+            extent: 20.0,
+            near: 0.1,
+        }
+    }
+}
+
+impl Component for ShadowLight {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The view of the light drawing the shadow map, updated by the `LightSpaceSystem`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightSpace {
+    /// The light casting shadows, `None` if no directional or spot light has a `ShadowLight`.
+    pub light: Option<Entity>,
+    /// The view projection matrix of the light, from world space to its clip space.
+    pub matrix: Matrix4<f32>,
+    /// The bias of the `ShadowLight`.
+    pub bias: f32,
+}
+
+impl Default for LightSpace {
+    fn default() -> Self {
+        LightSpace {
+            light: None,
+            matrix: Matrix4::identity(),
+            bias: 0.0,
+        }
+    }
+}
+
+/// Finds the light casting shadows and updates the `LightSpace` resource.
+///
+/// Added by the `RenderBundle` with `with_shadows`, as "light_space_system".
+#[derive(Default)]
+pub struct LightSpaceSystem<N> {
+    _marker: PhantomData<N>,
+}
+
+impl<N> LightSpaceSystem<N> {
+    /// Creates a new `LightSpaceSystem`.
+    pub fn new() -> Self {
+        LightSpaceSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, N: RealField + SubsetOf<f32>> System<'a> for LightSpaceSystem<N> {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Light>,
+        ReadStorage<'a, ShadowLight>,
+        ReadStorage<'a, Transform<N>>,
+        Write<'a, LightSpace>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, active, cameras, lights, shadows, transforms, mut light_space): Self::SystemData,
+    ) {
+        let position = |transform: &Transform<N>| {
+            convert::<Matrix4<N>, Matrix4<f32>>(*transform.global_matrix())
+                .column(3)
+                .xyz()
+        };
+        let camera = active
+            .entity
+            .and_then(|entity| transforms.get(entity))
+            .or_else(|| (&cameras, &transforms).join().map(|(_, t)| t).next())
+            .map(position)
+            .unwrap_or_else(Vector3::zeros);
+
+        *light_space = LightSpace::default();
+        for (entity, light, shadow, transform) in
+            (&*entities, &lights, &shadows, transforms.maybe()).join()
+        {
+            let matrix = match (light, transform) {
+                (Light::Directional(light), _) => {
+                    directional_matrix(&Vector3::from(light.direction), &camera, shadow)
+                }
+                (Light::Spot(light), Some(transform)) => spot_matrix(
+                    &position(transform),
+                    &Vector3::from(light.direction),
+                    light.angle,
+                    light.range,
+                    shadow,
+                ),
+                _ => continue,
+            };
+            *light_space = LightSpace {
+                light: Some(entity),
+                matrix,
+                bias: shadow.bias,
+            };
+            break;
+        }
+    }
+}
+
+/// Returns a vector perpendicular to the direction, to look along it with.
+fn up(direction: &Vector3<f32>) -> Vector3<f32> {
+    if direction.y.abs() > 0.99 {
+        Vector3::z()
+    } else {
+        Vector3::y()
+    }
+}
+
+/// An orthographic view of the area around `center`, looking along the light.
+fn directional_matrix(
+    direction: &Vector3<f32>,
+    center: &Vector3<f32>,
+    shadow: &ShadowLight,
+) -> Matrix4<f32> {
+    let direction = direction.try_normalize(1e-6).unwrap_or(-Vector3::y());
+    let extent = shadow.extent;
+    // Casters up to twice the extent above the area are included.
+    let eye = center - direction * extent * 2.0;
+    let view = Matrix4::look_at_rh(&Point3::from(eye), &Point3::from(*center), &up(&direction));
+    let projection =
+        Orthographic3::new(-extent, extent, -extent, extent, 0.0, extent * 4.0).to_homogeneous();
+    projection * view
+}
+
+/// A perspective view of the cone of a spot light.
+fn spot_matrix(
+    position: &Vector3<f32>,
+    direction: &Vector3<f32>,
+    angle: f32,
+    range: f32,
+    shadow: &ShadowLight,
+) -> Matrix4<f32> {
+    let direction = direction.try_normalize(1e-6).unwrap_or(-Vector3::y());
+    let view = Matrix4::look_at_rh(
+        &Point3::from(*position),
+        &Point3::from(position + direction),
+        &up(&direction),
+    );
+    let fov = (angle * 2.0).max(0.01).min(std::f32::consts::PI - 0.01);
+    let projection =
+        Perspective3::new(1.0, fov, shadow.near, range.max(shadow.near + 0.01)).to_homogeneous();
+    projection * view
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(matrix: &Matrix4<f32>, point: Vector3<f32>) -> Vector3<f32> {
+        let clip = matrix * point.push(1.0);
+        clip.xyz() / clip.w
+    }
+
+    #[test]
+    fn light_matrices() {
+        let shadow = ShadowLight::default();
+
+        let matrix = directional_matrix(&-Vector3::y(), &Vector3::new(5.0, 0.0, 0.0), &shadow);
+        let center = project(&matrix, Vector3::new(5.0, 0.0, 0.0));
+        assert!(center.xy().norm() < 1e-5);
+        // The area is halfway through the depth range, higher is closer to the light.
+        assert!(center.z.abs() < 1e-5);
+        assert!(project(&matrix, Vector3::new(5.0, 1.0, 0.0)).z < center.z);
+
+        let matrix = spot_matrix(
+            &Vector3::new(0.0, 5.0, 0.0),
+            &-Vector3::y(),
+            0.5,
+            10.0,
+            &shadow,
+        );
+        let below = project(&matrix, Vector3::zeros());
+        assert!(below.xy().norm() < 1e-5);
+        assert!(below.z > -1.0 && below.z < 1.0);
+        assert!(project(&matrix, Vector3::new(0.0, -10.0, 0.0)).z > 1.0);
+    }
+}
@@ -11,7 +11,7 @@ use amethyst_core::{
 use amethyst_error::Error;
 
 use crate::{
-    formats::MeshCreator,
+    formats::{offset_positions, MeshCreator},
     mesh::{Mesh, MeshBuilder},
     renderer::Renderer,
     vertex::{Attribute, Color, Normal, Position, Separate, Tangent, TexCoord},
@@ -64,18 +64,14 @@ pub type AnimatedVertexBufferCombination = (
 /// Build Mesh with vertex buffer combination
 fn build_mesh_with_combo(
     combo: AnimatedVertexBufferCombination,
+    indices: Option<Vec<u32>>,
     renderer: &mut Renderer,
 ) -> Result<Mesh, Error> {
-    build_mesh_with_some!(
-        MeshBuilder::new(combo.0),
-        renderer,
-        combo.1,
-        combo.2,
-        combo.3,
-        combo.4,
-        combo.5,
-        combo.6
-    )
+    let mut builder = MeshBuilder::new(combo.0);
+    if let Some(indices) = indices {
+        builder = builder.with_indices(indices);
+    }
+    build_mesh_with_some!(builder, renderer, combo.1, combo.2, combo.3, combo.4, combo.5, combo.6)
 }
 
 /// Mesh creator for `VertexBufferCombination`.
@@ -83,24 +79,45 @@ fn build_mesh_with_combo(
 pub struct AnimatedComboMeshCreator {
     /// The internal mesh combo data.
     pub combo: AnimatedVertexBufferCombination,
+    /// The indices of the triangles, if the mesh is indexed.
+    pub indices: Option<Vec<u32>>,
 }
 
 impl AnimatedComboMeshCreator {
     /// Create a new combo mesh creator with the given combo
     pub fn new(combo: AnimatedVertexBufferCombination) -> Self {
-        AnimatedComboMeshCreator { combo }
+        AnimatedComboMeshCreator {
+            combo,
+            indices: None,
+        }
+    }
+
+    /// Draws the triangles given by `indices` into the vertices of the combo.
+    pub fn with_indices(mut self, indices: Vec<u32>) -> Self {
+        self.indices = Some(indices);
+        self
     }
 }
 
 impl MeshCreator for AnimatedComboMeshCreator {
     fn build(self: Box<Self>, renderer: &mut Renderer) -> Result<Mesh, Error> {
-        build_mesh_with_combo(self.combo, renderer)
+        build_mesh_with_combo(self.combo, self.indices, renderer)
     }
 
     fn vertices(&self) -> &Vec<Separate<Position>> {
         &self.combo.0
     }
 
+    fn indices(&self) -> Option<&[u32]> {
+        self.indices.as_ref().map(Vec::as_slice)
+    }
+
+    fn with_position_offsets(&self, offsets: &[[f32; 3]]) -> Option<Box<dyn MeshCreator>> {
+        let mut creator = self.clone();
+        offset_positions(&mut creator.combo.0, offsets);
+        Some(Box::new(creator))
+    }
+
     fn box_clone(&self) -> Box<dyn MeshCreator> {
         Box::new((*self).clone())
     }
@@ -0,0 +1,247 @@
+//! Packing of many small images into one sprite sheet texture.
+
+use image::RgbaImage;
+
+use amethyst_assets::{AssetStorage, Loader, Progress};
+use amethyst_error::Error;
+
+use crate::{
+    error,
+    formats::{ImageData, TextureData, TextureMetadata},
+    sprite::{Sprite, SpriteSheet},
+    tex::Texture,
+};
+
+/// The default maximum width and height of an atlas, which all current GPUs support.
+const DEFAULT_MAX_SIZE: u32 = 4096;
+
+/// Packs small images into a shared texture at load time.
+///
+/// `DrawFlat2D` batches the sprites by texture, so the sprites of all the packed images are drawn
+/// together instead of with a draw call per image:
+///
+/// ```rust,ignore
+/// let mut builder = SpriteAtlasBuilder::new();
+/// let player = builder.add(player_image, [16.0, 16.0]);
+/// let coin = builder.add(coin_image, [8.0, 8.0]);
+/// let sheet = builder
+///     .build(TextureMetadata::srgb_scale())?
+///     .load(&loader, &texture_storage, ());
+/// let sheet = loader.load_from_data(sheet, (), &sprite_sheet_storage);
+///
+/// // Draw the player with `SpriteRender { sprite_sheet: sheet.clone(), sprite_number: player }`
+/// ```
+#[derive(Clone, Debug)]
+pub struct SpriteAtlasBuilder {
+    images: Vec<(RgbaImage, [f32; 2])>,
+    padding: u32,
+    max_size: u32,
+}
+
+impl Default for SpriteAtlasBuilder {
+    fn default() -> Self {
+        SpriteAtlasBuilder {
+            images: Vec::new(),
+            padding: 1,
+            max_size: DEFAULT_MAX_SIZE,
+        }
+    }
+}
+
+impl SpriteAtlasBuilder {
+    /// Create an empty `SpriteAtlasBuilder`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the transparent pixels left between the images, 1 by default. Increase it if the
+    /// sprites are drawn with linear filtering or mipmaps, so they don't bleed into each other.
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the maximum width and height of the atlas, 4096 by default.
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Adds an image, and returns the index of its sprite in the sprite sheet.
+    ///
+    /// The `offsets` are the number of pixels to shift the sprite to the left and down relative to
+    /// the entity, like `Sprite::offsets`.
+    pub fn add(&mut self, image: RgbaImage, offsets: [f32; 2]) -> usize {
+        self.images.push((image, offsets));
+        self.images.len() - 1
+    }
+
+    /// Packs the images into the smallest power of two texture they fit in.
+    ///
+    /// Fails if they don't fit into the maximum size.
+    pub fn build(self, metadata: TextureMetadata) -> Result<SpriteAtlas, Error> {
+        let sizes = self
+            .images
+            .iter()
+            .map(|(image, _)| (image.width(), image.height()))
+            .collect::<Vec<_>>();
+        let (width, height, positions) = pack(&sizes, self.padding, self.max_size)
+            .ok_or_else(|| error::Error::AtlasTooLarge(self.max_size))?;
+
+        let mut atlas = RgbaImage::new(width, height);
+        let mut sprites = Vec::with_capacity(self.images.len());
+        for ((image, offsets), &(left, top)) in self.images.iter().zip(&positions) {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                atlas.put_pixel(left + x, top + y, *pixel);
+            }
+            sprites.push(Sprite::from_pixel_values(
+                width,
+                height,
+                image.width(),
+                image.height(),
+                left,
+                top,
+                *offsets,
+            ));
+        }
+
+        Ok(SpriteAtlas {
+            texture: TextureData::Image(ImageData { rgba: atlas }, metadata),
+            sprites,
+        })
+    }
+}
+
+/// The texture and sprites packed by a `SpriteAtlasBuilder`.
+#[derive(Clone, Debug)]
+pub struct SpriteAtlas {
+    /// The texture with all images.
+    pub texture: TextureData,
+    /// The sprite of each image, in the order they were added.
+    pub sprites: Vec<Sprite>,
+}
+
+impl SpriteAtlas {
+    /// Loads the texture, and returns the sprite sheet using it.
+    pub fn load<P: Progress>(
+        self,
+        loader: &Loader,
+        storage: &AssetStorage<Texture>,
+        progress: P,
+    ) -> SpriteSheet {
+        SpriteSheet {
+            texture: loader.load_from_data(self.texture, progress, storage),
+            sprites: self.sprites,
+        }
+    }
+}
+
+/// Places the rectangles on shelves sorted by height, trying wider atlases until they fit.
+///
+/// Returns the size of the atlas and the top left corner of each rectangle.
+fn pack(sizes: &[(u32, u32)], padding: u32, max_size: u32) -> Option<(u32, u32, Vec<(u32, u32)>)> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        sizes[b]
+            .1
+            .cmp(&sizes[a].1)
+            .then(sizes[b].0.cmp(&sizes[a].0))
+    });
+
+    let area = sizes
+        .iter()
+        .map(|&(w, h)| u64::from(w + padding) * u64::from(h + padding))
+        .sum::<u64>();
+    let widest = sizes.iter().map(|&(w, _)| w).max().unwrap_or(1);
+    let mut width = ((area as f64).sqrt() as u32)
+        .max(widest)
+        .max(1)
+        .next_power_of_two();
+
+    while width <= max_size {
+        let mut positions = vec![(0, 0); sizes.len()];
+        let (mut x, mut y, mut shelf) = (0, 0, 0);
+        for &index in &order {
+            let (w, h) = sizes[index];
+            if x > 0 && x + w > width {
+                x = 0;
+                y += shelf + padding;
+                shelf = 0;
+            }
+            positions[index] = (x, y);
+            x += w + padding;
+            shelf = shelf.max(h);
+        }
+        let height = (y + shelf).max(1).next_power_of_two();
+        if height <= max_size {
+            return Some((width, height, positions));
+        }
+        width *= 2;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn packs_without_overlap() {
+        let sizes = [(30, 10), (8, 8), (16, 40), (64, 2), (5, 5), (30, 10)];
+        let (width, height, positions) = pack(&sizes, 1, 256).unwrap();
+        assert!(width.is_power_of_two() && height.is_power_of_two());
+        let rects = sizes
+            .iter()
+            .zip(&positions)
+            .map(|(&(w, h), &(x, y))| (x, y, x + w, y + h))
+            .collect::<Vec<_>>();
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.2 <= width && a.3 <= height);
+            for b in &rects[i + 1..] {
+                assert!(a.2 <= b.0 || b.2 <= a.0 || a.3 <= b.1 || b.3 <= a.1);
+            }
+        }
+        assert_eq!(pack(&sizes, 1, 32), None);
+    }
+
+    #[test]
+    fn sprites_point_at_their_images() {
+        let mut builder = SpriteAtlasBuilder::new().with_padding(0);
+        let red = builder.add(
+            RgbaImage::from_pixel(
+                4,
+                4,
+                Rgba {
+                    data: [255, 0, 0, 255],
+                },
+            ),
+            [0.0; 2],
+        );
+        let blue = builder.add(
+            RgbaImage::from_pixel(
+                2,
+                8,
+                Rgba {
+                    data: [0, 0, 255, 255],
+                },
+            ),
+            [1.0; 2],
+        );
+        let atlas = builder.build(TextureMetadata::srgb()).unwrap();
+        let image = match atlas.texture {
+            TextureData::Image(ref data, _) => &data.rgba,
+            _ => panic!("Expected image data"),
+        };
+        for &(sprite, color) in &[(red, [255, 0, 0, 255]), (blue, [0, 0, 255, 255])] {
+            let sprite = &atlas.sprites[sprite];
+            let coords = &sprite.tex_coords;
+            let x = (coords.left * image.width() as f32) as u32;
+            let y = ((1.0 - coords.top) * image.height() as f32) as u32;
+            assert_eq!(image.get_pixel(x, y).data, color);
+            assert_eq!(sprite.width as u32 * sprite.height as u32, 16);
+        }
+        assert_eq!(atlas.sprites[blue].offsets, [1.0, 1.0]);
+    }
+}
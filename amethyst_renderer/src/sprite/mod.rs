@@ -1,6 +1,9 @@
-pub use self::prefab::{
-    SpriteGrid, SpriteList, SpritePosition, SpriteRenderPrefab, SpriteScenePrefab,
-    SpriteSheetPrefab, Sprites,
+pub use self::{
+    atlas::{SpriteAtlas, SpriteAtlasBuilder},
+    prefab::{
+        SpriteGrid, SpriteList, SpritePosition, SpriteRenderPrefab, SpriteScenePrefab,
+        SpriteSheetPrefab, Sprites,
+    },
 };
 
 use ron::de::from_bytes as from_ron_bytes;
@@ -12,6 +15,7 @@ use amethyst_error::Error;
 
 use crate::{error, Texture};
 
+mod atlas;
 mod prefab;
 
 /// An asset handle to sprite sheet metadata.
@@ -0,0 +1,176 @@
+//! Stencil settings for the passes and the per entity stencil reference.
+
+use gfx_core::state::{self, Comparison, Stencil, StencilSide};
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage};
+
+/// The stencil reference value of an entity.
+///
+/// Passes with stencil settings compare the stencil buffer to this value and write it for
+/// `StencilOp::Replace`. Entities without the component use 0, which is also the value the
+/// stencil buffer is cleared to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StencilMask(pub u8);
+
+impl Component for StencilMask {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The comparison between the reference value and the stencil buffer which must pass for a
+/// fragment to be drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StencilTest {
+    /// Never draw.
+    Never,
+    /// Draw if the reference is less than the buffer value.
+    Less,
+    /// Draw if the reference is less than or equal to the buffer value.
+    LessEqual,
+    /// Draw if the reference is equal to the buffer value.
+    Equal,
+    /// Draw if the reference is greater than or equal to the buffer value.
+    GreaterEqual,
+    /// Draw if the reference is greater than the buffer value.
+    Greater,
+    /// Draw if the reference isn't equal to the buffer value.
+    NotEqual,
+    /// Always draw.
+    Always,
+}
+
+/// What happens to the stencil buffer value of a fragment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StencilOp {
+    /// Keep the current value.
+    Keep,
+    /// Set the value to 0.
+    Zero,
+    /// Set the value to the reference.
+    Replace,
+    /// Increment the value, up to 255.
+    IncrementClamp,
+    /// Increment the value, wrapping to 0.
+    IncrementWrap,
+    /// Decrement the value, down to 0.
+    DecrementClamp,
+    /// Decrement the value, wrapping to 255.
+    DecrementWrap,
+    /// Flip all bits of the value.
+    Invert,
+}
+
+/// The stencil state of a pass, see `DrawPbm::with_stencil` for example.
+///
+/// The stencil buffer is part of the depth buffer of the target, so a stage needs a target with a
+/// depth buffer. On macOS the depth buffer has no stencil bits, and the settings have no effect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StencilSettings {
+    /// The test of the reference value against the buffer.
+    pub test: StencilTest,
+    /// The bits of the reference and buffer value used by the test.
+    pub read_mask: u8,
+    /// The bits of the buffer value which may be changed.
+    pub write_mask: u8,
+    /// The operation if the stencil test fails.
+    pub fail: StencilOp,
+    /// The operation if the stencil test passes but the depth test fails.
+    pub depth_fail: StencilOp,
+    /// The operation if both tests pass.
+    pub pass: StencilOp,
+}
+
+impl StencilSettings {
+    /// Always draws, and writes the reference of the entity where it's drawn.
+    ///
+    /// Use it for the shape of a portal or mirror, usually together with a pass not writing any
+    /// color.
+    pub fn write() -> Self {
+        StencilSettings {
+            test: StencilTest::Always,
+            read_mask: 0xff,
+            write_mask: 0xff,
+            fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Replace,
+        }
+    }
+
+    /// Only draws where the buffer has the reference of the entity, for the view through a
+    /// portal.
+    pub fn inside() -> Self {
+        StencilSettings {
+            test: StencilTest::Equal,
+            read_mask: 0xff,
+            write_mask: 0,
+            fail: StencilOp::Keep,
+            depth_fail: StencilOp::Keep,
+            pass: StencilOp::Keep,
+        }
+    }
+
+    /// Only draws where the buffer doesn't have the reference of the entity.
+    pub fn outside() -> Self {
+        StencilSettings {
+            test: StencilTest::NotEqual,
+            ..StencilSettings::inside()
+        }
+    }
+
+    pub(crate) fn to_gfx(self) -> Stencil {
+        let side = StencilSide {
+            fun: match self.test {
+                StencilTest::Never => Comparison::Never,
+                StencilTest::Less => Comparison::Less,
+                StencilTest::LessEqual => Comparison::LessEqual,
+                StencilTest::Equal => Comparison::Equal,
+                StencilTest::GreaterEqual => Comparison::GreaterEqual,
+                StencilTest::Greater => Comparison::Greater,
+                StencilTest::NotEqual => Comparison::NotEqual,
+                StencilTest::Always => Comparison::Always,
+            },
+            mask_read: self.read_mask,
+            mask_write: self.write_mask,
+            op_fail: self.fail.to_gfx(),
+            op_depth_fail: self.depth_fail.to_gfx(),
+            op_pass: self.pass.to_gfx(),
+        };
+        Stencil {
+            front: side,
+            back: side,
+        }
+    }
+}
+
+impl StencilOp {
+    fn to_gfx(self) -> state::StencilOp {
+        match self {
+            StencilOp::Keep => state::StencilOp::Keep,
+            StencilOp::Zero => state::StencilOp::Zero,
+            StencilOp::Replace => state::StencilOp::Replace,
+            StencilOp::IncrementClamp => state::StencilOp::IncrementClamp,
+            StencilOp::IncrementWrap => state::StencilOp::IncrementWrap,
+            StencilOp::DecrementClamp => state::StencilOp::DecrementClamp,
+            StencilOp::DecrementWrap => state::StencilOp::DecrementWrap,
+            StencilOp::Invert => state::StencilOp::Invert,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_to_gfx() {
+        let stencil = StencilSettings::write().to_gfx();
+        assert_eq!(stencil.front, stencil.back);
+        assert_eq!(stencil.front.fun, Comparison::Always);
+        assert_eq!(stencil.front.op_pass, state::StencilOp::Replace);
+        assert_eq!(stencil.front.op_fail, state::StencilOp::Keep);
+
+        let stencil = StencilSettings::outside().to_gfx();
+        assert_eq!(stencil.front.fun, Comparison::NotEqual);
+        assert_eq!(stencil.front.mask_write, 0);
+    }
+}
@@ -1,33 +1,35 @@
 //! Rendering system.
 //!
 
-use std::{mem, sync::Arc};
+use std::{mem, time::Instant};
 
 use derivative::Derivative;
-use log::error;
-use rayon::ThreadPool;
+use log::{error, info};
 use winit::{DeviceEvent, Event, WindowEvent};
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-use amethyst_assets::{AssetStorage, HotReloadStrategy};
+use amethyst_assets::{Asset, AssetSize, AssetStats, AssetStorage, HotReloadStrategy, Loader};
 use amethyst_core::{
     ecs::prelude::{Read, ReadExpect, Resources, RunNow, SystemData, Write, WriteExpect},
     shrev::EventChannel,
     Time,
 };
-use amethyst_error::Error;
+use amethyst_error::{format_err, Error};
 
 use crate::{
     config::DisplayConfig,
-    formats::{create_mesh_asset, create_texture_asset},
+    cubemap::{create_cubemap_asset, Cubemap},
+    formats::{create_mesh_asset, create_texture_asset, MeshData, TextureData},
     mesh::Mesh,
     mtl::{Material, MaterialDefaults},
     pipe::{PipelineBuild, PipelineData, PolyPipeline},
     renderer::Renderer,
-    resources::{ScreenDimensions, WindowMessages},
+    resources::{DrawCalls, RenderStats, ScreenDimensions, WindowControl, WindowMessages},
+    shape::Shape,
     tex::Texture,
+    vertex::PosNormTangTex,
 };
 
 /// Rendering system.
@@ -41,6 +43,22 @@ pub struct RenderSystem<P> {
     // This only exists to allow the system to re-use a vec allocation
     // during event compression.  It's length 0 except during `fn render`.
     event_vec: Vec<Event>,
+    placeholders: bool,
+    fullscreen: bool,
+    #[derivative(Debug = "ignore")]
+    recovery: Option<PipelineRecovery<P>>,
+    reload_assets: bool,
+}
+
+/// Builds the pipeline of a `RenderSystem` again after the graphics device was lost.
+pub(crate) type PipelineRecovery<P> = Box<dyn Fn(&mut Renderer) -> Result<P, Error>>;
+
+pub(crate) fn pipeline_recovery<B, P>(pipe: B) -> PipelineRecovery<P>
+where
+    B: PipelineBuild<Pipeline = P> + Clone + 'static,
+    P: PolyPipeline,
+{
+    Box::new(move |renderer| renderer.create_pipe(pipe.clone()))
 }
 
 impl<P> RenderSystem<P>
@@ -58,6 +76,7 @@ where
         // see https://github.com/amethyst/amethyst/issues/890
         env::set_var("WINIT_UNIX_BACKEND", "x11");
 
+        let fullscreen = config.as_ref().map_or(false, |config| config.fullscreen);
         let mut renderer = {
             let mut renderer = Renderer::build();
 
@@ -69,7 +88,10 @@ where
         };
 
         match renderer.create_pipe(pipe) {
-            Ok(pipe) => Ok(Self::new(pipe, renderer)),
+            Ok(pipe) => Ok(Self {
+                fullscreen,
+                ..Self::new(pipe, renderer)
+            }),
             Err(err) => {
                 error!("Failed creating pipeline: {}", err);
                 Err(err)
@@ -89,37 +111,96 @@ where
             renderer,
             cached_size,
             event_vec: Vec::with_capacity(20),
+            placeholders: false,
+            fullscreen: false,
+            recovery: None,
+            reload_assets: false,
         }
     }
 
+    /// Recovers from the loss of the graphics device, e.g. by a driver reset or a switch of the
+    /// GPU, instead of panicking.
+    ///
+    /// The window and the device are recreated, the pipeline is built again from `pipe` and all
+    /// meshes, textures and cubemaps loaded from a source are reloaded. Assets created from data,
+    /// like the meshes of glTF files, can't be restored and have to be loaded again by the game,
+    /// which can watch `RenderStats::device_losses` for this.
+    pub fn with_device_recovery<B>(mut self, pipe: B) -> Self
+    where
+        B: PipelineBuild<Pipeline = P> + Clone + 'static,
+    {
+        self.recovery = Some(pipeline_recovery(pipe));
+        self
+    }
+
+    pub(crate) fn with_recovery(mut self, recovery: Option<PipelineRecovery<P>>) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Substitute placeholders for meshes and textures that fail to load.
+    ///
+    /// Failed textures are replaced by a magenta texture and failed meshes by a unit cube, so
+    /// prefabs referring to them still finish loading. The errors are still reported to the
+    /// `ProgressCounter` of the load.
+    pub fn with_placeholders(mut self, placeholders: bool) -> Self {
+        self.placeholders = placeholders;
+        self
+    }
+
     fn asset_loading(
         &mut self,
-        (time, pool, strategy, mut mesh_storage, mut texture_storage): AssetLoadingData<'_>,
+        (time, loader, strategy, mut mesh_storage, mut texture_storage, mut cubemap_storage, stats): AssetLoadingData<'_>,
     ) {
         use std::ops::Deref;
 
         let strategy = strategy.as_ref().map(Deref::deref);
 
+        if self.reload_assets {
+            self.reload_assets = false;
+            mesh_storage.reload_all(&**loader.pool());
+            texture_storage.reload_all(&**loader.pool());
+            cubemap_storage.reload_all(&**loader.pool());
+        }
+
         mesh_storage.process(
             |d| create_mesh_asset(d, &mut self.renderer),
             time.frame_number(),
-            &**pool,
+            &**loader.pool(),
             strategy,
         );
 
         texture_storage.process(
             |d| create_texture_asset(d, &mut self.renderer),
             time.frame_number(),
-            &**pool,
+            &**loader.pool(),
             strategy,
         );
+
+        cubemap_storage.process(
+            |d| create_cubemap_asset(d, &mut self.renderer),
+            time.frame_number(),
+            &**loader.pool(),
+            strategy,
+        );
+
+        if let Some(stats) = stats {
+            let now = time.absolute_real_time();
+            stats.refresh(Mesh::NAME, now, || mesh_storage.stats());
+            stats.refresh(Texture::NAME, now, || texture_storage.stats());
+            stats.refresh(Cubemap::NAME, now, || cubemap_storage.stats());
+        }
     }
 
-    fn window_management(&mut self, (mut window_messages, mut screen_dimensions): WindowData<'_>) {
+    fn window_management(
+        &mut self,
+        (mut window_messages, mut window_control, mut screen_dimensions): WindowData<'_>,
+    ) {
         // Process window commands
         for mut command in window_messages.queue.drain() {
             command(self.renderer.window());
         }
+        window_control.apply(self.renderer.window());
 
         let width = screen_dimensions.w;
         let height = screen_dimensions.h;
@@ -149,28 +230,68 @@ where
         screen_dimensions.update_hidpi_factor(hidpi);
     }
 
-    fn render(&mut self, (mut event_handler, data): RenderData<'_, P>) {
-        self.renderer.draw(&mut self.pipe, data);
+    /// Draws a frame, returns `false` if the graphics device was lost while drawing.
+    fn render(
+        &mut self,
+        (mut event_handler, mut stats, draw_calls, data): RenderData<'_, P>,
+    ) -> bool {
+        let start = Instant::now();
+        let result = self.renderer.draw(&mut self.pipe, data);
+        stats.draw_calls = draw_calls.take();
+        stats.render_time = start.elapsed();
+        // Other rendering failures panic, only the loss of the context can be recovered from.
+        let lost = result.is_err();
+        if lost {
+            error!("Rendering failed, the graphics device was lost");
+            stats.device_losses += 1;
+        }
         let events = &mut self.event_vec;
         self.renderer.events_mut().poll_events(|new_event| {
             compress_events(events, new_event);
         });
         event_handler.iter_write(events.drain(..));
+        !lost
+    }
+}
+
+impl<P> RenderSystem<P>
+where
+    P: PolyPipeline,
+{
+    fn recover(&mut self, res: &Resources) -> Result<(), Error> {
+        let recovery = self
+            .recovery
+            .as_ref()
+            .ok_or_else(|| format_err!("Device recovery is not enabled"))?;
+        self.renderer.recreate()?;
+        self.pipe = recovery(&mut self.renderer)?;
+        self.reload_assets = true;
+        create_defaults(res, self.placeholders);
+        info!("Recreated the renderer after the graphics device was lost");
+        Ok(())
     }
 }
 
 type AssetLoadingData<'a> = (
     Read<'a, Time>,
-    ReadExpect<'a, Arc<ThreadPool>>,
+    ReadExpect<'a, Loader>,
     Option<Read<'a, HotReloadStrategy>>,
     Write<'a, AssetStorage<Mesh>>,
     Write<'a, AssetStorage<Texture>>,
+    Write<'a, AssetStorage<Cubemap>>,
+    Option<Read<'a, AssetStats>>,
 );
 
-type WindowData<'a> = (Write<'a, WindowMessages>, WriteExpect<'a, ScreenDimensions>);
+type WindowData<'a> = (
+    Write<'a, WindowMessages>,
+    Write<'a, WindowControl>,
+    WriteExpect<'a, ScreenDimensions>,
+);
 
 type RenderData<'a, P> = (
     Write<'a, EventChannel<Event>>,
+    Write<'a, RenderStats>,
+    Read<'a, DrawCalls>,
     <P as PipelineData<'a>>::Data,
 );
 
@@ -194,7 +315,14 @@ where
         {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_render");
-            self.render(RenderData::<P>::fetch(res));
+            if !self.render(RenderData::<P>::fetch(res)) {
+                if let Err(err) = self.recover(res) {
+                    panic!(
+                        "Failed to recover from the loss of the graphics device: {}",
+                        err
+                    );
+                }
+            }
         }
     }
 
@@ -203,7 +331,24 @@ where
         WindowData::setup(res);
         RenderData::<P>::setup(res);
 
-        let mat = create_default_mat(res);
+        res.fetch_mut::<AssetStorage<Mesh>>()
+            .set_size_estimator(|mesh: &Mesh| AssetSize::gpu(mesh.byte_size() as u64));
+        res.fetch_mut::<AssetStorage<Texture>>()
+            .set_size_estimator(|tex: &Texture| AssetSize::gpu(tex.byte_size() as u64));
+        res.fetch_mut::<AssetStorage<Cubemap>>()
+            .set_size_estimator(|cubemap: &Cubemap| AssetSize::gpu(cubemap.byte_size() as u64));
+
+        if self.placeholders {
+            res.fetch_mut::<AssetStorage<Mesh>>()
+                .set_placeholder(placeholder_mesh);
+            res.fetch_mut::<AssetStorage<Texture>>()
+                .set_placeholder(placeholder_texture);
+        }
+
+        let mat = placeholder_material(
+            &res.fetch::<Loader>(),
+            &res.fetch::<AssetStorage<Texture>>(),
+        );
         res.insert(MaterialDefaults(mat));
         let (width, height) = self
             .renderer
@@ -213,15 +358,31 @@ where
             .into();
         let hidpi = self.renderer.window().get_hidpi_factor();
         res.insert(ScreenDimensions::new(width, height, hidpi));
+        res.insert(WindowControl::new(self.fullscreen));
     }
 }
 
-fn create_default_mat(res: &mut Resources) -> Material {
-    use crate::mtl::TextureOffset;
-
-    use amethyst_assets::Loader;
+/// Sets the placeholders again and replaces the `MaterialDefaults`, whose textures are created
+/// from data and can't be reloaded after the graphics device was lost.
+fn create_defaults(res: &Resources, placeholders: bool) {
+    if placeholders {
+        res.fetch_mut::<AssetStorage<Mesh>>()
+            .set_placeholder(placeholder_mesh);
+        res.fetch_mut::<AssetStorage<Texture>>()
+            .set_placeholder(placeholder_texture);
+    }
+    let mat = placeholder_material(
+        &res.fetch::<Loader>(),
+        &res.fetch::<AssetStorage<Texture>>(),
+    );
+    *res.fetch_mut::<MaterialDefaults>() = MaterialDefaults(mat);
+}
 
-    let loader = res.fetch::<Loader>();
+/// Material used in place of materials whose textures are still loading, or failed to load
+/// without a placeholder texture: a gray, rough and non-metallic surface. It is also the
+/// `MaterialDefaults`.
+pub fn placeholder_material(loader: &Loader, tex_storage: &AssetStorage<Texture>) -> Material {
+    use crate::mtl::TextureOffset;
 
     let albedo = [0.5, 0.5, 0.5, 1.0].into();
     let emission = [0.0; 4].into();
@@ -231,15 +392,13 @@ fn create_default_mat(res: &mut Resources) -> Material {
     let ambient_occlusion = [1.0; 4].into();
     let caveat = [1.0; 4].into();
 
-    let tex_storage = res.fetch();
-
-    let albedo = loader.load_from_data(albedo, (), &tex_storage);
-    let emission = loader.load_from_data(emission, (), &tex_storage);
-    let normal = loader.load_from_data(normal, (), &tex_storage);
-    let metallic = loader.load_from_data(metallic, (), &tex_storage);
-    let roughness = loader.load_from_data(roughness, (), &tex_storage);
-    let ambient_occlusion = loader.load_from_data(ambient_occlusion, (), &tex_storage);
-    let caveat = loader.load_from_data(caveat, (), &tex_storage);
+    let albedo = loader.load_from_data(albedo, (), tex_storage);
+    let emission = loader.load_from_data(emission, (), tex_storage);
+    let normal = loader.load_from_data(normal, (), tex_storage);
+    let metallic = loader.load_from_data(metallic, (), tex_storage);
+    let roughness = loader.load_from_data(roughness, (), tex_storage);
+    let ambient_occlusion = loader.load_from_data(ambient_occlusion, (), tex_storage);
+    let caveat = loader.load_from_data(caveat, (), tex_storage);
 
     Material {
         alpha_cutoff: 0.01,
@@ -257,9 +416,20 @@ fn create_default_mat(res: &mut Resources) -> Material {
         ambient_occlusion_offset: TextureOffset::default(),
         caveat,
         caveat_offset: TextureOffset::default(),
+        unlit: false,
     }
 }
 
+/// Texture data used in place of textures that failed to load: a single magenta texel.
+pub fn placeholder_texture() -> TextureData {
+    [1.0, 0.0, 1.0, 1.0].into()
+}
+
+/// Mesh data used in place of meshes that failed to load: a cube with an edge length of one.
+pub fn placeholder_mesh() -> MeshData {
+    Shape::Cube.generate::<Vec<PosNormTangTex>>(Some((0.5, 0.5, 0.5)))
+}
+
 /// Input devices can sometimes generate a lot of motion events per frame, these are
 /// useless as the extra precision is wasted and these events tend to overflow our
 /// otherwise very adequate event buffers.  So this function removes and compresses redundant
@@ -381,3 +551,36 @@ fn compress_events(vec: &mut Vec<Event>, new_event: Event) {
     }
     vec.push(new_event);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::ThreadPoolBuilder;
+    use std::sync::Arc;
+
+    #[test]
+    fn recreating_defaults_replaces_the_handles() {
+        let mut res = Resources::new();
+        res.insert(Loader::new(
+            ".",
+            Arc::new(ThreadPoolBuilder::new().build().unwrap()),
+        ));
+        res.insert(AssetStorage::<Mesh>::default());
+        res.insert(AssetStorage::<Texture>::default());
+        let mat = placeholder_material(
+            &res.fetch::<Loader>(),
+            &res.fetch::<AssetStorage<Texture>>(),
+        );
+        res.insert(MaterialDefaults(mat));
+        let old = res.fetch::<MaterialDefaults>().0.clone();
+
+        create_defaults(&res, true);
+
+        let new = &res.fetch::<MaterialDefaults>().0;
+        assert_ne!(old.albedo, new.albedo);
+        assert_ne!(old.normal, new.normal);
+        assert_ne!(old.caveat, new.caveat);
+        assert!(res.fetch::<AssetStorage<Mesh>>().has_placeholder());
+        assert!(res.fetch::<AssetStorage<Texture>>().has_placeholder());
+    }
+}
@@ -56,6 +56,32 @@ impl Texture {
         let (w, h, _, _) = self.texture.get_info().kind.get_dimensions();
         (w as usize, h as usize)
     }
+
+    /// Returns an estimate of the GPU memory used by the texture in bytes, including all mip
+    /// levels, array layers and cubemap faces.
+    pub fn byte_size(&self) -> usize {
+        use gfx::texture::Kind;
+
+        let info = self.texture.get_info();
+        let (w, h, _, _) = info.kind.get_dimensions();
+        // Only the depth of 3D textures shrinks with the mip levels.
+        let (depth, layers) = match info.kind {
+            Kind::D3(_, _, d) => (d as usize, 1),
+            Kind::D1Array(_, a) | Kind::D2Array(_, _, a, _) => (1, a as usize),
+            Kind::Cube(_) => (1, 6),
+            Kind::CubeArray(_, a) => (1, 6 * a as usize),
+            Kind::D1(_) | Kind::D2(..) => (1, 1),
+        };
+        let bytes_per_texel = info.format.get_total_bits() as usize / 8;
+        (0..info.levels)
+            .map(|level| {
+                let size = |dim: usize| (dim >> level).max(1);
+                size(w as usize) * size(h as usize) * size(depth)
+            })
+            .sum::<usize>()
+            * layers
+            * bytes_per_texel
+    }
 }
 
 impl Asset for Texture {
@@ -3,9 +3,12 @@ use std::cmp::Ordering;
 use hibitset::BitSet;
 
 use amethyst_core::{
-    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, Write},
-    math::{self as na, zero, Point3, RealField, Vector3},
-    Transform,
+    alga::general::SubsetOf,
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, Write,
+    },
+    math::{self as na, convert, zero, Matrix4, Point3, RealField, Vector3},
+    Frustum, Transform, WorldBounds,
 };
 
 use crate::{
@@ -24,11 +27,33 @@ pub struct Visibility {
     pub visible_ordered: Vec<Entity>,
 }
 
+/// Sorts an entity into `Visibility::visible_ordered`, which the passes draw after the opaque
+/// entities without an order.
+///
+/// Ordered entities are drawn from the lowest order to the highest, and transparent entities
+/// without a `RenderOrder` have an order of 0. For example decals with an order of 1 are drawn
+/// after the level and the transparent entities, and a weapon viewmodel with a higher order is
+/// drawn last. Of the entities with the same order, the opaque ones are drawn first, nearest to
+/// the camera first, and then the transparent ones back to front.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderOrder(pub i32);
+
+impl Component for RenderOrder {
+    type Storage = DenseVecStorage<Self>;
+}
+
 /// Determine what entities are visible to the camera, and which are not. Will also sort transparent
-/// entities back to front based on distance from camera.
+/// entities back to front based on distance from camera, and entities with a `RenderOrder` by
+/// their order.
+///
+/// Entities with `WorldBounds`, like the meshes of glTF scenes, are culled against the frustum of
+/// the camera, so the passes only draw the ones in view, and sorted by the center of their bounds.
+/// The primitives of a glTF mesh share the origin of their node, so sorting them by it would draw
+/// overlapping transparent primitives in an arbitrary order. Entities without bounds are sorted
+/// by their origin, and only culled when it is behind the camera.
 ///
 /// Note that this should run after `GlobalTransform` has been updated for the current frame, and
-/// before rendering occurs.
+/// before rendering occurs, and after the "bounds_system" of the `TransformBundle`.
 pub struct VisibilitySortingSystem<N: RealField> {
     centroids: Vec<Internals<N>>,
     transparent: Vec<Internals<N>>,
@@ -38,8 +63,9 @@ pub struct VisibilitySortingSystem<N: RealField> {
 struct Internals<N: RealField> {
     entity: Entity,
     transparent: bool,
+    order: Option<i32>,
     centroid: Point3<N>,
-    camera_distance: N,
+    camera_distance: f32,
     from_camera: Vector3<N>,
 }
 
@@ -53,7 +79,7 @@ impl<N: RealField> VisibilitySortingSystem<N> {
     }
 }
 
-impl<'a, N: RealField> System<'a> for VisibilitySortingSystem<N> {
+impl<'a, N: RealField + SubsetOf<f32>> System<'a> for VisibilitySortingSystem<N> {
     type SystemData = (
         Entities<'a>,
         Write<'a, Visibility>,
@@ -62,12 +88,25 @@ impl<'a, N: RealField> System<'a> for VisibilitySortingSystem<N> {
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
         ReadStorage<'a, Transparent>,
+        ReadStorage<'a, RenderOrder>,
         ReadStorage<'a, Transform<N>>,
+        ReadStorage<'a, WorldBounds>,
     );
 
     fn run(
         &mut self,
-        (entities, mut visibility, hidden, hidden_prop, active, camera, transparent, transform): Self::SystemData,
+        (
+            entities,
+            mut visibility,
+            hidden,
+            hidden_prop,
+            active,
+            camera,
+            transparent,
+            order,
+            transform,
+            bounds,
+        ): Self::SystemData,
     ) {
         let origin = Point3::origin();
 
@@ -81,6 +120,15 @@ impl<'a, N: RealField> System<'a> for VisibilitySortingSystem<N> {
         let camera_centroid = camera
             .map(|g| g.global_matrix().transform_point(&origin))
             .unwrap_or(origin);
+        let camera_position = convert::<_, Point3<f32>>(camera_centroid);
+        let frustum = active
+            .entity
+            .and_then(|entity| Some((camera.get(entity)?, transform.get(entity)?)))
+            .or_else(|| (&camera, &transform).join().next())
+            .map(|(camera, transform)| {
+                let global_matrix = convert::<_, Matrix4<f32>>(*transform.global_matrix());
+                Frustum::from_camera(&camera.proj, &global_matrix)
+            });
 
         self.centroids.clear();
         self.centroids.extend(
@@ -92,23 +140,49 @@ impl<'a, N: RealField> System<'a> for VisibilitySortingSystem<N> {
                 .map(|(entity, centroid)| Internals {
                     entity,
                     transparent: transparent.contains(entity),
+                    order: order.get(entity).map(|order| order.0),
                     centroid,
-                    camera_distance: na::distance_squared(&centroid, &camera_centroid),
+                    camera_distance: match bounds.get(entity) {
+                        Some(bounds) => {
+                            na::distance_squared(&Point3::from(bounds.center), &camera_position)
+                        }
+                        None => na::distance_squared(
+                            &convert::<_, Point3<f32>>(centroid),
+                            &camera_position,
+                        ),
+                    },
                     from_camera: centroid - camera_centroid,
                 })
-                .filter(|c| c.from_camera.dot(&camera_backward) < zero()), // filter entities behind the camera
+                .filter(|c| match (&frustum, bounds.get(c.entity)) {
+                    (Some(frustum), Some(bounds)) => {
+                        frustum.contains_sphere(&bounds.center, bounds.radius)
+                            && frustum.contains_aabb(&bounds.aabb)
+                    }
+                    // filter entities behind the camera
+                    _ => c.from_camera.dot(&camera_backward) < zero(),
+                }),
         );
         self.transparent.clear();
-        self.transparent
-            .extend(self.centroids.iter().filter(|c| c.transparent).cloned());
+        self.transparent.extend(
+            self.centroids
+                .iter()
+                .filter(|c| c.transparent || c.order.is_some())
+                .cloned(),
+        );
         self.transparent.sort_by(|a, b| {
-            b.camera_distance
-                .partial_cmp(&a.camera_distance)
-                .unwrap_or(Ordering::Equal)
+            let distance = match (a.transparent, b.transparent) {
+                (false, false) => a.camera_distance.partial_cmp(&b.camera_distance),
+                (true, true) => b.camera_distance.partial_cmp(&a.camera_distance),
+                _ => Some(a.transparent.cmp(&b.transparent)),
+            };
+            a.order
+                .unwrap_or(0)
+                .cmp(&b.order.unwrap_or(0))
+                .then(distance.unwrap_or(Ordering::Equal))
         });
         visibility.visible_unordered.clear();
         for c in &self.centroids {
-            if !c.transparent {
+            if !c.transparent && c.order.is_none() {
                 visibility.visible_unordered.add(c.entity.id());
             }
         }
@@ -118,3 +192,92 @@ impl<'a, N: RealField> System<'a> for VisibilitySortingSystem<N> {
             .extend(self.transparent.iter().map(|c| c.entity));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::{
+        ecs::prelude::{Builder, RunNow, World},
+        Aabb,
+    };
+
+    #[test]
+    fn entities_outside_the_frustum_are_culled() {
+        let mut world = World::new();
+        let mut system = VisibilitySortingSystem::<f32>::new();
+        System::setup(&mut system, &mut world.res);
+
+        world
+            .create_entity()
+            .with(Camera::standard_3d(100.0, 100.0))
+            .with(Transform::<f32>::default())
+            .build();
+        let bounds = |z: f32, x: f32| {
+            let center = Vector3::new(x, 0.0, z);
+            let aabb = Aabb::new(center - Vector3::repeat(1.0), center + Vector3::repeat(1.0));
+            WorldBounds {
+                aabb,
+                center,
+                radius: 3.0f32.sqrt(),
+            }
+        };
+        let ahead = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(bounds(-10.0, 0.0))
+            .build();
+        let aside = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(bounds(-10.0, 100.0))
+            .build();
+        let behind = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(bounds(10.0, 0.0))
+            .build();
+
+        system.run_now(&world.res);
+
+        let visibility = world.read_resource::<Visibility>();
+        assert!(visibility.visible_unordered.contains(ahead.id()));
+        assert!(!visibility.visible_unordered.contains(aside.id()));
+        assert!(!visibility.visible_unordered.contains(behind.id()));
+    }
+
+    #[test]
+    fn transparent_entities_are_sorted_by_their_bounds() {
+        let mut world = World::new();
+        let mut system = VisibilitySortingSystem::<f32>::new();
+        System::setup(&mut system, &mut world.res);
+
+        world
+            .create_entity()
+            .with(Camera::standard_3d(100.0, 100.0))
+            .with(Transform::<f32>::default())
+            .build();
+        // The entities share an origin, like the primitives of a glTF mesh.
+        let primitive = |world: &mut World, z: f32| {
+            let center = Vector3::new(0.0, 0.0, z);
+            world
+                .create_entity()
+                .with(Transform::<f32>::default())
+                .with(Transparent)
+                .with(WorldBounds {
+                    aabb: Aabb::new(center - Vector3::repeat(1.0), center + Vector3::repeat(1.0)),
+                    center,
+                    radius: 3.0f32.sqrt(),
+                })
+                .build()
+        };
+        let near = primitive(&mut world, -5.0);
+        let far = primitive(&mut world, -20.0);
+        let middle = primitive(&mut world, -10.0);
+
+        system.run_now(&world.res);
+
+        let visibility = world.read_resource::<Visibility>();
+        assert_eq!(visibility.visible_ordered, vec![far, middle, near]);
+    }
+}
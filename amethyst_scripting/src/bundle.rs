@@ -0,0 +1,60 @@
+use amethyst_assets::Processor;
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    Named, Transform,
+};
+use amethyst_error::Error;
+use amethyst_ui::UiText;
+
+use crate::{component::ScriptComponent, script::Script, system::ScriptSystem};
+
+/// The bundle running Lua scripts, which can read and write `Transform<f32>`, `Named` and
+/// `UiText` components.
+///
+/// # Systems
+///
+/// This bundle adds the following systems:
+///
+/// * `Processor<Script>`, named `"script_processor"`
+/// * `ScriptSystem`, as a thread local system
+pub struct ScriptingBundle {
+    system: ScriptSystem,
+}
+
+impl Default for ScriptingBundle {
+    fn default() -> Self {
+        ScriptingBundle::new()
+    }
+}
+
+impl ScriptingBundle {
+    /// Creates a bundle without scripts.
+    pub fn new() -> Self {
+        ScriptingBundle {
+            system: ScriptSystem::new()
+                .with_component::<Transform<f32>>()
+                .with_component::<Named>()
+                .with_component::<UiText>(),
+        }
+    }
+
+    /// Lets scripts read and write the component, see `ScriptComponent`.
+    pub fn with_component<C: ScriptComponent>(mut self) -> Self {
+        self.system = self.system.with_component::<C>();
+        self
+    }
+
+    /// Loads and runs the script at `name`, relative to the asset directory.
+    pub fn with_script<N: Into<String>>(mut self, name: N) -> Self {
+        self.system = self.system.with_script(name);
+        self
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for ScriptingBundle {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        builder.add(Processor::<Script>::new(), "script_processor", &[]);
+        builder.add_thread_local(self.system, "script_system");
+        Ok(())
+    }
+}
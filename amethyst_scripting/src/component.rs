@@ -0,0 +1,246 @@
+use std::{borrow::Cow, marker::PhantomData};
+
+use rlua::{Context, Table, Value};
+
+use amethyst_core::{
+    ecs::prelude::{Component, Entity, ReadStorage, Resources, SystemData, WriteStorage},
+    math::{UnitQuaternion, Vector3},
+    Named, Transform,
+};
+use amethyst_ui::UiText;
+
+/// A component scripts can read and write, as a table of its fields.
+///
+/// The `ScriptingBundle` registers `Transform`, `Named` and `UiText`. Other components are
+/// registered with `ScriptingBundle::with_component`.
+pub trait ScriptComponent: Component + Send + Sync {
+    /// The name scripts refer to the component by.
+    const NAME: &'static str;
+
+    /// Returns the table a script reads.
+    fn to_table<'lua>(&self, lua: Context<'lua>) -> rlua::Result<Table<'lua>>;
+
+    /// Updates the component from a table written by a script. Fields missing from the table
+    /// keep their value.
+    fn update(&mut self, table: &Table<'_>) -> rlua::Result<()>;
+}
+
+fn vector_table<'lua>(lua: Context<'lua>, vector: &Vector3<f32>) -> rlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    table.set("x", vector.x)?;
+    table.set("y", vector.y)?;
+    table.set("z", vector.z)?;
+    Ok(table)
+}
+
+/// Reads the vector in the field `key` of the table, if there is one.
+fn update_vector(table: &Table<'_>, key: &str, vector: &mut Vector3<f32>) -> rlua::Result<bool> {
+    let fields = match table.get::<_, Option<Table<'_>>>(key)? {
+        Some(fields) => fields,
+        None => return Ok(false),
+    };
+    for (i, axis) in ["x", "y", "z"].iter().enumerate() {
+        if let Some(value) = fields.get::<_, Option<f32>>(*axis)? {
+            vector[i] = value;
+        }
+    }
+    Ok(true)
+}
+
+/// The `translation` and `scale` of the transform are tables with `x`, `y` and `z`, the
+/// `rotation` a quaternion with `x`, `y`, `z` and `w`. Scripts can set the rotation with an
+/// `euler` table of angles around each axis in radians instead, which takes precedence.
+impl ScriptComponent for Transform<f32> {
+    const NAME: &'static str = "Transform";
+
+    fn to_table<'lua>(&self, lua: Context<'lua>) -> rlua::Result<Table<'lua>> {
+        let table = lua.create_table()?;
+        table.set("translation", vector_table(lua, self.translation())?)?;
+        let quaternion = self.rotation().quaternion();
+        let rotation = lua.create_table()?;
+        rotation.set("x", quaternion.i)?;
+        rotation.set("y", quaternion.j)?;
+        rotation.set("z", quaternion.k)?;
+        rotation.set("w", quaternion.w)?;
+        table.set("rotation", rotation)?;
+        table.set("scale", vector_table(lua, self.scale())?)?;
+        Ok(table)
+    }
+
+    fn update(&mut self, table: &Table<'_>) -> rlua::Result<()> {
+        update_vector(table, "translation", self.translation_mut())?;
+        update_vector(table, "scale", self.scale_mut())?;
+        let mut euler = Vector3::zeros();
+        if update_vector(table, "euler", &mut euler)? {
+            self.set_rotation_euler(euler.x, euler.y, euler.z);
+        } else if let Some(rotation) = table.get::<_, Option<Table<'_>>>("rotation")? {
+            let mut quaternion = *self.rotation().quaternion();
+            for (i, axis) in ["x", "y", "z", "w"].iter().enumerate() {
+                if let Some(value) = rotation.get::<_, Option<f32>>(*axis)? {
+                    quaternion.coords[i] = value;
+                }
+            }
+            let rotation = UnitQuaternion::try_new(quaternion, 1e-6).ok_or_else(|| {
+                rlua::Error::RuntimeError("The rotation of a Transform can't be zero".into())
+            })?;
+            self.set_rotation(rotation);
+        }
+        Ok(())
+    }
+}
+
+/// The table has the `name` of the entity.
+impl ScriptComponent for Named {
+    const NAME: &'static str = "Named";
+
+    fn to_table<'lua>(&self, lua: Context<'lua>) -> rlua::Result<Table<'lua>> {
+        let table = lua.create_table()?;
+        table.set("name", &*self.name)?;
+        Ok(table)
+    }
+
+    fn update(&mut self, table: &Table<'_>) -> rlua::Result<()> {
+        if let Some(name) = table.get::<_, Option<String>>("name")? {
+            self.name = Cow::Owned(name);
+        }
+        Ok(())
+    }
+}
+
+/// The table has the `text`, `font_size` and `password` of the text, and its `color` as a
+/// table with `r`, `g`, `b` and `a`.
+impl ScriptComponent for UiText {
+    const NAME: &'static str = "UiText";
+
+    fn to_table<'lua>(&self, lua: Context<'lua>) -> rlua::Result<Table<'lua>> {
+        let table = lua.create_table()?;
+        table.set("text", self.text.as_str())?;
+        table.set("font_size", self.font_size)?;
+        table.set("password", self.password)?;
+        let color = lua.create_table()?;
+        for (channel, value) in ["r", "g", "b", "a"].iter().zip(&self.color) {
+            color.set(*channel, *value)?;
+        }
+        table.set("color", color)?;
+        Ok(table)
+    }
+
+    fn update(&mut self, table: &Table<'_>) -> rlua::Result<()> {
+        if let Some(text) = table.get::<_, Option<String>>("text")? {
+            self.text = text;
+        }
+        if let Some(font_size) = table.get::<_, Option<f32>>("font_size")? {
+            self.font_size = font_size;
+        }
+        if let Some(password) = table.get::<_, Option<bool>>("password")? {
+            self.password = password;
+        }
+        if let Some(color) = table.get::<_, Option<Table<'_>>>("color")? {
+            for (channel, value) in ["r", "g", "b", "a"].iter().zip(self.color.iter_mut()) {
+                if let Some(channel) = color.get::<_, Option<f32>>(*channel)? {
+                    *value = channel;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The storage of a `ScriptComponent`, fetched by its name while a script runs.
+pub(crate) trait ComponentAccess: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn setup(&self, res: &mut Resources);
+
+    /// The indices of the entities with the component, in order.
+    fn ids(&self, res: &Resources) -> Vec<u32>;
+
+    fn has(&self, res: &Resources, id: u32) -> bool;
+
+    /// Returns the table of the component of the entity, or nil if it has none.
+    fn get<'lua>(
+        &self,
+        res: &Resources,
+        entity: Entity,
+        lua: Context<'lua>,
+    ) -> rlua::Result<Value<'lua>>;
+
+    /// Updates the component of the entity, returning whether it has one.
+    fn set(&self, res: &Resources, entity: Entity, table: &Table<'_>) -> rlua::Result<bool>;
+}
+
+pub(crate) struct Access<C>(PhantomData<C>);
+
+impl<C> Access<C> {
+    pub(crate) fn new() -> Self {
+        Access(PhantomData)
+    }
+}
+
+impl<C: ScriptComponent> ComponentAccess for Access<C> {
+    fn name(&self) -> &'static str {
+        C::NAME
+    }
+
+    fn setup(&self, res: &mut Resources) {
+        WriteStorage::<'_, C>::setup(res);
+    }
+
+    fn ids(&self, res: &Resources) -> Vec<u32> {
+        use amethyst_core::ecs::prelude::Join;
+
+        ReadStorage::<'_, C>::fetch(res).mask().join().collect()
+    }
+
+    fn has(&self, res: &Resources, id: u32) -> bool {
+        ReadStorage::<'_, C>::fetch(res).mask().contains(id)
+    }
+
+    fn get<'lua>(
+        &self,
+        res: &Resources,
+        entity: Entity,
+        lua: Context<'lua>,
+    ) -> rlua::Result<Value<'lua>> {
+        match ReadStorage::<'_, C>::fetch(res).get(entity) {
+            Some(component) => component.to_table(lua).map(Value::Table),
+            None => Ok(Value::Nil),
+        }
+    }
+
+    fn set(&self, res: &Resources, entity: Entity, table: &Table<'_>) -> rlua::Result<bool> {
+        match WriteStorage::<'_, C>::fetch(res).get_mut(entity) {
+            Some(component) => component.update(table).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rlua::Lua;
+
+    use super::*;
+
+    #[test]
+    fn transform_tables() {
+        let mut transform = Transform::<f32>::default();
+        transform.set_translation_xyz(1.0, 2.0, 3.0);
+
+        Lua::new().context(|lua| {
+            let table = transform.to_table(lua).unwrap();
+            let translation: Table<'_> = table.get("translation").unwrap();
+            assert_eq!(translation.get::<_, f32>("y").unwrap(), 2.0);
+
+            let table: Table<'_> = lua
+                .load("return { translation = { x = 5 }, euler = { x = 0, y = 1.5, z = 0 } }")
+                .eval()
+                .unwrap();
+            transform.update(&table).unwrap();
+        });
+
+        assert_eq!(*transform.translation(), Vector3::new(5.0, 2.0, 3.0));
+        assert!((transform.euler_angles().1 - 1.5).abs() < 1e-4);
+        assert_eq!(*transform.scale(), Vector3::from_element(1.0));
+    }
+}
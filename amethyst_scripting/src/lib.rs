@@ -0,0 +1,37 @@
+//! Amethyst scripting crate.
+//!
+//! Runs systems written in Lua, which read and write the components registered with
+//! `ScriptingBundle::with_component`: `Transform`, `Named` and `UiText` by default. A script
+//! returns a table of the systems it registers, functions called every frame with the `world`
+//! and the time since the last frame:
+//!
+//! ```lua
+//! return {
+//!     spin = function(world, dt)
+//!         for _, entity in ipairs(world.query("Transform", "Named")) do
+//!             local transform = world.get(entity, "Transform")
+//!             transform.euler = { x = 0, y = world.time * 0.5, z = 0 }
+//!             world.set(entity, "Transform", transform)
+//!         end
+//!     end,
+//! }
+//! ```
+//!
+//! Scripts are assets loaded with the `LuaFormat`. With the `HotReloadBundle` added, changed
+//! scripts are loaded again while the game runs, replacing the systems they registered.
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use rlua;
+
+pub use self::{
+    bundle::ScriptingBundle,
+    component::ScriptComponent,
+    script::{LuaFormat, Script, ScriptHandle, Scripts},
+    system::ScriptSystem,
+};
+
+mod bundle;
+mod component;
+mod script;
+mod system;
@@ -0,0 +1,81 @@
+use amethyst_assets::{Asset, AssetStorage, Handle, Loader, ProcessingState, SimpleFormat};
+use amethyst_core::ecs::prelude::VecStorage;
+use amethyst_error::Error;
+
+/// Loads Lua scripts.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LuaFormat;
+
+impl SimpleFormat<Script> for LuaFormat {
+    const NAME: &'static str = "LUA";
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<Script, Error> {
+        Ok(Script {
+            source: String::from_utf8(bytes)?,
+        })
+    }
+}
+
+impl Into<Result<ProcessingState<Script>, Error>> for Script {
+    fn into(self) -> Result<ProcessingState<Script>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+/// A handle to a script.
+pub type ScriptHandle = Handle<Script>;
+
+/// A loaded script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    /// The Lua source of the script.
+    pub source: String,
+}
+
+impl Asset for Script {
+    const NAME: &'static str = "scripting::Script";
+    type Data = Script;
+    type HandleStorage = VecStorage<ScriptHandle>;
+}
+
+/// The scripts run by the `ScriptSystem`, in order.
+///
+/// Removing a script also removes the systems it registered.
+#[derive(Debug, Default)]
+pub struct Scripts {
+    scripts: Vec<(String, ScriptHandle)>,
+}
+
+impl Scripts {
+    /// Loads the script at `name` with the `LuaFormat` and adds it.
+    pub fn load<N: Into<String>>(
+        &mut self,
+        name: N,
+        loader: &Loader,
+        storage: &AssetStorage<Script>,
+    ) -> ScriptHandle {
+        let name = name.into();
+        let handle = loader.load(name.as_str(), LuaFormat, (), (), storage);
+        self.insert(name, handle.clone());
+        handle
+    }
+
+    /// Adds a loaded script. The name is used in the errors of the script.
+    pub fn insert<N: Into<String>>(&mut self, name: N, handle: ScriptHandle) {
+        self.scripts.push((name.into(), handle));
+    }
+
+    /// Removes a script.
+    pub fn remove(&mut self, handle: &ScriptHandle) {
+        self.scripts.retain(|(_, script)| script != handle);
+    }
+
+    /// Returns the names and handles of the scripts.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ScriptHandle)> {
+        self.scripts
+            .iter()
+            .map(|(name, handle)| (name.as_str(), handle))
+    }
+}
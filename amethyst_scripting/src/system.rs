@@ -0,0 +1,390 @@
+use log::{error, info};
+use rlua::{Context, Function, Lua, RegistryKey, Scope, Table, Value, Variadic};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    ecs::{
+        prelude::{
+            Entities, Entity, Join, Read, ReadExpect, ReadStorage, Resources, RunNow, SystemData,
+            Write,
+        },
+        storage::MaskedStorage,
+    },
+    Named, Time,
+};
+
+use crate::{
+    component::{Access, ComponentAccess, ScriptComponent},
+    script::{Script, ScriptHandle, Scripts},
+};
+
+/// A script of the `Scripts`, and the systems it registered.
+struct LoadedScript {
+    handle: ScriptHandle,
+    /// The source the systems were loaded from, to notice when the script is reloaded.
+    source: Option<String>,
+    systems: Vec<(String, RegistryKey)>,
+}
+
+/// Runs the systems registered by the `Scripts`, and loads the scripts again when they change.
+///
+/// Scripts reach the components registered with `with_component` through the `world` table
+/// passed to their systems:
+///
+/// * `world.get(entity, name)` returns the table of a component of the entity, or nil.
+/// * `world.set(entity, name, table)` updates a component of the entity from the table, and
+///   returns whether the entity has one.
+/// * `world.query(names...)` returns the entities with all the components.
+/// * `world.find(name)` returns the entity with the `Named` component of that name, or nil.
+/// * `world.time` is the time since the start of the game in seconds.
+///
+/// A system raising an error is stopped until its script is reloaded. A script failing to load
+/// keeps the systems of its previous version.
+///
+/// The Lua state can't be shared between threads, so the system is added as a thread local
+/// system by the `ScriptingBundle`, after the other systems.
+pub struct ScriptSystem {
+    lua: Lua,
+    components: Vec<Box<dyn ComponentAccess>>,
+    scripts: Vec<String>,
+    loaded: Vec<LoadedScript>,
+}
+
+impl Default for ScriptSystem {
+    fn default() -> Self {
+        ScriptSystem::new()
+    }
+}
+
+impl ScriptSystem {
+    /// Creates a system without any components for scripts.
+    pub fn new() -> Self {
+        ScriptSystem {
+            lua: Lua::new(),
+            components: Vec::new(),
+            scripts: Vec::new(),
+            loaded: Vec::new(),
+        }
+    }
+
+    /// Lets scripts read and write the component.
+    pub fn with_component<C: ScriptComponent>(mut self) -> Self {
+        self.components.push(Box::new(Access::<C>::new()));
+        self
+    }
+
+    /// Loads the script at `name` into the `Scripts` on setup.
+    pub fn with_script<N: Into<String>>(mut self, name: N) -> Self {
+        self.scripts.push(name.into());
+        self
+    }
+
+    /// Follows the `Scripts`, loading the systems of new and changed scripts.
+    fn update_scripts(&mut self, scripts: &Scripts, storage: &AssetStorage<Script>) {
+        let mut previous = std::mem::replace(&mut self.loaded, Vec::new());
+        let mut expired = false;
+        for (name, handle) in scripts.iter() {
+            let mut loaded = match previous.iter().position(|loaded| loaded.handle == *handle) {
+                Some(index) => previous.remove(index),
+                None => LoadedScript {
+                    handle: handle.clone(),
+                    source: None,
+                    systems: Vec::new(),
+                },
+            };
+            if let Some(script) = storage.get(handle) {
+                if loaded.source.as_ref() != Some(&script.source) {
+                    match load_systems(&self.lua, name, &script.source) {
+                        Ok(systems) => {
+                            info!("Loaded {} systems from script {:?}", systems.len(), name);
+                            expired |= !loaded.systems.is_empty();
+                            loaded.systems = systems;
+                        }
+                        Err(err) => error!("Failed to load script {:?}: {}", name, err),
+                    }
+                    loaded.source = Some(script.source.clone());
+                }
+            }
+            self.loaded.push(loaded);
+        }
+        // Frees the functions of the replaced and removed systems.
+        expired |= !previous.is_empty();
+        drop(previous);
+        if expired {
+            self.lua.context(|lua| lua.expire_registry_values());
+        }
+    }
+
+    /// Calls the systems of the scripts, stopping those raising an error.
+    fn run_systems(&mut self, res: &Resources) {
+        let (delta_seconds, time) = {
+            let time = res.fetch::<Time>();
+            (time.delta_seconds(), time.absolute_time_seconds())
+        };
+        let components = &self.components;
+        let loaded = &mut self.loaded;
+        self.lua.context(|lua| {
+            let failed = lua.scope(|scope| -> rlua::Result<_> {
+                let world = world_table(lua, scope, res, components)?;
+                world.set("time", time)?;
+                let mut failed = Vec::new();
+                for (i, script) in loaded.iter().enumerate() {
+                    for (j, (_, key)) in script.systems.iter().enumerate() {
+                        let system: Function<'_> = lua.registry_value(key)?;
+                        if let Err(err) = system.call::<_, ()>((world.clone(), delta_seconds)) {
+                            failed.push((i, j, err));
+                        }
+                    }
+                }
+                Ok(failed)
+            });
+            match failed {
+                Ok(failed) => {
+                    for (i, j, err) in failed.into_iter().rev() {
+                        let (name, key) = loaded[i].systems.remove(j);
+                        error!("Script system {:?} failed and was stopped: {}", name, err);
+                        // The key was the only reference to the function.
+                        let _ = lua.remove_registry_value(key);
+                    }
+                }
+                Err(err) => error!("Failed to run the script systems: {}", err),
+            }
+        });
+    }
+}
+
+/// Runs a script, returning the systems of the table it returns sorted by name.
+fn load_systems(lua: &Lua, name: &str, source: &str) -> rlua::Result<Vec<(String, RegistryKey)>> {
+    lua.context(|lua| {
+        let table = match lua.load(source).set_name(name)?.eval::<Value<'_>>()? {
+            Value::Table(table) => table,
+            Value::Nil => return Ok(Vec::new()),
+            _ => {
+                return Err(rlua::Error::RuntimeError(
+                    "A script has to return a table of systems, or nothing".into(),
+                ));
+            }
+        };
+        let mut systems = table
+            .pairs::<String, Function<'_>>()
+            .collect::<rlua::Result<Vec<_>>>()?;
+        systems.sort_by(|a, b| a.0.cmp(&b.0));
+        systems
+            .into_iter()
+            .map(|(name, system)| Ok((name, lua.create_registry_value(system)?)))
+            .collect()
+    })
+}
+
+/// Returns the entity with the index, if it is alive.
+fn entity(res: &Resources, id: u32) -> Option<Entity> {
+    let entities = Entities::fetch(res);
+    let entity = entities.entity(id);
+    if entities.is_alive(entity) {
+        Some(entity)
+    } else {
+        None
+    }
+}
+
+fn access<'a>(
+    components: &'a [Box<dyn ComponentAccess>],
+    name: &str,
+) -> rlua::Result<&'a dyn ComponentAccess> {
+    components
+        .iter()
+        .find(|access| access.name() == name)
+        .map(|access| &**access)
+        .ok_or_else(|| {
+            rlua::Error::RuntimeError(format!(
+                "No component named {:?} is registered for scripts",
+                name
+            ))
+        })
+}
+
+/// The `world` passed to the systems of scripts, which is only valid while they run.
+fn world_table<'lua, 'scope>(
+    lua: Context<'lua>,
+    scope: &Scope<'lua, 'scope>,
+    res: &'scope Resources,
+    components: &'scope [Box<dyn ComponentAccess>],
+) -> rlua::Result<Table<'lua>> {
+    let world = lua.create_table()?;
+    world.set(
+        "get",
+        scope.create_function(move |lua, (id, name): (u32, String)| {
+            let access = access(components, &name)?;
+            match entity(res, id) {
+                Some(entity) => access.get(res, entity, lua),
+                None => Ok(Value::Nil),
+            }
+        })?,
+    )?;
+    world.set(
+        "set",
+        scope.create_function(move |_, (id, name, table): (u32, String, Table<'_>)| {
+            let access = access(components, &name)?;
+            match entity(res, id) {
+                Some(entity) => access.set(res, entity, &table),
+                None => Ok(false),
+            }
+        })?,
+    )?;
+    world.set(
+        "query",
+        scope.create_function(move |lua, names: Variadic<String>| {
+            let accesses = names
+                .iter()
+                .map(|name| access(components, name))
+                .collect::<rlua::Result<Vec<_>>>()?;
+            let mut ids = match accesses.split_first() {
+                Some((first, _)) => first.ids(res),
+                None => Vec::new(),
+            };
+            for access in accesses.iter().skip(1) {
+                ids.retain(|&id| access.has(res, id));
+            }
+            ids.retain(|&id| entity(res, id).is_some());
+            lua.create_sequence_from(ids)
+        })?,
+    )?;
+    world.set(
+        "find",
+        scope.create_function(move |_, name: String| {
+            if !res.has_value::<MaskedStorage<Named>>() {
+                return Ok(None);
+            }
+            let (entities, names) = <(Entities<'_>, ReadStorage<'_, Named>)>::fetch(res);
+            Ok((&*entities, &names)
+                .join()
+                .find(|(_, named)| named.name == name)
+                .map(|(entity, _)| entity.id()))
+        })?,
+    )?;
+    Ok(world)
+}
+
+impl<'a> RunNow<'a> for ScriptSystem {
+    fn run_now(&mut self, res: &'a Resources) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("script_system");
+
+        {
+            let (scripts, storage) =
+                <(Read<'_, Scripts>, Read<'_, AssetStorage<Script>>)>::fetch(res);
+            self.update_scripts(&scripts, &storage);
+        }
+        self.run_systems(res);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        <(
+            Write<'_, Scripts>,
+            Read<'_, AssetStorage<Script>>,
+            Read<'_, Time>,
+        )>::setup(res);
+        for access in &self.components {
+            access.setup(res);
+        }
+
+        let (loader, storage, mut scripts) = <(
+            ReadExpect<'_, Loader>,
+            Read<'_, AssetStorage<Script>>,
+            Write<'_, Scripts>,
+        )>::fetch(res);
+        for name in self.scripts.drain(..) {
+            scripts.load(name, &loader, &storage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_core::{
+        ecs::prelude::{Builder, World},
+        Transform,
+    };
+
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        return {
+            a_move = function(world, dt)
+                local player = world.find("player")
+                local transform = world.get(player, "Transform")
+                transform.translation.x = #world.query("Transform") + 0.5
+                world.set(player, "Transform", transform)
+            end,
+            b_broken = function(world, dt)
+                world.get(0, "Missing")
+            end,
+        }
+    "#;
+
+    #[test]
+    fn scripts_write_components() {
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let mut world = World::new();
+        world.add_resource(Loader::new(".", pool.clone()));
+        world.add_resource(Time::default());
+        let mut system = ScriptSystem::new()
+            .with_component::<Transform<f32>>()
+            .with_component::<Named>();
+        system.setup(&mut world.res);
+
+        let player = world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .with(Named::new("player"))
+            .build();
+        world
+            .create_entity()
+            .with(Transform::<f32>::default())
+            .build();
+
+        let script = Script {
+            source: SCRIPT.into(),
+        };
+        let handle = world.read_resource::<Loader>().load_from_data(
+            script,
+            (),
+            &world.read_resource::<AssetStorage<Script>>(),
+        );
+        world
+            .write_resource::<Scripts>()
+            .insert("test", handle.clone());
+        world
+            .write_resource::<AssetStorage<Script>>()
+            .process(Into::into, 0, &pool, None);
+
+        system.run_now(&world.res);
+        assert_eq!(
+            world
+                .read_storage::<Transform<f32>>()
+                .get(player)
+                .unwrap()
+                .translation()
+                .x,
+            2.5
+        );
+        // The failing system is stopped.
+        assert_eq!(system.loaded[0].systems.len(), 1);
+
+        // A changed script replaces its systems.
+        world
+            .write_resource::<AssetStorage<Script>>()
+            .get_mut(&handle)
+            .unwrap()
+            .source = "return {}".into();
+        system.run_now(&world.res);
+        assert!(system.loaded[0].systems.is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 use std::marker::PhantomData;
 
-use amethyst::{core::bundle::SystemBundle, ecs::prelude::*, error::Error};
+use amethyst::{
+    core::bundle::{BundleBuilder, SystemBundle},
+    ecs::prelude::*,
+    error::Error,
+};
 
 use derive_new::new;
 
@@ -25,7 +29,7 @@ impl<'a, 'b, Sys> SystemBundle<'a, 'b> for SystemInjectionBundle<'a, Sys>
 where
     Sys: for<'s> System<'s> + Send + 'a,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             self.system,
             &self.system_name,
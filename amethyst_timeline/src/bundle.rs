@@ -0,0 +1,42 @@
+use amethyst_assets::Processor;
+use amethyst_core::bundle::{BundleBuilder, SystemBundle};
+use amethyst_error::Error;
+
+use crate::{system::TimelineSystem, timeline::Timeline};
+
+/// The bundle playing `Timeline`s.
+///
+/// The animations of the timelines are played by the `AnimationBundle::<usize, Transform<f32>>`,
+/// which has to be added as well.
+///
+/// # Systems
+///
+/// This bundle adds the following systems:
+///
+/// * `Processor<Timeline>`, named `"timeline_processor"`
+/// * `TimelineSystem`, named `"timeline_system"`
+#[derive(Default)]
+pub struct TimelineBundle<'a> {
+    dep: &'a [&'a str],
+}
+
+impl<'a> TimelineBundle<'a> {
+    /// Creates a new `TimelineBundle`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set dependencies for the `TimelineSystem`.
+    pub fn with_dep(mut self, dep: &'a [&'a str]) -> Self {
+        self.dep = dep;
+        self
+    }
+}
+
+impl<'a, 'b, 'c> SystemBundle<'a, 'b> for TimelineBundle<'c> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        builder.add(Processor::<Timeline>::new(), "timeline_processor", &[]);
+        builder.add(TimelineSystem::new(), "timeline_system", self.dep);
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+//! Amethyst timeline crate.
+//!
+//! A `Timeline` is an asset with tracks of animation clips, camera cuts, sounds and events. A
+//! `TimelinePlayer` on an entity plays it, starting the animations of the entity and its named
+//! descendants, so cutscenes of imported glTF scenes can be authored in a file instead of a
+//! hand-written state machine.
+//!
+//! ```rust,ignore
+//! let handle = loader.load("cutscenes/intro.ron", RonFormat, (), (), &timelines);
+//! world
+//!     .write_storage::<TimelinePlayer>()
+//!     .insert(scene_root, TimelinePlayer::playing(handle))?;
+//! ```
+//!
+//! The game reacts to the markers of the timeline, and to its end, by reading the
+//! `EventChannel<TimelineEvent>`.
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use self::{
+    bundle::TimelineBundle,
+    player::{PlaybackState, TimelineEvent, TimelinePlayer},
+    system::TimelineSystem,
+    timeline::{AnimationClip, AudioCue, CameraCut, Marker, Timeline, TimelineHandle, Track},
+};
+
+mod bundle;
+mod player;
+mod system;
+mod timeline;
@@ -0,0 +1,134 @@
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage, Entity};
+
+use crate::timeline::TimelineHandle;
+
+/// The playback state of a `TimelinePlayer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaybackState {
+    /// At the start of the timeline, without anything applied.
+    Stopped,
+    /// Advancing the timeline.
+    Playing,
+    /// Holding at the current time, with the animations of the timeline paused.
+    Paused,
+    /// Reached the end of a timeline which doesn't loop. The animations keep their last pose
+    /// until the player is stopped.
+    Finished,
+}
+
+/// Plays a `Timeline`, advanced by the `TimelineSystem`.
+///
+/// Tracks are applied relative to the entity of the player, so for a cutscene of a glTF scene
+/// the player is added to the root entity of the scene.
+#[derive(Clone, Debug)]
+pub struct TimelinePlayer {
+    /// The timeline to play.
+    pub timeline: TimelineHandle,
+    /// How fast the timeline plays, 1.0 is its normal speed.
+    pub speed: f32,
+    /// Starts the timeline over when it reaches its end.
+    pub looping: bool,
+    pub(crate) state: PlaybackState,
+    pub(crate) time: f32,
+    /// Set when the timeline has to start over, for the system to abort the started clips.
+    pub(crate) reset: bool,
+    /// The animations started by the player.
+    pub(crate) clips: Vec<ActiveClip>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ActiveClip {
+    pub(crate) entity: Entity,
+    pub(crate) animation: usize,
+    pub(crate) rate: f32,
+    /// The time the clip is aborted at.
+    pub(crate) end: Option<f32>,
+    /// Set until the clip could be added to the `AnimationControlSet`.
+    pub(crate) pending: bool,
+}
+
+impl TimelinePlayer {
+    /// Creates a stopped player for the timeline.
+    pub fn new(timeline: TimelineHandle) -> Self {
+        TimelinePlayer {
+            timeline,
+            speed: 1.0,
+            looping: false,
+            state: PlaybackState::Stopped,
+            time: 0.0,
+            reset: false,
+            clips: Vec::new(),
+        }
+    }
+
+    /// Sets the speed of the player.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Makes the player start the timeline over when it reaches its end.
+    pub fn with_looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Creates a player which starts playing the timeline right away.
+    pub fn playing(timeline: TimelineHandle) -> Self {
+        let mut player = TimelinePlayer::new(timeline);
+        player.play();
+        player
+    }
+
+    /// Plays the timeline, resuming it if it is paused and starting it over if it finished.
+    pub fn play(&mut self) {
+        if self.state == PlaybackState::Finished {
+            self.reset = true;
+        }
+        self.state = PlaybackState::Playing;
+    }
+
+    /// Pauses the timeline.
+    pub fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+        }
+    }
+
+    /// Stops the timeline, aborting its animations. Playing it again starts it from the start.
+    pub fn stop(&mut self) {
+        self.state = PlaybackState::Stopped;
+        self.reset = true;
+    }
+
+    /// Returns the playback state of the player.
+    pub fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    /// Returns the current time of the timeline in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+}
+
+impl Component for TimelinePlayer {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// An event sent by the `TimelineSystem` into the `EventChannel<TimelineEvent>` resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelineEvent {
+    /// A marker of an `Events` track was reached.
+    Marker {
+        /// The entity of the `TimelinePlayer`.
+        player: Entity,
+        /// The name of the marker.
+        name: String,
+    },
+    /// A timeline which doesn't loop reached its end.
+    Finished {
+        /// The entity of the `TimelinePlayer`.
+        player: Entity,
+    },
+}
@@ -0,0 +1,300 @@
+use std::collections::VecDeque;
+
+use fnv::FnvHashMap;
+use log::warn;
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_animation::{
+    get_animation_set, AnimationCommand, AnimationControlSet, AnimationSet, EndControl,
+};
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_audio::{output::Output, AudioMixer, Source, SourceHandle};
+use amethyst_core::{
+    ecs::{
+        prelude::{
+            Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
+        },
+        world::EntitiesRes,
+    },
+    shrev::EventChannel,
+    Named, ParentHierarchy, Time, Transform,
+};
+use amethyst_renderer::ActiveCamera;
+
+use crate::{
+    player::{ActiveClip, PlaybackState, TimelineEvent, TimelinePlayer},
+    timeline::{reached, Timeline, Track},
+};
+
+/// Advances the `TimelinePlayer`s and applies their tracks.
+///
+/// Animation tracks control the `AnimationControlSet<usize, Transform<f32>>` of their target,
+/// so the `AnimationBundle::<usize, Transform<f32>>` has to be added as well. The sounds of the
+/// timelines are loaded up front, and played on the `Output` if there is one.
+#[derive(Default)]
+pub struct TimelineSystem {
+    sources: FnvHashMap<String, SourceHandle>,
+}
+
+impl TimelineSystem {
+    /// Creates a new `TimelineSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// The entities the tracks of a player refer to by name.
+struct Targets<'s, 'a> {
+    entities: &'s EntitiesRes,
+    hierarchy: &'s ParentHierarchy,
+    names: &'s ReadStorage<'a, Named>,
+}
+
+impl<'s, 'a> Targets<'s, 'a> {
+    /// Finds the entity with the name, searching the player and its descendants before the rest
+    /// of the world.
+    fn find(&self, player: Entity, name: &str) -> Option<Entity> {
+        let named = |entity: Entity| {
+            self.names
+                .get(entity)
+                .map(|named| named.name == name)
+                .unwrap_or(false)
+        };
+        let mut queue = VecDeque::new();
+        queue.push_back(player);
+        while let Some(entity) = queue.pop_front() {
+            if named(entity) {
+                return Some(entity);
+            }
+            queue.extend(self.hierarchy.children(entity).iter().cloned());
+        }
+        (self.entities, self.names)
+            .join()
+            .find(|&(_, named)| named.name == name)
+            .map(|(entity, _)| entity)
+    }
+}
+
+impl<'a> System<'a> for TimelineSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Timeline>>,
+        Read<'a, AssetStorage<Source>>,
+        Option<Read<'a, Output>>,
+        Option<Read<'a, AudioMixer>>,
+        ReadExpect<'a, ParentHierarchy>,
+        ReadStorage<'a, Named>,
+        ReadStorage<'a, AnimationSet<usize, Transform<f32>>>,
+        WriteStorage<'a, AnimationControlSet<usize, Transform<f32>>>,
+        WriteStorage<'a, TimelinePlayer>,
+        Write<'a, ActiveCamera>,
+        Write<'a, EventChannel<TimelineEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            loader,
+            timelines,
+            sources,
+            output,
+            mixer,
+            hierarchy,
+            names,
+            animation_sets,
+            mut controls,
+            mut players,
+            mut active_camera,
+            mut events,
+        ): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("timeline_system");
+
+        let targets = Targets {
+            entities: &entities,
+            hierarchy: &hierarchy,
+            names: &names,
+        };
+
+        for (player_entity, player) in (&*entities, &mut players).join() {
+            if player.reset {
+                for clip in player.clips.drain(..) {
+                    if let Some(control_set) = controls.get_mut(clip.entity) {
+                        control_set.abort(clip.animation);
+                    }
+                }
+                player.time = 0.0;
+                player.reset = false;
+            }
+
+            let timeline = match timelines.get(&player.timeline) {
+                Some(timeline) => timeline,
+                None => continue,
+            };
+
+            for track in &timeline.tracks {
+                if let Track::Audio { ref cues } = *track {
+                    for cue in cues {
+                        if !self.sources.contains_key(&cue.file) {
+                            let source = loader.load(
+                                cue.file.as_str(),
+                                cue.format.clone(),
+                                (),
+                                (),
+                                &sources,
+                            );
+                            self.sources.insert(cue.file.clone(), source);
+                        }
+                    }
+                }
+            }
+
+            let paused = player.state != PlaybackState::Playing;
+            for clip in &player.clips {
+                if let Some(control_set) = controls.get_mut(clip.entity) {
+                    let rate = if paused {
+                        0.0
+                    } else {
+                        clip.rate * player.speed
+                    };
+                    control_set.set_rate(clip.animation, rate);
+                }
+            }
+            if paused {
+                continue;
+            }
+
+            let duration = timeline.duration();
+            let from = player.time;
+            let to = (from + time.delta_seconds() * player.speed).min(duration);
+            let last = to >= duration;
+
+            for track in &timeline.tracks {
+                match *track {
+                    Track::Animation {
+                        ref target,
+                        ref clips,
+                    } => {
+                        let entity = match *target {
+                            Some(ref name) => match targets.find(player_entity, name) {
+                                Some(entity) => entity,
+                                None => {
+                                    warn!("Timeline animation target {:?} not found", name);
+                                    continue;
+                                }
+                            },
+                            None => player_entity,
+                        };
+                        for clip in clips {
+                            if reached(clip.start, from, to, last) {
+                                player.clips.push(ActiveClip {
+                                    entity,
+                                    animation: clip.animation,
+                                    rate: clip.rate,
+                                    end: clip.duration.map(|duration| clip.start + duration),
+                                    pending: true,
+                                });
+                            }
+                        }
+                    }
+                    Track::Camera { ref cuts } => {
+                        let cut = cuts
+                            .iter()
+                            .filter(|cut| reached(cut.start, from, to, last))
+                            .last();
+                        if let Some(cut) = cut {
+                            match targets.find(player_entity, &cut.camera) {
+                                Some(camera) => active_camera.entity = Some(camera),
+                                None => warn!("Timeline camera {:?} not found", cut.camera),
+                            }
+                        }
+                    }
+                    Track::Audio { ref cues } => {
+                        let output = match output {
+                            Some(ref output) => output,
+                            None => continue,
+                        };
+                        for cue in cues.iter().filter(|cue| reached(cue.start, from, to, last)) {
+                            let source = self
+                                .sources
+                                .get(&cue.file)
+                                .and_then(|handle| sources.get(handle));
+                            match (source, &mixer) {
+                                (Some(source), Some(mixer)) => {
+                                    output.play_on_bus(source, cue.volume, cue.bus, mixer)
+                                }
+                                (Some(source), None) => output.play_once(source, cue.volume),
+                                (None, _) => warn!("Timeline sound {:?} is not loaded", cue.file),
+                            }
+                        }
+                    }
+                    Track::Events { ref markers } => {
+                        for marker in markers
+                            .iter()
+                            .filter(|marker| reached(marker.start, from, to, last))
+                        {
+                            events.single_write(TimelineEvent::Marker {
+                                player: player_entity,
+                                name: marker.name.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+
+            let mut clips = Vec::with_capacity(player.clips.len());
+            for mut clip in player.clips.drain(..) {
+                let control_set = match get_animation_set(&mut controls, clip.entity) {
+                    Some(control_set) => control_set,
+                    None => continue,
+                };
+                if clip.end.map(|end| end <= to).unwrap_or(false) {
+                    control_set.abort(clip.animation);
+                    continue;
+                }
+                if clip.pending {
+                    // The animation of an earlier clip with the same id has to finish aborting
+                    // before it can be replaced.
+                    if control_set.has_animation(clip.animation) {
+                        control_set.abort(clip.animation);
+                    } else {
+                        match animation_sets
+                            .get(clip.entity)
+                            .and_then(|set| set.animations.get(&clip.animation))
+                        {
+                            Some(animation) => control_set.add_animation(
+                                clip.animation,
+                                animation,
+                                EndControl::Stay,
+                                clip.rate * player.speed,
+                                AnimationCommand::Start,
+                            ),
+                            None => warn!("Timeline animation {} not found", clip.animation),
+                        }
+                        clip.pending = false;
+                    }
+                }
+                clips.push(clip);
+            }
+            player.clips = clips;
+
+            player.time = to;
+            if last {
+                if player.looping && duration > 0.0 {
+                    player.reset = true;
+                } else {
+                    player.state = PlaybackState::Finished;
+                    events.single_write(TimelineEvent::Finished {
+                        player: player_entity,
+                    });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, Handle, ProcessingState};
+use amethyst_audio::{AudioBus, AudioFormat};
+use amethyst_core::ecs::prelude::VecStorage;
+use amethyst_error::Error;
+
+/// A handle to a timeline.
+pub type TimelineHandle = Handle<Timeline>;
+
+/// An authored sequence of animations, camera cuts, sounds and events, played by a
+/// `TimelinePlayer`. Timelines are loaded from RON files with the `RonFormat`.
+///
+/// Tracks refer to entities by their `Named` component, searching the entity of the
+/// `TimelinePlayer` and its children first, so the same timeline can drive several instances of
+/// a glTF scene when the player is on the root entity of each.
+///
+/// ```ron
+/// (
+///     tracks: [
+///         Animation(clips: [(start: 0.0, animation: 0)]),
+///         Camera(cuts: [(start: 0.0, camera: "wide"), (start: 4.0, camera: "close")]),
+///         Audio(cues: [(start: 1.0, file: "audio/line.ogg", format: Ogg, bus: Voice)]),
+///         Events(markers: [(start: 2.5, name: "explosion")]),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Timeline {
+    /// The tracks, which all start at the start of the timeline.
+    pub tracks: Vec<Track>,
+    /// The length of the timeline in seconds, by default until the last key of its tracks.
+    pub length: Option<f32>,
+}
+
+impl Asset for Timeline {
+    const NAME: &'static str = "timeline::Timeline";
+    type Data = Timeline;
+    type HandleStorage = VecStorage<TimelineHandle>;
+}
+
+impl Into<Result<ProcessingState<Timeline>, Error>> for Timeline {
+    fn into(self) -> Result<ProcessingState<Timeline>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+impl Timeline {
+    /// Returns the length of the timeline in seconds.
+    pub fn duration(&self) -> f32 {
+        self.length
+            .unwrap_or_else(|| self.tracks.iter().map(Track::end).fold(0.0, f32::max))
+    }
+}
+
+/// A track of a `Timeline`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Track {
+    /// Plays animations of the `AnimationSet<usize, Transform<f32>>` of an entity, like the
+    /// animations of a glTF scene on its root entity.
+    Animation {
+        /// The entity with the animations, by default the entity of the `TimelinePlayer`.
+        #[serde(default)]
+        target: Option<String>,
+        /// The animations to play.
+        clips: Vec<AnimationClip>,
+    },
+    /// Switches the `ActiveCamera`.
+    Camera {
+        /// The cuts to the cameras.
+        cuts: Vec<CameraCut>,
+    },
+    /// Plays sounds.
+    Audio {
+        /// The sounds to play.
+        cues: Vec<AudioCue>,
+    },
+    /// Sends `TimelineEvent::Marker`s, for the game to react to.
+    Events {
+        /// The events to send.
+        markers: Vec<Marker>,
+    },
+}
+
+impl Track {
+    /// The time of the last key of the track.
+    fn end(&self) -> f32 {
+        match *self {
+            Track::Animation { ref clips, .. } => latest(
+                clips
+                    .iter()
+                    .map(|clip| clip.start + clip.duration.unwrap_or(0.0)),
+            ),
+            Track::Camera { ref cuts } => latest(cuts.iter().map(|cut| cut.start)),
+            Track::Audio { ref cues } => latest(cues.iter().map(|cue| cue.start)),
+            Track::Events { ref markers } => latest(markers.iter().map(|marker| marker.start)),
+        }
+    }
+}
+
+fn latest<I: Iterator<Item = f32>>(times: I) -> f32 {
+    times.fold(0.0, f32::max)
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Plays an animation of an `Animation` track.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AnimationClip {
+    /// The time the animation starts at.
+    pub start: f32,
+    /// The index of the animation in the `AnimationSet`. It is also the id of the animation in
+    /// the `AnimationControlSet` of the entity.
+    pub animation: usize,
+    /// Aborts the animation after this many seconds, by default it keeps its last pose until the
+    /// timeline is stopped.
+    #[serde(default)]
+    pub duration: Option<f32>,
+    /// The speed of the animation.
+    #[serde(default = "one")]
+    pub rate: f32,
+}
+
+/// Makes the camera with the given name the `ActiveCamera`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CameraCut {
+    /// The time of the cut.
+    pub start: f32,
+    /// The name of the camera entity.
+    pub camera: String,
+}
+
+/// Plays a sound once.
+///
+/// The sounds of a timeline are loaded as soon as it is given to a `TimelinePlayer`. A sound
+/// which is still loading when its cue is reached is skipped.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AudioCue {
+    /// The time the sound starts at.
+    pub start: f32,
+    /// The path of the sound.
+    pub file: String,
+    /// The format of the sound.
+    pub format: AudioFormat,
+    /// The volume of the sound, 1.0 is unchanged and 0.0 is silent.
+    #[serde(default = "one")]
+    pub volume: f32,
+    /// The bus the sound is mixed on, when there is an `AudioMixer`.
+    #[serde(default)]
+    pub bus: AudioBus,
+}
+
+/// Sends a `TimelineEvent::Marker`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Marker {
+    /// The time of the event.
+    pub start: f32,
+    /// The name of the event.
+    pub name: String,
+}
+
+/// Returns whether a key at `time` is reached while a timeline plays from `from` to `to`.
+///
+/// Keys are reached when the timeline passes them, so a key at the start of the timeline is
+/// reached by the first update. The end of the timeline is included when `last` is set.
+pub(crate) fn reached(time: f32, from: f32, to: f32, last: bool) -> bool {
+    time >= from && (time < to || (last && time <= to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timelines_end_at_their_last_key() {
+        let timeline: Timeline = ron::de::from_str(
+            r#"(
+                tracks: [
+                    Animation(clips: [(start: 1.0, animation: 0, duration: Some(3.0))]),
+                    Events(markers: [(start: 2.5, name: "explosion")]),
+                ],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(timeline.duration(), 4.0);
+        match timeline.tracks[0] {
+            Track::Animation {
+                ref target,
+                ref clips,
+            } => {
+                assert_eq!(*target, None);
+                assert_eq!(clips[0].rate, 1.0);
+            }
+            _ => panic!("Expected an animation track"),
+        }
+
+        assert!(reached(0.0, 0.0, 0.1, false));
+        assert!(!reached(0.1, 0.0, 0.1, false));
+        assert!(reached(0.1, 0.0, 0.1, true));
+        assert!(!reached(0.0, 0.05, 0.1, true));
+    }
+}
@@ -0,0 +1,442 @@
+//! Exposes the user interface to assistive technologies, and adapts it for players who need more
+//! contrast or larger text.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, Write,
+        WriteStorage,
+    },
+    Parent,
+};
+use amethyst_renderer::{Hidden, HiddenPropagate};
+
+use crate::{
+    CachedSelectionOrder, NumericValue, Selected, TextEditing, TextOutline, TextShadow, UiText,
+    UiTransform,
+};
+
+/// What kind of element an `Accessible` entity is, as announced by screen readers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccessibleRole {
+    /// A button, activated by clicking it.
+    Button,
+    /// Text which can't be edited.
+    Label,
+    /// An editable text field.
+    TextInput,
+    /// A field editing a number, like a `UiNumericInput`.
+    SpinButton,
+    /// An image or icon.
+    Image,
+    /// A container of other elements, like a menu or a panel.
+    Group,
+    /// Any other kind of element, named for the platform backend.
+    Custom(String),
+}
+
+/// Exposes an entity of the user interface in the `AccessibilityTree`.
+///
+/// The widget builders add this component with the role of the widget and its text as the name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Accessible {
+    /// The name announced for the element, like the text of a button.
+    pub name: String,
+    /// What kind of element it is.
+    pub role: AccessibleRole,
+    /// A longer explanation of the element, announced after its name.
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl Accessible {
+    /// Creates an accessible element without description.
+    pub fn new<S: Into<String>>(name: S, role: AccessibleRole) -> Self {
+        Accessible {
+            name: name.into(),
+            role,
+            description: None,
+        }
+    }
+
+    /// Set the description
+    pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl Component for Accessible {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// An `Accessible` element, as published in the `AccessibilityTree`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessibilityNode {
+    /// The entity of the element.
+    pub entity: Entity,
+    /// The closest ancestor which is also `Accessible`.
+    pub parent: Option<Entity>,
+    /// The name of the element.
+    pub name: String,
+    /// The role of the element.
+    pub role: AccessibleRole,
+    /// The description of the element.
+    pub description: Option<String>,
+    /// The value of text fields and numeric inputs. Password fields have no value.
+    pub value: Option<String>,
+    /// The tab order of the element if it is `Selectable`.
+    pub tab_order: Option<u32>,
+    /// If the element is selected.
+    pub focused: bool,
+    /// The left, bottom, width and height of the element on the screen, in pixels.
+    pub bounds: [f32; 4],
+}
+
+/// The visible `Accessible` elements of the user interface, updated every frame by the
+/// `AccessibilitySystem` for platform backends like screen readers to consume.
+///
+/// The nodes are ordered by tab order first, then in reading order from the top left.
+#[derive(Debug, Clone, Default)]
+pub struct AccessibilityTree {
+    nodes: Vec<AccessibilityNode>,
+    version: u64,
+}
+
+impl AccessibilityTree {
+    /// Returns the nodes.
+    pub fn nodes(&self) -> &[AccessibilityNode] {
+        &self.nodes
+    }
+
+    /// Returns the node of an entity, if it is accessible and visible.
+    pub fn node(&self, entity: Entity) -> Option<&AccessibilityNode> {
+        self.nodes.iter().find(|node| node.entity == entity)
+    }
+
+    /// Returns the nodes whose closest accessible ancestor is `parent`, or the roots for `None`.
+    pub fn children(
+        &self,
+        parent: Option<Entity>,
+    ) -> impl Iterator<Item = &AccessibilityNode> + '_ {
+        self.nodes.iter().filter(move |node| node.parent == parent)
+    }
+
+    /// Returns the selected node, or the first one if several are selected.
+    pub fn focused(&self) -> Option<&AccessibilityNode> {
+        self.nodes.iter().find(|node| node.focused)
+    }
+
+    /// Increases whenever the nodes change, so backends can skip unchanged frames.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+/// The colors forced on the text of the user interface when `UiAccessibility::high_contrast` is
+/// set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiContrastTheme {
+    /// The color of all text.
+    pub text_color: [f32; 4],
+    /// The outline of all text, keeping it readable over any background.
+    pub outline: Option<TextOutline>,
+    /// The color of selected text in text fields.
+    pub selected_text_color: [f32; 4],
+    /// The color behind selected text in text fields.
+    pub selected_background_color: [f32; 4],
+}
+
+impl Default for UiContrastTheme {
+    fn default() -> Self {
+        UiContrastTheme {
+            text_color: [1.0, 1.0, 1.0, 1.0],
+            outline: Some(TextOutline {
+                width: 2.0,
+                color: [0.0, 0.0, 0.0, 1.0],
+            }),
+            selected_text_color: [0.0, 0.0, 0.0, 1.0],
+            selected_background_color: [1.0, 1.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Accessibility settings of the user interface, as a resource.
+///
+/// These apply to all text, overriding the styles of the widgets until they are unset, and can
+/// be loaded from a configuration file to let players change them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiAccessibility {
+    /// Forces the colors of a high contrast theme on all text, without shadows or gradients.
+    pub high_contrast: Option<UiContrastTheme>,
+    /// Text smaller than this is drawn at this size.
+    pub min_font_size: Option<f32>,
+}
+
+/// The style of a text before `UiAccessibility` overrode it.
+struct TextStyle {
+    font_size: f32,
+    /// Only kept while the high contrast theme is applied, so that the colors set by the widgets
+    /// are left alone otherwise.
+    colors: Option<TextColors>,
+}
+
+struct TextColors {
+    color: [f32; 4],
+    shadow: Option<TextShadow>,
+    outline: Option<TextOutline>,
+    gradient: Option<[f32; 4]>,
+    selection: Option<([f32; 4], [f32; 4])>,
+}
+
+impl TextColors {
+    fn save(text: &UiText, edit: Option<&TextEditing>) -> Self {
+        TextColors {
+            color: text.color,
+            shadow: text.shadow.clone(),
+            outline: text.outline.clone(),
+            gradient: text.gradient,
+            selection: edit.map(|edit| (edit.selected_text_color, edit.selected_background_color)),
+        }
+    }
+
+    fn restore(self, text: &mut UiText, edit: Option<&mut TextEditing>) {
+        text.color = self.color;
+        text.shadow = self.shadow;
+        text.outline = self.outline;
+        text.gradient = self.gradient;
+        if let (Some(edit), Some((selected_text, background))) = (edit, self.selection) {
+            edit.selected_text_color = selected_text;
+            edit.selected_background_color = background;
+        }
+    }
+}
+
+/// Builds the `AccessibilityTree`, and applies the `UiAccessibility` settings.
+#[derive(Default)]
+pub struct AccessibilitySystem {
+    styles: HashMap<Entity, TextStyle>,
+}
+
+impl AccessibilitySystem {
+    /// Creates a new `AccessibilitySystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn apply_settings(
+        &mut self,
+        entities: &Entities<'_>,
+        settings: &UiAccessibility,
+        texts: &mut WriteStorage<'_, UiText>,
+        editing: &mut WriteStorage<'_, TextEditing>,
+    ) {
+        let styles = &mut self.styles;
+        if settings.high_contrast.is_none() && settings.min_font_size.is_none() {
+            for (entity, style) in styles.drain() {
+                if let Some(text) = texts.get_mut(entity) {
+                    text.font_size = style.font_size;
+                    if let Some(colors) = style.colors {
+                        colors.restore(text, editing.get_mut(entity));
+                    }
+                }
+            }
+            return;
+        }
+
+        styles.retain(|entity, _| entities.is_alive(*entity));
+        for (entity, text, mut edit) in (&**entities, &mut *texts, (&mut *editing).maybe()).join() {
+            let style = styles.entry(entity).or_insert_with(|| TextStyle {
+                font_size: text.font_size,
+                colors: None,
+            });
+
+            text.font_size = settings
+                .min_font_size
+                .map_or(style.font_size, |size| style.font_size.max(size));
+            match settings.high_contrast {
+                Some(ref theme) => {
+                    if style.colors.is_none() {
+                        style.colors = Some(TextColors::save(text, edit.as_ref().map(|e| &**e)));
+                    }
+                    text.color = theme.text_color;
+                    text.shadow = None;
+                    text.outline = theme.outline.clone();
+                    text.gradient = None;
+                    if let Some(edit) = edit.as_mut() {
+                        edit.selected_text_color = theme.selected_text_color;
+                        edit.selected_background_color = theme.selected_background_color;
+                    }
+                }
+                None => {
+                    if let Some(colors) = style.colors.take() {
+                        colors.restore(text, edit.as_mut().map(|e| &mut **e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> System<'a> for AccessibilitySystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, UiAccessibility>,
+        Read<'a, CachedSelectionOrder>,
+        ReadStorage<'a, Accessible>,
+        ReadStorage<'a, UiTransform>,
+        ReadStorage<'a, Parent>,
+        ReadStorage<'a, Selected>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, NumericValue>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, TextEditing>,
+        Write<'a, AccessibilityTree>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            settings,
+            selection_order,
+            accessibles,
+            transforms,
+            parents,
+            selected,
+            hidden,
+            hidden_propagate,
+            numeric_values,
+            mut texts,
+            mut editing,
+            mut tree,
+        ): Self::SystemData,
+    ) {
+        self.apply_settings(&entities, &settings, &mut texts, &mut editing);
+
+        let tab_order = selection_order
+            .cache
+            .iter()
+            .map(|&(order, entity)| (entity, order))
+            .collect::<HashMap<_, _>>();
+
+        let mut nodes = Vec::new();
+        for (entity, accessible, transform, _, _) in (
+            &*entities,
+            &accessibles,
+            &transforms,
+            !&hidden,
+            !&hidden_propagate,
+        )
+            .join()
+        {
+            let mut parent = parents.get(entity).map(|parent| parent.entity);
+            while let Some(ancestor) = parent {
+                if accessibles.contains(ancestor) {
+                    break;
+                }
+                parent = parents.get(ancestor).map(|parent| parent.entity);
+            }
+
+            let value = match (numeric_values.get(entity), texts.get(entity)) {
+                (Some(numeric), _) => Some(numeric.format()),
+                (None, Some(text)) if editing.contains(entity) && !text.password => {
+                    Some(text.text.clone())
+                }
+                _ => None,
+            };
+
+            nodes.push(AccessibilityNode {
+                entity,
+                parent,
+                name: accessible.name.clone(),
+                role: accessible.role.clone(),
+                description: accessible.description.clone(),
+                value,
+                tab_order: tab_order.get(&entity).cloned(),
+                focused: selected.contains(entity),
+                bounds: [
+                    transform.pixel_x - transform.pixel_width / 2.0,
+                    transform.pixel_y - transform.pixel_height / 2.0,
+                    transform.pixel_width,
+                    transform.pixel_height,
+                ],
+            });
+        }
+        nodes.sort_by(reading_order);
+
+        if nodes != tree.nodes {
+            tree.nodes = nodes;
+            tree.version += 1;
+        }
+    }
+}
+
+/// Orders the selectable nodes by tab order first, then the others from top to bottom and left
+/// to right.
+fn reading_order(a: &AccessibilityNode, b: &AccessibilityNode) -> Ordering {
+    match (a.tab_order, b.tab_order) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => {
+            let top = |node: &AccessibilityNode| node.bounds[1] + node.bounds[3];
+            top(b)
+                .partial_cmp(&top(a))
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    a.bounds[0]
+                        .partial_cmp(&b.bounds[0])
+                        .unwrap_or(Ordering::Equal)
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amethyst_core::ecs::prelude::{Builder, World};
+
+    fn node(entity: Entity, tab_order: Option<u32>, bounds: [f32; 4]) -> AccessibilityNode {
+        AccessibilityNode {
+            entity,
+            parent: None,
+            name: String::new(),
+            role: AccessibleRole::Label,
+            description: None,
+            value: None,
+            tab_order,
+            focused: false,
+            bounds,
+        }
+    }
+
+    #[test]
+    fn sorts_nodes_in_reading_order() {
+        let mut world = World::new();
+        let entities = (0..4)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let mut nodes = vec![
+            node(entities[0], None, [100.0, 0.0, 10.0, 10.0]),
+            node(entities[1], None, [0.0, 100.0, 10.0, 10.0]),
+            node(entities[2], Some(2), [0.0, 0.0, 10.0, 10.0]),
+            node(entities[3], None, [0.0, 0.0, 10.0, 10.0]),
+        ];
+        nodes.sort_by(reading_order);
+
+        let order = nodes.iter().map(|node| node.entity).collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![entities[2], entities[1], entities[3], entities[0]]
+        );
+    }
+}
@@ -6,15 +6,16 @@ use derive_new::new;
 
 use amethyst_assets::Processor;
 use amethyst_audio::AudioFormat;
-use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
+use amethyst_core::bundle::{BundleBuilder, SystemBundle};
 use amethyst_error::Error;
 use amethyst_renderer::{BlinkSystem, TextureFormat};
 
 use crate::{
-    CacheSelectionOrderSystem, FontAsset, FontFormat, NoCustomUi, ResizeSystem,
-    SelectionKeyboardSystem, SelectionMouseSystem, TextEditingInputSystem, TextEditingMouseSystem,
-    ToNativeWidget, UiButtonActionRetriggerSystem, UiButtonSystem, UiLoaderSystem, UiMouseSystem,
-    UiSoundRetriggerSystem, UiSoundSystem, UiTransformSystem, WidgetId,
+    AccessibilitySystem, CacheSelectionOrderSystem, FontAsset, FontFormat, NoCustomUi,
+    NumericInputSystem, ResizeSystem, SelectionKeyboardSystem, SelectionMouseSystem,
+    TextEditingInputSystem, TextEditingMouseSystem, ToNativeWidget, UiButtonActionRetriggerSystem,
+    UiButtonSystem, UiCursorSystem, UiLoaderSystem, UiMouseSystem, UiSoundRetriggerSystem,
+    UiSoundSystem, UiSoundThemeSystem, UiTransformSystem, UiTransitionSystem, WidgetId,
 };
 
 /// UI bundle
@@ -37,7 +38,7 @@ where
     W: WidgetId,
     G: Send + Sync + PartialEq + 'static,
 {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(
             UiLoaderSystem::<
                 AudioFormat,
@@ -49,10 +50,11 @@ where
             "ui_loader",
             &[],
         );
+        builder.add(UiTransitionSystem::new(), "ui_transition", &["ui_loader"]);
         builder.add(
             UiTransformSystem::default(),
             "ui_transform",
-            &["transform_system"],
+            &["transform_system", "ui_transition"],
         );
         builder.add(
             Processor::<FontAsset>::new(),
@@ -98,12 +100,42 @@ where
             &["ui_mouse_system"],
         );
 
+        builder.add(
+            NumericInputSystem::<A, B>::new(),
+            "ui_numeric_input_system",
+            // Committed text is parsed after the text editing systems wrote it.
+            &[
+                "ui_mouse_system",
+                "ui_text_editing_mouse_system",
+                "ui_text_editing_input_system",
+            ],
+        );
         builder.add(
             UiButtonActionRetriggerSystem::new(),
             "ui_button_action_retrigger_system",
             &["ui_button_system"],
         );
         builder.add(UiSoundSystem::new(), "ui_sound_system", &[]);
+        builder.add(
+            UiCursorSystem::new(),
+            "ui_cursor_system",
+            &["ui_mouse_system"],
+        );
+        builder.add(
+            AccessibilitySystem::new(),
+            "ui_accessibility_system",
+            // The colors and text set by the widgets are overridden and exposed afterwards.
+            &[
+                "ui_transform",
+                "ui_button_system",
+                "ui_numeric_input_system",
+            ],
+        );
+        builder.add(
+            UiSoundThemeSystem::new(),
+            "ui_sound_theme_system",
+            &["ui_mouse_system", "ui_numeric_input_system"],
+        );
         builder.add(
             UiSoundRetriggerSystem::new(),
             "ui_sound_retrigger_system",
@@ -12,8 +12,8 @@ use amethyst_renderer::{Texture, TextureHandle};
 
 use crate::{
     font::default::get_default_font,
-    Anchor, FontAsset, FontHandle, Interactable, Selectable, Stretch, UiButton, UiButtonAction,
-    UiButtonActionRetrigger,
+    Accessible, AccessibleRole, Anchor, FontAsset, FontHandle, Interactable, Selectable, Stretch,
+    UiButton, UiButtonAction, UiButtonActionRetrigger,
     UiButtonActionType::{self, *},
     UiPlaySoundAction, UiSoundRetrigger, UiText, UiTransform, WidgetId, Widgets,
 };
@@ -43,6 +43,7 @@ pub struct UiButtonBuilderResources<'a, G: PartialEq + Send + Sync + 'static, I:
     sound_retrigger: WriteStorage<'a, UiSoundRetrigger>,
     button_action_retrigger: WriteStorage<'a, UiButtonActionRetrigger>,
     selectables: WriteStorage<'a, Selectable<G>>,
+    accessible: WriteStorage<'a, Accessible>,
 }
 
 /// Convenience structure for building a button
@@ -239,18 +240,21 @@ impl<'a, G: PartialEq + Send + Sync + 'static, I: WidgetId> UiButtonBuilder<G, I
     }
 
     /// Sound emitted when this button is hovered over
+    /// instead of `UiSoundTheme::hover`
     pub fn with_hover_sound(mut self, sound: SourceHandle) -> Self {
         self.on_hover_sound = Some(UiPlaySoundAction(sound));
         self
     }
 
     /// Sound emitted when this button is pressed
+    /// instead of `UiSoundTheme::press`
     pub fn with_press_sound(mut self, sound: SourceHandle) -> Self {
         self.on_click_start_sound = Some(UiPlaySoundAction(sound));
         self
     }
 
     /// Sound emitted when this button is released
+    /// instead of `UiSoundTheme::release`
     pub fn with_release_sound(mut self, sound: SourceHandle) -> Self {
         self.on_click_stop_sound = Some(UiPlaySoundAction(sound));
         self
@@ -377,6 +381,12 @@ impl<'a, G: PartialEq + Send + Sync + 'static, I: WidgetId> UiButtonBuilder<G, I
         let font_handle = self
             .font
             .unwrap_or_else(|| get_default_font(&res.loader, &res.font_asset));
+        res.accessible
+            .insert(
+                image_entity,
+                Accessible::new(self.text.clone(), AccessibleRole::Button),
+            )
+            .expect("Unreachable: Inserting newly created entity");
         res.text
             .insert(
                 text_entity,
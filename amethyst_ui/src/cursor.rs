@@ -0,0 +1,104 @@
+use amethyst_core::{
+    ecs::{
+        prelude::{Component, DenseVecStorage, Entity},
+        Read, ReadStorage, Resources, System, SystemData, Write,
+    },
+    shrev::{EventChannel, ReaderId},
+};
+use amethyst_renderer::CursorState;
+use winit::MouseCursor;
+
+use crate::event::{UiEvent, UiEventType::*};
+
+/// Attach this to an interactable ui entity to change the cursor icon while the cursor hovers
+/// over it or clicks it.
+///
+/// Only system cursor icons are supported by the window backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiCursor {
+    /// The icon shown while the cursor hovers over the entity.
+    pub hovered: MouseCursor,
+    /// The icon shown while the entity is being clicked.
+    pub pressed: MouseCursor,
+}
+
+impl UiCursor {
+    /// Creates a component showing the same icon while hovering and clicking.
+    pub fn new(icon: MouseCursor) -> Self {
+        UiCursor {
+            hovered: icon,
+            pressed: icon,
+        }
+    }
+}
+
+impl Component for UiCursor {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Sets the icon of the `CursorState` from the `UiCursor` of the hovered or clicked entity.
+///
+/// The icon is only written when the hovered or clicked entity changes, so icons set by other
+/// systems are kept until then. Leaving an entity with a `UiCursor` restores the default icon.
+#[derive(Default)]
+pub struct UiCursorSystem {
+    event_reader: Option<ReaderId<UiEvent>>,
+    hovered: Option<Entity>,
+    pressed: Option<Entity>,
+    icon: Option<MouseCursor>,
+}
+
+impl UiCursorSystem {
+    /// Constructs a default `UiCursorSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for UiCursorSystem {
+    type SystemData = (
+        Read<'a, EventChannel<UiEvent>>,
+        ReadStorage<'a, UiCursor>,
+        Write<'a, CursorState>,
+    );
+
+    fn run(&mut self, (events, cursors, mut state): Self::SystemData) {
+        let mut changed = false;
+        for event in events.read(
+            self.event_reader
+                .as_mut()
+                .expect("`UiCursorSystem::setup` was not called before `UiCursorSystem::run`"),
+        ) {
+            match event.event_type {
+                HoverStart => self.hovered = Some(event.target),
+                HoverStop if self.hovered == Some(event.target) => self.hovered = None,
+                ClickStart => self.pressed = Some(event.target),
+                ClickStop if self.pressed == Some(event.target) => self.pressed = None,
+                _ => continue,
+            }
+            changed = true;
+        }
+        if !changed {
+            return;
+        }
+
+        let icon = self
+            .pressed
+            .and_then(|e| cursors.get(e))
+            .map(|cursor| cursor.pressed)
+            .or_else(|| {
+                self.hovered
+                    .and_then(|e| cursors.get(e))
+                    .map(|cursor| cursor.hovered)
+            });
+        if icon != self.icon {
+            state.icon = icon.unwrap_or_default();
+            self.icon = icon;
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+}
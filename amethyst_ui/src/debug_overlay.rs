@@ -0,0 +1,272 @@
+use std::{collections::VecDeque, fmt::Write as _, hash::Hash, marker::PhantomData};
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    ecs::prelude::{Entities, Entity, Join, Read, ReadExpect, System, WriteStorage},
+    timing::duration_to_secs,
+};
+use amethyst_error::Error;
+use amethyst_input::InputHandler;
+use amethyst_renderer::{Hidden, RenderStats, Rgba, Texture, TextureHandle};
+use amethyst_utils::fps_counter::FPSCounter;
+use winit::VirtualKeyCode;
+
+use crate::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform};
+
+const WIDTH: f32 = 240.0;
+const TEXT_HEIGHT: f32 = 80.0;
+const GRAPH_HEIGHT: f32 = 50.0;
+const BAR_WIDTH: f32 = 3.0;
+const BARS: usize = 80;
+const MARGIN: f32 = 10.0;
+const FONT_SIZE: f32 = 14.0;
+/// The frame time filling the whole graph, in seconds.
+const GRAPH_MAX: f32 = 1.0 / 30.0;
+
+/// Shows the frames per second, a graph of the recent frame times, the number of entities and the
+/// `RenderStats` in the top left corner of the screen.
+///
+/// The frame times are read from the `FPSCounter`, which is updated by the `FPSCounterSystem`.
+///
+/// The overlay is made of ui entities, which are created on the first run. Pressing the toggle key
+/// hides or shows them.
+pub struct DebugOverlaySystem<A, B> {
+    toggle_key: VirtualKeyCode,
+    visible: bool,
+    key_down: bool,
+    frame_times: VecDeque<f32>,
+    text: Option<Entity>,
+    bars: Vec<Entity>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> DebugOverlaySystem<A, B> {
+    /// Creates the overlay, toggled with `toggle_key`.
+    pub fn new(toggle_key: VirtualKeyCode, visible: bool) -> Self {
+        DebugOverlaySystem {
+            toggle_key,
+            visible,
+            key_down: false,
+            frame_times: VecDeque::with_capacity(BARS),
+            text: None,
+            bars: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for DebugOverlaySystem<A, B>
+where
+    A: Send + Sync + Hash + Eq + Clone + 'static,
+    B: Send + Sync + Hash + Eq + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, FPSCounter>,
+        Option<Read<'a, RenderStats>>,
+        Read<'a, InputHandler<A, B>>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<FontAsset>>,
+        Read<'a, AssetStorage<Texture>>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, TextureHandle>,
+        WriteStorage<'a, Rgba>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            counter,
+            stats,
+            input,
+            loader,
+            fonts,
+            texture_storage,
+            mut transforms,
+            mut texts,
+            mut textures,
+            mut colors,
+            mut hidden,
+        ): Self::SystemData,
+    ) {
+        let key_down = input.key_is_down(self.toggle_key);
+        if key_down && !self.key_down {
+            self.visible = !self.visible;
+        }
+        self.key_down = key_down;
+
+        let frame_fps = counter.frame_fps();
+        if frame_fps > 0.0 {
+            if self.frame_times.len() == BARS {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(1.0 / frame_fps);
+        }
+
+        let alive = self.text.map_or(false, |e| entities.is_alive(e));
+        if !alive {
+            let text_entity = entities.create();
+            let transform = UiTransform::new(
+                "debug_overlay_text".to_string(),
+                Anchor::TopLeft,
+                MARGIN + WIDTH / 2.0,
+                -MARGIN - TEXT_HEIGHT / 2.0,
+                100.0,
+                WIDTH,
+                TEXT_HEIGHT,
+            );
+            let mut text = UiText::new(
+                get_default_font(&loader, &fonts),
+                String::new(),
+                [1.0, 1.0, 1.0, 1.0],
+                FONT_SIZE,
+            );
+            text.line_mode = LineMode::Wrap;
+            text.align = Anchor::TopLeft;
+            transforms
+                .insert(text_entity, transform)
+                .expect("Unreachable: Entity was just created");
+            texts
+                .insert(text_entity, text)
+                .expect("Unreachable: Entity was just created");
+            self.text = Some(text_entity);
+
+            let white = loader.load_from_data([1.0, 1.0, 1.0, 1.0].into(), (), &texture_storage);
+            for bar in self.bars.drain(..) {
+                let _ = entities.delete(bar);
+            }
+            for i in 0..BARS {
+                let bar = entities.create();
+                let transform = UiTransform::new(
+                    format!("debug_overlay_bar_{}", i),
+                    Anchor::TopLeft,
+                    MARGIN + (i as f32 + 0.5) * BAR_WIDTH,
+                    0.0,
+                    100.0,
+                    BAR_WIDTH,
+                    0.0,
+                )
+                .as_transparent();
+                transforms
+                    .insert(bar, transform)
+                    .expect("Unreachable: Entity was just created");
+                textures
+                    .insert(bar, white.clone())
+                    .expect("Unreachable: Entity was just created");
+                self.bars.push(bar);
+            }
+        }
+
+        let overlay = self.text.iter().chain(self.bars.iter());
+        if !self.visible {
+            for &entity in overlay {
+                if !hidden.contains(entity) {
+                    hidden
+                        .insert(entity, Hidden)
+                        .expect("Unreachable: Entity is alive");
+                }
+            }
+            return;
+        }
+        for &entity in overlay {
+            hidden.remove(entity);
+        }
+
+        let bottom = -MARGIN - TEXT_HEIGHT - GRAPH_HEIGHT;
+        for (&bar, &frame_time) in self.bars.iter().zip(self.frame_times.iter()) {
+            let height = (frame_time / GRAPH_MAX).min(1.0) * GRAPH_HEIGHT;
+            if let Some(transform) = transforms.get_mut(bar) {
+                transform.height = height;
+                transform.local_y = bottom + height / 2.0;
+            }
+            let color = if frame_time <= 1.0 / 60.0 {
+                Rgba::GREEN
+            } else if frame_time <= GRAPH_MAX {
+                Rgba(1.0, 1.0, 0.0, 1.0)
+            } else {
+                Rgba::RED
+            };
+            colors
+                .insert(bar, color)
+                .expect("Unreachable: Entity is alive");
+        }
+
+        if let Some(text) = self.text.and_then(|e| texts.get_mut(e)) {
+            let fps = counter.sampled_fps();
+            text.text.clear();
+            if fps > 0.0 {
+                let _ = writeln!(text.text, "{:.0} fps ({:.2} ms)", fps, 1000.0 / fps);
+            }
+            let _ = writeln!(text.text, "entities: {}", (&*entities).join().count());
+            if let Some(stats) = stats {
+                let _ = writeln!(
+                    text.text,
+                    "draw calls: {}\nrender: {:.2} ms",
+                    stats.draw_calls,
+                    duration_to_secs(stats.render_time) * 1000.0
+                );
+            }
+        }
+    }
+}
+
+/// Adds the `DebugOverlaySystem`, with name `debug_overlay_system`.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+/// The overlay is toggled with F3 by default.
+///
+/// The system runs after the `fps_counter_system`, so the `FPSCounterBundle` has to be added
+/// before this bundle.
+pub struct DebugOverlayBundle<A = String, B = String> {
+    toggle_key: VirtualKeyCode,
+    visible: bool,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> DebugOverlayBundle<A, B> {
+    /// Creates a bundle showing the overlay, toggled with F3.
+    pub fn new() -> Self {
+        DebugOverlayBundle {
+            toggle_key: VirtualKeyCode::F3,
+            visible: true,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Toggles the overlay with a different key.
+    pub fn with_toggle_key(mut self, key: VirtualKeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    /// Sets whether the overlay is shown before the toggle key is pressed.
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+}
+
+impl<A, B> Default for DebugOverlayBundle<A, B> {
+    fn default() -> Self {
+        DebugOverlayBundle::new()
+    }
+}
+
+impl<'a, 'b, A, B> SystemBundle<'a, 'b> for DebugOverlayBundle<A, B>
+where
+    A: Send + Sync + Hash + Eq + Clone + 'static,
+    B: Send + Sync + Hash + Eq + Clone + 'static,
+{
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        builder.add(
+            DebugOverlaySystem::<A, B>::new(self.toggle_key, self.visible),
+            "debug_overlay_system",
+            &["fps_counter_system"],
+        );
+        Ok(())
+    }
+}
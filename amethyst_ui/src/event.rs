@@ -15,7 +15,7 @@ use amethyst_renderer::{MouseButton, ScreenDimensions};
 
 use serde::{Deserialize, Serialize};
 
-use crate::transform::UiTransform;
+use crate::{scale::UiScale, transform::UiTransform};
 
 pub trait TargetedEvent {
     fn get_target(&self) -> Entity;
@@ -122,12 +122,13 @@ where
         ReadStorage<'a, Interactable>,
         Read<'a, InputHandler<A, B>>,
         ReadExpect<'a, ScreenDimensions>,
+        Read<'a, UiScale>,
         Write<'a, EventChannel<UiEvent>>,
     );
 
     fn run(
         &mut self,
-        (entities, transform, react, input, screen_dimensions, mut events): Self::SystemData,
+        (entities, transform, react, input, screen_dimensions, ui_scale, mut events): Self::SystemData,
     ) {
         let down = input.mouse_button_is_down(MouseButton::Left);
 
@@ -136,8 +137,10 @@ where
         let click_stopped = !down && self.was_down;
 
         if let Some((pos_x, pos_y)) = input.mouse_position() {
-            let x = pos_x as f32;
-            let y = screen_dimensions.height() - pos_y as f32;
+            let (x, y) = ui_scale.to_virtual(
+                &screen_dimensions,
+                (pos_x as f32, screen_dimensions.height() - pos_y as f32),
+            );
 
             let target = targeted((x, y), (&*entities, &transform, react.maybe()).join());
             if target != self.last_target {
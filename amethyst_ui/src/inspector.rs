@@ -0,0 +1,428 @@
+use std::{
+    fmt::{Debug, Write as _},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    ecs::prelude::{
+        Component, Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write,
+        WriteStorage,
+    },
+    Named, Transform,
+};
+use amethyst_error::Error;
+use amethyst_input::InputHandler;
+use amethyst_renderer::{Hidden, Material};
+use winit::VirtualKeyCode;
+
+use crate::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform};
+
+const WIDTH: f32 = 380.0;
+const HEIGHT: f32 = 520.0;
+const FONT_SIZE: f32 = 14.0;
+const LISTED: usize = 7;
+const FIELDS: [&str; 9] = [
+    "translation.x",
+    "translation.y",
+    "translation.z",
+    "rotation.x",
+    "rotation.y",
+    "rotation.z",
+    "scale.x",
+    "scale.y",
+    "scale.z",
+];
+
+/// The state of the entity inspector, shared by the `EntityInspectorSystem` and the
+/// `InspectComponentSystem`s.
+#[derive(Debug, Default)]
+pub struct EntityInspector {
+    visible: bool,
+    selected: Option<Entity>,
+    field: usize,
+    sections: Vec<String>,
+}
+
+impl EntityInspector {
+    /// Returns `true` if the inspector is shown.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the inspector.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Returns the inspected entity.
+    pub fn selected(&self) -> Option<Entity> {
+        self.selected
+    }
+
+    /// Inspects the entity, e.g. one picked with the mouse.
+    pub fn select(&mut self, entity: Entity) {
+        self.selected = Some(entity);
+    }
+}
+
+/// Adds a description of the `C` component of the inspected entity to the `EntityInspector`.
+///
+/// Added by `EntityInspectorBundle::with_component`.
+pub struct InspectComponentSystem<C> {
+    name: String,
+    describe: fn(&C) -> String,
+}
+
+impl<C> InspectComponentSystem<C> {
+    /// Creates a system describing the component with `describe`.
+    pub fn new(name: String, describe: fn(&C) -> String) -> Self {
+        InspectComponentSystem { name, describe }
+    }
+}
+
+impl<'a, C> System<'a> for InspectComponentSystem<C>
+where
+    C: Component,
+{
+    type SystemData = (ReadStorage<'a, C>, Write<'a, EntityInspector>);
+
+    fn run(&mut self, (components, mut inspector): Self::SystemData) {
+        if !inspector.visible {
+            return;
+        }
+        let component = inspector.selected.and_then(|e| components.get(e));
+        if let Some(component) = component {
+            let section = format!("{}: {}", self.name, (self.describe)(component));
+            inspector.sections.push(section);
+        }
+    }
+}
+
+/// Shows the entities with a `Transform` and the components of the selected one in the top right
+/// corner of the screen, and edits its `Transform`.
+///
+/// Development tool for debugging scenes. While the inspector is shown, `PageUp` and `PageDown`
+/// select the entity, `Up` and `Down` a value of its transform and `Left` and `Right` change the
+/// value, in steps of ten times the size while `LShift` is held. Rotations are edited as euler
+/// angles in degrees.
+pub struct EntityInspectorSystem<A, B> {
+    toggle_key: VirtualKeyCode,
+    pressed: [bool; 7],
+    entity: Option<Entity>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> EntityInspectorSystem<A, B> {
+    /// Creates the inspector, toggled with `toggle_key`.
+    pub fn new(toggle_key: VirtualKeyCode) -> Self {
+        EntityInspectorSystem {
+            toggle_key,
+            pressed: [false; 7],
+            entity: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for EntityInspectorSystem<A, B>
+where
+    A: Send + Sync + Hash + Eq + Clone + 'static,
+    B: Send + Sync + Hash + Eq + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, EntityInspector>,
+        Read<'a, InputHandler<A, B>>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<FontAsset>>,
+        ReadStorage<'a, Named>,
+        WriteStorage<'a, Transform<f32>>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut inspector,
+            input,
+            loader,
+            fonts,
+            names,
+            mut transforms,
+            mut ui_transforms,
+            mut texts,
+            mut hidden,
+        ): Self::SystemData,
+    ) {
+        let keys = [
+            self.toggle_key,
+            VirtualKeyCode::PageUp,
+            VirtualKeyCode::PageDown,
+            VirtualKeyCode::Up,
+            VirtualKeyCode::Down,
+            VirtualKeyCode::Left,
+            VirtualKeyCode::Right,
+        ];
+        let mut pressed = [false; 7];
+        for (i, &key) in keys.iter().enumerate() {
+            let down = input.key_is_down(key);
+            pressed[i] = down && !self.pressed[i];
+            self.pressed[i] = down;
+        }
+        if pressed[0] {
+            inspector.visible = !inspector.visible;
+        }
+        let sections = std::mem::replace(&mut inspector.sections, Vec::new());
+
+        let entity = match self.entity {
+            Some(entity) if entities.is_alive(entity) => entity,
+            _ => {
+                let entity = entities.create();
+                let transform = UiTransform::new(
+                    "entity_inspector".to_string(),
+                    Anchor::TopRight,
+                    -WIDTH / 2.0 - 10.0,
+                    -HEIGHT / 2.0 - 10.0,
+                    100.0,
+                    WIDTH,
+                    HEIGHT,
+                );
+                let mut text = UiText::new(
+                    get_default_font(&loader, &fonts),
+                    String::new(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    FONT_SIZE,
+                );
+                text.line_mode = LineMode::Wrap;
+                text.align = Anchor::TopLeft;
+                ui_transforms
+                    .insert(entity, transform)
+                    .expect("Unreachable: Entity was just created");
+                texts
+                    .insert(entity, text)
+                    .expect("Unreachable: Entity was just created");
+                self.entity = Some(entity);
+                entity
+            }
+        };
+
+        if !inspector.visible {
+            if !hidden.contains(entity) {
+                hidden
+                    .insert(entity, Hidden)
+                    .expect("Unreachable: Entity is alive");
+            }
+            return;
+        }
+        hidden.remove(entity);
+
+        let list = (&*entities, &transforms)
+            .join()
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        let mut index = inspector
+            .selected
+            .and_then(|selected| list.iter().position(|&e| e == selected))
+            .unwrap_or(0);
+        if !list.is_empty() {
+            if pressed[1] {
+                index = (index + list.len() - 1) % list.len();
+            }
+            if pressed[2] {
+                index = (index + 1) % list.len();
+            }
+        }
+        inspector.selected = list.get(index).cloned();
+        if pressed[3] {
+            inspector.field = (inspector.field + FIELDS.len() - 1) % FIELDS.len();
+        }
+        if pressed[4] {
+            inspector.field = (inspector.field + 1) % FIELDS.len();
+        }
+
+        let edit = match (pressed[5], pressed[6]) {
+            (true, false) => -1.0,
+            (false, true) => 1.0,
+            _ => 0.0,
+        };
+        let field = inspector.field;
+        if let Some(transform) = inspector.selected.and_then(|e| transforms.get_mut(e)) {
+            if edit != 0.0 {
+                let scale = if input.key_is_down(VirtualKeyCode::LShift) {
+                    10.0
+                } else {
+                    1.0
+                };
+                edit_field(transform, field, edit * scale);
+            }
+        }
+
+        let text = match texts.get_mut(entity) {
+            Some(text) => text,
+            None => return,
+        };
+        text.text.clear();
+        let _ = writeln!(
+            text.text,
+            "Entities with a transform: {}\nPgUp/PgDn: entity, Up/Down: value, Left/Right: edit",
+            list.len()
+        );
+        let first = index.saturating_sub(LISTED / 2);
+        for (i, &e) in list.iter().enumerate().skip(first).take(LISTED) {
+            let marker = if i == index { ">" } else { " " };
+            let _ = writeln!(text.text, "{} {}", marker, describe_entity(e, &names));
+        }
+
+        if let Some(selected) = inspector.selected {
+            let _ = writeln!(text.text, "\n{}", describe_entity(selected, &names));
+            if let Some(transform) = transforms.get(selected) {
+                for (i, name) in FIELDS.iter().enumerate() {
+                    let marker = if i == field { ">" } else { " " };
+                    let _ = writeln!(
+                        text.text,
+                        "{} {}: {:.3}",
+                        marker,
+                        name,
+                        field_value(transform, i)
+                    );
+                }
+            }
+            for section in sections {
+                let _ = writeln!(text.text, "{}", section);
+            }
+        }
+    }
+}
+
+fn describe_entity(entity: Entity, names: &ReadStorage<'_, Named>) -> String {
+    match names.get(entity) {
+        Some(named) => format!("{} \"{}\"", entity.id(), named.name),
+        None => format!("{}", entity.id()),
+    }
+}
+
+fn field_value(transform: &Transform<f32>, field: usize) -> f32 {
+    let (x, y, z) = transform.euler_angles();
+    match field {
+        0..=2 => transform.translation()[field],
+        3 => x.to_degrees(),
+        4 => y.to_degrees(),
+        5 => z.to_degrees(),
+        _ => transform.scale()[field - 6],
+    }
+}
+
+fn edit_field(transform: &mut Transform<f32>, field: usize, amount: f32) {
+    match field {
+        0..=2 => transform.translation_mut()[field] += amount * 0.1,
+        3..=5 => {
+            let (x, y, z) = transform.euler_angles();
+            let mut angles = [x, y, z];
+            angles[field - 3] += (amount * 5.0).to_radians();
+            transform.set_rotation_euler(angles[0], angles[1], angles[2]);
+        }
+        _ => transform.scale_mut()[field - 6] += amount * 0.1,
+    }
+}
+
+fn describe_material(material: &Material) -> String {
+    format!(
+        "albedo #{}, emission #{}, normal #{}, metallic #{}, roughness #{}",
+        material.albedo.id(),
+        material.emission.id(),
+        material.normal.id(),
+        material.metallic.id(),
+        material.roughness.id()
+    )
+}
+
+type AddSystem = Box<dyn for<'r, 'x, 'y> Fn(&mut BundleBuilder<'r, 'x, 'y>) -> String + Send>;
+
+/// Adds the `EntityInspectorSystem` with name `entity_inspector`, and an `InspectComponentSystem`
+/// for the `Material` and every component added with `with_component`.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+/// The inspector is toggled with F4 by default, and meant for development builds.
+pub struct EntityInspectorBundle<A = String, B = String> {
+    toggle_key: VirtualKeyCode,
+    components: Vec<AddSystem>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> EntityInspectorBundle<A, B> {
+    /// Creates a bundle inspecting the `Transform`, `Named` and `Material` components.
+    pub fn new() -> Self {
+        EntityInspectorBundle {
+            toggle_key: VirtualKeyCode::F4,
+            components: Vec::new(),
+            _marker: PhantomData,
+        }
+        .with_component_described::<Material>("Material", describe_material)
+    }
+
+    /// Toggles the inspector with a different key.
+    pub fn with_toggle_key(mut self, key: VirtualKeyCode) -> Self {
+        self.toggle_key = key;
+        self
+    }
+
+    /// Shows the `Debug` output of the `C` component, e.g. `GltfNodeExtent`.
+    pub fn with_component<C>(self, name: &str) -> Self
+    where
+        C: Component + Debug,
+    {
+        self.with_component_described::<C>(name, |c| format!("{:?}", c))
+    }
+
+    /// Shows the `C` component as described by `describe`.
+    pub fn with_component_described<C>(mut self, name: &str, describe: fn(&C) -> String) -> Self
+    where
+        C: Component,
+    {
+        let name = name.to_string();
+        let index = self.components.len();
+        self.components.push(Box::new(move |builder| {
+            let system_name = format!("inspect_component_{}", index);
+            builder.add(
+                InspectComponentSystem::new(name.clone(), describe),
+                &system_name,
+                &[],
+            );
+            system_name
+        }));
+        self
+    }
+}
+
+impl<A, B> Default for EntityInspectorBundle<A, B> {
+    fn default() -> Self {
+        EntityInspectorBundle::new()
+    }
+}
+
+impl<'a, 'b, A, B> SystemBundle<'a, 'b> for EntityInspectorBundle<A, B>
+where
+    A: Send + Sync + Hash + Eq + Clone + 'static,
+    B: Send + Sync + Hash + Eq + Clone + 'static,
+{
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        let names = self
+            .components
+            .iter()
+            .map(|add| add(builder))
+            .collect::<Vec<_>>();
+        let dependencies = names.iter().map(String::as_str).collect::<Vec<_>>();
+        builder.add(
+            EntityInspectorSystem::<A, B>::new(self.toggle_key),
+            "entity_inspector",
+            &dependencies,
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    define_widget, font::default::get_default_font, Anchor, FontAsset, FontHandle, Stretch, UiText,
-    UiTransform, WidgetId, Widgets,
+    define_widget, font::default::get_default_font, Accessible, AccessibleRole, Anchor, FontAsset,
+    FontHandle, Stretch, UiText, UiTransform, WidgetId, Widgets,
 };
 use shred::SystemData;
 use shred_derive::SystemData;
@@ -34,6 +34,7 @@ where
     entities: Entities<'a>,
     text: WriteStorage<'a, UiText>,
     transform: WriteStorage<'a, UiTransform>,
+    accessible: WriteStorage<'a, Accessible>,
     label_widgets: WriteExpect<'a, Widgets<UiLabel, I>>,
 }
 
@@ -202,6 +203,12 @@ where
             .font
             .unwrap_or_else(|| get_default_font(&res.loader, &res.font_asset));
 
+        res.accessible
+            .insert(
+                text_entity,
+                Accessible::new(self.text.clone(), AccessibleRole::Label),
+            )
+            .expect("Unreachable: Inserting newly created entity");
         res.text
             .insert(
                 text_entity,
@@ -6,14 +6,14 @@ use thread_profiler::profile_scope;
 
 use amethyst_core::{
     ecs::prelude::{
-        BitSet, ComponentEvent, Join, ReadExpect, ReadStorage, ReaderId, Resources, System,
+        BitSet, ComponentEvent, Join, Read, ReadExpect, ReadStorage, ReaderId, Resources, System,
         WriteStorage,
     },
     HierarchyEvent, Parent, ParentHierarchy,
 };
 use amethyst_renderer::ScreenDimensions;
 
-use super::UiTransform;
+use super::{UiScale, UiTransform};
 
 /// Indicates if the position and margins should be calculated in pixel or
 /// relative to their parent size.
@@ -143,10 +143,11 @@ impl<'a> System<'a> for UiTransformSystem {
         WriteStorage<'a, UiTransform>,
         ReadStorage<'a, Parent>,
         ReadExpect<'a, ScreenDimensions>,
+        Read<'a, UiScale>,
         ReadExpect<'a, ParentHierarchy>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (mut transforms, parents, screen_dim, hierarchy) = data;
+        let (mut transforms, parents, screen_dim, ui_scale, hierarchy) = data;
         #[cfg(feature = "profiler")]
         profile_scope!("ui_parent_system");
 
@@ -181,13 +182,14 @@ impl<'a> System<'a> for UiTransformSystem {
             }
         }
 
-        let current_screen_size = (screen_dim.width(), screen_dim.height());
+        // The root entities are laid out in the screen size of the `UiScale`.
+        let current_screen_size = ui_scale.virtual_size(&screen_dim);
         let screen_resized = current_screen_size != self.screen_size;
         self.screen_size = current_screen_size;
         if screen_resized {
             process_root_iter(
                 (&mut transforms, !&parents).join().map(|i| i.0),
-                &*screen_dim,
+                current_screen_size,
             );
         } else {
             // Immutable borrow
@@ -196,7 +198,7 @@ impl<'a> System<'a> for UiTransformSystem {
                 (&mut transforms, !&parents, self_transform_modified)
                     .join()
                     .map(|i| i.0),
-                &*screen_dim,
+                current_screen_size,
             );
         }
 
@@ -323,27 +325,27 @@ impl<'a> System<'a> for UiTransformSystem {
     }
 }
 
-fn process_root_iter<'a, I>(iter: I, screen_dim: &ScreenDimensions)
+fn process_root_iter<'a, I>(iter: I, (screen_width, screen_height): (f32, f32))
 where
     I: Iterator<Item = &'a mut UiTransform>,
 {
     for transform in iter {
         let norm = transform.anchor.norm_offset();
-        transform.pixel_x = screen_dim.width() / 2.0 + screen_dim.width() * norm.0;
-        transform.pixel_y = screen_dim.height() / 2.0 + screen_dim.height() * norm.1;
+        transform.pixel_x = screen_width / 2.0 + screen_width * norm.0;
+        transform.pixel_y = screen_height / 2.0 + screen_height * norm.1;
         transform.global_z = transform.local_z;
 
         let new_size = match transform.stretch {
             Stretch::NoStretch => (transform.width, transform.height),
-            Stretch::X { x_margin } => (screen_dim.width() - x_margin * 2.0, transform.height),
-            Stretch::Y { y_margin } => (transform.width, screen_dim.height() - y_margin * 2.0),
+            Stretch::X { x_margin } => (screen_width - x_margin * 2.0, transform.height),
+            Stretch::Y { y_margin } => (transform.width, screen_height - y_margin * 2.0),
             Stretch::XY {
                 keep_aspect_ratio: false,
                 x_margin,
                 y_margin,
             } => (
-                screen_dim.width() - x_margin * 2.0,
-                screen_dim.height() - y_margin * 2.0,
+                screen_width - x_margin * 2.0,
+                screen_height - y_margin * 2.0,
             ),
             Stretch::XY {
                 keep_aspect_ratio: true,
@@ -351,8 +353,8 @@ where
                 y_margin,
             } => {
                 let scale = f32::min(
-                    (screen_dim.width() - x_margin * 2.0) / transform.width,
-                    (screen_dim.height() - y_margin * 2.0) / transform.height,
+                    (screen_width - x_margin * 2.0) / transform.width,
+                    (screen_height - y_margin * 2.0) / transform.height,
                 );
 
                 (transform.width * scale, transform.height * scale)
@@ -368,10 +370,10 @@ where
                 transform.pixel_height = transform.height;
             }
             ScaleMode::Percent => {
-                transform.pixel_x += transform.local_x * screen_dim.width();
-                transform.pixel_y += transform.local_y * screen_dim.height();
-                transform.pixel_width = transform.width * screen_dim.width();
-                transform.pixel_height = transform.height * screen_dim.height();
+                transform.pixel_x += transform.local_x * screen_width;
+                transform.pixel_y += transform.local_y * screen_height;
+                transform.pixel_width = transform.width * screen_width;
+                transform.pixel_height = transform.height * screen_height;
             }
         }
     }
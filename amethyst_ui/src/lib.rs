@@ -3,11 +3,17 @@
 #![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
 
 pub use self::{
+    accessibility::{
+        AccessibilityNode, AccessibilitySystem, AccessibilityTree, Accessible, AccessibleRole,
+        UiAccessibility, UiContrastTheme,
+    },
     bundle::UiBundle,
     button::{
         UiButton, UiButtonAction, UiButtonActionRetrigger, UiButtonActionRetriggerSystem,
         UiButtonActionType, UiButtonBuilder, UiButtonBuilderResources, UiButtonSystem,
     },
+    cursor::{UiCursor, UiCursorSystem},
+    debug_overlay::{DebugOverlayBundle, DebugOverlaySystem},
     event::{targeted, Interactable, UiEvent, UiEventType, UiMouseSystem},
     event_retrigger::{EventReceiver, EventRetriggerSystem},
     font::{
@@ -15,41 +21,65 @@ pub use self::{
         systemfont::{default_system_font, get_all_font_handles, list_system_font_families},
     },
     format::{FontAsset, FontFormat, FontHandle, OtfFormat, TtfFormat},
+    inspector::{
+        EntityInspector, EntityInspectorBundle, EntityInspectorSystem, InspectComponentSystem,
+    },
     label::{UiLabel, UiLabelBuilder, UiLabelBuilderResources},
     layout::{Anchor, ScaleMode, Stretch, UiTransformSystem},
+    minimap::{MinimapMarker, MinimapMarkerId, UiMinimap},
+    numeric_input::{
+        NumericInputEvent, NumericInputEventType, NumericInputSystem, NumericValue, UiNumericInput,
+        UiNumericInputBuilder, UiNumericInputBuilderResources,
+    },
     pass::DrawUi,
     prefab::{
         NoCustomUi, ToNativeWidget, UiCreator, UiFormat, UiImagePrefab, UiLoader, UiLoaderSystem,
         UiPrefab, UiTextBuilder, UiTransformBuilder, UiWidget,
     },
+    profile_overlay::SystemProfileOverlaySystem,
     resize::{ResizeSystem, UiResize},
+    scale::{UiScale, UiScaleMode},
     selection::{Selectable, Selected, SelectionKeyboardSystem, SelectionMouseSystem},
     selection_order_cache::{CacheSelectionOrderSystem, CachedSelectionOrder},
-    sound::{UiPlaySoundAction, UiSoundRetrigger, UiSoundRetriggerSystem, UiSoundSystem},
-    text::{LineMode, TextEditing, TextEditingMouseSystem, UiText},
+    sound::{
+        UiPlaySoundAction, UiSoundRetrigger, UiSoundRetriggerSystem, UiSoundSystem, UiSoundTheme,
+        UiSoundThemeSystem,
+    },
+    text::{LineMode, TextEditing, TextEditingMouseSystem, TextOutline, TextShadow, UiText},
     text_editing::TextEditingInputSystem,
     transform::{UiFinder, UiTransform},
+    transition::{UiEasing, UiTransition, UiTransitionEffect, UiTransitionSystem, UiTransitions},
     widgets::{Widget, WidgetId, Widgets},
 };
 
 pub(crate) use amethyst_core::ecs::prelude::Entity;
 pub(crate) use paste;
 
+mod accessibility;
 mod bundle;
 mod button;
+mod cursor;
+mod debug_overlay;
 mod event;
 mod event_retrigger;
 mod font;
 mod format;
+mod inspector;
 mod label;
 mod layout;
+mod minimap;
+mod numeric_input;
 mod pass;
 mod prefab;
+mod profile_overlay;
 mod resize;
+mod scale;
 mod selection;
 mod selection_order_cache;
 mod sound;
 mod text;
 mod text_editing;
+mod text_style;
 mod transform;
+mod transition;
 mod widgets;
@@ -0,0 +1,165 @@
+//! A widget showing the view of a camera rendering into a target, with markers at world
+//! positions.
+
+use std::collections::BTreeMap;
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, Entity},
+    math::{Point3, Vector4},
+    Transform,
+};
+use amethyst_renderer::{Camera, TextureHandle};
+
+/// Identifies a marker of a `UiMinimap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MinimapMarkerId(u32);
+
+/// An icon drawn on a `UiMinimap` at a position in the world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimapMarker {
+    /// The position in the world, projected with the camera of the minimap.
+    pub position: Point3<f32>,
+    /// The icon.
+    pub image: TextureHandle,
+    /// The width and height of the icon, in the same units as the `UiTransform`.
+    pub size: (f32, f32),
+}
+
+impl MinimapMarker {
+    /// Creates a marker drawing `image` with the given `size` at `position`.
+    pub fn new(position: Point3<f32>, image: TextureHandle, size: (f32, f32)) -> Self {
+        MinimapMarker {
+            position,
+            image,
+            size,
+        }
+    }
+}
+
+/// Displays what a camera renders into a texture in the area of the `UiTransform` of the entity.
+///
+/// The camera entity needs a `TargetCamera` naming a target of the pipeline, rendered by stages
+/// before the one drawing the user interface, and is usually orthographic and looking down on the
+/// world:
+///
+/// ```rust,ignore
+/// let camera = world
+///     .create_entity()
+///     .with(Camera::from(Projection::orthographic(-50.0, 50.0, -50.0, 50.0)))
+///     .with(TargetCamera::new("minimap"))
+///     .with(transform)
+///     .build();
+/// let mut minimap = UiMinimap::new(camera);
+/// let player_marker = minimap.add_marker(MinimapMarker::new(position, icon, (16.0, 16.0)));
+/// ```
+///
+/// Markers outside of the view of the camera are not drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiMinimap {
+    /// The camera entity whose target is displayed.
+    pub camera: Entity,
+    markers: BTreeMap<MinimapMarkerId, MinimapMarker>,
+    next_marker: u32,
+}
+
+impl UiMinimap {
+    /// Creates a minimap displaying the target of `camera`, without markers.
+    pub fn new(camera: Entity) -> Self {
+        UiMinimap {
+            camera,
+            markers: BTreeMap::new(),
+            next_marker: 0,
+        }
+    }
+
+    /// Adds a marker, returning the id to move or remove it with.
+    pub fn add_marker(&mut self, marker: MinimapMarker) -> MinimapMarkerId {
+        let id = MinimapMarkerId(self.next_marker);
+        self.next_marker += 1;
+        self.markers.insert(id, marker);
+        id
+    }
+
+    /// Moves a marker to another world position. Returns false if the marker doesn't exist.
+    pub fn set_marker_position(&mut self, id: MinimapMarkerId, position: Point3<f32>) -> bool {
+        match self.markers.get_mut(&id) {
+            Some(marker) => {
+                marker.position = position;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a marker.
+    pub fn marker(&self, id: MinimapMarkerId) -> Option<&MinimapMarker> {
+        self.markers.get(&id)
+    }
+
+    /// Returns a marker to change it.
+    pub fn marker_mut(&mut self, id: MinimapMarkerId) -> Option<&mut MinimapMarker> {
+        self.markers.get_mut(&id)
+    }
+
+    /// Removes a marker, returning it.
+    pub fn remove_marker(&mut self, id: MinimapMarkerId) -> Option<MinimapMarker> {
+        self.markers.remove(&id)
+    }
+
+    /// Removes all markers.
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    /// Iterates over the markers, in the order they were added and are drawn.
+    pub fn markers(&self) -> impl Iterator<Item = (MinimapMarkerId, &MinimapMarker)> {
+        self.markers.iter().map(|(id, marker)| (*id, marker))
+    }
+}
+
+impl Component for UiMinimap {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Projects a world position with a camera, returning where it lands in the view from -1 to 1 on
+/// both axes with y up, or `None` if the camera doesn't see it.
+pub(crate) fn project_marker(
+    camera: &Camera,
+    transform: &Transform<f32>,
+    position: &Point3<f32>,
+) -> Option<(f32, f32)> {
+    let view = transform.global_matrix().try_inverse()?;
+    let clip = camera.proj * view * Vector4::new(position.x, position.y, position.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip / clip.w;
+    if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0 || ndc.z.abs() > 1.0 {
+        return None;
+    }
+    Some((ndc.x, ndc.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_markers_in_view() {
+        let camera = Camera::standard_2d();
+        let transform = Transform::default();
+
+        assert_eq!(
+            project_marker(&camera, &transform, &Point3::new(0.5, -0.25, -1.0)),
+            Some((0.5, -0.25))
+        );
+        assert_eq!(
+            project_marker(&camera, &transform, &Point3::new(1.5, 0.0, -1.0)),
+            None
+        );
+        assert_eq!(
+            project_marker(&camera, &transform, &Point3::new(0.0, 0.0, 1.0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,696 @@
+//! A text field editing a number, with buttons, dragging, the arrow keys and the mouse wheel.
+
+use std::{hash::Hash, marker::PhantomData};
+
+use shred::SystemData;
+use shred_derive::SystemData;
+use winit::{MouseButton, VirtualKeyCode};
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReadStorage,
+        Resources, System, World, Write, WriteExpect, WriteStorage,
+    },
+    shrev::{EventChannel, ReaderId},
+    Parent,
+};
+use amethyst_input::{InputEvent, InputHandler, ScrollDirection};
+
+use crate::{
+    define_widget, font::default::get_default_font, Accessible, AccessibleRole, Anchor, FontAsset,
+    FontHandle, Interactable, Selectable, Selected, TextEditing, UiEvent, UiEventType, UiText,
+    UiTransform, WidgetId, Widgets,
+};
+
+const DEFAULT_Z: f32 = 1.0;
+const DEFAULT_WIDTH: f32 = 128.0;
+const DEFAULT_HEIGHT: f32 = 32.0;
+const DEFAULT_TXT_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const DEFAULT_MAX_LENGTH: usize = 24;
+/// How far the mouse moves before a click on the field becomes a drag, in pixels.
+const DRAG_THRESHOLD: f64 = 3.0;
+
+define_widget!(UiNumericInput =>
+    entities: [text_entity, increment_entity, decrement_entity]
+    components: [
+        (has UiTransform as position on text_entity),
+        (has UiText as text on text_entity),
+        (has NumericValue as value on text_entity),
+        (has UiText as increment_text on increment_entity),
+        (has UiText as decrement_text on decrement_entity)
+    ]
+);
+
+/// The number edited by a `UiNumericInput`, kept on its text entity.
+///
+/// The `NumericInputSystem` writes the value into the `UiText` of the entity whenever it changes,
+/// and parses the text when it is committed with `Enter` or by selecting another element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericValue {
+    value: f32,
+    /// The smallest value.
+    pub min: f32,
+    /// The largest value.
+    pub max: f32,
+    /// The increments of the value, starting from `min`. Values are snapped to them, unless the
+    /// step is 0.
+    pub step: f32,
+    /// The number of decimals shown.
+    pub decimals: usize,
+    /// How much the value changes per pixel the mouse is dragged to the right.
+    pub drag_speed: f32,
+    /// The entity adding a step to the value when clicked.
+    pub increment: Option<Entity>,
+    /// The entity removing a step from the value when clicked.
+    pub decrement: Option<Entity>,
+    dirty: bool,
+}
+
+impl NumericValue {
+    /// Creates a value between `min` and `max` in increments of `step`.
+    pub fn new(value: f32, min: f32, max: f32, step: f32) -> Self {
+        let mut numeric = NumericValue {
+            value: 0.0,
+            min,
+            max,
+            step,
+            decimals: decimals_of(step),
+            drag_speed: step,
+            increment: None,
+            decrement: None,
+            dirty: true,
+        };
+        numeric.value = numeric.constrain(value);
+        numeric
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Sets the value, clamped to the range and snapped to the step, and returns the stored
+    /// value. This doesn't send a `NumericInputEvent`.
+    pub fn set_value(&mut self, value: f32) -> f32 {
+        self.value = self.constrain(value);
+        self.dirty = true;
+        self.value
+    }
+
+    /// Returns the value as shown in the field.
+    pub fn format(&self) -> String {
+        format!("{:.*}", self.decimals, self.value)
+    }
+
+    /// Clamps a value to the range and snaps it to the step.
+    pub fn constrain(&self, value: f32) -> f32 {
+        let value = value.max(self.min).min(self.max);
+        if self.step <= 0.0 {
+            return value;
+        }
+        let snapped = self.min + ((value - self.min) / self.step).round() * self.step;
+        snapped.max(self.min).min(self.max)
+    }
+}
+
+impl Component for NumericValue {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The number of decimals needed to show multiples of `step`.
+fn decimals_of(step: f32) -> usize {
+    let mut decimals = 0;
+    let mut scaled = step.abs();
+    while decimals < 6 && scaled > 0.0 && (scaled - scaled.round()).abs() > 1e-4 {
+        scaled *= 10.0;
+        decimals += 1;
+    }
+    decimals
+}
+
+/// Parses the text of a field, rejecting anything but finite numbers.
+fn parse(text: &str) -> Option<f32> {
+    text.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// What happened to the value of a `UiNumericInput`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumericInputEventType {
+    /// The value was changed by the user.
+    Changed,
+    /// The user asked for a value out of the range, which was clamped to it.
+    Clamped {
+        /// The value asked for.
+        requested: f32,
+    },
+    /// The committed text is not a number, and was replaced by the previous value.
+    Rejected {
+        /// The committed text.
+        text: String,
+    },
+}
+
+/// An event sent by the `NumericInputSystem` through an `EventChannel<NumericInputEvent>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericInputEvent {
+    /// What happened.
+    pub event_type: NumericInputEventType,
+    /// The text entity of the `UiNumericInput`.
+    pub target: Entity,
+    /// The value after the event.
+    pub value: f32,
+}
+
+/// Edits the `NumericValue`s of the `UiNumericInput`s.
+///
+/// The value changes by a step when the increment or decrement entity is clicked, when the
+/// arrow keys `Up` and `Down` are pressed while the field is selected, ten steps while `LShift`
+/// is held, and when the mouse wheel is scrolled over the field. Dragging the mouse sideways
+/// from the field changes the value by `drag_speed` per pixel.
+pub struct NumericInputSystem<A, B> {
+    ui_reader: Option<ReaderId<UiEvent>>,
+    input_reader: Option<ReaderId<InputEvent<B>>>,
+    hovered: Option<Entity>,
+    drag: Option<Drag>,
+    pressed: [bool; 2],
+    _marker: PhantomData<A>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    entity: Entity,
+    start_x: f64,
+    start_value: f32,
+    moved: bool,
+}
+
+impl<A, B> NumericInputSystem<A, B> {
+    /// Creates a new `NumericInputSystem`.
+    pub fn new() -> Self {
+        NumericInputSystem {
+            ui_reader: None,
+            input_reader: None,
+            hovered: None,
+            drag: None,
+            pressed: [false; 2],
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for NumericInputSystem<A, B>
+where
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<UiEvent>>,
+        Read<'a, EventChannel<InputEvent<B>>>,
+        Read<'a, InputHandler<A, B>>,
+        ReadStorage<'a, Selected>,
+        WriteStorage<'a, NumericValue>,
+        WriteStorage<'a, UiText>,
+        Write<'a, EventChannel<NumericInputEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, ui_events, input_events, input, selected, mut values, mut texts, mut events): Self::SystemData,
+    ) {
+        let mut changes = Vec::new();
+
+        for event in ui_events.read(self.ui_reader.as_mut().expect("`setup` was not called")) {
+            let target = event.target;
+            match event.event_type {
+                UiEventType::Click => {
+                    for (entity, value) in (&*entities, &values).join() {
+                        if value.increment == Some(target) {
+                            changes.push((entity, value.value + value.step));
+                        } else if value.decrement == Some(target) {
+                            changes.push((entity, value.value - value.step));
+                        }
+                    }
+                }
+                UiEventType::ClickStart => {
+                    if let (Some(value), Some((x, _))) =
+                        (values.get(target), input.mouse_position())
+                    {
+                        self.drag = Some(Drag {
+                            entity: target,
+                            start_x: x,
+                            start_value: value.value,
+                            moved: false,
+                        });
+                    }
+                }
+                UiEventType::HoverStart if values.contains(target) => {
+                    self.hovered = Some(target);
+                }
+                UiEventType::HoverStop if self.hovered == Some(target) => {
+                    self.hovered = None;
+                }
+                UiEventType::ValueCommit | UiEventType::Blur => {
+                    let text = match (values.get(target), texts.get(target)) {
+                        (Some(_), Some(text)) => text.text.clone(),
+                        _ => continue,
+                    };
+                    match parse(&text) {
+                        Some(requested) => changes.push((target, requested)),
+                        None => {
+                            let value = values.get_mut(target).expect("Checked above");
+                            value.dirty = true;
+                            events.single_write(NumericInputEvent {
+                                event_type: NumericInputEventType::Rejected { text },
+                                target,
+                                value: value.value,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !input.mouse_button_is_down(MouseButton::Left) {
+            self.drag = None;
+        }
+        if let (Some(drag), Some((x, _))) = (self.drag.as_mut(), input.mouse_position()) {
+            let distance = x - drag.start_x;
+            drag.moved |= distance.abs() > DRAG_THRESHOLD;
+            if drag.moved {
+                if let Some(value) = values.get(drag.entity) {
+                    let requested = drag.start_value + distance as f32 * value.drag_speed;
+                    // Dragging past the range is expected, so the value is clamped silently.
+                    changes.push((drag.entity, value.constrain(requested)));
+                }
+            }
+        }
+
+        let keys = [VirtualKeyCode::Up, VirtualKeyCode::Down];
+        let mut pressed = [false; 2];
+        for (i, &key) in keys.iter().enumerate() {
+            let down = input.key_is_down(key);
+            pressed[i] = down && !self.pressed[i];
+            self.pressed[i] = down;
+        }
+        let steps = if input.key_is_down(VirtualKeyCode::LShift) {
+            10.0
+        } else {
+            1.0
+        };
+        let key_steps = match pressed {
+            [true, false] => steps,
+            [false, true] => -steps,
+            _ => 0.0,
+        };
+        if key_steps != 0.0 {
+            for (entity, value, _) in (&*entities, &values, &selected).join() {
+                changes.push((entity, value.value + key_steps * value.step));
+            }
+        }
+
+        for event in input_events.read(self.input_reader.as_mut().expect("`setup` was not called"))
+        {
+            let wheel_steps = match *event {
+                InputEvent::MouseWheelMoved(ScrollDirection::ScrollUp) => 1.0,
+                InputEvent::MouseWheelMoved(ScrollDirection::ScrollDown) => -1.0,
+                _ => continue,
+            };
+            if let Some(value) = self.hovered.and_then(|entity| values.get(entity)) {
+                let entity = self.hovered.expect("Checked above");
+                changes.push((entity, value.value + wheel_steps * value.step));
+            }
+        }
+
+        for (entity, requested) in changes {
+            let value = match values.get_mut(entity) {
+                Some(value) => value,
+                None => continue,
+            };
+            let previous = value.value;
+            let new = value.set_value(requested);
+            if requested < value.min || requested > value.max {
+                events.single_write(NumericInputEvent {
+                    event_type: NumericInputEventType::Clamped { requested },
+                    target: entity,
+                    value: new,
+                });
+            }
+            if new != previous {
+                events.single_write(NumericInputEvent {
+                    event_type: NumericInputEventType::Changed,
+                    target: entity,
+                    value: new,
+                });
+            }
+        }
+
+        for (value, text) in (&mut values, &mut texts).join() {
+            if value.dirty {
+                text.text = value.format();
+                value.dirty = false;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.ui_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<B>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Container for all the resources the builder needs to make a new `UiNumericInput`.
+#[derive(SystemData)]
+pub struct UiNumericInputBuilderResources<'a, G = (), I: WidgetId = u32>
+where
+    G: Send + Sync + 'static,
+    I: WidgetId,
+{
+    font_asset: Read<'a, AssetStorage<FontAsset>>,
+    loader: ReadExpect<'a, Loader>,
+    entities: Entities<'a>,
+    text: WriteStorage<'a, UiText>,
+    editing: WriteStorage<'a, TextEditing>,
+    transform: WriteStorage<'a, UiTransform>,
+    value: WriteStorage<'a, NumericValue>,
+    interactable: WriteStorage<'a, Interactable>,
+    selectable: WriteStorage<'a, Selectable<G>>,
+    parent: WriteStorage<'a, Parent>,
+    accessible: WriteStorage<'a, Accessible>,
+    numeric_input_widgets: WriteExpect<'a, Widgets<UiNumericInput, I>>,
+}
+
+/// Convenience structure for building a numeric input: an editable text field with buttons to
+/// increment and decrement the value on its right side.
+pub struct UiNumericInputBuilder<G = (), I = u32>
+where
+    I: WidgetId,
+{
+    id: Option<I>,
+    x: f32,
+    y: f32,
+    z: f32,
+    width: f32,
+    height: f32,
+    anchor: Anchor,
+    value: NumericValue,
+    text_color: [f32; 4],
+    font: Option<FontHandle>,
+    font_size: f32,
+    tab_order: u32,
+    parent: Option<Entity>,
+    name: String,
+    _marker: PhantomData<G>,
+}
+
+impl<G, I> UiNumericInputBuilder<G, I>
+where
+    G: Send + Sync + 'static,
+    I: WidgetId + 'static,
+{
+    /// Construct a new `UiNumericInputBuilder` for a value between `min` and `max` in increments
+    /// of `step`.
+    pub fn new(value: f32, min: f32, max: f32, step: f32) -> Self {
+        UiNumericInputBuilder {
+            id: None,
+            x: 0.,
+            y: 0.,
+            z: DEFAULT_Z,
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            anchor: Anchor::TopLeft,
+            value: NumericValue::new(value, min, max, step),
+            text_color: DEFAULT_TXT_COLOR,
+            font: None,
+            font_size: 24.,
+            tab_order: 0,
+            parent: None,
+            name: String::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets an ID for this widget. The type of this ID will determine which `Widgets`
+    /// resource this widget will be added to, see [`Widgets`](../struct.Widgets.html).
+    pub fn with_id(mut self, id: I) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the size of the field, including the buttons.
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Set the position.
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+
+    /// Add an anchor to the field.
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set the number of decimals shown, by default those of the step.
+    pub fn with_decimals(mut self, decimals: usize) -> Self {
+        self.value.decimals = decimals;
+        self
+    }
+
+    /// Set how much the value changes per pixel dragged, by default one step.
+    pub fn with_drag_speed(mut self, drag_speed: f32) -> Self {
+        self.value.drag_speed = drag_speed;
+        self
+    }
+
+    /// Set text color
+    pub fn with_text_color(mut self, text_color: [f32; 4]) -> Self {
+        self.text_color = text_color;
+        self
+    }
+
+    /// Use a different font.
+    pub fn with_font(mut self, font: FontHandle) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Set font size
+    pub fn with_font_size(mut self, size: f32) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Set the tab order of the field.
+    pub fn with_tab_order(mut self, tab_order: u32) -> Self {
+        self.tab_order = tab_order;
+        self
+    }
+
+    /// Set the name announced by screen readers, see `Accessible`.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Add a parent to the field.
+    pub fn with_parent(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Build this with the `UiNumericInputBuilderResources`.
+    pub fn build(self, mut res: UiNumericInputBuilderResources<'_, G, I>) -> (I, UiNumericInput) {
+        let text_entity = res.entities.create();
+        let increment_entity = res.entities.create();
+        let decrement_entity = res.entities.create();
+        let widget = UiNumericInput::new(text_entity, increment_entity, decrement_entity);
+
+        let id = {
+            let widget = widget.clone();
+
+            if let Some(id) = self.id {
+                let added_id = id.clone();
+                res.numeric_input_widgets.add_with_id(id, widget);
+                added_id
+            } else {
+                res.numeric_input_widgets.add(widget)
+            }
+        };
+
+        let font_handle = self
+            .font
+            .unwrap_or_else(|| get_default_font(&res.loader, &res.font_asset));
+        let button_size = self.height / 2.0;
+
+        res.transform
+            .insert(
+                text_entity,
+                UiTransform::new(
+                    format!("{}_numeric_input", id),
+                    self.anchor,
+                    self.x,
+                    self.y,
+                    self.z,
+                    self.width,
+                    self.height,
+                ),
+            )
+            .expect("Unreachable: Inserting newly created entity");
+        let mut text = UiText::new(
+            font_handle.clone(),
+            self.value.format(),
+            self.text_color,
+            self.font_size,
+        );
+        text.align = Anchor::MiddleLeft;
+        res.text
+            .insert(text_entity, text)
+            .expect("Unreachable: Inserting newly created entity");
+        res.editing
+            .insert(
+                text_entity,
+                TextEditing::new(
+                    DEFAULT_MAX_LENGTH,
+                    self.text_color,
+                    [0.6, 0.6, 0.6, 1.0],
+                    false,
+                ),
+            )
+            .expect("Unreachable: Inserting newly created entity");
+        res.interactable
+            .insert(text_entity, Interactable)
+            .expect("Unreachable: Inserting newly created entity");
+        res.selectable
+            .insert(text_entity, Selectable::<G>::new(self.tab_order))
+            .expect("Unreachable: Inserting newly created entity");
+        res.accessible
+            .insert(
+                text_entity,
+                Accessible::new(self.name, AccessibleRole::SpinButton),
+            )
+            .expect("Unreachable: Inserting newly created entity");
+        if let Some(parent) = self.parent {
+            res.parent
+                .insert(text_entity, Parent { entity: parent })
+                .expect("Unreachable: Inserting newly created entity");
+        }
+
+        let buttons = [
+            (
+                increment_entity,
+                "+",
+                "increment",
+                "Increment",
+                button_size / 2.0,
+            ),
+            (
+                decrement_entity,
+                "-",
+                "decrement",
+                "Decrement",
+                -button_size / 2.0,
+            ),
+        ];
+        for &(entity, label, name, accessible_name, y) in &buttons {
+            res.transform
+                .insert(
+                    entity,
+                    UiTransform::new(
+                        format!("{}_numeric_input_{}", id, name),
+                        Anchor::MiddleRight,
+                        -button_size / 2.0,
+                        y,
+                        0.1,
+                        button_size,
+                        button_size,
+                    ),
+                )
+                .expect("Unreachable: Inserting newly created entity");
+            res.text
+                .insert(
+                    entity,
+                    UiText::new(
+                        font_handle.clone(),
+                        label.to_string(),
+                        self.text_color,
+                        button_size,
+                    ),
+                )
+                .expect("Unreachable: Inserting newly created entity");
+            res.interactable
+                .insert(entity, Interactable)
+                .expect("Unreachable: Inserting newly created entity");
+            res.accessible
+                .insert(
+                    entity,
+                    Accessible::new(accessible_name, AccessibleRole::Button),
+                )
+                .expect("Unreachable: Inserting newly created entity");
+            res.parent
+                .insert(
+                    entity,
+                    Parent {
+                        entity: text_entity,
+                    },
+                )
+                .expect("Unreachable: Inserting newly created entity");
+        }
+
+        let mut value = self.value;
+        value.increment = Some(increment_entity);
+        value.decrement = Some(decrement_entity);
+        res.value
+            .insert(text_entity, value)
+            .expect("Unreachable: Inserting newly created entity");
+
+        (id, widget)
+    }
+
+    /// Create the `UiNumericInput` based on provided configuration parameters.
+    pub fn build_from_world(self, world: &World) -> (I, UiNumericInput) {
+        self.build(UiNumericInputBuilderResources::<G, I>::fetch(&world.res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrains_values() {
+        let value = NumericValue::new(0.0, -1.0, 1.0, 0.25);
+        assert_eq!(value.constrain(0.3), 0.25);
+        assert_eq!(value.constrain(0.4), 0.5);
+        assert_eq!(value.constrain(5.0), 1.0);
+        assert_eq!(value.constrain(-5.0), -1.0);
+        assert_eq!(NumericValue::new(7.0, 0.0, 10.0, 3.0).value(), 6.0);
+        assert_eq!(NumericValue::new(7.3, 0.0, 10.0, 0.0).value(), 7.3);
+    }
+
+    #[test]
+    fn formats_and_parses() {
+        assert_eq!(decimals_of(1.0), 0);
+        assert_eq!(decimals_of(0.25), 2);
+        assert_eq!(NumericValue::new(0.5, 0.0, 1.0, 0.1).format(), "0.5");
+        assert_eq!(parse(" 2.5 "), Some(2.5));
+        assert_eq!(parse("-3"), Some(-3.0));
+        assert_eq!(parse("abc"), None);
+        assert_eq!(parse("inf"), None);
+    }
+}
@@ -7,7 +7,15 @@ use std::{
 
 use derive_new::new;
 use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
-use gfx::{preset::blend, pso::buffer::ElemStride, state::ColorMask};
+use gfx::{
+    handle::{RawShaderResourceView, Sampler},
+    memory::Typed,
+    preset::blend,
+    pso::buffer::ElemStride,
+    state::ColorMask,
+    texture::{FilterMethod, SamplerInfo, WrapMode},
+    Factory as GfxFactory,
+};
 use gfx_glyph::{
     BuiltInLineBreaker, FontId, GlyphBrush, GlyphBrushBuilder, GlyphCruncher, Layout, Point, Scale,
     SectionText, VariedSection,
@@ -21,20 +29,26 @@ use unicode_segmentation::UnicodeSegmentation;
 use thread_profiler::profile_scope;
 
 use amethyst_assets::{AssetStorage, Handle, Loader};
-use amethyst_core::ecs::prelude::{
-    Entities, Entity, Join, Read, ReadExpect, ReadStorage, WriteStorage,
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, WriteStorage},
+    Transform,
 };
 use amethyst_error::Error;
 use amethyst_renderer::{
     pipe::{
         pass::{Pass, PassData},
-        Effect, NewEffect,
+        Effect, NewEffect, Target, Targets,
     },
-    Encoder, Factory, Hidden, HiddenPropagate, Mesh, PosTex, Resources, Rgba, ScreenDimensions,
-    Shape, Texture, TextureData, TextureHandle, TextureMetadata, VertexFormat,
+    Camera, Encoder, Factory, Hidden, HiddenPropagate, Mesh, PosTex, Resources, Rgba,
+    ScreenDimensions, Shape, TargetCamera, Texture, TextureData, TextureHandle, TextureMetadata,
+    VertexFormat,
 };
 
 use super::*;
+use crate::{
+    minimap::project_marker,
+    text_style::{StyledText, TextStyle},
+};
 
 const VERT_SRC: &[u8] = include_bytes!("shaders/vertex.glsl");
 const FRAG_SRC: &[u8] = include_bytes!("shaders/frag.glsl");
@@ -88,6 +102,12 @@ pub struct DrawUi {
     glyph_brushes: GlyphBrushCache,
     #[new(default)]
     next_brush_cache_id: u64,
+    #[new(default)]
+    styled_texts: HashMap<Entity, StyledText>,
+    #[new(default)]
+    target_views: HashMap<String, RawShaderResourceView<Resources>>,
+    #[new(default)]
+    target_sampler: Option<Sampler<Resources>>,
 }
 
 type GlyphBrushCache = HashMap<u64, GlyphBrush<'static, Resources, Factory>>;
@@ -97,6 +117,7 @@ impl<'a> PassData<'a> for DrawUi {
         Entities<'a>,
         ReadExpect<'a, Loader>,
         ReadExpect<'a, ScreenDimensions>,
+        Read<'a, UiScale>,
         Read<'a, AssetStorage<Texture>>,
         Read<'a, AssetStorage<FontAsset>>,
         ReadStorage<'a, Handle<Texture>>,
@@ -107,6 +128,10 @@ impl<'a> PassData<'a> for DrawUi {
         ReadStorage<'a, HiddenPropagate>,
         ReadStorage<'a, Selected>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, UiMinimap>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, TargetCamera>,
+        ReadStorage<'a, Transform<f32>>,
     );
 }
 
@@ -119,6 +144,11 @@ impl Pass for DrawUi {
         // Centered around (0,0) and of size 2
         let data = Shape::Plane(None).generate_vertices::<Vec<PosTex>>(None);
         self.mesh = Some(Mesh::build(data).build(&mut effect.factory)?);
+        self.target_sampler = Some(
+            effect
+                .factory
+                .create_sampler(SamplerInfo::new(FilterMethod::Bilinear, WrapMode::Clamp)),
+        );
 
         // Create the effect
         use std::mem;
@@ -144,6 +174,7 @@ impl Pass for DrawUi {
             entities,
             loader,
             screen_dimensions,
+            ui_scale,
             tex_storage,
             font_storage,
             ui_image,
@@ -154,6 +185,10 @@ impl Pass for DrawUi {
             hidden_prop,
             selecteds,
             rgba,
+            minimaps,
+            cameras,
+            target_cameras,
+            transforms,
         ): <Self as PassData<'_>>::Data,
     ) {
         #[cfg(feature = "profiler")]
@@ -227,6 +262,10 @@ impl Pass for DrawUi {
             1. / screen_dimensions.height(),
         ];
 
+        // Maps the layout of the `UiScale` onto the screen. Everything below is drawn in screen
+        // pixels.
+        let ((scale_x, scale_y), (offset_x, offset_y)) = ui_scale.projection(&screen_dimensions);
+
         let mesh = self
             .mesh
             .as_ref()
@@ -245,6 +284,7 @@ impl Pass for DrawUi {
             .iter()
             .map(|(id, _)| *id)
             .collect::<HashSet<_>>();
+        let mut unused_styled_texts = self.styled_texts.keys().cloned().collect::<HashSet<_>>();
 
         let highest_abs_z = {
             #[cfg(feature = "profiler")]
@@ -268,6 +308,10 @@ impl Pass for DrawUi {
             let ui_transform = ui_transform
                 .get(entity)
                 .expect("Unreachable: Entity is guaranteed to be present based on earlier actions");
+            let pixel_x = ui_transform.pixel_x * scale_x + offset_x;
+            let pixel_y = ui_transform.pixel_y * scale_y + offset_y;
+            let pixel_width = ui_transform.pixel_width * scale_x;
+            let pixel_height = ui_transform.pixel_height * scale_y;
             let rgba: [f32; 4] = rgba.get(entity).cloned().unwrap_or(Rgba::WHITE).into();
             if let Some(image) = ui_image
                 .get(entity)
@@ -278,8 +322,8 @@ impl Pass for DrawUi {
                 let vertex_args = VertexArgs {
                     invert_window_size: invert_window_size.into(),
                     // Coordinates are middle centered. It makes it easier to do layouting in most cases.
-                    coord: [ui_transform.pixel_x, ui_transform.pixel_y].into(),
-                    dimension: [ui_transform.pixel_width, ui_transform.pixel_height].into(),
+                    coord: [pixel_x, pixel_y].into(),
+                    dimension: [pixel_width, pixel_height].into(),
                     color: rgba.into(),
                 };
 
@@ -291,6 +335,61 @@ impl Pass for DrawUi {
                 effect.data.samplers.clear();
             }
 
+            if let Some(minimap) = minimaps.get(entity) {
+                #[cfg(feature = "profiler")]
+                profile_scope!("ui_pass_draw_uiminimap");
+                let view = target_cameras
+                    .get(minimap.camera)
+                    .and_then(|target_camera| self.target_views.get(&target_camera.target));
+                if let (Some(view), Some(sampler)) = (view, self.target_sampler.as_ref()) {
+                    let vertex_args = VertexArgs {
+                        invert_window_size: invert_window_size.into(),
+                        coord: [pixel_x, pixel_y].into(),
+                        dimension: [pixel_width, pixel_height].into(),
+                        color: rgba.into(),
+                    };
+                    effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
+                    effect.data.textures.push(view.clone());
+                    effect.data.samplers.push(sampler.clone());
+                    effect.draw(mesh.slice(), encoder);
+                    effect.data.textures.clear();
+                    effect.data.samplers.clear();
+                }
+
+                let camera = cameras
+                    .get(minimap.camera)
+                    .and_then(|camera| transforms.get(minimap.camera).map(|t| (camera, t)));
+                if let Some((camera, camera_transform)) = camera {
+                    for (_, marker) in minimap.markers() {
+                        let (x, y) =
+                            match project_marker(camera, camera_transform, &marker.position) {
+                                Some(position) => position,
+                                None => continue,
+                            };
+                        let image = match tex_storage.get(&marker.image) {
+                            Some(image) => image,
+                            None => continue,
+                        };
+                        let vertex_args = VertexArgs {
+                            invert_window_size: invert_window_size.into(),
+                            coord: [
+                                pixel_x + x * pixel_width / 2.0,
+                                pixel_y + y * pixel_height / 2.0,
+                            ]
+                            .into(),
+                            dimension: [marker.size.0 * scale_x, marker.size.1 * scale_y].into(),
+                            color: rgba.into(),
+                        };
+                        effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
+                        effect.data.textures.push(image.view().clone());
+                        effect.data.samplers.push(image.sampler().clone());
+                        effect.draw(mesh.slice(), encoder);
+                        effect.data.textures.clear();
+                        effect.data.samplers.clear();
+                    }
+                }
+            }
+
             if let Some(ui_text) = ui_text.get_mut(entity) {
                 #[cfg(feature = "profiler")]
                 profile_scope!("ui_pass_draw_uitext");
@@ -328,7 +427,10 @@ impl Pass for DrawUi {
                 let rendered_string = password_string.as_ref().unwrap_or(&ui_text.text);
                 let hidpi = screen_dimensions.hidpi_factor() as f32;
                 let size = ui_text.font_size;
-                let scale = Scale::uniform(size);
+                let scale = Scale {
+                    x: size * scale_x,
+                    y: size * scale_y,
+                };
                 let text = editing
                     .and_then(|editing| {
                         if editing.highlight_vector == 0 {
@@ -402,14 +504,13 @@ impl Pass for DrawUi {
                     // Needs a recenter because we are using [-0.5,0.5] for the mesh
                     // instead of the expected [0,1]
                     screen_position: (
-                        (ui_transform.pixel_x
-                            + ui_transform.pixel_width * ui_text.align.norm_offset().0),
+                        (pixel_x + pixel_width * ui_text.align.norm_offset().0),
                         // invert y because gfx-glyph inverts it back
                         (screen_dimensions.height()
-                            - ui_transform.pixel_y
-                            - ui_transform.pixel_height * ui_text.align.norm_offset().1),
+                            - pixel_y
+                            - pixel_height * ui_text.align.norm_offset().1),
                     ),
-                    bounds: (ui_transform.pixel_width, ui_transform.pixel_height),
+                    bounds: (pixel_width, pixel_height),
                     // Invert z because of gfx-glyph using z+ forward
                     z: ui_transform.global_z / highest_abs_z,
                     layout,
@@ -464,7 +565,7 @@ impl Pass for DrawUi {
                         .fonts()
                         .get(0)
                         .expect("Unable to get first font of brush")
-                        .v_metrics(Scale::uniform(ui_text.font_size))
+                        .v_metrics(scale)
                         .ascent;
                     for glyph in brush
                         .glyphs(&section)
@@ -494,8 +595,51 @@ impl Pass for DrawUi {
                     effect.data.textures.clear();
                     effect.data.samplers.clear();
                 }
-                // Render text
-                {
+                // Render styled text
+                let styled_texture = if editing.is_none() && ui_text.is_styled() {
+                    #[cfg(feature = "profiler")]
+                    profile_scope!("ui_pass_draw_uitext_renderstyledtext");
+                    unused_styled_texts.remove(&entity);
+                    let style = TextStyle {
+                        color: multiply_colors(ui_text.color, rgba),
+                        gradient: ui_text.gradient.map(|bottom| multiply_colors(bottom, rgba)),
+                        outline: ui_text.outline.as_ref().map(|outline| TextOutline {
+                            width: outline.width * scale_y,
+                            color: multiply_colors(outline.color, rgba),
+                        }),
+                        shadow: ui_text.shadow.as_ref().map(|shadow| TextShadow {
+                            offset: (shadow.offset.0 * scale_x, shadow.offset.1 * scale_y),
+                            color: multiply_colors(shadow.color, rgba),
+                            blur: shadow.blur * scale_y,
+                        }),
+                    };
+                    self.styled_texts
+                        .entry(entity)
+                        .or_insert_with(StyledText::default)
+                        .update(brush.glyphs(&section), style, &loader, &tex_storage)
+                } else {
+                    None
+                };
+                if let Some((texture, [left, top, width, height])) = styled_texture {
+                    let vertex_args = VertexArgs {
+                        invert_window_size: invert_window_size.into(),
+                        // The texture is placed y down like the glyphs.
+                        coord: [
+                            left + width / 2.0,
+                            screen_dimensions.height() - top - height / 2.0,
+                        ]
+                        .into(),
+                        dimension: [width, height].into(),
+                        color: [1.0, 1.0, 1.0, 1.0].into(),
+                    };
+                    effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
+                    effect.data.textures.push(texture.view().clone());
+                    effect.data.samplers.push(texture.sampler().clone());
+                    effect.draw(mesh.slice(), encoder);
+                    effect.data.textures.clear();
+                    effect.data.samplers.clear();
+                } else {
+                    // Render text
                     #[cfg(feature = "profiler")]
                     profile_scope!("ui_pass_draw_uitext_rendertext");
                     brush.queue(section.clone());
@@ -537,7 +681,7 @@ impl Pass for DrawUi {
                                     .get(0)
                                     .expect("Unable to get first font of brush")
                                     .glyph(' ')
-                                    .scaled(Scale::uniform(ui_text.font_size))
+                                    .scaled(scale)
                                     .h_metrics()
                                     .advance_width
                             } else {
@@ -548,7 +692,7 @@ impl Pass for DrawUi {
                                 .fonts()
                                 .get(0)
                                 .expect("Unable to get first font of brush")
-                                .v_metrics(Scale::uniform(ui_text.font_size))
+                                .v_metrics(scale)
                                 .ascent;
                             let glyph_len = brush.glyphs(&section).count();
                             let (glyph, at_end) = if editing.cursor_position as usize >= glyph_len {
@@ -560,25 +704,20 @@ impl Pass for DrawUi {
                                 )
                             };
                             let (height, width) = if editing.use_block_cursor {
-                                let height = if blink_on {
-                                    ui_text.font_size
-                                } else {
-                                    ui_text.font_size / 10.0
-                                };
+                                let height = if blink_on { scale.y } else { scale.y / 10.0 };
 
                                 (height, space_width)
                             } else {
-                                (ui_text.font_size, 2.0)
+                                (scale.y, 2.0)
                             };
 
                             let mut pos = glyph.map(|g| g.position()).unwrap_or(Point {
-                                x: ui_transform.pixel_x
-                                    + ui_transform.width * ui_text.align.norm_offset().0,
+                                x: pixel_x
+                                    + ui_transform.width * scale_x * ui_text.align.norm_offset().0,
                                 y: 0.0,
                             });
                             // gfx-glyph uses y down so we need to convert to y up
-                            pos.y =
-                                screen_dimensions.height() - ui_transform.pixel_y + ascent / 2.0;
+                            pos.y = screen_dimensions.height() - pixel_y + ascent / 2.0;
 
                             let mut x = pos.x;
                             if let Some(glyph) = glyph {
@@ -588,7 +727,7 @@ impl Pass for DrawUi {
                             }
                             let mut y = pos.y;
                             if editing.use_block_cursor && !blink_on {
-                                y -= ui_text.font_size * 0.9;
+                                y -= scale.y * 0.9;
                             }
                             let vertex_args = VertexArgs {
                                 invert_window_size: invert_window_size.into(),
@@ -613,6 +752,22 @@ impl Pass for DrawUi {
         for id in unused_glyph_brushes.drain() {
             self.glyph_brushes.remove(&id);
         }
+        for entity in unused_styled_texts.drain() {
+            self.styled_texts.remove(&entity);
+        }
+    }
+
+    fn new_targets(&mut self, _output: &Target, targets: &Targets) {
+        // Keep the views of all targets, for the minimaps to display.
+        self.target_views = targets
+            .iter()
+            .filter_map(|(name, target)| {
+                target
+                    .color_buf(0)
+                    .and_then(|buffer| buffer.as_input.as_ref())
+                    .map(|view| (name.clone(), view.raw().clone()))
+            })
+            .collect();
     }
 }
 
@@ -17,9 +17,10 @@ use amethyst_renderer::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    get_default_font, Anchor, FontAsset, FontFormat, Interactable, LineMode, Selectable, Stretch,
-    TextEditing, UiButton, UiButtonAction, UiButtonActionRetrigger, UiButtonActionType,
-    UiPlaySoundAction, UiSoundRetrigger, UiText, UiTransform, WidgetId, Widgets,
+    get_default_font, Accessible, Anchor, FontAsset, FontFormat, Interactable, LineMode,
+    Selectable, Stretch, TextEditing, TextOutline, TextShadow, UiButton, UiButtonAction,
+    UiButtonActionRetrigger, UiButtonActionType, UiPlaySoundAction, UiSoundRetrigger, UiText,
+    UiTransform, UiTransitions, WidgetId, Widgets,
 };
 
 /// Loadable `UiTransform` data.
@@ -66,6 +67,10 @@ pub struct UiTransformBuilder<G> {
     /// this ordering backwards.
     // TODO: Make full prefab for Selectable.
     pub selectable: Option<u32>,
+    /// Transitions played when the element is spawned, shown or hidden.
+    pub transitions: Option<UiTransitions>,
+    /// The name and role announced by screen readers.
+    pub accessible: Option<Accessible>,
     #[serde(skip)]
     _phantom: PhantomData<G>,
 }
@@ -124,6 +129,18 @@ impl<G> UiTransformBuilder<G> {
         self.stretch = Some(stretch);
         self
     }
+
+    /// Set transitions
+    pub fn with_transitions(mut self, transitions: UiTransitions) -> Self {
+        self.transitions = Some(transitions);
+        self
+    }
+
+    /// Set the name and role announced by screen readers
+    pub fn with_accessible(mut self, accessible: Accessible) -> Self {
+        self.accessible = Some(accessible);
+        self
+    }
 }
 
 impl<'a, G> PrefabData<'a> for UiTransformBuilder<G>
@@ -135,6 +152,8 @@ where
         WriteStorage<'a, Interactable>,
         WriteStorage<'a, HiddenPropagate>,
         WriteStorage<'a, Selectable<G>>,
+        WriteStorage<'a, UiTransitions>,
+        WriteStorage<'a, Accessible>,
     );
     type Result = ();
 
@@ -176,6 +195,14 @@ where
             system_data.3.insert(entity, Selectable::<G>::new(u))?;
         }
 
+        if let Some(ref transitions) = self.transitions {
+            system_data.4.insert(entity, transitions.clone())?;
+        }
+
+        if let Some(ref accessible) = self.accessible {
+            system_data.5.insert(entity, accessible.clone())?;
+        }
+
         Ok(())
     }
 }
@@ -209,6 +236,15 @@ where
     /// Optionally make the text editable
     #[serde(default)]
     pub editable: Option<TextEditingPrefab>,
+    /// A drop shadow drawn behind the text
+    #[serde(default)]
+    pub shadow: Option<TextShadow>,
+    /// An outline drawn around the glyphs
+    #[serde(default)]
+    pub outline: Option<TextOutline>,
+    /// The color at the bottom of the glyphs, for a vertical gradient from `color`
+    #[serde(default)]
+    pub gradient: Option<[f32; 4]>,
 }
 
 /// Loadable `TextEditing` data
@@ -271,6 +307,10 @@ where
             ui_text.line_mode = line_mode.clone();
         }
 
+        ui_text.shadow = self.shadow.clone();
+        ui_text.outline = self.outline.clone();
+        ui_text.gradient = self.gradient;
+
         texts.insert(entity, ui_text)?;
         if let Some(ref editing) = self.editable {
             editables.insert(
@@ -842,6 +882,9 @@ fn walk_ui_tree<A, I, F, C, W>(
                 line_mode: None,
                 text: button.text.clone(),
                 font_size: button.font_size,
+                shadow: None,
+                outline: None,
+                gradient: None,
             };
 
             prefab
@@ -0,0 +1,107 @@
+use std::fmt::Write as _;
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity, Read, ReadExpect, System, WriteStorage},
+    SystemProfile,
+};
+
+use crate::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform};
+
+const WIDTH: f32 = 420.0;
+const HEIGHT: f32 = 400.0;
+const FONT_SIZE: f32 = 14.0;
+
+/// Shows the slowest systems of the `SystemProfile` in the top left corner of the screen.
+///
+/// The overlay is a `UiText` entity using the default font, which is created on the first run and
+/// can be hidden like any other ui entity. Only systems profiled with `SystemExt::profiled`, for
+/// example through `GameDataBuilder::with_profiling`, are listed.
+pub struct SystemProfileOverlaySystem {
+    count: usize,
+    entity: Option<Entity>,
+}
+
+impl SystemProfileOverlaySystem {
+    /// Creates an overlay listing the `count` slowest systems.
+    pub fn new(count: usize) -> Self {
+        SystemProfileOverlaySystem {
+            count,
+            entity: None,
+        }
+    }
+}
+
+impl Default for SystemProfileOverlaySystem {
+    fn default() -> Self {
+        SystemProfileOverlaySystem::new(10)
+    }
+}
+
+impl<'a> System<'a> for SystemProfileOverlaySystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, SystemProfile>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<FontAsset>>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, profile, loader, fonts, mut transforms, mut texts): Self::SystemData,
+    ) {
+        let entity = match self.entity {
+            Some(entity) if entities.is_alive(entity) => entity,
+            _ => {
+                let entity = entities.create();
+                let transform = UiTransform::new(
+                    "system_profile_overlay".to_string(),
+                    Anchor::TopLeft,
+                    WIDTH / 2.0 + 10.0,
+                    -HEIGHT / 2.0 - 10.0,
+                    100.0,
+                    WIDTH,
+                    HEIGHT,
+                );
+                let mut text = UiText::new(
+                    get_default_font(&loader, &fonts),
+                    String::new(),
+                    [1.0, 1.0, 1.0, 1.0],
+                    FONT_SIZE,
+                );
+                text.line_mode = LineMode::Wrap;
+                text.align = Anchor::TopLeft;
+                transforms
+                    .insert(entity, transform)
+                    .expect("Unreachable: Entity was just created");
+                texts
+                    .insert(entity, text)
+                    .expect("Unreachable: Entity was just created");
+                self.entity = Some(entity);
+                entity
+            }
+        };
+
+        if let Some(text) = texts.get_mut(entity) {
+            text.text.clear();
+            let dispatch = profile.dispatch();
+            let _ = writeln!(
+                text.text,
+                "dispatch: {:.2} ms (max {:.2} ms)",
+                dispatch.average * 1000.0,
+                dispatch.max * 1000.0
+            );
+            for (name, timing) in profile.slowest(self.count) {
+                let _ = writeln!(
+                    text.text,
+                    "{}: {:.2} ms (max {:.2} ms)",
+                    name,
+                    timing.average * 1000.0,
+                    timing.max * 1000.0
+                );
+            }
+        }
+    }
+}
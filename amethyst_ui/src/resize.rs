@@ -1,7 +1,7 @@
 use amethyst_core::{
     ecs::prelude::{
-        BitSet, Component, ComponentEvent, FlaggedStorage, Join, ReadExpect, Resources, System,
-        WriteStorage,
+        BitSet, Component, ComponentEvent, FlaggedStorage, Join, Read, ReadExpect, Resources,
+        System, WriteStorage,
     },
     shrev::ReaderId,
 };
@@ -10,7 +10,8 @@ use amethyst_renderer::ScreenDimensions;
 use super::*;
 
 /// Whenever the window is resized the function in this component will be called on this
-/// entity's UiTransform, along with the new width and height of the window.
+/// entity's UiTransform, along with the new width and height of the window, or of the reference
+/// size of the `UiScale` when the interface is scaled.
 ///
 /// The function in this component is also guaranteed to be called at least once by the
 /// `ResizeSystem` when either the component is attached, or the function is changed.
@@ -56,9 +57,10 @@ impl<'a> System<'a> for ResizeSystem {
         WriteStorage<'a, UiTransform>,
         WriteStorage<'a, UiResize>,
         ReadExpect<'a, ScreenDimensions>,
+        Read<'a, UiScale>,
     );
 
-    fn run(&mut self, (mut transform, mut resize, dimensions): Self::SystemData) {
+    fn run(&mut self, (mut transform, mut resize, dimensions, ui_scale): Self::SystemData) {
         self.local_modified.clear();
 
         let self_local_modified = &mut self.local_modified;
@@ -77,7 +79,7 @@ impl<'a> System<'a> for ResizeSystem {
                 ComponentEvent::Removed(_id) => {}
             });
 
-        let screen_size = (dimensions.width() as f32, dimensions.height() as f32);
+        let screen_size = ui_scale.virtual_size(&dimensions);
         if self.screen_size != screen_size {
             self.screen_size = screen_size;
             for (transform, resize) in (&mut transform, &mut resize).join() {
@@ -0,0 +1,164 @@
+//! Scaling of the whole user interface to the size of the screen.
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_renderer::ScreenDimensions;
+
+/// How the user interface is mapped onto the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UiScaleMode {
+    /// The interface is laid out in screen pixels, ignoring the reference size.
+    Native,
+    /// The interface is laid out at the reference size and stretched to fill the screen, which
+    /// distorts it when the aspect ratio differs.
+    Stretch,
+    /// The interface is laid out at the reference size and scaled uniformly to fit into the
+    /// screen, centered, leaving empty bars on the sides that don't fit.
+    Letterbox,
+    /// Like `Letterbox`, but only scales by whole numbers so each reference pixel covers the same
+    /// number of screen pixels. The interface is never scaled below its reference size.
+    PixelPerfect,
+}
+
+impl Default for UiScaleMode {
+    fn default() -> Self {
+        UiScaleMode::Native
+    }
+}
+
+/// The resource selecting how the user interface follows the size of the screen.
+///
+/// With any mode other than `UiScaleMode::Native`, the `UiTransform`s of the root entities are
+/// laid out in a `reference` sized screen, which `DrawUi` scales to the real screen, and mouse
+/// positions are mapped back to it. A HUD authored for 1280x720 can for instance keep its layout
+/// on any window with:
+///
+/// ```rust,ignore
+/// world.add_resource(UiScale::new(UiScaleMode::Letterbox, 1280.0, 720.0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UiScale {
+    /// How the reference size is mapped onto the screen.
+    pub mode: UiScaleMode,
+    /// The width and height the interface was authored for.
+    pub reference: (f32, f32),
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        UiScale {
+            mode: UiScaleMode::Native,
+            reference: (1280.0, 720.0),
+        }
+    }
+}
+
+impl UiScale {
+    /// Creates a `UiScale` mapping a `width` by `height` interface onto the screen with `mode`.
+    pub fn new(mode: UiScaleMode, width: f32, height: f32) -> Self {
+        UiScale {
+            mode,
+            reference: (width, height),
+        }
+    }
+
+    /// The size of the screen the interface is laid out in.
+    pub fn virtual_size(&self, screen: &ScreenDimensions) -> (f32, f32) {
+        self.virtual_size_for((screen.width(), screen.height()))
+    }
+
+    /// The scale on each axis and the offset, in screen pixels, mapping the interface onto the
+    /// screen.
+    pub fn projection(&self, screen: &ScreenDimensions) -> ((f32, f32), (f32, f32)) {
+        self.projection_for((screen.width(), screen.height()))
+    }
+
+    /// Maps a point of the interface to screen pixels.
+    pub fn to_screen(&self, screen: &ScreenDimensions, point: (f32, f32)) -> (f32, f32) {
+        let ((scale_x, scale_y), (offset_x, offset_y)) = self.projection(screen);
+        (point.0 * scale_x + offset_x, point.1 * scale_y + offset_y)
+    }
+
+    /// Maps a point in screen pixels, with y up, to the interface.
+    pub fn to_virtual(&self, screen: &ScreenDimensions, point: (f32, f32)) -> (f32, f32) {
+        let ((scale_x, scale_y), (offset_x, offset_y)) = self.projection(screen);
+        (
+            (point.0 - offset_x) / scale_x,
+            (point.1 - offset_y) / scale_y,
+        )
+    }
+
+    fn virtual_size_for(&self, screen: (f32, f32)) -> (f32, f32) {
+        match self.mode {
+            UiScaleMode::Native => screen,
+            _ => self.reference,
+        }
+    }
+
+    fn projection_for(&self, screen: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+        let (width, height) = self.reference;
+        let fit = f32::min(screen.0 / width, screen.1 / height);
+        let uniform = |scale: f32, round: bool| {
+            let offset = (
+                (screen.0 - width * scale) / 2.0,
+                (screen.1 - height * scale) / 2.0,
+            );
+            let offset = if round {
+                (offset.0.floor(), offset.1.floor())
+            } else {
+                offset
+            };
+            ((scale, scale), offset)
+        };
+        match self.mode {
+            UiScaleMode::Native => ((1.0, 1.0), (0.0, 0.0)),
+            UiScaleMode::Stretch => ((screen.0 / width, screen.1 / height), (0.0, 0.0)),
+            UiScaleMode::Letterbox => uniform(fit, false),
+            UiScaleMode::PixelPerfect => uniform(fit.floor().max(1.0), true),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_the_reference_size() {
+        let screen = (1920.0, 1200.0);
+        let scale = |mode| UiScale::new(mode, 1280.0, 720.0);
+
+        assert_eq!(
+            scale(UiScaleMode::Native).virtual_size_for(screen),
+            (1920.0, 1200.0)
+        );
+        assert_eq!(
+            scale(UiScaleMode::Native).projection_for(screen),
+            ((1.0, 1.0), (0.0, 0.0))
+        );
+        assert_eq!(
+            scale(UiScaleMode::Letterbox).virtual_size_for(screen),
+            (1280.0, 720.0)
+        );
+        assert_eq!(
+            scale(UiScaleMode::Stretch).projection_for(screen),
+            ((1.5, 1200.0 / 720.0), (0.0, 0.0))
+        );
+        assert_eq!(
+            scale(UiScaleMode::Letterbox).projection_for(screen),
+            ((1.5, 1.5), (0.0, 60.0))
+        );
+        assert_eq!(
+            scale(UiScaleMode::PixelPerfect).projection_for(screen),
+            ((1.0, 1.0), (320.0, 240.0))
+        );
+        assert_eq!(
+            scale(UiScaleMode::PixelPerfect).projection_for((2600.0, 1500.0)),
+            ((2.0, 2.0), (20.0, 30.0))
+        );
+        assert_eq!(
+            scale(UiScaleMode::PixelPerfect).projection_for((800.0, 600.0)),
+            ((1.0, 1.0), (-240.0, -60.0))
+        );
+    }
+}
@@ -1,17 +1,17 @@
 use amethyst_assets::AssetStorage;
-use amethyst_audio::{output::Output, Source, SourceHandle};
+use amethyst_audio::{output::Output, AudioBus, AudioMixer, Source, SourceHandle};
 use amethyst_core::{
     ecs::{
         prelude::{Component, DenseVecStorage},
-        Read, Resources, System, SystemData, Write,
+        Read, ReadStorage, Resources, System, SystemData, Write,
     },
     shrev::{EventChannel, ReaderId},
 };
 
 use crate::{
-    event::{UiEvent, UiEventType::*},
+    event::{Interactable, UiEvent, UiEventType::*},
     event_retrigger::{EventRetrigger, EventRetriggerSystem},
-    EventReceiver,
+    EventReceiver, NumericInputEvent, NumericInputEventType,
 };
 
 /// Provides an `EventRetriggerSystem` that will handle incoming `UiEvent`s
@@ -63,6 +63,112 @@ impl EventRetrigger for UiSoundRetrigger {
     }
 }
 
+/// The sounds played for all interactive widgets, as a resource.
+///
+/// A sound of the theme is played for an `Interactable` entity unless it has a `UiSoundRetrigger`
+/// with its own sound for the same event, such as a button built with
+/// `UiButtonBuilder::with_hover_sound`.
+#[derive(Debug, Clone, Default)]
+pub struct UiSoundTheme {
+    /// The sound that is played when the user starts hovering over a widget
+    pub hover: Option<UiPlaySoundAction>,
+    /// The sound that is played when the user begins a click on a widget
+    pub press: Option<UiPlaySoundAction>,
+    /// The sound that is played when the user ends a click on a widget
+    pub release: Option<UiPlaySoundAction>,
+    /// The sound that is played when the input of the user is rejected or clamped, like text
+    /// which isn't a number in a `UiNumericInput`
+    pub error: Option<UiPlaySoundAction>,
+    /// The sound that is played when the value of a `UiNumericInput` changes
+    pub slider_tick: Option<UiPlaySoundAction>,
+}
+
+/// Plays the sounds of the `UiSoundTheme` resource, if there is one, for the `UiEvent`s and
+/// `NumericInputEvent`s.
+#[derive(Default)]
+pub struct UiSoundThemeSystem {
+    ui_reader: Option<ReaderId<UiEvent>>,
+    numeric_reader: Option<ReaderId<NumericInputEvent>>,
+}
+
+impl UiSoundThemeSystem {
+    /// Constructs a default `UiSoundThemeSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'s> System<'s> for UiSoundThemeSystem {
+    type SystemData = (
+        Read<'s, EventChannel<UiEvent>>,
+        Read<'s, EventChannel<NumericInputEvent>>,
+        ReadStorage<'s, Interactable>,
+        ReadStorage<'s, UiSoundRetrigger>,
+        Option<Read<'s, UiSoundTheme>>,
+        Write<'s, EventChannel<UiPlaySoundAction>>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.ui_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+        self.numeric_reader = Some(
+            res.fetch_mut::<EventChannel<NumericInputEvent>>()
+                .register_reader(),
+        );
+    }
+
+    fn run(
+        &mut self,
+        (ui_events, numeric_events, interactables, retriggers, theme, mut sounds): Self::SystemData,
+    ) {
+        let ui_events = ui_events.read(
+            self.ui_reader
+                .as_mut()
+                .expect("`UiSoundThemeSystem::setup` was not called before `run`"),
+        );
+        let numeric_events = numeric_events.read(
+            self.numeric_reader
+                .as_mut()
+                .expect("`UiSoundThemeSystem::setup` was not called before `run`"),
+        );
+        // The events are read even without a theme, so the old ones aren't played once it is added.
+        let theme = match theme {
+            Some(theme) => theme,
+            None => return,
+        };
+
+        for event in ui_events {
+            if !interactables.contains(event.target) {
+                continue;
+            }
+            let retrigger = retriggers.get(event.target);
+            let (sound, own_sound) = match event.event_type {
+                HoverStart => (&theme.hover, retrigger.map(|r| &r.on_hover_start)),
+                ClickStart => (&theme.press, retrigger.map(|r| &r.on_click_start)),
+                ClickStop => (&theme.release, retrigger.map(|r| &r.on_click_stop)),
+                _ => continue,
+            };
+            if own_sound.map_or(true, Option::is_none) {
+                if let Some(sound) = sound {
+                    sounds.single_write(sound.clone());
+                }
+            }
+        }
+
+        for event in numeric_events {
+            let sound = match event.event_type {
+                NumericInputEventType::Changed => &theme.slider_tick,
+                NumericInputEventType::Clamped { .. } | NumericInputEventType::Rejected { .. } => {
+                    &theme.error
+                }
+            };
+            if let Some(sound) = sound {
+                sounds.single_write(sound.clone());
+            }
+        }
+    }
+}
+
 /// Handles any dispatches `UiPlaySoundAction`s and plays the received
 /// sounds through the set `Output`.
 #[derive(Default)]
@@ -84,6 +190,7 @@ impl<'s> System<'s> for UiSoundSystem {
         Write<'s, EventChannel<UiPlaySoundAction>>,
         Read<'s, AssetStorage<Source>>,
         Option<Read<'s, Output>>,
+        Option<Read<'s, AudioMixer>>,
     );
 
     fn setup(&mut self, res: &mut Resources) {
@@ -94,7 +201,7 @@ impl<'s> System<'s> for UiSoundSystem {
         );
     }
 
-    fn run(&mut self, (sound_events, audio_storage, audio_output): Self::SystemData) {
+    fn run(&mut self, (sound_events, audio_storage, audio_output, mixer): Self::SystemData) {
         let event_reader = self
             .event_reader
             .as_mut()
@@ -103,7 +210,10 @@ impl<'s> System<'s> for UiSoundSystem {
         for event in sound_events.read(event_reader) {
             if let Some(output) = audio_output.as_ref() {
                 if let Some(sound) = audio_storage.get(&event.0) {
-                    output.play_once(sound, 1.0);
+                    match mixer {
+                        Some(ref mixer) => output.play_on_bus(sound, 1.0, AudioBus::Sfx, mixer),
+                        None => output.play_once(sound, 1.0),
+                    }
                 }
             }
         }
@@ -27,6 +27,47 @@ pub enum LineMode {
     Wrap,
 }
 
+/// A drop shadow drawn behind a `UiText`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TextShadow {
+    /// The offset of the shadow from the text in pixels, with y up.
+    pub offset: (f32, f32),
+    /// The color of the shadow.
+    pub color: [f32; 4],
+    /// The radius of the blur of the shadow in pixels, 0 for a sharp shadow.
+    pub blur: f32,
+}
+
+impl Default for TextShadow {
+    fn default() -> Self {
+        TextShadow {
+            offset: (2.0, -2.0),
+            color: [0.0, 0.0, 0.0, 0.75],
+            blur: 0.0,
+        }
+    }
+}
+
+/// An outline drawn around the glyphs of a `UiText`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TextOutline {
+    /// The width of the outline in pixels.
+    pub width: f32,
+    /// The color of the outline.
+    pub color: [f32; 4],
+}
+
+impl Default for TextOutline {
+    fn default() -> Self {
+        TextOutline {
+            width: 1.0,
+            color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
 /// A component used to display text in this entity's UiTransform
 #[derive(Clone, Derivative, Serialize)]
 #[derivative(Debug)]
@@ -46,6 +87,16 @@ pub struct UiText {
     pub line_mode: LineMode,
     /// How to align the text within its `UiTransform`.
     pub align: Anchor,
+    /// A drop shadow drawn behind the text.
+    pub shadow: Option<TextShadow>,
+    /// An outline drawn around the glyphs, below the fill and above the shadow.
+    pub outline: Option<TextOutline>,
+    /// The color at the bottom of the glyphs, blending vertically from `color` at the top.
+    ///
+    /// Texts with a shadow, outline or gradient are drawn from a texture which is rendered
+    /// again whenever the text changes, so avoid styling texts which change every frame. They are
+    /// drawn without the styles while edited.
+    pub gradient: Option<[f32; 4]>,
     /// Cached FontHandle, used to detect changes to the font.
     #[serde(skip)]
     pub(crate) cached_font: FontHandle,
@@ -76,11 +127,37 @@ impl UiText {
             password: false,
             line_mode: LineMode::Single,
             align: Anchor::Middle,
+            shadow: None,
+            outline: None,
+            gradient: None,
             cached_font: font,
             cached_glyphs: Vec::new(),
             brush_id: None,
         }
     }
+
+    /// Draws a drop shadow behind the text.
+    pub fn with_shadow(mut self, shadow: TextShadow) -> Self {
+        self.shadow = Some(shadow);
+        self
+    }
+
+    /// Draws an outline around the glyphs.
+    pub fn with_outline(mut self, width: f32, color: [f32; 4]) -> Self {
+        self.outline = Some(TextOutline { width, color });
+        self
+    }
+
+    /// Fills the glyphs with a vertical gradient from `color` at the top to `bottom`.
+    pub fn with_gradient(mut self, bottom: [f32; 4]) -> Self {
+        self.gradient = Some(bottom);
+        self
+    }
+
+    /// Whether the text has a shadow, outline or gradient.
+    pub(crate) fn is_styled(&self) -> bool {
+        self.shadow.is_some() || self.outline.is_some() || self.gradient.is_some()
+    }
 }
 
 impl Component for UiText {
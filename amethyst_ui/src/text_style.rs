@@ -0,0 +1,393 @@
+//! Rendering of the shadow, outline and gradient of a `UiText` into an image.
+
+use gfx_glyph::PositionedGlyph;
+
+use amethyst_assets::{AssetStorage, Loader};
+use amethyst_renderer::{Texture, TextureData, TextureHandle, TextureMetadata};
+
+use crate::{TextOutline, TextShadow};
+
+/// The texture of a styled `UiText`, cached by `DrawUi` until the glyphs or styles change.
+#[derive(Default)]
+pub(crate) struct StyledText {
+    key: Option<StyledTextKey>,
+    current: Option<StyledTexture>,
+    pending: Option<StyledTexture>,
+}
+
+#[derive(PartialEq)]
+struct StyledTextKey {
+    glyphs: Vec<(u32, [f32; 4])>,
+    style: TextStyle,
+}
+
+struct StyledTexture {
+    handle: TextureHandle,
+    bounds: [f32; 4],
+}
+
+impl StyledText {
+    /// Renders the glyphs again if they or the style changed, and returns the latest loaded
+    /// texture with its left, top, width and height in screen pixels, y down.
+    ///
+    /// The previous texture is returned until the new one is loaded.
+    pub fn update<'a, 'b: 'a, 's, I>(
+        &mut self,
+        glyphs: I,
+        style: TextStyle,
+        loader: &Loader,
+        storage: &'s AssetStorage<Texture>,
+    ) -> Option<(&'s Texture, [f32; 4])>
+    where
+        I: IntoIterator<Item = &'a PositionedGlyph<'b>>,
+    {
+        let glyphs = glyphs.into_iter().collect::<Vec<_>>();
+        let key = StyledTextKey {
+            glyphs: glyphs
+                .iter()
+                .map(|glyph| {
+                    let (position, scale) = (glyph.position(), glyph.scale());
+                    (glyph.id().0, [position.x, position.y, scale.x, scale.y])
+                })
+                .collect(),
+            style,
+        };
+        if self.key.as_ref() != Some(&key) {
+            self.pending = GlyphMask::new(glyphs.iter().cloned(), &key.style).map(|mask| {
+                let metadata =
+                    TextureMetadata::unorm().with_size(mask.width as u16, mask.height as u16);
+                let data = TextureData::U8(mask.compose(&key.style), metadata);
+                StyledTexture {
+                    handle: loader.load_from_data(data, (), storage),
+                    bounds: [
+                        mask.left as f32,
+                        mask.top as f32,
+                        mask.width as f32,
+                        mask.height as f32,
+                    ],
+                }
+            });
+            if self.pending.is_none() {
+                // Nothing is visible.
+                self.current = None;
+            }
+            self.key = Some(key);
+        }
+        let loaded = self
+            .pending
+            .as_ref()
+            .map(|pending| storage.get(&pending.handle).is_some())
+            .unwrap_or(false);
+        if loaded {
+            self.current = self.pending.take();
+        }
+        self.current.as_ref().and_then(|current| {
+            storage
+                .get(&current.handle)
+                .map(|texture| (texture, current.bounds))
+        })
+    }
+}
+
+/// The styles of a `UiText`, in screen pixels and multiplied by the tint of the entity.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TextStyle {
+    pub color: [f32; 4],
+    pub gradient: Option<[f32; 4]>,
+    pub outline: Option<TextOutline>,
+    pub shadow: Option<TextShadow>,
+}
+
+impl TextStyle {
+    /// The pixels needed around the glyphs for the outline and shadow.
+    fn padding(&self) -> usize {
+        let outline = self
+            .outline
+            .as_ref()
+            .map(|o| o.width.max(0.0))
+            .unwrap_or(0.0);
+        let shadow = self
+            .shadow
+            .as_ref()
+            .map(|s| s.blur.max(0.0) + s.offset.0.abs().max(s.offset.1.abs()))
+            .unwrap_or(0.0);
+        (outline + shadow).ceil() as usize + 1
+    }
+}
+
+/// The coverage of the glyphs of a text, with room for its styles.
+pub(crate) struct GlyphMask {
+    /// The screen position of the top left pixel, with y down like the glyphs.
+    pub left: i32,
+    pub top: i32,
+    pub width: usize,
+    pub height: usize,
+    padding: usize,
+    coverage: Vec<f32>,
+}
+
+impl GlyphMask {
+    /// Rasterizes the glyphs, or returns `None` if none of them are visible.
+    pub fn new<'a, 'b: 'a, I>(glyphs: I, style: &TextStyle) -> Option<GlyphMask>
+    where
+        I: IntoIterator<Item = &'a PositionedGlyph<'b>>,
+    {
+        let glyphs = glyphs
+            .into_iter()
+            .filter_map(|glyph| glyph.pixel_bounding_box().map(|bounds| (glyph, bounds)))
+            .collect::<Vec<_>>();
+        let left = glyphs.iter().map(|(_, b)| b.min.x).min()?;
+        let top = glyphs.iter().map(|(_, b)| b.min.y).min()?;
+        let right = glyphs.iter().map(|(_, b)| b.max.x).max()?;
+        let bottom = glyphs.iter().map(|(_, b)| b.max.y).max()?;
+
+        let padding = style.padding();
+        let mut mask = GlyphMask::empty((right - left) as usize, (bottom - top) as usize, padding);
+        mask.left = left - padding as i32;
+        mask.top = top - padding as i32;
+        for (glyph, bounds) in glyphs {
+            let x0 = (bounds.min.x - mask.left) as usize;
+            let y0 = (bounds.min.y - mask.top) as usize;
+            let coverage = &mut mask.coverage;
+            let width = mask.width;
+            glyph.draw(|x, y, value| {
+                let pixel = &mut coverage[(y0 + y as usize) * width + x0 + x as usize];
+                *pixel = pixel.max(value);
+            });
+        }
+        Some(mask)
+    }
+
+    fn empty(glyphs_width: usize, glyphs_height: usize, padding: usize) -> GlyphMask {
+        let width = glyphs_width + padding * 2;
+        let height = glyphs_height + padding * 2;
+        GlyphMask {
+            left: 0,
+            top: 0,
+            width,
+            height,
+            padding,
+            coverage: vec![0.0; width * height],
+        }
+    }
+
+    /// Composes the shadow, outline and fill into RGBA pixels, with the top row first.
+    pub fn compose(&self, style: &TextStyle) -> Vec<u8> {
+        let outline = style
+            .outline
+            .as_ref()
+            .map(|outline| (dilate(self, outline.width), outline.color));
+        let shadow = style.shadow.as_ref().map(|shadow| {
+            let base = outline
+                .as_ref()
+                .map(|(coverage, _)| &coverage[..])
+                .unwrap_or(&self.coverage[..]);
+            // The shadow offset is y up, the mask y down.
+            let shifted = shift(
+                base,
+                self.width,
+                self.height,
+                shadow.offset.0.round() as i32,
+                -shadow.offset.1.round() as i32,
+            );
+            let radius = shadow.blur.round().max(0.0) as usize;
+            (
+                blur(&shifted, self.width, self.height, radius),
+                shadow.color,
+            )
+        });
+
+        let glyph_rows = (self.height - self.padding * 2).max(2) as f32 - 1.0;
+        let mut pixels = Vec::with_capacity(self.coverage.len() * 4);
+        for y in 0..self.height {
+            let t = ((y as f32 - self.padding as f32) / glyph_rows)
+                .max(0.0)
+                .min(1.0);
+            let fill = match style.gradient {
+                Some(bottom) => lerp(style.color, bottom, t),
+                None => style.color,
+            };
+            for x in 0..self.width {
+                let i = y * self.width + x;
+                // Premultiplied colors, drawn back to front.
+                let mut pixel = [0.0; 4];
+                let layers = shadow
+                    .iter()
+                    .chain(outline.iter())
+                    .map(|(coverage, color)| (coverage[i], *color))
+                    .chain(Some((self.coverage[i], fill)));
+                for (coverage, color) in layers {
+                    let alpha = coverage * color[3];
+                    for c in 0..3 {
+                        pixel[c] = color[c] * alpha + pixel[c] * (1.0 - alpha);
+                    }
+                    pixel[3] = alpha + pixel[3] * (1.0 - alpha);
+                }
+                let alpha = pixel[3];
+                for c in 0..3 {
+                    let value = if alpha > 0.0 { pixel[c] / alpha } else { 0.0 };
+                    pixels.push(to_u8(value));
+                }
+                pixels.push(to_u8(alpha));
+            }
+        }
+        pixels
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn lerp(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+/// Grows the coverage by `radius` pixels, with an antialiased edge.
+fn dilate(mask: &GlyphMask, radius: f32) -> Vec<f32> {
+    let reach = radius.ceil().max(0.0) as i32 + 1;
+    let (width, height) = (mask.width as i32, mask.height as i32);
+    let mut dilated = vec![0.0f32; mask.coverage.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let coverage = mask.coverage[(y * width + x) as usize];
+            if coverage == 0.0 {
+                continue;
+            }
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        continue;
+                    }
+                    let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                    let edge = (radius + 1.0 - distance).max(0.0).min(1.0);
+                    let pixel = &mut dilated[(ny * width + nx) as usize];
+                    *pixel = pixel.max(coverage * edge);
+                }
+            }
+        }
+    }
+    dilated
+}
+
+/// Moves the coverage by whole pixels, dropping what moves out.
+fn shift(coverage: &[f32], width: usize, height: usize, dx: i32, dy: i32) -> Vec<f32> {
+    let mut shifted = vec![0.0; coverage.len()];
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let (sx, sy) = (x - dx, y - dy);
+            if sx >= 0 && sy >= 0 && sx < width as i32 && sy < height as i32 {
+                shifted[(y as usize) * width + x as usize] =
+                    coverage[(sy as usize) * width + sx as usize];
+            }
+        }
+    }
+    shifted
+}
+
+/// Two passes of a box blur with the given radius on each axis, close to a gaussian blur.
+fn blur(coverage: &[f32], width: usize, height: usize, radius: usize) -> Vec<f32> {
+    if radius == 0 {
+        return coverage.to_vec();
+    }
+    let mut blurred = coverage.to_vec();
+    for _ in 0..2 {
+        blurred = box_blur(&blurred, width, height, radius, 1, width);
+        blurred = box_blur(&blurred, height, width, radius, width, 1);
+    }
+    blurred
+}
+
+/// Blurs the `lines` lines of `length` values, where `step` separates the values of a line and
+/// `line_step` the lines.
+fn box_blur(
+    values: &[f32],
+    length: usize,
+    lines: usize,
+    radius: usize,
+    step: usize,
+    line_step: usize,
+) -> Vec<f32> {
+    let mut blurred = vec![0.0; values.len()];
+    let size = (radius * 2 + 1) as f32;
+    for line in 0..lines {
+        let start = line * line_step;
+        for i in 0..length {
+            let first = i.saturating_sub(radius);
+            let last = (i + radius).min(length - 1);
+            let sum = (first..=last)
+                .map(|j| values[start + j * step])
+                .sum::<f32>();
+            blurred[start + i * step] = sum / size;
+        }
+    }
+    blurred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A mask with a filled square of glyph pixels.
+    fn square(size: usize, style: &TextStyle) -> GlyphMask {
+        let mut mask = GlyphMask::empty(size, size, style.padding());
+        for y in 0..size {
+            for x in 0..size {
+                mask.coverage[(y + mask.padding) * mask.width + x + mask.padding] = 1.0;
+            }
+        }
+        mask
+    }
+
+    fn pixel(mask: &GlyphMask, pixels: &[u8], x: usize, y: usize) -> [u8; 4] {
+        let i = (y * mask.width + x) * 4;
+        [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+    }
+
+    #[test]
+    fn fills_with_a_vertical_gradient() {
+        let style = TextStyle {
+            color: [1.0, 0.0, 0.0, 1.0],
+            gradient: Some([0.0, 0.0, 1.0, 1.0]),
+            outline: None,
+            shadow: None,
+        };
+        let mask = square(4, &style);
+        let pixels = mask.compose(&style);
+        let (first, last) = (mask.padding, mask.padding + 3);
+        assert_eq!(pixel(&mask, &pixels, first, first), [255, 0, 0, 255]);
+        assert_eq!(pixel(&mask, &pixels, first, last), [0, 0, 255, 255]);
+        assert_eq!(pixel(&mask, &pixels, 0, 0)[3], 0);
+    }
+
+    #[test]
+    fn draws_the_outline_and_shadow_around_the_fill() {
+        let style = TextStyle {
+            color: [1.0, 1.0, 1.0, 1.0],
+            gradient: None,
+            outline: Some(TextOutline {
+                width: 1.0,
+                color: [0.0, 1.0, 0.0, 1.0],
+            }),
+            shadow: Some(TextShadow {
+                offset: (3.0, -3.0),
+                color: [0.0, 0.0, 0.0, 1.0],
+                blur: 0.0,
+            }),
+        };
+        let mask = square(4, &style);
+        let pixels = mask.compose(&style);
+        let (first, last) = (mask.padding, mask.padding + 3);
+        assert_eq!(pixel(&mask, &pixels, first, first), [255, 255, 255, 255]);
+        assert_eq!(pixel(&mask, &pixels, first - 1, first), [0, 255, 0, 255]);
+        // The shadow is offset to the right and down, past the outline.
+        assert_eq!(pixel(&mask, &pixels, last + 3, last + 3), [0, 0, 0, 255]);
+        assert_eq!(pixel(&mask, &pixels, first - 2, first - 2)[3], 0);
+    }
+}
@@ -0,0 +1,423 @@
+//! Enter and exit transitions of widgets.
+
+use fnv::FnvHashMap as HashMap;
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        BitSet, Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReadStorage,
+        System, WriteStorage,
+    },
+    timing::Time,
+    ParentHierarchy,
+};
+use amethyst_renderer::{Hidden, HiddenPropagate, Rgba};
+
+use super::UiTransform;
+
+/// A change of a widget animated by a `UiTransition`, described from the hidden state.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum UiTransitionEffect {
+    /// Fades the widget and its children in from transparent, through their `Rgba`.
+    Fade,
+    /// Slides the widget in from this offset of its position.
+    Slide(f32, f32),
+    /// Grows the widget from this fraction of its size.
+    Scale(f32),
+}
+
+/// The speed curve of a `UiTransition`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum UiEasing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly and accelerates.
+    EaseIn,
+    /// Starts fast and decelerates.
+    EaseOut,
+    /// Starts and ends slowly.
+    EaseInOut,
+}
+
+impl Default for UiEasing {
+    fn default() -> Self {
+        UiEasing::EaseOut
+    }
+}
+
+impl UiEasing {
+    /// Maps the elapsed fraction of a transition to the fraction of its change.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            UiEasing::Linear => t,
+            UiEasing::EaseIn => t * t * t,
+            UiEasing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t) * (1.0 - t),
+            UiEasing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - 4.0 * (1.0 - t) * (1.0 - t) * (1.0 - t)
+                }
+            }
+        }
+    }
+}
+
+/// An animation of some `UiTransitionEffect`s played when a widget is shown or hidden.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiTransition {
+    /// The effects, which play together.
+    pub effects: Vec<UiTransitionEffect>,
+    /// The length of the transition in seconds.
+    pub duration: f32,
+    /// The time in seconds before the transition starts. Increase it across the widgets of a menu
+    /// to stagger them, see `UiTransition::staggered`.
+    pub delay: f32,
+    /// The speed curve.
+    pub easing: UiEasing,
+}
+
+impl Default for UiTransition {
+    fn default() -> Self {
+        UiTransition {
+            effects: vec![UiTransitionEffect::Fade],
+            duration: 0.25,
+            delay: 0.0,
+            easing: UiEasing::default(),
+        }
+    }
+}
+
+impl UiTransition {
+    /// Creates a transition playing `effects` for `duration` seconds.
+    pub fn new(effects: Vec<UiTransitionEffect>, duration: f32) -> Self {
+        UiTransition {
+            effects,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the delay before the transition starts.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the speed curve.
+    pub fn with_easing(mut self, easing: UiEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Delays the transition of the `index`th widget of a list by `step` seconds per widget.
+    pub fn staggered(mut self, index: usize, step: f32) -> Self {
+        self.delay += index as f32 * step;
+        self
+    }
+}
+
+/// The transitions of a widget, played by the `UiTransitionSystem` when the widget is spawned
+/// visible or shown by removing its `Hidden` or `HiddenPropagate` component, and when it is
+/// hidden by adding one of them.
+///
+/// The exit transition takes the component away while it plays, and adds it back once it ends.
+/// While a transition plays, the system owns the position and size of the `UiTransform` and the
+/// `Rgba` of the widget and of its children for a fade, and restores them when it ends. Stretched
+/// widgets keep the size of their parent.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiTransitions {
+    /// The transition played when the widget appears.
+    pub enter: Option<UiTransition>,
+    /// The transition played when the widget is hidden, in reverse: a fade goes to transparent.
+    pub exit: Option<UiTransition>,
+}
+
+impl UiTransitions {
+    /// Plays `transition` when the widget appears, and in reverse when it is hidden.
+    pub fn both(transition: UiTransition) -> Self {
+        UiTransitions {
+            enter: Some(transition.clone()),
+            exit: Some(transition),
+        }
+    }
+}
+
+impl Component for UiTransitions {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Which component hid a widget whose exit transition plays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HiddenBy {
+    Hidden,
+    HiddenPropagate,
+}
+
+/// What a transition changes, to restore it once the transition ends.
+#[derive(Debug, Clone)]
+struct Base {
+    position: (f32, f32),
+    size: (f32, f32),
+    colors: Vec<(Entity, Option<Rgba>)>,
+}
+
+#[derive(Debug, Clone)]
+struct Playing {
+    transition: UiTransition,
+    elapsed: f32,
+    hidden_by: Option<HiddenBy>,
+    base: Base,
+}
+
+/// Plays the `UiTransitions` of widgets.
+#[derive(Debug, Default)]
+pub struct UiTransitionSystem {
+    seen: BitSet,
+    shown: BitSet,
+    playing: HashMap<Entity, Playing>,
+}
+
+impl UiTransitionSystem {
+    /// Creates a new `UiTransitionSystem`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> System<'a> for UiTransitionSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, ParentHierarchy>,
+        ReadStorage<'a, UiTransitions>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, Rgba>,
+        WriteStorage<'a, Hidden>,
+        WriteStorage<'a, HiddenPropagate>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            hierarchy,
+            transitions,
+            mut transforms,
+            mut colors,
+            mut hidden,
+            mut hidden_propagate,
+        ): Self::SystemData,
+    ) {
+        self.playing
+            .retain(|entity, _| transitions.contains(*entity) && transforms.contains(*entity));
+        let existing = transitions.mask().clone();
+        self.seen &= &existing;
+        self.shown &= &existing;
+
+        for (entity, transition, _) in (&*entities, &transitions, &transforms.mask().clone()).join()
+        {
+            let id = entity.id();
+            let is_hidden = hidden.contains(entity) || hidden_propagate.contains(entity);
+            let exiting = self
+                .playing
+                .get(&entity)
+                .map_or(false, |playing| playing.hidden_by.is_some());
+            let first_seen = !self.seen.add(id);
+            let was_shown = self.shown.contains(id);
+
+            let start = if exiting {
+                None
+            } else if is_hidden && was_shown && !first_seen {
+                transition.exit.as_ref().map(|exit| {
+                    let hidden_by = if hidden.remove(entity).is_some() {
+                        HiddenBy::Hidden
+                    } else {
+                        hidden_propagate.remove(entity);
+                        HiddenBy::HiddenPropagate
+                    };
+                    (exit.clone(), Some(hidden_by))
+                })
+            } else if !is_hidden && (first_seen || !was_shown) {
+                transition.enter.clone().map(|enter| (enter, None))
+            } else {
+                None
+            };
+
+            if let Some((transition, hidden_by)) = start {
+                // A transition interrupting another one starts from the state the first one
+                // restores.
+                let mut base = match self.playing.remove(&entity) {
+                    Some(playing) => playing.base,
+                    None => {
+                        let transform = transforms.get(entity).expect("Joined above");
+                        Base {
+                            position: (transform.local_x, transform.local_y),
+                            size: (transform.width, transform.height),
+                            colors: Vec::new(),
+                        }
+                    }
+                };
+                // Colors are only changed by fades, so the current ones are the base colors.
+                if base.colors.is_empty() && transition.effects.contains(&UiTransitionEffect::Fade)
+                {
+                    let mut faded = vec![entity];
+                    let mut index = 0;
+                    while index < faded.len() {
+                        faded.extend(hierarchy.children(faded[index]).iter().cloned());
+                        index += 1;
+                    }
+                    base.colors = faded
+                        .into_iter()
+                        .map(|e| (e, colors.get(e).cloned()))
+                        .collect();
+                }
+                self.playing.insert(
+                    entity,
+                    Playing {
+                        transition,
+                        elapsed: 0.0,
+                        hidden_by,
+                        base,
+                    },
+                );
+            }
+
+            if is_hidden && !self.playing.contains_key(&entity) {
+                self.shown.remove(id);
+            } else {
+                self.shown.add(id);
+            }
+        }
+
+        let delta = time.delta_seconds();
+        let mut finished = Vec::new();
+        for (entity, playing) in &mut self.playing {
+            playing.elapsed += delta;
+            let transition = &playing.transition;
+            let t = if transition.duration > 0.0 {
+                (playing.elapsed - transition.delay) / transition.duration
+            } else if playing.elapsed >= transition.delay {
+                1.0
+            } else {
+                0.0
+            };
+            if t >= 1.0 {
+                finished.push(*entity);
+                continue;
+            }
+            let mut shown = transition.easing.apply(t);
+            if playing.hidden_by.is_some() {
+                shown = 1.0 - shown;
+            }
+            apply(
+                *entity,
+                &transition.effects,
+                &playing.base,
+                shown,
+                &mut transforms,
+                &mut colors,
+            );
+        }
+
+        for entity in finished {
+            let playing = self.playing.remove(&entity).expect("Listed above");
+            apply(
+                entity,
+                &[],
+                &playing.base,
+                1.0,
+                &mut transforms,
+                &mut colors,
+            );
+            let result = match playing.hidden_by {
+                Some(HiddenBy::Hidden) => hidden.insert(entity, Hidden).map(|_| ()),
+                Some(HiddenBy::HiddenPropagate) => {
+                    hidden_propagate.insert(entity, HiddenPropagate).map(|_| ())
+                }
+                None => Ok(()),
+            };
+            if result.is_ok() && playing.hidden_by.is_some() {
+                self.shown.remove(entity.id());
+            }
+        }
+    }
+}
+
+/// Sets the widget to the state of its effects where `shown` is 0 when hidden and 1 when shown.
+/// Without effects it restores the base state.
+fn apply(
+    entity: Entity,
+    effects: &[UiTransitionEffect],
+    base: &Base,
+    shown: f32,
+    transforms: &mut WriteStorage<'_, UiTransform>,
+    colors: &mut WriteStorage<'_, Rgba>,
+) {
+    let mut position = base.position;
+    let mut size = base.size;
+    let mut alpha = 1.0;
+    for effect in effects {
+        match *effect {
+            UiTransitionEffect::Fade => alpha = shown,
+            UiTransitionEffect::Slide(x, y) => {
+                position.0 += x * (1.0 - shown);
+                position.1 += y * (1.0 - shown);
+            }
+            UiTransitionEffect::Scale(from) => {
+                let scale = from + (1.0 - from) * shown;
+                size = (size.0 * scale, size.1 * scale);
+            }
+        }
+    }
+
+    if let Some(transform) = transforms.get_mut(entity) {
+        transform.local_x = position.0;
+        transform.local_y = position.1;
+        transform.width = size.0;
+        transform.height = size.1;
+    }
+    let faded = effects.contains(&UiTransitionEffect::Fade);
+    for &(e, color) in &base.colors {
+        match color {
+            _ if faded => {
+                let color = color.unwrap_or(Rgba::WHITE);
+                colors
+                    .insert(e, Rgba(color.0, color.1, color.2, color.3 * alpha))
+                    .ok();
+            }
+            Some(color) => {
+                colors.insert(e, color).ok();
+            }
+            None => {
+                colors.remove(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easings_cover_the_whole_change() {
+        for easing in &[
+            UiEasing::Linear,
+            UiEasing::EaseIn,
+            UiEasing::EaseOut,
+            UiEasing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            assert_eq!(easing.apply(2.0), 1.0);
+        }
+        assert_eq!(UiEasing::EaseInOut.apply(0.5), 0.5);
+        assert!(UiEasing::EaseIn.apply(0.5) < 0.5);
+        assert!(UiEasing::EaseOut.apply(0.5) > 0.5);
+    }
+}
@@ -0,0 +1,94 @@
+//! Reloads configuration resources when their file changes.
+
+use std::{marker::PhantomData, time::Duration};
+
+use amethyst_config::LayeredConfig;
+use amethyst_core::{
+    ecs::prelude::{Read, Resources, System, SystemData, Write},
+    shrev::EventChannel,
+    timing::Time,
+};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+/// Event written to `EventChannel<ConfigChanged<T>>` when the `ConfigReloadSystem<T>` replaced
+/// the `T` resource.
+pub struct ConfigChanged<T> {
+    _marker: PhantomData<T>,
+}
+
+impl<T> ConfigChanged<T> {
+    fn new() -> Self {
+        ConfigChanged {
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Inserts the configuration loaded from a `LayeredConfig` as a resource, and loads it again
+/// whenever its file is modified.
+///
+/// The file is checked once per `interval`, one second by default. Systems which need to react to
+/// the changes can read `EventChannel<ConfigChanged<T>>`. A file which fails to load is logged
+/// and leaves the resource unchanged.
+pub struct ConfigReloadSystem<T> {
+    config: LayeredConfig<T>,
+    interval: Duration,
+    elapsed: Duration,
+}
+
+impl<T> ConfigReloadSystem<T>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Default,
+{
+    /// Creates a system reloading `config`.
+    pub fn new(config: LayeredConfig<T>) -> Self {
+        ConfigReloadSystem {
+            config,
+            interval: Duration::from_secs(1),
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// Sets how often the file is checked for changes.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl<'a, T> System<'a> for ConfigReloadSystem<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize + Default + Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, Time>,
+        Write<'a, T>,
+        Write<'a, EventChannel<ConfigChanged<T>>>,
+    );
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        match self.config.load() {
+            Ok(config) => res.insert(config),
+            Err(e) => error!("Failed to load config: {}", e),
+        }
+    }
+
+    fn run(&mut self, (time, mut value, mut changes): Self::SystemData) {
+        self.elapsed += time.delta_real_time();
+        if self.elapsed < self.interval {
+            return;
+        }
+        self.elapsed = Duration::from_secs(0);
+
+        match self.config.reload_if_changed() {
+            Some(Ok(config)) => {
+                *value = config;
+                changes.single_write(ConfigChanged::new());
+            }
+            Some(Err(e)) => error!("Failed to reload config: {}", e),
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Allows you to delete an entity together with all of its descendants, e.g. a spawned prefab.
+
+use amethyst_core::{
+    bundle::{BundleBuilder, SystemBundle},
+    ecs::{
+        world::EntitiesRes, Component, Entities, Entity, Join, NullStorage, ReadExpect,
+        ReadStorage, System,
+    },
+    ParentHierarchy,
+};
+use amethyst_error::Error;
+
+use log::error;
+
+/// Deletes the entity to which this is attached and all of its descendants.
+///
+/// Deleting only the root of a hierarchy leaves its children behind until the `TransformSystem`
+/// catches up, one level per frame, and not at all if it isn't running. With this marker the
+/// `DestroyHierarchySystem` of the `DestroyHierarchyBundle` deletes the whole tree at once. Asset handles held by the deleted
+/// entities are dropped with them, so assets only used by the tree get unloaded by their
+/// storages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DestroyHierarchy;
+
+impl Component for DestroyHierarchy {
+    type Storage = NullStorage<Self>;
+}
+
+/// Deletes the entity and all of its descendants.
+///
+/// The descendants are taken from the `ParentHierarchy`, so entities parented during the current
+/// frame are only found after the `HierarchySystem` ran.
+pub fn destroy_hierarchy(entities: &EntitiesRes, hierarchy: &ParentHierarchy, entity: Entity) {
+    let mut stack = vec![entity];
+    while let Some(e) = stack.pop() {
+        stack.extend(hierarchy.children(e).iter().cloned());
+        if let Err(err) = entities.delete(e) {
+            error!("Failed to delete entity: {:?}", err);
+        }
+    }
+}
+
+/// The system in charge of destroying entities with the `DestroyHierarchy` component, together
+/// with their descendants. Added by the `DestroyHierarchyBundle`.
+pub struct DestroyHierarchySystem;
+
+impl<'a> System<'a> for DestroyHierarchySystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, ParentHierarchy>,
+        ReadStorage<'a, DestroyHierarchy>,
+    );
+
+    fn run(&mut self, (entities, hierarchy, destroy): Self::SystemData) {
+        for (e, _) in (&entities, &destroy).join() {
+            destroy_hierarchy(&entities, &hierarchy, e);
+        }
+    }
+}
+
+/// Adds the `DestroyHierarchySystem` with the name `destroy_hierarchy_system`, after the
+/// `parent_hierarchy_system` of the `TransformBundle`, which has to be added before.
+#[derive(Default)]
+pub struct DestroyHierarchyBundle;
+
+impl<'a, 'b> SystemBundle<'a, 'b> for DestroyHierarchyBundle {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
+        builder.add(
+            DestroyHierarchySystem,
+            "destroy_hierarchy_system",
+            &["parent_hierarchy_system"],
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst_core::{
+        ecs::{Builder, DispatcherBuilder, World},
+        transform::{Parent, TransformBundle},
+    };
+
+    #[test]
+    fn deletes_the_descendants() {
+        let mut world = World::new();
+        let mut dispatcher = DispatcherBuilder::new();
+        TransformBundle::<f32>::new()
+            .build(&mut BundleBuilder::new(&mut dispatcher))
+            .unwrap();
+        DestroyHierarchyBundle
+            .build(&mut BundleBuilder::new(&mut dispatcher))
+            .unwrap();
+        let mut dispatcher = dispatcher.build();
+        dispatcher.setup(&mut world.res);
+
+        let parent = world.create_entity().build();
+        let child = world
+            .create_entity()
+            .with(Parent { entity: parent })
+            .build();
+        let grandchild = world.create_entity().with(Parent { entity: child }).build();
+        let other = world.create_entity().build();
+        dispatcher.dispatch(&world.res);
+        world.maintain();
+
+        world
+            .write_storage::<DestroyHierarchy>()
+            .insert(parent, DestroyHierarchy)
+            .unwrap();
+        dispatcher.dispatch(&world.res);
+        world.maintain();
+
+        assert!(!world.is_alive(parent));
+        assert!(!world.is_alive(child));
+        assert!(!world.is_alive(grandchild));
+        assert!(world.is_alive(other));
+    }
+}
@@ -1,9 +1,9 @@
 //! Util Resources
 
 use amethyst_core::{
-    ecs::prelude::{DispatcherBuilder, Read, System, Write},
+    ecs::prelude::{Read, System, Write},
     timing::{duration_to_nanos, Time},
-    SystemBundle,
+    BundleBuilder, SystemBundle,
 };
 use amethyst_error::Error;
 
@@ -79,7 +79,7 @@ impl<'a> System<'a> for FPSCounterSystem {
 pub struct FPSCounterBundle;
 
 impl<'a, 'b> SystemBundle<'a, 'b> for FPSCounterBundle {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(FPSCounterSystem, "fps_counter_system", &[]);
         Ok(())
     }
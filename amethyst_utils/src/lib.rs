@@ -7,10 +7,14 @@ pub use self::app_root_dir::*;
 pub mod app_root_dir;
 pub mod auto_fov;
 pub mod circular_buffer;
+pub mod config_reload;
+pub mod destroy_hierarchy;
 pub mod fps_counter;
+pub mod navmesh;
 pub mod ortho_camera;
 pub mod removal;
 pub mod render;
+pub mod rng;
 pub mod scene;
 pub mod tag;
 pub mod time_destroy;
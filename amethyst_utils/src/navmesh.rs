@@ -0,0 +1,700 @@
+//! Navigation meshes built from the triangles of the level, for finding paths of agents.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use amethyst_core::{
+    ecs::prelude::{Component, Join, NullStorage, ReadStorage, System, Write},
+    math::{Matrix4, Vector3, Vector4},
+    Transform,
+};
+use amethyst_renderer::MeshData;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+const NONE: u32 = std::u32::MAX;
+/// The most columns a navigation mesh is built with, about 16 million.
+const MAX_COLUMNS: u64 = 1 << 24;
+/// Cell offsets of the links of a node: first the four sides, then the four corners.
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (-1, -1),
+    (1, -1),
+];
+
+/// The dimensions of the agents walking on a `NavMesh`, and how finely the level is sampled.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavMeshSettings {
+    /// The horizontal size of the cells the level is divided into.
+    pub cell_size: f32,
+    /// The vertical distance up to which triangles are merged into one surface.
+    pub cell_height: f32,
+    /// The free space needed above a surface for it to be walkable.
+    pub agent_height: f32,
+    /// The distance agents keep from walls and edges.
+    pub agent_radius: f32,
+    /// The largest step agents can climb between two cells.
+    pub max_climb: f32,
+    /// The steepest walkable slope, in degrees.
+    pub max_slope: f32,
+}
+
+impl Default for NavMeshSettings {
+    fn default() -> Self {
+        NavMeshSettings {
+            cell_size: 0.3,
+            cell_height: 0.2,
+            agent_height: 2.0,
+            agent_radius: 0.6,
+            max_climb: 0.9,
+            max_slope: 45.0,
+        }
+    }
+}
+
+impl NavMeshSettings {
+    /// Returns why the settings can't be used to build a navigation mesh, if they can't.
+    fn check(&self) -> Result<(), &'static str> {
+        if !(self.cell_size > 0.0 && self.cell_size.is_finite()) {
+            return Err("the cell size must be positive and finite");
+        }
+        if !(self.cell_height > 0.0 && self.cell_height.is_finite()) {
+            return Err("the cell height must be positive and finite");
+        }
+        let lengths = [self.agent_height, self.agent_radius, self.max_climb];
+        if !lengths
+            .iter()
+            .all(|length| *length >= 0.0 && length.is_finite())
+        {
+            return Err(
+                "the agent height, agent radius and max climb must be finite and not negative",
+            );
+        }
+        if !self.max_slope.is_finite() {
+            return Err("the max slope must be finite");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    x: u32,
+    z: u32,
+    y: f32,
+    links: [u32; 8],
+}
+
+/// A walkable surface built from level geometry, answering path queries.
+///
+/// The level is divided into a grid of cells like in Recast: the triangles are voxelized into
+/// columns of solid spans, and the top of every span with enough free space above it and a flat
+/// enough surface becomes a node. Nodes are linked to the nodes in the neighbouring cells they
+/// can step to, and nodes closer than the agent radius to an edge are removed.
+///
+/// The resource starts empty; call `request_build` once the level is loaded and the
+/// `NavMeshBuildSystem` builds it from all entities with a `MeshData` and a `Transform`, for
+/// example the meshes of a glTF scene. Entities with a `NavMeshIgnore` component are skipped.
+#[derive(Clone, Debug)]
+pub struct NavMesh {
+    settings: NavMeshSettings,
+    origin: Vector3<f32>,
+    width: u32,
+    depth: u32,
+    columns: Vec<u32>,
+    nodes: Vec<Node>,
+    pending: Option<NavMeshSettings>,
+}
+
+impl Default for NavMesh {
+    fn default() -> Self {
+        NavMesh {
+            settings: NavMeshSettings::default(),
+            origin: Vector3::zeros(),
+            width: 0,
+            depth: 0,
+            columns: vec![0],
+            nodes: Vec::new(),
+            pending: None,
+        }
+    }
+}
+
+impl NavMesh {
+    /// Builds a navigation mesh from world space triangles.
+    ///
+    /// Returns an empty mesh and logs a warning if the settings are invalid, or if the level would
+    /// need more than about 16 million cells at the cell size of the settings.
+    pub fn build(triangles: &[[Vector3<f32>; 3]], settings: &NavMeshSettings) -> Self {
+        let mut mesh = NavMesh {
+            settings: settings.clone(),
+            ..Default::default()
+        };
+        let mut min = Vector3::repeat(std::f32::MAX);
+        let mut max = Vector3::repeat(std::f32::MIN);
+        for vertex in triangles.iter().flat_map(|t| t.iter()) {
+            min = min.inf(vertex);
+            max = max.sup(vertex);
+        }
+        if triangles.is_empty() {
+            return mesh;
+        }
+        if let Err(reason) = settings.check() {
+            warn!("Not building the navigation mesh, {}", reason);
+            return mesh;
+        }
+        let extent = max - min;
+        if !(extent.x.is_finite() && extent.z.is_finite()) {
+            warn!("Not building the navigation mesh, the level has vertices which are not finite");
+            return mesh;
+        }
+
+        let cell_size = settings.cell_size;
+        let cells = |length: f32| {
+            let cells = (length / cell_size).floor();
+            // Checked before the cast, which is only defined for values in range.
+            if cells < std::u32::MAX as f32 {
+                Some(cells as u64 + 1)
+            } else {
+                None
+            }
+        };
+        let columns = match (cells(extent.x), cells(extent.z)) {
+            (Some(width), Some(depth)) => width
+                .checked_mul(depth)
+                .filter(|columns| *columns <= MAX_COLUMNS)
+                .map(|columns| (width, depth, columns)),
+            _ => None,
+        };
+        let (width, depth, columns) = match columns {
+            Some(columns) => columns,
+            None => {
+                warn!(
+                    "Not building the navigation mesh, a level of {} by {} units needs more than \
+                     {} cells of size {}",
+                    extent.x, extent.z, MAX_COLUMNS, cell_size
+                );
+                return mesh;
+            }
+        };
+        mesh.origin = min;
+        mesh.width = width as u32;
+        mesh.depth = depth as u32;
+
+        // Voxelize the triangles into samples per column.
+        let mut samples = vec![Vec::<(f32, bool)>::new(); columns as usize];
+        let min_normal_y = settings.max_slope.to_radians().cos();
+        for &[a, b, c] in triangles {
+            let normal = (b - a).cross(&(c - a));
+            let area = normal.norm();
+            if area <= std::f32::EPSILON {
+                continue;
+            }
+            let walkable = (normal.y / area).abs() >= min_normal_y;
+            let longest = (b - a).norm().max((c - a).norm()).max((c - b).norm());
+            let steps = (longest / (cell_size * 0.5)).ceil().max(1.0) as u32;
+            for i in 0..=steps {
+                for j in 0..=steps - i {
+                    let u = i as f32 / steps as f32;
+                    let v = j as f32 / steps as f32;
+                    let point = a + (b - a) * u + (c - a) * v;
+                    if let Some(column) = mesh.column(&point) {
+                        samples[column].push((point.y, walkable));
+                    }
+                }
+            }
+        }
+
+        // Merge the samples into spans and keep the walkable tops with enough space above.
+        let mut candidates = Vec::with_capacity(samples.len());
+        for (column, mut samples) in samples.into_iter().enumerate() {
+            samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+            let mut spans: Vec<(f32, f32, bool)> = Vec::new();
+            for (y, walkable) in samples {
+                // The samples are sorted, so the last one decides whether the top is walkable.
+                match spans.last_mut() {
+                    Some(span) if y - span.1 <= settings.cell_height => {
+                        span.1 = y;
+                        span.2 = walkable;
+                    }
+                    _ => spans.push((y, y, walkable)),
+                }
+            }
+            let x = column as u32 % mesh.width;
+            let z = column as u32 / mesh.width;
+            let tops = spans
+                .iter()
+                .enumerate()
+                .filter(|&(i, span)| {
+                    span.2
+                        && spans
+                            .get(i + 1)
+                            .map_or(true, |above| above.0 - span.1 >= settings.agent_height)
+                })
+                .map(|(_, span)| Node {
+                    x,
+                    z,
+                    y: span.1,
+                    links: [NONE; 8],
+                })
+                .collect::<Vec<_>>();
+            candidates.push(tops);
+        }
+
+        mesh.set_nodes(candidates);
+        mesh.erode();
+        mesh
+    }
+
+    /// Requests the `NavMeshBuildSystem` to build the navigation mesh from the level geometry.
+    pub fn request_build(&mut self, settings: NavMeshSettings) {
+        self.pending = Some(settings);
+    }
+
+    /// Returns the settings the navigation mesh was built with.
+    pub fn settings(&self) -> &NavMeshSettings {
+        &self.settings
+    }
+
+    /// Returns the number of walkable cells.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if nothing is walkable.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the centers of the walkable cells, e.g. for debug drawing.
+    pub fn points<'a>(&'a self) -> impl Iterator<Item = Vector3<f32>> + 'a {
+        self.nodes.iter().map(move |node| self.position(node))
+    }
+
+    /// Returns the walkable point closest to `point`, searching up to the agent radius plus one
+    /// cell away.
+    pub fn nearest_point(&self, point: &Vector3<f32>) -> Option<Vector3<f32>> {
+        self.nearest_node(point)
+            .map(|node| self.position(&self.nodes[node as usize]))
+    }
+
+    /// Finds a path from `start` to `end`, both of which have to be close to walkable cells.
+    ///
+    /// The path starts and ends at the horizontal position of the query points on the height of
+    /// the surface, and only contains the corners in between. Returns `None` if the points are
+    /// not connected.
+    pub fn find_path(&self, start: &Vector3<f32>, end: &Vector3<f32>) -> Option<Vec<Vector3<f32>>> {
+        let from = self.nearest_node(start)?;
+        let to = self.nearest_node(end)?;
+        let nodes = self.search(from, to)?;
+
+        let mut corners = vec![from];
+        let mut anchor = 0;
+        while anchor + 1 < nodes.len() {
+            let mut next = anchor + 1;
+            while next + 1 < nodes.len() && self.straight(nodes[anchor], nodes[next + 1]) {
+                next += 1;
+            }
+            corners.push(nodes[next]);
+            anchor = next;
+        }
+        if corners.len() == 1 {
+            corners.push(to);
+        }
+
+        let last = corners.len() - 1;
+        Some(
+            corners
+                .into_iter()
+                .enumerate()
+                .map(|(i, node)| {
+                    let position = self.position(&self.nodes[node as usize]);
+                    match i {
+                        0 => Vector3::new(start.x, position.y, start.z),
+                        i if i == last => Vector3::new(end.x, position.y, end.z),
+                        _ => position,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn column(&self, point: &Vector3<f32>) -> Option<usize> {
+        let x = ((point.x - self.origin.x) / self.settings.cell_size).floor();
+        let z = ((point.z - self.origin.z) / self.settings.cell_size).floor();
+        self.column_at(x as i32, z as i32)
+    }
+
+    fn column_at(&self, x: i32, z: i32) -> Option<usize> {
+        if x < 0 || z < 0 || x as u32 >= self.width || z as u32 >= self.depth {
+            None
+        } else {
+            Some((z as u32 * self.width + x as u32) as usize)
+        }
+    }
+
+    fn column_nodes(&self, column: usize) -> std::ops::Range<u32> {
+        self.columns[column]..self.columns[column + 1]
+    }
+
+    fn position(&self, node: &Node) -> Vector3<f32> {
+        let cell_size = self.settings.cell_size;
+        Vector3::new(
+            self.origin.x + (node.x as f32 + 0.5) * cell_size,
+            node.y,
+            self.origin.z + (node.z as f32 + 0.5) * cell_size,
+        )
+    }
+
+    /// Stores the nodes of every column and links them to their neighbours.
+    fn set_nodes(&mut self, columns: Vec<Vec<Node>>) {
+        self.columns = Vec::with_capacity(columns.len() + 1);
+        self.nodes.clear();
+        for column in columns {
+            self.columns.push(self.nodes.len() as u32);
+            self.nodes.extend(column);
+        }
+        self.columns.push(self.nodes.len() as u32);
+
+        for index in 0..self.nodes.len() {
+            let (x, z, y) = {
+                let node = &self.nodes[index];
+                (node.x as i32, node.z as i32, node.y)
+            };
+            let mut links = [NONE; 8];
+            for (dir, &(dx, dz)) in DIRECTIONS.iter().enumerate() {
+                // Corners can only be cut if both sides are walkable.
+                if dir >= 4 && (links[dir - 4] == NONE || links[(dir - 3) % 4] == NONE) {
+                    continue;
+                }
+                if let Some(column) = self.column_at(x + dx, z + dz) {
+                    links[dir] = self
+                        .column_nodes(column)
+                        .filter(|&n| {
+                            (self.nodes[n as usize].y - y).abs() <= self.settings.max_climb
+                        })
+                        .min_by(|&a, &b| {
+                            let a = (self.nodes[a as usize].y - y).abs();
+                            let b = (self.nodes[b as usize].y - y).abs();
+                            a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                        })
+                        .unwrap_or(NONE);
+                }
+            }
+            self.nodes[index].links = links;
+        }
+    }
+
+    /// Removes the nodes closer than the agent radius to an edge.
+    fn erode(&mut self) {
+        let radius = (self.settings.agent_radius / self.settings.cell_size).ceil() as u32;
+        if radius == 0 {
+            return;
+        }
+        let mut distance = vec![NONE; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.links[..4].iter().any(|&l| l == NONE) {
+                distance[index] = 0;
+                queue.push_back(index);
+            }
+        }
+        while let Some(index) = queue.pop_front() {
+            for &link in &self.nodes[index].links[..4] {
+                if link != NONE && distance[link as usize] == NONE {
+                    distance[link as usize] = distance[index] + 1;
+                    queue.push_back(link as usize);
+                }
+            }
+        }
+
+        let mut columns = vec![Vec::new(); self.columns.len() - 1];
+        for (index, node) in self.nodes.iter().enumerate() {
+            if distance[index] >= radius {
+                let column = (node.z * self.width + node.x) as usize;
+                columns[column].push(Node {
+                    links: [NONE; 8],
+                    ..node.clone()
+                });
+            }
+        }
+        self.set_nodes(columns);
+    }
+
+    fn nearest_node(&self, point: &Vector3<f32>) -> Option<u32> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let cell_size = self.settings.cell_size;
+        let x = ((point.x - self.origin.x) / cell_size).floor() as i32;
+        let z = ((point.z - self.origin.z) / cell_size).floor() as i32;
+        let range = (self.settings.agent_radius / cell_size).ceil() as i32 + 1;
+        let mut best = None;
+        let mut best_distance = std::f32::MAX;
+        for dz in -range..=range {
+            for dx in -range..=range {
+                let column = match self.column_at(x + dx, z + dz) {
+                    Some(column) => column,
+                    None => continue,
+                };
+                for node in self.column_nodes(column) {
+                    let distance =
+                        (self.position(&self.nodes[node as usize]) - point).norm_squared();
+                    if distance < best_distance {
+                        best_distance = distance;
+                        best = Some(node);
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// A* search over the links, returning the nodes of the path.
+    fn search(&self, from: u32, to: u32) -> Option<Vec<u32>> {
+        let goal = self.position(&self.nodes[to as usize]);
+        let heuristic = |node: u32| (self.position(&self.nodes[node as usize]) - goal).norm();
+
+        let mut cost = vec![std::f32::MAX; self.nodes.len()];
+        let mut previous = vec![NONE; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+        cost[from as usize] = 0.0;
+        open.push(Open {
+            estimate: heuristic(from),
+            node: from,
+        });
+        while let Some(Open { node, .. }) = open.pop() {
+            if node == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while previous[current as usize] != NONE {
+                    current = previous[current as usize];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let position = self.position(&self.nodes[node as usize]);
+            for &link in self.nodes[node as usize]
+                .links
+                .iter()
+                .filter(|&&l| l != NONE)
+            {
+                let step = (self.position(&self.nodes[link as usize]) - position).norm();
+                let new_cost = cost[node as usize] + step;
+                if new_cost < cost[link as usize] {
+                    cost[link as usize] = new_cost;
+                    previous[link as usize] = node;
+                    open.push(Open {
+                        estimate: new_cost + heuristic(link),
+                        node: link,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if the straight line between the nodes only crosses linked cells.
+    fn straight(&self, from: u32, to: u32) -> bool {
+        let start = self.position(&self.nodes[from as usize]);
+        let end = self.position(&self.nodes[to as usize]);
+        let steps = ((end - start).xz().norm() / (self.settings.cell_size * 0.25)).ceil() as u32;
+        let mut current = from;
+        for step in 1..=steps {
+            let point = start + (end - start) * (step as f32 / steps as f32);
+            let node = &self.nodes[current as usize];
+            let x = ((point.x - self.origin.x) / self.settings.cell_size).floor() as i32;
+            let z = ((point.z - self.origin.z) / self.settings.cell_size).floor() as i32;
+            let offset = (x - node.x as i32, z - node.z as i32);
+            if offset == (0, 0) {
+                continue;
+            }
+            match DIRECTIONS.iter().position(|&d| d == offset) {
+                Some(dir) if node.links[dir] != NONE => current = node.links[dir],
+                _ => return false,
+            }
+        }
+        current == to
+    }
+}
+
+#[derive(PartialEq)]
+struct Open {
+    estimate: f32,
+    node: u32,
+}
+
+impl Eq for Open {}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the `BinaryHeap` pops the lowest estimate first.
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Excludes the mesh of an entity from the `NavMesh`, e.g. for characters or moving objects.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NavMeshIgnore;
+
+impl Component for NavMeshIgnore {
+    type Storage = NullStorage<Self>;
+}
+
+/// Builds the `NavMesh` from the `MeshData` of all entities when it's requested with
+/// `NavMesh::request_build`.
+///
+/// The global matrices of the `Transform`s are used, so the system should run after the
+/// `TransformSystem`.
+#[derive(Debug, Default)]
+pub struct NavMeshBuildSystem;
+
+impl<'a> System<'a> for NavMeshBuildSystem {
+    type SystemData = (
+        ReadStorage<'a, MeshData>,
+        ReadStorage<'a, Transform<f32>>,
+        ReadStorage<'a, NavMeshIgnore>,
+        Write<'a, NavMesh>,
+    );
+
+    fn run(&mut self, (meshes, transforms, ignored, mut navmesh): Self::SystemData) {
+        let settings = match navmesh.pending.take() {
+            Some(settings) => settings,
+            None => return,
+        };
+
+        let mut triangles = Vec::new();
+        for (mesh, transform, _) in (&meshes, &transforms, !&ignored).join() {
+            let matrix = transform.global_matrix();
+            triangles.extend(positions(mesh).chunks(3).filter(|t| t.len() == 3).map(|t| {
+                [
+                    transformed(matrix, &t[0]),
+                    transformed(matrix, &t[1]),
+                    transformed(matrix, &t[2]),
+                ]
+            }));
+        }
+        *navmesh = NavMesh::build(&triangles, &settings);
+    }
+}
+
+fn transformed(matrix: &Matrix4<f32>, position: &Vector3<f32>) -> Vector3<f32> {
+    (matrix * Vector4::new(position.x, position.y, position.z, 1.0)).xyz()
+}
+
+/// Returns the vertex positions of a triangle list mesh, with the corners of its triangles in
+/// order if the mesh is indexed.
+fn positions(mesh: &MeshData) -> Vec<Vector3<f32>> {
+    match mesh {
+        MeshData::PosColor(vertices) => vertices.iter().map(|v| v.position).collect(),
+        MeshData::PosColorNorm(vertices) => vertices.iter().map(|v| v.position).collect(),
+        MeshData::PosTex(vertices) => vertices.iter().map(|v| v.position).collect(),
+        MeshData::PosNormTex(vertices) => vertices.iter().map(|v| v.position).collect(),
+        MeshData::PosNormTangTex(vertices) => vertices.iter().map(|v| v.position).collect(),
+        MeshData::Creator(creator) => {
+            let vertices = creator.vertices();
+            let position = |index: usize| Vector3::from(vertices[index].0);
+            match creator.indices() {
+                Some(indices) => indices.iter().map(|&i| position(i as usize)).collect(),
+                None => (0..vertices.len()).map(position).collect(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use amethyst_renderer::{AnimatedComboMeshCreator, Separate};
+
+    fn quad(min: (f32, f32), max: (f32, f32), y: f32) -> Vec<[Vector3<f32>; 3]> {
+        let a = Vector3::new(min.0, y, min.1);
+        let b = Vector3::new(max.0, y, min.1);
+        let c = Vector3::new(max.0, y, max.1);
+        let d = Vector3::new(min.0, y, max.1);
+        vec![[a, b, c], [a, c, d]]
+    }
+
+    #[test]
+    fn path_around_gap() {
+        // Two platforms connected by a bridge at the far end.
+        let mut triangles = quad((0.0, 0.0), (4.0, 10.0), 0.0);
+        triangles.extend(quad((6.0, 0.0), (10.0, 10.0), 0.0));
+        triangles.extend(quad((4.0, 7.0), (6.0, 10.0), 0.0));
+        let settings = NavMeshSettings {
+            cell_size: 0.5,
+            agent_radius: 0.5,
+            ..Default::default()
+        };
+        let navmesh = NavMesh::build(&triangles, &settings);
+        assert!(!navmesh.is_empty());
+
+        let start = Vector3::new(2.0, 0.0, 1.0);
+        let end = Vector3::new(8.0, 0.0, 1.0);
+        let path = navmesh.find_path(&start, &end).expect("No path found");
+        assert_eq!(start, path[0]);
+        assert_eq!(end, *path.last().unwrap());
+        assert!(path.iter().any(|p| p.z > 7.0));
+        assert!(path.iter().all(|p| p.x <= 4.0 || p.x >= 6.0 || p.z >= 7.0));
+    }
+
+    #[test]
+    fn unconnected_points_have_no_path() {
+        let mut triangles = quad((0.0, 0.0), (4.0, 4.0), 0.0);
+        triangles.extend(quad((0.0, 6.0), (4.0, 10.0), 5.0));
+        let navmesh = NavMesh::build(&triangles, &NavMeshSettings::default());
+        assert!(navmesh
+            .find_path(&Vector3::new(2.0, 0.0, 2.0), &Vector3::new(2.0, 5.0, 8.0))
+            .is_none());
+    }
+
+    #[test]
+    fn oversized_grids_are_not_built() {
+        let triangles = quad((0.0, 0.0), (20_000.0, 20_000.0), 0.0);
+        let navmesh = NavMesh::build(&triangles, &NavMeshSettings::default());
+        assert!(navmesh.is_empty());
+
+        let settings = NavMeshSettings {
+            cell_size: std::f32::NAN,
+            ..Default::default()
+        };
+        assert!(NavMesh::build(&quad((0.0, 0.0), (4.0, 4.0), 0.0), &settings).is_empty());
+    }
+
+    #[test]
+    fn reads_the_triangles_of_mesh_creators() {
+        let vertices = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 0.0, 1.0],
+            [0.0, 0.0, 1.0],
+        ]
+        .into_iter()
+        .map(Separate::new)
+        .collect();
+        let creator = AnimatedComboMeshCreator::new((vertices, None, None, None, None, None, None))
+            .with_indices(vec![0, 1, 2, 0, 2, 3]);
+        let positions = positions(&creator.into());
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions[3], Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(positions[5], Vector3::new(0.0, 0.0, 1.0));
+    }
+}
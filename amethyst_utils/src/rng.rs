@@ -0,0 +1,106 @@
+//! Seedable random numbers for reproducible simulations.
+
+use rand::{Error, RngCore};
+use rand_pcg::Pcg32;
+
+/// Random number generator used by `GameRng` and its streams.
+pub type RngStream = Pcg32;
+
+/// Resource providing random numbers which can be reproduced from a seed.
+///
+/// With the same seed, a run produces the same numbers, e.g. for replaying a simulation in tests
+/// or placing the same procedural props every time a level is loaded. Systems running in parallel
+/// would make the order of calls to a shared generator nondeterministic, so each system should
+/// rather take its own `stream` in `System::setup`. A stream only depends on the seed and its
+/// name, not on any other use of the generator.
+///
+/// `GameRng` itself implements `RngCore`, so it can be used with all of `rand`'s `Rng` methods.
+///
+/// ```
+/// use amethyst_utils::rng::GameRng;
+/// use rand::Rng;
+///
+/// let mut rng = GameRng::new(42);
+/// let mut particles = rng.stream("particles");
+/// let first: f32 = particles.gen();
+///
+/// assert_eq!(first, GameRng::new(42).stream("particles").gen::<f32>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct GameRng {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl GameRng {
+    /// Creates a generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: Pcg32::new(seed, 0),
+        }
+    }
+
+    /// Creates a generator with a random seed. The seed can be read with `seed`, so the run can
+    /// be reproduced later.
+    pub fn from_entropy() -> Self {
+        GameRng::new(rand::random())
+    }
+
+    /// The seed of the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the generator from the seed.
+    pub fn reseed(&mut self, seed: u64) {
+        *self = GameRng::new(seed);
+    }
+
+    /// Returns an independent generator for the stream with the given name.
+    ///
+    /// Returns the same generator every time it's called with the same name and seed.
+    pub fn stream(&self, name: &str) -> RngStream {
+        // Stream 0 is used by `self`, so the lowest bit is set for named streams.
+        Pcg32::new(self.seed, fnv1a(name.as_bytes()) | 1)
+    }
+
+    /// Splits off a new generator, advancing this one.
+    ///
+    /// The new generator depends on how often this one was used, so the order of calls matters.
+    pub fn fork(&mut self) -> RngStream {
+        Pcg32::new(self.rng.next_u64(), self.rng.next_u64() | 1)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::from_entropy()
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// The 64 bit FNV-1a hash, which unlike the `Hash` implementation of the standard library is
+/// guaranteed to stay the same.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
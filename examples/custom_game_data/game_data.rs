@@ -1,5 +1,5 @@
 use amethyst::{
-    core::{ArcThreadPool, SystemBundle},
+    core::{ArcThreadPool, BundleBuilder, SystemBundle},
     ecs::prelude::{Dispatcher, DispatcherBuilder, System, World},
     error::Error,
     DataInit,
@@ -51,7 +51,7 @@ impl<'a, 'b> CustomGameDataBuilder<'a, 'b> {
     where
         B: SystemBundle<'a, 'b>,
     {
-        bundle.build(&mut self.base)?;
+        bundle.build(&mut BundleBuilder::new(&mut self.base))?;
         Ok(self)
     }
 
@@ -36,6 +36,18 @@ impl<'s> System<'s> for ExampleLinesSystem {
             [0.0, 0.0, 0.2].into(),
             [0.5, 0.05, 0.65, 1.0].into(),
         );
+
+        debug_lines_resource.draw_box(
+            [2.0, 0.0, 2.0].into(),
+            [2.6, 0.6, 2.6].into(),
+            [0.9, 0.6, 0.1, 1.0].into(),
+        );
+
+        debug_lines_resource.draw_sphere(
+            [-2.0, 0.5 + t * 0.2, 2.0].into(),
+            0.3,
+            [0.1, 0.6, 0.9, 1.0].into(),
+        );
     }
 }
 
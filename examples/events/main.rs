@@ -2,11 +2,11 @@ extern crate amethyst;
 
 use amethyst::{
     core::{
-        bundle::SystemBundle,
+        bundle::{BundleBuilder, SystemBundle},
         frame_limiter::FrameRateLimitStrategy,
         shrev::{EventChannel, ReaderId},
     },
-    ecs::{DispatcherBuilder, Read, Resources, System, SystemData, World, Write},
+    ecs::{Read, Resources, System, SystemData, World, Write},
     prelude::*,
 };
 
@@ -17,7 +17,7 @@ use core::result::Result;
 struct MyBundle;
 
 impl<'a, 'b> SystemBundle<'a, 'b> for MyBundle {
-    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+    fn build(self, builder: &mut BundleBuilder<'_, 'a, 'b>) -> Result<(), Error> {
         builder.add(SpammingSystem, "spamming_system", &[]);
         builder.add(
             ReceivingSystem {
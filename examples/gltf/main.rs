@@ -6,14 +6,14 @@ use amethyst::{
         EndControl, VertexSkinningBundle,
     },
     assets::{
-        AssetPrefab, Completion, Handle, Prefab, PrefabData, PrefabLoader, PrefabLoaderSystem,
-        ProgressCounter, RonFormat,
+        AssetPrefab, AssetStorage, Completion, Directory, Handle, Loader, Prefab, PrefabData,
+        PrefabLoader, PrefabLoaderSystem, ProgressCounter, RonFormat,
     },
     controls::{ControlTagPrefab, FlyControlBundle},
     core::transform::{Transform, TransformBundle},
     derive::PrefabData,
-    ecs::prelude::{Entity, ReadStorage, Write, WriteStorage},
-    input::{is_close_requested, is_key_down},
+    ecs::prelude::{Entity, Read, ReadExpect, ReadStorage, Write, WriteStorage},
+    input::{is_close_requested, is_key_down, InputEvent},
     prelude::*,
     renderer::*,
     utils::{
@@ -26,6 +26,11 @@ use amethyst_gltf::{GltfSceneAsset, GltfSceneFormat, GltfSceneLoaderSystem};
 
 use serde::{Deserialize, Serialize};
 
+use std::path::Path;
+
+/// Id of the asset source for files dropped onto the window, which have absolute paths.
+const DROPPED_SOURCE: &str = "dropped";
+
 #[derive(Default)]
 struct Example {
     entity: Option<Entity>,
@@ -59,6 +64,10 @@ impl SimpleState for Example {
 
         self.progress = Some(ProgressCounter::default());
 
+        world
+            .write_resource::<Loader>()
+            .add_source(DROPPED_SOURCE, Directory::new(""));
+
         world.exec(
             |(loader, mut scene): (PrefabLoader<'_, ScenePrefabData>, Write<'_, Scene>)| {
                 scene.handle = Some(loader.load(
@@ -91,6 +100,9 @@ impl SimpleState for Example {
             } else {
                 Trans::None
             }
+        } else if let StateEvent::Input(InputEvent::FileDropped(path)) = &event {
+            load_dropped_scene(world, path);
+            Trans::None
         } else {
             Trans::None
         }
@@ -137,6 +149,42 @@ impl SimpleState for Example {
     }
 }
 
+/// Spawns the glTF scene dropped onto the window, next to the scene of the example.
+fn load_dropped_scene(world: &mut World, path: &Path) {
+    let is_gltf = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| {
+            ext.eq_ignore_ascii_case("glb") || ext.eq_ignore_ascii_case("gltf")
+        });
+    if !is_gltf {
+        println!("Ignoring dropped file {:?}, it is not a glTF scene", path);
+        return;
+    }
+
+    println!("Loading dropped scene {:?}", path);
+    let handle = world.exec(
+        |(loader, storage): (
+            ReadExpect<'_, Loader>,
+            Read<'_, AssetStorage<GltfSceneAsset<f32>>>,
+        )| {
+            loader.load_from(
+                path.to_string_lossy(),
+                GltfSceneFormat,
+                Default::default(),
+                DROPPED_SOURCE,
+                (),
+                &storage,
+            )
+        },
+    );
+    world
+        .create_entity()
+        .with(handle)
+        .with(Transform::<f32>::default())
+        .build();
+}
+
 fn toggle_or_cycle_animation(
     entity: Option<Entity>,
     scene: &mut Scene,
@@ -26,7 +26,7 @@ use crate::{
     },
     error::Error,
     game_data::DataInit,
-    state::{State, StateData, StateMachine, TransEvent},
+    state::{State, StateChangeEvent, StateData, StateMachine, TransEvent},
     state_event::{StateEvent, StateEventReader},
     ui::UiEvent,
 };
@@ -512,11 +512,30 @@ where
         } else {
             pool = thread_pool_builder.build().map(Arc::new)?;
         }
-        world.add_resource(Loader::new(path.as_ref().to_owned(), pool.clone()));
+        let io_thread_count: Option<usize> = env::var("AMETHYST_NUM_IO_THREADS")
+            .as_ref()
+            .map(|s| {
+                s.as_str()
+                    .parse()
+                    .expect("AMETHYST_NUM_IO_THREADS was provided but is not a valid number!")
+            })
+            .ok();
+        let io_pool = match io_thread_count {
+            Some(io_thread_count) => {
+                debug!(
+                    "Running asset loading on dedicated thread pool: {}",
+                    io_thread_count
+                );
+                io_thread_pool(io_thread_count)?
+            }
+            None => pool.clone(),
+        };
+        world.add_resource(Loader::new(path.as_ref().to_owned(), io_pool));
         world.add_resource(pool);
         world.add_resource(EventChannel::<Event>::with_capacity(2000));
         world.add_resource(EventChannel::<UiEvent>::with_capacity(40));
         world.add_resource(EventChannel::<TransEvent<T, StateEvent>>::with_capacity(2));
+        world.add_resource(EventChannel::<StateChangeEvent>::with_capacity(4));
         world.add_resource(Errors::default());
         world.add_resource(FrameLimiter::default());
         world.add_resource(Stopwatch::default());
@@ -757,6 +776,23 @@ where
         self
     }
 
+    /// Loads and decodes assets on a dedicated thread pool with `num_threads` threads, instead of
+    /// the pool the systems are dispatched on.
+    ///
+    /// This keeps expensive imports (decompressing textures, reading glTF files) from competing
+    /// with the game systems. Turning the decoded data into assets, e.g. uploading it to the GPU,
+    /// still happens in the processor systems. The same can be achieved by setting the
+    /// `AMETHYST_NUM_IO_THREADS` environment variable.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_io_thread_pool(self, num_threads: usize) -> Result<Self, Error> {
+        let pool = io_thread_pool(num_threads)?;
+        self.world.write_resource::<Loader>().set_pool(pool);
+        Ok(self)
+    }
+
     /// Sets the maximum frames per second of this game.
     ///
     /// # Parameters
@@ -801,6 +837,25 @@ where
         self
     }
 
+    /// Limits the number of fixed updates per frame, by default there is no limit.
+    ///
+    /// When a frame takes longer than `max_steps` fixed updates, the remaining time is dropped
+    /// instead of being caught up on in the next frames.
+    ///
+    /// # Parameters
+    ///
+    /// `max_steps`: The maximum number of fixed updates per frame.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_max_fixed_steps(self, max_steps: u32) -> Self {
+        self.world
+            .write_resource::<Time>()
+            .set_max_fixed_steps(Some(max_steps));
+        self
+    }
+
     /// Tells the resulting application window to ignore close events if ignore is true.
     /// This will make your game window unresponsive to operating system close commands.
     /// Use with caution.
@@ -875,3 +930,15 @@ where
         })
     }
 }
+
+/// Builds the thread pool assets are loaded and decoded on, if it is separate from the main pool.
+fn io_thread_pool(num_threads: usize) -> Result<ArcThreadPool, Error> {
+    let builder = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .thread_name(|index| format!("amethyst-io-{}", index));
+    #[cfg(feature = "profiler")]
+    let builder = builder.start_handler(|_index| {
+        register_thread_with_profiler();
+    });
+    Ok(builder.build().map(Arc::new)?)
+}
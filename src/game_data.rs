@@ -1,10 +1,10 @@
-use std::{marker::PhantomData, path::Path};
+use std::{marker::PhantomData, path::Path, time::Instant};
 
 use crate::{
     core::{
         ecs::prelude::{Dispatcher, DispatcherBuilder, System, World},
         math::RealField,
-        ArcThreadPool, SystemBundle,
+        ArcThreadPool, BundleBuilder, SystemBundle, SystemProfile,
     },
     error::Error,
     renderer::pipe::pass::Pass,
@@ -22,23 +22,67 @@ pub trait DataInit<T> {
 /// field.
 pub struct GameData<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    fixed_dispatcher: Option<Dispatcher<'a, 'b>>,
 }
 
 impl<'a, 'b> GameData<'a, 'b> {
     /// Create new game data
     pub fn new(dispatcher: Dispatcher<'a, 'b>) -> Self {
-        GameData { dispatcher }
+        GameData {
+            dispatcher,
+            fixed_dispatcher: None,
+        }
+    }
+
+    /// Create new game data with a dispatcher for the fixed update stage
+    pub fn with_fixed(
+        dispatcher: Dispatcher<'a, 'b>,
+        fixed_dispatcher: Dispatcher<'a, 'b>,
+    ) -> Self {
+        GameData {
+            dispatcher,
+            fixed_dispatcher: Some(fixed_dispatcher),
+        }
     }
 
     /// Update game data
+    ///
+    /// The time of the dispatch is recorded in the `SystemProfile`, if the resource exists.
     pub fn update(&mut self, world: &World) {
+        let start = Instant::now();
         self.dispatcher.dispatch(&world.res);
+        if let Some(mut profile) = world.res.try_fetch_mut::<SystemProfile>() {
+            profile.record_dispatch(start.elapsed());
+        }
+    }
+
+    /// Run the systems of the fixed update stage
+    ///
+    /// The world is maintained afterwards, so entities and components created or deleted lazily
+    /// by one fixed step are visible in the next one.
+    ///
+    /// `SimpleState`s call this from `fixed_update`. A `State<GameData>` implemented by hand has
+    /// to call it itself, like `update`.
+    pub fn fixed_update(&mut self, world: &mut World) {
+        if let Some(ref mut dispatcher) = self.fixed_dispatcher {
+            dispatcher.dispatch(&world.res);
+            world.maintain();
+        }
     }
 }
 
 /// Builder for default game data
+///
+/// Systems are either added to the variable rate stage, which runs once per frame, or with the
+/// `with_fixed*` methods to the fixed update stage, which runs at the interval set by
+/// `ApplicationBuilder::with_fixed_step_length`, as often as needed to catch up with the frame
+/// time. The number of fixed updates per frame can be limited with
+/// `ApplicationBuilder::with_max_fixed_steps`.
 pub struct GameDataBuilder<'a, 'b, N: RealField = f32> {
     disp_builder: DispatcherBuilder<'a, 'b>,
+    fixed_disp_builder: Option<DispatcherBuilder<'a, 'b>>,
+    profiling: bool,
+    thread_locals: usize,
     _marker: PhantomData<N>,
 }
 
@@ -53,10 +97,24 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     pub fn new() -> Self {
         GameDataBuilder {
             disp_builder: DispatcherBuilder::new(),
+            fixed_disp_builder: None,
+            profiling: false,
+            thread_locals: 0,
             _marker: PhantomData,
         }
     }
 
+    /// Records the run times of all systems added afterwards in the `SystemProfile` resource,
+    /// using their names.
+    ///
+    /// This includes the systems added by bundles. Thread-local systems added with
+    /// `with_thread_local` have no name, so they are recorded as `thread_local_0`,
+    /// `thread_local_1` and so on, in the order they were added.
+    pub fn with_profiling(mut self) -> Self {
+        self.profiling = true;
+        self
+    }
+
     /// Inserts a barrier which assures that all systems added before the
     /// barrier are executed before the ones after this barrier.
     ///
@@ -149,7 +207,7 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     where
         for<'c> S: System<'c> + Send + 'a,
     {
-        self.disp_builder.add(system, name, dependencies);
+        bundle_builder(&mut self.disp_builder, self.profiling).add(system, name, dependencies);
         self
     }
 
@@ -195,7 +253,8 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     where
         for<'c> S: System<'c> + 'b,
     {
-        self.disp_builder.add_thread_local(system);
+        let name = self.next_thread_local_name();
+        bundle_builder(&mut self.disp_builder, self.profiling).add_thread_local(system, &name);
         self
     }
 
@@ -222,10 +281,95 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     where
         B: SystemBundle<'a, 'b>,
     {
-        bundle.build(&mut self.disp_builder)?;
+        bundle.build(&mut bundle_builder(&mut self.disp_builder, self.profiling))?;
+        Ok(self)
+    }
+
+    /// Adds a given system to the fixed update stage.
+    ///
+    /// The systems of the fixed update stage run before `State::update`, any number of times per
+    /// frame, at the interval given by `Time::fixed_time`. Dependencies can only refer to other
+    /// systems of the fixed update stage. The world is maintained after every fixed step.
+    ///
+    /// The stage is run by `GameData::fixed_update`, which `SimpleState`s call every fixed step.
+    /// A `State<GameData>` implemented by hand has to call it from its `fixed_update`, as it
+    /// calls `GameData::update` from `update`, or the fixed systems never run.
+    ///
+    /// See `with` for the parameters and panics.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// GameDataBuilder::<f32>::default()
+    ///     // "physics" runs 60 times per second, regardless of the frame rate
+    ///     .with_fixed(NopSystem, "physics", &[])
+    ///     .with(NopSystem, "animation", &[]);
+    /// ~~~
+    pub fn with_fixed<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        let builder = self
+            .fixed_disp_builder
+            .get_or_insert_with(DispatcherBuilder::new);
+        bundle_builder(builder, self.profiling).add(system, name, dependencies);
+        self
+    }
+
+    /// Inserts a barrier into the fixed update stage.
+    ///
+    /// See `with_barrier`.
+    pub fn with_fixed_barrier(mut self) -> Self {
+        self.fixed_disp_builder
+            .get_or_insert_with(DispatcherBuilder::new)
+            .add_barrier();
+        self
+    }
+
+    /// Add a given thread-local system to the fixed update stage.
+    ///
+    /// See `with_thread_local`.
+    pub fn with_fixed_thread_local<S>(mut self, system: S) -> Self
+    where
+        for<'c> S: System<'c> + 'b,
+    {
+        let name = self.next_thread_local_name();
+        let builder = self
+            .fixed_disp_builder
+            .get_or_insert_with(DispatcherBuilder::new);
+        bundle_builder(builder, self.profiling).add_thread_local(system, &name);
+        self
+    }
+
+    /// Add a given ECS bundle to the fixed update stage.
+    ///
+    /// See `with_bundle`.
+    pub fn with_fixed_bundle<B>(mut self, bundle: B) -> Result<Self, Error>
+    where
+        B: SystemBundle<'a, 'b>,
+    {
+        let builder = self
+            .fixed_disp_builder
+            .get_or_insert_with(DispatcherBuilder::new);
+        bundle.build(&mut bundle_builder(builder, self.profiling))?;
         Ok(self)
     }
 
+    fn next_thread_local_name(&mut self) -> String {
+        let name = format!("thread_local_{}", self.thread_locals);
+        self.thread_locals += 1;
+        name
+    }
+
     /// Create a basic renderer with a single given `Pass`, and optional support for the `DrawUi` pass.
     ///
     /// Will set the clear color to black.
@@ -265,17 +409,39 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     }
 }
 
+fn bundle_builder<'r, 'a, 'b>(
+    dispatcher: &'r mut DispatcherBuilder<'a, 'b>,
+    profiling: bool,
+) -> BundleBuilder<'r, 'a, 'b> {
+    if profiling {
+        BundleBuilder::profiled(dispatcher)
+    } else {
+        BundleBuilder::new(dispatcher)
+    }
+}
+
 impl<'a, 'b> DataInit<GameData<'a, 'b>> for GameDataBuilder<'a, 'b> {
     fn build(self, world: &mut World) -> GameData<'a, 'b> {
         #[cfg(not(no_threading))]
         let pool = world.read_resource::<ArcThreadPool>().clone();
 
         #[cfg(not(no_threading))]
-        let mut dispatcher = self.disp_builder.with_pool(pool).build();
+        let mut dispatcher = self.disp_builder.with_pool(pool.clone()).build();
         #[cfg(no_threading)]
         let mut dispatcher = self.disp_builder.build();
         dispatcher.setup(&mut world.res);
-        GameData::new(dispatcher)
+
+        match self.fixed_disp_builder {
+            Some(fixed_disp_builder) => {
+                #[cfg(not(no_threading))]
+                let mut fixed_dispatcher = fixed_disp_builder.with_pool(pool).build();
+                #[cfg(no_threading)]
+                let mut fixed_dispatcher = fixed_disp_builder.build();
+                fixed_dispatcher.setup(&mut world.res);
+                GameData::with_fixed(dispatcher, fixed_dispatcher)
+            }
+            None => GameData::new(dispatcher),
+        }
     }
 }
 
@@ -72,7 +72,13 @@ pub use amethyst_input as input;
 pub use amethyst_locale as locale;
 #[cfg(feature = "network")]
 pub use amethyst_network as network;
+#[cfg(feature = "physics")]
+pub use amethyst_physics as physics;
 pub use amethyst_renderer as renderer;
+#[cfg(feature = "scripting")]
+pub use amethyst_scripting as scripting;
+#[cfg(feature = "timeline")]
+pub use amethyst_timeline as timeline;
 pub use amethyst_ui as ui;
 pub use amethyst_utils as utils;
 pub use winit;
@@ -88,8 +94,8 @@ pub use self::{
     game_data::{DataInit, GameData, GameDataBuilder},
     logger::{start_logger, LevelFilter as LogLevelFilter, Logger, LoggerConfig, StdoutLog},
     state::{
-        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, StateMachine, Trans,
-        TransEvent,
+        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateChangeEvent, StateData,
+        StateMachine, Trans, TransEvent, TransPayload,
     },
     state_event::{StateEvent, StateEventReader},
 };
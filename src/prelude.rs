@@ -8,7 +8,8 @@ pub use crate::{
     ecs::prelude::{Builder, World},
     game_data::{DataInit, GameData, GameDataBuilder},
     state::{
-        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, Trans, TransEvent,
+        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateChangeEvent, StateData,
+        Trans, TransEvent, TransPayload,
     },
     state_event::StateEvent,
 };
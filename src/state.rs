@@ -4,9 +4,12 @@ use amethyst_input::is_close_requested;
 
 use derivative::Derivative;
 
-use crate::{ecs::prelude::World, GameData, StateEvent};
+use crate::{ecs::prelude::World, shrev::EventChannel, GameData, StateEvent};
 
-use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::{
+    any::Any,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
 
 /// Error type for errors occurring in StateMachine
 #[derive(Debug)]
@@ -58,6 +61,51 @@ pub enum Trans<T, E> {
     Switch(Box<dyn State<T, E>>),
     /// Stop and remove all states and shut down the engine.
     Quit,
+    /// Like `Pop`, passing the payload to `on_resume_with` of the next state on the stack.
+    PopWith(TransPayload),
+    /// Like `Push`, passing the payload to `on_start_with` of the new state.
+    PushWith(Box<dyn State<T, E>>, TransPayload),
+    /// Like `Switch`, passing the payload to `on_start_with` of the new state.
+    SwitchWith(Box<dyn State<T, E>>, TransPayload),
+}
+
+/// Data passed along with a `Trans`, e.g. the level a loading state should load or the choice
+/// made in a menu which was pushed on top of the game.
+///
+/// The receiving state gets the payload back with `Box::downcast`:
+///
+/// ```rust, ignore
+/// fn on_resume_with(&mut self, data: StateData<'_, GameData<'_, '_>>, payload: TransPayload) {
+///     if let Ok(choice) = payload.downcast::<MenuChoice>() {
+///         self.apply(*choice);
+///     }
+/// }
+/// ```
+pub type TransPayload = Box<dyn Any + Send + Sync>;
+
+/// Event written to `EventChannel<StateChangeEvent>` whenever the `StateMachine` changes its
+/// stack, so systems can react to states starting and stopping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateChangeEvent {
+    /// The state machine started with its initial state.
+    Started,
+    /// A state was pushed, pausing the previous one.
+    Pushed {
+        /// The number of states on the stack after the push.
+        depth: usize,
+    },
+    /// The active state was popped, resuming the next one on the stack, if any.
+    Popped {
+        /// The number of states on the stack after the pop.
+        depth: usize,
+    },
+    /// The active state was replaced.
+    Switched {
+        /// The number of states on the stack.
+        depth: usize,
+    },
+    /// All states were stopped.
+    Stopped,
 }
 
 /// Event queue to trigger state `Trans` from other places than a `State`'s methods.
@@ -81,6 +129,12 @@ pub trait State<T, E: Send + Sync + 'static> {
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, T>) {}
 
+    /// Executed instead of `on_start` when the game state begins through `Trans::PushWith` or
+    /// `Trans::SwitchWith`. Calls `on_start` by default, ignoring the payload.
+    fn on_start_with(&mut self, data: StateData<'_, T>, _payload: TransPayload) {
+        self.on_start(data)
+    }
+
     /// Executed when the game state exits.
     fn on_stop(&mut self, _data: StateData<'_, T>) {}
 
@@ -90,6 +144,12 @@ pub trait State<T, E: Send + Sync + 'static> {
     /// Executed when the application returns to this game state once again.
     fn on_resume(&mut self, _data: StateData<'_, T>) {}
 
+    /// Executed instead of `on_resume` when the state above this one returned `Trans::PopWith`.
+    /// Calls `on_resume` by default, ignoring the payload.
+    fn on_resume_with(&mut self, data: StateData<'_, T>, _payload: TransPayload) {
+        self.on_resume(data)
+    }
+
     /// Executed on every frame before updating, for use in reacting to events.
     fn handle_event(&mut self, _data: StateData<'_, T>, _event: E) -> Trans<T, E> {
         Trans::None
@@ -125,6 +185,12 @@ pub trait EmptyState {
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, ()>) {}
 
+    /// Executed instead of `on_start` when the game state begins through `Trans::PushWith` or
+    /// `Trans::SwitchWith`. Calls `on_start` by default, ignoring the payload.
+    fn on_start_with(&mut self, data: StateData<'_, ()>, _payload: TransPayload) {
+        self.on_start(data)
+    }
+
     /// Executed when the game state exits.
     fn on_stop(&mut self, _data: StateData<'_, ()>) {}
 
@@ -134,6 +200,12 @@ pub trait EmptyState {
     /// Executed when the application returns to this game state once again.
     fn on_resume(&mut self, _data: StateData<'_, ()>) {}
 
+    /// Executed instead of `on_resume` when the state above this one returned `Trans::PopWith`.
+    /// Calls `on_resume` by default, ignoring the payload.
+    fn on_resume_with(&mut self, data: StateData<'_, ()>, _payload: TransPayload) {
+        self.on_resume(data)
+    }
+
     /// Executed on every frame before updating, for use in reacting to events.
     fn handle_event(&mut self, _data: StateData<'_, ()>, event: StateEvent) -> EmptyTrans {
         if let StateEvent::Window(event) = &event {
@@ -176,6 +248,11 @@ impl<T: EmptyState> State<(), StateEvent> for T {
         self.on_start(data)
     }
 
+    /// Executed instead of `on_start` when the game state begins with a payload.
+    fn on_start_with(&mut self, data: StateData<'_, ()>, payload: TransPayload) {
+        self.on_start_with(data, payload)
+    }
+
     /// Executed when the game state exits.
     fn on_stop(&mut self, data: StateData<'_, ()>) {
         self.on_stop(data)
@@ -191,6 +268,11 @@ impl<T: EmptyState> State<(), StateEvent> for T {
         self.on_resume(data)
     }
 
+    /// Executed instead of `on_resume` when the application returns to this state with a payload.
+    fn on_resume_with(&mut self, data: StateData<'_, ()>, payload: TransPayload) {
+        self.on_resume_with(data, payload)
+    }
+
     /// Executed on every frame before updating, for use in reacting to events.
     fn handle_event(&mut self, data: StateData<'_, ()>, event: StateEvent) -> EmptyTrans {
         self.handle_event(data, event)
@@ -228,6 +310,12 @@ pub trait SimpleState {
     /// Executed when the game state begins.
     fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
 
+    /// Executed instead of `on_start` when the game state begins through `Trans::PushWith` or
+    /// `Trans::SwitchWith`. Calls `on_start` by default, ignoring the payload.
+    fn on_start_with(&mut self, data: StateData<'_, GameData<'_, '_>>, _payload: TransPayload) {
+        self.on_start(data)
+    }
+
     /// Executed when the game state exits.
     fn on_stop(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
 
@@ -237,6 +325,12 @@ pub trait SimpleState {
     /// Executed when the application returns to this game state once again.
     fn on_resume(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
 
+    /// Executed instead of `on_resume` when the state above this one returned `Trans::PopWith`.
+    /// Calls `on_resume` by default, ignoring the payload.
+    fn on_resume_with(&mut self, data: StateData<'_, GameData<'_, '_>>, _payload: TransPayload) {
+        self.on_resume(data)
+    }
+
     /// Executed on every frame before updating, for use in reacting to events.
     fn handle_event(
         &mut self,
@@ -285,6 +379,11 @@ impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
         self.on_start(data)
     }
 
+    /// Executed instead of `on_start` when the game state begins with a payload.
+    fn on_start_with(&mut self, data: StateData<'_, GameData<'_, '_>>, payload: TransPayload) {
+        self.on_start_with(data, payload)
+    }
+
     /// Executed when the game state exits.
     fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         self.on_stop(data)
@@ -300,6 +399,11 @@ impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
         self.on_resume(data)
     }
 
+    /// Executed instead of `on_resume` when the application returns to this state with a payload.
+    fn on_resume_with(&mut self, data: StateData<'_, GameData<'_, '_>>, payload: TransPayload) {
+        self.on_resume_with(data, payload)
+    }
+
     /// Executed on every frame before updating, for use in reacting to events.
     fn handle_event(
         &mut self,
@@ -312,7 +416,10 @@ impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
     /// Executed repeatedly at stable, predictable intervals (1/60th of a second
     /// by default).
     fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
-        self.fixed_update(data)
+        let StateData { world, data } = data;
+        let r = self.fixed_update(StateData::new(&mut *world, &mut *data));
+        data.fixed_update(world);
+        r
     }
 
     /// Executed on every frame immediately, as fast as the engine will allow (taking into account the frame rate limit).
@@ -364,12 +471,14 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
     /// Initializes the state machine.
     pub fn start(&mut self, data: StateData<'_, T>) -> Result<(), StateError> {
         if !self.running {
+            let StateData { world, data } = data;
             let state = self
                 .state_stack
                 .last_mut()
                 .ok_or(StateError::NoStatesPresent)?;
-            state.on_start(data);
+            state.on_start(StateData { world, data });
             self.running = true;
+            notify(world, StateChangeEvent::Started);
         }
         Ok(())
     }
@@ -428,16 +537,24 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         if self.running {
             match request {
                 Trans::None => (),
-                Trans::Pop => self.pop(data),
-                Trans::Push(state) => self.push(state, data),
-                Trans::Switch(state) => self.switch(state, data),
+                Trans::Pop => self.pop(data, None),
+                Trans::Push(state) => self.push(state, data, None),
+                Trans::Switch(state) => self.switch(state, data, None),
                 Trans::Quit => self.stop(data),
+                Trans::PopWith(payload) => self.pop(data, Some(payload)),
+                Trans::PushWith(state, payload) => self.push(state, data, Some(payload)),
+                Trans::SwitchWith(state, payload) => self.switch(state, data, Some(payload)),
             }
         }
     }
 
     /// Removes the current state on the stack and inserts a different one.
-    fn switch(&mut self, state: Box<dyn State<T, E>>, data: StateData<'_, T>) {
+    fn switch(
+        &mut self,
+        state: Box<dyn State<T, E>>,
+        data: StateData<'_, T>,
+        payload: Option<TransPayload>,
+    ) {
         if self.running {
             let StateData { world, data } = data;
             if let Some(mut state) = self.state_stack.pop() {
@@ -448,12 +565,22 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
 
             //State was just pushed, thus pop will always succeed
             let state = self.state_stack.last_mut().unwrap();
-            state.on_start(StateData { world, data });
+            match payload {
+                Some(payload) => state.on_start_with(StateData { world, data }, payload),
+                None => state.on_start(StateData { world, data }),
+            }
+            let depth = self.state_stack.len();
+            notify(world, StateChangeEvent::Switched { depth });
         }
     }
 
     /// Pauses the active state and pushes a new state onto the state stack.
-    fn push(&mut self, state: Box<dyn State<T, E>>, data: StateData<'_, T>) {
+    fn push(
+        &mut self,
+        state: Box<dyn State<T, E>>,
+        data: StateData<'_, T>,
+        payload: Option<TransPayload>,
+    ) {
         if self.running {
             let StateData { world, data } = data;
             if let Some(state) = self.state_stack.last_mut() {
@@ -464,13 +591,18 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
 
             //State was just pushed, thus pop will always succeed
             let state = self.state_stack.last_mut().unwrap();
-            state.on_start(StateData { world, data });
+            match payload {
+                Some(payload) => state.on_start_with(StateData { world, data }, payload),
+                None => state.on_start(StateData { world, data }),
+            }
+            let depth = self.state_stack.len();
+            notify(world, StateChangeEvent::Pushed { depth });
         }
     }
 
     /// Stops and removes the active state and un-pauses the next state on the
     /// stack (if any).
-    fn pop(&mut self, data: StateData<'_, T>) {
+    fn pop(&mut self, data: StateData<'_, T>, payload: Option<TransPayload>) {
         if self.running {
             let StateData { world, data } = data;
             if let Some(mut state) = self.state_stack.pop() {
@@ -478,10 +610,18 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
             }
 
             if let Some(state) = self.state_stack.last_mut() {
-                state.on_resume(StateData { world, data });
+                match payload {
+                    Some(payload) => state.on_resume_with(StateData { world, data }, payload),
+                    None => state.on_resume(StateData { world, data }),
+                }
             } else {
                 self.running = false;
             }
+            let depth = self.state_stack.len();
+            notify(world, StateChangeEvent::Popped { depth });
+            if !self.running {
+                notify(world, StateChangeEvent::Stopped);
+            }
         }
     }
 
@@ -494,10 +634,18 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
             }
 
             self.running = false;
+            notify(world, StateChangeEvent::Stopped);
         }
     }
 }
 
+/// Writes the event if the world has an `EventChannel<StateChangeEvent>`.
+fn notify(world: &World, event: StateChangeEvent) {
+    if let Some(mut channel) = world.res.try_fetch_mut::<EventChannel<StateChangeEvent>>() {
+        channel.single_write(event);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -540,4 +688,66 @@ mod tests {
         sm.update(StateData::new(&mut world, &mut ()));
         assert!(!sm.is_running());
     }
+
+    struct Menu;
+    struct Game;
+
+    impl State<(), ()> for Game {
+        fn update(&mut self, data: StateData<'_, ()>) -> Trans<(), ()> {
+            if data.world.res.has_value::<u32>() {
+                Trans::Quit
+            } else {
+                Trans::PushWith(Box::new(Menu), Box::new(1u32))
+            }
+        }
+
+        fn on_resume_with(&mut self, data: StateData<'_, ()>, payload: TransPayload) {
+            let choice = payload.downcast::<u32>().expect("Unexpected payload");
+            data.world.add_resource(*choice);
+        }
+    }
+
+    impl State<(), ()> for Menu {
+        fn on_start_with(&mut self, _: StateData<'_, ()>, payload: TransPayload) {
+            assert_eq!(Some(&1), payload.downcast_ref::<u32>());
+        }
+
+        fn update(&mut self, _: StateData<'_, ()>) -> Trans<(), ()> {
+            Trans::PopWith(Box::new(2u32))
+        }
+    }
+
+    #[test]
+    fn payloads_and_events() {
+        use crate::ecs::prelude::World;
+
+        let mut world = World::new();
+        world.add_resource(EventChannel::<StateChangeEvent>::new());
+        let mut reader = world
+            .write_resource::<EventChannel<StateChangeEvent>>()
+            .register_reader();
+
+        let mut sm = StateMachine::new(Game);
+        sm.start(StateData::new(&mut world, &mut ())).unwrap();
+        for _ in 0..3 {
+            sm.update(StateData::new(&mut world, &mut ()));
+        }
+
+        assert_eq!(2, *world.read_resource::<u32>());
+        assert!(!sm.is_running());
+        let events = world
+            .read_resource::<EventChannel<StateChangeEvent>>()
+            .read(&mut reader)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                StateChangeEvent::Started,
+                StateChangeEvent::Pushed { depth: 2 },
+                StateChangeEvent::Popped { depth: 1 },
+                StateChangeEvent::Stopped,
+            ],
+            events
+        );
+    }
 }